@@ -0,0 +1,45 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::collector::FacetCollector;
+use tantivy::query::AllQuery;
+use tantivy::schema::{Facet, FacetOptions, Schema};
+use tantivy::{doc, Index};
+
+const NUM_SEGMENTS: u64 = 4;
+const NUM_DOCS_PER_SEGMENT: u64 = 50_000;
+const NUM_FACETS: u64 = 50_000;
+
+pub fn facet_collector_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+    let schema = schema_builder.build();
+    let index = Index::create_in_ram(schema);
+    {
+        let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+        // The segments share most of their facets, so that the merge of their counts
+        // dominates.
+        for segment_ord in 0..NUM_SEGMENTS {
+            for doc_id in 0..NUM_DOCS_PER_SEGMENT {
+                let facet_id = (doc_id + segment_ord * 1_000) % NUM_FACETS;
+                let facet = Facet::from(&format!("/category/{}", facet_id));
+                index_writer
+                    .add_document(doc!(facet_field => facet))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+    }
+    let searcher = index.reader().unwrap().searcher();
+
+    let mut group = c.benchmark_group("facet-collector");
+    group.bench_function("high-cardinality-facet", |b| {
+        b.iter(|| {
+            let mut facet_collector = FacetCollector::for_field(facet_field);
+            facet_collector.add_facet("/category");
+            let facet_counts = searcher.search(&AllQuery, &facet_collector).unwrap();
+            assert_eq!(facet_counts.get("/category").count(), NUM_FACETS as usize);
+        })
+    });
+}
+
+criterion_group!(benches, facet_collector_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::collector::Count;
+use tantivy::query::{BooleanQuery, FastFieldSetQuery, Occur, Query, TermQuery};
+use tantivy::schema::{IndexRecordOption, Schema, FAST, INDEXED};
+use tantivy::{doc, Index, Term};
+
+const NUM_DOCS: u64 = 100_000;
+const NUM_VALUES: u64 = 5_000;
+
+pub fn fast_field_set_query_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+    let schema = schema_builder.build();
+    let index = Index::create_in_ram(schema);
+    {
+        let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+        for id in 0..NUM_DOCS {
+            index_writer.add_document(doc!(id_field => id)).unwrap();
+        }
+        index_writer.commit().unwrap();
+    }
+    let searcher = index.reader().unwrap().searcher();
+    // One value out of 20 belongs to the set.
+    let values: Vec<u64> = (0..NUM_VALUES)
+        .map(|i| i * (NUM_DOCS / NUM_VALUES))
+        .collect();
+
+    let fast_field_set_query = FastFieldSetQuery::new(id_field, values.iter().copied());
+    let boolean_query = BooleanQuery::new(
+        values
+            .iter()
+            .map(|&val| {
+                let term_query: Box<dyn Query> = Box::new(TermQuery::new(
+                    Term::from_field_u64(id_field, val),
+                    IndexRecordOption::Basic,
+                ));
+                (Occur::Should, term_query)
+            })
+            .collect(),
+    );
+
+    let mut group = c.benchmark_group("fast-field-set-query");
+    group.bench_function("fast-field-set-query", |b| {
+        b.iter(|| {
+            let count = searcher.search(&fast_field_set_query, &Count).unwrap();
+            assert_eq!(count, NUM_VALUES as usize);
+        })
+    });
+    group.bench_function("boolean-query-of-terms", |b| {
+        b.iter(|| {
+            let count = searcher.search(&boolean_query, &Count).unwrap();
+            assert_eq!(count, NUM_VALUES as usize);
+        })
+    });
+}
+
+criterion_group!(benches, fast_field_set_query_benchmark);
+criterion_main!(benches);
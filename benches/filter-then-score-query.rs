@@ -0,0 +1,82 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::collector::TopDocs;
+use tantivy::query::{
+    BooleanQuery, BoostQuery, FilterThenScoreQuery, Occur, Query, QueryParser, TermQuery,
+};
+use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+use tantivy::{doc, Index, Term};
+
+const NUM_DOCS: u64 = 100_000;
+const NUM_TAGS: u64 = 2_000;
+// The filter matches the documents having one of 20 tags, that is one document out of 100.
+const NUM_FILTER_TAGS: u64 = 20;
+
+pub fn filter_then_score_query_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let body_field = schema_builder.add_text_field("body", TEXT);
+    let tag_field = schema_builder.add_text_field("tag", TEXT);
+    let schema = schema_builder.build();
+    let index = Index::create_in_ram(schema);
+    {
+        let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+        for id in 0..NUM_DOCS {
+            let body = match id % 4 {
+                0 => "the quick brown fox",
+                1 => "the lazy dog jumps over the fox",
+                2 => "a brown dog and a quick cat",
+                _ => "the cat sleeps",
+            };
+            let tag = format!("tag{}", id % NUM_TAGS);
+            index_writer
+                .add_document(doc!(body_field => body, tag_field => tag))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+    }
+    let searcher = index.reader().unwrap().searcher();
+    let query_parser = QueryParser::for_index(&index, vec![body_field]);
+    let scoring_query = query_parser.parse_query("the quick brown fox dog").unwrap();
+    let filter = BooleanQuery::new(
+        (0..NUM_FILTER_TAGS)
+            .map(|tag| {
+                let term_query: Box<dyn Query> = Box::new(TermQuery::new(
+                    Term::from_field_text(tag_field, &format!("tag{}", tag)),
+                    IndexRecordOption::WithFreqs,
+                ));
+                (Occur::Should, term_query)
+            })
+            .collect(),
+    );
+
+    let filter_then_score_query =
+        FilterThenScoreQuery::new(Box::new(filter.clone()), scoring_query.box_clone());
+    let boolean_query = BooleanQuery::new(vec![
+        (Occur::Must, scoring_query),
+        (
+            Occur::Must,
+            Box::new(BoostQuery::new(Box::new(filter), 0.0)),
+        ),
+    ]);
+    // The filter does not contribute to the score of the documents, but the boolean query
+    // still computes the score of each of its terms.
+    let top_docs = TopDocs::with_limit(10);
+
+    let mut group = c.benchmark_group("filter-then-score-query");
+    group.bench_function("filter-then-score-query", |b| {
+        b.iter(|| {
+            let docs = searcher
+                .search(&filter_then_score_query, &top_docs)
+                .unwrap();
+            assert_eq!(docs.len(), 10);
+        })
+    });
+    group.bench_function("boolean-query-with-filter", |b| {
+        b.iter(|| {
+            let docs = searcher.search(&boolean_query, &top_docs).unwrap();
+            assert_eq!(docs.len(), 10);
+        })
+    });
+}
+
+criterion_group!(benches, filter_then_score_query_benchmark);
+criterion_main!(benches);
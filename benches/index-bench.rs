@@ -1,7 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
-use tantivy::schema::{INDEXED, STORED, STRING, TEXT};
-use tantivy::Index;
+use tantivy::schema::{Cardinality, NumericOptions, FAST, INDEXED, STORED, STRING, TEXT};
+use tantivy::{doc, Index};
 
 const HDFS_LOGS: &str = include_str!("hdfs.json");
 const NUM_REPEATS: usize = 2;
@@ -113,9 +113,45 @@ pub fn hdfs_index_benchmark(c: &mut Criterion) {
     });
 }
 
+pub fn numeric_index_benchmark(c: &mut Criterion) {
+    const NUM_DOCS: u64 = 100_000;
+    let mut schema_builder = tantivy::schema::SchemaBuilder::new();
+    let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+    let delta_field = schema_builder.add_i64_field("delta", INDEXED | FAST);
+    let price_field = schema_builder.add_f64_field("price", INDEXED | FAST);
+    let tags_field = schema_builder.add_u64_field(
+        "tags",
+        NumericOptions::default()
+            .set_indexed()
+            .set_fast(Cardinality::MultiValues),
+    );
+    let schema = schema_builder.build();
+
+    let mut group = c.benchmark_group("index-numeric");
+    group.sample_size(20);
+    group.bench_function("index-indexed-and-fast-numeric-fields", |b| {
+        b.iter(|| {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+            for id in 0..NUM_DOCS {
+                index_writer
+                    .add_document(doc!(
+                        id_field => id,
+                        delta_field => id as i64 - 50_000,
+                        price_field => id as f64 / 100.0,
+                        tags_field => id % 100,
+                        tags_field => id % 7,
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        })
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
-    targets = hdfs_index_benchmark
+    targets = hdfs_index_benchmark, numeric_index_benchmark
 }
 criterion_main!(benches);
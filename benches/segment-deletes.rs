@@ -0,0 +1,67 @@
+use common::{BitSet, ReadOnlyBitSet};
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::collector::{Collector, Count, TopDocs};
+use tantivy::fastfield::AliveBitSet;
+use tantivy::query::{Query, TermQuery};
+use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+use tantivy::{doc, Index, SegmentReader, Term};
+
+const NUM_DOCS: u32 = 500_000;
+
+fn alive_bitset(deleted_docs: &[u32]) -> AliveBitSet {
+    let mut bitset = BitSet::with_max_value_and_full(NUM_DOCS);
+    for &doc in deleted_docs {
+        bitset.remove(doc);
+    }
+    AliveBitSet::from(ReadOnlyBitSet::from(&bitset))
+}
+
+pub fn segment_deletes_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    {
+        let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+        for doc_id in 0..NUM_DOCS {
+            let text = if doc_id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(text_field => text)).unwrap();
+        }
+        index_writer.commit().unwrap();
+    }
+    let searcher = index.reader().unwrap().searcher();
+    let segment = index.segment(index.searchable_segment_metas().unwrap()[0].clone());
+    let readers = [
+        ("delete-free", SegmentReader::open(&segment).unwrap()),
+        (
+            "empty-delete-bitset",
+            SegmentReader::open_with_custom_alive_set(&segment, Some(alive_bitset(&[]))).unwrap(),
+        ),
+        (
+            "one-delete",
+            SegmentReader::open_with_custom_alive_set(&segment, Some(alive_bitset(&[1]))).unwrap(),
+        ),
+    ];
+    let query = TermQuery::new(
+        Term::from_field_text(text_field, "even"),
+        IndexRecordOption::WithFreqs,
+    );
+    let weight = query.weight(&searcher, true).unwrap();
+    let collector = TopDocs::with_limit(10);
+
+    let mut group = c.benchmark_group("segment-deletes");
+    for (name, reader) in &readers {
+        group.bench_function(format!("top-docs-{}", name), |b| {
+            b.iter(|| {
+                collector
+                    .collect_segment(weight.as_ref(), 0, reader)
+                    .unwrap()
+            })
+        });
+        group.bench_function(format!("count-{}", name), |b| {
+            b.iter(|| Count.collect_segment(weight.as_ref(), 0, reader).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, segment_deletes_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::collector::TopDocs;
+use tantivy::merge_policy::NoMergePolicy;
+use tantivy::query::AllQuery;
+use tantivy::schema::{Schema, FAST};
+use tantivy::{doc, Index, IndexSettings, IndexSortByField, Order};
+
+const NUM_SEGMENTS: u64 = 10;
+const NUM_DOCS_PER_SEGMENT: u64 = 100_000;
+
+fn create_index(sort_by_field: Option<IndexSortByField>) -> Index {
+    let mut schema_builder = Schema::builder();
+    let rating = schema_builder.add_u64_field("rating", FAST);
+    let settings = IndexSettings {
+        sort_by_field,
+        ..Default::default()
+    };
+    let index = Index::builder()
+        .schema(schema_builder.build())
+        .settings(settings)
+        .create_in_ram()
+        .unwrap();
+    let mut index_writer = index.writer_with_num_threads(1, 100_000_000).unwrap();
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for segment in 0..NUM_SEGMENTS {
+        for i in 0..NUM_DOCS_PER_SEGMENT {
+            let rating_val = (i * 7_919 + segment) % NUM_DOCS_PER_SEGMENT;
+            index_writer
+                .add_document(doc!(rating => rating_val))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+    }
+    index
+}
+
+pub fn top_docs_by_field_benchmark(c: &mut Criterion) {
+    let indexes = [
+        ("unsorted-index", create_index(None)),
+        (
+            "index-sorted-by-field",
+            create_index(Some(IndexSortByField {
+                field: "rating".to_string(),
+                order: Order::Desc,
+            })),
+        ),
+    ];
+    let mut group = c.benchmark_group("top-docs-by-field");
+    for (name, index) in &indexes {
+        let rating = index.schema().get_field("rating").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), NUM_SEGMENTS as usize);
+        let collector = TopDocs::with_limit(1_000).order_by_u64_field(rating);
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let top_docs = searcher.search(&AllQuery, &collector).unwrap();
+                assert_eq!(top_docs.len(), 1_000);
+            })
+        });
+    }
+}
+
+criterion_group!(benches, top_docs_by_field_benchmark);
+criterion_main!(benches);
@@ -19,6 +19,11 @@
 // While this is counterintuitive, adding and deleting documents do not require mutability
 // over the `IndexWriter`, so several threads will be able to do this operation concurrently.
 //
+// Producers cannot overwhelm the `IndexWriter`: its memory usage is bounded by the memory budget
+// of its indexing threads, and by the size of its indexing pipeline. When the pipeline is full,
+// `add_document` blocks until an indexing thread catches up. A producer that prefers not to block
+// can call `try_add_document`, which hands the document back when the pipeline is full.
+//
 // The example below does not represent an actual real-life use case (who would spawn thread to
 // index a single document?), but aims at demonstrating the mechanism that makes indexing
 // from several threads possible.
@@ -88,6 +93,31 @@ fn main() -> tantivy::Result<()> {
         Result::<(), TantivyError>::Ok(())
     });
 
+    // # Third indexing thread, which never blocks.
+    let index_writer_clone_3 = index_writer.clone();
+    thread::spawn(move || {
+        for i in 0..100 {
+            let mut doc = doc!(
+                title => "The Old Man and the Sea",
+                body => "He was an old man who fished alone in a skiff in the Gulf Stream..."
+            );
+            // If the indexing pipeline is full, the document is handed back to us,
+            // and we can do something else before retrying.
+            let opstamp = loop {
+                let index_writer_rlock = index_writer_clone_3.read().unwrap();
+                match index_writer_rlock.try_add_document(doc)? {
+                    Ok(opstamp) => break opstamp,
+                    Err(rejected_doc) => doc = rejected_doc,
+                }
+                drop(index_writer_rlock);
+                thread::sleep(Duration::from_millis(1));
+            };
+            println!("add doc {} from thread 3 - opstamp {}", i, opstamp);
+            thread::sleep(Duration::from_millis(10));
+        }
+        Result::<(), TantivyError>::Ok(())
+    });
+
     // # In the main thread, we commit 10 times, once every 500ms.
     for _ in 0..10 {
         let opstamp: Opstamp = {
@@ -64,6 +64,41 @@ mod tests {
         let col = Arc::new(get_reader_for_bench::<Codec>(data));
         bench_get_dynamic_helper(b, col);
     }
+    /// Runs of 64 consecutive indexes, every 512 indexes.
+    fn clustered_indexes(num_vals: u32) -> Vec<u32> {
+        (0..num_vals).filter(|idx| idx % 512 < 64).collect()
+    }
+
+    /// As many indexes as `clustered_indexes`, spread randomly over the column.
+    fn scattered_indexes(num_vals: u32) -> Vec<u32> {
+        let mut rng = StdRng::seed_from_u64(3u64);
+        let mut indexes: Vec<u32> = (0..clustered_indexes(num_vals).len())
+            .map(|_| rng.gen_range(0..num_vals))
+            .collect();
+        indexes.sort_unstable();
+        indexes
+    }
+
+    fn bench_get_val_indexes(b: &mut Bencher, indexes: &[u32]) {
+        let col: Arc<dyn Column> = serialize_and_load(&get_data());
+        b.iter(|| {
+            let mut sum = 0u64;
+            for &idx in indexes {
+                sum = sum.wrapping_add(col.get_val(idx as u64));
+            }
+            sum
+        });
+    }
+
+    fn bench_get_vals_indexes(b: &mut Bencher, indexes: &[u32]) {
+        let col: Arc<dyn Column> = serialize_and_load(&get_data());
+        let mut vals = vec![0u64; indexes.len()];
+        b.iter(|| {
+            col.get_vals(indexes, &mut vals);
+            vals.iter().fold(0u64, |sum, &val| sum.wrapping_add(val))
+        });
+    }
+
     fn bench_create<Codec: FastFieldCodec>(b: &mut Bencher, data: &[u64]) {
         let mut bytes = Vec::new();
         b.iter(|| {
@@ -121,4 +156,24 @@ mod tests {
         let data: Vec<_> = get_data();
         bench_get_dynamic::<BlockwiseLinearCodec>(b, &data);
     }
+    #[bench]
+    fn bench_fastfield_get_val_clustered(b: &mut Bencher) {
+        let num_vals = get_data().len() as u32;
+        bench_get_val_indexes(b, &clustered_indexes(num_vals));
+    }
+    #[bench]
+    fn bench_fastfield_get_vals_clustered(b: &mut Bencher) {
+        let num_vals = get_data().len() as u32;
+        bench_get_vals_indexes(b, &clustered_indexes(num_vals));
+    }
+    #[bench]
+    fn bench_fastfield_get_val_scattered(b: &mut Bencher) {
+        let num_vals = get_data().len() as u32;
+        bench_get_val_indexes(b, &scattered_indexes(num_vals));
+    }
+    #[bench]
+    fn bench_fastfield_get_vals_scattered(b: &mut Bencher) {
+        let num_vals = get_data().len() as u32;
+        bench_get_vals_indexes(b, &scattered_indexes(num_vals));
+    }
 }
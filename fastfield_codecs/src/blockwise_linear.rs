@@ -171,6 +171,29 @@ impl Column for BlockwiseLinearReader {
         interpoled_val.wrapping_add(bitpacked_diff)
     }
 
+    fn get_vals(&self, indexes: &[u32], output: &mut [u64]) {
+        assert_eq!(indexes.len(), output.len());
+        let mut current_block: Option<(usize, &Block, &[u8])> = None;
+        for (out, &idx) in output.iter_mut().zip(indexes) {
+            let block_id = idx as usize / CHUNK_SIZE;
+            let idx_within_block = (idx as usize % CHUNK_SIZE) as u64;
+            let (block, block_bytes) = match current_block {
+                Some((current_block_id, block, block_bytes)) if current_block_id == block_id => {
+                    (block, block_bytes)
+                }
+                _ => {
+                    let block = &self.blocks[block_id];
+                    let block_bytes = &self.data[block.data_start_offset..];
+                    current_block = Some((block_id, block, block_bytes));
+                    (block, block_bytes)
+                }
+            };
+            let interpoled_val: u64 = block.line.eval(idx_within_block);
+            let bitpacked_diff = block.bit_unpacker.get(idx_within_block, block_bytes);
+            *out = interpoled_val.wrapping_add(bitpacked_diff);
+        }
+    }
+
     fn min_value(&self) -> u64 {
         // The BlockwiseLinearReader assumes a normalized vector.
         0u64
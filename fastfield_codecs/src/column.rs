@@ -28,6 +28,26 @@ pub trait Column<T = u64>: Send + Sync {
         }
     }
 
+    /// Fills an output buffer with the fast field values associated with the given
+    /// `indexes`: `output[i]` receives the value at `indexes[i]`.
+    ///
+    /// Values are decoded in the order of `indexes`. Passing sorted indexes, for instance
+    /// the `DocId`s matching a filter, yields a sequential access to the data with a better
+    /// cache behavior than random calls to `get_val`. Codecs encoding their data by blocks
+    /// only decode the metadata of a block once for consecutive indexes in the same block.
+    ///
+    /// # Panics
+    ///
+    /// Must panic if `indexes` and `output` have different lengths, or if an index
+    /// is greater than the column length.
+    #[inline]
+    fn get_vals(&self, indexes: &[u32], output: &mut [T]) {
+        assert_eq!(indexes.len(), output.len());
+        for (out, &idx) in output.iter_mut().zip(indexes) {
+            *out = self.get_val(u64::from(idx));
+        }
+    }
+
     /// Returns the minimum value for this fast field.
     ///
     /// This min_value may not be exact.
@@ -82,6 +102,10 @@ impl<'a, C: Column<T>, T: Copy + PartialOrd> Column<T> for &'a C {
     fn get_range(&self, start: u64, output: &mut [T]) {
         (*self).get_range(start, output)
     }
+
+    fn get_vals(&self, indexes: &[u32], output: &mut [T]) {
+        (*self).get_vals(indexes, output)
+    }
 }
 
 impl<'a, T: Copy + PartialOrd + Send + Sync> Column<T> for VecColumn<'a, T> {
@@ -138,7 +162,7 @@ pub fn monotonic_map_column<C, T, Input, Output>(
 where
     C: Column<Input>,
     T: Fn(Input) -> Output + Send + Sync,
-    Input: Send + Sync,
+    Input: Copy + Send + Sync,
     Output: Send + Sync,
 {
     MonotonicMappingColumn {
@@ -152,7 +176,7 @@ impl<C, T, Input, Output> Column<Output> for MonotonicMappingColumn<C, T, Input>
 where
     C: Column<Input>,
     T: Fn(Input) -> Output + Send + Sync,
-    Input: Send + Sync,
+    Input: Copy + Send + Sync,
     Output: Send + Sync,
 {
     #[inline]
@@ -179,6 +203,28 @@ where
         Box::new(self.from_column.iter().map(&self.monotonic_mapping))
     }
 
+    fn get_vals(&self, indexes: &[u32], output: &mut [Output]) {
+        assert_eq!(indexes.len(), output.len());
+        let first_idx = if let Some(&first_idx) = indexes.first() {
+            first_idx
+        } else {
+            return;
+        };
+        // The values are fetched by chunks, so that the specialized implementation of the
+        // underlying column is used.
+        const CHUNK_LEN: usize = 64;
+        let mut buffer = [self.from_column.get_val(u64::from(first_idx)); CHUNK_LEN];
+        for (indexes_chunk, output_chunk) in
+            indexes.chunks(CHUNK_LEN).zip(output.chunks_mut(CHUNK_LEN))
+        {
+            let buffer = &mut buffer[..indexes_chunk.len()];
+            self.from_column.get_vals(indexes_chunk, buffer);
+            for (out, &from_val) in output_chunk.iter_mut().zip(buffer.iter()) {
+                *out = (self.monotonic_mapping)(from_val);
+            }
+        }
+    }
+
     // We voluntarily do not implement get_range as it yields a regression,
     // and we do not have any specialized implementation anyway.
 }
@@ -318,4 +364,18 @@ mod tests {
         mapped.get_range(7, &mut buf[..]);
         assert_eq!(&val_i64s[7..][..20], &buf);
     }
+
+    #[test]
+    fn test_monotonic_mapping_get_vals() {
+        let vals: Vec<u64> = (-1..199).map(i64::to_u64).collect();
+        let col = VecColumn::from(&vals);
+        let mapped = monotonic_map_column(col, |el| i64::from_u64(el) * 10i64);
+        let indexes: Vec<u32> = (0..200).filter(|idx| idx % 3 != 1).collect();
+        let mut buf = vec![0i64; indexes.len()];
+        mapped.get_vals(&indexes, &mut buf[..]);
+        for (&idx, &val) in indexes.iter().zip(buf.iter()) {
+            assert_eq!(val, mapped.get_val(u64::from(idx)));
+        }
+        mapped.get_vals(&[], &mut []);
+    }
 }
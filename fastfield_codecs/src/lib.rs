@@ -82,6 +82,26 @@ pub fn open<T: MonotonicallyMappableToU64>(
     }
 }
 
+/// Returns the codec used to serialize the column, as persisted in its header.
+pub fn codec_type(mut bytes: OwnedBytes) -> io::Result<FastFieldCodecType> {
+    let header = Header::deserialize(&mut bytes)?;
+    Ok(header.codec_type)
+}
+
+/// Returns the minimum value of the column and the greatest common divisor of the
+/// differences between its values and this minimum, as persisted in its header.
+///
+/// All of the values of the column are equal to `min_value + k * gcd` for some `k`. The gcd
+/// is 0 if all of the values are equal.
+pub fn min_value_and_gcd(mut bytes: OwnedBytes) -> io::Result<(u64, u64)> {
+    let header = Header::deserialize(&mut bytes)?;
+    if header.min_value == header.max_value {
+        return Ok((header.min_value, 0));
+    }
+    let gcd = header.gcd.map(|gcd| gcd.get()).unwrap_or(1);
+    Ok((header.min_value, gcd))
+}
+
 fn open_specific_codec<C: FastFieldCodec, Item: MonotonicallyMappableToU64>(
     bytes: OwnedBytes,
     header: &Header,
@@ -176,6 +196,15 @@ mod tests {
                  `{data:?}`",
             );
         }
+        // Every other index, plus the last one.
+        let indexes: Vec<u32> = (0..data.len() as u32)
+            .filter(|&idx| idx % 2 == 0 || idx as usize == data.len() - 1)
+            .collect();
+        let mut vals = vec![0u64; indexes.len()];
+        reader.get_vals(&indexes, &mut vals);
+        for (&idx, &val) in indexes.iter().zip(vals.iter()) {
+            assert_eq!(val, data[idx as usize], "get_vals in data set {name}");
+        }
         Some((estimation, actual_compression))
     }
 
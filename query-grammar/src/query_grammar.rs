@@ -478,6 +478,11 @@ mod test {
         test_parse_query_to_ast_helper("a^3", "(\"a\")^3");
         test_parse_query_to_ast_helper("a^3 b^2", "(*(\"a\")^3 *(\"b\")^2)");
         test_parse_query_to_ast_helper("a^1", "\"a\"");
+        test_parse_query_to_ast_helper("title:a^2.5", "(\"title\":\"a\")^2.5");
+        test_parse_query_to_ast_helper("\"a b\"^2", "(\"a b\")^2");
+        test_parse_query_to_ast_helper("\"a b\"~1^2", "(\"a b\"~1)^2");
+        test_parse_query_to_ast_helper("(a^2 b)^3", "((*(\"a\")^2 *\"b\"))^3");
+        test_parse_query_to_ast_helper("title:[a TO b]^2", "(\"title\":[\"a\" TO \"b\"])^2");
     }
 
     #[test]
@@ -6,7 +6,7 @@ use super::agg_result::AggregationResults;
 use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::segment_agg_result::SegmentAggregationResultsCollector;
 use crate::aggregation::agg_req_with_accessor::get_aggs_with_accessor_and_validate;
-use crate::collector::{Collector, SegmentCollector};
+use crate::collector::{Collector, DistributedCollector, SegmentCollector};
 use crate::{SegmentReader, TantivyError};
 
 /// The default max bucket count, before the aggregation fails.
@@ -87,6 +87,15 @@ impl Collector for DistributedAggregationCollector {
     }
 }
 
+impl DistributedCollector for DistributedAggregationCollector {
+    fn merge_distributed_fruits(
+        &self,
+        fruits: Vec<IntermediateAggregationResults>,
+    ) -> crate::Result<Self::Fruit> {
+        merge_fruits(fruits.into_iter().map(Ok).collect())
+    }
+}
+
 impl Collector for AggregationCollector {
     type Fruit = AggregationResults;
 
@@ -0,0 +1,287 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+
+use super::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastValue;
+use crate::schema::{Cardinality, Field};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `CollapseCollector` groups the matching documents by the value of a single-valued
+/// fast field, and returns the top `docs_per_group` documents, by score, of each group.
+///
+/// This is typically used to collapse search results, e.g. to show only the best result of
+/// each domain.
+///
+/// Groups are ranked by the score of their best document, and only the top `limit` groups
+/// are returned. Within a group, documents are sorted by decreasing score.
+///
+/// The memory usage is proportional to the number of distinct groups, times `docs_per_group`,
+/// regardless of the number of matching documents.
+///
+/// ```rust
+/// use tantivy::collector::CollapseCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let domain = schema_builder.add_u64_field("domain", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "diary of a rust programmer", domain => 1u64))?;
+/// index_writer.add_document(doc!(title => "rust rust rust", domain => 1u64))?;
+/// index_writer.add_document(doc!(title => "the rust book", domain => 2u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+/// let groups = searcher.search(&query, &CollapseCollector::<u64>::new(domain, 10, 1))?;
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].0, 1u64);
+/// assert_eq!(groups[0].1.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CollapseCollector<TFastValue: FastValue> {
+    field: Field,
+    limit: usize,
+    docs_per_group: usize,
+    _marker: PhantomData<TFastValue>,
+}
+
+impl<TFastValue: FastValue> CollapseCollector<TFastValue> {
+    /// Creates a `CollapseCollector` grouping the documents by the fast field `field`,
+    /// returning at most `limit` groups, and the top `docs_per_group` documents of each group.
+    ///
+    /// # Panics
+    /// The method panics if `limit` or `docs_per_group` is 0.
+    pub fn new(field: Field, limit: usize, docs_per_group: usize) -> CollapseCollector<TFastValue> {
+        assert!(limit >= 1, "Limit must be strictly greater than 0.");
+        assert!(
+            docs_per_group >= 1,
+            "The number of documents per group must be strictly greater than 0."
+        );
+        CollapseCollector {
+            field,
+            limit,
+            docs_per_group,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Top documents of the groups of a segment, keyed by the `u64` representation of their
+/// group value.
+pub struct SegmentGroups(HashMap<u64, Vec<(Score, DocAddress)>>);
+
+impl<TFastValue: FastValue> Collector for CollapseCollector<TFastValue> {
+    type Fruit = Vec<(TFastValue, Vec<(Score, DocAddress)>)>;
+
+    type Child = CollapseSegmentCollector<TFastValue>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<CollapseSegmentCollector<TFastValue>> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let requested_type = TFastValue::to_type();
+        let field_schema_type = field_entry.field_type().value_type();
+        if requested_type != field_schema_type {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is of type {:?}!={:?}",
+                field_entry.name(),
+                requested_type,
+                field_schema_type
+            )));
+        }
+        if TFastValue::fast_field_cardinality(field_entry.field_type())
+            != Some(Cardinality::SingleValue)
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a single-valued fast field.",
+                field_entry.name()
+            )));
+        }
+        let ff_reader = segment_reader
+            .fast_fields()
+            .typed_fast_field_reader(self.field)?;
+        Ok(CollapseSegmentCollector {
+            ff_reader,
+            segment_ord: segment_local_id,
+            docs_per_group: self.docs_per_group,
+            groups: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<SegmentGroups>,
+    ) -> crate::Result<Vec<(TFastValue, Vec<(Score, DocAddress)>)>> {
+        let mut segment_docs_per_group: HashMap<u64, Vec<Vec<(Score, DocAddress)>>> =
+            HashMap::new();
+        for SegmentGroups(segment_groups) in segment_fruits {
+            for (group, docs) in segment_groups {
+                segment_docs_per_group.entry(group).or_default().push(docs);
+            }
+        }
+        let top_collector = TopCollector::with_limit(self.docs_per_group);
+        let mut groups = Vec::with_capacity(segment_docs_per_group.len());
+        for (group, segment_docs) in segment_docs_per_group {
+            let docs = top_collector.merge_fruits(segment_docs)?;
+            groups.push((TFastValue::from_u64(group), docs));
+        }
+        // Groups are never empty, so they all have a best document.
+        groups.sort_by(|(_, left_docs), (_, right_docs)| {
+            let (left_score, left_doc) = left_docs[0];
+            let (right_score, right_doc) = right_docs[0];
+            right_score
+                .partial_cmp(&left_score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left_doc.cmp(&right_doc))
+        });
+        groups.truncate(self.limit);
+        Ok(groups)
+    }
+}
+
+/// Segment collector associated to the `CollapseCollector`.
+pub struct CollapseSegmentCollector<TFastValue: FastValue> {
+    ff_reader: Arc<dyn Column<TFastValue>>,
+    segment_ord: SegmentOrdinal,
+    docs_per_group: usize,
+    groups: HashMap<u64, TopSegmentCollector<Score>>,
+}
+
+impl<TFastValue: FastValue> SegmentCollector for CollapseSegmentCollector<TFastValue> {
+    type Fruit = SegmentGroups;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let group = self.ff_reader.get_val(u64::from(doc)).to_u64();
+        let segment_ord = self.segment_ord;
+        let docs_per_group = self.docs_per_group;
+        self.groups
+            .entry(group)
+            .or_insert_with(|| TopSegmentCollector::new(segment_ord, docs_per_group))
+            .collect(doc, score);
+    }
+
+    fn harvest(self) -> SegmentGroups {
+        SegmentGroups(
+            self.groups
+                .into_iter()
+                .map(|(group, top_segment_collector)| (group, top_segment_collector.harvest()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollapseCollector;
+    use crate::merge_policy::NoMergePolicy;
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, FAST, INDEXED, STORED, TEXT};
+    use crate::{DocAddress, Index};
+
+    #[test]
+    fn test_collapse_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let group = schema_builder.add_i64_field("group", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // The score of a document decreases with the length of its text.
+        let docs: &[(&str, i64)] = &[
+            ("a", -1),
+            ("a b", -1),
+            ("a b c", -1),
+            ("a b c d e", 2),
+            ("a b c d", 2),
+            ("b", 2),
+            ("a b c d e f", 3),
+        ];
+        for (i, &(doc_text, doc_group)) in docs.iter().enumerate() {
+            index_writer.add_document(doc!(text => doc_text, group => doc_group))?;
+            if i == 3 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("a")?;
+        let group_docs = |limit: usize, docs_per_group: usize| -> crate::Result<_> {
+            let groups = searcher.search(
+                &query,
+                &CollapseCollector::<i64>::new(group, limit, docs_per_group),
+            )?;
+            Ok(groups
+                .into_iter()
+                .map(|(group, docs)| {
+                    let doc_addresses: Vec<DocAddress> = docs
+                        .into_iter()
+                        .map(|(_, doc_address)| doc_address)
+                        .collect();
+                    (group, doc_addresses)
+                })
+                .collect::<Vec<_>>())
+        };
+        assert_eq!(
+            group_docs(10, 2)?,
+            vec![
+                (-1, vec![DocAddress::new(0, 0), DocAddress::new(0, 1)]),
+                (2, vec![DocAddress::new(1, 0), DocAddress::new(0, 3)]),
+                (3, vec![DocAddress::new(1, 2)]),
+            ]
+        );
+        assert_eq!(
+            group_docs(2, 1)?,
+            vec![
+                (-1, vec![DocAddress::new(0, 0)]),
+                (2, vec![DocAddress::new(1, 0)]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_collapse_collector_requires_single_valued_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexed_only = schema_builder.add_u64_field("indexed_only", INDEXED);
+        let fast = schema_builder.add_u64_field("fast", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(indexed_only => 1u64, fast => 1u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &CollapseCollector::<u64>::new(indexed_only, 1, 1)
+            )
+            .is_err());
+        assert!(searcher
+            .search(&AllQuery, &CollapseCollector::<i64>::new(fast, 1, 1))
+            .is_err());
+        assert_eq!(
+            searcher.search(&AllQuery, &CollapseCollector::<u64>::new(fast, 1, 1))?,
+            vec![(1u64, vec![(1.0, DocAddress::new(0, 0))])]
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use super::Collector;
-use crate::collector::SegmentCollector;
+use crate::collector::{DistributedCollector, SegmentCollector};
 use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
 
 /// `CountCollector` collector only counts how many
@@ -57,6 +57,12 @@ impl Collector for Count {
     }
 }
 
+impl DistributedCollector for Count {
+    fn merge_distributed_fruits(&self, counts: Vec<usize>) -> crate::Result<usize> {
+        Ok(counts.into_iter().sum())
+    }
+}
+
 #[derive(Default)]
 pub struct SegmentCountCollector {
     count: usize,
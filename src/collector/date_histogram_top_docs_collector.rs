@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fastdivide::DividerU64;
+use fastfield_codecs::Column;
+
+use super::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{type_and_cardinality, FastType};
+use crate::schema::{Cardinality, Field};
+use crate::{DateTime, DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `DateHistogramTopDocsCollector` buckets the matching documents by the value of a date
+/// fast field, and returns the top `docs_per_bucket` documents, by score, of each bucket.
+///
+/// This is typically used to show the top articles of each day.
+///
+/// Like for the [`HistogramCollector`](super::HistogramCollector), the buckets are given at
+/// construction: the `i`-th bucket is defined by
+/// `[min_value + i * bucket_width, min_value + (i + 1) * bucket_width)`. The documents outside
+/// of the buckets are ignored.
+///
+/// The result maps the start of each bucket to its top documents, sorted by decreasing score.
+/// Buckets without any matching document are omitted.
+///
+/// The memory usage is bounded by the number of buckets times `docs_per_bucket`, regardless
+/// of the number of matching documents.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::DateHistogramTopDocsCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DateTime, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(
+///     title => "rust 1.0 released",
+///     published => DateTime::from_timestamp_secs(3_600),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "rust rust rust",
+///     published => DateTime::from_timestamp_secs(7_200),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "the rust book",
+///     published => DateTime::from_timestamp_secs(86_400 + 3_600),
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+/// // The top document of each of the first 7 days.
+/// let collector = DateHistogramTopDocsCollector::new(
+///     published,
+///     DateTime::from_timestamp_secs(0),
+///     Duration::from_secs(86_400),
+///     7,
+///     1,
+/// );
+/// let top_docs_per_day = searcher.search(&query, &collector)?;
+/// assert_eq!(top_docs_per_day.len(), 2);
+/// assert_eq!(top_docs_per_day[&DateTime::from_timestamp_secs(0)].len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DateHistogramTopDocsCollector {
+    field: Field,
+    min_value: DateTime,
+    bucket_width_micros: u64,
+    num_buckets: usize,
+    docs_per_bucket: usize,
+}
+
+impl DateHistogramTopDocsCollector {
+    /// Creates a `DateHistogramTopDocsCollector` bucketing the documents by the date fast field
+    /// `field` into `num_buckets` buckets of `bucket_width` starting at `min_value`, and
+    /// returning the top `docs_per_bucket` documents of each bucket.
+    ///
+    /// # Panics
+    /// The method panics if `bucket_width` is shorter than a microsecond, or if
+    /// `docs_per_bucket` is 0.
+    pub fn new(
+        field: Field,
+        min_value: DateTime,
+        bucket_width: Duration,
+        num_buckets: usize,
+        docs_per_bucket: usize,
+    ) -> DateHistogramTopDocsCollector {
+        let bucket_width_micros = bucket_width.as_micros() as u64;
+        assert!(
+            bucket_width_micros >= 1,
+            "The bucket width must be at least one microsecond."
+        );
+        assert!(
+            docs_per_bucket >= 1,
+            "The number of documents per bucket must be strictly greater than 0."
+        );
+        DateHistogramTopDocsCollector {
+            field,
+            min_value,
+            bucket_width_micros,
+            num_buckets,
+            docs_per_bucket,
+        }
+    }
+
+    fn bucket_start(&self, bucket_ord: usize) -> DateTime {
+        let offset_micros = bucket_ord as i64 * self.bucket_width_micros as i64;
+        DateTime::from_timestamp_micros(self.min_value.into_timestamp_micros() + offset_micros)
+    }
+}
+
+impl Collector for DateHistogramTopDocsCollector {
+    type Fruit = BTreeMap<DateTime, Vec<(Score, DocAddress)>>;
+
+    type Child = DateHistogramTopDocsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<DateHistogramTopDocsSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        if !matches!(
+            type_and_cardinality(field_entry.field_type()),
+            Some((FastType::Date, Cardinality::SingleValue))
+        ) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a single-valued date fast field.",
+                field_entry.name()
+            )));
+        }
+        let ff_reader = segment_reader.fast_fields().date(self.field)?;
+        Ok(DateHistogramTopDocsSegmentCollector {
+            ff_reader,
+            segment_ord: segment_local_id,
+            min_value_micros: self.min_value.into_timestamp_micros(),
+            divider: DividerU64::divide_by(self.bucket_width_micros),
+            docs_per_bucket: self.docs_per_bucket,
+            buckets: (0..self.num_buckets).map(|_| None).collect(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(usize, Vec<(Score, DocAddress)>)>>,
+    ) -> crate::Result<BTreeMap<DateTime, Vec<(Score, DocAddress)>>> {
+        let mut segment_docs_per_bucket: BTreeMap<usize, Vec<Vec<(Score, DocAddress)>>> =
+            BTreeMap::new();
+        for segment_buckets in segment_fruits {
+            for (bucket_ord, docs) in segment_buckets {
+                segment_docs_per_bucket
+                    .entry(bucket_ord)
+                    .or_default()
+                    .push(docs);
+            }
+        }
+        let top_collector = TopCollector::with_limit(self.docs_per_bucket);
+        let mut buckets = BTreeMap::new();
+        for (bucket_ord, segment_docs) in segment_docs_per_bucket {
+            let docs = top_collector.merge_fruits(segment_docs)?;
+            buckets.insert(self.bucket_start(bucket_ord), docs);
+        }
+        Ok(buckets)
+    }
+}
+
+/// Segment collector associated to the `DateHistogramTopDocsCollector`.
+pub struct DateHistogramTopDocsSegmentCollector {
+    ff_reader: Arc<dyn Column<DateTime>>,
+    segment_ord: SegmentOrdinal,
+    min_value_micros: i64,
+    divider: DividerU64,
+    docs_per_bucket: usize,
+    buckets: Vec<Option<TopSegmentCollector<Score>>>,
+}
+
+impl SegmentCollector for DateHistogramTopDocsSegmentCollector {
+    type Fruit = Vec<(usize, Vec<(Score, DocAddress)>)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let value_micros = self
+            .ff_reader
+            .get_val(u64::from(doc))
+            .into_timestamp_micros();
+        if value_micros < self.min_value_micros {
+            return;
+        }
+        let delta = (value_micros as i128 - self.min_value_micros as i128) as u64;
+        let bucket_ord = self.divider.divide(delta) as usize;
+        let segment_ord = self.segment_ord;
+        let docs_per_bucket = self.docs_per_bucket;
+        if let Some(bucket) = self.buckets.get_mut(bucket_ord) {
+            bucket
+                .get_or_insert_with(|| TopSegmentCollector::new(segment_ord, docs_per_bucket))
+                .collect(doc, score);
+        }
+    }
+
+    /// Returns the top documents of each non-empty bucket, keyed by the ordinal of the bucket.
+    fn harvest(self) -> Vec<(usize, Vec<(Score, DocAddress)>)> {
+        self.buckets
+            .into_iter()
+            .enumerate()
+            .filter_map(|(bucket_ord, bucket)| {
+                bucket.map(|top_segment_collector| (bucket_ord, top_segment_collector.harvest()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::DateHistogramTopDocsCollector;
+    use crate::merge_policy::NoMergePolicy;
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, FAST, STORED, TEXT};
+    use crate::{DateTime, Index, TantivyError};
+
+    const DAY_SECS: i64 = 86_400;
+
+    #[test]
+    fn test_date_histogram_top_docs_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let published = schema_builder.add_date_field("published", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // The score of a document decreases with the length of its text.
+        let docs: &[(&str, i64)] = &[
+            ("a b c", 0),
+            ("a", 0),
+            ("a b c d e", 0),
+            ("a b", 0),
+            ("a b c d", 1),
+            ("b", 1),
+            ("a b c d e f", 3),
+            ("a b", 3),
+            // Before the first bucket.
+            ("a", -1),
+            // After the last bucket.
+            ("a", 4),
+        ];
+        for (i, &(doc_text, day)) in docs.iter().enumerate() {
+            let hour_secs = 3_600 * (i as i64 % 24);
+            let date = DateTime::from_timestamp_secs(day * DAY_SECS + hour_secs);
+            index_writer.add_document(doc!(text => doc_text, published => date))?;
+            // Spread the documents of a day over several segments.
+            if i % 3 == 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("a")?;
+        let collector = DateHistogramTopDocsCollector::new(
+            published,
+            DateTime::from_timestamp_secs(0),
+            Duration::from_secs(DAY_SECS as u64),
+            4,
+            3,
+        );
+        let top_docs_per_day = searcher.search(&query, &collector)?;
+        let top_texts_per_day: Vec<(i64, Vec<String>)> = top_docs_per_day
+            .iter()
+            .map(|(day_start, top_docs)| {
+                let texts = top_docs
+                    .iter()
+                    .map(|(_, doc_address)| {
+                        let doc = searcher.doc(*doc_address)?;
+                        Ok(doc.get_first(text).unwrap().as_text().unwrap().to_string())
+                    })
+                    .collect::<crate::Result<_>>()?;
+                Ok((day_start.into_timestamp_secs() / DAY_SECS, texts))
+            })
+            .collect::<crate::Result<_>>()?;
+        // The second day only has one matching document, and the third day has none.
+        assert_eq!(
+            top_texts_per_day,
+            vec![
+                (
+                    0,
+                    vec!["a".to_string(), "a b".to_string(), "a b c".to_string()]
+                ),
+                (1, vec!["a b c d".to_string()]),
+                (3, vec!["a b".to_string(), "a b c d e f".to_string()]),
+            ]
+        );
+        for top_docs in top_docs_per_day.values() {
+            assert!(top_docs.windows(2).all(|docs| docs[0].0 >= docs[1].0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_histogram_top_docs_collector_requires_date_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(timestamp => 1i64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let collector = DateHistogramTopDocsCollector::new(
+            timestamp,
+            DateTime::from_timestamp_secs(0),
+            Duration::from_secs(1),
+            10,
+            1,
+        );
+        assert!(matches!(
+            searcher.search(&AllQuery, &collector),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,613 @@
+use std::cmp::Ordering;
+
+use super::{Collector, SegmentCollector};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+// Containers holding more values than this are stored as a bitmap.
+const ARRAY_CONTAINER_MAX_LEN: usize = 4_096;
+const BITMAP_CONTAINER_NUM_WORDS: usize = 1_024;
+
+type BitmapWords = Box<[u64; BITMAP_CONTAINER_NUM_WORDS]>;
+
+/// Set of the lowest 16 bits of the doc ids sharing the same highest 16 bits.
+///
+/// The representation is canonical: a container holds at most `ARRAY_CONTAINER_MAX_LEN` values
+/// if and only if it is an array.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Container {
+    // Sorted values.
+    Array(Vec<u16>),
+    Bitmap(BitmapWords),
+}
+
+fn word_bits(mut word: u64) -> impl Iterator<Item = u16> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as u16;
+        word &= word - 1;
+        Some(bit)
+    })
+}
+
+impl Container {
+    fn from_words(words: BitmapWords) -> Option<Container> {
+        let len: u32 = words.iter().map(|word| word.count_ones()).sum();
+        if len == 0 {
+            return None;
+        }
+        if len as usize > ARRAY_CONTAINER_MAX_LEN {
+            return Some(Container::Bitmap(words));
+        }
+        let values = Container::Bitmap(words).iter().collect();
+        Some(Container::Array(values))
+    }
+
+    fn from_sorted_values(values: Vec<u16>) -> Option<Container> {
+        if values.is_empty() {
+            None
+        } else if values.len() > ARRAY_CONTAINER_MAX_LEN {
+            Some(Container::Bitmap(Container::Array(values).to_words()))
+        } else {
+            Some(Container::Array(values))
+        }
+    }
+
+    fn to_words(&self) -> BitmapWords {
+        match self {
+            Container::Array(values) => {
+                let mut words: BitmapWords = Box::new([0u64; BITMAP_CONTAINER_NUM_WORDS]);
+                for &value in values {
+                    words[value as usize / 64] |= 1u64 << (value % 64);
+                }
+                words
+            }
+            Container::Bitmap(words) => words.clone(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Container::Array(values) => values.len(),
+            Container::Bitmap(words) => words.iter().map(|word| word.count_ones() as usize).sum(),
+        }
+    }
+
+    fn contains(&self, value: u16) -> bool {
+        match self {
+            Container::Array(values) => values.binary_search(&value).is_ok(),
+            Container::Bitmap(words) => words[value as usize / 64] & (1u64 << (value % 64)) != 0,
+        }
+    }
+
+    fn insert(&mut self, value: u16) {
+        match self {
+            Container::Array(values) => {
+                // Documents are usually collected in increasing order.
+                if values.last().map(|&last| last < value).unwrap_or(true) {
+                    values.push(value);
+                } else if let Err(pos) = values.binary_search(&value) {
+                    values.insert(pos, value);
+                } else {
+                    return;
+                }
+                if values.len() > ARRAY_CONTAINER_MAX_LEN {
+                    *self = Container::Bitmap(self.to_words());
+                }
+            }
+            Container::Bitmap(words) => words[value as usize / 64] |= 1u64 << (value % 64),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            Container::Array(values) => Box::new(values.iter().cloned()),
+            Container::Bitmap(words) => Box::new(
+                words
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, &word)| word_bits(word).map(move |bit| i as u16 * 64 + bit)),
+            ),
+        }
+    }
+
+    fn union(&self, other: &Container) -> Option<Container> {
+        match (self, other) {
+            (Container::Array(left), Container::Array(right)) => {
+                let mut values = Vec::with_capacity(left.len() + right.len());
+                let (mut left_it, mut right_it) = (left.iter().peekable(), right.iter().peekable());
+                loop {
+                    let value = match (left_it.peek(), right_it.peek()) {
+                        (Some(&&left_value), Some(&&right_value)) => {
+                            match left_value.cmp(&right_value) {
+                                Ordering::Less => left_it.next(),
+                                Ordering::Greater => right_it.next(),
+                                Ordering::Equal => {
+                                    right_it.next();
+                                    left_it.next()
+                                }
+                            }
+                        }
+                        (Some(_), None) => left_it.next(),
+                        (None, Some(_)) => right_it.next(),
+                        (None, None) => break,
+                    };
+                    values.extend(value);
+                }
+                Container::from_sorted_values(values)
+            }
+            _ => {
+                let mut words = self.to_words();
+                for (word, other_word) in words.iter_mut().zip(other.to_words().iter()) {
+                    *word |= other_word;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+
+    fn intersect(&self, other: &Container) -> Option<Container> {
+        match (self, other) {
+            (Container::Array(values), _) => Container::from_sorted_values(
+                values
+                    .iter()
+                    .cloned()
+                    .filter(|&value| other.contains(value))
+                    .collect(),
+            ),
+            (_, Container::Array(_)) => other.intersect(self),
+            (Container::Bitmap(left_words), Container::Bitmap(right_words)) => {
+                let mut words = left_words.clone();
+                for (word, right_word) in words.iter_mut().zip(right_words.iter()) {
+                    *word &= right_word;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+
+    fn difference(&self, other: &Container) -> Option<Container> {
+        match self {
+            Container::Array(values) => Container::from_sorted_values(
+                values
+                    .iter()
+                    .cloned()
+                    .filter(|&value| !other.contains(value))
+                    .collect(),
+            ),
+            Container::Bitmap(left_words) => {
+                let mut words = left_words.clone();
+                for (word, other_word) in words.iter_mut().zip(other.to_words().iter()) {
+                    *word &= !other_word;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+}
+
+/// Compressed set of the doc ids of a segment.
+///
+/// As in a Roaring bitmap, the doc ids are split into chunks of 2^16 doc ids. The doc ids of
+/// a chunk are stored as a sorted array if the chunk has few of them, and as a bitmap
+/// otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocBitmap {
+    // Sorted by the highest 16 bits of the doc ids of the container.
+    containers: Vec<(u16, Container)>,
+}
+
+fn split_doc(doc: DocId) -> (u16, u16) {
+    ((doc >> 16) as u16, doc as u16)
+}
+
+impl DocBitmap {
+    /// Creates an empty `DocBitmap`.
+    pub fn new() -> DocBitmap {
+        DocBitmap::default()
+    }
+
+    /// Inserts a doc id in the set.
+    pub fn insert(&mut self, doc: DocId) {
+        let (key, value) = split_doc(doc);
+        let pos = match self.containers.last() {
+            Some((last_key, _)) if *last_key == key => self.containers.len() - 1,
+            _ => match self
+                .containers
+                .binary_search_by_key(&key, |(container_key, _)| *container_key)
+            {
+                Ok(pos) => pos,
+                Err(pos) => {
+                    self.containers
+                        .insert(pos, (key, Container::Array(Vec::new())));
+                    pos
+                }
+            },
+        };
+        self.containers[pos].1.insert(value);
+    }
+
+    /// Returns true if the doc id is in the set.
+    pub fn contains(&self, doc: DocId) -> bool {
+        let (key, value) = split_doc(doc);
+        self.containers
+            .binary_search_by_key(&key, |(container_key, _)| *container_key)
+            .map(|pos| self.containers[pos].1.contains(value))
+            .unwrap_or(false)
+    }
+
+    /// Returns the number of doc ids in the set.
+    pub fn len(&self) -> usize {
+        self.containers
+            .iter()
+            .map(|(_, container)| container.len())
+            .sum()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Iterates over the doc ids of the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = DocId> + '_ {
+        self.containers.iter().flat_map(|(key, container)| {
+            let high_bits = (*key as DocId) << 16;
+            container
+                .iter()
+                .map(move |value| high_bits | value as DocId)
+        })
+    }
+
+    fn combine(
+        &self,
+        other: &DocBitmap,
+        keep_left_only: bool,
+        keep_right_only: bool,
+        combine_containers: impl Fn(&Container, &Container) -> Option<Container>,
+    ) -> DocBitmap {
+        let mut containers = Vec::new();
+        let mut left_it = self.containers.iter().peekable();
+        let mut right_it = other.containers.iter().peekable();
+        loop {
+            match (left_it.peek(), right_it.peek()) {
+                (Some((left_key, left_container)), Some((right_key, right_container))) => {
+                    match left_key.cmp(right_key) {
+                        Ordering::Less => {
+                            if keep_left_only {
+                                containers.push((*left_key, left_container.clone()));
+                            }
+                            left_it.next();
+                        }
+                        Ordering::Greater => {
+                            if keep_right_only {
+                                containers.push((*right_key, right_container.clone()));
+                            }
+                            right_it.next();
+                        }
+                        Ordering::Equal => {
+                            if let Some(container) =
+                                combine_containers(left_container, right_container)
+                            {
+                                containers.push((*left_key, container));
+                            }
+                            left_it.next();
+                            right_it.next();
+                        }
+                    }
+                }
+                (Some(_), None) => {
+                    if keep_left_only {
+                        containers.extend(left_it.cloned());
+                    }
+                    break;
+                }
+                (None, Some(_)) => {
+                    if keep_right_only {
+                        containers.extend(right_it.cloned());
+                    }
+                    break;
+                }
+                (None, None) => break,
+            }
+        }
+        DocBitmap { containers }
+    }
+
+    /// Returns the doc ids that are in this set or in `other`.
+    pub fn union(&self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, true, true, Container::union)
+    }
+
+    /// Returns the doc ids that are both in this set and in `other`.
+    pub fn intersect(&self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, false, false, Container::intersect)
+    }
+
+    /// Returns the doc ids of this set that are not in `other`.
+    pub fn difference(&self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, true, false, Container::difference)
+    }
+}
+
+impl FromIterator<DocId> for DocBitmap {
+    fn from_iter<T: IntoIterator<Item = DocId>>(docs: T) -> DocBitmap {
+        let mut doc_bitmap = DocBitmap::new();
+        for doc in docs {
+            doc_bitmap.insert(doc);
+        }
+        doc_bitmap
+    }
+}
+
+/// The documents matched by a query, as one [`DocBitmap`] per segment.
+///
+/// The set operations only make sense between the `DocBitmaps` of queries run on the same
+/// [`Searcher`](crate::Searcher), e.g. to combine the cached results of several queries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocBitmaps {
+    // Indexed by segment ordinal.
+    segment_bitmaps: Vec<DocBitmap>,
+}
+
+impl DocBitmaps {
+    /// Returns the doc ids of the segment `segment_ord`.
+    pub fn segment_bitmap(&self, segment_ord: SegmentOrdinal) -> Option<&DocBitmap> {
+        self.segment_bitmaps.get(segment_ord as usize)
+    }
+
+    /// Returns the number of documents in the set.
+    pub fn len(&self) -> usize {
+        self.segment_bitmaps.iter().map(DocBitmap::len).sum()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.segment_bitmaps.iter().all(DocBitmap::is_empty)
+    }
+
+    /// Returns true if the document is in the set.
+    pub fn contains(&self, doc_address: DocAddress) -> bool {
+        self.segment_bitmap(doc_address.segment_ord)
+            .map(|segment_bitmap| segment_bitmap.contains(doc_address.doc_id))
+            .unwrap_or(false)
+    }
+
+    /// Iterates over the documents of the set, ordered by segment and doc id.
+    pub fn iter(&self) -> impl Iterator<Item = DocAddress> + '_ {
+        self.segment_bitmaps
+            .iter()
+            .enumerate()
+            .flat_map(|(segment_ord, segment_bitmap)| {
+                segment_bitmap
+                    .iter()
+                    .map(move |doc| DocAddress::new(segment_ord as SegmentOrdinal, doc))
+            })
+    }
+
+    fn combine(
+        &self,
+        other: &DocBitmaps,
+        combine_bitmaps: impl Fn(&DocBitmap, &DocBitmap) -> DocBitmap,
+    ) -> DocBitmaps {
+        let num_segments = self.segment_bitmaps.len().max(other.segment_bitmaps.len());
+        let empty_bitmap = DocBitmap::new();
+        let segment_bitmaps = (0..num_segments)
+            .map(|segment_ord| {
+                let left = self.segment_bitmaps.get(segment_ord);
+                let right = other.segment_bitmaps.get(segment_ord);
+                combine_bitmaps(
+                    left.unwrap_or(&empty_bitmap),
+                    right.unwrap_or(&empty_bitmap),
+                )
+            })
+            .collect();
+        DocBitmaps { segment_bitmaps }
+    }
+
+    /// Returns the documents that are in this set or in `other`.
+    pub fn union(&self, other: &DocBitmaps) -> DocBitmaps {
+        self.combine(other, DocBitmap::union)
+    }
+
+    /// Returns the documents that are both in this set and in `other`.
+    pub fn intersect(&self, other: &DocBitmaps) -> DocBitmaps {
+        self.combine(other, DocBitmap::intersect)
+    }
+
+    /// Returns the documents of this set that are not in `other`.
+    pub fn difference(&self, other: &DocBitmaps) -> DocBitmaps {
+        self.combine(other, DocBitmap::difference)
+    }
+}
+
+/// Collector returning the documents matching a query as [`DocBitmaps`], a compressed bitmap
+/// of doc ids per segment.
+///
+/// Contrary to the [`DocSetCollector`](super::DocSetCollector), the result stays compact for
+/// queries matching a large number of documents, and can be combined with the results of
+/// other queries with set operations.
+///
+/// ```rust
+/// use tantivy::collector::DocBitmapCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "the diary of a young girl"))?;
+/// index_writer.add_document(doc!(title => "the diary of muadib"))?;
+/// index_writer.add_document(doc!(title => "a young wizard"))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let diaries = searcher.search(&query_parser.parse_query("diary")?, &DocBitmapCollector)?;
+/// let young = searcher.search(&query_parser.parse_query("young")?, &DocBitmapCollector)?;
+/// assert_eq!(diaries.intersect(&young).len(), 1);
+/// assert_eq!(diaries.union(&young).len(), 3);
+/// assert_eq!(diaries.difference(&young).len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DocBitmapCollector;
+
+impl Collector for DocBitmapCollector {
+    type Fruit = DocBitmaps;
+
+    type Child = DocBitmapSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        _reader: &SegmentReader,
+    ) -> crate::Result<DocBitmapSegmentCollector> {
+        Ok(DocBitmapSegmentCollector {
+            segment_local_id,
+            doc_bitmap: DocBitmap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(SegmentOrdinal, DocBitmap)>,
+    ) -> crate::Result<DocBitmaps> {
+        let mut segment_bitmaps = Vec::new();
+        for (segment_local_id, doc_bitmap) in segment_fruits {
+            let segment_ord = segment_local_id as usize;
+            if segment_bitmaps.len() <= segment_ord {
+                segment_bitmaps.resize_with(segment_ord + 1, DocBitmap::new);
+            }
+            segment_bitmaps[segment_ord] = doc_bitmap;
+        }
+        Ok(DocBitmaps { segment_bitmaps })
+    }
+}
+
+/// Segment collector of the [`DocBitmapCollector`].
+pub struct DocBitmapSegmentCollector {
+    segment_local_id: SegmentOrdinal,
+    doc_bitmap: DocBitmap,
+}
+
+impl SegmentCollector for DocBitmapSegmentCollector {
+    type Fruit = (SegmentOrdinal, DocBitmap);
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.doc_bitmap.insert(doc);
+    }
+
+    fn harvest(self) -> (SegmentOrdinal, DocBitmap) {
+        (self.segment_local_id, self.doc_bitmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use super::{DocBitmap, DocBitmapCollector};
+    use crate::collector::DocSetCollector;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{DocAddress, DocId, Index, Term};
+
+    #[test]
+    fn test_doc_bitmap_set_operations() {
+        // Sparse and dense chunks, including chunks only present in one of the sets.
+        let left_docs: BTreeSet<DocId> = (0..10_000)
+            .chain((70_000..80_000).step_by(3))
+            .chain([200_000, 300_000])
+            .collect();
+        let right_docs: BTreeSet<DocId> = (5_000..20_000)
+            .step_by(2)
+            .chain(75_000..140_000)
+            .chain([300_000, 400_000])
+            .collect();
+        let left: DocBitmap = left_docs.iter().cloned().collect();
+        let right: DocBitmap = right_docs.iter().rev().cloned().collect();
+        assert_eq!(left.len(), left_docs.len());
+        assert_eq!(left.iter().collect::<BTreeSet<_>>(), left_docs);
+        assert!(left.contains(70_003));
+        assert!(!left.contains(70_004));
+        assert!(!left.contains(500_000));
+
+        let check = |doc_bitmap: DocBitmap, expected: BTreeSet<DocId>| {
+            assert_eq!(doc_bitmap.len(), expected.len());
+            let expected_docs: Vec<DocId> = expected.iter().cloned().collect();
+            assert_eq!(doc_bitmap.iter().collect::<Vec<_>>(), expected_docs);
+            // The representation does not depend on how the set was built.
+            assert_eq!(doc_bitmap, expected.into_iter().collect::<DocBitmap>());
+        };
+        check(
+            left.union(&right),
+            left_docs.union(&right_docs).cloned().collect(),
+        );
+        check(
+            left.intersect(&right),
+            left_docs.intersection(&right_docs).cloned().collect(),
+        );
+        check(
+            left.difference(&right),
+            left_docs.difference(&right_docs).cloned().collect(),
+        );
+        check(
+            right.difference(&left),
+            right_docs.difference(&left_docs).cloned().collect(),
+        );
+        assert!(left.difference(&left).is_empty());
+    }
+
+    #[test]
+    fn test_doc_bitmap_collector_intersection() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color = schema_builder.add_text_field("color", STRING);
+        let size = schema_builder.add_text_field("size", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..10_000 {
+            let color_value = if i % 3 == 0 { "red" } else { "blue" };
+            let size_value = if i % 2 == 0 { "small" } else { "large" };
+            index_writer.add_document(doc!(color => color_value, size => size_value))?;
+            if i % 4_000 == 3_999 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let term_query = |field, text| {
+            TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic)
+        };
+
+        let red = searcher.search(&term_query(color, "red"), &DocBitmapCollector)?;
+        let small = searcher.search(&term_query(size, "small"), &DocBitmapCollector)?;
+        assert_eq!(red.len(), 3_334);
+        assert_eq!(small.len(), 5_000);
+        let red_and_small = red.intersect(&small);
+        // The multiples of 6.
+        assert_eq!(red_and_small.len(), 1_667);
+        let expected: HashSet<DocAddress> = searcher
+            .search(&term_query(color, "red"), &DocSetCollector)?
+            .intersection(&searcher.search(&term_query(size, "small"), &DocSetCollector)?)
+            .cloned()
+            .collect();
+        assert_eq!(red_and_small.iter().collect::<HashSet<_>>(), expected);
+        assert!(expected
+            .iter()
+            .all(|&doc_address| red_and_small.contains(doc_address)));
+        assert_eq!(red.union(&small).len(), 3_334 + 5_000 - 1_667);
+        assert_eq!(red.difference(&small).len(), 3_334 - 1_667);
+        Ok(())
+    }
+}
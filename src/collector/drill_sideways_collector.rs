@@ -0,0 +1,489 @@
+use std::ops::Range;
+
+use super::facet_collector::FacetSegmentCollector;
+use crate::collector::{Collector, FacetCollector, FacetCounts, SegmentCollector};
+use crate::fastfield::FacetReader;
+use crate::schema::{Facet, Field};
+use crate::termdict::{TermDictionary, TermOrdinal};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// Collector computing "drill sideways" facet counts.
+///
+/// When a user narrows down a search by selecting a facet value (drill down),
+/// the counts of the other values of the same facet are typically still displayed,
+/// so that the user can broaden or change the selection.
+///
+/// Given a base query and a set of selected facets, the `DrillSidewaysCollector`
+/// computes, for each of the requested facets, the counts of its children as if the
+/// selection on that facet had not been applied, while the selections on all of the other
+/// facets still apply.
+///
+/// Facets to count are registered with `.add_facet(...)`, as with the
+/// [`FacetCollector`]. Selected facets are registered with `.add_drill_down(...)`.
+/// A selected facet belongs to the dimension of the registered facet it descends from.
+/// Several selections within the same dimension are combined as an `OR`, while different
+/// dimensions are combined as an `AND`. Selections that do not belong to any dimension
+/// are combined as an `OR`, and act as a plain filter on all of the counts.
+///
+/// The query passed to the searcher must be the base query, **without** the
+/// drill down filters. The documents matching the drill down (the base query and all of the
+/// selections) can be retrieved by running a `BooleanQuery` combining the base query with
+/// `TermQuery` on the selected facets.
+///
+/// ```rust
+/// use tantivy::collector::DrillSidewaysCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Facet, FacetOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(
+///     facet => Facet::from("/lang/en"),
+///     facet => Facet::from("/category/fiction")
+/// ))?;
+/// index_writer.add_document(doc!(
+///     facet => Facet::from("/lang/fr"),
+///     facet => Facet::from("/category/fiction")
+/// ))?;
+/// index_writer.add_document(doc!(
+///     facet => Facet::from("/lang/en"),
+///     facet => Facet::from("/category/biography")
+/// ))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let mut collector = DrillSidewaysCollector::for_field(facet);
+/// collector.add_facet("/lang");
+/// collector.add_facet("/category");
+/// collector.add_drill_down("/lang/en");
+/// let facet_counts = searcher.search(&AllQuery, &collector)?;
+///
+/// // The selection on `/lang` is not applied on the `/lang` counts...
+/// let lang_counts: Vec<(&Facet, u64)> = facet_counts.get("/lang").collect();
+/// assert_eq!(
+///     lang_counts,
+///     vec![(&Facet::from("/lang/en"), 2), (&Facet::from("/lang/fr"), 1)]
+/// );
+/// // ... but it is applied on the other facets.
+/// let category_counts: Vec<(&Facet, u64)> = facet_counts.get("/category").collect();
+/// assert_eq!(
+///     category_counts,
+///     vec![
+///         (&Facet::from("/category/biography"), 1),
+///         (&Facet::from("/category/fiction"), 1)
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct DrillSidewaysCollector {
+    field: Field,
+    dimensions: Vec<Dimension>,
+    // Drill down facets that do not belong to any dimension.
+    filters: Vec<Facet>,
+}
+
+struct Dimension {
+    facet_collector: FacetCollector,
+    root: Facet,
+    drill_downs: Vec<Facet>,
+}
+
+impl DrillSidewaysCollector {
+    /// Create a drill sideways collector to collect the facets
+    /// from a specific facet `Field`.
+    ///
+    /// This function does not check whether the field
+    /// is of the proper type.
+    pub fn for_field(field: Field) -> DrillSidewaysCollector {
+        DrillSidewaysCollector {
+            field,
+            dimensions: Vec::new(),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Adds a facet that we want to record counts
+    ///
+    /// See [`FacetCollector::add_facet`].
+    ///
+    /// Adding two facets within which one is the prefix of the other is forbidden.
+    pub fn add_facet<T>(&mut self, facet_from: T)
+    where Facet: From<T> {
+        let root = Facet::from(facet_from);
+        for dimension in &self.dimensions {
+            assert!(
+                !dimension.root.is_prefix_of(&root) && !root.is_prefix_of(&dimension.root),
+                "Tried to add a facet which is an ancestor or a descendant of an already added \
+                 facet."
+            );
+        }
+        let mut facet_collector = FacetCollector::for_field(self.field);
+        facet_collector.add_facet::<Facet>(root.clone());
+        let mut dimension = Dimension {
+            facet_collector,
+            root,
+            drill_downs: Vec::new(),
+        };
+        // Attach the existing filters belonging to this new dimension.
+        let (drill_downs, filters) = std::mem::take(&mut self.filters)
+            .into_iter()
+            .partition(|filter| dimension.root.is_prefix_of(filter));
+        dimension.drill_downs = drill_downs;
+        self.filters = filters;
+        self.dimensions.push(dimension);
+    }
+
+    /// Restricts the results to the documents having the given facet (or one of its
+    /// descendants).
+    ///
+    /// If the facet descends from a facet registered with `.add_facet(...)`, the
+    /// restriction does not apply to the counts of that facet.
+    pub fn add_drill_down<T>(&mut self, facet_from: T)
+    where Facet: From<T> {
+        let facet = Facet::from(facet_from);
+        if let Some(dimension) = self
+            .dimensions
+            .iter_mut()
+            .find(|dimension| dimension.root.is_prefix_of(&facet))
+        {
+            dimension.drill_downs.push(facet);
+        } else {
+            self.filters.push(facet);
+        }
+    }
+}
+
+/// Returns the range of facet ordinals for the given facet and all of its descendants.
+fn facet_ord_range(facet_dict: &TermDictionary, facet: &Facet) -> crate::Result<Range<u64>> {
+    let first_ord_from = |bound: &[u8]| -> crate::Result<TermOrdinal> {
+        let mut stream = facet_dict.range().ge(bound).into_stream()?;
+        if stream.advance() {
+            Ok(stream.term_ord())
+        } else {
+            Ok(facet_dict.num_terms() as TermOrdinal)
+        }
+    };
+    let mut facet_after: String = facet.encoded_str().to_owned();
+    facet_after.push('\u{1}');
+    let start = first_ord_from(facet.encoded_str().as_bytes())?;
+    let end = first_ord_from(facet_after.as_bytes())?;
+    Ok(start..end)
+}
+
+fn facet_ord_ranges(facet_dict: &TermDictionary, facets: &[Facet]) -> crate::Result<FacetFilter> {
+    let ranges = facets
+        .iter()
+        .map(|facet| facet_ord_range(facet_dict, facet))
+        .collect::<crate::Result<Vec<_>>>()?;
+    Ok(FacetFilter { ranges })
+}
+
+/// A filter matching the documents having at least one facet ordinal in any of the ranges.
+struct FacetFilter {
+    ranges: Vec<Range<u64>>,
+}
+
+impl FacetFilter {
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    fn matches(&self, facet_ords: &[u64]) -> bool {
+        facet_ords.iter().any(|facet_ord| {
+            self.ranges
+                .iter()
+                .any(|facet_ord_range| facet_ord_range.contains(facet_ord))
+        })
+    }
+}
+
+impl Collector for DrillSidewaysCollector {
+    type Fruit = FacetCounts;
+
+    type Child = DrillSidewaysSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<DrillSidewaysSegmentCollector> {
+        let facet_reader = reader.facet_reader(self.field)?;
+        let facet_dict = facet_reader.facet_dict();
+        let filter = facet_ord_ranges(facet_dict, &self.filters)?;
+        let mut dimension_filters = Vec::with_capacity(self.dimensions.len());
+        let mut dimension_collectors = Vec::with_capacity(self.dimensions.len());
+        for dimension in &self.dimensions {
+            dimension_filters.push(facet_ord_ranges(facet_dict, &dimension.drill_downs)?);
+            dimension_collectors.push(
+                dimension
+                    .facet_collector
+                    .for_segment(segment_local_id, reader)?,
+            );
+        }
+        Ok(DrillSidewaysSegmentCollector {
+            facet_reader,
+            facet_ords_buf: Vec::with_capacity(255),
+            filter,
+            dimension_filters,
+            dimension_collectors,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segments_facet_counts: Vec<FacetCounts>) -> crate::Result<FacetCounts> {
+        Ok(FacetCounts::merge(segments_facet_counts))
+    }
+}
+
+/// Segment collector for the [`DrillSidewaysCollector`].
+pub struct DrillSidewaysSegmentCollector {
+    facet_reader: FacetReader,
+    facet_ords_buf: Vec<u64>,
+    filter: FacetFilter,
+    dimension_filters: Vec<FacetFilter>,
+    dimension_collectors: Vec<FacetSegmentCollector>,
+}
+
+impl SegmentCollector for DrillSidewaysSegmentCollector {
+    type Fruit = FacetCounts;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.facet_reader.facet_ords(doc, &mut self.facet_ords_buf);
+        if !self.filter.is_empty() && !self.filter.matches(&self.facet_ords_buf) {
+            return;
+        }
+        let mut failing_dimension_opt = None;
+        for (dimension_ord, dimension_filter) in self.dimension_filters.iter().enumerate() {
+            if dimension_filter.is_empty() || dimension_filter.matches(&self.facet_ords_buf) {
+                continue;
+            }
+            if failing_dimension_opt.is_some() {
+                // The document misses the selection of at least two dimensions:
+                // it does not contribute to any count.
+                return;
+            }
+            failing_dimension_opt = Some(dimension_ord);
+        }
+        if let Some(failing_dimension) = failing_dimension_opt {
+            // The document only misses the selection of one dimension: it is a
+            // "sideways" hit for that dimension.
+            self.dimension_collectors[failing_dimension].collect(doc, score);
+        } else {
+            for dimension_collector in &mut self.dimension_collectors {
+                dimension_collector.collect(doc, score);
+            }
+        }
+    }
+
+    fn harvest(self) -> FacetCounts {
+        // Dimensions are disjoint, so that the counts of all of the dimensions can be merged
+        // into a single `FacetCounts`.
+        FacetCounts::merge(
+            self.dimension_collectors
+                .into_iter()
+                .map(SegmentCollector::harvest),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrillSidewaysCollector;
+    use crate::collector::{Count, FacetCollector};
+    use crate::query::{AllQuery, BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{Facet, FacetOptions, IndexRecordOption, Schema};
+    use crate::{Index, Term};
+
+    fn counts(facet_counts: &crate::collector::FacetCounts, facet: &str) -> Vec<(String, u64)> {
+        facet_counts
+            .get(facet)
+            .map(|(facet, count)| (facet.to_string(), count))
+            .collect()
+    }
+
+    fn test_index() -> crate::Result<(Index, crate::schema::Field)> {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        let docs = [
+            ("/author/bob", "/year/2010"),
+            ("/author/lisa", "/year/2010"),
+            ("/author/lisa", "/year/2012"),
+            ("/author/susan", "/year/2012"),
+            ("/author/frank", "/year/1999"),
+        ];
+        for (i, (author, year)) in docs.iter().enumerate() {
+            index_writer.add_document(doc!(
+                facet_field => Facet::from(*author),
+                facet_field => Facet::from(*year),
+            ))?;
+            // Spread the documents over several segments.
+            if i % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        Ok((index, facet_field))
+    }
+
+    #[test]
+    fn test_drill_sideways_without_drill_down() -> crate::Result<()> {
+        let (index, facet_field) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        collector.add_facet("/author");
+        collector.add_facet("/year");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        let mut facet_collector = FacetCollector::for_field(facet_field);
+        facet_collector.add_facet("/author");
+        facet_collector.add_facet("/year");
+        let expected_facet_counts = searcher.search(&AllQuery, &facet_collector)?;
+        for facet in ["/author", "/year"] {
+            assert_eq!(
+                counts(&facet_counts, facet),
+                counts(&expected_facet_counts, facet)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_sideways() -> crate::Result<()> {
+        let (index, facet_field) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        collector.add_facet("/author");
+        collector.add_facet("/year");
+        collector.add_drill_down("/author/lisa");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        // The `/author` counts ignore the selection on `/author`.
+        assert_eq!(
+            counts(&facet_counts, "/author"),
+            vec![
+                ("/author/bob".to_string(), 1),
+                ("/author/frank".to_string(), 1),
+                ("/author/lisa".to_string(), 2),
+                ("/author/susan".to_string(), 1),
+            ]
+        );
+        // The `/year` counts are restricted to `/author/lisa`.
+        assert_eq!(
+            counts(&facet_counts, "/year"),
+            vec![("/year/2010".to_string(), 1), ("/year/2012".to_string(), 1)]
+        );
+
+        // Selecting a year as well restricts the `/author` counts.
+        collector.add_drill_down("/year/2010");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(
+            counts(&facet_counts, "/author"),
+            vec![
+                ("/author/bob".to_string(), 1),
+                ("/author/lisa".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            counts(&facet_counts, "/year"),
+            vec![("/year/2010".to_string(), 1), ("/year/2012".to_string(), 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_sideways_multiple_values_in_dimension() -> crate::Result<()> {
+        let (index, facet_field) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        // Drill downs registered before their dimension are attached to it.
+        collector.add_drill_down("/author/bob");
+        collector.add_drill_down("/author/susan");
+        collector.add_facet("/author");
+        collector.add_facet("/year");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(counts(&facet_counts, "/author").len(), 4);
+        assert_eq!(
+            counts(&facet_counts, "/year"),
+            vec![("/year/2010".to_string(), 1), ("/year/2012".to_string(), 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_sideways_filter_outside_dimensions() -> crate::Result<()> {
+        let (index, facet_field) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        collector.add_facet("/author");
+        collector.add_drill_down("/year/2012");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(
+            counts(&facet_counts, "/author"),
+            vec![
+                ("/author/lisa".to_string(), 1),
+                ("/author/susan".to_string(), 1)
+            ]
+        );
+        // A selection matching no facet filters out all documents.
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        collector.add_facet("/author");
+        collector.add_drill_down("/year/3000");
+        let facet_counts = searcher.search(&AllQuery, &collector)?;
+        assert!(counts(&facet_counts, "/author").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_sideways_with_base_query() -> crate::Result<()> {
+        let (index, facet_field) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |facet: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_facet(facet_field, &Facet::from(facet)),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let base_query = BooleanQuery::new(vec![
+            (Occur::Should, term_query("/year/2010")),
+            (Occur::Should, term_query("/year/2012")),
+        ]);
+        let mut collector = DrillSidewaysCollector::for_field(facet_field);
+        collector.add_facet("/author");
+        collector.add_facet("/year");
+        collector.add_drill_down("/author/susan");
+        let (facet_counts, count) = searcher.search(&base_query, &(collector, Count))?;
+        assert_eq!(count, 4);
+        assert_eq!(
+            counts(&facet_counts, "/author"),
+            vec![
+                ("/author/bob".to_string(), 1),
+                ("/author/lisa".to_string(), 2),
+                ("/author/susan".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            counts(&facet_counts, "/year"),
+            vec![("/year/2012".to_string(), 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to add a facet which is an ancestor or a descendant")]
+    fn test_drill_sideways_nested_dimensions() {
+        let mut collector =
+            DrillSidewaysCollector::for_field(crate::schema::Field::from_field_id(0));
+        collector.add_facet("/author");
+        collector.add_facet("/author/lisa");
+    }
+}
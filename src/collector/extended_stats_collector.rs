@@ -0,0 +1,384 @@
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::collector::{Collector, DistributedCollector, SegmentCollector};
+use crate::schema::{Field, Type};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `ExtendedStatsCollector` computes, in a single pass over the matching documents,
+/// statistics about the values of a single-valued fast field: count, sum, min, max, average,
+/// variance and standard deviation.
+///
+/// Supported field types are u64, i64, and f64.
+///
+/// The variance is computed using Welford's online algorithm, which does not suffer from
+/// the catastrophic cancellation of the naive `E[X^2] - E[X]^2` formula. Partial results
+/// of the different segments are merged using the parallel version of the algorithm.
+///
+/// ```rust
+/// use tantivy::collector::ExtendedStatsCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(price => 2u64))?;
+/// index_writer.add_document(doc!(price => 4u64))?;
+/// index_writer.add_document(doc!(price => 6u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let stats = searcher.search(&AllQuery, &ExtendedStatsCollector::new(price))?;
+///
+/// assert_eq!(stats.count(), 3);
+/// assert_eq!(stats.sum(), 12.0);
+/// assert_eq!(stats.min(), Some(2.0));
+/// assert_eq!(stats.max(), Some(6.0));
+/// assert_eq!(stats.avg(), Some(4.0));
+/// assert_eq!(stats.variance(), Some(8.0 / 3.0));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ExtendedStatsCollector {
+    field: Field,
+}
+
+impl ExtendedStatsCollector {
+    /// Creates a new `ExtendedStatsCollector` computing the statistics of the given fast field.
+    pub fn new(field: Field) -> ExtendedStatsCollector {
+        ExtendedStatsCollector { field }
+    }
+}
+
+/// Statistics computed by the [`ExtendedStatsCollector`].
+///
+/// Variance and standard deviation are the population ones.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtendedStats {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    // Sum of the squared differences to the mean.
+    m2: f64,
+}
+
+impl Default for ExtendedStats {
+    fn default() -> Self {
+        ExtendedStats {
+            count: 0,
+            sum: 0.0,
+            min: f64::MAX,
+            max: f64::MIN,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl ExtendedStats {
+    /// Returns the number of values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of the values.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the minimum value, or `None` if there are no values.
+    pub fn min(&self) -> Option<f64> {
+        self.if_not_empty(self.min)
+    }
+
+    /// Returns the maximum value, or `None` if there are no values.
+    pub fn max(&self) -> Option<f64> {
+        self.if_not_empty(self.max)
+    }
+
+    /// Returns the average of the values, or `None` if there are no values.
+    pub fn avg(&self) -> Option<f64> {
+        self.if_not_empty(self.mean)
+    }
+
+    /// Returns the variance of the values, or `None` if there are no values.
+    pub fn variance(&self) -> Option<f64> {
+        self.if_not_empty(self.m2 / self.count as f64)
+    }
+
+    /// Returns the standard deviation of the values, or `None` if there are no values.
+    pub fn std_deviation(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    fn if_not_empty(&self, val: f64) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(val)
+        }
+    }
+
+    #[inline]
+    fn add_value(&mut self, val: f64) {
+        self.count += 1;
+        self.sum += val;
+        self.min = self.min.min(val);
+        self.max = self.max.max(val);
+        let delta = val - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (val - self.mean);
+    }
+
+    /// Merges the statistics of another set of values into this one.
+    pub fn merge(&mut self, other: &ExtendedStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 +=
+            other.m2 + delta * delta * (self.count as f64 * other.count as f64) / count as f64;
+        self.count = count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    fn merge_all(stats: Vec<ExtendedStats>) -> ExtendedStats {
+        let mut merged = ExtendedStats::default();
+        for stats in &stats {
+            merged.merge(stats);
+        }
+        merged
+    }
+}
+
+impl Collector for ExtendedStatsCollector {
+    type Fruit = ExtendedStats;
+
+    type Child = ExtendedStatsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<ExtendedStatsSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let field_type = field_entry.field_type().value_type();
+        if !matches!(field_type, Type::U64 | Type::I64 | Type::F64) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64 or f64 fast field.",
+                field_entry.name()
+            )));
+        }
+        let fast_fields = segment_reader.fast_fields();
+        // Checks the field is a single-valued fast field.
+        match field_type {
+            Type::I64 => fast_fields.i64(self.field).map(|_| ())?,
+            Type::F64 => fast_fields.f64(self.field).map(|_| ())?,
+            _ => fast_fields.u64(self.field).map(|_| ())?,
+        }
+        Ok(ExtendedStatsSegmentCollector {
+            ff_reader: fast_fields.u64_lenient(self.field)?,
+            field_type,
+            stats: ExtendedStats::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_stats: Vec<ExtendedStats>) -> crate::Result<ExtendedStats> {
+        Ok(ExtendedStats::merge_all(segment_stats))
+    }
+}
+
+impl DistributedCollector for ExtendedStatsCollector {
+    fn merge_distributed_fruits(&self, stats: Vec<ExtendedStats>) -> crate::Result<ExtendedStats> {
+        Ok(ExtendedStats::merge_all(stats))
+    }
+}
+
+/// Segment collector for the [`ExtendedStatsCollector`].
+pub struct ExtendedStatsSegmentCollector {
+    ff_reader: Arc<dyn Column<u64>>,
+    field_type: Type,
+    stats: ExtendedStats,
+}
+
+impl SegmentCollector for ExtendedStatsSegmentCollector {
+    type Fruit = ExtendedStats;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let val = self.ff_reader.get_val(doc as u64);
+        self.stats
+            .add_value(f64_from_fastfield_u64(val, &self.field_type));
+    }
+
+    fn harvest(self) -> ExtendedStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtendedStats, ExtendedStatsCollector};
+    use crate::collector::DistributedCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Term, FAST, INDEXED, STRING};
+    use crate::{assert_nearly_equals, Index};
+
+    fn reference_stats(vals: &[f64]) -> (f64, f64, f64, f64) {
+        let count = vals.len() as f64;
+        let sum: f64 = vals.iter().sum();
+        let avg = sum / count;
+        let variance = vals
+            .iter()
+            .map(|val| (val - avg) * (val - avg))
+            .sum::<f64>()
+            / count;
+        (sum, avg, variance, variance.sqrt())
+    }
+
+    #[test]
+    fn test_extended_stats_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_f64_field("val", FAST);
+        let parity_field = schema_builder.add_text_field("parity", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let vals: Vec<f64> = (0..1_000u64)
+            .map(|i| 1_000_000_000.0 + ((i * 7_919) % 1_000) as f64 / 8.0)
+            .collect();
+        for (i, &val) in vals.iter().enumerate() {
+            let parity = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(val_field => val, parity_field => parity))?;
+            if i % 300 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+
+        let stats = searcher.search(&AllQuery, &ExtendedStatsCollector::new(val_field))?;
+        let (sum, avg, variance, std_deviation) = reference_stats(&vals);
+        assert_eq!(stats.count(), 1_000);
+        assert_nearly_equals!(stats.sum(), sum);
+        assert_eq!(stats.min(), Some(1_000_000_000.0));
+        assert_eq!(stats.max(), Some(1_000_000_000.0 + 999.0 / 8.0));
+        assert_nearly_equals!(stats.avg().unwrap(), avg);
+        assert_nearly_equals!(stats.variance().unwrap(), variance);
+        assert_nearly_equals!(stats.std_deviation().unwrap(), std_deviation);
+
+        let even_query = TermQuery::new(
+            Term::from_field_text(parity_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let even_stats = searcher.search(&even_query, &ExtendedStatsCollector::new(val_field))?;
+        let even_vals: Vec<f64> = vals.iter().cloned().step_by(2).collect();
+        let (sum, avg, variance, _) = reference_stats(&even_vals);
+        assert_eq!(even_stats.count(), 500);
+        assert_nearly_equals!(even_stats.sum(), sum);
+        assert_nearly_equals!(even_stats.avg().unwrap(), avg);
+        assert_nearly_equals!(even_stats.variance().unwrap(), variance);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_stats_collector_i64() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_i64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for val in [-3i64, 5i64, -8i64, 2i64] {
+            index_writer.add_document(doc!(val_field => val))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stats = searcher.search(&AllQuery, &ExtendedStatsCollector::new(val_field))?;
+        let (sum, avg, variance, _) = reference_stats(&[-3.0, 5.0, -8.0, 2.0]);
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.sum(), sum);
+        assert_eq!(stats.min(), Some(-8.0));
+        assert_eq!(stats.max(), Some(5.0));
+        assert_eq!(stats.avg(), Some(avg));
+        assert_nearly_equals!(stats.variance().unwrap(), variance);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_stats_collector_empty() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let stats = searcher.search(&AllQuery, &ExtendedStatsCollector::new(val_field))?;
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.sum(), 0.0);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+        assert_eq!(stats.avg(), None);
+        assert_eq!(stats.variance(), None);
+        assert_eq!(stats.std_deviation(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_stats_collector_not_fast() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val_field = schema_builder.add_u64_field("val", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(val_field => 1u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&AllQuery, &ExtendedStatsCollector::new(val_field))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_stats_merge_distributed_fruits() -> crate::Result<()> {
+        let vals: Vec<f64> = (0..100).map(|i| (i * i) as f64).collect();
+        let mut partial_stats = vec![];
+        for chunk in vals.chunks(30) {
+            let mut stats = ExtendedStats::default();
+            for &val in chunk {
+                stats.add_value(val);
+            }
+            partial_stats.push(stats);
+        }
+        partial_stats.push(ExtendedStats::default());
+        let val_field = Schema::builder().add_u64_field("val", FAST);
+        let stats =
+            ExtendedStatsCollector::new(val_field).merge_distributed_fruits(partial_stats)?;
+        let (sum, avg, variance, _) = reference_stats(&vals);
+        assert_eq!(stats.count(), 100);
+        assert_eq!(stats.sum(), sum);
+        assert_eq!(stats.min(), Some(0.0));
+        assert_eq!(stats.max(), Some(99.0 * 99.0));
+        assert_nearly_equals!(stats.avg().unwrap(), avg);
+        assert_nearly_equals!(stats.variance().unwrap(), variance);
+        Ok(())
+    }
+}
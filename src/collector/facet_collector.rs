@@ -180,6 +180,8 @@ pub struct FacetSegmentCollector {
     counts: Vec<u64>,
     // collapse facet_id -> facet_ord
     collapse_facet_ords: Vec<u64>,
+    // buffer used to resolve facet ordinals
+    facet_bytes_buf: Vec<u8>,
 }
 
 enum SkipResult {
@@ -312,6 +314,7 @@ impl Collector for FacetCollector {
             collapse_mapping,
             counts,
             collapse_facet_ords,
+            facet_bytes_buf: Vec::new(),
         })
     }
 
@@ -320,13 +323,7 @@ impl Collector for FacetCollector {
     }
 
     fn merge_fruits(&self, segments_facet_counts: Vec<FacetCounts>) -> crate::Result<FacetCounts> {
-        let mut facet_counts: BTreeMap<Facet, u64> = BTreeMap::new();
-        for segment_facet_counts in segments_facet_counts {
-            for (facet, count) in segment_facet_counts.facet_counts {
-                *(facet_counts.entry(facet).or_insert(0)) += count;
-            }
-        }
-        Ok(FacetCounts { facet_counts })
+        Ok(FacetCounts::merge(segments_facet_counts))
     }
 }
 
@@ -351,23 +348,68 @@ impl SegmentCollector for FacetSegmentCollector {
     ///
     /// This method does not just return the counters,
     /// it also translates the facet ordinals of the last segment.
-    fn harvest(self) -> FacetCounts {
-        let mut facet_counts = BTreeMap::new();
+    fn harvest(mut self) -> FacetCounts {
+        self.take_counts()
+    }
+}
+
+impl FacetSegmentCollector {
+    /// Returns the counts of the documents collected so far, and resets them, so that
+    /// the collector can be used again to count the facets of other documents.
+    pub(crate) fn take_counts(&mut self) -> FacetCounts {
         let facet_dict = self.reader.facet_dict();
-        for (collapsed_facet_ord, count) in self.counts.iter().cloned().enumerate() {
-            if count == 0 {
-                continue;
+        // The collapsed facet ordinals are sorted by facet ordinals, so that the counted facets
+        // are resolved in the order of the dictionary.
+        let counted_facets: Vec<(u64, u64)> = self
+            .counts
+            .iter_mut()
+            .zip(self.collapse_facet_ords.iter())
+            .filter_map(|(count, &facet_ord)| {
+                let count = std::mem::take(count);
+                (count > 0).then_some((facet_ord, count))
+            })
+            .collect();
+        let mut facet_counts: Vec<(Facet, u64)> = Vec::with_capacity(counted_facets.len());
+        let mut push_facet = |facet_bytes: &[u8], count: u64| {
+            // A single allocation, of the exact size of the facet, is made per counted facet.
+            if let Ok(facet_str) = std::str::from_utf8(facet_bytes) {
+                let facet = Facet::from_encoded_string(facet_str.to_string());
+                facet_counts.push((facet, count));
+            }
+        };
+        // Resolving an ordinal walks the dictionary from its root. When many facets are
+        // counted, streaming the dictionary once is cheaper.
+        let facet_streamer_opt = if counted_facets.len() * 8 >= facet_dict.num_terms() {
+            facet_dict.stream().ok()
+        } else {
+            None
+        };
+        if let Some(mut facet_streamer) = facet_streamer_opt {
+            let mut has_facet = facet_streamer.advance();
+            for (facet_ord, count) in counted_facets {
+                while has_facet && facet_streamer.term_ord() < facet_ord {
+                    has_facet = facet_streamer.advance();
+                }
+                if !has_facet {
+                    break;
+                }
+                push_facet(facet_streamer.key(), count);
             }
-            let mut facet = vec![];
-            let facet_ord = self.collapse_facet_ords[collapsed_facet_ord];
-            // TODO handle errors.
-            if facet_dict.ord_to_term(facet_ord as u64, &mut facet).is_ok() {
-                if let Ok(facet) = Facet::from_encoded(facet) {
-                    facet_counts.insert(facet, count);
+        } else {
+            for (facet_ord, count) in counted_facets {
+                // TODO handle errors.
+                if facet_dict
+                    .ord_to_term(facet_ord, &mut self.facet_bytes_buf)
+                    .is_ok()
+                {
+                    push_facet(&self.facet_bytes_buf, count);
                 }
             }
         }
-        FacetCounts { facet_counts }
+        // The facets are sorted, which makes building the map cheaper.
+        FacetCounts {
+            facet_counts: facet_counts.into_iter().collect(),
+        }
     }
 }
 
@@ -390,6 +432,31 @@ impl<'a> Iterator for FacetChildIterator<'a> {
 }
 
 impl FacetCounts {
+    /// Sums up the counts of several `FacetCounts`.
+    ///
+    /// The counts are merged into the largest of the `FacetCounts`, so that its facets do not
+    /// need to be inserted again.
+    pub(crate) fn merge(all_facet_counts: impl IntoIterator<Item = FacetCounts>) -> FacetCounts {
+        let mut all_facet_counts: Vec<BTreeMap<Facet, u64>> = all_facet_counts
+            .into_iter()
+            .map(|facet_counts| facet_counts.facet_counts)
+            .collect();
+        let largest_ord_opt = all_facet_counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, facet_counts)| facet_counts.len())
+            .map(|(ord, _)| ord);
+        let mut facet_counts: BTreeMap<Facet, u64> = largest_ord_opt
+            .map(|largest_ord| all_facet_counts.swap_remove(largest_ord))
+            .unwrap_or_default();
+        for other_facet_counts in all_facet_counts {
+            for (facet, count) in other_facet_counts {
+                *(facet_counts.entry(facet).or_insert(0)) += count;
+            }
+        }
+        FacetCounts { facet_counts }
+    }
+
     /// Returns an iterator over all of the facet count pairs inside this result.
     /// See the documentation for [FacetCollector] for a usage example.
     pub fn get<T>(&self, facet_from: T) -> FacetChildIterator<'_>
@@ -445,6 +512,7 @@ impl FacetCounts {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::iter;
 
     use rand::distributions::Uniform;
@@ -685,6 +753,38 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_facet_collector_high_cardinality_multi_segment() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut expected_counts: BTreeMap<Facet, u64> = BTreeMap::new();
+        // The segments have overlapping facets, and different numbers of facets.
+        for (num_docs, modulo) in [(3_000, 1_000), (2_500, 2_000), (2_000, 300)] {
+            for doc_id in 0..num_docs {
+                let facet = Facet::from(&format!("/cat/{}", doc_id % modulo));
+                *expected_counts.entry(facet.clone()).or_insert(0) += 1;
+                index_writer.add_document(
+                    doc!(facet_field => facet, facet_field => Facet::from("/other")),
+                )?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let mut facet_collector = FacetCollector::for_field(facet_field);
+        facet_collector.add_facet("/cat");
+        let counts: FacetCounts = searcher.search(&AllQuery, &facet_collector)?;
+        let facet_counts: BTreeMap<Facet, u64> = counts
+            .get("/cat")
+            .map(|(facet, count)| (facet.clone(), count))
+            .collect();
+        assert_eq!(facet_counts.len(), 2_000);
+        assert_eq!(facet_counts, expected_counts);
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
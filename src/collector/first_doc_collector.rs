@@ -0,0 +1,335 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{type_and_cardinality, FastValue};
+use crate::query::Weight;
+use crate::schema::{Cardinality, Field};
+use crate::{
+    DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError, TERMINATED,
+};
+
+/// The `FirstDocByFieldCollector` returns the matching document with the greatest value of a
+/// single-valued fast field, e.g. the latest record by timestamp, or with the lowest value if
+/// the order is ascending.
+///
+/// It returns the same document as `TopDocs::with_limit(1).order_by_fast_field(field)` for the
+/// descending order, but only keeps track of a single document, rather than of a heap. In case
+/// of a tie, the document with the lowest [`DocAddress`] is returned.
+///
+/// If the index is sorted by the field, in the same order (see
+/// [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field)), the collection of a
+/// segment stops at its first matching document.
+///
+/// ```rust
+/// use tantivy::collector::FirstDocByFieldCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, Index, Order};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "first", timestamp => 1_000i64))?;
+/// index_writer.add_document(doc!(title => "latest", timestamp => 3_000i64))?;
+/// index_writer.add_document(doc!(title => "second", timestamp => 2_000i64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let latest = searcher.search(
+///     &AllQuery,
+///     &FirstDocByFieldCollector::<i64>::new(timestamp, Order::Desc),
+/// )?;
+/// assert_eq!(latest, Some((3_000, DocAddress::new(0, 1))));
+/// let earliest = searcher.search(
+///     &AllQuery,
+///     &FirstDocByFieldCollector::<i64>::new(timestamp, Order::Asc),
+/// )?;
+/// assert_eq!(earliest, Some((1_000, DocAddress::new(0, 0))));
+/// # Ok(())
+/// # }
+/// ```
+pub struct FirstDocByFieldCollector<TFastValue> {
+    field: Field,
+    order: Order,
+    fast_value: PhantomData<TFastValue>,
+}
+
+impl<TFastValue: FastValue> FirstDocByFieldCollector<TFastValue> {
+    /// Creates a new `FirstDocByFieldCollector`, returning the document with the greatest value
+    /// of `field` if `order` is descending, and with the lowest value if it is ascending.
+    ///
+    /// The type of the field needs to match `TFastValue`. Otherwise, an error is returned at
+    /// the moment of search.
+    pub fn new(field: Field, order: Order) -> FirstDocByFieldCollector<TFastValue> {
+        FirstDocByFieldCollector {
+            field,
+            order,
+            fast_value: PhantomData,
+        }
+    }
+
+    fn is_sorted_by_field(&self, segment_reader: &SegmentReader) -> bool {
+        segment_reader
+            .sort_by_field()
+            .map(|sort_by_field| {
+                sort_by_field.order == self.order
+                    && segment_reader.schema().get_field(&sort_by_field.field) == Some(self.field)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Returns true if `val` ranks before `best_val` in the given order.
+fn ranks_before(val: u64, best_val: u64, order: &Order) -> bool {
+    if order.is_desc() {
+        val > best_val
+    } else {
+        val < best_val
+    }
+}
+
+impl<TFastValue: FastValue> Collector for FirstDocByFieldCollector<TFastValue> {
+    type Fruit = Option<(TFastValue, DocAddress)>;
+
+    type Child = FirstDocByFieldSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<FirstDocByFieldSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let field_type = field_entry.field_type();
+        if type_and_cardinality(field_type).map(|(_, cardinality)| cardinality)
+            != Some(Cardinality::SingleValue)
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a single-valued fast field.",
+                field_entry.name()
+            )));
+        }
+        if field_type.value_type() != TFastValue::to_type() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is of type {:?}!={:?}",
+                field_entry.name(),
+                field_type.value_type(),
+                TFastValue::to_type()
+            )));
+        }
+        Ok(FirstDocByFieldSegmentCollector {
+            ff_reader: segment_reader.fast_fields().u64_lenient(self.field)?,
+            order: self.order.clone(),
+            segment_ord: segment_local_id,
+            best_doc: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Option<(u64, DocAddress)>>,
+    ) -> crate::Result<Option<(TFastValue, DocAddress)>> {
+        let mut best_doc: Option<(u64, DocAddress)> = None;
+        // The fruits are in the order of the segments: on a tie, the first one is kept.
+        for (val, doc_address) in segment_fruits.into_iter().flatten() {
+            if best_doc
+                .map(|(best_val, _)| ranks_before(val, best_val, &self.order))
+                .unwrap_or(true)
+            {
+                best_doc = Some((val, doc_address));
+            }
+        }
+        Ok(best_doc.map(|(val, doc_address)| (TFastValue::from_u64(val), doc_address)))
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<Option<(u64, DocAddress)>> {
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        let alive_bitset_opt = reader.alive_bitset();
+        if self.is_sorted_by_field(reader) {
+            // The documents are visited in the order of the field: the first alive document is
+            // the one to return for the segment.
+            let mut scorer = weight.scorer(reader, 1.0)?;
+            let mut doc = scorer.doc();
+            while doc != TERMINATED {
+                let is_alive = alive_bitset_opt
+                    .map(|alive_bitset| alive_bitset.is_alive(doc))
+                    .unwrap_or(true);
+                if is_alive {
+                    segment_collector.collect(doc, 0.0);
+                    break;
+                }
+                doc = scorer.advance();
+            }
+        } else if let Some(alive_bitset) = alive_bitset_opt {
+            weight.for_each(reader, &mut |doc, _score| {
+                if alive_bitset.is_alive(doc) {
+                    segment_collector.collect(doc, 0.0);
+                }
+            })?;
+        } else {
+            weight.for_each(reader, &mut |doc, _score| {
+                segment_collector.collect(doc, 0.0);
+            })?;
+        }
+        Ok(segment_collector.harvest())
+    }
+}
+
+/// Segment collector associated to the [`FirstDocByFieldCollector`].
+pub struct FirstDocByFieldSegmentCollector {
+    ff_reader: Arc<dyn Column<u64>>,
+    order: Order,
+    segment_ord: SegmentOrdinal,
+    best_doc: Option<(u64, DocId)>,
+}
+
+impl SegmentCollector for FirstDocByFieldSegmentCollector {
+    type Fruit = Option<(u64, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let val = self.ff_reader.get_val(doc as u64);
+        // The documents are collected by increasing doc id: on a tie, the first one is kept.
+        if self
+            .best_doc
+            .map(|(best_val, _)| ranks_before(val, best_val, &self.order))
+            .unwrap_or(true)
+        {
+            self.best_doc = Some((val, doc));
+        }
+    }
+
+    fn harvest(self) -> Option<(u64, DocAddress)> {
+        let segment_ord = self.segment_ord;
+        self.best_doc
+            .map(|(val, doc_id)| (val, DocAddress::new(segment_ord, doc_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FirstDocByFieldCollector;
+    use crate::collector::{DocSetCollector, TopDocs};
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{DocAddress, Index, IndexSettings, IndexSortByField, Order, Term};
+
+    #[test]
+    fn test_first_doc_by_field_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST | INDEXED);
+        let schema = schema_builder.build();
+        for sort_order_opt in [None, Some(Order::Desc), Some(Order::Asc)] {
+            let settings = IndexSettings {
+                sort_by_field: sort_order_opt.clone().map(|order| IndexSortByField {
+                    field: "timestamp".to_string(),
+                    order,
+                }),
+                ..Default::default()
+            };
+            let index = Index::builder()
+                .schema(schema.clone())
+                .settings(settings)
+                .create_in_ram()?;
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..100i64 {
+                let tag_value = if i % 3 == 0 { "a" } else { "b" };
+                // Many ties, within and across the segments.
+                let timestamp_value = (i * 37) % 50 - 20;
+                index_writer.add_document(doc!(tag => tag_value, timestamp => timestamp_value))?;
+                // Spread the documents over several segments.
+                if i % 30 == 29 {
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.commit()?;
+            // Deletes some of the documents with the greatest timestamp.
+            index_writer.delete_term(Term::from_field_i64(timestamp, 29));
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), 4);
+            let tag_query =
+                TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+            for query in [&AllQuery as &dyn Query, &tag_query] {
+                let top_docs = searcher.search(
+                    query,
+                    &TopDocs::with_limit(1).order_by_fast_field::<i64>(timestamp),
+                )?;
+                let latest = searcher.search(
+                    query,
+                    &FirstDocByFieldCollector::<i64>::new(timestamp, Order::Desc),
+                )?;
+                assert_eq!(latest, top_docs.first().cloned(), "{:?}", sort_order_opt);
+                assert_eq!(latest.map(|(val, _)| val), Some(28));
+
+                let earliest = searcher.search(
+                    query,
+                    &FirstDocByFieldCollector::<i64>::new(timestamp, Order::Asc),
+                )?;
+                // Brute force lookup of the first document with the lowest timestamp.
+                let matching_docs = searcher.search(query, &DocSetCollector)?;
+                let mut expected_earliest: Option<(i64, DocAddress)> = None;
+                for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                    let ff_reader = segment_reader.fast_fields().i64(timestamp)?;
+                    for doc in 0..segment_reader.max_doc() {
+                        let doc_address = DocAddress::new(segment_ord as u32, doc);
+                        if !matching_docs.contains(&doc_address) {
+                            continue;
+                        }
+                        let val = ff_reader.get_val(doc as u64);
+                        if expected_earliest
+                            .map(|(best_val, _)| val < best_val)
+                            .unwrap_or(true)
+                        {
+                            expected_earliest = Some((val, doc_address));
+                        }
+                    }
+                }
+                assert_eq!(earliest, expected_earliest, "{:?}", sort_order_opt);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_doc_by_field_collector_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a", timestamp => 1i64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        // The field is not a fast field.
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &FirstDocByFieldCollector::<u64>::new(tag, Order::Desc)
+            )
+            .is_err());
+        // The type of the field does not match.
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &FirstDocByFieldCollector::<u64>::new(timestamp, Order::Desc)
+            )
+            .is_err());
+        Ok(())
+    }
+}
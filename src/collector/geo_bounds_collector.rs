@@ -0,0 +1,388 @@
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+use serde::{Deserialize, Serialize};
+
+use crate::collector::{Collector, DistributedCollector, SegmentCollector};
+use crate::schema::{Field, Type};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `GeoBoundsCollector` computes the bounding box of the locations of the matching
+/// documents, e.g. to zoom a map on the results of a query.
+///
+/// The latitude and the longitude of the documents are read from two single-valued f64 fast
+/// fields, in degrees. As single-valued fast fields do not distinguish missing values, all of
+/// the matching documents are expected to have a location.
+///
+/// When there are locations in both the eastern and the western hemispheres, the bounding box is
+/// the narrowest of the box that does not cross the antimeridian, and of the box that crosses
+/// it. In the latter case, the left longitude of the box is greater than its right one.
+///
+/// ```rust
+/// use tantivy::collector::GeoBoundsCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let lat = schema_builder.add_f64_field("lat", FAST);
+/// let lon = schema_builder.add_f64_field("lon", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// // Fiji and Samoa.
+/// index_writer.add_document(doc!(lat => -18.1, lon => 178.4))?;
+/// index_writer.add_document(doc!(lat => -13.8, lon => -171.8))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let geo_bounds = searcher.search(&AllQuery, &GeoBoundsCollector::new(lat, lon))?;
+/// let bounding_box = geo_bounds.bounding_box().unwrap();
+/// assert_eq!(bounding_box.top, -13.8);
+/// assert_eq!(bounding_box.bottom, -18.1);
+/// assert_eq!(bounding_box.left, 178.4);
+/// assert_eq!(bounding_box.right, -171.8);
+/// assert!(bounding_box.crosses_antimeridian());
+/// # Ok(())
+/// # }
+/// ```
+pub struct GeoBoundsCollector {
+    lat_field: Field,
+    lon_field: Field,
+}
+
+impl GeoBoundsCollector {
+    /// Creates a new `GeoBoundsCollector` reading the latitudes and the longitudes of the
+    /// documents from the given fast fields.
+    pub fn new(lat_field: Field, lon_field: Field) -> GeoBoundsCollector {
+        GeoBoundsCollector {
+            lat_field,
+            lon_field,
+        }
+    }
+}
+
+/// A bounding box, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBoundingBox {
+    /// Maximum latitude.
+    pub top: f64,
+    /// Minimum latitude.
+    pub bottom: f64,
+    /// Western longitude of the box.
+    pub left: f64,
+    /// Eastern longitude of the box.
+    pub right: f64,
+}
+
+impl GeoBoundingBox {
+    /// Returns true if the box crosses the antimeridian, i.e. spans from its `left` longitude
+    /// to 180, and from -180 to its `right` longitude.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.left > self.right
+    }
+}
+
+/// The bounds of the locations collected by the [`GeoBoundsCollector`].
+///
+/// The longitudes are tracked separately for the eastern and the western hemispheres, so that
+/// the bounding box can cross the antimeridian.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBounds {
+    count: u64,
+    top: f64,
+    bottom: f64,
+    pos_left: f64,
+    pos_right: f64,
+    neg_left: f64,
+    neg_right: f64,
+}
+
+impl Default for GeoBounds {
+    fn default() -> Self {
+        GeoBounds {
+            count: 0,
+            top: f64::MIN,
+            bottom: f64::MAX,
+            pos_left: f64::MAX,
+            pos_right: f64::MIN,
+            neg_left: f64::MAX,
+            neg_right: f64::MIN,
+        }
+    }
+}
+
+impl GeoBounds {
+    /// Returns the number of locations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the bounding box of the locations, or `None` if there are no locations.
+    pub fn bounding_box(&self) -> Option<GeoBoundingBox> {
+        if self.count == 0 {
+            return None;
+        }
+        let has_pos = self.pos_left <= self.pos_right;
+        let has_neg = self.neg_left <= self.neg_right;
+        let (left, right) = if !has_neg {
+            (self.pos_left, self.pos_right)
+        } else if !has_pos {
+            (self.neg_left, self.neg_right)
+        } else {
+            let unwrapped_width = self.pos_right - self.neg_left;
+            let wrapped_width = (180.0 - self.pos_left) + (self.neg_right + 180.0);
+            if unwrapped_width <= wrapped_width {
+                (self.neg_left, self.pos_right)
+            } else {
+                (self.pos_left, self.neg_right)
+            }
+        };
+        Some(GeoBoundingBox {
+            top: self.top,
+            bottom: self.bottom,
+            left,
+            right,
+        })
+    }
+
+    #[inline]
+    fn add_location(&mut self, lat: f64, lon: f64) {
+        self.count += 1;
+        self.top = self.top.max(lat);
+        self.bottom = self.bottom.min(lat);
+        if lon >= 0.0 {
+            self.pos_left = self.pos_left.min(lon);
+            self.pos_right = self.pos_right.max(lon);
+        } else {
+            self.neg_left = self.neg_left.min(lon);
+            self.neg_right = self.neg_right.max(lon);
+        }
+    }
+
+    /// Merges the bounds of another set of locations into this one.
+    pub fn merge(&mut self, other: &GeoBounds) {
+        self.count += other.count;
+        self.top = self.top.max(other.top);
+        self.bottom = self.bottom.min(other.bottom);
+        self.pos_left = self.pos_left.min(other.pos_left);
+        self.pos_right = self.pos_right.max(other.pos_right);
+        self.neg_left = self.neg_left.min(other.neg_left);
+        self.neg_right = self.neg_right.max(other.neg_right);
+    }
+
+    fn merge_all(bounds: Vec<GeoBounds>) -> GeoBounds {
+        let mut merged = GeoBounds::default();
+        for bounds in &bounds {
+            merged.merge(bounds);
+        }
+        merged
+    }
+}
+
+fn f64_column(segment_reader: &SegmentReader, field: Field) -> crate::Result<Arc<dyn Column<f64>>> {
+    let field_entry = segment_reader.schema().get_field_entry(field);
+    if field_entry.field_type().value_type() != Type::F64 {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a f64 fast field.",
+            field_entry.name()
+        )));
+    }
+    segment_reader.fast_fields().f64(field)
+}
+
+impl Collector for GeoBoundsCollector {
+    type Fruit = GeoBounds;
+
+    type Child = GeoBoundsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<GeoBoundsSegmentCollector> {
+        Ok(GeoBoundsSegmentCollector {
+            lat_reader: f64_column(segment_reader, self.lat_field)?,
+            lon_reader: f64_column(segment_reader, self.lon_field)?,
+            bounds: GeoBounds::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_bounds: Vec<GeoBounds>) -> crate::Result<GeoBounds> {
+        Ok(GeoBounds::merge_all(segment_bounds))
+    }
+}
+
+impl DistributedCollector for GeoBoundsCollector {
+    fn merge_distributed_fruits(&self, bounds: Vec<GeoBounds>) -> crate::Result<GeoBounds> {
+        Ok(GeoBounds::merge_all(bounds))
+    }
+}
+
+/// Segment collector for the [`GeoBoundsCollector`].
+pub struct GeoBoundsSegmentCollector {
+    lat_reader: Arc<dyn Column<f64>>,
+    lon_reader: Arc<dyn Column<f64>>,
+    bounds: GeoBounds,
+}
+
+impl SegmentCollector for GeoBoundsSegmentCollector {
+    type Fruit = GeoBounds;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let lat = self.lat_reader.get_val(doc as u64);
+        let lon = self.lon_reader.get_val(doc as u64);
+        self.bounds.add_location(lat, lon);
+    }
+
+    fn harvest(self) -> GeoBounds {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeoBoundingBox, GeoBounds, GeoBoundsCollector};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Term, FAST, INDEXED, STRING};
+    use crate::Index;
+
+    #[test]
+    fn test_geo_bounds_collector_antimeridian() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let lat = schema_builder.add_f64_field("lat", FAST);
+        let lon = schema_builder.add_f64_field("lon", FAST);
+        let region = schema_builder.add_text_field("region", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let locations = [
+            ("pacific", -18.0, 178.0),
+            ("pacific", 52.0, -176.0),
+            ("pacific", -14.0, -171.0),
+            ("pacific", 7.0, 171.0),
+            ("europe", 48.8, 2.3),
+            ("europe", 40.4, -3.7),
+        ];
+        for (i, (region_value, lat_value, lon_value)) in locations.iter().enumerate() {
+            index_writer.add_document(doc!(
+                region => *region_value,
+                lat => *lat_value,
+                lon => *lon_value
+            ))?;
+            // Spread the documents over several segments.
+            if i % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let region_query = |region_value: &str| {
+            TermQuery::new(
+                Term::from_field_text(region, region_value),
+                IndexRecordOption::Basic,
+            )
+        };
+
+        // The box of the Pacific locations spans over the dateline.
+        let geo_bounds =
+            searcher.search(&region_query("pacific"), &GeoBoundsCollector::new(lat, lon))?;
+        assert_eq!(geo_bounds.count(), 4);
+        let bounding_box = geo_bounds.bounding_box().unwrap();
+        assert_eq!(
+            bounding_box,
+            GeoBoundingBox {
+                top: 52.0,
+                bottom: -18.0,
+                left: 171.0,
+                right: -171.0,
+            }
+        );
+        assert!(bounding_box.crosses_antimeridian());
+
+        // The box of the European locations spans over the prime meridian.
+        let geo_bounds =
+            searcher.search(&region_query("europe"), &GeoBoundsCollector::new(lat, lon))?;
+        let bounding_box = geo_bounds.bounding_box().unwrap();
+        assert_eq!(
+            bounding_box,
+            GeoBoundingBox {
+                top: 48.8,
+                bottom: 40.4,
+                left: -3.7,
+                right: 2.3,
+            }
+        );
+        assert!(!bounding_box.crosses_antimeridian());
+
+        let geo_bounds = searcher.search(&AllQuery, &GeoBoundsCollector::new(lat, lon))?;
+        assert_eq!(geo_bounds.count(), 6);
+
+        let geo_bounds =
+            searcher.search(&region_query("africa"), &GeoBoundsCollector::new(lat, lon))?;
+        assert_eq!(geo_bounds, GeoBounds::default());
+        assert_eq!(geo_bounds.bounding_box(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_bounds_narrowest_box() {
+        let bounds = |locations: &[(f64, f64)]| {
+            let mut bounds = GeoBounds::default();
+            for &(lat, lon) in locations {
+                bounds.add_location(lat, lon);
+            }
+            let bounding_box = bounds.bounding_box().unwrap();
+            (bounding_box.left, bounding_box.right)
+        };
+        assert_eq!(bounds(&[(0.0, 10.0), (0.0, 178.0)]), (10.0, 178.0));
+        assert_eq!(
+            bounds(&[(0.0, -100.0), (0.0, 10.0), (0.0, 20.0)]),
+            (-100.0, 20.0)
+        );
+        assert_eq!(
+            bounds(&[(0.0, 10.0), (0.0, 178.0), (0.0, -176.0), (0.0, -171.0)]),
+            (10.0, -171.0)
+        );
+        // Merging the bounds of several sets of locations.
+        let mut east_bounds = GeoBounds::default();
+        east_bounds.add_location(-1.0, 170.0);
+        let mut west_bounds = GeoBounds::default();
+        west_bounds.add_location(2.0, -170.0);
+        east_bounds.merge(&west_bounds);
+        assert_eq!(
+            east_bounds.bounding_box(),
+            Some(GeoBoundingBox {
+                top: 2.0,
+                bottom: -1.0,
+                left: 170.0,
+                right: -170.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_geo_bounds_collector_wrong_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let lat = schema_builder.add_f64_field("lat", FAST);
+        let lon_u64 = schema_builder.add_u64_field("lon_u64", FAST);
+        let lon_not_fast = schema_builder.add_f64_field("lon_not_fast", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(lat => 1.0, lon_u64 => 1u64, lon_not_fast => 1.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&AllQuery, &GeoBoundsCollector::new(lat, lon_u64))
+            .is_err());
+        assert!(searcher
+            .search(&AllQuery, &GeoBoundsCollector::new(lat, lon_not_fast))
+            .is_err());
+        Ok(())
+    }
+}
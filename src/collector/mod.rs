@@ -77,6 +77,15 @@
 //! you can rely on `MultiCollector`'s.
 //!
 //!
+//! # Distributed search
+//!
+//! When an index is sharded across several nodes, each node can run the collector
+//! on its own searcher, ship its fruit to a coordinator, and the coordinator merges them with
+//! [`DistributedCollector::merge_distributed_fruits`].
+//! The fruits of [`Count`], [`TopDocs`] and
+//! [`DistributedAggregationCollector`](crate::aggregation::DistributedAggregationCollector)
+//! are serializable with serde for that purpose.
+//!
 //! # Implementing your own collectors.
 //!
 //! See the `custom_collector` example.
@@ -91,6 +100,36 @@ pub use self::count_collector::Count;
 mod histogram_collector;
 pub use histogram_collector::HistogramCollector;
 
+mod date_histogram_top_docs_collector;
+pub use self::date_histogram_top_docs_collector::{
+    DateHistogramTopDocsCollector, DateHistogramTopDocsSegmentCollector,
+};
+
+mod extended_stats_collector;
+pub use self::extended_stats_collector::{ExtendedStats, ExtendedStatsCollector};
+
+mod score_weighted_average_collector;
+pub use self::score_weighted_average_collector::{
+    ScoreWeightedAverage, ScoreWeightedAverageCollector, ScoreWeightedAverageSegmentCollector,
+};
+
+mod geo_bounds_collector;
+pub use self::geo_bounds_collector::{
+    GeoBoundingBox, GeoBounds, GeoBoundsCollector, GeoBoundsSegmentCollector,
+};
+
+mod first_doc_collector;
+pub use self::first_doc_collector::{FirstDocByFieldCollector, FirstDocByFieldSegmentCollector};
+
+mod sample_collector;
+pub use self::sample_collector::SampleCollector;
+
+mod collapse_collector;
+pub use self::collapse_collector::{CollapseCollector, CollapseSegmentCollector, SegmentGroups};
+
+mod timeout_collector;
+pub use self::timeout_collector::{TimeoutCollector, TimeoutFruit};
+
 mod multi_collector;
 pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
@@ -102,19 +141,44 @@ pub use self::top_score_collector::TopDocs;
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
+mod tie_break_top_collector;
+
 mod tweak_score_top_collector;
 pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
 
+mod rerank;
+pub use self::rerank::rerank;
+
 mod facet_collector;
 pub use self::facet_collector::{FacetCollector, FacetCounts};
 use crate::query::Weight;
 
+mod drill_sideways_collector;
+pub use self::drill_sideways_collector::DrillSidewaysCollector;
+
+mod top_docs_facet_collector;
+pub use self::top_docs_facet_collector::{TopDocsFacetCollector, TopDocsFacetSegmentCollector};
+
 mod docset_collector;
 pub use self::docset_collector::DocSetCollector;
 
+mod doc_bitmap_collector;
+pub use self::doc_bitmap_collector::{
+    DocBitmap, DocBitmapCollector, DocBitmapSegmentCollector, DocBitmaps,
+};
+
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::FilterCollector;
 
+mod normalize_score_collector;
+pub use self::normalize_score_collector::{NormalizeScoreCollector, NormalizedScore};
+
+mod score_histogram_collector;
+pub use self::score_histogram_collector::{ScoreHistogram, ScoreHistogramCollector};
+
+mod top_terms_collector;
+pub use self::top_terms_collector::{TopTermsCollector, TopTermsSegmentCollector};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
@@ -242,6 +306,27 @@ impl<TCollector: Collector> Collector for Option<TCollector> {
     }
 }
 
+/// Collectors whose fruits, computed independently on different searchers,
+/// can be merged into one global fruit.
+///
+/// This is typically used in a distributed setup: every node runs the collector on its
+/// own searcher, serializes its fruit and sends it to a coordinator, which merges all of
+/// the partial fruits.
+///
+/// It is implemented by [`Count`], [`TopDocs`], [`ExtendedStatsCollector`],
+/// [`DistributedAggregationCollector`](crate::aggregation::DistributedAggregationCollector),
+/// and tuples of such collectors.
+///
+/// Note that the [`DocAddress`](crate::DocAddress) returned by a node refers to the segments of
+/// that node's searcher. The coordinator needs to keep track of which node each document
+/// comes from to fetch it. Also, [`TopDocs::and_offset`] is applied on every node as
+/// well as when merging: nodes should rather run `TopDocs::with_limit(limit + offset)`, and
+/// the coordinator merge with the offset collector.
+pub trait DistributedCollector: Collector {
+    /// Merges the fruits returned by different searchers into one fruit.
+    fn merge_distributed_fruits(&self, fruits: Vec<Self::Fruit>) -> crate::Result<Self::Fruit>;
+}
+
 /// The `SegmentCollector` is the trait in charge of defining the
 /// collect operation at the scale of the segment.
 ///
@@ -318,6 +403,25 @@ where
     }
 }
 
+impl<Left, Right> DistributedCollector for (Left, Right)
+where
+    Left: DistributedCollector,
+    Right: DistributedCollector,
+{
+    fn merge_distributed_fruits(&self, fruits: Vec<Self::Fruit>) -> crate::Result<Self::Fruit> {
+        let mut left_fruits = vec![];
+        let mut right_fruits = vec![];
+        for (left_fruit, right_fruit) in fruits {
+            left_fruits.push(left_fruit);
+            right_fruits.push(right_fruit);
+        }
+        Ok((
+            self.0.merge_distributed_fruits(left_fruits)?,
+            self.1.merge_distributed_fruits(right_fruits)?,
+        ))
+    }
+}
+
 // 3-Tuple
 
 impl<One, Two, Three> Collector for (One, Two, Three)
@@ -383,6 +487,29 @@ where
     }
 }
 
+impl<One, Two, Three> DistributedCollector for (One, Two, Three)
+where
+    One: DistributedCollector,
+    Two: DistributedCollector,
+    Three: DistributedCollector,
+{
+    fn merge_distributed_fruits(&self, fruits: Vec<Self::Fruit>) -> crate::Result<Self::Fruit> {
+        let mut one_fruits = vec![];
+        let mut two_fruits = vec![];
+        let mut three_fruits = vec![];
+        for (one_fruit, two_fruit, three_fruit) in fruits {
+            one_fruits.push(one_fruit);
+            two_fruits.push(two_fruit);
+            three_fruits.push(three_fruit);
+        }
+        Ok((
+            self.0.merge_distributed_fruits(one_fruits)?,
+            self.1.merge_distributed_fruits(two_fruits)?,
+            self.2.merge_distributed_fruits(three_fruits)?,
+        ))
+    }
+}
+
 // 4-Tuple
 
 impl<One, Two, Three, Four> Collector for (One, Two, Three, Four)
@@ -463,6 +590,33 @@ where
     }
 }
 
+impl<One, Two, Three, Four> DistributedCollector for (One, Two, Three, Four)
+where
+    One: DistributedCollector,
+    Two: DistributedCollector,
+    Three: DistributedCollector,
+    Four: DistributedCollector,
+{
+    fn merge_distributed_fruits(&self, fruits: Vec<Self::Fruit>) -> crate::Result<Self::Fruit> {
+        let mut one_fruits = vec![];
+        let mut two_fruits = vec![];
+        let mut three_fruits = vec![];
+        let mut four_fruits = vec![];
+        for (one_fruit, two_fruit, three_fruit, four_fruit) in fruits {
+            one_fruits.push(one_fruit);
+            two_fruits.push(two_fruit);
+            three_fruits.push(three_fruit);
+            four_fruits.push(four_fruit);
+        }
+        Ok((
+            self.0.merge_distributed_fruits(one_fruits)?,
+            self.1.merge_distributed_fruits(two_fruits)?,
+            self.2.merge_distributed_fruits(three_fruits)?,
+            self.3.merge_distributed_fruits(four_fruits)?,
+        ))
+    }
+}
+
 impl_downcast!(Fruit);
 
 #[cfg(test)]
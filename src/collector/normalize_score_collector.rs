@@ -0,0 +1,208 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocAddress, Score, SegmentOrdinal, SegmentReader};
+
+/// A score, along with its normalized value within `[0, 1]`.
+///
+/// See [`NormalizeScoreCollector`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizedScore {
+    /// The score, as returned by the wrapped collector.
+    pub raw: Score,
+    /// The score, normalized within `[0, 1]`.
+    pub normalized: Score,
+}
+
+/// The `NormalizeScoreCollector` wraps a top-K collector (typically [`TopDocs`]) and
+/// normalizes the scores it returns within `[0, 1]`.
+///
+/// By default, scores are divided by the maximum score of the result set, so that the best
+/// document gets a normalized score of `1`. Alternatively, the maximum score can be provided
+/// by the user with [`NormalizeScoreCollector::with_max_score`]. In that case, normalized
+/// scores greater than `1` are capped to `1`.
+///
+/// Negative scores are normalized to `0`. If the maximum score is not strictly positive,
+/// all normalized scores are `0`.
+///
+/// [`TopDocs`]: crate::collector::TopDocs
+///
+/// ```rust
+/// use tantivy::collector::{NormalizeScoreCollector, TopDocs};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let query = query_parser.parse_query("diary")?;
+/// let top_docs = searcher.search(
+///     &query,
+///     &NormalizeScoreCollector::new(TopDocs::with_limit(2)),
+/// )?;
+///
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].0.normalized, 1.0);
+/// assert!(top_docs[1].0.normalized < 1.0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct NormalizeScoreCollector<TCollector> {
+    collector: TCollector,
+    max_score: Option<Score>,
+}
+
+impl<TCollector> NormalizeScoreCollector<TCollector>
+where TCollector: Collector<Fruit = Vec<(Score, DocAddress)>>
+{
+    /// Creates a new `NormalizeScoreCollector`, normalizing scores by the maximum score in the
+    /// result set.
+    pub fn new(collector: TCollector) -> NormalizeScoreCollector<TCollector> {
+        NormalizeScoreCollector {
+            collector,
+            max_score: None,
+        }
+    }
+
+    /// Creates a new `NormalizeScoreCollector`, normalizing scores by the given maximum score.
+    pub fn with_max_score(
+        collector: TCollector,
+        max_score: Score,
+    ) -> NormalizeScoreCollector<TCollector> {
+        NormalizeScoreCollector {
+            collector,
+            max_score: Some(max_score),
+        }
+    }
+}
+
+fn normalize_scores(
+    scored_docs: Vec<(Score, DocAddress)>,
+    max_score_opt: Option<Score>,
+) -> Vec<(NormalizedScore, DocAddress)> {
+    let max_score = max_score_opt.unwrap_or_else(|| {
+        scored_docs
+            .iter()
+            .map(|(score, _)| *score)
+            .fold(Score::NEG_INFINITY, Score::max)
+    });
+    scored_docs
+        .into_iter()
+        .map(|(raw, doc_address)| {
+            let normalized = if max_score > 0.0 {
+                (raw / max_score).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (NormalizedScore { raw, normalized }, doc_address)
+        })
+        .collect()
+}
+
+impl<TCollector> Collector for NormalizeScoreCollector<TCollector>
+where TCollector: Collector<Fruit = Vec<(Score, DocAddress)>>
+{
+    type Fruit = Vec<(NormalizedScore, DocAddress)>;
+
+    type Child = TCollector::Child;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let scored_docs = self.collector.merge_fruits(segment_fruits)?;
+        Ok(normalize_scores(scored_docs, self.max_score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_scores, NormalizeScoreCollector, NormalizedScore};
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::Schema;
+    use crate::{DocAddress, Document, Index};
+
+    fn normalized(scored_docs: &[(f32, u32)], max_score_opt: Option<f32>) -> Vec<(f32, f32)> {
+        let scored_docs = scored_docs
+            .iter()
+            .map(|&(score, doc)| (score, DocAddress::new(0, doc)))
+            .collect();
+        normalize_scores(scored_docs, max_score_opt)
+            .into_iter()
+            .map(|(NormalizedScore { raw, normalized }, _)| (raw, normalized))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_scores_by_max_in_result_set() {
+        assert_eq!(
+            normalized(&[(4.0, 0), (2.0, 1), (1.0, 2)], None),
+            vec![(4.0, 1.0), (2.0, 0.5), (1.0, 0.25)]
+        );
+    }
+
+    #[test]
+    fn test_normalize_scores_by_provided_max() {
+        assert_eq!(
+            normalized(&[(4.0, 0), (2.0, 1), (1.0, 2)], Some(8.0)),
+            vec![(4.0, 0.5), (2.0, 0.25), (1.0, 0.125)]
+        );
+        // Scores above the provided max are capped.
+        assert_eq!(
+            normalized(&[(4.0, 0), (2.0, 1)], Some(2.0)),
+            vec![(4.0, 1.0), (2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_normalize_scores_edge_cases() {
+        assert!(normalized(&[], None).is_empty());
+        assert!(normalized(&[], Some(1.0)).is_empty());
+        assert_eq!(normalized(&[(0.3, 0)], None), vec![(0.3, 1.0)]);
+        assert_eq!(
+            normalized(&[(0.0, 0), (0.0, 1)], None),
+            vec![(0.0, 0.0), (0.0, 0.0)]
+        );
+        assert_eq!(
+            normalized(&[(2.0, 0), (-2.0, 1)], None),
+            vec![(2.0, 1.0), (-2.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_normalize_score_collector() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let collector = NormalizeScoreCollector::new(TopDocs::with_limit(2));
+        let top_docs = index.reader()?.searcher().search(&AllQuery, &collector)?;
+        assert!(top_docs.is_empty());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(Document::default())?;
+        index_writer.commit()?;
+        let top_docs = index.reader()?.searcher().search(&AllQuery, &collector)?;
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(top_docs[0].0.normalized, 1.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::collector::top_collector::ComparableDoc;
+use crate::collector::{ScoreSegmentTweaker, ScoreTweaker};
+use crate::{DocAddress, Score, Searcher};
+
+/// Re-ranks the top documents of a search, typically returned by [`TopDocs`](super::TopDocs),
+/// using a new score computed by `score_tweaker`.
+///
+/// This makes it possible to run a cheap query first, and to only apply an expensive scoring
+/// function, e.g. a model reading the stored or fast fields of the documents, on the top-K
+/// documents it returned.
+///
+/// The documents are grouped by segment, so that the segment tweaker, and the fast field or
+/// store readers it opens, are only created once per segment. Within a segment, the documents
+/// are scored by increasing `DocId`. The original score of each document is passed to the
+/// segment tweaker.
+///
+/// The re-ranked documents are returned by decreasing new score. In case of a tie, they are
+/// sorted by ascending [`DocAddress`].
+///
+/// ```rust
+/// use tantivy::collector::{rerank, TopDocs};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, DocId, Index, Score, SegmentReader};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let popularity = schema_builder.add_u64_field("popularity", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "diary diary", popularity => 1u64))?;
+/// index_writer.add_document(doc!(title => "diary", popularity => 10u64))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+/// let reranked_docs = rerank(&searcher, &top_docs, move |segment_reader: &SegmentReader| {
+///     let popularity_reader = segment_reader.fast_fields().u64(popularity).unwrap();
+///     move |doc: DocId, _original_score: Score| popularity_reader.get_val(doc as u64)
+/// })?;
+/// assert_eq!(
+///     reranked_docs,
+///     vec![(10, DocAddress::new(0, 1)), (1, DocAddress::new(0, 0))]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn rerank<TScore, TScoreTweaker>(
+    searcher: &Searcher,
+    top_docs: &[(Score, DocAddress)],
+    score_tweaker: TScoreTweaker,
+) -> crate::Result<Vec<(TScore, DocAddress)>>
+where
+    TScore: PartialOrd,
+    TScoreTweaker: ScoreTweaker<TScore>,
+{
+    let mut docs_by_segment: Vec<(Score, DocAddress)> = top_docs.to_vec();
+    docs_by_segment.sort_unstable_by_key(|(_, doc_address)| *doc_address);
+    let mut reranked_docs = Vec::with_capacity(docs_by_segment.len());
+    let mut remaining_docs = &docs_by_segment[..];
+    while let Some(&(_, first_doc_address)) = remaining_docs.first() {
+        let segment_ord = first_doc_address.segment_ord;
+        let segment_len = remaining_docs
+            .iter()
+            .take_while(|(_, doc_address)| doc_address.segment_ord == segment_ord)
+            .count();
+        let (segment_docs, next_docs) = remaining_docs.split_at(segment_len);
+        remaining_docs = next_docs;
+        let segment_reader = searcher.segment_reader(segment_ord);
+        let mut segment_tweaker = score_tweaker.segment_tweaker(segment_reader)?;
+        for &(score, doc_address) in segment_docs {
+            reranked_docs.push(ComparableDoc {
+                feature: segment_tweaker.score(doc_address.doc_id, score),
+                doc: doc_address,
+            });
+        }
+    }
+    // `ComparableDoc` is ordered by decreasing feature.
+    reranked_docs.sort_unstable();
+    Ok(reranked_docs
+        .into_iter()
+        .map(|comparable_doc| (comparable_doc.feature, comparable_doc.doc))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rerank;
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, FAST, STORED, TEXT};
+    use crate::{DocAddress, DocId, Index, Score, SegmentReader};
+
+    #[test]
+    fn test_rerank_by_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let docs = [
+            ("rust rust rust rust", 2u64),
+            ("rust rust rust", 40u64),
+            ("rust rust", 30u64),
+            ("rust", 10u64),
+            ("java", 100u64),
+        ];
+        for (i, (text, popularity_value)) in docs.iter().enumerate() {
+            index_writer.add_document(doc!(title => *text, popularity => *popularity_value))?;
+            // Spread the documents over several segments.
+            if i % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+
+        let reranked_docs = rerank(
+            &searcher,
+            &top_docs,
+            move |segment_reader: &SegmentReader| {
+                let popularity_reader = segment_reader.fast_fields().u64(popularity).unwrap();
+                move |doc: DocId, original_score: Score| {
+                    assert!(original_score > 0.0);
+                    popularity_reader.get_val(doc as u64)
+                }
+            },
+        )?;
+        let titles = |docs: &[DocAddress]| -> crate::Result<Vec<String>> {
+            docs.iter()
+                .map(|doc_address| {
+                    let doc = searcher.doc(*doc_address)?;
+                    Ok(doc.get_first(title).unwrap().as_text().unwrap().to_string())
+                })
+                .collect()
+        };
+        let reranked_addresses: Vec<DocAddress> = reranked_docs
+            .iter()
+            .map(|(_, doc_address)| *doc_address)
+            .collect();
+        // Only the top documents are re-ranked: the popular "java" document is not part of them.
+        assert_eq!(
+            titles(&reranked_addresses)?,
+            vec!["rust rust rust", "rust rust", "rust rust rust rust"]
+        );
+        let new_scores: Vec<u64> = reranked_docs.iter().map(|(score, _)| *score).collect();
+        assert_eq!(new_scores, vec![40, 30, 2]);
+
+        let no_docs = rerank(&searcher, &[], |_: &SegmentReader| {
+            |_: DocId, score: Score| score
+        })?;
+        assert!(no_docs.is_empty());
+        Ok(())
+    }
+}
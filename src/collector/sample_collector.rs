@@ -0,0 +1,307 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `SampleCollector` returns a uniform random sample of at most `sample_size` of the
+/// documents matching the query.
+///
+/// Each segment is sampled in a single pass, using reservoir sampling. The samples of the
+/// different segments are then merged, drawing from each segment proportionally to its
+/// number of matching documents, so that every matching document has the same probability
+/// to belong to the final sample.
+///
+/// The sample is returned sorted by `DocAddress`.
+///
+/// By default, the random number generator is seeded randomly.
+/// Use [`SampleCollector::with_seed`] to get reproducible samples: the same seed over the
+/// same searcher always yields the same sample.
+///
+/// ```rust
+/// use tantivy::collector::SampleCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let id = schema_builder.add_u64_field("id", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// for i in 0u64..100u64 {
+///     index_writer.add_document(doc!(id => i))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let collector = SampleCollector::new(10).with_seed(42);
+/// let sample = searcher.search(&AllQuery, &collector)?;
+/// assert_eq!(sample.len(), 10);
+/// assert_eq!(searcher.search(&AllQuery, &collector)?, sample);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SampleCollector {
+    sample_size: usize,
+    seed: u64,
+}
+
+impl SampleCollector {
+    /// Creates a new `SampleCollector` returning at most `sample_size` documents.
+    pub fn new(sample_size: usize) -> SampleCollector {
+        let seed = RandomState::new().build_hasher().finish();
+        SampleCollector { sample_size, seed }
+    }
+
+    /// Sets the seed of the random number generator.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> SampleCollector {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Collector for SampleCollector {
+    type Fruit = Vec<DocAddress>;
+    type Child = SampleSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        _segment: &SegmentReader,
+    ) -> crate::Result<SampleSegmentCollector> {
+        Ok(SampleSegmentCollector {
+            segment_ord: segment_local_id,
+            sample_size: self.sample_size,
+            num_docs: 0,
+            sample: Vec::new(),
+            rng: SplitMix64::new(self.seed ^ mix64(u64::from(segment_local_id) + 1)),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_samples: Vec<SegmentSample>) -> crate::Result<Vec<DocAddress>> {
+        let mut segment_samples: Vec<SegmentSample> = segment_samples
+            .into_iter()
+            .filter(|segment_sample| segment_sample.num_docs > 0)
+            .collect();
+        let mut num_remaining_docs: u64 = segment_samples
+            .iter()
+            .map(|segment_sample| segment_sample.num_docs)
+            .sum();
+        let sample_size = (self.sample_size as u64).min(num_remaining_docs) as usize;
+        let mut rng = SplitMix64::new(self.seed);
+        let mut sample = Vec::with_capacity(sample_size);
+        // Draws without replacement from the union of the matching documents of all segments:
+        // the segment of each draw is picked proportionally to the number of its matching
+        // documents that were not drawn yet, and a random document of its reservoir is taken.
+        while sample.len() < sample_size {
+            let mut draw = rng.next_below(num_remaining_docs);
+            let segment_sample = segment_samples
+                .iter_mut()
+                .find(|segment_sample| {
+                    if draw < segment_sample.num_docs {
+                        true
+                    } else {
+                        draw -= segment_sample.num_docs;
+                        false
+                    }
+                })
+                .expect("draw is lower than the total number of remaining docs");
+            let idx = rng.next_below(segment_sample.sample.len() as u64) as usize;
+            sample.push(segment_sample.sample.swap_remove(idx));
+            segment_sample.num_docs -= 1;
+            num_remaining_docs -= 1;
+        }
+        sample.sort();
+        Ok(sample)
+    }
+}
+
+/// Sample of the matching documents of a segment.
+pub struct SegmentSample {
+    num_docs: u64,
+    sample: Vec<DocAddress>,
+}
+
+/// Segment collector associated to the `SampleCollector`.
+pub struct SampleSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    sample_size: usize,
+    num_docs: u64,
+    sample: Vec<DocAddress>,
+    rng: SplitMix64,
+}
+
+impl SegmentCollector for SampleSegmentCollector {
+    type Fruit = SegmentSample;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let doc_address = DocAddress::new(self.segment_ord, doc);
+        if self.sample.len() < self.sample_size {
+            self.sample.push(doc_address);
+        } else {
+            let idx = self.rng.next_below(self.num_docs + 1);
+            if idx < self.sample_size as u64 {
+                self.sample[idx as usize] = doc_address;
+            }
+        }
+        self.num_docs += 1;
+    }
+
+    fn harvest(self) -> SegmentSample {
+        SegmentSample {
+            num_docs: self.num_docs,
+            sample: self.sample,
+        }
+    }
+}
+
+/// Finalizer of the SplitMix64 generator.
+fn mix64(val: u64) -> u64 {
+    let mut z = val;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// SplitMix64 pseudo random number generator.
+///
+/// It is not cryptographically secure, but it is fast and of good enough quality
+/// for sampling.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        mix64(self.state)
+    }
+
+    /// Returns a random number within `[0, upper_bound)`.
+    ///
+    /// `upper_bound` must be strictly positive.
+    fn next_below(&mut self, upper_bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(upper_bound)) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::SampleCollector;
+    use crate::collector::Count;
+    use crate::merge_policy::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED};
+    use crate::{Index, Searcher, Term};
+
+    fn build_searcher(segment_sizes: &[u64]) -> crate::Result<Searcher> {
+        let mut schema_builder = Schema::builder();
+        let parity = schema_builder.add_u64_field("parity", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for &segment_size in segment_sizes {
+            for i in 0..segment_size {
+                index_writer.add_document(doc!(parity => i % 2))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok(index.reader()?.searcher())
+    }
+
+    #[test]
+    fn test_sample_collector_size() -> crate::Result<()> {
+        let searcher = build_searcher(&[10, 20])?;
+        let sample = searcher.search(&AllQuery, &SampleCollector::new(5))?;
+        assert_eq!(sample.len(), 5);
+        assert_eq!(sample.iter().collect::<HashSet<_>>().len(), 5);
+        // Asking for more docs than there are matches returns all of the matches.
+        let sample = searcher.search(&AllQuery, &SampleCollector::new(100))?;
+        assert_eq!(sample.len(), 30);
+        assert_eq!(sample.iter().collect::<HashSet<_>>().len(), 30);
+        let sample = searcher.search(&AllQuery, &SampleCollector::new(0))?;
+        assert!(sample.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample_collector_seed() -> crate::Result<()> {
+        let searcher = build_searcher(&[100, 50])?;
+        let parity = searcher.schema().get_field("parity").unwrap();
+        let even = TermQuery::new(Term::from_field_u64(parity, 0), IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&even, &Count)?, 75);
+        let sample = searcher.search(&even, &SampleCollector::new(10).with_seed(7))?;
+        assert_eq!(sample.len(), 10);
+        for doc_address in &sample {
+            let parity_reader = searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields()
+                .u64(parity)?;
+            assert_eq!(parity_reader.get_val(u64::from(doc_address.doc_id)), 0);
+        }
+        let same_sample = searcher.search(&even, &SampleCollector::new(10).with_seed(7))?;
+        assert_eq!(same_sample, sample);
+        let other_sample = searcher.search(&even, &SampleCollector::new(10).with_seed(8))?;
+        assert_ne!(other_sample, sample);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample_collector_uniform() -> crate::Result<()> {
+        let segment_sizes = [100, 300, 600];
+        let searcher = build_searcher(&segment_sizes)?;
+        let num_docs: u64 = segment_sizes.iter().sum();
+        let sample_size = 10;
+        let num_samples = 2_000;
+        let mut num_draws_per_segment = vec![0u64; searcher.segment_readers().len()];
+        // Docs are bucketed by decile of their doc id within their segment.
+        let mut num_draws_per_decile = [0u64; 10];
+        for seed in 0..num_samples {
+            let collector = SampleCollector::new(sample_size).with_seed(seed);
+            let sample = searcher.search(&AllQuery, &collector)?;
+            assert_eq!(sample.len(), sample_size);
+            for doc_address in sample {
+                num_draws_per_segment[doc_address.segment_ord as usize] += 1;
+                let max_doc = searcher.segment_reader(doc_address.segment_ord).max_doc();
+                num_draws_per_decile[(doc_address.doc_id * 10 / max_doc) as usize] += 1;
+            }
+        }
+        let num_draws = (num_samples * sample_size as u64) as f64;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let expected_ratio = f64::from(segment_reader.max_doc()) / num_docs as f64;
+            let ratio = num_draws_per_segment[segment_ord] as f64 / num_draws;
+            assert!(
+                (ratio - expected_ratio).abs() < 0.02,
+                "segment {}: ratio {}, expected {}",
+                segment_ord,
+                ratio,
+                expected_ratio
+            );
+        }
+        for (decile, &num_decile_draws) in num_draws_per_decile.iter().enumerate() {
+            let ratio = num_decile_draws as f64 / num_draws;
+            assert!(
+                (ratio - 0.1).abs() < 0.01,
+                "decile {}: ratio {}",
+                decile,
+                ratio
+            );
+        }
+        Ok(())
+    }
+}
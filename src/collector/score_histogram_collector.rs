@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `ScoreHistogramCollector` counts, in a single pass, the matching documents whose score
+/// falls in each of `num_buckets` buckets of equal width.
+///
+/// By default, the buckets span the range between the minimum and the maximum score of the
+/// matching documents. In that case, the scores of each segment are kept until the results
+/// of the segments are merged. If all of the scores are equal, all of the documents are
+/// counted in the first bucket.
+///
+/// Alternatively, the range of the buckets can be provided with
+/// [`ScoreHistogramCollector::with_range`]. The documents are then counted directly, and the
+/// documents whose score is out of the range are ignored.
+///
+/// ```rust
+/// use tantivy::collector::ScoreHistogramCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let query = query_parser.parse_query("diary")?;
+/// let histogram = searcher.search(&query, &ScoreHistogramCollector::new(4))?;
+///
+/// assert_eq!(histogram.counts().iter().sum::<u64>(), 2);
+/// assert!(histogram.min_score() < histogram.max_score());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ScoreHistogramCollector {
+    range: Option<(Score, Score)>,
+    num_buckets: usize,
+}
+
+impl ScoreHistogramCollector {
+    /// Creates a `ScoreHistogramCollector` with `num_buckets` buckets, spanning the range of
+    /// the scores of the matching documents.
+    ///
+    /// # Panics
+    /// The method panics if `num_buckets` is 0.
+    pub fn new(num_buckets: usize) -> ScoreHistogramCollector {
+        assert!(
+            num_buckets > 0,
+            "num_buckets must be strictly greater than 0."
+        );
+        ScoreHistogramCollector {
+            range: None,
+            num_buckets,
+        }
+    }
+
+    /// Creates a `ScoreHistogramCollector` with `num_buckets` buckets, spanning
+    /// `[min_score, max_score]`.
+    ///
+    /// # Panics
+    /// The method panics if `num_buckets` is 0, or if `min_score` is not strictly lower than
+    /// `max_score`.
+    pub fn with_range(
+        min_score: Score,
+        max_score: Score,
+        num_buckets: usize,
+    ) -> ScoreHistogramCollector {
+        assert!(
+            num_buckets > 0,
+            "num_buckets must be strictly greater than 0."
+        );
+        assert!(
+            min_score < max_score,
+            "min_score must be strictly lower than max_score."
+        );
+        ScoreHistogramCollector {
+            range: Some((min_score, max_score)),
+            num_buckets,
+        }
+    }
+}
+
+/// Histogram of the scores computed by the [`ScoreHistogramCollector`].
+///
+/// The bucket `i` counts the documents whose score is within
+/// `[min_score + i * bucket_width, min_score + (i + 1) * bucket_width)`. The last bucket also
+/// counts the documents whose score is `max_score`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScoreHistogram {
+    min_score: Score,
+    max_score: Score,
+    counts: Vec<u64>,
+}
+
+impl ScoreHistogram {
+    fn empty(min_score: Score, max_score: Score, num_buckets: usize) -> ScoreHistogram {
+        ScoreHistogram {
+            min_score,
+            max_score,
+            counts: vec![0; num_buckets],
+        }
+    }
+
+    /// Returns the lower bound of the first bucket.
+    pub fn min_score(&self) -> Score {
+        self.min_score
+    }
+
+    /// Returns the upper bound of the last bucket.
+    pub fn max_score(&self) -> Score {
+        self.max_score
+    }
+
+    /// Returns the width of the buckets.
+    pub fn bucket_width(&self) -> Score {
+        (self.max_score - self.min_score) / self.counts.len() as Score
+    }
+
+    /// Returns the number of documents of each bucket.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    #[inline]
+    fn bucket(&self, score: Score) -> Option<usize> {
+        // Also rejects NaN scores.
+        if !(score >= self.min_score && score <= self.max_score) {
+            return None;
+        }
+        let bucket_width = self.bucket_width();
+        if bucket_width <= 0.0 {
+            return Some(0);
+        }
+        let bucket = ((score - self.min_score) / bucket_width) as usize;
+        Some(bucket.min(self.counts.len() - 1))
+    }
+
+    #[inline]
+    fn add_score(&mut self, score: Score) {
+        if let Some(bucket) = self.bucket(score) {
+            self.counts[bucket] += 1;
+        }
+    }
+}
+
+/// Partial result of the [`ScoreHistogramCollector`] on a segment.
+pub enum SegmentScoreHistogram {
+    /// The scores of the matching documents, if the range of the buckets is not known yet.
+    Scores(Vec<Score>),
+    /// The histogram of the scores, if the range of the buckets is known.
+    Histogram(ScoreHistogram),
+}
+
+impl Collector for ScoreHistogramCollector {
+    type Fruit = ScoreHistogram;
+
+    type Child = ScoreHistogramSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        _segment_reader: &SegmentReader,
+    ) -> crate::Result<ScoreHistogramSegmentCollector> {
+        let fruit = match self.range {
+            Some((min_score, max_score)) => SegmentScoreHistogram::Histogram(
+                ScoreHistogram::empty(min_score, max_score, self.num_buckets),
+            ),
+            None => SegmentScoreHistogram::Scores(Vec::new()),
+        };
+        Ok(ScoreHistogramSegmentCollector { fruit })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<SegmentScoreHistogram>,
+    ) -> crate::Result<ScoreHistogram> {
+        if let Some((min_score, max_score)) = self.range {
+            let mut histogram = ScoreHistogram::empty(min_score, max_score, self.num_buckets);
+            for segment_fruit in segment_fruits {
+                if let SegmentScoreHistogram::Histogram(segment_histogram) = segment_fruit {
+                    for (count, segment_count) in
+                        histogram.counts.iter_mut().zip(segment_histogram.counts)
+                    {
+                        *count += segment_count;
+                    }
+                }
+            }
+            return Ok(histogram);
+        }
+        let scores: Vec<Score> = segment_fruits
+            .into_iter()
+            .flat_map(|segment_fruit| match segment_fruit {
+                SegmentScoreHistogram::Scores(scores) => scores,
+                SegmentScoreHistogram::Histogram(_) => Vec::new(),
+            })
+            .filter(|score| !score.is_nan())
+            .collect();
+        let min_score = scores.iter().copied().reduce(Score::min).unwrap_or(0.0);
+        let max_score = scores.iter().copied().reduce(Score::max).unwrap_or(0.0);
+        let mut histogram = ScoreHistogram::empty(min_score, max_score, self.num_buckets);
+        for score in scores {
+            histogram.add_score(score);
+        }
+        Ok(histogram)
+    }
+}
+
+#[doc(hidden)]
+pub struct ScoreHistogramSegmentCollector {
+    fruit: SegmentScoreHistogram,
+}
+
+impl SegmentCollector for ScoreHistogramSegmentCollector {
+    type Fruit = SegmentScoreHistogram;
+
+    fn collect(&mut self, _doc: DocId, score: Score) {
+        match &mut self.fruit {
+            SegmentScoreHistogram::Scores(scores) => scores.push(score),
+            SegmentScoreHistogram::Histogram(histogram) => histogram.add_score(score),
+        }
+    }
+
+    fn harvest(self) -> SegmentScoreHistogram {
+        self.fruit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoreHistogramCollector;
+    use crate::query::{AllQuery, BooleanQuery, ConstScoreQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_score_histogram_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let tags = ["a", "b", "c", "d"];
+        // `num_tags` documents have `num_tags` tags.
+        for num_tags in 1..=tags.len() {
+            for _ in 0..num_tags {
+                let mut doc = crate::Document::default();
+                for tag_value in &tags[..num_tags] {
+                    doc.add_text(tag, tag_value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        // The score of a document is its number of tags.
+        let subqueries: Vec<(Occur, Box<dyn Query>)> = tags
+            .iter()
+            .map(|tag_value| {
+                let term_query = TermQuery::new(
+                    Term::from_field_text(tag, tag_value),
+                    IndexRecordOption::Basic,
+                );
+                let query: Box<dyn Query> =
+                    Box::new(ConstScoreQuery::new(Box::new(term_query), 1.0));
+                (Occur::Should, query)
+            })
+            .collect();
+        let query = BooleanQuery::new(subqueries);
+
+        let histogram = searcher.search(&query, &ScoreHistogramCollector::new(3))?;
+        assert_eq!(histogram.min_score(), 1.0);
+        assert_eq!(histogram.max_score(), 4.0);
+        assert_eq!(histogram.bucket_width(), 1.0);
+        assert_eq!(histogram.counts(), &[1, 2, 7]);
+
+        let histogram =
+            searcher.search(&query, &ScoreHistogramCollector::with_range(0.0, 5.0, 5))?;
+        assert_eq!(histogram.counts(), &[0, 1, 2, 3, 4]);
+
+        let histogram =
+            searcher.search(&query, &ScoreHistogramCollector::with_range(1.5, 3.0, 2))?;
+        assert_eq!(histogram.counts(), &[2, 3]);
+
+        // All of the scores are equal.
+        let histogram = searcher.search(&AllQuery, &ScoreHistogramCollector::new(3))?;
+        assert_eq!(histogram.min_score(), 1.0);
+        assert_eq!(histogram.max_score(), 1.0);
+        assert_eq!(histogram.counts(), &[10, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_histogram_collector_no_match() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(Term::from_field_text(tag, "z"), IndexRecordOption::Basic);
+        let histogram = searcher.search(&query, &ScoreHistogramCollector::new(2))?;
+        assert_eq!(histogram.counts(), &[0, 0]);
+        Ok(())
+    }
+}
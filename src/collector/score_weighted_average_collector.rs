@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::collector::{Collector, DistributedCollector, SegmentCollector};
+use crate::schema::{Field, Type};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `ScoreWeightedAverageCollector` computes the average of the values of a single-valued
+/// fast field over the matching documents, weighted by their score.
+///
+/// It accumulates `sum(score_i * value_i)` and `sum(score_i)`, and returns their ratio.
+///
+/// Supported field types are u64, i64, and f64.
+///
+/// ```rust
+/// use tantivy::collector::ScoreWeightedAverageCollector;
+/// use tantivy::query::{BooleanQuery, ConstScoreQuery, Occur, Query, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, FAST, STRING};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", STRING);
+/// let rating = schema_builder.add_u64_field("rating", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "good", rating => 4u64))?;
+/// index_writer.add_document(doc!(title => "perfect", rating => 5u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let title_query = |title_value: &str, score| -> (Occur, Box<dyn Query>) {
+///     let term_query = TermQuery::new(
+///         Term::from_field_text(title, title_value),
+///         IndexRecordOption::Basic,
+///     );
+///     (Occur::Should, Box::new(ConstScoreQuery::new(Box::new(term_query), score)))
+/// };
+/// let query = BooleanQuery::new(vec![title_query("good", 1.0), title_query("perfect", 3.0)]);
+/// let weighted_average =
+///     searcher.search(&query, &ScoreWeightedAverageCollector::new(rating))?;
+/// assert_eq!(weighted_average.average(), Some((1.0 * 4.0 + 3.0 * 5.0) / 4.0));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ScoreWeightedAverageCollector {
+    field: Field,
+}
+
+impl ScoreWeightedAverageCollector {
+    /// Creates a new `ScoreWeightedAverageCollector` computing the score-weighted average of
+    /// the given fast field.
+    pub fn new(field: Field) -> ScoreWeightedAverageCollector {
+        ScoreWeightedAverageCollector { field }
+    }
+}
+
+/// Score-weighted average computed by the [`ScoreWeightedAverageCollector`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreWeightedAverage {
+    count: u64,
+    weighted_sum: f64,
+    total_score: f64,
+}
+
+impl ScoreWeightedAverage {
+    /// Returns the number of values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of the values, weighted by the scores of their documents.
+    pub fn weighted_sum(&self) -> f64 {
+        self.weighted_sum
+    }
+
+    /// Returns the sum of the scores of the documents.
+    pub fn total_score(&self) -> f64 {
+        self.total_score
+    }
+
+    /// Returns the score-weighted average of the values, or `None` if the total score is zero,
+    /// e.g. if there are no values.
+    pub fn average(&self) -> Option<f64> {
+        if self.total_score == 0.0 {
+            None
+        } else {
+            Some(self.weighted_sum / self.total_score)
+        }
+    }
+
+    #[inline]
+    fn add_value(&mut self, val: f64, score: Score) {
+        let score = score as f64;
+        self.count += 1;
+        self.weighted_sum += score * val;
+        self.total_score += score;
+    }
+
+    /// Merges the score-weighted average of another set of values into this one.
+    pub fn merge(&mut self, other: &ScoreWeightedAverage) {
+        self.count += other.count;
+        self.weighted_sum += other.weighted_sum;
+        self.total_score += other.total_score;
+    }
+
+    fn merge_all(averages: Vec<ScoreWeightedAverage>) -> ScoreWeightedAverage {
+        let mut merged = ScoreWeightedAverage::default();
+        for average in &averages {
+            merged.merge(average);
+        }
+        merged
+    }
+}
+
+impl Collector for ScoreWeightedAverageCollector {
+    type Fruit = ScoreWeightedAverage;
+
+    type Child = ScoreWeightedAverageSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<ScoreWeightedAverageSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let field_type = field_entry.field_type().value_type();
+        if !matches!(field_type, Type::U64 | Type::I64 | Type::F64) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64 or f64 fast field.",
+                field_entry.name()
+            )));
+        }
+        let fast_fields = segment_reader.fast_fields();
+        // Checks the field is a single-valued fast field.
+        match field_type {
+            Type::I64 => fast_fields.i64(self.field).map(|_| ())?,
+            Type::F64 => fast_fields.f64(self.field).map(|_| ())?,
+            _ => fast_fields.u64(self.field).map(|_| ())?,
+        }
+        Ok(ScoreWeightedAverageSegmentCollector {
+            ff_reader: fast_fields.u64_lenient(self.field)?,
+            field_type,
+            average: ScoreWeightedAverage::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_averages: Vec<ScoreWeightedAverage>,
+    ) -> crate::Result<ScoreWeightedAverage> {
+        Ok(ScoreWeightedAverage::merge_all(segment_averages))
+    }
+}
+
+impl DistributedCollector for ScoreWeightedAverageCollector {
+    fn merge_distributed_fruits(
+        &self,
+        averages: Vec<ScoreWeightedAverage>,
+    ) -> crate::Result<ScoreWeightedAverage> {
+        Ok(ScoreWeightedAverage::merge_all(averages))
+    }
+}
+
+/// Segment collector for the [`ScoreWeightedAverageCollector`].
+pub struct ScoreWeightedAverageSegmentCollector {
+    ff_reader: Arc<dyn Column<u64>>,
+    field_type: Type,
+    average: ScoreWeightedAverage,
+}
+
+impl SegmentCollector for ScoreWeightedAverageSegmentCollector {
+    type Fruit = ScoreWeightedAverage;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let val = self.ff_reader.get_val(doc as u64);
+        self.average
+            .add_value(f64_from_fastfield_u64(val, &self.field_type), score);
+    }
+
+    fn harvest(self) -> ScoreWeightedAverage {
+        self.average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoreWeightedAverageCollector;
+    use crate::query::{AllQuery, BooleanQuery, ConstScoreQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Term, FAST, STRING};
+    use crate::{assert_nearly_equals, Index};
+
+    #[test]
+    fn test_score_weighted_average_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let val_field = schema_builder.add_i64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let docs: [(&[&str], i64); 5] = [
+            (&["a"], 10),
+            (&["b"], -20),
+            (&["a", "b"], 30),
+            (&["c"], 1_000),
+            (&["a"], 5),
+        ];
+        for (i, (tags, val)) in docs.iter().enumerate() {
+            let mut doc = doc!(val_field => *val);
+            for tag_value in tags.iter() {
+                doc.add_text(tag, tag_value);
+            }
+            index_writer.add_document(doc)?;
+            // Spread the documents over several segments.
+            if i % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let tag_query = |tag_value: &str, score| -> (Occur, Box<dyn Query>) {
+            let term_query = TermQuery::new(
+                Term::from_field_text(tag, tag_value),
+                IndexRecordOption::Basic,
+            );
+            (
+                Occur::Should,
+                Box::new(ConstScoreQuery::new(Box::new(term_query), score)),
+            )
+        };
+
+        // The documents tagged with "a" score 1.0, the ones tagged with "b" score 3.0, and the
+        // one tagged with both scores 4.0.
+        let query = BooleanQuery::new(vec![tag_query("a", 1.0), tag_query("b", 3.0)]);
+        let weighted_average =
+            searcher.search(&query, &ScoreWeightedAverageCollector::new(val_field))?;
+        assert_eq!(weighted_average.count(), 4);
+        assert_eq!(weighted_average.total_score(), 1.0 + 3.0 + 4.0 + 1.0);
+        assert_eq!(
+            weighted_average.weighted_sum(),
+            1.0 * 10.0 + 3.0 * -20.0 + 4.0 * 30.0 + 1.0 * 5.0
+        );
+        assert_nearly_equals!(weighted_average.average().unwrap(), 75.0 / 9.0);
+
+        // A total score of zero has no average.
+        let zero_score_query = ConstScoreQuery::new(Box::new(AllQuery), 0.0);
+        let weighted_average = searcher.search(
+            &zero_score_query,
+            &ScoreWeightedAverageCollector::new(val_field),
+        )?;
+        assert_eq!(weighted_average.count(), 5);
+        assert_eq!(weighted_average.average(), None);
+        let no_match_query = BooleanQuery::new(vec![tag_query("d", 1.0)]);
+        let weighted_average = searcher.search(
+            &no_match_query,
+            &ScoreWeightedAverageCollector::new(val_field),
+        )?;
+        assert_eq!(weighted_average.count(), 0);
+        assert_eq!(weighted_average.average(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_weighted_average_collector_not_fast() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher
+            .search(&AllQuery, &ScoreWeightedAverageCollector::new(tag))
+            .is_err());
+        Ok(())
+    }
+}
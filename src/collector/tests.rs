@@ -293,3 +293,86 @@ fn test_option_collector_none() -> crate::Result<()> {
     assert_eq!(counts, None);
     Ok(())
 }
+
+#[test]
+fn test_distributed_collector_merge() -> crate::Result<()> {
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+    use crate::aggregation::DistributedAggregationCollector;
+    use crate::query::TermQuery;
+    use crate::schema::IndexRecordOption;
+    use crate::Term;
+
+    type NodeFruit = (
+        usize,
+        Vec<(Score, DocAddress)>,
+        IntermediateAggregationResults,
+    );
+
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let price = schema_builder.add_u64_field("price", FAST);
+    let schema = schema_builder.build();
+    let make_node_searcher = |docs: &[(&str, u64)]| -> crate::Result<Searcher> {
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        for &(text_val, price_val) in docs {
+            index_writer.add_document(doc!(text => text_val, price => price_val))?;
+        }
+        index_writer.commit()?;
+        Ok(index.reader()?.searcher())
+    };
+    let node_searchers = [
+        make_node_searcher(&[("a b", 3), ("a a a", 17), ("b c", 8)])?,
+        make_node_searcher(&[("a", 12), ("c", 1)])?,
+        make_node_searcher(&[("a b c", 20), ("a a", 5), ("b", 9), ("a c", 11)])?,
+    ];
+
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "price_stats": { "stats": { "field": "price" } }
+    }))
+    .unwrap();
+    let query = TermQuery::new(
+        Term::from_field_text(text, "a"),
+        IndexRecordOption::WithFreqs,
+    );
+    let collector = (
+        Count,
+        TopDocs::with_limit(3),
+        DistributedAggregationCollector::from_aggs(agg_req.clone(), None),
+    );
+
+    // Each node computes and serializes its partial fruit.
+    let serialized_fruits: Vec<String> = node_searchers
+        .iter()
+        .map(|searcher| {
+            let fruit = searcher.search(&query, &collector)?;
+            Ok(serde_json::to_string(&fruit).unwrap())
+        })
+        .collect::<crate::Result<_>>()?;
+
+    // The coordinator deserializes and merges them.
+    let fruits: Vec<NodeFruit> = serialized_fruits
+        .iter()
+        .map(|serialized_fruit| serde_json::from_str(serialized_fruit).unwrap())
+        .collect();
+    let mut expected_scores: Vec<Score> = fruits
+        .iter()
+        .flat_map(|(_, top_docs, _)| top_docs.iter().map(|(score, _)| *score))
+        .collect();
+    expected_scores.sort_by(|left, right| right.partial_cmp(left).unwrap());
+    expected_scores.truncate(3);
+
+    let (count, top_docs, intermediate_agg_res) = collector.merge_distributed_fruits(fruits)?;
+
+    assert_eq!(count, 6);
+    let top_scores: Vec<Score> = top_docs.iter().map(|(score, _)| *score).collect();
+    assert_eq!(top_scores, expected_scores);
+    let agg_res = intermediate_agg_res.into_final_bucket_result(agg_req)?;
+    let agg_res_json = serde_json::to_value(&agg_res).unwrap();
+    assert_eq!(agg_res_json["price_stats"]["count"], 6);
+    assert_eq!(agg_res_json["price_stats"]["sum"], 68.0);
+    assert_eq!(agg_res_json["price_stats"]["min"], 3.0);
+    assert_eq!(agg_res_json["price_stats"]["max"], 20.0);
+    Ok(())
+}
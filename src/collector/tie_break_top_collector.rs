@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+use smallvec::SmallVec;
+
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::type_and_cardinality;
+use crate::schema::{Cardinality, Field};
+use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Score of a document, followed by the values of its tie-breaking fast fields.
+///
+/// The values are the `u64` representation of the fast field values, which is monotonic, and
+/// are complemented for the ascending tie-breakers, so that a higher value is always a better
+/// rank.
+#[derive(Clone)]
+pub struct ScoreWithTieBreakers {
+    score: Score,
+    tie_breakers: SmallVec<[u64; 2]>,
+}
+
+impl PartialEq for ScoreWithTieBreakers {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for ScoreWithTieBreakers {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.score.partial_cmp(&other.score) {
+            Some(Ordering::Equal) => Some(self.tie_breakers.cmp(&other.tie_breakers)),
+            by_score => by_score,
+        }
+    }
+}
+
+/// Collects the top-K documents by decreasing score, and breaks the ties on the score using the
+/// values of a list of fast fields.
+///
+/// The tie-breaking values are only read for the documents that can enter the top-K, i.e.
+/// whose score is at least the score of the lowest ranked document collected so far.
+pub(crate) struct TieBreakTopCollector {
+    tie_breakers: Vec<(Field, Order)>,
+    collector: TopCollector<ScoreWithTieBreakers>,
+}
+
+impl TieBreakTopCollector {
+    pub(crate) fn new(
+        tie_breakers: Vec<(Field, Order)>,
+        collector: TopCollector<ScoreWithTieBreakers>,
+    ) -> TieBreakTopCollector {
+        TieBreakTopCollector {
+            tie_breakers,
+            collector,
+        }
+    }
+}
+
+impl Collector for TieBreakTopCollector {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    type Child = TieBreakTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<TieBreakTopSegmentCollector> {
+        let schema = segment_reader.schema();
+        let mut tie_breaker_readers = Vec::with_capacity(self.tie_breakers.len());
+        for (field, order) in &self.tie_breakers {
+            let field_entry = schema.get_field_entry(*field);
+            if type_and_cardinality(field_entry.field_type()).map(|(_, cardinality)| cardinality)
+                != Some(Cardinality::SingleValue)
+            {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a single-valued fast field.",
+                    field_entry.name()
+                )));
+            }
+            let ff_reader = segment_reader.fast_fields().u64_lenient(*field)?;
+            tie_breaker_readers.push((ff_reader, order.clone()));
+        }
+        Ok(TieBreakTopSegmentCollector {
+            segment_collector: self.collector.for_segment(segment_local_id, segment_reader),
+            tie_breaker_readers,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(ScoreWithTieBreakers, DocAddress)>>,
+    ) -> crate::Result<Self::Fruit> {
+        let top_docs = self.collector.merge_fruits(segment_fruits)?;
+        Ok(top_docs
+            .into_iter()
+            .map(|(score_with_tie_breakers, doc_address)| {
+                (score_with_tie_breakers.score, doc_address)
+            })
+            .collect())
+    }
+}
+
+/// Segment collector associated to the tie-breaking [`TopDocs`](super::TopDocs) collector.
+pub struct TieBreakTopSegmentCollector {
+    segment_collector: TopSegmentCollector<ScoreWithTieBreakers>,
+    tie_breaker_readers: Vec<(Arc<dyn Column<u64>>, Order)>,
+}
+
+impl SegmentCollector for TieBreakTopSegmentCollector {
+    type Fruit = Vec<(ScoreWithTieBreakers, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if let Some(limit_feature) = self.segment_collector.limit_feature() {
+            if score < limit_feature.score {
+                return;
+            }
+        }
+        let tie_breakers = self
+            .tie_breaker_readers
+            .iter()
+            .map(|(ff_reader, order)| {
+                let val = ff_reader.get_val(doc as u64);
+                if order.is_asc() {
+                    !val
+                } else {
+                    val
+                }
+            })
+            .collect();
+        self.segment_collector.collect(
+            doc,
+            ScoreWithTieBreakers {
+                score,
+                tie_breakers,
+            },
+        );
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.segment_collector.harvest()
+    }
+}
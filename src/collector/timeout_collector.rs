@@ -0,0 +1,297 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::query::Weight;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+const DEFAULT_CHECK_INTERVAL: u32 = 1_024;
+
+/// Result of a search executed with a [`TimeoutCollector`].
+#[derive(Debug)]
+pub struct TimeoutFruit<TFruit> {
+    fruit: TFruit,
+    timed_out: bool,
+}
+
+impl<TFruit> TimeoutFruit<TFruit> {
+    /// Returns true if the deadline was reached before all of the matching documents
+    /// were collected.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Returns the fruit of the wrapped collector.
+    ///
+    /// If the search timed out, it only accounts for the documents collected before the
+    /// deadline.
+    pub fn fruit(&self) -> &TFruit {
+        &self.fruit
+    }
+
+    /// Consumes the result, returning the fruit of the wrapped collector.
+    pub fn into_fruit(self) -> TFruit {
+        self.fruit
+    }
+}
+
+/// The `TimeoutCollector` wraps a collector, and stops collecting documents once a deadline
+/// is reached.
+///
+/// The deadline is checked every `check_interval` collected documents (1024 by default), and
+/// before starting to collect a segment. Once it is reached, the subsequent documents are not
+/// passed to the wrapped collector anymore, and the segments that were not started yet are
+/// skipped: the search returns the partial result of the wrapped collector, flagged as
+/// [timed out](TimeoutFruit::timed_out).
+///
+/// Note that the documents of the segment being collected when the deadline is reached are
+/// still matched by the query, they are only no longer collected. The segments are collected
+/// document by document, so that the optimized collection of some collectors (e.g. the
+/// pruning of [`TopDocs`](crate::collector::TopDocs)) does not apply to their wrapped
+/// version.
+///
+/// Note that the deadline is computed when the `TimeoutCollector` is created.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::{Count, TimeoutCollector};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let collector = TimeoutCollector::new(Count, Duration::from_secs(10));
+/// let count = searcher.search(&AllQuery, &collector)?;
+/// assert!(!count.timed_out());
+/// assert_eq!(*count.fruit(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TimeoutCollector<TCollector> {
+    collector: TCollector,
+    deadline: Deadline,
+    check_interval: u32,
+}
+
+/// Instant at which the collection stops, along with the clock it is checked against.
+#[derive(Clone)]
+struct Deadline {
+    deadline: Instant,
+    clock: Arc<dyn Fn() -> Instant + Send + Sync>,
+}
+
+impl Deadline {
+    fn is_expired(&self) -> bool {
+        (self.clock)() >= self.deadline
+    }
+}
+
+impl<TCollector: Collector> TimeoutCollector<TCollector> {
+    /// Creates a `TimeoutCollector` wrapping `collector`, that stops collecting `timeout`
+    /// after its creation.
+    pub fn new(collector: TCollector, timeout: Duration) -> TimeoutCollector<TCollector> {
+        TimeoutCollector::with_deadline(collector, Instant::now() + timeout)
+    }
+
+    /// Creates a `TimeoutCollector` wrapping `collector`, that stops collecting at
+    /// `deadline`.
+    pub fn with_deadline(collector: TCollector, deadline: Instant) -> TimeoutCollector<TCollector> {
+        TimeoutCollector {
+            collector,
+            deadline: Deadline {
+                deadline,
+                clock: Arc::new(Instant::now),
+            },
+            check_interval: DEFAULT_CHECK_INTERVAL,
+        }
+    }
+
+    /// Sets the number of matching documents collected between two checks of the deadline.
+    ///
+    /// # Panics
+    /// Panics if `check_interval` is 0.
+    #[must_use]
+    pub fn with_check_interval(mut self, check_interval: u32) -> TimeoutCollector<TCollector> {
+        assert!(check_interval > 0, "The check interval must be positive.");
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Sets the clock the deadline is checked against.
+    #[cfg(test)]
+    fn with_clock(
+        mut self,
+        clock: impl Fn() -> Instant + Send + Sync + 'static,
+    ) -> TimeoutCollector<TCollector> {
+        self.deadline.clock = Arc::new(clock);
+        self
+    }
+}
+
+impl<TCollector: Collector> Collector for TimeoutCollector<TCollector> {
+    type Fruit = TimeoutFruit<TCollector::Fruit>;
+
+    type Child = TimeoutSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let segment_collector = self.collector.for_segment(segment_local_id, segment)?;
+        Ok(TimeoutSegmentCollector {
+            segment_collector,
+            deadline: self.deadline.clone(),
+            check_interval: self.check_interval,
+            num_docs_before_check: self.check_interval,
+            timed_out: false,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(<TCollector::Child as SegmentCollector>::Fruit, bool)>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut timed_out = false;
+        let segment_fruits = segment_fruits
+            .into_iter()
+            .map(|(segment_fruit, segment_timed_out)| {
+                timed_out |= segment_timed_out;
+                segment_fruit
+            })
+            .collect();
+        let fruit = self.collector.merge_fruits(segment_fruits)?;
+        Ok(TimeoutFruit { fruit, timed_out })
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        if self.deadline.is_expired() {
+            segment_collector.timed_out = true;
+            return Ok(segment_collector.harvest());
+        }
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            weight.for_each(reader, &mut |doc, score| {
+                if alive_bitset.is_alive(doc) {
+                    segment_collector.collect(doc, score);
+                }
+            })?;
+        } else {
+            weight.for_each(reader, &mut |doc, score| {
+                segment_collector.collect(doc, score);
+            })?;
+        }
+        Ok(segment_collector.harvest())
+    }
+}
+
+/// Segment collector associated to the `TimeoutCollector`.
+pub struct TimeoutSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    deadline: Deadline,
+    check_interval: u32,
+    num_docs_before_check: u32,
+    timed_out: bool,
+}
+
+impl<TSegmentCollector: SegmentCollector> SegmentCollector
+    for TimeoutSegmentCollector<TSegmentCollector>
+{
+    type Fruit = (TSegmentCollector::Fruit, bool);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.timed_out {
+            return;
+        }
+        self.segment_collector.collect(doc, score);
+        self.num_docs_before_check -= 1;
+        if self.num_docs_before_check == 0 {
+            self.timed_out = self.deadline.is_expired();
+            self.num_docs_before_check = self.check_interval;
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        (self.segment_collector.harvest(), self.timed_out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    use super::TimeoutCollector;
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST};
+    use crate::Index;
+
+    #[test]
+    fn test_timeout_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0u64..1_000u64 {
+            index_writer.add_document(doc!(id => i))?;
+            if i % 250 == 249 {
+                index_writer.commit()?;
+            }
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+
+        // The clock reaches the deadline on its third reading: before the first segment, and
+        // after its 10th and 20th documents.
+        let deadline = Instant::now();
+        let num_readings = AtomicUsize::new(0);
+        let clock = move || {
+            if num_readings.fetch_add(1, Ordering::SeqCst) < 2 {
+                deadline - Duration::from_secs(1)
+            } else {
+                deadline
+            }
+        };
+        let collector = TimeoutCollector::with_deadline(Count, deadline)
+            .with_check_interval(10)
+            .with_clock(clock);
+        let count = searcher.search(&AllQuery, &collector)?;
+        assert!(count.timed_out());
+        assert_eq!(count.into_fruit(), 20);
+
+        // An expired deadline does not collect anything.
+        let collector = TimeoutCollector::with_deadline(Count, Instant::now());
+        let count = searcher.search(&AllQuery, &collector)?;
+        assert!(count.timed_out());
+        assert_eq!(count.into_fruit(), 0);
+
+        // The searcher can still be used afterwards.
+        let collector = TimeoutCollector::new(Count, Duration::from_secs(60));
+        let count = searcher.search(&AllQuery, &collector)?;
+        assert!(!count.timed_out());
+        assert_eq!(count.into_fruit(), 1_000);
+        Ok(())
+    }
+}
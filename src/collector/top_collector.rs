@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
 use std::marker::PhantomData;
 
@@ -53,6 +53,24 @@ impl<T: PartialOrd, D: PartialOrd> PartialEq for ComparableDoc<T, D> {
 
 impl<T: PartialOrd, D: PartialOrd> Eq for ComparableDoc<T, D> {}
 
+/// Returns true if `left` is ranked before `right`: it has a greater feature, or the same
+/// feature and a lower document address.
+fn ranks_before<T: PartialOrd>(left: &(T, DocAddress), right: &(T, DocAddress)) -> bool {
+    match left.0.partial_cmp(&right.0) {
+        Some(Ordering::Greater) => true,
+        Some(Ordering::Less) => false,
+        _ => left.1 < right.1,
+    }
+}
+
+/// Returns true if the documents of `fruit` are sorted by rank, as harvested by a
+/// [`TopSegmentCollector`].
+pub(crate) fn is_sorted_fruit<T: PartialOrd>(fruit: &[(T, DocAddress)]) -> bool {
+    fruit
+        .windows(2)
+        .all(|top_docs| !ranks_before(&top_docs[1], &top_docs[0]))
+}
+
 pub(crate) struct TopCollector<T> {
     pub limit: usize,
     pub offset: usize,
@@ -111,6 +129,55 @@ where T: PartialOrd + Clone
             .collect())
     }
 
+    /// Merges the fruits of the segments with a k-way merge.
+    ///
+    /// Each fruit is required to be sorted by decreasing feature, and by increasing document
+    /// address in case of a tie, as returned by [`TopSegmentCollector::harvest`]. The result is
+    /// the same as the one of [`TopCollector::merge_fruits`], but only the heads of the fruits
+    /// are kept in a heap, and only the returned documents are visited.
+    pub fn merge_sorted_fruits(
+        &self,
+        children: Vec<Vec<(T, DocAddress)>>,
+    ) -> crate::Result<Vec<(T, DocAddress)>> {
+        debug_assert!(children
+            .iter()
+            .all(|child_fruit| is_sorted_fruit(child_fruit)));
+        if self.limit == 0 {
+            return Ok(Vec::new());
+        }
+        let num_docs = self.limit + self.offset;
+        let mut children: Vec<std::vec::IntoIter<(T, DocAddress)>> =
+            children.into_iter().map(Vec::into_iter).collect();
+        // `ComparableDoc` is ordered as a min-heap: it is reversed to keep the best head on top.
+        let mut heads = BinaryHeap::with_capacity(children.len());
+        for (child_ord, child_fruit) in children.iter_mut().enumerate() {
+            if let Some((feature, doc)) = child_fruit.next() {
+                heads.push(Reverse(ComparableDoc {
+                    feature,
+                    doc: (doc, child_ord),
+                }));
+            }
+        }
+        let mut top_docs = Vec::with_capacity(self.limit);
+        for rank in 0..num_docs {
+            let head = match heads.pop() {
+                Some(Reverse(head)) => head,
+                None => break,
+            };
+            let (doc, child_ord) = head.doc;
+            if let Some((feature, doc)) = children[child_ord].next() {
+                heads.push(Reverse(ComparableDoc {
+                    feature,
+                    doc: (doc, child_ord),
+                }));
+            }
+            if rank >= self.offset {
+                top_docs.push((head.feature, doc));
+            }
+        }
+        Ok(top_docs)
+    }
+
     pub(crate) fn for_segment<F: PartialOrd>(
         &self,
         segment_id: SegmentOrdinal,
@@ -146,7 +213,7 @@ pub(crate) struct TopSegmentCollector<T> {
 }
 
 impl<T: PartialOrd> TopSegmentCollector<T> {
-    fn new(segment_ord: SegmentOrdinal, limit: usize) -> TopSegmentCollector<T> {
+    pub(crate) fn new(segment_ord: SegmentOrdinal, limit: usize) -> TopSegmentCollector<T> {
         TopSegmentCollector {
             limit,
             heap: BinaryHeap::with_capacity(limit),
@@ -179,6 +246,16 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
         self.heap.len() >= self.limit
     }
 
+    /// Returns the feature of the lowest ranked document, if more documents have been collected
+    /// than the limit.
+    #[inline]
+    pub(crate) fn limit_feature(&self) -> Option<&T> {
+        if !self.at_capacity() {
+            return None;
+        }
+        self.heap.peek().map(|head| &head.feature)
+    }
+
     /// Collects a document scored by the given feature
     ///
     /// It collects documents until it has reached the max capacity. Once it reaches capacity, it
@@ -205,7 +282,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::{TopCollector, TopSegmentCollector};
+    use super::{is_sorted_fruit, TopCollector, TopSegmentCollector};
     use crate::DocAddress;
 
     #[test]
@@ -301,6 +378,55 @@ mod tests {
         assert_eq!(results, vec![(0.8, DocAddress::new(0, 2)),]);
     }
 
+    #[test]
+    fn test_top_collector_merge_sorted_fruits() {
+        let children = || {
+            vec![
+                vec![
+                    (0.9, DocAddress::new(0, 1)),
+                    (0.5, DocAddress::new(0, 2)),
+                    (0.5, DocAddress::new(0, 4)),
+                    (0.1, DocAddress::new(0, 3)),
+                ],
+                vec![],
+                vec![
+                    (0.7, DocAddress::new(2, 5)),
+                    (0.5, DocAddress::new(2, 0)),
+                    (0.2, DocAddress::new(2, 1)),
+                ],
+            ]
+        };
+        assert!(children().iter().all(|fruit| is_sorted_fruit(fruit)));
+        for limit in 1..9 {
+            for offset in 0..4 {
+                let collector = TopCollector::with_limit(limit).and_offset(offset);
+                assert_eq!(
+                    collector.merge_sorted_fruits(children()).unwrap(),
+                    collector.merge_fruits(children()).unwrap(),
+                    "limit={limit}, offset={offset}"
+                );
+            }
+        }
+        let collector = TopCollector::with_limit(4).and_offset(1);
+        assert_eq!(
+            collector.merge_sorted_fruits(children()).unwrap(),
+            vec![
+                (0.7, DocAddress::new(2, 5)),
+                (0.5, DocAddress::new(0, 2)),
+                (0.5, DocAddress::new(0, 4)),
+                (0.5, DocAddress::new(2, 0)),
+            ]
+        );
+        assert!(!is_sorted_fruit(&[
+            (0.5, DocAddress::new(0, 2)),
+            (0.9, DocAddress::new(0, 1)),
+        ]));
+        assert!(!is_sorted_fruit(&[
+            (0.5, DocAddress::new(0, 2)),
+            (0.5, DocAddress::new(0, 1)),
+        ]));
+    }
+
     #[test]
     fn test_top_collector_with_limit_and_offset_larger_than_set() {
         let collector = TopCollector::with_limit(2).and_offset(20);
@@ -321,6 +447,7 @@ mod bench {
     use test::Bencher;
 
     use super::TopSegmentCollector;
+    use crate::DocAddress;
 
     #[bench]
     fn bench_top_segment_collector_collect_not_at_capacity(b: &mut Bencher) {
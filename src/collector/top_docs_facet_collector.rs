@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use super::facet_collector::FacetSegmentCollector;
+use super::top_score_collector::TopScoreSegmentCollector;
+use crate::collector::{Collector, FacetCollector, FacetCounts, SegmentCollector, TopDocs};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// Collector returning the top documents of a query, along with the facet counts of
+/// exactly these documents.
+///
+/// Contrary to combining a [`TopDocs`] and a [`FacetCollector`] in a tuple, where the facets
+/// are counted over all of the matching documents, the facets are only counted over the
+/// returned documents, e.g. to display the facets of the page of results a user sees.
+///
+/// The collection happens in two phases. Each segment first collects its own top documents,
+/// and only counts the facets of those once the segment is collected. The top documents of
+/// the segments are then merged, and the facet counts of the documents that are not part of
+/// the final top documents are discarded.
+///
+/// If fewer documents than the limit of the `TopDocs` match, the facets of all of the
+/// matching documents are counted.
+///
+/// ```rust
+/// use tantivy::collector::{FacetCollector, TopDocs, TopDocsFacetCollector};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Facet, FacetOptions, Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let category = schema_builder.add_facet_field("category", FacetOptions::default());
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(
+///     title => "rust rust rust",
+///     category => Facet::from("/category/book")
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "rust rust",
+///     category => Facet::from("/category/video")
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "rust",
+///     category => Facet::from("/category/video")
+/// ))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+/// let mut facet_collector = FacetCollector::for_field(category);
+/// facet_collector.add_facet("/category");
+/// let collector = TopDocsFacetCollector::new(TopDocs::with_limit(2), facet_collector);
+/// let (top_docs, facet_counts) = searcher.search(&query, &collector)?;
+/// assert_eq!(top_docs.len(), 2);
+/// // The least relevant video is not part of the top documents.
+/// let counts: Vec<(&Facet, u64)> = facet_counts.get("/category").collect();
+/// assert_eq!(
+///     counts,
+///     vec![
+///         (&Facet::from("/category/book"), 1),
+///         (&Facet::from("/category/video"), 1)
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TopDocsFacetCollector {
+    top_docs: TopDocs,
+    facet_collector: FacetCollector,
+}
+
+impl TopDocsFacetCollector {
+    /// Creates a `TopDocsFacetCollector` returning the documents of `top_docs`, and counting
+    /// their facets as configured in `facet_collector`.
+    pub fn new(top_docs: TopDocs, facet_collector: FacetCollector) -> TopDocsFacetCollector {
+        TopDocsFacetCollector {
+            top_docs,
+            facet_collector,
+        }
+    }
+}
+
+impl Collector for TopDocsFacetCollector {
+    type Fruit = (Vec<(Score, DocAddress)>, FacetCounts);
+
+    type Child = TopDocsFacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<TopDocsFacetSegmentCollector> {
+        let top_docs = self.top_docs.for_segment(segment_local_id, reader)?;
+        let facet_collector = self.facet_collector.for_segment(segment_local_id, reader)?;
+        Ok(TopDocsFacetSegmentCollector {
+            top_docs,
+            facet_collector,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(Score, DocAddress, FacetCounts)>>,
+    ) -> crate::Result<(Vec<(Score, DocAddress)>, FacetCounts)> {
+        let mut doc_facet_counts: HashMap<DocAddress, FacetCounts> = HashMap::new();
+        let mut segment_top_docs = Vec::with_capacity(segment_fruits.len());
+        for segment_fruit in segment_fruits {
+            let mut top_docs = Vec::with_capacity(segment_fruit.len());
+            for (score, doc_address, facet_counts) in segment_fruit {
+                top_docs.push((score, doc_address));
+                doc_facet_counts.insert(doc_address, facet_counts);
+            }
+            segment_top_docs.push(top_docs);
+        }
+        let top_docs = self.top_docs.merge_fruits(segment_top_docs)?;
+        let facet_counts = FacetCounts::merge(
+            top_docs
+                .iter()
+                .filter_map(|(_, doc_address)| doc_facet_counts.remove(doc_address)),
+        );
+        Ok((top_docs, facet_counts))
+    }
+}
+
+/// Segment collector of the [`TopDocsFacetCollector`].
+pub struct TopDocsFacetSegmentCollector {
+    top_docs: TopScoreSegmentCollector,
+    facet_collector: FacetSegmentCollector,
+}
+
+impl SegmentCollector for TopDocsFacetSegmentCollector {
+    type Fruit = Vec<(Score, DocAddress, FacetCounts)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.top_docs.collect(doc, score);
+    }
+
+    /// Returns the top documents of the segment, along with the facet counts of each of them.
+    fn harvest(self) -> Vec<(Score, DocAddress, FacetCounts)> {
+        let mut facet_collector = self.facet_collector;
+        self.top_docs
+            .harvest()
+            .into_iter()
+            .map(|(score, doc_address)| {
+                facet_collector.collect(doc_address.doc_id, score);
+                (score, doc_address, facet_collector.take_counts())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopDocsFacetCollector;
+    use crate::collector::{FacetCollector, TopDocs};
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Facet, FacetOptions, Schema, STORED, TEXT};
+    use crate::{Document, Index};
+
+    #[test]
+    fn test_top_docs_facet_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let docs = [
+            ("rust rust rust rust", vec!["/lang/en", "/category/book"]),
+            ("rust rust rust", vec!["/lang/fr", "/category/book"]),
+            ("rust rust", vec!["/lang/en", "/category/video"]),
+            ("rust", vec!["/lang/de", "/category/video"]),
+            ("java", vec!["/lang/en", "/category/book"]),
+        ];
+        for (i, (text, facets)) in docs.iter().enumerate() {
+            let mut doc = Document::default();
+            doc.add_text(title, text);
+            for facet_path in facets {
+                doc.add_facet(facet, *facet_path);
+            }
+            index_writer.add_document(doc)?;
+            // Spread the documents over several segments.
+            if i % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+        let facet_collector = || {
+            let mut facet_collector = FacetCollector::for_field(facet);
+            facet_collector.add_facet("/lang");
+            facet_collector.add_facet("/category");
+            facet_collector
+        };
+
+        let collector = TopDocsFacetCollector::new(TopDocs::with_limit(3), facet_collector());
+        let (top_docs, facet_counts) = searcher.search(&query, &collector)?;
+        let top_titles: Vec<String> = top_docs
+            .iter()
+            .map(|(_, doc_address)| {
+                let doc = searcher.doc(*doc_address)?;
+                Ok(doc.get_first(title).unwrap().as_text().unwrap().to_string())
+            })
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(
+            top_titles,
+            vec!["rust rust rust rust", "rust rust rust", "rust rust"]
+        );
+        // The counts are the ones of the facets of the top documents.
+        let lang_counts: Vec<(&Facet, u64)> = facet_counts.get("/lang").collect();
+        assert_eq!(
+            lang_counts,
+            vec![(&Facet::from("/lang/en"), 2), (&Facet::from("/lang/fr"), 1)]
+        );
+        let category_counts: Vec<(&Facet, u64)> = facet_counts.get("/category").collect();
+        assert_eq!(
+            category_counts,
+            vec![
+                (&Facet::from("/category/book"), 2),
+                (&Facet::from("/category/video"), 1)
+            ]
+        );
+
+        // The offset skips the facets of the skipped documents as well.
+        let collector =
+            TopDocsFacetCollector::new(TopDocs::with_limit(2).and_offset(2), facet_collector());
+        let (top_docs, facet_counts) = searcher.search(&query, &collector)?;
+        assert_eq!(top_docs.len(), 2);
+        let lang_counts: Vec<(&Facet, u64)> = facet_counts.get("/lang").collect();
+        assert_eq!(
+            lang_counts,
+            vec![(&Facet::from("/lang/de"), 1), (&Facet::from("/lang/en"), 1)]
+        );
+
+        // With fewer matching documents than the limit, the facets of all of the matching
+        // documents are counted.
+        let collector = TopDocsFacetCollector::new(TopDocs::with_limit(10), facet_collector());
+        let (top_docs, facet_counts) = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(top_docs.len(), 5);
+        let category_counts: Vec<(&Facet, u64)> = facet_counts.get("/category").collect();
+        assert_eq!(
+            category_counts,
+            vec![
+                (&Facet::from("/category/book"), 3),
+                (&Facet::from("/category/video"), 2)
+            ]
+        );
+        Ok(())
+    }
+}
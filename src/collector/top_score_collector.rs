@@ -7,15 +7,20 @@ use fastfield_codecs::Column;
 
 use super::Collector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
+use crate::collector::tie_break_top_collector::TieBreakTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
-    CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    CustomScorer, CustomSegmentScorer, DistributedCollector, ScoreSegmentTweaker, ScoreTweaker,
+    SegmentCollector,
 };
 use crate::fastfield::FastValue;
 use crate::query::Weight;
 use crate::schema::Field;
-use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+use crate::{
+    DocAddress, DocId, DocSet, Order, Score, SegmentOrdinal, SegmentReader, TantivyError,
+    TERMINATED,
+};
 
 struct FastFieldConvertCollector<
     TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
@@ -40,6 +45,43 @@ where
         segment_local_id: crate::SegmentOrdinal,
         segment: &SegmentReader,
     ) -> crate::Result<Self::Child> {
+        self.check_field_type(segment)?;
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        self.check_field_type(reader)?;
+        self.collector.collect_segment(weight, segment_ord, reader)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let raw_result = self.collector.merge_fruits(segment_fruits)?;
+        let transformed_result = raw_result
+            .into_iter()
+            .map(|(score, doc_address)| (TFastValue::from_u64(score), doc_address))
+            .collect::<Vec<_>>();
+        Ok(transformed_result)
+    }
+}
+
+impl<TCollector, TFastValue> FastFieldConvertCollector<TCollector, TFastValue>
+where
+    TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
+    TFastValue: FastValue,
+{
+    fn check_field_type(&self, segment: &SegmentReader) -> crate::Result<()> {
         let schema = segment.schema();
         let field_entry = schema.get_field_entry(self.field);
         if !field_entry.is_fast() {
@@ -58,23 +100,7 @@ where
                 requested_type
             )));
         }
-        self.collector.for_segment(segment_local_id, segment)
-    }
-
-    fn requires_scoring(&self) -> bool {
-        self.collector.requires_scoring()
-    }
-
-    fn merge_fruits(
-        &self,
-        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
-    ) -> crate::Result<Self::Fruit> {
-        let raw_result = self.collector.merge_fruits(segment_fruits)?;
-        let transformed_result = raw_result
-            .into_iter()
-            .map(|(score, doc_address)| (TFastValue::from_u64(score), doc_address))
-            .collect::<Vec<_>>();
-        Ok(transformed_result)
+        Ok(())
     }
 }
 
@@ -145,6 +171,136 @@ struct ScorerByField {
     field: Field,
 }
 
+/// Collects the top-K documents by decreasing value of a fast field.
+///
+/// If the index is sorted by decreasing value of the same field, the documents of each
+/// segment are visited by decreasing value, and the collection of a segment stops as soon as
+/// `K` documents have been collected. Otherwise, all of the matching documents are visited.
+///
+/// If all of the segments are sorted by the field, their top documents are merged with a
+/// k-way merge, rather than by pushing all of them in a top-K heap.
+struct TopDocsByFieldCollector {
+    field: Field,
+    collector: CustomScoreTopCollector<ScorerByField, u64>,
+    top_collector: TopCollector<u64>,
+    num_docs_per_segment: usize,
+}
+
+impl TopDocsByFieldCollector {
+    fn is_sorted_by_field(&self, segment_reader: &SegmentReader) -> bool {
+        segment_reader
+            .sort_by_field()
+            .map(|sort_by_field| {
+                sort_by_field.order.is_desc()
+                    && segment_reader.schema().get_field(&sort_by_field.field) == Some(self.field)
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl Collector for TopDocsByFieldCollector {
+    type Fruit = Vec<(u64, DocAddress)>;
+
+    type Child = TopDocsByFieldSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let segment_collector = self
+            .collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(TopDocsByFieldSegmentCollector {
+            segment_collector,
+            is_sorted_by_field: self.is_sorted_by_field(segment_reader),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<TopDocsByFieldSegmentFruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let are_sorted_by_field = segment_fruits
+            .iter()
+            .all(|segment_fruit| segment_fruit.is_sorted_by_field);
+        let segment_top_docs: Vec<Vec<(u64, DocAddress)>> = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| segment_fruit.top_docs)
+            .collect();
+        if are_sorted_by_field {
+            return self.top_collector.merge_sorted_fruits(segment_top_docs);
+        }
+        self.collector.merge_fruits(segment_top_docs)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<TopDocsByFieldSegmentFruit> {
+        if !self.is_sorted_by_field(reader) {
+            let top_docs = self
+                .collector
+                .collect_segment(weight, segment_ord, reader)?;
+            return Ok(TopDocsByFieldSegmentFruit {
+                top_docs,
+                is_sorted_by_field: false,
+            });
+        }
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        let mut scorer = weight.scorer(reader, 1.0)?;
+        let alive_bitset_opt = reader.alive_bitset();
+        // Documents are visited by decreasing field value, and ties are visited by increasing
+        // doc id, as they are ranked by the collector: the first alive documents are the top
+        // documents of the segment.
+        let mut num_collected = 0;
+        let mut doc = scorer.doc();
+        while doc != TERMINATED && num_collected < self.num_docs_per_segment {
+            let is_alive = alive_bitset_opt
+                .map(|alive_bitset| alive_bitset.is_alive(doc))
+                .unwrap_or(true);
+            if is_alive {
+                segment_collector.collect(doc, scorer.score());
+                num_collected += 1;
+            }
+            doc = scorer.advance();
+        }
+        Ok(segment_collector.harvest())
+    }
+}
+
+/// Top documents of a segment, sorted by decreasing field value.
+struct TopDocsByFieldSegmentFruit {
+    top_docs: Vec<(u64, DocAddress)>,
+    is_sorted_by_field: bool,
+}
+
+struct TopDocsByFieldSegmentCollector {
+    segment_collector: <CustomScoreTopCollector<ScorerByField, u64> as Collector>::Child,
+    is_sorted_by_field: bool,
+}
+
+impl SegmentCollector for TopDocsByFieldSegmentCollector {
+    type Fruit = TopDocsByFieldSegmentFruit;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> TopDocsByFieldSegmentFruit {
+        TopDocsByFieldSegmentFruit {
+            top_docs: self.segment_collector.harvest(),
+            is_sorted_by_field: self.is_sorted_by_field,
+        }
+    }
+}
+
 impl CustomScorer<u64> for ScorerByField {
     type Child = ScorerByFastFieldReader;
 
@@ -224,6 +380,12 @@ impl TopDocs {
     /// will return returns a monotonic u64-representation (ie. the order is still correct) of
     /// the requested field type.
     ///
+    /// If the index is sorted by decreasing value of the field (see
+    /// [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field)), the collection of
+    /// a segment stops as soon as `limit + offset` documents have been collected, and the top
+    /// documents of the segments, already sorted, are merged with a k-way merge. Otherwise, they
+    /// are merged with a heap.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -292,7 +454,18 @@ impl TopDocs {
         self,
         field: Field,
     ) -> impl Collector<Fruit = Vec<(u64, DocAddress)>> {
-        CustomScoreTopCollector::new(ScorerByField { field }, self.0.into_tscore())
+        self.top_docs_by_field_collector(field)
+    }
+
+    fn top_docs_by_field_collector(self, field: Field) -> TopDocsByFieldCollector {
+        let num_docs_per_segment = self.0.limit + self.0.offset;
+        let top_collector = TopCollector::with_limit(self.0.limit).and_offset(self.0.offset);
+        TopDocsByFieldCollector {
+            field,
+            collector: CustomScoreTopCollector::new(ScorerByField { field }, self.0.into_tscore()),
+            top_collector,
+            num_docs_per_segment,
+        }
     }
 
     /// Set top-K to rank documents by a given fast field.
@@ -307,6 +480,9 @@ impl TopDocs {
     /// Implementation-wise, for performance reason, tantivy will manipulate the u64 representation
     /// of your fast field until the last moment.
     ///
+    /// Like [.order_by_u64_field(...)](#method.order_by_u64_field), the collection of a segment
+    /// stops early if the index is sorted by decreasing value of the field.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -380,6 +556,55 @@ impl TopDocs {
         }
     }
 
+    /// Breaks the ties on the score of the documents using the values of fast fields.
+    ///
+    /// By default, documents with the same score are ranked by ascending [`DocAddress`], which
+    /// is stable but rarely meaningful. With tie-breakers, documents with the same score are
+    /// ranked by the value of the first tie-breaking field, in the given [`Order`], then by the
+    /// value of the second one in case of a new tie, and so on. Documents tied on all of the
+    /// tie-breakers are still ranked by ascending `DocAddress`.
+    ///
+    /// The tie-breaking fields need to be single-valued fast fields. Otherwise, an error is
+    /// returned at the moment of search. Their values are only read for the documents whose
+    /// score is high enough to enter the top documents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, FAST, TEXT};
+    /// use tantivy::{doc, DocAddress, Index, Order};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let year = schema_builder.add_u64_field("year", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    ///
+    /// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Name of the Wind", year => 2007u64))?;
+    /// index_writer.add_document(doc!(title => "The Wise Man's Fear", year => 2011u64))?;
+    /// index_writer.add_document(doc!(title => "The Slow Regard of Silent Things", year => 2014u64))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// // All of the documents have the same score: the most recent ones come first.
+    /// let top_docs = searcher.search(
+    ///     &AllQuery,
+    ///     &TopDocs::with_limit(2).tie_break_by_fast_fields(vec![(year, Order::Desc)]),
+    /// )?;
+    /// assert_eq!(top_docs, vec![(1.0, DocAddress::new(0, 2)), (1.0, DocAddress::new(0, 1))]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tie_break_by_fast_fields(
+        self,
+        tie_breakers: Vec<(Field, Order)>,
+    ) -> impl Collector<Fruit = Vec<(Score, DocAddress)>> {
+        TieBreakTopCollector::new(tie_breakers, self.0.into_tscore())
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -694,6 +919,15 @@ impl Collector for TopDocs {
     }
 }
 
+impl DistributedCollector for TopDocs {
+    fn merge_distributed_fruits(
+        &self,
+        fruits: Vec<Vec<(Score, DocAddress)>>,
+    ) -> crate::Result<Self::Fruit> {
+        self.0.merge_fruits(fruits)
+    }
+}
+
 /// Segment Collector associated to `TopDocs`.
 pub struct TopScoreSegmentCollector(TopSegmentCollector<Score>);
 
@@ -711,13 +945,20 @@ impl SegmentCollector for TopScoreSegmentCollector {
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Reverse;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
     use super::TopDocs;
     use crate::collector::Collector;
-    use crate::query::{AllQuery, Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::query::{AllQuery, Explanation, Query, QueryParser, Scorer, Weight};
+    use crate::schema::{Field, Schema, FAST, INDEXED, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
-    use crate::{DateTime, DocAddress, DocId, Index, IndexWriter, Score, SegmentReader};
+    use crate::{
+        DateTime, DocAddress, DocId, DocSet, Index, IndexSettings, IndexSortByField, IndexWriter,
+        Order, Score, Searcher, SegmentReader, Term,
+    };
 
     fn make_index() -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
@@ -1102,4 +1343,313 @@ mod tests {
         let query = query_parser.parse_query(query).unwrap();
         (index, query)
     }
+
+    /// `AllQuery`, counting the number of times its scorers advance.
+    #[derive(Clone, Debug, Default)]
+    struct AdvanceCountingQuery {
+        num_advances: Arc<AtomicUsize>,
+    }
+
+    struct AdvanceCountingWeight {
+        weight: Box<dyn Weight>,
+        num_advances: Arc<AtomicUsize>,
+    }
+
+    struct AdvanceCountingScorer {
+        scorer: Box<dyn Scorer>,
+        num_advances: Arc<AtomicUsize>,
+    }
+
+    impl Query for AdvanceCountingQuery {
+        fn weight(
+            &self,
+            searcher: &Searcher,
+            scoring_enabled: bool,
+        ) -> crate::Result<Box<dyn Weight>> {
+            Ok(Box::new(AdvanceCountingWeight {
+                weight: AllQuery.weight(searcher, scoring_enabled)?,
+                num_advances: self.num_advances.clone(),
+            }))
+        }
+    }
+
+    impl Weight for AdvanceCountingWeight {
+        fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+            Ok(Box::new(AdvanceCountingScorer {
+                scorer: self.weight.scorer(reader, boost)?,
+                num_advances: self.num_advances.clone(),
+            }))
+        }
+
+        fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+            self.weight.explain(reader, doc)
+        }
+    }
+
+    impl DocSet for AdvanceCountingScorer {
+        fn advance(&mut self) -> DocId {
+            self.num_advances.fetch_add(1, Ordering::SeqCst);
+            self.scorer.advance()
+        }
+
+        fn doc(&self) -> DocId {
+            self.scorer.doc()
+        }
+
+        fn size_hint(&self) -> u32 {
+            self.scorer.size_hint()
+        }
+    }
+
+    impl Scorer for AdvanceCountingScorer {
+        fn score(&mut self) -> Score {
+            self.scorer.score()
+        }
+    }
+
+    #[test]
+    fn test_top_field_collector_early_termination_on_sorted_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let rating = schema_builder.add_u64_field("rating", FAST | INDEXED);
+        let schema = schema_builder.build();
+        let create_index = |order_opt: Option<Order>| -> crate::Result<Index> {
+            let settings = IndexSettings {
+                sort_by_field: order_opt.map(|order| IndexSortByField {
+                    field: "rating".to_string(),
+                    order,
+                }),
+                ..Default::default()
+            };
+            let index = Index::builder()
+                .schema(schema.clone())
+                .settings(settings)
+                .create_in_ram()?;
+            let mut index_writer = index.writer_for_tests()?;
+            // The first segment contains the even ratings within [0, 200), the second segment
+            // the odd ones, in a shuffled order.
+            for segment in 0..2u64 {
+                for i in 0..100u64 {
+                    index_writer.add_document(doc!(rating => (i * 37) % 100 * 2 + segment))?;
+                }
+                index_writer.commit()?;
+            }
+            // Deletes the top document of the second segment.
+            index_writer.delete_term(Term::from_field_u64(rating, 199));
+            index_writer.commit()?;
+            Ok(index)
+        };
+        // (order of the index, expected number of advances)
+        let test_cases = [
+            (None, 200),
+            (Some(Order::Asc), 200),
+            // 3 advances in the first segment and 4 in the second one, as its first document
+            // is deleted.
+            (Some(Order::Desc), 7),
+        ];
+        for (order_opt, expected_num_advances) in test_cases {
+            let index = create_index(order_opt.clone())?;
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.num_docs(), 199);
+            let query = AdvanceCountingQuery::default();
+            let top_docs = searcher.search(
+                &query,
+                &TopDocs::with_limit(2)
+                    .and_offset(1)
+                    .order_by_fast_field::<u64>(rating),
+            )?;
+            let top_ratings: Vec<u64> = top_docs.iter().map(|(rating, _)| *rating).collect();
+            assert_eq!(top_ratings, vec![197, 196], "{:?}", order_opt);
+            assert_eq!(
+                query.num_advances.load(Ordering::SeqCst),
+                expected_num_advances,
+                "{:?}",
+                order_opt
+            );
+            let top_docs =
+                searcher.search(&query, &TopDocs::with_limit(3).order_by_u64_field(rating))?;
+            let top_ratings: Vec<u64> = top_docs.iter().map(|(rating, _)| *rating).collect();
+            assert_eq!(top_ratings, vec![198, 197, 196], "{:?}", order_opt);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_field_collector_merge_on_sorted_segments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let rating = schema_builder.add_u64_field("rating", FAST);
+        let schema = schema_builder.build();
+        for order in [Order::Desc, Order::Asc] {
+            let settings = IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "rating".to_string(),
+                    order: order.clone(),
+                }),
+                ..Default::default()
+            };
+            let index = Index::builder()
+                .schema(schema.clone())
+                .settings(settings)
+                .create_in_ram()?;
+            let mut index_writer = index.writer_for_tests()?;
+            for segment in 0..4u64 {
+                // Many ties, within and across the segments.
+                for i in 0..50u64 {
+                    index_writer.add_document(doc!(rating => (i * 13 + segment) % 20))?;
+                }
+                index_writer.commit()?;
+            }
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), 4);
+            // Only the segments sorted by decreasing rating are merged with a k-way merge.
+            let weight = AllQuery.weight(&searcher, false)?;
+            let collector = TopDocs::with_limit(5)
+                .and_offset(2)
+                .top_docs_by_field_collector(rating);
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                let segment_fruit = collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord as u32,
+                    segment_reader,
+                )?;
+                assert_eq!(segment_fruit.is_sorted_by_field, order.is_desc());
+                assert_eq!(segment_fruit.top_docs.len(), 7);
+            }
+            // Ranks all of the documents by decreasing rating and increasing address.
+            let mut all_docs: Vec<(u64, DocAddress)> = Vec::new();
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                let ff_reader = segment_reader.fast_fields().u64(rating)?;
+                for doc in 0..segment_reader.max_doc() {
+                    let rating_val = ff_reader.get_val(doc as u64);
+                    all_docs.push((rating_val, DocAddress::new(segment_ord as u32, doc)));
+                }
+            }
+            all_docs.sort_by_key(|(rating_val, doc_address)| (Reverse(*rating_val), *doc_address));
+            for (limit, offset) in [(1, 0), (5, 0), (7, 3), (30, 12), (250, 0)] {
+                let top_docs = searcher.search(
+                    &AllQuery,
+                    &TopDocs::with_limit(limit)
+                        .and_offset(offset)
+                        .order_by_u64_field(rating),
+                )?;
+                let expected: Vec<(u64, DocAddress)> =
+                    all_docs.iter().skip(offset).take(limit).cloned().collect();
+                assert_eq!(
+                    top_docs, expected,
+                    "order={order:?}, limit={limit}, offset={offset}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_tie_break_by_fast_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let bucket = schema_builder.add_u64_field("bucket", FAST);
+        let rank = schema_builder.add_i64_field("rank", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            index_writer.add_document(doc!(bucket => i % 3, rank => (i * 7 % 10) as i64 - 5))?;
+            // Spread the documents over several segments.
+            if i % 25 == 24 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+
+        // All of the documents have the same score: they are ranked by ascending bucket, then
+        // by descending rank, then by ascending doc address.
+        let mut expected_docs = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let bucket_reader = segment_reader.fast_fields().u64(bucket)?;
+            let rank_reader = segment_reader.fast_fields().i64(rank)?;
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc_address = DocAddress::new(segment_ord as u32, doc_id);
+                expected_docs.push((
+                    bucket_reader.get_val(doc_id as u64),
+                    -rank_reader.get_val(doc_id as u64),
+                    doc_address,
+                ));
+            }
+        }
+        expected_docs.sort();
+        let expected_doc_addresses: Vec<DocAddress> = expected_docs[5..25]
+            .iter()
+            .map(|(_, _, doc_address)| *doc_address)
+            .collect();
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(20)
+                .and_offset(5)
+                .tie_break_by_fast_fields(vec![(bucket, Order::Asc), (rank, Order::Desc)]),
+        )?;
+        assert!(top_docs.iter().all(|(score, _)| *score == 1.0));
+        let doc_addresses: Vec<DocAddress> = top_docs
+            .iter()
+            .map(|(_, doc_address)| *doc_address)
+            .collect();
+        assert_eq!(doc_addresses, expected_doc_addresses);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_tie_break_only_applies_to_ties() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let rating = schema_builder.add_u64_field("rating", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "droopy", rating => 1u64))?;
+        index_writer.add_document(doc!(text_field => "droopy droopy", rating => 2u64))?;
+        index_writer.add_document(doc!(text_field => "droopy", rating => 3u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field]).parse_query("droopy")?;
+        let top_docs = searcher.search(
+            &query,
+            &TopDocs::with_limit(3).tie_break_by_fast_fields(vec![(rating, Order::Desc)]),
+        )?;
+        let doc_addresses: Vec<DocAddress> = top_docs
+            .iter()
+            .map(|(_, doc_address)| *doc_address)
+            .collect();
+        assert_eq!(
+            doc_addresses,
+            vec![
+                DocAddress::new(0, 1),
+                DocAddress::new(0, 2),
+                DocAddress::new(0, 0)
+            ]
+        );
+        assert_eq!(
+            searcher.search(&query, &TopDocs::with_limit(3))?,
+            top_docs
+                .iter()
+                .map(|(score, _)| *score)
+                .zip([1, 0, 2].map(|doc_id| DocAddress::new(0, doc_id)))
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_tie_break_not_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "droopy"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let top_docs =
+            TopDocs::with_limit(3).tie_break_by_fast_fields(vec![(text_field, Order::Asc)]);
+        let err = searcher.search(&AllQuery, &top_docs).unwrap_err();
+        assert!(
+            matches!(err, crate::TantivyError::SchemaError(msg) if msg.contains("not a single-valued fast field"))
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,227 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{type_and_cardinality, MultiValuedFastFieldReader};
+use crate::schema::{Cardinality, Field, FieldType};
+use crate::{DocId, InvertedIndexReader, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// The `TopTermsCollector` returns the terms of a text field found in the largest number of
+/// matching documents, along with their number of matching documents.
+///
+/// The field must be indexed and declared as a fast field, e.g. with `STRING | FAST`. The
+/// term ordinals of the matching documents are counted using the fast field, and only
+/// resolved to their term once per segment, before the counts of the segments are merged.
+///
+/// A document having several times the same term is counted once for this term. Terms with
+/// the same count are sorted by their text.
+///
+/// ```rust
+/// use tantivy::collector::TopTermsCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STRING};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let tag = schema_builder.add_text_field("tag", STRING | FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(tag => "rust", tag => "search"))?;
+/// index_writer.add_document(doc!(tag => "rust"))?;
+/// index_writer.add_document(doc!(tag => "java", tag => "search"))?;
+/// index_writer.add_document(doc!(tag => "rust"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let top_terms = searcher.search(&AllQuery, &TopTermsCollector::new(tag, 2))?;
+/// assert_eq!(
+///     top_terms,
+///     vec![("rust".to_string(), 3), ("search".to_string(), 2)]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TopTermsCollector {
+    field: Field,
+    limit: usize,
+}
+
+impl TopTermsCollector {
+    /// Creates a `TopTermsCollector` returning the `limit` terms of `field` found in the largest
+    /// number of matching documents.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn new(field: Field, limit: usize) -> TopTermsCollector {
+        assert!(limit >= 1, "Limit must be strictly greater than 0.");
+        TopTermsCollector { field, limit }
+    }
+}
+
+impl Collector for TopTermsCollector {
+    type Fruit = Vec<(String, u64)>;
+
+    type Child = TopTermsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<TopTermsSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let is_str_fast_field = matches!(field_entry.field_type(), FieldType::Str(_))
+            && matches!(
+                type_and_cardinality(field_entry.field_type()),
+                Some((_, Cardinality::MultiValues))
+            );
+        if !is_str_fast_field || !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not an indexed text fast field.",
+                field_entry.name()
+            )));
+        }
+        let term_ords = segment_reader.fast_fields().u64s_lenient(self.field)?;
+        let inverted_index = segment_reader.inverted_index(self.field)?;
+        let num_terms = inverted_index.terms().num_terms();
+        Ok(TopTermsSegmentCollector {
+            term_ords,
+            inverted_index,
+            counts: vec![0; num_terms],
+            doc_term_ords: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_counts: Vec<HashMap<String, u64>>,
+    ) -> crate::Result<Vec<(String, u64)>> {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for segment_counts in segment_counts {
+            for (term, count) in segment_counts {
+                *counts.entry(term).or_insert(0) += count;
+            }
+        }
+        let mut top_terms: Vec<(String, u64)> = counts.into_iter().collect();
+        top_terms.sort_unstable_by(|(left_term, left_count), (right_term, right_count)| {
+            (Reverse(left_count), left_term).cmp(&(Reverse(right_count), right_term))
+        });
+        top_terms.truncate(self.limit);
+        Ok(top_terms)
+    }
+}
+
+/// Segment collector of the [`TopTermsCollector`].
+///
+/// It counts the matching documents per term ordinal of the segment.
+pub struct TopTermsSegmentCollector {
+    term_ords: MultiValuedFastFieldReader<u64>,
+    inverted_index: Arc<InvertedIndexReader>,
+    counts: Vec<u64>,
+    doc_term_ords: Vec<u64>,
+}
+
+impl SegmentCollector for TopTermsSegmentCollector {
+    type Fruit = HashMap<String, u64>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.term_ords.get_vals(doc, &mut self.doc_term_ords);
+        self.doc_term_ords.sort_unstable();
+        self.doc_term_ords.dedup();
+        for &term_ord in &self.doc_term_ords {
+            self.counts[term_ord as usize] += 1;
+        }
+    }
+
+    fn harvest(self) -> HashMap<String, u64> {
+        let mut term_counts = HashMap::new();
+        let mut term_bytes = Vec::new();
+        for (term_ord, &count) in self.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            // The ordinals of the fast field are the ones of the term dictionary.
+            let found = self
+                .inverted_index
+                .terms()
+                .ord_to_term(term_ord as u64, &mut term_bytes)
+                .unwrap_or(false);
+            if found {
+                let term = String::from_utf8_lossy(&term_bytes).into_owned();
+                term_counts.insert(term, count);
+            }
+        }
+        term_counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopTermsCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_top_terms_collector_multi_segment() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(kind => "book", tag => "rust", tag => "search"))?;
+        index_writer.add_document(doc!(kind => "book", tag => "rust", tag => "rust"))?;
+        index_writer.commit()?;
+        // The ordinals of this segment differ from the ones of the first one.
+        index_writer.add_document(doc!(kind => "book", tag => "java", tag => "search"))?;
+        index_writer.add_document(doc!(kind => "video", tag => "rust"))?;
+        index_writer.add_document(doc!(kind => "book", tag => "zig", tag => "search"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(kind => "video"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let top_terms = searcher.search(&AllQuery, &TopTermsCollector::new(tag, 2))?;
+        assert_eq!(
+            top_terms,
+            vec![("rust".to_string(), 3), ("search".to_string(), 3)]
+        );
+        let top_terms = searcher.search(&AllQuery, &TopTermsCollector::new(tag, 10))?;
+        assert_eq!(
+            top_terms,
+            vec![
+                ("rust".to_string(), 3),
+                ("search".to_string(), 3),
+                ("java".to_string(), 1),
+                ("zig".to_string(), 1),
+            ]
+        );
+
+        let books = TermQuery::new(
+            Term::from_field_text(kind, "book"),
+            IndexRecordOption::Basic,
+        );
+        let top_terms = searcher.search(&books, &TopTermsCollector::new(tag, 2))?;
+        assert_eq!(
+            top_terms,
+            vec![("search".to_string(), 3), ("rust".to_string(), 2)]
+        );
+
+        for field in [kind, title] {
+            assert!(matches!(
+                searcher.search(&AllQuery, &TopTermsCollector::new(field, 2)),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        Ok(())
+    }
+}
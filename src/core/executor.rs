@@ -31,6 +31,18 @@ impl Executor {
         Ok(Executor::ThreadPool(pool))
     }
 
+    /// Runs a task in the background.
+    ///
+    /// For the `ThreadPool` variant, the task is spawned in the thread pool.
+    /// The `SingleThread` variant cannot run anything in the background without
+    /// blocking the caller thread, so the task is spawned in rayon's global thread pool.
+    pub(crate) fn spawn<F: FnOnce() + Send + 'static>(&self, task: F) {
+        match self {
+            Executor::SingleThread => rayon::spawn(task),
+            Executor::ThreadPool(pool) => pool.spawn(task),
+        }
+    }
+
     /// Perform a map in the thread pool.
     ///
     /// Regardless of the executor (`SingleThread` or `ThreadPool`), panics in the task
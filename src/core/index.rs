@@ -9,7 +9,7 @@ use super::segment::Segment;
 use super::IndexSettings;
 use crate::core::single_segment_index_writer::SingleSegmentIndexWriter;
 use crate::core::{
-    Executor, IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory, META_FILEPATH,
+    Executor, IndexMeta, IndexSnapshot, SegmentId, SegmentMeta, SegmentMetaInventory, META_FILEPATH,
 };
 use crate::directory::error::OpenReadError;
 #[cfg(feature = "mmap")]
@@ -103,7 +103,7 @@ fn save_new_metas(
 pub struct IndexBuilder {
     schema: Option<Schema>,
     index_settings: IndexSettings,
-    tokenizer_manager: TokenizerManager,
+    tokenizer_manager: Option<TokenizerManager>,
 }
 impl Default for IndexBuilder {
     fn default() -> Self {
@@ -116,7 +116,7 @@ impl IndexBuilder {
         Self {
             schema: None,
             index_settings: IndexSettings::default(),
-            tokenizer_manager: TokenizerManager::default(),
+            tokenizer_manager: None,
         }
     }
 
@@ -136,7 +136,7 @@ impl IndexBuilder {
 
     /// Set the tokenizers .
     pub fn tokenizers(mut self, tokenizers: TokenizerManager) -> Self {
-        self.tokenizer_manager = tokenizers;
+        self.tokenizer_manager = Some(tokenizers);
         self
     }
 
@@ -204,14 +204,34 @@ impl IndexBuilder {
             .ok_or(TantivyError::IndexBuilderMissingArgument("schema"))
     }
 
+    /// Opens an existing index in the provided directory, using the tokenizers of the builder.
+    ///
+    /// The schema of the builder, if any, is ignored.
+    ///
+    /// Returns a `SchemaError` if a tokenizer used by one of the fields of the index
+    /// is not registered (See [`Index::check_tokenizers`]).
+    pub fn open<T: Into<Box<dyn Directory>>>(self, dir: T) -> crate::Result<Index> {
+        let mut index = Index::open(dir)?;
+        index.set_tokenizers(self.tokenizer_manager.unwrap_or_default());
+        index.check_tokenizers()?;
+        Ok(index)
+    }
+
     /// Opens or creates a new index in the provided directory
+    ///
+    /// If the index already exists and tokenizers were set with [`IndexBuilder::tokenizers`],
+    /// returns a `SchemaError` if a tokenizer used by one of its fields is not registered
+    /// (See [`Index::check_tokenizers`]).
     pub fn open_or_create<T: Into<Box<dyn Directory>>>(self, dir: T) -> crate::Result<Index> {
         let dir = dir.into();
         if !Index::exists(&*dir)? {
             return self.create(dir);
         }
         let mut index = Index::open(dir)?;
-        index.set_tokenizers(self.tokenizer_manager.clone());
+        if let Some(tokenizer_manager) = self.tokenizer_manager.clone() {
+            index.set_tokenizers(tokenizer_manager);
+            index.check_tokenizers()?;
+        }
         if index.schema() == self.get_expect_schema()? {
             Ok(index)
         } else {
@@ -234,7 +254,7 @@ impl IndexBuilder {
         let mut metas = IndexMeta::with_schema(self.get_expect_schema()?);
         metas.index_settings = self.index_settings;
         let mut index = Index::open_from_metas(directory, &metas, SegmentMetaInventory::default());
-        index.set_tokenizers(self.tokenizer_manager);
+        index.set_tokenizers(self.tokenizer_manager.unwrap_or_default());
         Ok(index)
     }
 }
@@ -362,6 +382,27 @@ impl Index {
         }
     }
 
+    /// Returns a copy of this index, with a different schema.
+    ///
+    /// The directory, the settings, the tokenizers and the segment inventory are shared
+    /// with the original index.
+    pub(crate) fn with_schema(&self, schema: Schema) -> Index {
+        Index {
+            schema,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the index with its settings replaced by `settings`.
+    ///
+    /// See [`Index::with_schema`].
+    pub(crate) fn with_settings(&self, settings: IndexSettings) -> Index {
+        Index {
+            settings,
+            ..self.clone()
+        }
+    }
+
     /// Setter for the tokenizer manager.
     pub fn set_tokenizers(&mut self, tokenizers: TokenizerManager) {
         self.tokenizers = tokenizers;
@@ -372,7 +413,50 @@ impl Index {
         &self.tokenizers
     }
 
+    /// Checks that all of the tokenizers used by the fields of the schema are registered
+    /// in the tokenizer manager.
+    ///
+    /// The tokenizer names are persisted in the schema, but the tokenizers themselves are not.
+    /// When reopening an index that relies on custom tokenizers, they have to be registered
+    /// again with the same names, otherwise documents would be indexed with the default
+    /// tokenizer and queries analyzed differently from the indexed documents.
+    ///
+    /// This check is run by [`IndexBuilder::open`], and by [`IndexBuilder::open_or_create`] when
+    /// tokenizers were set with [`IndexBuilder::tokenizers`].
+    /// When opening an index with [`Index::open`], tokenizers are registered after opening,
+    /// so this method should be called once they have been registered.
+    pub fn check_tokenizers(&self) -> crate::Result<()> {
+        for (_, field_entry) in self.schema.fields() {
+            let indexing_options_opt = match field_entry.field_type() {
+                FieldType::JsonObject(options) => options.get_text_indexing_options(),
+                FieldType::Str(options) => options.get_indexing_options(),
+                _ => None,
+            };
+            if let Some(indexing_options) = indexing_options_opt {
+                let tokenizer_names = [
+                    indexing_options.tokenizer(),
+                    indexing_options.search_tokenizer(),
+                ];
+                for tokenizer_name in tokenizer_names {
+                    if self.tokenizers.get(tokenizer_name).is_none() {
+                        return Err(TantivyError::SchemaError(format!(
+                            "Tokenizer {:?} used by field {:?} is not registered. Custom \
+                             tokenizers need to be registered with the name used at indexing time.",
+                            tokenizer_name,
+                            field_entry.name()
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Helper to access the tokenizer associated to a specific field.
+    ///
+    /// This is the tokenizer used at indexing time. See
+    /// [`TextFieldIndexing::search_tokenizer`](crate::schema::TextFieldIndexing::search_tokenizer)
+    /// for the tokenizer used by the queries.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
@@ -590,6 +674,13 @@ impl Index {
         Ok(self.load_metas()?.segments)
     }
 
+    /// Takes a snapshot of the last commit of the index, listing its segment files.
+    ///
+    /// See [`IndexSnapshot`] for how to use it for incremental backups.
+    pub fn snapshot(&self) -> crate::Result<IndexSnapshot> {
+        IndexSnapshot::create(self)
+    }
+
     /// Returns the list of segment ids that are searchable.
     pub fn searchable_segment_ids(&self) -> crate::Result<Vec<SegmentId>> {
         Ok(self
@@ -631,9 +722,14 @@ mod tests {
     use crate::collector::Count;
     use crate::directory::{RamDirectory, WatchCallback};
     use crate::query::TermQuery;
-    use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, TEXT};
-    use crate::tokenizer::TokenizerManager;
-    use crate::{Directory, Index, IndexBuilder, IndexReader, IndexSettings, ReloadPolicy, Term};
+    use crate::schema::{
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, INDEXED, TEXT,
+    };
+    use crate::tokenizer::{NgramTokenizer, TokenizerManager};
+    use crate::{
+        Directory, Index, IndexBuilder, IndexReader, IndexSettings, ReloadPolicy, TantivyError,
+        Term,
+    };
 
     #[test]
     fn test_indexer_for_field() {
@@ -664,6 +760,55 @@ mod tests {
         assert!(index.tokenizers().get("raw").is_none());
     }
 
+    #[test]
+    fn test_open_with_missing_tokenizer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("custom_tokenizer"));
+        schema_builder.add_text_field("title", text_options);
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema.clone(), IndexSettings::default())?;
+        assert!(index.check_tokenizers().is_err());
+        index
+            .tokenizers()
+            .register("custom_tokenizer", NgramTokenizer::new(1, 2, false));
+        assert!(index.check_tokenizers().is_ok());
+
+        let err = IndexBuilder::new().open(directory.clone()).err().unwrap();
+        assert_eq!(
+            format!("{}", err),
+            "Schema error: 'Tokenizer \"custom_tokenizer\" used by field \"title\" is not \
+             registered. Custom tokenizers need to be registered with the name used at indexing \
+             time.'"
+        );
+        assert!(matches!(
+            IndexBuilder::new()
+                .tokenizers(TokenizerManager::default())
+                .schema(schema.clone())
+                .open_or_create(directory.clone()),
+            Err(TantivyError::SchemaError(_))
+        ));
+        // Without tokenizers, `open_or_create` does not check them, so that they can be
+        // registered after opening.
+        let index = Index::open_or_create(directory.clone(), schema.clone())?;
+        assert!(index.tokenizers().get("custom_tokenizer").is_none());
+
+        let tokenizers = TokenizerManager::default();
+        tokenizers.register("custom_tokenizer", NgramTokenizer::new(1, 2, false));
+        let index = IndexBuilder::new()
+            .tokenizers(tokenizers.clone())
+            .open(directory.clone())?;
+        assert!(index.tokenizers().get("custom_tokenizer").is_some());
+        assert!(IndexBuilder::new()
+            .tokenizers(tokenizers)
+            .schema(schema)
+            .open_or_create(directory)
+            .is_ok());
+        Ok(())
+    }
+
     #[test]
     fn test_index_exists() {
         let directory: Box<dyn Directory> = Box::new(RamDirectory::create());
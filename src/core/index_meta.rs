@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
@@ -39,6 +39,7 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            user_metadata: BTreeMap::new(),
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -111,20 +112,19 @@ impl SegmentMeta {
     /// is by removing all files that have been created by tantivy
     /// and are not used by any segment anymore.
     pub fn list_files(&self) -> HashSet<PathBuf> {
-        if self
+        let include_temp_doc_store = self
             .tracked
             .include_temp_doc_store
-            .load(std::sync::atomic::Ordering::Relaxed)
-        {
-            SegmentComponent::iterator()
-                .map(|component| self.relative_path(*component))
-                .collect::<HashSet<PathBuf>>()
-        } else {
-            SegmentComponent::iterator()
-                .filter(|comp| *comp != &SegmentComponent::TempStore)
-                .map(|component| self.relative_path(*component))
-                .collect::<HashSet<PathBuf>>()
-        }
+            .load(std::sync::atomic::Ordering::Relaxed);
+        // The temporary fast fields and term blocks are only used while the segment is written,
+        // and it has no documents yet. Once the segment is written, a leftover file is garbage.
+        let include_temp_files = self.max_doc() == 0;
+        SegmentComponent::iterator()
+            .filter(|comp| include_temp_doc_store || *comp != &SegmentComponent::TempStore)
+            .filter(|comp| include_temp_files || *comp != &SegmentComponent::TempFastFields)
+            .filter(|comp| include_temp_files || *comp != &SegmentComponent::TempTerms)
+            .map(|component| self.relative_path(*component))
+            .collect::<HashSet<PathBuf>>()
     }
 
     /// Returns the relative path of a component of our segment.
@@ -140,7 +140,11 @@ impl SegmentMeta {
             SegmentComponent::Store => ".store".to_string(),
             SegmentComponent::TempStore => ".store.temp".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
+            SegmentComponent::TempFastFields => ".fast.temp".to_string(),
+            SegmentComponent::TempTerms => ".term.temp".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
+            SegmentComponent::Vectors => ".vec".to_string(),
+            SegmentComponent::TermVectors => ".tv".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
         });
         PathBuf::from(path)
@@ -175,6 +179,25 @@ impl SegmentMeta {
         self.num_deleted_docs() > 0
     }
 
+    /// Returns the metadata attached to the segment by the application.
+    ///
+    /// See [`IndexWriter::set_segment_user_metadata`](crate::IndexWriter::set_segment_user_metadata).
+    pub fn user_metadata(&self) -> &BTreeMap<String, String> {
+        &self.tracked.user_metadata
+    }
+
+    /// Replaces the metadata attached to the segment by the application.
+    pub(crate) fn with_user_metadata(self, user_metadata: BTreeMap<String, String>) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            user_metadata,
+        });
+        SegmentMeta { tracked }
+    }
+
     /// Updates the max_doc value from the `SegmentMeta`.
     ///
     /// This method is only used when updating `max_doc` from 0
@@ -187,6 +210,7 @@ impl SegmentMeta {
             max_doc,
             deletes: None,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            user_metadata: inner_meta.user_metadata.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -207,6 +231,7 @@ impl SegmentMeta {
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            user_metadata: inner_meta.user_metadata.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -222,6 +247,9 @@ struct InnerSegmentMeta {
     #[serde(skip)]
     #[serde(default = "default_temp_store")]
     pub(crate) include_temp_doc_store: Arc<AtomicBool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    user_metadata: BTreeMap<String, String>,
 }
 fn default_temp_store() -> Arc<AtomicBool> {
     Arc::new(AtomicBool::new(false))
@@ -458,9 +486,7 @@ mod tests {
                     field: "text".to_string(),
                     order: Order::Asc,
                 }),
-                docstore_compression: crate::store::Compressor::Zstd(ZstdCompressor {
-                    compression_level: Some(4),
-                }),
+                docstore_compression: crate::store::Compressor::Zstd(ZstdCompressor::new(Some(4))),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
             },
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::core::{Index, IndexMeta};
+use crate::directory::Directory;
+
+/// A consistent snapshot of the last commit of an index, used to back it up incrementally.
+///
+/// The snapshot lists the files of the segments of the commit. As segment files are never
+/// modified, backing up a new snapshot only requires copying the files that were not part of
+/// the previous one, as returned by [`IndexSnapshot::files_to_copy`].
+///
+/// The `meta.json` file of the index may be overwritten by new commits at any time. To get a
+/// backup that is consistent with its segment files, the segment files should be copied first,
+/// and the content of [`IndexSnapshot::meta_json`] written last, as the `meta.json` file of the
+/// backup.
+///
+/// The segment files of the snapshot are not garbage collected as long as the snapshot is
+/// alive, even if their segments are merged or deleted in the meantime.
+pub struct IndexSnapshot {
+    index_meta: IndexMeta,
+    meta_json: Vec<u8>,
+    segment_files: HashSet<PathBuf>,
+}
+
+impl IndexSnapshot {
+    pub(crate) fn create(index: &Index) -> crate::Result<IndexSnapshot> {
+        let index_meta = index.load_metas()?;
+        let mut meta_json = serde_json::to_vec_pretty(&index_meta)?;
+        writeln!(&mut meta_json)?;
+        let directory = index.directory();
+        let mut segment_files = HashSet::new();
+        for segment_meta in &index_meta.segments {
+            // Some of the components of a segment may not have been written.
+            for file in segment_meta.list_files() {
+                if directory.exists(&file)? {
+                    segment_files.insert(file);
+                }
+            }
+        }
+        Ok(IndexSnapshot {
+            index_meta,
+            meta_json,
+            segment_files,
+        })
+    }
+
+    /// Returns the metas of the commit of the snapshot.
+    pub fn meta(&self) -> &IndexMeta {
+        &self.index_meta
+    }
+
+    /// Returns the content of the `meta.json` file of the snapshot.
+    pub fn meta_json(&self) -> &[u8] {
+        &self.meta_json
+    }
+
+    /// Returns the paths of all of the segment files of the snapshot, relative to the
+    /// directory of the index.
+    ///
+    /// The `meta.json` file is not part of the list: see [`IndexSnapshot::meta_json`].
+    pub fn segment_files(&self) -> &HashSet<PathBuf> {
+        &self.segment_files
+    }
+
+    /// Returns the segment files of the snapshot that are not among the segment files of a
+    /// previous snapshot.
+    pub fn files_to_copy(&self, previous_segment_files: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+        self.segment_files
+            .difference(previous_segment_files)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the segment files of a previous snapshot that are not part of this snapshot
+    /// anymore, e.g. because their segment was merged, and can be removed from the backup
+    /// once the snapshot is backed up.
+    pub fn obsolete_files(&self, previous_segment_files: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+        previous_segment_files
+            .difference(&self.segment_files)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    use super::IndexSnapshot;
+    use crate::collector::Count;
+    use crate::core::META_FILEPATH;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STORED, STRING};
+    use crate::{Index, IndexWriter, SegmentComponent, Term};
+
+    fn backup(
+        index: &Index,
+        snapshot: &IndexSnapshot,
+        backup_directory: &RamDirectory,
+        files: &HashSet<PathBuf>,
+    ) -> crate::Result<()> {
+        for file in files {
+            let data = index.directory().atomic_read(file)?;
+            backup_directory.atomic_write(file, &data)?;
+        }
+        backup_directory.atomic_write(&META_FILEPATH, snapshot.meta_json())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_snapshot() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let backup_directory = RamDirectory::create();
+        let first_snapshot = index.snapshot()?;
+        assert_eq!(first_snapshot.meta().segments.len(), 1);
+        let first_files = first_snapshot.files_to_copy(&HashSet::new());
+        assert_eq!(&first_files, first_snapshot.segment_files());
+        backup(&index, &first_snapshot, &backup_directory, &first_files)?;
+
+        // The second commit adds a segment, and deletes a document of the first segment.
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.commit()?;
+
+        let second_snapshot = index.snapshot()?;
+        let files_to_copy = second_snapshot.files_to_copy(first_snapshot.segment_files());
+        let first_segment_id = first_snapshot.meta().segments[0].id();
+        let new_segment_meta = second_snapshot
+            .meta()
+            .segments
+            .iter()
+            .find(|segment_meta| segment_meta.id() != first_segment_id)
+            .unwrap();
+        let first_segment_meta = second_snapshot
+            .meta()
+            .segments
+            .iter()
+            .find(|segment_meta| segment_meta.id() == first_segment_id)
+            .unwrap();
+        // Only the files of the new segment, and the new delete file of the first segment need
+        // to be copied.
+        let mut expected_files_to_copy: HashSet<PathBuf> = second_snapshot
+            .segment_files()
+            .iter()
+            .filter(|file| new_segment_meta.list_files().contains(*file))
+            .cloned()
+            .collect();
+        expected_files_to_copy.insert(first_segment_meta.relative_path(SegmentComponent::Delete));
+        assert_eq!(files_to_copy, expected_files_to_copy);
+        backup(&index, &second_snapshot, &backup_directory, &files_to_copy)?;
+
+        // The backup is consistent with the second commit.
+        let backup_index = Index::open(backup_directory)?;
+        let searcher = backup_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let count_id = |id_value: &str| {
+            let query = TermQuery::new(
+                Term::from_field_text(id, id_value),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count_id("a")?, 0);
+        assert_eq!(count_id("c")?, 1);
+
+        // Merged segments are not garbage collected while a snapshot is alive.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.garbage_collect_files().wait()?;
+        for file in second_snapshot.segment_files() {
+            assert!(index.directory().exists(file)?);
+        }
+        let third_snapshot = index.snapshot()?;
+        let obsolete_files = third_snapshot.obsolete_files(second_snapshot.segment_files());
+        assert_eq!(&obsolete_files, second_snapshot.segment_files());
+        drop(second_snapshot);
+        drop(first_snapshot);
+        index_writer.garbage_collect_files().wait()?;
+        for file in &obsolete_files {
+            assert!(!index.directory().exists(file)?);
+        }
+        Ok(())
+    }
+}
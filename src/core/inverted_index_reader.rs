@@ -4,9 +4,10 @@ use common::BinarySerializable;
 
 use crate::directory::FileSlice;
 use crate::positions::PositionReader;
-use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
-use crate::schema::{IndexRecordOption, Term};
-use crate::termdict::TermDictionary;
+use crate::postings::{BlockSegmentPostings, Postings, SegmentPostings, TermInfo};
+use crate::schema::{IndexRecordOption, PositionsCompression, Term};
+use crate::termdict::{TermDictionary, TermStreamer};
+use crate::{DocId, DocSet, TERMINATED};
 
 /// The inverted index reader is in charge of accessing
 /// the inverted index associated to a specific field.
@@ -26,6 +27,7 @@ pub struct InvertedIndexReader {
     postings_file_slice: FileSlice,
     positions_file_slice: FileSlice,
     record_option: IndexRecordOption,
+    positions_compression: PositionsCompression,
     total_num_tokens: u64,
 }
 
@@ -36,6 +38,7 @@ impl InvertedIndexReader {
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
         record_option: IndexRecordOption,
+        positions_compression: PositionsCompression,
     ) -> io::Result<InvertedIndexReader> {
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
         let total_num_tokens = u64::deserialize(&mut total_num_tokens_slice.read_bytes()?)?;
@@ -44,6 +47,7 @@ impl InvertedIndexReader {
             postings_file_slice: postings_body,
             positions_file_slice,
             record_option,
+            positions_compression,
             total_num_tokens,
         })
     }
@@ -56,6 +60,7 @@ impl InvertedIndexReader {
             postings_file_slice: FileSlice::empty(),
             positions_file_slice: FileSlice::empty(),
             record_option,
+            positions_compression: PositionsCompression::default(),
             total_num_tokens: 0u64,
         }
     }
@@ -142,7 +147,8 @@ impl InvertedIndexReader {
                 let positions_data = self
                     .positions_file_slice
                     .read_bytes_slice(term_info.positions_range.clone())?;
-                let position_reader = PositionReader::open(positions_data)?;
+                let position_reader =
+                    PositionReader::open(positions_data, self.positions_compression)?;
                 Some(position_reader)
             } else {
                 None
@@ -197,6 +203,82 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32))
     }
+
+    /// Returns true if the term appears in at least one document of the segment.
+    ///
+    /// Unlike [`InvertedIndexReader::doc_freq`], this only looks the term up in the term
+    /// dictionary FST, without reading its `TermInfo` nor opening its postings.
+    pub fn has_term(&self, term: &Term) -> io::Result<bool> {
+        Ok(self.termdict.term_ord(term.value_bytes())?.is_some())
+    }
+
+    /// Returns an iterator streaming the whole inverted index, term by term.
+    ///
+    /// This is meant for debugging or for exporting an index to an external tool.
+    /// Only the postings of the current term are loaded in memory.
+    ///
+    /// See [`TermPostingsIter`] for more details.
+    pub fn iter_postings(&self) -> io::Result<TermPostingsIter<'_>> {
+        Ok(TermPostingsIter {
+            inverted_index: self,
+            term_stream: self.termdict.stream()?,
+        })
+    }
+}
+
+/// The postings of a term: for each document containing the term,
+/// its `DocId`, the term frequency and the term positions.
+pub type TermPostings = Vec<(DocId, u32, Vec<u32>)>;
+
+/// Iterator over the terms of an inverted index and their postings,
+/// created by [`InvertedIndexReader::iter_postings`].
+///
+/// Terms are emitted in their sorted order, as the value bytes of the term
+/// (that is without the field and type header).
+///
+/// The information returned depends on the field's indexing options:
+/// if term frequencies are not recorded, they are reported as `1`, and
+/// if positions are not recorded, the positions are empty.
+///
+/// Deleted documents are returned too. If required, they can be filtered out using
+/// [`SegmentReader::alive_bitset`](crate::SegmentReader::alive_bitset).
+pub struct TermPostingsIter<'a> {
+    inverted_index: &'a InvertedIndexReader,
+    term_stream: TermStreamer<'a>,
+}
+
+impl<'a> TermPostingsIter<'a> {
+    fn read_term_postings(&self, term_info: &TermInfo) -> io::Result<TermPostings> {
+        let record_option = self.inverted_index.record_option;
+        let mut postings = self
+            .inverted_index
+            .read_postings_from_terminfo(term_info, record_option)?;
+        let mut term_postings = Vec::with_capacity(term_info.doc_freq as usize);
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            let mut positions = Vec::new();
+            if record_option.has_positions() {
+                postings.positions(&mut positions);
+            }
+            term_postings.push((doc, postings.term_freq(), positions));
+            doc = postings.advance();
+        }
+        Ok(term_postings)
+    }
+}
+
+impl<'a> Iterator for TermPostingsIter<'a> {
+    type Item = io::Result<(Vec<u8>, TermPostings)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.term_stream.advance() {
+            return None;
+        }
+        let term_postings_res = self.read_term_postings(self.term_stream.value());
+        Some(
+            term_postings_res.map(|term_postings| (self.term_stream.key().to_vec(), term_postings)),
+        )
+    }
 }
 
 #[cfg(feature = "quickwit")]
@@ -240,3 +322,93 @@ impl InvertedIndexReader {
             .unwrap_or(0u32))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, Term};
+
+    /// The terms of a field, along with their `(doc, term_freq, positions)` postings.
+    type TermPostings = Vec<(Vec<u8>, Vec<(u32, u32, Vec<u32>)>)>;
+
+    #[test]
+    fn test_iter_postings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let string_field = schema_builder.add_text_field("string", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a b a", string_field => "x"))?;
+        index_writer.add_document(doc!(text_field => "c b", string_field => "y"))?;
+        index_writer.add_document(doc!(text_field => "b", string_field => "x"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+
+        let text_inverted_index = segment_reader.inverted_index(text_field)?;
+        let text_postings: TermPostings = text_inverted_index
+            .iter_postings()?
+            .collect::<std::io::Result<_>>()?;
+        assert_eq!(
+            text_postings,
+            vec![
+                (b"a".to_vec(), vec![(0, 2, vec![0, 2])]),
+                (
+                    b"b".to_vec(),
+                    vec![(0, 1, vec![1]), (1, 1, vec![1]), (2, 1, vec![0])]
+                ),
+                (b"c".to_vec(), vec![(1, 1, vec![0])]),
+            ]
+        );
+
+        // STRING fields have neither term frequencies nor positions.
+        let string_inverted_index = segment_reader.inverted_index(string_field)?;
+        let string_postings: TermPostings = string_inverted_index
+            .iter_postings()?
+            .collect::<std::io::Result<_>>()?;
+        assert_eq!(
+            string_postings,
+            vec![
+                (b"x".to_vec(), vec![(0, 1, vec![]), (2, 1, vec![])]),
+                (b"y".to_vec(), vec![(1, 1, vec![])]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_term_and_doc_freq() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let other_field = schema_builder.add_text_field("other", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a b", other_field => "c"))?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "b c"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let term_doc_freqs = |text: &str| -> crate::Result<Vec<(bool, u32)>> {
+            let term = Term::from_field_text(text_field, text);
+            let mut term_doc_freqs = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let inverted_index = segment_reader.inverted_index(text_field)?;
+                term_doc_freqs.push((
+                    inverted_index.has_term(&term)?,
+                    inverted_index.doc_freq(&term)?,
+                ));
+            }
+            term_doc_freqs.sort();
+            Ok(term_doc_freqs)
+        };
+        assert_eq!(term_doc_freqs("a")?, vec![(false, 0), (true, 2)]);
+        assert_eq!(term_doc_freqs("b")?, vec![(true, 1), (true, 1)]);
+        // "c" only appears in `text_field` in the second segment.
+        assert_eq!(term_doc_freqs("c")?, vec![(false, 0), (true, 1)]);
+        assert_eq!(term_doc_freqs("d")?, vec![(false, 0), (false, 0)]);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 mod executor;
 pub mod index;
 mod index_meta;
+mod index_snapshot;
 mod inverted_index_reader;
 pub mod searcher;
 mod segment;
@@ -18,12 +19,13 @@ pub use self::index::{Index, IndexBuilder};
 pub use self::index_meta::{
     IndexMeta, IndexSettings, IndexSortByField, Order, SegmentMeta, SegmentMetaInventory,
 };
-pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::{Searcher, SearcherGeneration};
+pub use self::index_snapshot::IndexSnapshot;
+pub use self::inverted_index_reader::{InvertedIndexReader, TermPostings, TermPostingsIter};
+pub use self::searcher::{Searcher, SearcherGeneration, TermStats};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
-pub use self::segment_reader::SegmentReader;
+pub use self::segment_reader::{FieldIndexStats, SegmentReader};
 pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
 
 /// The meta file contains all the information about the list of segments and the schema
@@ -4,11 +4,16 @@ use std::{fmt, io};
 
 use crate::collector::Collector;
 use crate::core::{Executor, SegmentReader};
+use crate::fastfield::type_and_cardinality;
+use crate::postings::FreqReadingOption;
 use crate::query::Query;
-use crate::schema::{Document, Schema, Term};
-use crate::space_usage::SearcherSpaceUsage;
+use crate::schema::{Cardinality, Document, Field, FieldType, IndexRecordOption, Schema, Term};
+use crate::space_usage::{SearcherSpaceUsage, SegmentStats};
 use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, SegmentId, TrackedObject};
+use crate::{
+    DocAddress, DocId, FutureResult, Index, Opstamp, SegmentId, SegmentOrdinal, TantivyError,
+    TrackedObject,
+};
 
 /// Identifies the searcher generation accessed by a [Searcher].
 ///
@@ -59,6 +64,17 @@ impl SearcherGeneration {
     }
 }
 
+/// Statistics of a term over all of the segments of a [`Searcher`].
+///
+/// See [`Searcher::term_statistics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TermStats {
+    /// Number of documents containing the term.
+    pub doc_freq: u64,
+    /// Number of occurrences of the term, summed over all of the documents containing it.
+    pub total_term_freq: u64,
+}
+
 /// Holds a list of `SegmentReader`s ready for search.
 ///
 /// It guarantees that the `Segment` will not be removed before
@@ -88,6 +104,27 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Iterates over all of the live documents of the searcher, in `DocAddress` order.
+    ///
+    /// Each block of the doc stores is decompressed only once, which makes exporting all
+    /// of the documents much cheaper than fetching them one at a time with
+    /// [`Searcher::doc`]. Deleted documents are skipped.
+    pub fn iter_docs(&self) -> impl Iterator<Item = crate::Result<(DocAddress, Document)>> + '_ {
+        self.inner
+            .segment_readers
+            .iter()
+            .zip(&self.inner.store_readers)
+            .enumerate()
+            .flat_map(|(segment_ord, (segment_reader, store_reader))| {
+                store_reader
+                    .iter_with_doc_ids(segment_reader.alive_bitset())
+                    .map(move |doc_res| {
+                        let (doc_id, doc) = doc_res?;
+                        Ok((DocAddress::new(segment_ord as SegmentOrdinal, doc_id), doc))
+                    })
+            })
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -134,6 +171,43 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the statistics of the given term over all of the segments.
+    ///
+    /// Segments where the term is absent do not contribute to the statistics. Like
+    /// [`Searcher::doc_freq`] and the statistics used for BM25 scoring, the statistics include
+    /// the deleted documents that have not been merged away yet.
+    ///
+    /// The total term frequency is computed by reading the term frequencies of the posting
+    /// lists. If the field does not record term frequencies, each document counts for one
+    /// occurrence of the term.
+    pub fn term_statistics(&self, term: &Term) -> crate::Result<TermStats> {
+        let mut term_stats = TermStats::default();
+        for segment_reader in &self.inner.segment_readers {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            let term_info = if let Some(term_info) = inverted_index.get_term_info(term)? {
+                term_info
+            } else {
+                continue;
+            };
+            term_stats.doc_freq += u64::from(term_info.doc_freq);
+            let mut block_postings = inverted_index
+                .read_block_postings_from_terminfo(&term_info, IndexRecordOption::WithFreqs)?;
+            if block_postings.freq_reading_option() != FreqReadingOption::ReadFreq {
+                term_stats.total_term_freq += u64::from(term_info.doc_freq);
+                continue;
+            }
+            while !block_postings.docs().is_empty() {
+                term_stats.total_term_freq += block_postings
+                    .freqs()
+                    .iter()
+                    .map(|&term_freq| u64::from(term_freq))
+                    .sum::<u64>();
+                block_postings.advance();
+            }
+        }
+        Ok(term_stats)
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -147,6 +221,100 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the minimum and the maximum values of the fast field `field` over all of the
+    /// segments, in their `u64` representation, or `None` if no segment has a value for the
+    /// field.
+    ///
+    /// The range is computed from the min and max values persisted by each segment, without
+    /// reading the values. It takes the deleted documents into account, and should therefore
+    /// be considered as bounds of the actual values. The mapping of the values of a
+    /// [`FastValue`](crate::fastfield::FastValue) to `u64` preserves their order, so that the
+    /// bounds of the `u64` representation are the bounds of the values.
+    ///
+    /// If `field` is not a numerical or date fast field, this method returns an Error.
+    pub fn fast_field_range(&self, field: Field) -> crate::Result<Option<(u64, u64)>> {
+        let mut range: Option<(u64, u64)> = None;
+        for segment_reader in self.segment_readers() {
+            if let Some((min_value, max_value)) = segment_reader.fast_fields().u64_range(field)? {
+                range = Some(match range {
+                    Some((global_min, global_max)) => {
+                        (global_min.min(min_value), global_max.max(max_value))
+                    }
+                    None => (min_value, max_value),
+                });
+            }
+        }
+        Ok(range)
+    }
+
+    /// Returns the values of the single-valued fast fields `fields` for each of the documents
+    /// of `doc_addresses`, e.g. the addresses of the hits returned by
+    /// [`TopDocs`](crate::collector::TopDocs).
+    ///
+    /// `values[i][j]` is the value of `fields[j]` for the document `doc_addresses[i]`, in its
+    /// `u64` representation. It can be converted back with
+    /// [`MonotonicallyMappableToU64::from_u64`](fastfield_codecs::MonotonicallyMappableToU64::from_u64).
+    ///
+    /// The addresses are grouped by segment: the fast field readers of each segment are opened
+    /// only once, and the values of the documents of a segment are read, in increasing doc id
+    /// order, with a single call to
+    /// [`Column::get_vals`](fastfield_codecs::Column::get_vals) per field.
+    ///
+    /// If one of the fields is not a single-valued numerical, boolean or date fast field, this
+    /// method returns an Error.
+    pub fn fast_field_values(
+        &self,
+        doc_addresses: &[DocAddress],
+        fields: &[Field],
+    ) -> crate::Result<Vec<Vec<u64>>> {
+        for &field in fields {
+            let field_entry = self.schema().get_field_entry(field);
+            let is_single_valued_fast_value = matches!(
+                type_and_cardinality(field_entry.field_type()),
+                Some((_, Cardinality::SingleValue))
+            ) && !matches!(
+                field_entry.field_type(),
+                FieldType::Facet(_) | FieldType::Str(_)
+            );
+            if !is_single_valued_fast_value {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a single-valued numerical fast field.",
+                    field_entry.name()
+                )));
+            }
+        }
+        let mut doc_ords: Vec<usize> = (0..doc_addresses.len()).collect();
+        doc_ords.sort_by_key(|&doc_ord| doc_addresses[doc_ord]);
+        let mut values: Vec<Vec<u64>> = vec![Vec::with_capacity(fields.len()); doc_addresses.len()];
+        let mut doc_ids: Vec<DocId> = Vec::new();
+        let mut segment_values: Vec<u64> = Vec::new();
+        let mut start = 0;
+        while start < doc_ords.len() {
+            let segment_ord = doc_addresses[doc_ords[start]].segment_ord;
+            let num_segment_docs = doc_ords[start..]
+                .partition_point(|&doc_ord| doc_addresses[doc_ord].segment_ord == segment_ord);
+            let segment_doc_ords = &doc_ords[start..start + num_segment_docs];
+            doc_ids.clear();
+            doc_ids.extend(
+                segment_doc_ords
+                    .iter()
+                    .map(|&doc_ord| doc_addresses[doc_ord].doc_id),
+            );
+            segment_values.clear();
+            segment_values.resize(doc_ids.len(), 0u64);
+            let fast_field_readers = self.segment_reader(segment_ord).fast_fields();
+            for &field in fields {
+                let column = fast_field_readers.u64_lenient(field)?;
+                column.get_vals(&doc_ids, &mut segment_values);
+                for (&doc_ord, &val) in segment_doc_ords.iter().zip(&segment_values) {
+                    values[doc_ord].push(val);
+                }
+            }
+            start += num_segment_docs;
+        }
+        Ok(values)
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.inner.segment_readers
@@ -197,10 +365,48 @@ impl Searcher {
         query: &dyn Query,
         collector: &C,
         executor: &Executor,
+    ) -> crate::Result<C::Fruit> {
+        self.search_segments(query, collector, executor, self.segment_readers())
+    }
+
+    /// Same as [`search(...)`](#method.search), but the documents that were deleted and not yet
+    /// merged away are searched too.
+    ///
+    /// This is meant for recovery or audit purposes, and should not be used for regular search.
+    /// The collector returns a list of hits, like [`TopDocs`](crate::collector::TopDocs), and
+    /// each of them is returned along with a flag that is true if its document is deleted. The
+    /// deleted documents can be retrieved with [`Searcher::doc`] like any other document. The
+    /// scores are computed with the statistics of the alive documents only.
+    pub fn search_including_deleted<T, C>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<Vec<(T, DocAddress, bool)>>
+    where
+        C: Collector<Fruit = Vec<(T, DocAddress)>>,
+    {
+        let segment_readers: Vec<SegmentReader> = self
+            .segment_readers()
+            .iter()
+            .map(SegmentReader::including_deleted_docs)
+            .collect();
+        let executor = self.inner.index.search_executor();
+        let hits = self.search_segments(query, collector, executor, &segment_readers)?;
+        Ok(hits
+            .into_iter()
+            .map(|(feature, doc_address)| (feature, doc_address, self.is_deleted(doc_address)))
+            .collect())
+    }
+
+    fn search_segments<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        executor: &Executor,
+        segment_readers: &[SegmentReader],
     ) -> crate::Result<C::Fruit> {
         let scoring_enabled = collector.requires_scoring();
         let weight = query.weight(self, scoring_enabled)?;
-        let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
                 collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
@@ -210,6 +416,55 @@ impl Searcher {
         collector.merge_fruits(fruits)
     }
 
+    /// Returns true if the document at `doc_address` is deleted.
+    ///
+    /// Deleted documents are only returned by
+    /// [`search_including_deleted(...)`](#method.search_including_deleted), which flags them.
+    pub fn is_deleted(&self, doc_address: DocAddress) -> bool {
+        self.segment_reader(doc_address.segment_ord)
+            .is_deleted(doc_address.doc_id)
+    }
+
+    /// Asynchronous version of [`search(...)`](#method.search).
+    ///
+    /// The search is CPU-bound: rather than blocking the caller, it is offloaded to
+    /// the index search executor thread pool (or to a global thread pool if the search executor
+    /// is single threaded), and the returned [`FutureResult`] resolves once the fruit is
+    /// available.
+    ///
+    /// The returned future does not depend on any specific async runtime. It does not need to be
+    /// polled for the search to progress, and dropping it does not cancel the search.
+    /// It can also be waited for in a sync context using [`FutureResult::wait()`].
+    ///
+    /// Contrary to `search`, the collector is taken by value, as it needs to be moved to the
+    /// thread running the search. The query is cloned for the same reason.
+    pub fn search_async<C: Collector + 'static>(
+        &self,
+        query: &dyn Query,
+        collector: C,
+    ) -> FutureResult<C::Fruit> {
+        let (future_result, sender) = FutureResult::create("Search task failed.");
+        let searcher = self.clone();
+        let query = query.box_clone();
+        self.inner.index.search_executor().spawn(move || {
+            let fruit_res = searcher.search(query.as_ref(), &collector);
+            // An error means the future was dropped: nobody is waiting for the result.
+            let _ = sender.send(fruit_res);
+        });
+        future_result
+    }
+
+    /// Returns the document counts and the sizes of the components of each segment.
+    ///
+    /// This is computed from the metadata of the segments only, and is therefore cheap.
+    /// See [`Searcher::space_usage`] for a per field breakdown.
+    pub fn segment_stats(&self) -> Vec<SegmentStats> {
+        self.segment_readers()
+            .iter()
+            .map(SegmentReader::segment_stats)
+            .collect()
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -280,3 +535,299 @@ impl fmt::Debug for Searcher {
         write!(f, "Searcher({:?})", segment_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fastfield_codecs::MonotonicallyMappableToU64;
+    use futures::executor::block_on;
+
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{
+        Cardinality, Document, IndexRecordOption, NumericOptions, Schema, FAST, INDEXED, STORED,
+        STRING, TEXT,
+    };
+    use crate::{DocAddress, Index, Term, TermStats};
+
+    fn test_index() -> crate::Result<(Index, TermQuery)> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..10 {
+            let text = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(text_field => text))?;
+            if i % 3 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        Ok((index, query))
+    }
+
+    #[test]
+    fn test_search_async() -> crate::Result<()> {
+        let (index, query) = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let count_future = searcher.search_async(&query, Count);
+        let top_docs_future = searcher.search_async(&query, (Count, TopDocs::with_limit(3)));
+        assert_eq!(block_on(count_future)?, 5);
+        let (count, top_docs) = block_on(top_docs_future)?;
+        assert_eq!(count, 5);
+        assert_eq!(top_docs, searcher.search(&query, &TopDocs::with_limit(3))?);
+        // The future can also be waited for in a sync context.
+        assert_eq!(searcher.search_async(&query, Count).wait()?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_async_multithread_executor() -> crate::Result<()> {
+        let (mut index, query) = test_index()?;
+        index.set_multithread_executor(2)?;
+        let searcher = index.reader()?.searcher();
+        let count_futures: Vec<_> = (0..4)
+            .map(|_| searcher.search_async(&query, Count))
+            .collect();
+        for count_future in count_futures {
+            assert_eq!(block_on(count_future)?, 5);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_range() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_i64_field("price", FAST);
+        let tags = schema_builder.add_u64_field(
+            "tags",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.fast_field_range(price)?, None);
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(price => -5i64, tags => 7u64))?;
+        index_writer.add_document(doc!(price => 12i64, tags => 3u64, tags => 9u64))?;
+        index_writer.commit()?;
+        // This segment has no value for the multivalued field.
+        index_writer.add_document(doc!(price => 40i64))?;
+        index_writer.add_document(doc!(price => 2i64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let mut segment_ranges = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.fast_fields().u64_range(tags))
+            .collect::<crate::Result<Vec<_>>>()?;
+        segment_ranges.sort();
+        assert_eq!(segment_ranges, vec![None, Some((3, 9))]);
+        let (min_price, max_price) = searcher.fast_field_range(price)?.unwrap();
+        assert_eq!(i64::from_u64(min_price), -5);
+        assert_eq!(i64::from_u64(max_price), 40);
+        assert_eq!(searcher.fast_field_range(tags)?, Some((3, 9)));
+        assert!(searcher.fast_field_range(text).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let score_field = schema_builder.add_i64_field("score", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..10u64 {
+            let text = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                text_field => text,
+                id_field => i,
+                score_field => -(i as i64)
+            ))?;
+            if i % 3 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 5);
+        let doc_addresses: Vec<DocAddress> = top_docs
+            .iter()
+            .map(|(_, doc_address)| *doc_address)
+            .collect();
+        let values = searcher.fast_field_values(&doc_addresses, &[id_field, score_field])?;
+        assert_eq!(values.len(), doc_addresses.len());
+        for (doc_address, doc_values) in doc_addresses.iter().zip(values.iter()) {
+            let fast_fields = searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields();
+            let doc_id = u64::from(doc_address.doc_id);
+            let id = fast_fields.u64(id_field)?.get_val(doc_id);
+            let score = fast_fields.i64(score_field)?.get_val(doc_id);
+            assert_eq!(doc_values, &vec![id, score.to_u64()]);
+            assert_eq!(id % 2, 0);
+            assert_eq!(i64::from_u64(doc_values[1]), -(id as i64));
+        }
+        assert!(matches!(
+            searcher.fast_field_values(&doc_addresses, &[text_field]),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        assert!(searcher.fast_field_values(&[], &[id_field])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_statistics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "rust rust rust", tag => "a"))?;
+        index_writer.add_document(doc!(text => "rust java", tag => "b"))?;
+        index_writer.commit()?;
+        // "rust" is absent from this segment.
+        index_writer.add_document(doc!(text => "java", tag => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "rust rust", tag => "c"))?;
+        index_writer.add_document(doc!(text => "rust", tag => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let rust = Term::from_field_text(text, "rust");
+        assert_eq!(
+            searcher.term_statistics(&rust)?,
+            TermStats {
+                doc_freq: 4,
+                total_term_freq: 7
+            }
+        );
+        assert_eq!(
+            searcher.term_statistics(&Term::from_field_text(text, "python"))?,
+            TermStats::default()
+        );
+        // Without term frequencies, each document counts for one occurrence.
+        assert_eq!(
+            searcher.term_statistics(&Term::from_field_text(tag, "a"))?,
+            TermStats {
+                doc_freq: 3,
+                total_term_freq: 3
+            }
+        );
+
+        // Deleted documents are counted until their segment is merged.
+        index_writer.delete_term(Term::from_field_text(tag, "c"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 4);
+        assert_eq!(searcher.term_statistics(&rust)?.total_term_freq, 7);
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(
+            searcher.term_statistics(&rust)?,
+            TermStats {
+                doc_freq: 3,
+                total_term_freq: 5
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED | INDEXED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..300u64 {
+            index_writer.add_document(doc!(id => i, body => format!("document {i}")))?;
+            if i % 100 == 99 {
+                index_writer.commit()?;
+            }
+        }
+        for i in (0..300u64).step_by(7) {
+            index_writer.delete_term(Term::from_field_u64(id, i));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let streamed_docs = searcher
+            .iter_docs()
+            .collect::<crate::Result<Vec<(DocAddress, Document)>>>()?;
+        let mut expected_docs = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc_address = DocAddress::new(segment_ord as u32, doc_id);
+                expected_docs.push((doc_address, searcher.doc(doc_address)?));
+            }
+        }
+        assert_eq!(streamed_docs.len(), 300 - 43);
+        assert_eq!(streamed_docs, expected_docs);
+        assert!(streamed_docs
+            .iter()
+            .all(|(_, doc)| doc.get_first(id).unwrap().as_u64().unwrap() % 7 != 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_including_deleted() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..3u64 {
+            index_writer.add_document(doc!(id => i, body => format!("document {i}")))?;
+        }
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id, 1));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(body, "document"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+
+        let top_docs = searcher.search_including_deleted(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 3);
+        for &(_, doc_address, is_deleted) in &top_docs {
+            assert_eq!(is_deleted, searcher.is_deleted(doc_address));
+        }
+        let deleted_docs: Vec<DocAddress> = top_docs
+            .iter()
+            .filter(|&&(_, _, is_deleted)| is_deleted)
+            .map(|&(_, doc_address, _)| doc_address)
+            .collect();
+        assert_eq!(deleted_docs.len(), 1);
+        let deleted_doc = searcher.doc(deleted_docs[0])?;
+        assert_eq!(deleted_doc.get_first(id).unwrap().as_u64(), Some(1));
+        assert_eq!(
+            deleted_doc.get_first(body).unwrap().as_text(),
+            Some("document 1")
+        );
+        // The regular search is not affected.
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 2);
+        assert!(top_docs
+            .iter()
+            .all(|&(_, doc_address)| !searcher.is_deleted(doc_address)));
+        Ok(())
+    }
+}
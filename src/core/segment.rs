@@ -81,6 +81,21 @@ impl Segment {
         self.index.directory().open_read(&path)
     }
 
+    /// Open one of the optional component files for a *regular* read.
+    ///
+    /// Returns `None` if the segment does not have this component file. Any other error is
+    /// returned.
+    pub(crate) fn open_read_if_exists(
+        &self,
+        component: SegmentComponent,
+    ) -> Result<Option<FileSlice>, OpenReadError> {
+        match self.open_read(component) {
+            Ok(file_slice) => Ok(Some(file_slice)),
+            Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+            Err(open_read_error) => Err(open_read_error),
+        }
+    }
+
     /// Open one of the component file for *regular* write.
     pub fn open_write(&mut self, component: SegmentComponent) -> Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
@@ -24,6 +24,16 @@ pub enum SegmentComponent {
     Store,
     /// Temporary storage of the documents, before streamed to `Store`.
     TempStore,
+    /// Temporary storage of the fast field values rebuilt by a merge, when they do not fit in
+    /// the merge memory budget.
+    TempFastFields,
+    /// Temporary storage of the sorted blocks of terms and postings flushed while the segment is
+    /// being built, before they are stitched into `Terms`, `Postings` and `Positions`.
+    TempTerms,
+    /// Vectors of the dense vector fields, and their graph for the nearest neighbor search.
+    Vectors,
+    /// Term vectors of the text fields that store them.
+    TermVectors,
     /// Bitset describing which document of the segment is alive.
     /// (It was representing deleted docs but changed to represent alive docs from v0.17)
     Delete,
@@ -32,7 +42,7 @@ pub enum SegmentComponent {
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 12] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
@@ -40,6 +50,10 @@ impl SegmentComponent {
             SegmentComponent::Terms,
             SegmentComponent::Store,
             SegmentComponent::TempStore,
+            SegmentComponent::TempFastFields,
+            SegmentComponent::TempTerms,
+            SegmentComponent::Vectors,
+            SegmentComponent::TermVectors,
             SegmentComponent::Delete,
         ];
         SEGMENT_COMPONENTS.iter()
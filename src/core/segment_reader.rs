@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::{fmt, io};
 
+use common::HasLen;
 use fail::fail_point;
 
 use crate::core::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
@@ -9,11 +10,29 @@ use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
 use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
-use crate::schema::{Field, FieldType, IndexRecordOption, Schema};
-use crate::space_usage::SegmentSpaceUsage;
+use crate::postings::Postings;
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, Type};
+use crate::space_usage::{SegmentSpaceUsage, SegmentStats};
 use crate::store::StoreReader;
 use crate::termdict::TermDictionary;
-use crate::{DocId, Opstamp};
+use crate::termvector::TermVectorReaders;
+use crate::vector::DenseVectorReaders;
+use crate::{DocId, DocSet, IndexSortByField, Opstamp};
+
+/// Indexing statistics of a field in a segment.
+///
+/// See [`SegmentReader::field_index_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FieldIndexStats {
+    /// Number of unique terms of the field.
+    pub num_unique_terms: u64,
+    /// Number of postings of the field, i.e. the sum of the document frequencies of its terms.
+    pub num_postings: u64,
+    /// Number of tokens of the field, over all of the documents.
+    pub total_num_tokens: u64,
+    /// Average number of tokens of the field per document.
+    pub average_field_length: f64,
+}
 
 /// Entry point to access all of the datastructures of the `Segment`
 ///
@@ -40,10 +59,13 @@ pub struct SegmentReader {
     positions_composite: CompositeFile,
     fast_fields_readers: Arc<FastFieldReaders>,
     fieldnorm_readers: FieldNormReaders,
+    dense_vector_readers: DenseVectorReaders,
+    term_vector_readers: TermVectorReaders,
 
     store_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
     schema: Schema,
+    sort_by_field: Option<IndexSortByField>,
 }
 
 impl SegmentReader {
@@ -89,6 +111,14 @@ impl SegmentReader {
         &self.fast_fields_readers
     }
 
+    /// Returns the number of bytes taken by the fast field data of `field` in this segment.
+    ///
+    /// Returns 0 if the segment has no fast field data for `field`, e.g. if it is not a fast
+    /// field.
+    pub fn fast_field_size(&self, field: Field) -> u64 {
+        self.fast_fields_readers.field_num_bytes(field) as u64
+    }
+
     /// Accessor to the `FacetReader` associated to a given `Field`.
     pub fn facet_reader(&self, field: Field) -> crate::Result<FacetReader> {
         let field_entry = self.schema.get_field_entry(field);
@@ -128,11 +158,96 @@ impl SegmentReader {
         })
     }
 
+    /// Returns the field norm of a document, that is the number of tokens of the field in the
+    /// document, as used for scoring.
+    ///
+    /// Field norms are encoded on one byte: they are exact up to 40 tokens and approximated
+    /// beyond that.
+    ///
+    /// Returns `Ok(None)` if the field does not record field norms, and an error if the field
+    /// norms of the field could not be opened, e.g. because the index is corrupted.
+    ///
+    /// # Panics
+    /// Panics if `doc` is not a document of the segment.
+    pub fn get_norm(&self, field: Field, doc: DocId) -> crate::Result<Option<u32>> {
+        let fieldnorm_reader_opt = self.fieldnorm_readers.get_field(field)?;
+        Ok(fieldnorm_reader_opt.map(|fieldnorm_reader| fieldnorm_reader.fieldnorm(doc)))
+    }
+
+    /// Rebuilds, on a best-effort basis, the token stream of a text field of a document from
+    /// the inverted index. This is meant for debugging fields that are indexed but not stored.
+    ///
+    /// Returns the `(position, text)` of the tokens of the document, sorted by position.
+    ///
+    /// The reconstruction is lossy: only the tokens as emitted by the tokenizer are recovered.
+    /// The original text, its whitespace and punctuation, as well as anything removed or
+    /// altered by the tokenizer (stop words, lowercasing, stemming...) are lost. Several tokens
+    /// can share the same position, and the positions of the different values of a
+    /// multivalued field are concatenated.
+    ///
+    /// This scans the whole term dictionary of the field and is therefore slow.
+    ///
+    /// Returns an error if the field is not a text field indexed with positions.
+    pub fn reconstruct_field(&self, field: Field, doc: DocId) -> crate::Result<Vec<(u32, String)>> {
+        let field_entry = self.schema.get_field_entry(field);
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(|record_option| record_option.has_positions())
+            .unwrap_or(false);
+        if field_entry.field_type().value_type() != Type::Str || !has_positions {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Field {:?} is not a text field indexed with positions.",
+                field_entry.name()
+            )));
+        }
+        let inverted_index = self.inverted_index(field)?;
+        let mut tokens = Vec::new();
+        let mut positions = Vec::new();
+        let mut term_stream = inverted_index.terms().stream()?;
+        while term_stream.advance() {
+            let mut postings = inverted_index.read_postings_from_terminfo(
+                term_stream.value(),
+                IndexRecordOption::WithFreqsAndPositions,
+            )?;
+            if postings.seek(doc) != doc {
+                continue;
+            }
+            postings.positions(&mut positions);
+            let text = String::from_utf8_lossy(term_stream.key());
+            tokens.extend(
+                positions
+                    .iter()
+                    .map(|&position| (position, text.to_string())),
+            );
+        }
+        tokens.sort();
+        Ok(tokens)
+    }
+
     #[doc(hidden)]
     pub fn fieldnorms_readers(&self) -> &FieldNormReaders {
         &self.fieldnorm_readers
     }
 
+    /// Accessor to the segment's dense vector readers.
+    pub fn dense_vectors(&self) -> &DenseVectorReaders {
+        &self.dense_vector_readers
+    }
+
+    /// Accessor to the segment's term vector readers.
+    pub fn term_vectors(&self) -> &TermVectorReaders {
+        &self.term_vector_readers
+    }
+
+    /// Returns the field and the order by which the documents of the segment are sorted,
+    /// if the index is sorted.
+    ///
+    /// See [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field).
+    pub fn sort_by_field(&self) -> Option<&IndexSortByField> {
+        self.sort_by_field.as_ref()
+    }
+
     /// Accessor to the segment's `StoreReader`.
     pub fn get_store_reader(&self, cache_size: usize) -> io::Result<StoreReader> {
         StoreReader::open(self.store_file.clone(), cache_size)
@@ -174,6 +289,18 @@ impl SegmentReader {
             Arc::new(FastFieldReaders::new(schema.clone(), fast_fields_composite));
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        // Segments written before dense vector fields were supported have no vectors file.
+        let dense_vector_readers = match segment.open_read_if_exists(SegmentComponent::Vectors)? {
+            Some(vectors_file) => DenseVectorReaders::open(schema.clone(), &vectors_file)?,
+            None => DenseVectorReaders::empty(schema.clone()),
+        };
+        // Segments written before term vectors were supported have no term vectors file.
+        let term_vector_readers = match segment
+            .open_read_if_exists(SegmentComponent::TermVectors)?
+        {
+            Some(term_vectors_file) => TermVectorReaders::open(schema.clone(), &term_vectors_file)?,
+            None => TermVectorReaders::empty(schema.clone()),
+        };
 
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
@@ -183,9 +310,11 @@ impl SegmentReader {
             None
         };
 
-        let alive_bitset_opt = intersect_alive_bitset(original_bitset, custom_bitset);
-
         let max_doc = segment.meta().max_doc();
+        // An alive bitset that does not delete any document is dropped, so that the scorers and
+        // the collectors do not consult it.
+        let alive_bitset_opt = intersect_alive_bitset(original_bitset, custom_bitset)
+            .filter(|alive_bitset| alive_bitset.num_alive_docs() < max_doc as usize);
         let num_docs = alive_bitset_opt
             .as_ref()
             .map(|alive_bitset| alive_bitset.num_alive_docs() as u32)
@@ -199,12 +328,15 @@ impl SegmentReader {
             postings_composite,
             fast_fields_readers,
             fieldnorm_readers,
+            dense_vector_readers,
+            term_vector_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
             store_file,
             alive_bitset_opt,
             positions_composite,
             schema,
+            sort_by_field: segment.index().settings().sort_by_field.clone(),
         })
     }
 
@@ -274,6 +406,7 @@ impl SegmentReader {
             postings_file,
             positions_file,
             record_option,
+            field_type.positions_compression(),
         )?);
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -286,6 +419,36 @@ impl SegmentReader {
         Ok(inv_idx_reader)
     }
 
+    /// Returns the indexing statistics of a field: its number of unique terms and of postings,
+    /// and its number of tokens.
+    ///
+    /// The deleted documents are included in the statistics, and the average field length is
+    /// computed over all of the documents of the segment, including the ones without any value
+    /// for the field.
+    ///
+    /// Counting the postings requires reading the whole term dictionary of the field.
+    pub fn field_index_stats(&self, field: Field) -> crate::Result<FieldIndexStats> {
+        let inverted_index = self.inverted_index(field)?;
+        let term_dict = inverted_index.terms();
+        let mut num_postings = 0u64;
+        let mut term_stream = term_dict.stream()?;
+        while term_stream.advance() {
+            num_postings += u64::from(term_stream.value().doc_freq);
+        }
+        let total_num_tokens = inverted_index.total_num_tokens();
+        let average_field_length = if self.max_doc == 0 {
+            0.0
+        } else {
+            total_num_tokens as f64 / f64::from(self.max_doc)
+        };
+        Ok(FieldIndexStats {
+            num_unique_terms: term_dict.num_terms() as u64,
+            num_postings,
+            total_num_tokens,
+            average_field_length,
+        })
+    }
+
     /// Returns the segment id
     pub fn segment_id(&self) -> SegmentId {
         self.segment_id
@@ -318,6 +481,39 @@ impl SegmentReader {
         }
     }
 
+    /// Returns a reader of the same segment in which the deleted documents are considered
+    /// alive.
+    pub(crate) fn including_deleted_docs(&self) -> SegmentReader {
+        let mut segment_reader = self.clone();
+        segment_reader.alive_bitset_opt = None;
+        segment_reader.num_docs = segment_reader.max_doc;
+        segment_reader
+    }
+
+    /// Returns the document counts and the sizes of the components of this segment.
+    ///
+    /// Contrary to [`SegmentReader::space_usage`], this does not require any IO.
+    pub fn segment_stats(&self) -> SegmentStats {
+        SegmentStats {
+            segment_id: self.segment_id,
+            num_docs: self.num_docs,
+            num_deleted_docs: self.num_deleted_docs(),
+            termdict: self.termdict_composite.space_usage().total(),
+            postings: self.postings_composite.space_usage().total(),
+            positions: self.positions_composite.space_usage().total(),
+            fast_fields: self.fast_fields_readers.space_usage().total(),
+            fieldnorms: self.fieldnorm_readers.space_usage().total(),
+            vectors: self.dense_vector_readers.space_usage().total(),
+            term_vectors: self.term_vector_readers.space_usage().total(),
+            store: self.store_file.len(),
+            deletes: self
+                .alive_bitset_opt
+                .as_ref()
+                .map(AliveBitSet::space_usage)
+                .unwrap_or(0),
+        }
+    }
+
     /// Summarize total space usage of this segment.
     pub fn space_usage(&self) -> io::Result<SegmentSpaceUsage> {
         Ok(SegmentSpaceUsage::new(
@@ -327,6 +523,8 @@ impl SegmentReader {
             self.positions_composite.space_usage(),
             self.fast_fields_readers.space_usage(),
             self.fieldnorm_readers.space_usage(),
+            self.dense_vector_readers.space_usage(),
+            self.term_vector_readers.space_usage(),
             self.get_store_reader(0)?.space_usage(),
             self.alive_bitset_opt
                 .as_ref()
@@ -359,9 +557,92 @@ impl fmt::Debug for SegmentReader {
 
 #[cfg(test)]
 mod test {
+    use common::HasLen;
+
+    use super::{FieldIndexStats, SegmentReader};
     use crate::core::Index;
-    use crate::schema::{Schema, Term, STORED, TEXT};
-    use crate::DocId;
+    use crate::fastfield::AliveBitSet;
+    use crate::schema::{
+        Cardinality, NumericOptions, Schema, Term, TextFieldIndexing, TextOptions, FAST, INDEXED,
+        STORED, STRING, TEXT,
+    };
+    use crate::{DocId, TantivyError};
+
+    #[test]
+    fn test_get_norm() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let text_without_norms = schema_builder.add_text_field(
+            "text_without_norms",
+            TextOptions::default()
+                .set_indexing_options(TextFieldIndexing::default().set_fieldnorms(false)),
+        );
+        let stored = schema_builder.add_text_field("stored", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let long_text = vec!["token"; 1_000].join(" ");
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                text => "the quick brown fox",
+                text_without_norms => "the quick brown fox",
+                stored => "the quick brown fox",
+            ))?;
+            // The field norms of multivalued fields sum the lengths of the values.
+            index_writer.add_document(doc!(text => "jumps over", text => "the lazy dog"))?;
+            index_writer.add_document(doc!())?;
+            index_writer.add_document(doc!(text => long_text))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let norms: Vec<Option<u32>> = (0..4)
+            .map(|doc| segment_reader.get_norm(text, doc))
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(&norms[..3], &[Some(4), Some(5), Some(0)]);
+        // Long field lengths are approximated.
+        let long_norm = norms[3].unwrap();
+        assert!((900..=1_000).contains(&long_norm));
+        assert_eq!(segment_reader.get_norm(text_without_norms, 0)?, None);
+        assert_eq!(segment_reader.get_norm(stored, 0)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconstruct_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let untokenized = schema_builder.add_text_field("untokenized", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let input = "The quick brown fox jumps over the lazy dog, the end.";
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => "another document about a fox"))?;
+            index_writer.add_document(doc!(text => input, untokenized => "id"))?;
+            index_writer.add_document(doc!())?;
+            index_writer.commit()?;
+        }
+        let mut expected_tokens = Vec::new();
+        let mut token_stream = index.tokenizer_for_field(text)?.token_stream(input);
+        while token_stream.advance() {
+            let token = token_stream.token();
+            expected_tokens.push((token.position as u32, token.text.clone()));
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let tokens = segment_reader.reconstruct_field(text, 1)?;
+        assert_eq!(tokens, expected_tokens);
+        let texts: Vec<&str> = tokens.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(
+            texts.join(" "),
+            "the quick brown fox jumps over the lazy dog the end"
+        );
+        assert!(segment_reader.reconstruct_field(text, 2)?.is_empty());
+        assert!(matches!(
+            segment_reader.reconstruct_field(untokenized, 1),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
 
     #[test]
     fn test_num_alive() -> crate::Result<()> {
@@ -420,4 +701,99 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_alive_bitset_without_deletes_is_dropped() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for name_value in ["a", "b", "c"] {
+            index_writer.add_document(doc!(name => name_value))?;
+        }
+        index_writer.commit()?;
+        let segment = index.segment(index.searchable_segment_metas()?[0].clone());
+
+        let all_alive = AliveBitSet::for_test_from_deleted_docs(&[], 3);
+        let segment_reader = SegmentReader::open_with_custom_alive_set(&segment, Some(all_alive))?;
+        assert!(segment_reader.alive_bitset().is_none());
+        assert!(!segment_reader.has_deletes());
+        assert_eq!(segment_reader.num_docs(), 3);
+
+        let with_deletes = AliveBitSet::for_test_from_deleted_docs(&[1], 3);
+        let segment_reader =
+            SegmentReader::open_with_custom_alive_set(&segment, Some(with_deletes))?;
+        assert_eq!(segment_reader.num_docs(), 2);
+        assert!(segment_reader.is_deleted(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_index_stats() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let untokenized = schema_builder.add_text_field("untokenized", STRING);
+        let stored = schema_builder.add_text_field("stored", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            // Unique terms: the, quick, brown, fox, lazy, dog, sleeps.
+            index_writer.add_document(doc!(
+                text => "the quick brown fox",
+                untokenized => "a",
+            ))?;
+            index_writer.add_document(doc!(text => "the lazy dog", untokenized => "a"))?;
+            index_writer.add_document(doc!(text => "the fox sleeps", untokenized => "b"))?;
+            index_writer.add_document(doc!(stored => "nothing indexed"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let text_stats = segment_reader.field_index_stats(text)?;
+        assert_eq!(text_stats.num_unique_terms, 7);
+        assert_eq!(text_stats.num_postings, 3 + 2 + 1 + 1 + 1 + 1 + 1);
+        assert_eq!(text_stats.total_num_tokens, 10);
+        assert_eq!(text_stats.average_field_length, 2.5);
+        let untokenized_stats = segment_reader.field_index_stats(untokenized)?;
+        assert_eq!(untokenized_stats.num_unique_terms, 2);
+        assert_eq!(untokenized_stats.num_postings, 3);
+        let stored_stats = segment_reader.field_index_stats(stored)?;
+        assert_eq!(stored_stats, FieldIndexStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_size() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast = schema_builder.add_u64_field("fast", FAST);
+        let multi_fast = schema_builder.add_u64_field(
+            "multi_fast",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let indexed = schema_builder.add_u64_field("indexed", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for val in 0u64..1_000 {
+                index_writer.add_document(doc!(
+                    fast => val,
+                    multi_fast => val,
+                    multi_fast => val * 2,
+                    indexed => val,
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let fast_size = segment_reader.fast_field_size(fast);
+        assert!(fast_size > 0);
+        assert_eq!(
+            fast_size,
+            segment_reader.fast_fields().fast_field_data(fast, 0)?.len() as u64
+        );
+        assert!(segment_reader.fast_field_size(multi_fast) > fast_size);
+        assert_eq!(segment_reader.fast_field_size(indexed), 0);
+        Ok(())
+    }
 }
@@ -168,6 +168,33 @@ impl CompositeFile {
             .map(|byte_range| self.data.slice(byte_range.clone()))
     }
 
+    /// Returns the `FileSlice`s stored in the `CompositeFile`, along with their `Field` and
+    /// index, in the order in which they were written.
+    pub(crate) fn file_slices(&self) -> Vec<(Field, usize, FileSlice)> {
+        let mut file_slices: Vec<(FileAddr, Range<usize>)> = self
+            .offsets_index
+            .iter()
+            .map(|(file_addr, byte_range)| (*file_addr, byte_range.clone()))
+            .collect();
+        file_slices.sort_by_key(|(_, byte_range)| byte_range.start);
+        file_slices
+            .into_iter()
+            .map(|(file_addr, byte_range)| {
+                (file_addr.field, file_addr.idx, self.data.slice(byte_range))
+            })
+            .collect()
+    }
+
+    /// Returns the number of bytes of the data stored for a field, summed over all of its
+    /// indexes, or 0 if the field has no data.
+    pub(crate) fn field_num_bytes(&self, field: Field) -> usize {
+        self.offsets_index
+            .iter()
+            .filter(|(file_addr, _)| file_addr.field == field)
+            .map(|(_, byte_range)| byte_range.len())
+            .sum()
+    }
+
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
         let mut fields = HashMap::new();
         for (&field_addr, byte_range) in &self.offsets_index {
@@ -603,10 +603,10 @@ mod tests {
             reader.reload()?;
             let num_segments = reader.searcher().segment_readers().len();
             assert!(num_segments <= 4);
-            let num_components_except_deletes_and_tempstore =
-                crate::core::SegmentComponent::iterator().len() - 2;
+            let num_components_except_deletes_and_temp_files =
+                crate::core::SegmentComponent::iterator().len() - 4;
             assert_eq!(
-                num_segments * num_components_except_deletes_and_tempstore,
+                num_segments * num_components_except_deletes_and_temp_files,
                 mmap_directory.get_cache_info().mmapped.len()
             );
         }
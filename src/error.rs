@@ -98,6 +98,9 @@ pub enum TantivyError {
     /// Index incompatible with current version of Tantivy.
     #[error("{0:?}")]
     IncompatibleIndex(Incompatibility),
+    /// The merge was cancelled with its [`MergeCancellationToken`](crate::MergeCancellationToken).
+    #[error("The merge was cancelled")]
+    MergeCancelled,
     /// An internal error occurred. This is are internal states that should not be reached.
     /// e.g. a datastructure is incorrectly inititalized.
     #[error("Internal error: '{0}'")]
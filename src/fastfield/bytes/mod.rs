@@ -35,6 +35,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_bytes_owned() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let bytes_field = schema_builder.add_bytes_field("bytesfield", FAST);
+        let other_field = schema_builder.add_u64_field("other", STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        let values: Vec<Option<Vec<u8>>> = vec![
+            Some(b"tantivy".to_vec()),
+            None,
+            Some(vec![]),
+            Some(vec![7u8; 100]),
+        ];
+        for value in &values {
+            match value {
+                Some(bytes) => index_writer.add_document(doc!(bytes_field => bytes.clone()))?,
+                None => index_writer.add_document(doc!(other_field => 1u64))?,
+            };
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let bytes_reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .bytes(bytes_field)?;
+        let owned: Vec<_> = (0..values.len() as u32)
+            .map(|doc| {
+                assert_eq!(
+                    bytes_reader.get_owned(doc).as_slice(),
+                    bytes_reader.get_bytes(doc)
+                );
+                bytes_reader.get_owned(doc)
+            })
+            .collect();
+        // Documents that are not in the segment do not have any bytes.
+        assert!(bytes_reader.get_bytes(values.len() as u32).is_empty());
+        assert!(bytes_reader.get_owned(u32::MAX).is_empty());
+        // The owned bytes outlive the reader.
+        drop(bytes_reader);
+        drop(searcher);
+        for (owned_bytes, value) in owned.iter().zip(values.iter()) {
+            let expected: &[u8] = value.as_deref().unwrap_or(&[]);
+            assert_eq!(owned_bytes.as_slice(), expected);
+        }
+        Ok(())
+    }
+
     fn create_index_for_test<T: Into<BytesOptions>>(byte_options: T) -> crate::Result<Searcher> {
         let mut schema_builder = Schema::builder();
         let field = schema_builder.add_bytes_field("string_bytes", byte_options.into());
@@ -33,17 +33,35 @@ impl BytesFastFieldReader {
 
     fn range(&self, doc: DocId) -> (usize, usize) {
         let idx = doc as u64;
+        // The index has one more value than the number of documents.
+        if idx + 1 >= self.idx_reader.num_vals() {
+            return (0, 0);
+        }
         let start = self.idx_reader.get_val(idx) as usize;
         let stop = self.idx_reader.get_val(idx + 1) as usize;
         (start, stop)
     }
 
     /// Returns the bytes associated to the given `doc`
+    ///
+    /// The slice borrows the data of the fast field, without any copy. It is empty if the
+    /// document does not have any bytes, or if `doc` is not a document of the segment.
     pub fn get_bytes(&self, doc: DocId) -> &[u8] {
         let (start, stop) = self.range(doc);
         &self.values.as_slice()[start..stop]
     }
 
+    /// Returns the bytes associated to the given `doc`, as `OwnedBytes` that can outlive
+    /// the reader.
+    ///
+    /// The bytes are not copied: the returned `OwnedBytes` shares the data of the fast field.
+    /// Like for [`BytesFastFieldReader::get_bytes`], they are empty if the document does not
+    /// have any bytes.
+    pub fn get_owned(&self, doc: DocId) -> OwnedBytes {
+        let (start, stop) = self.range(doc);
+        self.values.slice(start..stop)
+    }
+
     /// Returns the length of the bytes associated to the given `doc`
     pub fn num_bytes(&self, doc: DocId) -> usize {
         let (start, stop) = self.range(doc);
@@ -5,7 +5,7 @@ use fastfield_codecs::VecColumn;
 use crate::fastfield::serializer::CompositeFastFieldSerializer;
 use crate::fastfield::MultivalueStartIndex;
 use crate::indexer::doc_id_mapping::DocIdMapping;
-use crate::schema::{Document, Field, Value};
+use crate::schema::{Document, Field};
 use crate::DocId;
 
 /// Writer for byte array (as in, any number of bytes per document) fast fields
@@ -57,11 +57,8 @@ impl BytesFastFieldWriter {
     /// matching field values present in the document.
     pub fn add_document(&mut self, doc: &Document) {
         self.next_doc();
-        for field_value in doc.get_all(self.field) {
-            if let Value::Bytes(ref bytes) = field_value {
-                self.vals.extend_from_slice(bytes);
-                return;
-            }
+        if let Some(bytes) = doc.get_all_bytes(self.field).next() {
+            self.vals.extend_from_slice(bytes);
         }
     }
 
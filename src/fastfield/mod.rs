@@ -30,9 +30,12 @@ pub(crate) use self::multivalued::MultivalueStartIndex;
 pub use self::multivalued::{MultiValuedFastFieldReader, MultiValuedFastFieldWriter};
 pub use self::readers::FastFieldReaders;
 pub(crate) use self::readers::{type_and_cardinality, FastType};
-pub use self::serializer::{Column, CompositeFastFieldSerializer, FastFieldStats};
+pub use self::serializer::{
+    Column, CompositeFastFieldSerializer, FastFieldCodecType, FastFieldStats,
+};
+pub(crate) use self::writer::fast_field_default_value;
 pub use self::writer::{FastFieldsWriter, IntFastFieldWriter};
-use crate::schema::{Cardinality, FieldType, Type, Value};
+use crate::schema::{Cardinality, FieldType, FloatPrecision, Type, Value};
 use crate::{DateTime, DocId};
 
 mod alive_bitset;
@@ -160,6 +163,32 @@ impl FastValue for DateTime {
     }
 }
 
+/// Number of low bits of the `u64` representation of an f64 dropped to store it with the
+/// precision of an f32: an f64 has a 52 bits mantissa, an f32 a 23 bits one.
+const F32_PRECISION_DROPPED_BITS: u32 = 52 - 23;
+
+/// Truncates the `u64` representation of an f64 value, as given by `common::f64_to_u64`, to
+/// the precision of an f32.
+///
+/// The mapping is monotonic, so that the order of the values is preserved. The dropped low
+/// bits being zero, the fast field serializer then stores the values with their common
+/// divisor only.
+pub(crate) fn truncate_to_f32_precision(val: u64) -> u64 {
+    val & !((1u64 << F32_PRECISION_DROPPED_BITS) - 1)
+}
+
+/// Truncates the `u64` representation of a value of a fast field of type `field_type` to the
+/// precision with which its values are stored, so that the value of a query can be compared
+/// to them.
+pub(crate) fn truncate_to_stored_precision(val: u64, field_type: &FieldType) -> u64 {
+    match field_type {
+        FieldType::F64(options) if options.get_float_precision() == FloatPrecision::F32 => {
+            truncate_to_f32_precision(val)
+        }
+        _ => val,
+    }
+}
+
 fn value_to_u64(value: &Value) -> u64 {
     match value {
         Value::U64(val) => val.to_u64(),
@@ -195,7 +224,7 @@ impl FastFieldType {
 mod tests {
 
     use std::collections::HashMap;
-    use std::ops::Range;
+    use std::ops::{Bound, Range};
     use std::path::Path;
     use std::sync::Arc;
 
@@ -207,11 +236,18 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use super::*;
-    use crate::directory::{CompositeFile, Directory, RamDirectory, WritePtr};
+    use crate::collector::Count;
+    use crate::directory::{
+        CompositeFile, Directory, FileSlice, OwnedBytes, RamDirectory, WritePtr,
+    };
     use crate::merge_policy::NoMergePolicy;
-    use crate::schema::{Document, Field, Schema, SchemaBuilder, FAST, STRING, TEXT};
+    use crate::query::{FastFieldEqualityQuery, FastFieldRangeQuery, FastFieldSetQuery, Query};
+    use crate::schema::{
+        Document, Field, FloatPrecision, MultiValueReduction, NumericOptions, Schema,
+        SchemaBuilder, FAST, INDEXED, STRING, TEXT,
+    };
     use crate::time::OffsetDateTime;
-    use crate::{DateOptions, DatePrecision, Index, SegmentId, SegmentReader};
+    use crate::{DateOptions, DatePrecision, Index, IndexSettings, SegmentId, SegmentReader};
 
     pub static SCHEMA: Lazy<Schema> = Lazy::new(|| {
         let mut schema_builder = Schema::builder();
@@ -261,6 +297,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fast_field_values_before_serialization() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let schema = schema_builder.build();
+        let mut fast_field_writers = FastFieldsWriter::from_schema(&schema);
+        assert_eq!(
+            fast_field_writers.get_field_writer(id).unwrap().value_at(0),
+            None
+        );
+        fast_field_writers.add_document(&doc!(id => 3u64, delta => -1i64, not_fast => 3u64));
+        fast_field_writers.add_document(&doc!(id => 7u64, delta => 5i64, not_fast => 7u64));
+        fast_field_writers.add_document(&doc!(id => 11u64));
+        // Reads the values mid-build, before they are serialized.
+        let id_writer = fast_field_writers.get_field_writer(id).unwrap();
+        assert_eq!(id_writer.num_vals(), 3);
+        assert_eq!(id_writer.value_at(1), Some(7));
+        assert_eq!(id_writer.value_at(3), None);
+        assert_eq!(id_writer.iter_values().collect::<Vec<_>>(), vec![3, 7, 11]);
+        let delta_writer = fast_field_writers.get_field_writer(delta).unwrap();
+        // Documents without a value get the default value.
+        assert_eq!(
+            delta_writer
+                .iter_values()
+                .map(i64::from_u64)
+                .collect::<Vec<_>>(),
+            vec![-1, 5, 0]
+        );
+        assert!(fast_field_writers.get_field_writer(not_fast).is_none());
+    }
+
     #[test]
     fn test_intfastfield_large() -> crate::Result<()> {
         let path = Path::new("test");
@@ -303,6 +372,77 @@ mod tests {
         Ok(())
     }
 
+    // Composite fast field file of `write_endianness_fixture`, as produced on a little-endian
+    // platform. All of the fast field encodings are explicitly little-endian, so that this
+    // file is read identically on any platform.
+    const ENDIANNESS_FIXTURE: &[u8] = &[
+        131, 129, 7, 14, 24, 40, 64, 96, 0, 1, 129, 128, 1, 0, 0, 0, 0, 0, 0, 0, 0, 6, 202, 154,
+        59, 0, 0, 0, 0, 7, 7, 6, 5, 4, 3, 2, 1, 0, 0, 0, 0, 0, 0, 0, 131, 128, 7, 0, 0, 0, 0, 0, 0,
+        0, 0, 129, 133, 1, 153, 153, 153, 153, 153, 153, 153, 25, 155, 153, 153, 153, 153, 153,
+        153, 25, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 131, 127, 127, 127, 127, 127, 127,
+        127, 23, 192, 1, 0, 0, 0, 114, 11, 40, 105, 1, 129, 128, 1, 1, 0, 0, 0, 0, 0, 200, 127, 0,
+        0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 32, 95, 160, 210, 129, 0, 0, 0, 0, 0, 0, 0, 131, 128, 0, 0,
+        0, 0, 128, 172, 1, 0, 0, 0, 128, 173, 2, 0, 0, 0, 128, 19, 0, 0, 0,
+    ];
+
+    fn write_endianness_fixture(directory: &RamDirectory, path: &Path) -> crate::Result<Schema> {
+        let mut schema_builder = Schema::builder();
+        let u64_field = schema_builder.add_u64_field("u64", FAST);
+        let i64_field = schema_builder.add_i64_field("i64", FAST);
+        let f64_field = schema_builder.add_f64_field("f64", FAST);
+        let schema = schema_builder.build();
+        let write: WritePtr = directory.open_write(path)?;
+        let mut serializer = CompositeFastFieldSerializer::from_write(write)?;
+        let mut fast_field_writers = FastFieldsWriter::from_schema(&schema);
+        fast_field_writers.add_document(&doc!(
+            u64_field => 1u64,
+            i64_field => -3i64,
+            f64_field => 1.5f64,
+        ));
+        fast_field_writers.add_document(&doc!(
+            u64_field => 1_000_000_007u64,
+            i64_field => 7i64,
+            f64_field => -0.25f64,
+        ));
+        fast_field_writers.add_document(&doc!(
+            u64_field => 0x0102_0304_0506_0708u64,
+            i64_field => i64::MIN,
+            f64_field => 1e10f64,
+        ));
+        fast_field_writers.serialize(&mut serializer, &HashMap::new(), None)?;
+        serializer.close()?;
+        Ok(schema)
+    }
+
+    #[test]
+    fn test_fast_field_endianness_fixture() -> crate::Result<()> {
+        let path = Path::new("test");
+        let directory = RamDirectory::create();
+        let schema = write_endianness_fixture(&directory, path)?;
+        let bytes = directory.open_read(path)?.read_bytes()?;
+        assert_eq!(bytes.as_slice(), ENDIANNESS_FIXTURE);
+
+        let file = FileSlice::from(ENDIANNESS_FIXTURE.to_vec());
+        let composite_file = CompositeFile::open(&file)?;
+        let open_field = |name: &str| -> crate::Result<OwnedBytes> {
+            let field = schema.get_field(name).unwrap();
+            Ok(composite_file.open_read(field).unwrap().read_bytes()?)
+        };
+        let u64_reader = open::<u64>(open_field("u64")?)?;
+        assert_eq!(
+            u64_reader.iter().collect::<Vec<_>>(),
+            vec![1, 1_000_000_007, 0x0102_0304_0506_0708]
+        );
+        let i64_reader = open::<i64>(open_field("i64")?)?;
+        assert_eq!(i64_reader.iter().collect::<Vec<_>>(), vec![-3, 7, i64::MIN]);
+        let f64_reader = open::<f64>(open_field("f64")?)?;
+        assert_eq!(
+            f64_reader.iter().collect::<Vec<_>>(),
+            vec![1.5, -0.25, 1e10]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_intfastfield_null_amplitude() -> crate::Result<()> {
         let path = Path::new("test");
@@ -804,6 +944,219 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fastfield_sum_reduction() -> crate::Result<()> {
+        let sum_options =
+            NumericOptions::from(FAST).set_multi_value_reduction(MultiValueReduction::Sum);
+        let mut schema_builder = Schema::builder();
+        let u64_field = schema_builder.add_u64_field("u64", sum_options.clone().set_indexed());
+        let i64_field = schema_builder.add_i64_field("i64", sum_options.clone());
+        let f64_field = schema_builder.add_f64_field("f64", sum_options);
+        let first_field = schema_builder.add_u64_field("first", FAST | INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            u64_field => 1u64,
+            u64_field => 2u64,
+            u64_field => 3u64,
+            i64_field => -3i64,
+            i64_field => 10i64,
+            f64_field => 0.5f64,
+            f64_field => 1.25f64,
+            first_field => 4u64,
+            first_field => 6u64,
+        ))?;
+        index_writer.add_document(doc!(
+            u64_field => u64::MAX - 1,
+            u64_field => 5u64,
+            i64_field => i64::MIN,
+            i64_field => -1i64,
+        ))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let u64_reader = fast_fields.u64(u64_field)?;
+        let i64_reader = fast_fields.i64(i64_field)?;
+        let f64_reader = fast_fields.f64(f64_field)?;
+        let first_reader = fast_fields.u64(first_field)?;
+        assert_eq!(u64_reader.get_val(0), 6);
+        assert_eq!(i64_reader.get_val(0), 7);
+        assert_eq!(f64_reader.get_val(0), 1.75);
+        assert_eq!(first_reader.get_val(0), 4);
+        // The sums saturate instead of overflowing.
+        assert_eq!(u64_reader.get_val(1), u64::MAX);
+        assert_eq!(i64_reader.get_val(1), i64::MIN);
+        // Documents without values get the default value.
+        assert_eq!(u64_reader.get_val(2), 0);
+        assert_eq!(i64_reader.get_val(2), 0);
+        assert_eq!(f64_reader.get_val(2), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fastfield_f32_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let f64_field = schema_builder.add_f64_field("f64", FAST);
+        let f32_field = schema_builder.add_f64_field(
+            "f32",
+            NumericOptions::from(FAST).set_float_precision(FloatPrecision::F32),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut rng = StdRng::seed_from_u64(2);
+        let vals: Vec<f64> = (0..10_000).map(|_| rng.gen_range(-1e6..1e6)).collect();
+        for &val in &vals {
+            index_writer.add_document(doc!(f64_field => val, f32_field => val))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let f32_reader = fast_fields.f64(f32_field)?;
+        for (doc, &val) in vals.iter().enumerate() {
+            let stored_val = f32_reader.get_val(doc as u64);
+            assert!(stored_val <= val);
+            assert!((val - stored_val).abs() <= val.abs() * f32::EPSILON as f64);
+        }
+        // The order of the values is preserved.
+        let mut sorted_docs: Vec<u64> = (0..vals.len() as u64).collect();
+        sorted_docs.sort_by(|&left, &right| vals[left as usize].total_cmp(&vals[right as usize]));
+        for docs in sorted_docs.windows(2) {
+            assert!(f32_reader.get_val(docs[0]) <= f32_reader.get_val(docs[1]));
+        }
+        // Range queries on bounds having the precision of an f32 match the same documents.
+        for (lower, upper) in [(-1e5, 2e5), (0.0, 100.0), (-1e6, -0.5)] {
+            let count = |field| {
+                let query =
+                    FastFieldRangeQuery::new(field, Bound::Included(lower), Bound::Excluded(upper));
+                searcher.search(&query, &Count)
+            };
+            assert_eq!(count(f32_field)?, count(f64_field)?);
+        }
+        let f64_len = fast_fields.fast_field_data(f64_field, 0)?.len();
+        let f32_len = fast_fields.fast_field_data(f32_field, 0)?.len();
+        assert!(f32_len * 10 < f64_len * 6, "{} {}", f32_len, f64_len);
+
+        // The values of the queries are truncated like the stored values, including the
+        // values that an f32 cannot represent exactly.
+        index_writer.delete_all_documents()?;
+        for val in [0.1, -0.1, 0.2] {
+            index_writer.add_document(doc!(f64_field => val, f32_field => val))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_ne!(
+            searcher
+                .segment_reader(0)
+                .fast_fields()
+                .f64(f32_field)?
+                .get_val(0),
+            0.1
+        );
+        let count = |query: &dyn Query| searcher.search(query, &Count);
+        assert_eq!(count(&FastFieldEqualityQuery::new(f32_field, 0.1))?, 1);
+        assert_eq!(count(&FastFieldEqualityQuery::new(f32_field, -0.1))?, 1);
+        assert_eq!(
+            count(&FastFieldSetQuery::new(f32_field, vec![0.1, -0.1, 0.3]))?,
+            2
+        );
+        // (left bound, right bound, expected count)
+        let ranges = [
+            (Bound::Included(0.1), Bound::Included(0.1), 1),
+            (Bound::Included(0.1), Bound::Unbounded, 2),
+            (Bound::Excluded(0.1), Bound::Unbounded, 1),
+            (Bound::Unbounded, Bound::Included(0.1), 2),
+            (Bound::Unbounded, Bound::Excluded(0.1), 1),
+            (Bound::Excluded(-0.1), Bound::Excluded(0.2), 1),
+            (Bound::Included(-0.1), Bound::Included(0.2), 3),
+        ];
+        for (lower, upper, expected_count) in ranges {
+            for field in [f32_field, f64_field] {
+                let query = FastFieldRangeQuery::new(field, lower, upper);
+                assert_eq!(count(&query)?, expected_count, "{:?}", query);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multivalued_date_fastfield_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let dates_field = schema_builder.add_date_field(
+            "dates",
+            DateOptions::default()
+                .set_precision(DatePrecision::Microseconds)
+                .set_fast(Cardinality::MultiValues),
+        );
+        let schema = schema_builder.build();
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                dates_field => DateTime::from_timestamp_micros(1_500_001),
+                dates_field => DateTime::from_timestamp_micros(3_000_002)
+            ))?;
+            index_writer.add_document(doc!())?;
+            index_writer.commit()?;
+        }
+        // The precision of the field is made coarser after the first segment was written.
+        let meta_path = Path::new("meta.json");
+        let meta = String::from_utf8(directory.atomic_read(meta_path)?).unwrap();
+        let microseconds = r#""precision": "microseconds""#;
+        assert!(meta.contains(microseconds));
+        let meta = meta.replace(microseconds, r#""precision": "seconds""#);
+        directory.atomic_write(meta_path, meta.as_bytes())?;
+        let index = Index::open(directory)?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(
+            dates_field => DateTime::from_timestamp_micros(5_999_999)
+        ))?;
+        index_writer.commit()?;
+
+        let read_dates = |index: &Index| -> crate::Result<Vec<Vec<i64>>> {
+            let searcher = index.reader()?.searcher();
+            let mut dates_per_doc = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let dates_reader = segment_reader.fast_fields().dates(dates_field)?;
+                let mut dates = Vec::new();
+                for doc in 0..segment_reader.max_doc() {
+                    dates_reader.get_vals(doc, &mut dates);
+                    dates_per_doc.push(
+                        dates
+                            .iter()
+                            .map(|date| date.into_timestamp_micros())
+                            .collect(),
+                    );
+                }
+            }
+            dates_per_doc.sort();
+            Ok(dates_per_doc)
+        };
+        let expected: Vec<Vec<i64>> =
+            vec![vec![], vec![], vec![1_000_000, 3_000_000], vec![5_000_000]];
+        assert_eq!(read_dates(&index)?, expected);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(read_dates(&index)?, expected);
+        // The merged segment stores truncated values.
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let vals_bytes = fast_fields.fast_field_data(dates_field, 1)?.read_bytes()?;
+        let mut stored_vals: Vec<i64> = open::<DateTime>(vals_bytes)?
+            .iter()
+            .map(|date| date.into_timestamp_micros())
+            .collect();
+        stored_vals.sort_unstable();
+        assert_eq!(stored_vals, vec![1_000_000, 3_000_000, 5_000_000]);
+        Ok(())
+    }
+
     #[test]
     pub fn test_fastfield_bool() {
         let test_fastfield: Arc<dyn Column<bool>> =
@@ -936,6 +1289,95 @@ mod tests {
         Ok(directory)
     }
 
+    #[test]
+    fn test_codec_type() -> crate::Result<()> {
+        let docs: Vec<Document> = (0..10_000u64).map(|i| doc!(*FIELD=>i * 3)).collect();
+        for codec_type in fastfield_codecs::ALL_CODEC_TYPES {
+            let directory = get_index(&docs[..], &SCHEMA, &[codec_type])?;
+            let file = directory.open_read(Path::new("test"))?;
+            let fast_field_readers =
+                FastFieldReaders::new(SCHEMA.clone(), CompositeFile::open(&file)?);
+            assert_eq!(fast_field_readers.codec_type(*FIELD)?, codec_type);
+            let fast_field_reader = fast_field_readers.u64(*FIELD)?;
+            assert_eq!(fast_field_reader.get_val(10), 30);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_type_in_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let linear_field = schema_builder.add_u64_field("linear", FAST);
+        let multi_field = schema_builder.add_u64_field(
+            "multi",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..10_000u64 {
+            index_writer.add_document(doc!(
+                linear_field => 1_000 + i * 7,
+                multi_field => (i * 7_919) % 1_000,
+                multi_field => (i * 1_013) % 1_000,
+                text_field => "hello",
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert_eq!(
+            fast_fields.codec_type(linear_field)?,
+            FastFieldCodecType::Linear
+        );
+        assert_eq!(
+            fast_fields.codec_type(multi_field)?,
+            FastFieldCodecType::Bitpacked
+        );
+        assert!(matches!(
+            fast_fields.codec_type(text_field),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_columns_are_opened_lazily() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fields: Vec<Field> = (0..50)
+            .map(|i| schema_builder.add_u64_field(&format!("field{}", i), FAST))
+            .collect();
+        let multi_field = schema_builder.add_u64_field(
+            "multi",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            let mut doc = Document::default();
+            for &field in &fields {
+                doc.add_u64(field, i);
+            }
+            doc.add_u64(multi_field, i);
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert_eq!(fast_fields.num_opened_columns(), 0);
+        let column = fast_fields.u64(fields[7])?;
+        assert_eq!(column.get_val(3), 3);
+        assert_eq!(fast_fields.num_opened_columns(), 1);
+        // The column is opened once, and then shared.
+        assert!(Arc::ptr_eq(&column, &fast_fields.u64(fields[7])?));
+        assert_eq!(fast_fields.num_opened_columns(), 1);
+        let mut vals = Vec::new();
+        fast_fields.u64s(multi_field)?.get_vals(5, &mut vals);
+        assert_eq!(vals, vec![5]);
+        assert_eq!(fast_fields.num_opened_columns(), 3);
+        Ok(())
+    }
+
     #[test]
     pub fn test_gcd_date() -> crate::Result<()> {
         let size_prec_sec =
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use fastfield_codecs::Column;
 
 use crate::fastfield::{FastValue, MultiValueLength};
-use crate::DocId;
+use crate::{DocId, TantivyError};
 
 /// Reader for a multivalued `u64` fast field.
 ///
@@ -85,6 +85,33 @@ impl<Item: FastValue> MultiValuedFastFieldReader<Item> {
     pub fn total_num_vals(&self) -> u64 {
         self.idx_reader.max_value()
     }
+
+    /// Returns a single-valued view over the fast field, if every document has exactly one
+    /// value.
+    ///
+    /// This makes it possible to read a field declared as multivalued, but which only ever
+    /// received one value per document, as a single-valued fast field: the returned column
+    /// associates to each document its value.
+    ///
+    /// Checking the number of values of the documents requires to scan the index of the
+    /// field. An error is returned if a document has no value, or more than one value.
+    pub fn to_single_valued(&self) -> crate::Result<Arc<dyn Column<Item>>> {
+        let num_docs = self.idx_reader.num_vals().saturating_sub(1);
+        let mut offsets = self.idx_reader.iter().enumerate();
+        if let Some(doc) = offsets.find_map(|(idx, offset)| (offset != idx as u64).then_some(idx)) {
+            // The offset of the document `doc` is not aligned: the previous document does not
+            // have exactly one value.
+            let doc = doc.saturating_sub(1) as DocId;
+            return Err(TantivyError::InvalidArgument(format!(
+                "The multivalued fast field cannot be read as single-valued: document {} has {} \
+                 values.",
+                doc,
+                self.num_vals(doc)
+            )));
+        }
+        debug_assert_eq!(self.vals_reader.num_vals(), num_docs);
+        Ok(self.vals_reader.clone())
+    }
 }
 
 impl<Item: FastValue> MultiValueLength for MultiValuedFastFieldReader<Item> {
@@ -183,4 +210,45 @@ mod tests {
         assert_eq!(field_reader.max_value(), 6);
         Ok(())
     }
+
+    #[test]
+    fn test_multifastfield_reader_to_single_valued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field_options = NumericOptions::default().set_fast(Cardinality::MultiValues);
+        let single_field = schema_builder.add_u64_field("single", field_options.clone());
+        let multi_field = schema_builder.add_u64_field("multi", field_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(single_field => 3u64, multi_field => 1u64))?;
+        index_writer.add_document(doc!(single_field => 1u64))?;
+        index_writer.add_document(doc!(
+            single_field => 4u64,
+            multi_field => 2u64,
+            multi_field => 5u64,
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+
+        let single_reader = fast_fields.u64s(single_field)?.to_single_valued()?;
+        assert_eq!(single_reader.num_vals(), 3);
+        assert_eq!(single_reader.get_val(0), 3);
+        assert_eq!(single_reader.get_val(1), 1);
+        assert_eq!(single_reader.get_val(2), 4);
+        assert_eq!(single_reader.min_value(), 1);
+        assert_eq!(single_reader.max_value(), 4);
+
+        let err = fast_fields
+            .u64s(multi_field)?
+            .to_single_valued()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'The multivalued fast field cannot be read as \
+             single-valued: document 1 has 0 values.'"
+        );
+        Ok(())
+    }
 }
@@ -4,10 +4,12 @@ use std::sync::Mutex;
 use fastfield_codecs::{Column, MonotonicallyMappableToU64, VecColumn};
 use fnv::FnvHashMap;
 
-use crate::fastfield::{value_to_u64, CompositeFastFieldSerializer, FastFieldType};
+use crate::fastfield::{
+    truncate_to_f32_precision, value_to_u64, CompositeFastFieldSerializer, FastFieldType,
+};
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::postings::UnorderedTermId;
-use crate::schema::{Document, Field, Value};
+use crate::schema::{Document, Field, FloatPrecision, Value};
 use crate::termdict::TermOrdinal;
 use crate::{DatePrecision, DocId};
 
@@ -40,6 +42,7 @@ pub struct MultiValuedFastFieldWriter {
     vals: Vec<UnorderedTermId>,
     doc_index: Vec<u64>,
     fast_field_type: FastFieldType,
+    float_precision: FloatPrecision,
 }
 
 impl MultiValuedFastFieldWriter {
@@ -55,9 +58,15 @@ impl MultiValuedFastFieldWriter {
             vals: Vec::new(),
             doc_index: Vec::new(),
             fast_field_type,
+            float_precision: FloatPrecision::F64,
         }
     }
 
+    /// Sets the precision with which the f64 values are stored.
+    pub(crate) fn set_float_precision(&mut self, float_precision: FloatPrecision) {
+        self.float_precision = float_precision;
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
         self.vals.capacity() * std::mem::size_of::<UnorderedTermId>()
@@ -87,17 +96,16 @@ impl MultiValuedFastFieldWriter {
         if self.fast_field_type.is_storing_term_ids() {
             return;
         }
-        for field_value in doc.field_values() {
-            if field_value.field == self.field {
-                let value = field_value.value();
-                let value_u64 = match (self.precision_opt, value) {
-                    (Some(precision), Value::Date(date_val)) => {
-                        date_val.truncate(precision).to_u64()
-                    }
-                    _ => value_to_u64(value),
-                };
-                self.add_val(value_u64);
-            }
+        for value in doc.get_all(self.field) {
+            let value_u64 = match (self.precision_opt, value) {
+                (Some(precision), Value::Date(date_val)) => date_val.truncate(precision).to_u64(),
+                _ => value_to_u64(value),
+            };
+            let value_u64 = match self.float_precision {
+                FloatPrecision::F64 => value_u64,
+                FloatPrecision::F32 => truncate_to_f32_precision(value_u64),
+            };
+            self.add_val(value_u64);
         }
     }
 
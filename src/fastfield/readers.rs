@@ -1,23 +1,32 @@
-use std::sync::Arc;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
-use fastfield_codecs::{open, Column};
+use fastfield_codecs::{open, Column, FastFieldCodecType, MonotonicallyMappableToU64};
 
 use crate::directory::{CompositeFile, FileSlice};
 use crate::fastfield::{
     BytesFastFieldReader, FastFieldNotAvailableError, FastValue, MultiValuedFastFieldReader,
 };
-use crate::schema::{Cardinality, Field, FieldType, Schema};
+use crate::schema::{Cardinality, DatePrecision, Field, FieldType, Schema};
 use crate::space_usage::PerFieldSpaceUsage;
 use crate::{DateTime, TantivyError};
 
+/// Key of an opened column: the field, the index of the column within the field, and the
+/// type of its values.
+type ColumnKey = (Field, usize, TypeId);
+
 /// Provides access to all of the BitpackedFastFieldReader.
 ///
-/// Internally, `FastFieldReaders` have preloaded fast field readers,
-/// and just wraps several `HashMap`.
+/// Columns are opened lazily, the first time they are accessed, and are then cached:
+/// the columns that are never accessed cost nothing but the reading of the composite file
+/// footer.
 #[derive(Clone)]
 pub struct FastFieldReaders {
     schema: Schema,
     fast_fields_composite: CompositeFile,
+    // Each value is an `Arc<dyn Column<T>>`, with `T` the type of the key.
+    opened_columns: Arc<RwLock<HashMap<ColumnKey, Arc<dyn Any + Send + Sync>>>>,
 }
 #[derive(Eq, PartialEq, Debug)]
 pub(crate) enum FastType {
@@ -53,18 +62,78 @@ pub(crate) fn type_and_cardinality(field_type: &FieldType) -> Option<(FastType,
     }
 }
 
+/// Returns true if all of the dates of a column, described by its minimum value and the gcd
+/// of the differences to this minimum, are already truncated to `precision`.
+fn is_truncated((min_value, gcd): (u64, u64), precision: DatePrecision) -> bool {
+    let unit_micros = match precision {
+        DatePrecision::Seconds => 1_000_000,
+        DatePrecision::Milliseconds => 1_000,
+        DatePrecision::Microseconds => 1,
+    };
+    let min_date = DateTime::from_u64(min_value);
+    min_date.truncate(precision) == min_date && gcd % unit_micros == 0
+}
+
+/// Column truncating the dates of the underlying column to a given precision.
+///
+/// Dates are truncated at indexing time, so this column is only used for the segments
+/// holding finer values, e.g. written before the precision of the field was changed.
+///
+/// Truncation is monotonic, so the min and max values of the underlying column remain
+/// valid bounds.
+struct TruncatedDateColumn {
+    column: Arc<dyn Column<u64>>,
+    precision: DatePrecision,
+}
+
+impl TruncatedDateColumn {
+    #[inline]
+    fn truncate(&self, val: u64) -> u64 {
+        DateTime::from_u64(val).truncate(self.precision).to_u64()
+    }
+}
+
+impl<T: FastValue> Column<T> for TruncatedDateColumn {
+    #[inline]
+    fn get_val(&self, idx: u64) -> T {
+        T::from_u64(self.truncate(self.column.get_val(idx)))
+    }
+
+    fn min_value(&self) -> T {
+        T::from_u64(self.truncate(self.column.min_value()))
+    }
+
+    fn max_value(&self) -> T {
+        T::from_u64(self.truncate(self.column.max_value()))
+    }
+
+    fn num_vals(&self) -> u64 {
+        self.column.num_vals()
+    }
+}
+
 impl FastFieldReaders {
     pub(crate) fn new(schema: Schema, fast_fields_composite: CompositeFile) -> FastFieldReaders {
         FastFieldReaders {
             schema,
             fast_fields_composite,
+            opened_columns: Arc::default(),
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn num_opened_columns(&self) -> usize {
+        self.opened_columns.read().unwrap().len()
+    }
+
     pub(crate) fn space_usage(&self) -> PerFieldSpaceUsage {
         self.fast_fields_composite.space_usage()
     }
 
+    pub(crate) fn field_num_bytes(&self, field: Field) -> usize {
+        self.fast_fields_composite.field_num_bytes(field)
+    }
+
     #[doc(hidden)]
     pub fn fast_field_data(&self, field: Field, idx: usize) -> crate::Result<FileSlice> {
         self.fast_fields_composite
@@ -112,13 +181,136 @@ impl FastFieldReaders {
         &self,
         field: Field,
         index: usize,
+    ) -> crate::Result<Arc<dyn Column<TFastValue>>> {
+        let key = (field, index, TypeId::of::<TFastValue>());
+        if let Some(column) = self.opened_columns.read().unwrap().get(&key) {
+            return Ok(column
+                .downcast_ref::<Arc<dyn Column<TFastValue>>>()
+                .expect("the type of an opened column does not match its key")
+                .clone());
+        }
+        let column = self.open_column::<TFastValue>(field, index)?;
+        // Another thread may have opened the column in the meantime, in which case both
+        // columns are equivalent.
+        self.opened_columns
+            .write()
+            .unwrap()
+            .insert(key, Arc::new(column.clone()));
+        Ok(column)
+    }
+
+    fn open_column<TFastValue: FastValue>(
+        &self,
+        field: Field,
+        index: usize,
     ) -> crate::Result<Arc<dyn Column<TFastValue>>> {
         let fast_field_slice = self.fast_field_data(field, index)?;
         let bytes = fast_field_slice.read_bytes()?;
+        if let Some(precision) = self.date_precision(field, index) {
+            if !is_truncated(
+                fastfield_codecs::min_value_and_gcd(bytes.clone())?,
+                precision,
+            ) {
+                let column: Arc<dyn Column<u64>> = fastfield_codecs::open(bytes)?;
+                return Ok(Arc::new(TruncatedDateColumn { column, precision }));
+            }
+        }
         let column = fastfield_codecs::open(bytes)?;
         Ok(column)
     }
 
+    /// Returns the precision of the values stored at `index` for the date fast field
+    /// `field`, if those values need to be truncated on read.
+    ///
+    /// Values are truncated at indexing time, but segments written with a finer precision
+    /// (e.g. before the precision of the field was changed) may still hold finer values.
+    fn date_precision(&self, field: Field, index: usize) -> Option<DatePrecision> {
+        let field_entry = self.schema.get_field_entry(field);
+        let date_options = if let FieldType::Date(date_options) = field_entry.field_type() {
+            date_options
+        } else {
+            return None;
+        };
+        let values_index = match date_options.get_fastfield_cardinality()? {
+            Cardinality::SingleValue => 0,
+            Cardinality::MultiValues => 1,
+        };
+        let precision = date_options.get_precision();
+        if index != values_index || precision == DatePrecision::Microseconds {
+            return None;
+        }
+        Some(precision)
+    }
+
+    /// Returns the minimum and the maximum values of the fast field `field` in the segment, in
+    /// their `u64` representation, or `None` if the segment has no value for the field.
+    ///
+    /// The values of the deleted documents are taken into account, so that the range
+    /// should be considered as bounds of the actual values.
+    ///
+    /// If `field` is not a numerical or date fast field, this method returns an Error.
+    pub fn u64_range(&self, field: Field) -> crate::Result<Option<(u64, u64)>> {
+        let field_entry = self.schema.get_field_entry(field);
+        let cardinality = match type_and_cardinality(field_entry.field_type()) {
+            // The values of facets and strings are term ordinals, which are segment-local.
+            Some((_, cardinality))
+                if !matches!(
+                    field_entry.field_type(),
+                    FieldType::Facet(_) | FieldType::Str(_)
+                ) =>
+            {
+                cardinality
+            }
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a numerical fast field.",
+                    field_entry.name()
+                )));
+            }
+        };
+        let idx = match cardinality {
+            Cardinality::SingleValue => 0,
+            Cardinality::MultiValues => 1,
+        };
+        // Segments written before the field was a fast field do not have its values.
+        if self
+            .fast_fields_composite
+            .open_read_with_idx(field, idx)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let column = self.typed_fast_field_reader_with_idx::<u64>(field, idx)?;
+        if column.num_vals() == 0 {
+            return Ok(None);
+        }
+        Ok(Some((column.min_value(), column.max_value())))
+    }
+
+    /// Returns the codec used to encode the values of the fast field `field`, as persisted
+    /// in its header.
+    ///
+    /// For multivalued fast fields, this is the codec used for the values, not for the
+    /// index of the values of each document.
+    ///
+    /// If `field` is not a fast field, this method returns an Error.
+    pub fn codec_type(&self, field: Field) -> crate::Result<FastFieldCodecType> {
+        let field_entry = self.schema.get_field_entry(field);
+        let (_, cardinality) = type_and_cardinality(field_entry.field_type()).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                field_entry.name()
+            ))
+        })?;
+        let idx = match cardinality {
+            Cardinality::SingleValue => 0,
+            Cardinality::MultiValues => 1,
+        };
+        let fast_field_slice = self.fast_field_data(field, idx)?;
+        let codec_type = fastfield_codecs::codec_type(fast_field_slice.read_bytes()?)?;
+        Ok(codec_type)
+    }
+
     pub(crate) fn typed_fast_field_reader<TFastValue: FastValue>(
         &self,
         field: Field,
@@ -162,6 +354,8 @@ impl FastFieldReaders {
 
     /// Returns the `date` fast field reader reader associated to `field`.
     ///
+    /// Values are truncated to the precision of the field.
+    ///
     /// If `field` is not a date fast field, this method returns an Error.
     pub fn date(&self, field: Field) -> crate::Result<Arc<dyn Column<DateTime>>> {
         self.check_type(field, FastType::Date, Cardinality::SingleValue)?;
@@ -227,6 +421,8 @@ impl FastFieldReaders {
     /// Returns a `time::OffsetDateTime` multi-valued fast field reader reader associated to
     /// `field`.
     ///
+    /// Values are truncated to the precision of the field.
+    ///
     /// If `field` is not a `time::OffsetDateTime` multi-valued fast field, this method returns an
     /// Error.
     pub fn dates(&self, field: Field) -> crate::Result<MultiValuedFastFieldReader<DateTime>> {
@@ -256,3 +452,25 @@ impl FastFieldReaders {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fastfield_codecs::MonotonicallyMappableToU64;
+
+    use super::is_truncated;
+    use crate::schema::DatePrecision;
+    use crate::DateTime;
+
+    #[test]
+    fn test_is_truncated() {
+        let seconds = DateTime::from_timestamp_secs(-3).to_u64();
+        assert!(is_truncated((seconds, 0), DatePrecision::Seconds));
+        assert!(is_truncated((seconds, 2_000_000), DatePrecision::Seconds));
+        assert!(!is_truncated((seconds, 1_000), DatePrecision::Seconds));
+        assert!(is_truncated((seconds, 1_000), DatePrecision::Milliseconds));
+        let millis = DateTime::from_timestamp_millis(1_500).to_u64();
+        assert!(!is_truncated((millis, 0), DatePrecision::Seconds));
+        assert!(is_truncated((millis, 0), DatePrecision::Milliseconds));
+        assert!(!is_truncated((millis, 1), DatePrecision::Milliseconds));
+    }
+}
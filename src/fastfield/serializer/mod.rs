@@ -1,9 +1,9 @@
 use std::io::{self, Write};
 
-pub use fastfield_codecs::{Column, FastFieldStats};
-use fastfield_codecs::{FastFieldCodecType, MonotonicallyMappableToU64, ALL_CODEC_TYPES};
+pub use fastfield_codecs::{Column, FastFieldCodecType, FastFieldStats};
+use fastfield_codecs::{MonotonicallyMappableToU64, ALL_CODEC_TYPES};
 
-use crate::directory::{CompositeWrite, WritePtr};
+use crate::directory::{CompositeFile, CompositeWrite, WritePtr};
 use crate::schema::Field;
 
 /// `CompositeFastFieldSerializer` is in charge of serializing
@@ -79,6 +79,15 @@ impl CompositeFastFieldSerializer {
         self.composite_write.for_field_with_idx(field, 1)
     }
 
+    /// Copies, as is, all of the fast fields stored in an existing composite file.
+    pub(crate) fn copy_fast_fields(&mut self, composite_file: &CompositeFile) -> io::Result<()> {
+        for (field, idx, file_slice) in composite_file.file_slices() {
+            let field_write = self.composite_write.for_field_with_idx(field, idx);
+            field_write.write_all(file_slice.read_bytes()?.as_slice())?;
+        }
+        Ok(())
+    }
+
     /// Closes the serializer
     ///
     /// After this call the data must be persistently saved on disk.
@@ -12,9 +12,12 @@ use super::FastFieldType;
 use crate::fastfield::{BytesFastFieldWriter, CompositeFastFieldSerializer};
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::postings::UnorderedTermId;
-use crate::schema::{Cardinality, Document, Field, FieldEntry, FieldType, Schema, Value};
+use crate::schema::{
+    Cardinality, Document, Field, FieldEntry, FieldType, FloatPrecision, MultiValueReduction,
+    Schema, Value,
+};
 use crate::termdict::TermOrdinal;
-use crate::DatePrecision;
+use crate::{DatePrecision, DocId};
 
 /// The `FastFieldsWriter` groups all of the fast field writers.
 pub struct FastFieldsWriter {
@@ -22,9 +25,12 @@ pub struct FastFieldsWriter {
     single_value_writers: Vec<IntFastFieldWriter>,
     multi_values_writers: Vec<MultiValuedFastFieldWriter>,
     bytes_value_writers: Vec<BytesFastFieldWriter>,
+    // Indexed by field id, `true` for the numeric fast fields that are also indexed, and whose
+    // values can be recorded from their term by the `SegmentWriter`.
+    indexed_numeric_fields: Vec<bool>,
 }
 
-fn fast_field_default_value(field_entry: &FieldEntry) -> u64 {
+pub(crate) fn fast_field_default_value(field_entry: &FieldEntry) -> u64 {
     match *field_entry.field_type() {
         FieldType::I64(_) | FieldType::Date(_) => common::i64_to_u64(0i64),
         FieldType::F64(_) => common::f64_to_u64(0.0f64),
@@ -35,30 +41,60 @@ fn fast_field_default_value(field_entry: &FieldEntry) -> u64 {
 impl FastFieldsWriter {
     /// Create all `FastFieldWriter` required by the schema.
     pub fn from_schema(schema: &Schema) -> FastFieldsWriter {
+        FastFieldsWriter::for_fields(schema, schema.fields().map(|(field, _)| field))
+    }
+
+    /// Create the `FastFieldWriter`s required by the given fields of the schema.
+    pub(crate) fn for_fields(
+        schema: &Schema,
+        fields: impl IntoIterator<Item = Field>,
+    ) -> FastFieldsWriter {
         let mut single_value_writers = Vec::new();
         let mut term_id_writers = Vec::new();
         let mut multi_values_writers = Vec::new();
         let mut bytes_value_writers = Vec::new();
+        let mut indexed_numeric_fields = vec![false; schema.fields().count()];
 
-        for (field, field_entry) in schema.fields() {
+        for field in fields {
+            let field_entry = schema.get_field_entry(field);
             match field_entry.field_type() {
                 FieldType::I64(ref int_options)
                 | FieldType::U64(ref int_options)
                 | FieldType::F64(ref int_options)
                 | FieldType::Bool(ref int_options) => {
+                    // Dates are not part of them, as their terms are truncated to the second, not
+                    // to the precision of the fast field.
+                    // The values of the fields summing the values of a document are not
+                    // recorded from their terms either, as their type is needed to sum them,
+                    // nor are the values of the f64 fields stored with a lower precision.
+                    let reduction = match field_entry.field_type() {
+                        FieldType::Bool(_) => MultiValueReduction::First,
+                        _ => int_options.get_multi_value_reduction(),
+                    };
+                    let float_precision = match field_entry.field_type() {
+                        FieldType::F64(_) => int_options.get_float_precision(),
+                        _ => FloatPrecision::F64,
+                    };
+                    indexed_numeric_fields[field.field_id() as usize] = int_options.is_indexed()
+                        && int_options.is_fast()
+                        && reduction == MultiValueReduction::First
+                        && float_precision == FloatPrecision::F64;
                     match int_options.get_fastfield_cardinality() {
                         Some(Cardinality::SingleValue) => {
                             let mut fast_field_writer = IntFastFieldWriter::new(field, None);
                             let default_value = fast_field_default_value(field_entry);
                             fast_field_writer.set_val_if_missing(default_value);
+                            fast_field_writer.set_reduction(reduction);
+                            fast_field_writer.set_float_precision(float_precision);
                             single_value_writers.push(fast_field_writer);
                         }
                         Some(Cardinality::MultiValues) => {
-                            let fast_field_writer = MultiValuedFastFieldWriter::new(
+                            let mut fast_field_writer = MultiValuedFastFieldWriter::new(
                                 field,
                                 FastFieldType::Numeric,
                                 None,
                             );
+                            fast_field_writer.set_float_precision(float_precision);
                             multi_values_writers.push(fast_field_writer);
                         }
                         None => {}
@@ -98,7 +134,7 @@ impl FastFieldsWriter {
                         bytes_value_writers.push(fast_field_writer);
                     }
                 }
-                FieldType::Str(_) | FieldType::JsonObject(_) => {}
+                FieldType::Str(_) | FieldType::JsonObject(_) | FieldType::DenseVector(_) => {}
             }
         }
         FastFieldsWriter {
@@ -106,6 +142,7 @@ impl FastFieldsWriter {
             single_value_writers,
             multi_values_writers,
             bytes_value_writers,
+            indexed_numeric_fields,
         }
     }
 
@@ -208,6 +245,58 @@ impl FastFieldsWriter {
         }
     }
 
+    /// Indexes the fastfields of a new document, except for the numeric fields that are also
+    /// indexed.
+    ///
+    /// The `SegmentWriter` computes the `u64` representation of the values of these fields to
+    /// build their terms anyway, and records them with
+    /// [`FastFieldsWriter::add_indexed_numeric_vals`] once the document is indexed.
+    pub(crate) fn add_document_except_indexed_numeric(&mut self, doc: &Document) {
+        for field_writer in &mut self.term_id_writers {
+            field_writer.add_document(doc);
+        }
+        for field_writer in &mut self.single_value_writers {
+            if !self.indexed_numeric_fields[field_writer.field().field_id() as usize] {
+                field_writer.add_document(doc);
+            }
+        }
+        for field_writer in &mut self.multi_values_writers {
+            if self.indexed_numeric_fields[field_writer.field().field_id() as usize] {
+                field_writer.next_doc();
+            } else {
+                field_writer.add_document(doc);
+            }
+        }
+        for field_writer in &mut self.bytes_value_writers {
+            field_writer.add_document(doc);
+        }
+    }
+
+    /// Records the values of the numeric fields that are also indexed, for the document
+    /// started by [`FastFieldsWriter::add_document_except_indexed_numeric`].
+    ///
+    /// `vals` contains the `u64` representation of the values of the indexed numeric fields of
+    /// the document, in the order of the document.
+    pub(crate) fn add_indexed_numeric_vals(&mut self, vals: &[(Field, u64)]) {
+        for field_writer in &mut self.single_value_writers {
+            let field = field_writer.field();
+            if self.indexed_numeric_fields[field.field_id() as usize] {
+                match vals.iter().find(|(val_field, _)| *val_field == field) {
+                    Some((_, val)) => field_writer.add_val(*val),
+                    None => field_writer.add_val(field_writer.val_if_missing),
+                }
+            }
+        }
+        for field_writer in &mut self.multi_values_writers {
+            let field = field_writer.field();
+            if self.indexed_numeric_fields[field.field_id() as usize] {
+                for (_, val) in vals.iter().filter(|(val_field, _)| *val_field == field) {
+                    field_writer.add_val(*val);
+                }
+            }
+        }
+    }
+
     /// Serializes all of the `FastFieldWriter`s by pushing them in
     /// order to the fast field serializer.
     pub fn serialize(
@@ -235,6 +324,29 @@ impl FastFieldsWriter {
     }
 }
 
+/// Returns the `u64` representation of the sum of the numeric `values`, or `None` if there are
+/// no values.
+///
+/// The sum of `u64` and `i64` values saturates at the bounds of their type.
+fn sum_values<'a>(mut values: impl Iterator<Item = &'a Value>) -> Option<u64> {
+    let sum = match values.next()? {
+        Value::U64(first) => values
+            .filter_map(Value::as_u64)
+            .fold(*first, u64::saturating_add)
+            .to_u64(),
+        Value::I64(first) => values
+            .filter_map(Value::as_i64)
+            .fold(*first, i64::saturating_add)
+            .to_u64(),
+        Value::F64(first) => values
+            .filter_map(Value::as_f64)
+            .fold(*first, |sum, val| sum + val)
+            .to_u64(),
+        first => super::value_to_u64(first),
+    };
+    Some(sum)
+}
+
 /// Fast field writer for ints.
 /// The fast field writer just keeps the values in memory.
 ///
@@ -258,6 +370,8 @@ pub struct IntFastFieldWriter {
     val_if_missing: u64,
     val_min: u64,
     val_max: u64,
+    reduction: MultiValueReduction,
+    float_precision: FloatPrecision,
 }
 
 impl IntFastFieldWriter {
@@ -271,6 +385,8 @@ impl IntFastFieldWriter {
             val_if_missing: 0u64,
             val_min: u64::MAX,
             val_max: 0,
+            reduction: MultiValueReduction::First,
+            float_precision: FloatPrecision::F64,
         }
     }
 
@@ -292,6 +408,16 @@ impl IntFastFieldWriter {
         self.val_if_missing = val_if_missing;
     }
 
+    /// Sets how the values of a document having more than one value are reduced.
+    fn set_reduction(&mut self, reduction: MultiValueReduction) {
+        self.reduction = reduction;
+    }
+
+    /// Sets the precision with which the f64 values are stored.
+    fn set_float_precision(&mut self, float_precision: FloatPrecision) {
+        self.float_precision = float_precision;
+    }
+
     /// Records a new value.
     ///
     /// The n-th value being recorded is implicitly
@@ -323,28 +449,54 @@ impl IntFastFieldWriter {
     /// If the value is missing, then the default value is used
     /// instead.
     /// If the document has more than one value for the given field,
-    /// only the first one is taken in account.
+    /// they are reduced as defined by the `MultiValueReduction` of the field:
+    /// by default, only the first one is taken in account.
+    /// The f64 values are then truncated to the precision of the field.
     ///
     /// Values on text fast fields are skipped.
     pub fn add_document(&mut self, doc: &Document) {
-        match doc.get_first(self.field) {
-            Some(v) => {
-                let value = match (self.precision_opt, v) {
+        let value = if self.reduction == MultiValueReduction::Sum {
+            sum_values(doc.get_all(self.field)).unwrap_or(self.val_if_missing)
+        } else {
+            match doc.get_first(self.field) {
+                Some(v) => match (self.precision_opt, v) {
                     (Some(precision), Value::Date(date_val)) => {
                         date_val.truncate(precision).to_u64()
                     }
                     _ => super::value_to_u64(v),
-                };
-                self.add_val(value);
-            }
-            None => {
-                self.add_val(self.val_if_missing);
+                },
+                None => self.val_if_missing,
             }
         };
+        let value = match self.float_precision {
+            FloatPrecision::F64 => value,
+            FloatPrecision::F32 => super::truncate_to_f32_precision(value),
+        };
+        self.add_val(value);
+    }
+
+    /// Returns the number of values recorded so far, i.e. the number of documents.
+    pub fn num_vals(&self) -> usize {
+        self.val_count
+    }
+
+    /// Returns the value recorded for the document `doc`, or `None` if the document has not
+    /// been added yet.
+    ///
+    /// The value is the `u64` representation of the fast field value, as returned by
+    /// `MonotonicallyMappableToU64::to_u64`. It can be read while the segment is being built,
+    /// before it is serialized.
+    pub fn value_at(&self, doc: DocId) -> Option<u64> {
+        if doc as usize >= self.val_count {
+            return None;
+        }
+        Some(self.vals.get(doc as usize))
     }
 
-    /// get iterator over the data
-    pub(crate) fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+    /// Returns an iterator over the values recorded so far, in doc id order.
+    ///
+    /// See [`IntFastFieldWriter::value_at`].
+    pub fn iter_values(&self) -> impl Iterator<Item = u64> + '_ {
         self.vals.iter()
     }
 
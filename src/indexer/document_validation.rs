@@ -0,0 +1,223 @@
+use crate::schema::{Document, FieldType, Schema, Value};
+use crate::tokenizer::{TokenizerManager, MAX_TOKEN_LEN};
+use crate::vector::validate_dense_vectors;
+use crate::{IndexWriter, Opstamp, TantivyError, UserOperation};
+
+/// A document rejected by [`IndexWriter::add_documents`](crate::IndexWriter::add_documents),
+/// along with the reason why it was rejected.
+#[derive(Debug)]
+pub struct RejectedDocument {
+    document: Document,
+    error: TantivyError,
+}
+
+impl RejectedDocument {
+    /// Returns the document that was rejected.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Returns the reason why the document was rejected.
+    pub fn error(&self) -> &TantivyError {
+        &self.error
+    }
+
+    /// Returns the document that was rejected, e.g. to quarantine it.
+    pub fn into_document(self) -> Document {
+        self.document
+    }
+}
+
+fn has_expected_type(field_type: &FieldType, value: &Value) -> bool {
+    match field_type {
+        FieldType::Str(_) => matches!(value, Value::Str(_) | Value::PreTokStr(_)),
+        FieldType::U64(_) => matches!(value, Value::U64(_)),
+        FieldType::I64(_) => matches!(value, Value::I64(_)),
+        FieldType::F64(_) => matches!(value, Value::F64(_)),
+        FieldType::Bool(_) => matches!(value, Value::Bool(_)),
+        FieldType::Date(_) => matches!(value, Value::Date(_)),
+        FieldType::Facet(_) => matches!(value, Value::Facet(_)),
+        FieldType::Bytes(_) => matches!(value, Value::Bytes(_)),
+        FieldType::JsonObject(_) => matches!(value, Value::JsonObject(_)),
+        FieldType::DenseVector(_) => matches!(value, Value::DenseVector(_)),
+    }
+}
+
+/// Checks that a document can be indexed without failing the indexing thread.
+///
+/// The values of the fields that are indexed or fast must be of the type of their field, and
+/// the dense vectors must be valid. Values of fields that are only stored are not checked.
+pub(crate) fn validate_document(schema: &Schema, document: &Document) -> crate::Result<()> {
+    for field_value in document.field_values() {
+        let field_entry = schema.get_field_entry(field_value.field());
+        if !field_entry.is_indexed() && !field_entry.is_fast() {
+            continue;
+        }
+        if !has_expected_type(field_entry.field_type(), field_value.value()) {
+            return Err(TantivyError::SchemaError(format!(
+                "Expected a {:?} for field {:?}",
+                field_entry.field_type().value_type(),
+                field_entry.name()
+            )));
+        }
+    }
+    validate_dense_vectors(schema, document)
+}
+
+/// Checks that none of the tokens of the indexed text fields of a document exceed
+/// `MAX_TOKEN_LEN`.
+///
+/// Such tokens are otherwise dropped when the document is indexed. The text is tokenized
+/// with the tokenizer of its field.
+pub(crate) fn validate_token_lengths(
+    schema: &Schema,
+    tokenizers: &TokenizerManager,
+    document: &Document,
+) -> crate::Result<()> {
+    for field_value in document.field_values() {
+        let field_entry = schema.get_field_entry(field_value.field());
+        let indexing_options = match field_entry.field_type() {
+            FieldType::Str(text_options) => {
+                if let Some(indexing_options) = text_options.get_indexing_options() {
+                    indexing_options
+                } else {
+                    continue;
+                }
+            }
+            _ => continue,
+        };
+        let mut has_long_token = false;
+        match field_value.value() {
+            Value::PreTokStr(pre_tokenized_text) => {
+                has_long_token = pre_tokenized_text
+                    .tokens
+                    .iter()
+                    .any(|token| token.text.len() > MAX_TOKEN_LEN);
+            }
+            Value::Str(text) => {
+                let text_analyzer =
+                    tokenizers
+                        .get(indexing_options.tokenizer())
+                        .ok_or_else(|| {
+                            TantivyError::SchemaError(format!(
+                                "Tokenizer {:?} used by field {:?} is not registered.",
+                                indexing_options.tokenizer(),
+                                field_entry.name()
+                            ))
+                        })?;
+                text_analyzer.token_stream(text).process(&mut |token| {
+                    has_long_token |= token.text.len() > MAX_TOKEN_LEN;
+                });
+            }
+            _ => {}
+        }
+        if has_long_token {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field {:?} has a token exceeding MAX_TOKEN_LEN ({})",
+                field_entry.name(),
+                MAX_TOKEN_LEN
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn add_documents(
+    index_writer: &IndexWriter,
+    documents: Vec<Document>,
+) -> crate::Result<Vec<Result<Opstamp, RejectedDocument>>> {
+    let index = index_writer.index();
+    let schema = index.schema();
+    let mut valid_documents = Vec::with_capacity(documents.len());
+    let mut results = Vec::with_capacity(documents.len());
+    for document in documents {
+        let validation_result = validate_document(&schema, &document)
+            .and_then(|()| validate_token_lengths(&schema, index.tokenizers(), &document));
+        match validation_result {
+            Ok(()) => {
+                valid_documents.push(document);
+                // The opstamp is only known once the batch is sent.
+                results.push(Ok(0));
+            }
+            Err(error) => results.push(Err(RejectedDocument { document, error })),
+        }
+    }
+    let num_valid_documents = valid_documents.len() as Opstamp;
+    let batch_opstamp = index_writer.run(valid_documents.into_iter().map(UserOperation::Add))?;
+    // The operations of the batch are given the opstamps preceding the opstamp of the batch.
+    let mut opstamps = (batch_opstamp - num_valid_documents)..batch_opstamp;
+    for opstamp in results.iter_mut().flatten() {
+        *opstamp = opstamps
+            .next()
+            .expect("An opstamp is given to each valid document");
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::tokenizer::MAX_TOKEN_LEN;
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_add_documents_with_invalid_documents() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let results = index_writer.add_documents(vec![
+            doc!(id => "a", title => "first", count => 1u64),
+            // Wrong type.
+            doc!(id => "b", count => "two"),
+            // Token exceeding `MAX_TOKEN_LEN`.
+            doc!(id => "c".repeat(MAX_TOKEN_LEN + 1)),
+            doc!(id => "d", title => "fourth"),
+        ])?;
+        assert_eq!(results.len(), 4);
+        let first_opstamp = *results[0].as_ref().unwrap();
+        assert_eq!(results[3].as_ref().ok(), Some(&(first_opstamp + 1)));
+        let rejected_document = results[1].as_ref().unwrap_err();
+        assert!(matches!(
+            rejected_document.error(),
+            TantivyError::SchemaError(_)
+        ));
+        assert_eq!(
+            rejected_document
+                .document()
+                .get_first(id)
+                .unwrap()
+                .as_text(),
+            Some("b")
+        );
+        assert!(matches!(
+            results[2].as_ref().unwrap_err().error(),
+            TantivyError::InvalidArgument(_)
+        ));
+
+        // A rejected document is handed back as is, and the writer is still alive.
+        let mut rejected_documents = results.into_iter().filter_map(Result::err);
+        let mut fixed_document = rejected_documents.next().unwrap().into_document();
+        fixed_document.add_u64(count, 2);
+        assert!(matches!(
+            index_writer.add_document(fixed_document),
+            Err(TantivyError::SchemaError(_))
+        ));
+        index_writer.add_document(doc!(id => "b", count => 2u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        for (id_value, expected_count) in [("a", 1), ("b", 1), ("d", 1), ("e", 0)] {
+            let query = TermQuery::new(
+                Term::from_field_text(id, id_value),
+                IndexRecordOption::Basic,
+            );
+            assert_eq!(searcher.search(&query, &Count)?, expected_count);
+        }
+        Ok(())
+    }
+}
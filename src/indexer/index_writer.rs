@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
 use std::ops::Range;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
 
 use common::BitSet;
+use crossbeam_channel::TrySendError;
 use smallvec::smallvec;
 
 use super::operation::{AddOperation, UserOperation};
@@ -16,10 +19,15 @@ use crate::error::TantivyError;
 use crate::fastfield::write_alive_bitset;
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::document_validation::{add_documents, RejectedDocument};
+use crate::indexer::index_writer_events::IndexWriterEventListener;
 use crate::indexer::index_writer_status::IndexWriterStatus;
+use crate::indexer::json_lines::{add_json_lines, JsonLinesReport};
 use crate::indexer::operation::DeleteOperation;
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::{
+    validate_document, MergeCancellationToken, MergePolicy, SegmentEntry, SegmentWriter,
+};
 use crate::schema::{Document, IndexRecordOption, Term};
 use crate::{FutureResult, Opstamp};
 
@@ -175,6 +183,7 @@ fn index_documents(
     mut delete_cursor: DeleteCursor,
 ) -> crate::Result<()> {
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    segment_writer.set_term_block_memory_budget(segment_updater.get_term_block_memory_budget());
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
@@ -450,10 +459,93 @@ impl IndexWriter {
     }
 
     /// Setter for the merge policy.
+    ///
+    /// The merges started by the merge policy are cancelled when the `IndexWriter` is rolled
+    /// back or dropped.
     pub fn set_merge_policy(&self, merge_policy: Box<dyn MergePolicy>) {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Accessor to the memory budget of the merges, in bytes.
+    pub fn get_merge_memory_budget(&self) -> Option<usize> {
+        self.segment_updater.get_merge_memory_budget()
+    }
+
+    /// Sets a memory budget, in bytes, for the buffers of the merges started after this call.
+    ///
+    /// When a rebuilt fast field does not fit in the budget, its values are spilled to a
+    /// temporary file of the merged segment instead of being buffered in memory. The postings
+    /// of a sorted index are also merged without being buffered.
+    ///
+    /// `None`, the default, means that the merges buffer these in memory.
+    pub fn set_merge_memory_budget(&self, merge_memory_budget_in_bytes: Option<usize>) {
+        self.segment_updater
+            .set_merge_memory_budget(merge_memory_budget_in_bytes);
+    }
+
+    /// Accessor to the memory budget of the buffered terms of the indexing threads, in bytes.
+    pub fn get_term_block_memory_budget(&self) -> Option<usize> {
+        self.segment_updater.get_term_block_memory_budget()
+    }
+
+    /// Sets a memory budget, in bytes, for the terms and postings buffered by each indexing
+    /// thread, applying to the segments started after this call.
+    ///
+    /// When the budget is reached, the buffered terms are flushed as a sorted block to a
+    /// temporary file of the segment, and the blocks are stitched back into a single term
+    /// dictionary when the segment is finalized. The resulting segment is identical to the one
+    /// built without flushing.
+    ///
+    /// `None`, the default, means that the terms are buffered in memory until the segment is
+    /// finalized.
+    pub fn set_term_block_memory_budget(&self, term_block_memory_budget_in_bytes: Option<usize>) {
+        self.segment_updater
+            .set_term_block_memory_budget(term_block_memory_budget_in_bytes);
+    }
+
+    /// Accessor to the number of threads running the merges.
+    pub fn num_merge_threads(&self) -> usize {
+        self.segment_updater.num_merge_threads()
+    }
+
+    /// Sets the number of threads running the merges, independently of the number of
+    /// indexing threads. It defaults to 4.
+    ///
+    /// The merges started after this call run on a new pool of `num_merge_threads` threads.
+    /// Running merges are not interrupted.
+    ///
+    /// Returns an error if `num_merge_threads` is 0.
+    pub fn set_num_merge_threads(&self, num_merge_threads: usize) -> crate::Result<()> {
+        self.segment_updater
+            .set_num_merge_threads(num_merge_threads)
+    }
+
+    /// Sets a callback that each merge thread runs when it starts, with the index of the
+    /// thread in the merge thread pool.
+    ///
+    /// It can be used to lower the priority of the merge threads, e.g. by setting their nice
+    /// level, so that merges do not compete with indexing and search.
+    /// As for [`IndexWriter::set_num_merge_threads`], the merges started after this call run on
+    /// a new pool of threads.
+    pub fn set_merge_thread_start_handler<F>(&self, start_handler: F) -> crate::Result<()>
+    where F: Fn(usize) + Send + Sync + 'static {
+        self.segment_updater
+            .set_merge_thread_start_handler(Arc::new(start_handler))
+    }
+
+    /// Sets a listener notified of the creation of segments, and of the start and the end of
+    /// commits and merges, e.g. for observability. It replaces the previous listener, if any.
+    ///
+    /// The events are buffered and dispatched to the listener by a dedicated thread, so that
+    /// they never block indexing. They may therefore be received after the operation that
+    /// triggered them returned.
+    pub fn set_event_listener<L: IndexWriterEventListener>(
+        &self,
+        listener: L,
+    ) -> crate::Result<()> {
+        self.segment_updater.set_event_listener(Arc::new(listener))
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -516,11 +608,62 @@ impl IndexWriter {
     ///
     /// `segment_ids` is required to be non-empty.
     pub fn merge(&mut self, segment_ids: &[SegmentId]) -> FutureResult<Option<SegmentMeta>> {
-        let merge_operation = self.segment_updater.make_merge_operation(segment_ids);
+        self.merge_with_cancellation_token(segment_ids, MergeCancellationToken::default())
+    }
+
+    /// Merges a given list of segments, like [`IndexWriter::merge`], and makes it possible to
+    /// cancel the merge while it is in progress by calling
+    /// [`MergeCancellationToken::cancel`].
+    ///
+    /// A cancelled merge resolves to `TantivyError::MergeCancelled`, and the segments that
+    /// were to be merged are left as is.
+    ///
+    /// Unlike the merges started by the merge policy, the merge is not cancelled when the
+    /// `IndexWriter` is rolled back or dropped.
+    pub fn merge_with_cancellation_token(
+        &mut self,
+        segment_ids: &[SegmentId],
+        cancellation_token: MergeCancellationToken,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        let merge_operation = self
+            .segment_updater
+            .make_merge_operation(segment_ids, cancellation_token);
         let segment_updater = self.segment_updater.clone();
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Rewrites a single segment, without merging it with other segments.
+    ///
+    /// This is the degenerate case of [`IndexWriter::merge`] with one segment: the deleted
+    /// documents are removed, and the remaining documents get new, contiguous doc ids. This
+    /// makes it possible to reclaim the space used by the deletes of a large segment.
+    ///
+    /// If all of the documents of the segment are deleted, no new segment is created.
+    pub fn compact_segment(&mut self, segment_id: SegmentId) -> FutureResult<Option<SegmentMeta>> {
+        self.merge(&[segment_id])
+    }
+
+    /// Attaches metadata to a segment, e.g. the source file or the ingestion batch of its
+    /// documents, replacing the metadata previously attached to it.
+    ///
+    /// The segment can be committed or not. Like other changes, the metadata is only persisted
+    /// upon the next `commit`. It can then be read with [`SegmentMeta::user_metadata`].
+    ///
+    /// When segments are merged, the metadata of the merged segment is given by
+    /// [`MergePolicy::merge_user_metadata`]. Setting the metadata of a segment that is being
+    /// merged does not affect the merged segment.
+    ///
+    /// Returns an error if the segment is not a segment of the index.
+    pub fn set_segment_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: BTreeMap<String, String>,
+    ) -> crate::Result<()> {
+        self.segment_updater
+            .schedule_set_user_metadata(segment_id, user_metadata)
+            .wait()
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -547,7 +690,8 @@ impl IndexWriter {
     pub fn rollback(&mut self) -> crate::Result<Opstamp> {
         info!("Rolling back to opstamp {}", self.committed_opstamp);
         // marks the segment updater as killed. From now on, all
-        // segment updates will be ignored.
+        // segment updates will be ignored, and the merges started by the
+        // merge policy are cancelled.
         self.segment_updater.kill();
         let document_receiver_res = self.operation_receiver();
 
@@ -687,15 +831,135 @@ impl IndexWriter {
     ///
     /// If the indexing pipeline is full, this call may block.
     ///
+    /// The memory used by the `IndexWriter` is bounded: each indexing thread flushes its
+    /// segment as soon as its memory budget is reached, and at most `10_000` operations can
+    /// wait in the pipeline. Once the pipeline is full, this call blocks until an indexing
+    /// thread catches up, which provides backpressure to the clients adding documents.
+    /// See [`IndexWriter::try_add_document`] for a non-blocking alternative.
+    ///
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// An error is returned if a value of an indexed or fast field does not have the type of
+    /// its field, or if the document has an invalid vector in a dense vector field.
     pub fn add_document(&self, document: Document) -> crate::Result<Opstamp> {
+        validate_document(&self.index.schema(), &document)?;
         let opstamp = self.stamper.stamp();
         self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
         Ok(opstamp)
     }
 
+    /// Adds a document, unless the indexing pipeline is full.
+    ///
+    /// Unlike [`IndexWriter::add_document`], this call never blocks. If the pipeline is full,
+    /// the document is handed back as `Ok(Err(document))`, so that the client can slow down and
+    /// retry later.
+    ///
+    /// A rejected document gives its opstamp back, so that it is supplied to the next
+    /// operation. If another operation was stamped concurrently, the opstamp cannot be given
+    /// back, and the opstamps of the operations have a gap.
+    ///
+    /// An error is returned if the document is invalid, as in [`IndexWriter::add_document`],
+    /// or if the index writer was killed.
+    pub fn try_add_document(&self, document: Document) -> crate::Result<Result<Opstamp, Document>> {
+        validate_document(&self.index.schema(), &document)?;
+        if !self.index_writer_status.is_alive() {
+            return Err(error_in_index_worker_thread("An index writer was killed."));
+        }
+        let opstamp = self.stamper.stamp();
+        match self
+            .operation_sender
+            .try_send(smallvec![AddOperation { opstamp, document }])
+        {
+            Ok(()) => Ok(Ok(opstamp)),
+            Err(TrySendError::Full(mut add_ops)) => {
+                self.stamper.unstamp(opstamp);
+                let add_op = add_ops
+                    .pop()
+                    .expect("The batch contains exactly one operation");
+                Ok(Err(add_op.document))
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                Err(error_in_index_worker_thread("An index writer was killed."))
+            }
+        }
+    }
+
+    /// Adds a block of documents made of a parent document and its children, to be joined
+    /// with a [`BlockJoinQuery`](crate::query::BlockJoinQuery).
+    ///
+    /// The children are added in order, followed by the parent. The documents of a block are
+    /// guaranteed to be flushed contiguously into the same segment, and they stay contiguous
+    /// when segments are merged.
+    ///
+    /// Returns the opstamp of the parent document.
+    ///
+    /// An error is returned if the index is sorted by a field, as sorting would break the
+    /// blocks apart.
+    pub fn add_document_block(
+        &self,
+        children: Vec<Document>,
+        parent: Document,
+    ) -> crate::Result<Opstamp> {
+        if self.index.settings().sort_by_field.is_some() {
+            return Err(TantivyError::InvalidArgument(
+                "Document blocks are not supported for indices sorted by a field".to_string(),
+            ));
+        }
+        let schema = self.index.schema();
+        for document in children.iter().chain(std::iter::once(&parent)) {
+            validate_document(&schema, document)?;
+        }
+        let stamps = self.stamper.stamps(children.len() as u64 + 1);
+        let parent_opstamp = stamps.end - 1;
+        let add_ops: AddBatch = children
+            .into_iter()
+            .chain(std::iter::once(parent))
+            .zip(stamps)
+            .map(|(document, opstamp)| AddOperation { opstamp, document })
+            .collect();
+        self.send_add_documents_batch(add_ops)?;
+        Ok(parent_opstamp)
+    }
+
+    /// Adds a batch of documents, reporting the documents that cannot be indexed instead of
+    /// failing the whole batch.
+    ///
+    /// Each document is validated before the batch is sent: besides the checks of
+    /// [`IndexWriter::add_document`], documents having a token longer than
+    /// [`MAX_TOKEN_LEN`](crate::tokenizer::MAX_TOKEN_LEN) in a text field are rejected,
+    /// rather than having the token silently dropped. This requires tokenizing the text of
+    /// the documents twice.
+    ///
+    /// The valid documents are added as with [`IndexWriter::run`]: they receive contiguous
+    /// opstamps and are flushed into the same segment. The result has an entry per document,
+    /// in the order of the batch: either the opstamp of the document, or the rejected document
+    /// along with its error, so that it can be quarantined.
+    ///
+    /// An error is returned if the index writer was killed.
+    pub fn add_documents(
+        &self,
+        documents: Vec<Document>,
+    ) -> crate::Result<Vec<Result<Opstamp, RejectedDocument>>> {
+        add_documents(self, documents)
+    }
+
+    /// Parses a stream of newline-delimited JSON documents, and adds them to the index.
+    ///
+    /// Each line is parsed with [`Schema::parse_document`](crate::schema::Schema::parse_document).
+    /// Lines that cannot be parsed, or whose document is rejected by the validation of
+    /// [`IndexWriter::add_document`], are skipped and reported in the returned
+    /// [`JsonLinesReport`], without aborting the ingestion of the rest of the stream.
+    /// Empty lines are ignored.
+    ///
+    /// As with `add_document`, the documents are only searchable after a commit.
+    ///
+    /// An error is returned if reading the stream fails, or if the index writer fails.
+    pub fn add_json_lines<R: BufRead>(&self, json_lines: R) -> crate::Result<JsonLinesReport> {
+        add_json_lines(self, json_lines)
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -730,6 +994,13 @@ impl IndexWriter {
         I: IntoIterator<Item = UserOperation>,
         I::IntoIter: ExactSizeIterator,
     {
+        let user_operations: Vec<UserOperation> = user_operations.into_iter().collect();
+        let schema = self.index.schema();
+        for user_operation in &user_operations {
+            if let UserOperation::Add(document) = user_operation {
+                validate_document(&schema, document)?;
+            }
+        }
         let user_operations_it = user_operations.into_iter();
         let count = user_operations_it.len() as u64;
         if count == 0 {
@@ -776,23 +1047,34 @@ impl Drop for IndexWriter {
 #[cfg(test)]
 mod tests {
     use std::collections::{HashMap, HashSet};
+    use std::fmt;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     use proptest::prelude::*;
     use proptest::prop_oneof;
     use proptest::strategy::Strategy;
 
     use super::super::operation::UserOperation;
-    use crate::collector::TopDocs;
-    use crate::directory::error::LockError;
+    use crate::collector::{Count, TopDocs};
+    use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+    use crate::directory::{
+        Directory, FileHandle, FileSlice, RamDirectory, WatchCallback, WatchHandle, WritePtr,
+    };
     use crate::error::*;
-    use crate::indexer::NoMergePolicy;
+    use crate::indexer::merge_policy::tests::MergeWheneverPossible;
+    use crate::indexer::{IndexWriterEvent, MergeCancellationToken, NoMergePolicy};
     use crate::query::{QueryParser, TermQuery};
     use crate::schema::{
         self, Cardinality, Facet, FacetOptions, IndexRecordOption, NumericOptions,
         TextFieldIndexing, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
-    use crate::{DocAddress, Index, IndexSettings, IndexSortByField, Order, ReloadPolicy, Term};
+    use crate::tokenizer::{BoxTokenStream, SimpleTokenizer, Tokenizer};
+    use crate::{
+        DocAddress, Index, IndexSettings, IndexSortByField, Order, ReloadPolicy, SegmentId, Term,
+    };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
                          eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad \
@@ -1131,6 +1413,295 @@ mod tests {
         Ok(())
     }
 
+    /// Tokenizer blocking the indexing thread on the first document, until released.
+    #[derive(Clone)]
+    struct BlockingTokenizer {
+        started_sender: crossbeam_channel::Sender<()>,
+        release_receiver: crossbeam_channel::Receiver<()>,
+    }
+
+    impl Tokenizer for BlockingTokenizer {
+        fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+            let _ = self.started_sender.try_send(());
+            // Returns once the release sender is dropped.
+            let _ = self.release_receiver.recv();
+            SimpleTokenizer.token_stream(text)
+        }
+    }
+
+    #[test]
+    fn test_try_add_document_flood_stays_bounded() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field(
+            "text",
+            TextOptions::default()
+                .set_indexing_options(TextFieldIndexing::default().set_tokenizer("blocking")),
+        );
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let (started_sender, started_receiver) = crossbeam_channel::bounded(1);
+        let (release_sender, release_receiver) = crossbeam_channel::bounded(0);
+        index.tokenizers().register(
+            "blocking",
+            BlockingTokenizer {
+                started_sender,
+                release_receiver,
+            },
+        );
+        let mut index_writer =
+            index.writer_with_num_threads(1, super::MEMORY_ARENA_NUM_BYTES_MIN)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        const NUM_DOCS: u64 = 100_000;
+        let make_doc = |id: u64| doc!(text_field => format!("token{}", id), id_field => id);
+
+        // The indexing thread is blocked on the first document, so that the pipeline fills up.
+        let mut last_opstamp = index_writer.try_add_document(make_doc(0))?.unwrap();
+        started_receiver.recv().unwrap();
+        let mut id = 1;
+        let mut rejected_doc = loop {
+            match index_writer.try_add_document(make_doc(id))? {
+                Ok(opstamp) => {
+                    assert_eq!(opstamp, last_opstamp + 1);
+                    last_opstamp = opstamp;
+                    id += 1;
+                    assert!(id as usize <= super::PIPELINE_MAX_SIZE_IN_DOCS + 1);
+                }
+                Err(rejected_doc) => break rejected_doc,
+            }
+        };
+        assert_eq!(id as usize, super::PIPELINE_MAX_SIZE_IN_DOCS + 1);
+        assert!(index_writer.try_add_document(make_doc(id))?.is_err());
+        // The rejected documents did not consume any opstamp.
+        let delete_opstamp = index_writer.delete_term(Term::from_field_u64(id_field, NUM_DOCS));
+        assert_eq!(delete_opstamp, last_opstamp + 1);
+
+        drop(release_sender);
+        while id < NUM_DOCS {
+            match index_writer.try_add_document(rejected_doc)? {
+                Ok(_opstamp) => {
+                    id += 1;
+                    rejected_doc = make_doc(id);
+                }
+                Err(doc) => {
+                    // Wait for the indexing thread to catch up.
+                    rejected_doc = doc;
+                    std::thread::yield_now();
+                }
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), NUM_DOCS);
+        // The segment writer was flushed every time it reached its memory budget, rather than
+        // growing until the commit.
+        assert!(searcher.segment_readers().len() > 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_num_merge_threads() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert_eq!(
+            index_writer.num_merge_threads(),
+            super::super::segment_updater::DEFAULT_NUM_MERGE_THREADS
+        );
+        assert!(matches!(
+            index_writer.set_num_merge_threads(0),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        let started_threads = Arc::new(Mutex::new(Vec::new()));
+        let started_threads_clone = started_threads.clone();
+        index_writer.set_merge_thread_start_handler(move |thread_index| {
+            let thread_name = std::thread::current().name().map(str::to_string);
+            started_threads_clone
+                .lock()
+                .unwrap()
+                .push((thread_index, thread_name));
+        })?;
+        index_writer.set_num_merge_threads(2)?;
+        assert_eq!(index_writer.num_merge_threads(), 2);
+
+        for _ in 0..3 {
+            index_writer.add_document(doc!(text_field => "a"))?;
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+
+        // The threads of the pool start asynchronously.
+        for _ in 0..1_000 {
+            if started_threads.lock().unwrap().len() >= 6 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let mut started_threads = started_threads.lock().unwrap().clone();
+        started_threads.sort();
+        // The handler was set on the default pool of 4 threads, and then on the pool of 2 threads.
+        assert_eq!(
+            started_threads,
+            vec![
+                (0, Some("merge_thread_0".to_string())),
+                (0, Some("merge_thread_0".to_string())),
+                (1, Some("merge_thread_1".to_string())),
+                (1, Some("merge_thread_1".to_string())),
+                (2, Some("merge_thread_2".to_string())),
+                (3, Some("merge_thread_3".to_string())),
+            ]
+        );
+        index_writer.wait_merging_threads()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_segment() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", FAST | INDEXED | STORED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..10u64 {
+            let parity = if id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(id_field => id, text_field => parity))?;
+        }
+        index_writer.commit()?;
+        for id in [2u64, 5, 7] {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 1);
+        let segment_meta = index_writer
+            .compact_segment(segment_ids[0])
+            .wait()?
+            .unwrap();
+        assert_ne!(segment_meta.id(), segment_ids[0]);
+        assert_eq!(segment_meta.num_docs(), 7);
+        assert_eq!(segment_meta.num_deleted_docs(), 0);
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.max_doc(), 7);
+        assert!(!segment_reader.has_deletes());
+        let id_reader = segment_reader.fast_fields().u64(id_field)?;
+        let ids: Vec<u64> = (0..7u64).map(|doc| id_reader.get_val(doc)).collect();
+        assert_eq!(ids, vec![0, 1, 3, 4, 6, 8, 9]);
+        for (doc, id) in ids.iter().enumerate() {
+            let stored_doc = searcher.doc(DocAddress::new(0, doc as u32))?;
+            assert_eq!(stored_doc.get_first(id_field).unwrap().as_u64(), Some(*id));
+        }
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(text_field, "even"))?,
+            4
+        );
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(text_field, "odd"))?,
+            3
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_listener() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let (event_sender, event_receiver) = crossbeam_channel::unbounded();
+        index_writer.set_event_listener(move |event| {
+            event_sender.send(event).unwrap();
+        })?;
+        let next_event = || {
+            event_receiver
+                .recv_timeout(Duration::from_secs(10))
+                .unwrap()
+        };
+
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.add_document(doc!(text_field => "b"))?;
+        let first_opstamp = index_writer.commit()?;
+        let first_segment_id = match next_event() {
+            IndexWriterEvent::SegmentCreated(segment_meta) => {
+                assert_eq!(segment_meta.num_docs(), 2);
+                segment_meta.id()
+            }
+            event => panic!("Unexpected event {:?}", event),
+        };
+        assert!(matches!(
+            next_event(),
+            IndexWriterEvent::CommitStarted { opstamp } if opstamp == first_opstamp
+        ));
+        match next_event() {
+            IndexWriterEvent::CommitFinished { opstamp, segments } => {
+                assert_eq!(opstamp, first_opstamp);
+                assert_eq!(segments.len(), 1);
+                assert_eq!(segments[0].id(), first_segment_id);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+
+        index_writer.add_document(doc!(text_field => "c"))?;
+        let second_opstamp = index_writer.commit()?;
+        let second_segment_id = match next_event() {
+            IndexWriterEvent::SegmentCreated(segment_meta) => {
+                assert_eq!(segment_meta.num_docs(), 1);
+                segment_meta.id()
+            }
+            event => panic!("Unexpected event {:?}", event),
+        };
+        assert!(matches!(
+            next_event(),
+            IndexWriterEvent::CommitStarted { opstamp } if opstamp == second_opstamp
+        ));
+        assert!(matches!(
+            next_event(),
+            IndexWriterEvent::CommitFinished { segments, .. } if segments.len() == 2
+        ));
+
+        let merged_segment_meta = index_writer
+            .merge(&[first_segment_id, second_segment_id])
+            .wait()?
+            .unwrap();
+        match next_event() {
+            IndexWriterEvent::MergeStarted { segments } => {
+                let segment_ids: HashSet<SegmentId> = segments
+                    .iter()
+                    .map(|segment_meta| segment_meta.id())
+                    .collect();
+                assert_eq!(
+                    segment_ids,
+                    HashSet::from([first_segment_id, second_segment_id])
+                );
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        match next_event() {
+            IndexWriterEvent::MergeFinished {
+                segment_ids,
+                merged_segment,
+            } => {
+                assert_eq!(segment_ids.len(), 2);
+                let merged_segment = merged_segment.unwrap();
+                assert_eq!(merged_segment.id(), merged_segment_meta.id());
+                assert_eq!(merged_segment.num_docs(), 3);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        index_writer.wait_merging_threads()?;
+        assert!(event_receiver.try_recv().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_prepare_with_commit_message() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -1368,6 +1939,57 @@ mod tests {
         assert!(commit_again.is_ok());
     }
 
+    #[test]
+    fn test_add_document_block() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_with_num_threads(4, 12_000_000)?;
+        let mut num_docs = 0;
+        for block in 0u64..50u64 {
+            let num_children = block % 5;
+            let children = (1..=num_children)
+                .map(|i| doc!(id_field => block * 10 + i))
+                .collect();
+            let opstamp =
+                index_writer.add_document_block(children, doc!(id_field => block * 10))?;
+            num_docs += num_children + 1;
+            // The parent gets the last opstamp of the block.
+            assert_eq!(opstamp, num_docs - 1);
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for segment_reader in searcher.segment_readers() {
+            let ids: Vec<u64> = segment_reader.fast_fields().u64(id_field)?.iter().collect();
+            // Each block is contiguous, and ends with its parent.
+            for block_ids in ids.split_inclusive(|id| id % 10 == 0) {
+                let parent_id = *block_ids.last().unwrap();
+                let children_ids: Vec<u64> =
+                    (1..=(parent_id / 10) % 5).map(|i| parent_id + i).collect();
+                assert_eq!(&block_ids[..block_ids.len() - 1], &children_ids[..]);
+            }
+        }
+
+        let settings = IndexSettings {
+            sort_by_field: Some(IndexSortByField {
+                field: "id".to_string(),
+                order: Order::Desc,
+            }),
+            ..Default::default()
+        };
+        let sorted_index = Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()?;
+        let index_writer = sorted_index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.add_document_block(vec![doc!(id_field => 1u64)], doc!(id_field => 0u64)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_delete_with_sort_by_field() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -1810,4 +2432,213 @@ mod tests {
         index_writer.commit()?;
         Ok(())
     }
+
+    /// Calls `on_merging` as soon as the field norms of a segment other than the source
+    /// segments are read, i.e. in the middle of the merge, once its field norms are written.
+    #[derive(Clone)]
+    struct MidMergeDirectory {
+        directory: RamDirectory,
+        source_segment_ids: Arc<Mutex<Vec<SegmentId>>>,
+        on_merging: Arc<dyn Fn() + Send + Sync>,
+    }
+
+    impl MidMergeDirectory {
+        fn new(on_merging: impl Fn() + Send + Sync + 'static) -> MidMergeDirectory {
+            MidMergeDirectory {
+                directory: RamDirectory::create(),
+                source_segment_ids: Arc::default(),
+                on_merging: Arc::new(on_merging),
+            }
+        }
+
+        fn check_if_merging(&self, path: &Path) {
+            let source_segment_ids = self.source_segment_ids.lock().unwrap();
+            let is_source_segment = source_segment_ids.iter().any(|segment_id| {
+                path.to_string_lossy()
+                    .starts_with(&segment_id.uuid_string())
+            });
+            if !source_segment_ids.is_empty()
+                && !is_source_segment
+                && path.to_string_lossy().ends_with(".fieldnorm")
+            {
+                (self.on_merging)();
+            }
+        }
+    }
+
+    impl fmt::Debug for MidMergeDirectory {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("MidMergeDirectory")
+                .field("directory", &self.directory)
+                .finish()
+        }
+    }
+
+    impl Directory for MidMergeDirectory {
+        fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+            self.check_if_merging(path);
+            self.directory.get_file_handle(path)
+        }
+
+        fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+            self.check_if_merging(path);
+            self.directory.open_read(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            self.directory.delete(path)
+        }
+
+        fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+            self.directory.exists(path)
+        }
+
+        fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            self.directory.open_write(path)
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.directory.atomic_read(path)
+        }
+
+        fn atomic_write(&self, path: &Path, data: &[u8]) -> std::io::Result<()> {
+            self.directory.atomic_write(path, data)
+        }
+
+        fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+            self.directory.watch(watch_callback)
+        }
+
+        fn sync_directory(&self) -> std::io::Result<()> {
+            self.directory.sync_directory()
+        }
+    }
+
+    #[test]
+    fn test_cancel_merge() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let cancellation_token = MergeCancellationToken::new();
+        let directory = MidMergeDirectory::new({
+            let cancellation_token = cancellation_token.clone();
+            move || cancellation_token.cancel()
+        });
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..30 {
+            index_writer.add_document(doc!(text_field => format!("text {}", i % 3)))?;
+            if i % 10 == 9 {
+                index_writer.commit()?;
+            }
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 3);
+        directory
+            .source_segment_ids
+            .lock()
+            .unwrap()
+            .extend(segment_ids.iter().cloned());
+
+        let merge_res = index_writer
+            .merge_with_cancellation_token(&segment_ids, cancellation_token.clone())
+            .wait();
+        assert!(matches!(merge_res, Err(TantivyError::MergeCancelled)));
+        assert!(cancellation_token.is_cancelled());
+
+        // The source segments are left intact, and are still searchable after a commit.
+        index_writer.commit()?;
+        let mut segment_ids_after_cancel = index.searchable_segment_ids()?;
+        segment_ids_after_cancel.sort();
+        let mut expected_segment_ids = segment_ids.clone();
+        expected_segment_ids.sort();
+        assert_eq!(segment_ids_after_cancel, expected_segment_ids);
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 30);
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "1"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count)?, 10);
+        assert_eq!(searcher.iter_docs().count(), 30);
+
+        // The segments can still be merged.
+        directory.source_segment_ids.lock().unwrap().clear();
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.search(&term_query, &Count)?, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_cancels_background_merge() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        // Blocks the merge in the middle, until the index writer is dropped.
+        let (merging_sender, merging_receiver) = crossbeam_channel::bounded(1);
+        let (dropped_sender, dropped_receiver) = crossbeam_channel::bounded::<()>(1);
+        let directory = MidMergeDirectory::new(move || {
+            if merging_sender.try_send(()).is_ok() {
+                let _ = dropped_receiver.recv_timeout(Duration::from_secs(10));
+            }
+        });
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let (event_sender, event_receiver) = crossbeam_channel::unbounded();
+        index_writer.set_event_listener(move |event| {
+            if let IndexWriterEvent::MergeFailed { error, .. } = event {
+                event_sender.send(error).unwrap();
+            }
+        })?;
+        for i in 0..20 {
+            index_writer.add_document(doc!(text_field => format!("text {}", i % 3)))?;
+            if i % 10 == 9 {
+                index_writer.commit()?;
+            }
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        directory
+            .source_segment_ids
+            .lock()
+            .unwrap()
+            .extend(segment_ids.iter().cloned());
+
+        // The merge policy starts a merge of the two segments after the commit.
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        index_writer.commit()?;
+        merging_receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("The merge did not start.");
+        drop(index_writer);
+        dropped_sender.send(()).unwrap();
+
+        let error = event_receiver
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(error, TantivyError::MergeCancelled.to_string());
+        let mut segment_ids_after_drop = index.searchable_segment_ids()?;
+        segment_ids_after_drop.sort();
+        let mut expected_segment_ids = segment_ids;
+        expected_segment_ids.sort();
+        assert_eq!(segment_ids_after_drop, expected_segment_ids);
+        Ok(())
+    }
 }
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel as channel;
+
+use crate::{Opstamp, SegmentId, SegmentMeta};
+
+/// An event of the lifecycle of the segments of an index, reported to the
+/// [`IndexWriterEventListener`] of an [`IndexWriter`](crate::IndexWriter).
+///
+/// The segments are described by their [`SegmentMeta`], which gives their id and their number
+/// of documents.
+#[derive(Clone, Debug)]
+pub enum IndexWriterEvent {
+    /// An indexing thread created a new segment. It is not committed yet.
+    SegmentCreated(SegmentMeta),
+    /// A commit started.
+    CommitStarted {
+        /// Opstamp of the commit.
+        opstamp: Opstamp,
+    },
+    /// A commit finished.
+    CommitFinished {
+        /// Opstamp of the commit.
+        opstamp: Opstamp,
+        /// Committed segments of the index.
+        segments: Vec<SegmentMeta>,
+    },
+    /// A merge started.
+    MergeStarted {
+        /// Segments being merged.
+        segments: Vec<SegmentMeta>,
+    },
+    /// A merge finished.
+    MergeFinished {
+        /// Ids of the segments that were merged.
+        segment_ids: Vec<SegmentId>,
+        /// The merged segment, or `None` if all of the documents of the merged segments were
+        /// deleted.
+        merged_segment: Option<SegmentMeta>,
+    },
+    /// A merge failed, or was cancelled. The segments that were to be merged are left as is.
+    MergeFailed {
+        /// Ids of the segments that were to be merged.
+        segment_ids: Vec<SegmentId>,
+        /// Description of the error.
+        error: String,
+    },
+}
+
+/// Listener of the [`IndexWriterEvent`]s of an [`IndexWriter`](crate::IndexWriter), set with
+/// [`IndexWriter::set_event_listener`](crate::IndexWriter::set_event_listener).
+///
+/// The events are dispatched to the listener by a dedicated thread, in the order they
+/// occurred, so that a slow listener never blocks indexing, commits or merges.
+pub trait IndexWriterEventListener: Send + Sync + 'static {
+    /// Called for each event.
+    fn on_event(&self, event: IndexWriterEvent);
+}
+
+impl<F> IndexWriterEventListener for F
+where F: Fn(IndexWriterEvent) + Send + Sync + 'static
+{
+    fn on_event(&self, event: IndexWriterEvent) {
+        (self)(event)
+    }
+}
+
+/// Buffers the events in an unbounded channel, consumed by the thread running the listener.
+///
+/// The thread exits once the sender is dropped, and all of the buffered events are
+/// dispatched.
+pub(crate) struct EventSender(channel::Sender<IndexWriterEvent>);
+
+impl EventSender {
+    pub fn spawn(listener: Arc<dyn IndexWriterEventListener>) -> crate::Result<EventSender> {
+        let (sender, receiver) = channel::unbounded::<IndexWriterEvent>();
+        thread::Builder::new()
+            .name("index_writer_events".to_string())
+            .spawn(move || {
+                for event in receiver {
+                    listener.on_event(event);
+                }
+            })?;
+        Ok(EventSender(sender))
+    }
+
+    pub fn send(&self, event: IndexWriterEvent) {
+        // The listener thread only stops once the sender is dropped.
+        let _ = self.0.send(event);
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::BufRead;
+
+use thiserror::Error;
+
+use crate::indexer::document_validation::validate_document;
+use crate::schema::DocParsingError;
+use crate::{IndexWriter, TantivyError};
+
+/// Reason why a line of a JSON lines stream was not added to the index.
+#[derive(Debug, Error)]
+pub enum JsonLineErrorKind {
+    /// The line could not be parsed as a document of the schema.
+    #[error(transparent)]
+    Parsing(#[from] DocParsingError),
+    /// The document was parsed but is invalid, e.g. it has a dense vector of the wrong
+    /// dimension. See [`IndexWriter::add_document`].
+    #[error(transparent)]
+    InvalidDocument(TantivyError),
+}
+
+/// Error on a line of a JSON lines stream, reported by [`IndexWriter::add_json_lines`].
+#[derive(Debug)]
+pub struct JsonLineError {
+    line_number: usize,
+    error: JsonLineErrorKind,
+}
+
+impl JsonLineError {
+    /// Returns the number of the line, starting at 1.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Returns the error that happened while parsing or validating the line.
+    pub fn error(&self) -> &JsonLineErrorKind {
+        &self.error
+    }
+}
+
+/// Outcome of the ingestion of a JSON lines stream by [`IndexWriter::add_json_lines`].
+#[derive(Debug, Default)]
+pub struct JsonLinesReport {
+    num_added_docs: usize,
+    errors: Vec<JsonLineError>,
+}
+
+impl JsonLinesReport {
+    /// Returns the number of documents added to the index writer.
+    pub fn num_added_docs(&self) -> usize {
+        self.num_added_docs
+    }
+
+    /// Returns the lines that could not be parsed or turned into a valid document, in the order
+    /// of the stream.
+    pub fn errors(&self) -> &[JsonLineError] {
+        &self.errors
+    }
+}
+
+pub(crate) fn add_json_lines<R: BufRead>(
+    index_writer: &IndexWriter,
+    mut json_lines: R,
+) -> crate::Result<JsonLinesReport> {
+    let schema = index_writer.index().schema();
+    let mut report = JsonLinesReport::default();
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if json_lines.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        let doc_result = match std::str::from_utf8(&line) {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => schema.parse_document(line),
+            Err(_) => Err(DocParsingError::invalid_json(&String::from_utf8_lossy(
+                &line,
+            ))),
+        };
+        let doc = match doc_result {
+            Ok(doc) => doc,
+            Err(error) => {
+                report.errors.push(JsonLineError {
+                    line_number,
+                    error: JsonLineErrorKind::Parsing(error),
+                });
+                continue;
+            }
+        };
+        // The document is validated here, so that the error of `add_document` only reports a
+        // failure of the index writer, which aborts the ingestion.
+        if let Err(error) = validate_document(&schema, &doc) {
+            report.errors.push(JsonLineError {
+                line_number,
+                error: JsonLineErrorKind::InvalidDocument(error),
+            });
+            continue;
+        }
+        index_writer.add_document(doc)?;
+        report.num_added_docs += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonLineErrorKind;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{
+        DenseVectorOptions, DocParsingError, IndexRecordOption, Schema, FAST, STORED, STRING,
+    };
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_add_json_lines() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STRING | STORED);
+        let tags = schema_builder.add_text_field("tags", STRING);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let json_lines: &[u8] = br#"{"title": "book", "tags": ["paper", "fiction"], "price": 12}
+{"title": "pen", "price": -3}
+not json
+
+{"title": "ink", "unknown_field": 3}
+{"title": "mug", "price": 7}"#;
+        let report = index_writer.add_json_lines(json_lines)?;
+        assert_eq!(report.num_added_docs(), 3);
+        let errors = report.errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line_number(), 2);
+        assert!(matches!(
+            errors[0].error(),
+            JsonLineErrorKind::Parsing(DocParsingError::ValueError(field_name, _))
+                if field_name == "price"
+        ));
+        assert_eq!(errors[1].line_number(), 3);
+        assert!(matches!(
+            errors[1].error(),
+            JsonLineErrorKind::Parsing(DocParsingError::InvalidJson(_))
+        ));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let count_term = |field, text| {
+            let query =
+                TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic);
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count_term(title, "book")?, 1);
+        assert_eq!(count_term(title, "pen")?, 0);
+        assert_eq!(count_term(title, "ink")?, 1);
+        assert_eq!(count_term(title, "mug")?, 1);
+        assert_eq!(count_term(tags, "paper")?, 1);
+        assert_eq!(count_term(tags, "fiction")?, 1);
+        let price_reader = searcher.segment_reader(0).fast_fields().u64(price)?;
+        let mut prices: Vec<u64> = (0..3).map(|doc| price_reader.get_val(doc)).collect();
+        prices.sort_unstable();
+        assert_eq!(prices, vec![0, 7, 12]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_json_lines_invalid_utf8() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests()?;
+        let mut json_lines = b"{\"title\": \"\xff\"}\n".to_vec();
+        json_lines.extend_from_slice(b"{\"title\": \"ok\"}\n");
+        let report = index_writer.add_json_lines(&json_lines[..])?;
+        assert_eq!(report.num_added_docs(), 1);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.errors()[0].line_number(), 1);
+        assert!(matches!(
+            report.errors()[0].error(),
+            JsonLineErrorKind::Parsing(DocParsingError::InvalidJson(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_json_lines_invalid_vectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", STRING);
+        schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(2));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let json_lines: &[u8] = br#"{"title": "a", "embedding": [1.0, 2.0]}
+{"title": "b", "embedding": [1.0]}
+{"title": "c", "embedding": [3.0, 4.0]}
+{"title": "d", "embedding": [1.0, 2.0, 3.0]}"#;
+        let report = index_writer.add_json_lines(json_lines)?;
+        assert_eq!(report.num_added_docs(), 2);
+        let error_lines: Vec<usize> = report
+            .errors()
+            .iter()
+            .map(|error| error.line_number())
+            .collect();
+        assert_eq!(error_lines, vec![2, 4]);
+        assert!(report.errors().iter().all(|error| matches!(
+            error.error(),
+            JsonLineErrorKind::InvalidDocument(TantivyError::InvalidArgument(_))
+        )));
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        Ok(())
+    }
+}
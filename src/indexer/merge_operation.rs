@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::{Inventory, Opstamp, SegmentId, TrackedObject};
+use crate::{Inventory, Opstamp, SegmentId, TantivyError, TrackedObject};
 
 #[derive(Default)]
 pub(crate) struct MergeOperationInventory(Inventory<InnerMergeOperation>);
@@ -26,6 +28,43 @@ impl MergeOperationInventory {
     }
 }
 
+/// A token to cancel a merge while it is in progress.
+///
+/// The merge checks the token periodically, and stops as soon as it is cancelled. The
+/// segments that were to be merged are then left as is, and the files written so far for the
+/// merged segment are removed by the next garbage collection.
+///
+/// The clones of a token share the same state: cancelling any of them cancels the merge.
+#[derive(Clone, Debug, Default)]
+pub struct MergeCancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl MergeCancellationToken {
+    /// Creates a new token, which is not cancelled.
+    pub fn new() -> MergeCancellationToken {
+        MergeCancellationToken::default()
+    }
+
+    /// Cancels the merges this token was given to.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns a `TantivyError::MergeCancelled` error if the token has been cancelled.
+    pub(crate) fn check(&self) -> crate::Result<()> {
+        if self.is_cancelled() {
+            return Err(TantivyError::MergeCancelled);
+        }
+        Ok(())
+    }
+}
+
 /// A `MergeOperation` has two roles.
 /// It carries all of the information required to describe a merge:
 /// - `target_opstamp` is the opstamp up to which we want to consume the
@@ -46,17 +85,20 @@ pub struct MergeOperation {
 pub(crate) struct InnerMergeOperation {
     target_opstamp: Opstamp,
     segment_ids: Vec<SegmentId>,
+    cancellation_token: MergeCancellationToken,
 }
 
 impl MergeOperation {
-    pub(crate) fn new(
+    pub(crate) fn with_cancellation_token(
         inventory: &MergeOperationInventory,
         target_opstamp: Opstamp,
         segment_ids: Vec<SegmentId>,
+        cancellation_token: MergeCancellationToken,
     ) -> MergeOperation {
         let inner_merge_operation = InnerMergeOperation {
             target_opstamp,
             segment_ids,
+            cancellation_token,
         };
         MergeOperation {
             inner: inventory.track(inner_merge_operation),
@@ -70,4 +112,8 @@ impl MergeOperation {
     pub fn segment_ids(&self) -> &[SegmentId] {
         &self.inner.segment_ids[..]
     }
+
+    pub(crate) fn cancellation_token(&self) -> &MergeCancellationToken {
+        &self.inner.cancellation_token
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::marker;
 
@@ -17,6 +18,29 @@ pub trait MergePolicy: marker::Send + marker::Sync + Debug {
     /// This call happens on the segment updater thread, and will block
     /// other segment updates, so all implementations should happen rapidly.
     fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate>;
+
+    /// Given the metas of the segments being merged, returns the user metadata of the
+    /// resulting segment.
+    ///
+    /// By default, only the entries having the same value in all of the merged segments are
+    /// kept, and the other entries are dropped.
+    fn merge_user_metadata(&self, segments: &[SegmentMeta]) -> BTreeMap<String, String> {
+        let (first_segment, other_segments) = if let Some(segments) = segments.split_first() {
+            segments
+        } else {
+            return BTreeMap::new();
+        };
+        first_segment
+            .user_metadata()
+            .iter()
+            .filter(|(key, value)| {
+                other_segments
+                    .iter()
+                    .all(|segment| segment.user_metadata().get(*key) == Some(*value))
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
 }
 
 /// Never merge segments.
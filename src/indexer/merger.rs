@@ -1,26 +1,30 @@
-use std::cmp;
 use std::collections::HashMap;
-use std::io::Write;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::{cmp, mem};
 
-use fastfield_codecs::VecColumn;
 use itertools::Itertools;
 use measure_time::debug_time;
 
 use crate::core::{Segment, SegmentReader};
+use crate::directory::error::DeleteError;
+use crate::directory::{Directory, FileSlice, OwnedBytes, TerminatingWrite, WritePtr};
 use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
 use crate::fastfield::{
-    AliveBitSet, Column, CompositeFastFieldSerializer, FastFieldStats, MultiValueLength,
-    MultiValuedFastFieldReader,
+    fast_field_default_value, AliveBitSet, BytesFastFieldReader, Column,
+    CompositeFastFieldSerializer, FastFieldStats, MultiValueLength, MultiValuedFastFieldReader,
 };
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
 use crate::indexer::doc_id_mapping::{expect_field_id_for_sort_field, SegmentDocIdMapping};
-use crate::indexer::SegmentSerializer;
-use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
+use crate::indexer::{MergeCancellationToken, SegmentSerializer};
+use crate::postings::{FieldSerializer, InvertedIndexSerializer, Postings, SegmentPostings};
 use crate::schema::{Cardinality, Field, FieldType, Schema};
 use crate::store::StoreWriter;
 use crate::termdict::{TermMerger, TermOrdinal};
+use crate::termvector::{has_term_vectors, TermVectorReader, TermVectorsSerializer};
+use crate::vector::{DenseVectorReader, DenseVectorsSerializer};
 use crate::{
     DocAddress, DocId, IndexSettings, IndexSortByField, InvertedIndexReader, Order,
     SegmentComponent, SegmentOrdinal,
@@ -31,6 +35,106 @@ use crate::{
 /// We do not allow segments with more than
 pub const MAX_DOC_LIMIT: u32 = 1 << 31;
 
+/// Column holding `num_vals` times the same value.
+struct ConstantColumn {
+    val: u64,
+    num_vals: u64,
+}
+
+impl Column for ConstantColumn {
+    fn get_val(&self, _idx: u64) -> u64 {
+        self.val
+    }
+
+    fn min_value(&self) -> u64 {
+        self.val
+    }
+
+    fn max_value(&self) -> u64 {
+        self.val
+    }
+
+    fn num_vals(&self) -> u64 {
+        self.num_vals
+    }
+}
+
+// The data of a fast field or of the field norms of a field may be missing from a segment, if
+// the segment was written with a schema that did not have the field yet.
+// See `merge_indices_with_compatible_schemas`.
+//
+// The documents of such a segment are merged as if they had no value for the field.
+
+fn has_fast_field_data(reader: &SegmentReader, field: Field) -> bool {
+    reader.fast_fields().fast_field_data(field, 0).is_ok()
+}
+
+fn single_fast_field_reader_or_default(
+    reader: &SegmentReader,
+    field: Field,
+) -> Arc<dyn Column<u64>> {
+    if !has_fast_field_data(reader, field) {
+        let field_entry = reader.schema().get_field_entry(field);
+        return Arc::new(ConstantColumn {
+            val: fast_field_default_value(field_entry),
+            num_vals: reader.max_doc() as u64,
+        });
+    }
+    reader.fast_fields().typed_fast_field_reader(field).expect(
+        "Failed to find a reader for single fast field. This is a tantivy bug and it should never \
+         happen.",
+    )
+}
+
+fn empty_multi_value_idx(reader: &SegmentReader) -> Arc<dyn Column<u64>> {
+    Arc::new(ConstantColumn {
+        val: 0,
+        num_vals: reader.max_doc() as u64 + 1,
+    })
+}
+
+fn multi_fast_field_reader_or_default(
+    reader: &SegmentReader,
+    field: Field,
+) -> MultiValuedFastFieldReader<u64> {
+    if !has_fast_field_data(reader, field) {
+        let empty_vals = Arc::new(ConstantColumn {
+            val: 0,
+            num_vals: 0,
+        });
+        return MultiValuedFastFieldReader::open(empty_multi_value_idx(reader), empty_vals);
+    }
+    reader
+        .fast_fields()
+        .typed_fast_field_multi_reader::<u64>(field)
+        .expect(
+            "Failed to find multivalued fast field reader. This is a bug in tantivy. Please \
+             report.",
+        )
+}
+
+fn bytes_fast_field_reader_or_default(
+    reader: &SegmentReader,
+    field: Field,
+) -> BytesFastFieldReader {
+    if !has_fast_field_data(reader, field) {
+        return BytesFastFieldReader::open(empty_multi_value_idx(reader), FileSlice::empty())
+            .expect("Reading an empty file slice cannot fail.");
+    }
+    reader
+        .fast_fields()
+        .bytes(field)
+        .expect("Failed to find index for bytes field. This is a bug in tantivy, please report.")
+}
+
+fn fieldnorm_reader_or_default(
+    reader: &SegmentReader,
+    field: Field,
+) -> crate::Result<FieldNormReader> {
+    let fieldnorm_reader_opt = reader.fieldnorms_readers().get_field(field)?;
+    Ok(fieldnorm_reader_opt.unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 0)))
+}
+
 fn estimate_total_num_tokens_in_single_segment(
     reader: &SegmentReader,
     field: Field,
@@ -86,6 +190,9 @@ pub struct IndexMerger {
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
+    memory_budget: Option<usize>,
+    peak_buffered_bytes: AtomicUsize,
+    cancellation_token: MergeCancellationToken,
 }
 
 fn compute_min_max_val(
@@ -157,6 +264,136 @@ impl DeltaComputer {
     }
 }
 
+/// Buffer for the `u64` values of a fast field rebuilt by the merge.
+///
+/// The values are kept in memory if they fit in the merge memory budget. Otherwise, they
+/// are spilled to the `TempFastFields` file of the merged segment.
+enum ColumnBuffer {
+    InMemory(Vec<u64>),
+    // `write` is declared first so that it is closed before the file is deleted.
+    Spilled {
+        write: WritePtr,
+        temp_file: TempFastFieldsFile,
+    },
+}
+
+/// Guard deleting the `TempFastFields` file of a segment when dropped, whether the column
+/// was spilled successfully or not.
+struct TempFastFieldsFile {
+    segment: Segment,
+}
+
+impl Drop for TempFastFieldsFile {
+    fn drop(&mut self) {
+        let temp_path = self.segment.relative_path(SegmentComponent::TempFastFields);
+        match self.segment.index().directory().delete(&temp_path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+            // The file is left to the garbage collection.
+            Err(delete_err) => warn!(
+                "Failed to delete the temporary fast field file {:?}: {:?}",
+                temp_path, delete_err
+            ),
+        }
+    }
+}
+
+/// Column of values written to a `ColumnBuffer`.
+struct BufferedColumn {
+    vals: BufferedVals,
+    min_value: u64,
+    max_value: u64,
+    num_vals: u64,
+}
+
+enum BufferedVals {
+    InMemory(Vec<u64>),
+    Spilled(OwnedBytes),
+}
+
+struct ColumnBufferWriter {
+    buffer: ColumnBuffer,
+    min_value: u64,
+    max_value: u64,
+    num_vals: u64,
+}
+
+impl ColumnBufferWriter {
+    fn push(&mut self, val: u64) -> io::Result<()> {
+        self.min_value = cmp::min(self.min_value, val);
+        self.max_value = cmp::max(self.max_value, val);
+        self.num_vals += 1;
+        match &mut self.buffer {
+            ColumnBuffer::InMemory(vals) => vals.push(val),
+            ColumnBuffer::Spilled { write, .. } => write.write_all(&val.to_le_bytes())?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> crate::Result<BufferedColumn> {
+        let vals = match self.buffer {
+            ColumnBuffer::InMemory(vals) => BufferedVals::InMemory(vals),
+            ColumnBuffer::Spilled { write, temp_file } => {
+                write.terminate()?;
+                let bytes = temp_file
+                    .segment
+                    .open_read(SegmentComponent::TempFastFields)?
+                    .read_bytes()?;
+                // The data stays available after the file is deleted, so that the next column
+                // can be spilled to the same file.
+                drop(temp_file);
+                BufferedVals::Spilled(bytes)
+            }
+        };
+        let (min_value, max_value) = if self.num_vals == 0 {
+            (0, 0)
+        } else {
+            (self.min_value, self.max_value)
+        };
+        Ok(BufferedColumn {
+            vals,
+            min_value,
+            max_value,
+            num_vals: self.num_vals,
+        })
+    }
+}
+
+impl Column for BufferedColumn {
+    fn get_val(&self, idx: u64) -> u64 {
+        match &self.vals {
+            BufferedVals::InMemory(vals) => vals[idx as usize],
+            BufferedVals::Spilled(bytes) => {
+                let start = idx as usize * 8;
+                u64::from_le_bytes(bytes.as_slice()[start..start + 8].try_into().unwrap())
+            }
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        match &self.vals {
+            BufferedVals::InMemory(vals) => Box::new(vals.iter().copied()),
+            BufferedVals::Spilled(bytes) => Box::new(
+                bytes
+                    .as_slice()
+                    .chunks_exact(8)
+                    .map(|val_bytes| u64::from_le_bytes(val_bytes.try_into().unwrap())),
+            ),
+        }
+    }
+
+    fn min_value(&self) -> u64 {
+        self.min_value
+    }
+
+    fn max_value(&self) -> u64 {
+        self.max_value
+    }
+
+    fn num_vals(&self) -> u64 {
+        self.num_vals
+    }
+}
+
 impl IndexMerger {
     pub fn open(
         schema: Schema,
@@ -212,6 +449,73 @@ impl IndexMerger {
             schema,
             readers,
             max_doc,
+            memory_budget: None,
+            peak_buffered_bytes: AtomicUsize::new(0),
+            cancellation_token: MergeCancellationToken::default(),
+        })
+    }
+
+    /// Sets a memory budget for the buffers of the merge.
+    ///
+    /// By default, the merge buffers in memory the rebuilt fast fields and, if the index is
+    /// sorted, the postings of each term. With a memory budget, the fast fields that do not fit
+    /// in the budget are spilled to a temporary file of the merged segment, and the postings
+    /// are merged without buffering them.
+    ///
+    /// The doc id mapping, the field norms and the term ordinal mappings, which take a few
+    /// bytes per document and per term, are not subject to the budget.
+    pub fn set_memory_budget(&mut self, memory_budget_in_bytes: usize) {
+        self.memory_budget = Some(memory_budget_in_bytes);
+    }
+
+    /// Sets a token to cancel the merge.
+    ///
+    /// The token is checked between the steps of the merge, as well as for each term and for
+    /// each document. Once it is cancelled, [`IndexMerger::write`] returns
+    /// `TantivyError::MergeCancelled`.
+    pub fn set_cancellation_token(&mut self, cancellation_token: MergeCancellationToken) {
+        self.cancellation_token = cancellation_token;
+    }
+
+    /// Returns the largest number of bytes held at once by the buffers subject to the memory
+    /// budget, since the merger was opened.
+    pub fn peak_buffered_bytes(&self) -> usize {
+        self.peak_buffered_bytes.load(Ordering::Relaxed)
+    }
+
+    fn register_buffered_bytes(&self, num_bytes: usize) {
+        self.peak_buffered_bytes
+            .fetch_max(num_bytes, Ordering::Relaxed);
+    }
+
+    /// Creates a buffer for `num_vals` fast field values, spilled to disk if the values do not
+    /// fit in the memory budget.
+    fn new_column_buffer(
+        &self,
+        segment: &Segment,
+        num_vals: usize,
+    ) -> crate::Result<ColumnBufferWriter> {
+        let num_bytes = num_vals * mem::size_of::<u64>();
+        let buffer = if self
+            .memory_budget
+            .map(|memory_budget| num_bytes > memory_budget)
+            .unwrap_or(false)
+        {
+            let mut segment = segment.clone();
+            let write = segment.open_write(SegmentComponent::TempFastFields)?;
+            ColumnBuffer::Spilled {
+                write,
+                temp_file: TempFastFieldsFile { segment },
+            }
+        } else {
+            self.register_buffered_bytes(num_bytes);
+            ColumnBuffer::InMemory(Vec::with_capacity(num_vals))
+        };
+        Ok(ColumnBufferWriter {
+            buffer,
+            min_value: u64::MAX,
+            max_value: u64::MIN,
+            num_vals: 0,
         })
     }
 
@@ -252,7 +556,7 @@ impl IndexMerger {
             let fieldnorms_readers: Vec<FieldNormReader> = self
                 .readers
                 .iter()
-                .map(|reader| reader.get_fieldnorms_reader(field))
+                .map(|reader| fieldnorm_reader_or_default(reader, field))
                 .collect::<Result<_, _>>()?;
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
                 let fieldnorms_reader = &fieldnorms_readers[old_doc_addr.segment_ord as usize];
@@ -268,6 +572,7 @@ impl IndexMerger {
 
     fn write_fast_fields(
         &self,
+        segment: &Segment,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
         mut term_ord_mappings: HashMap<Field, TermOrdinalMapping>,
         doc_id_mapping: &SegmentDocIdMapping,
@@ -275,6 +580,7 @@ impl IndexMerger {
         debug_time!("write-fast-fields");
 
         for (field, field_entry) in self.schema.fields() {
+            self.cancellation_token.check()?;
             let field_type = field_entry.field_type();
             match field_type {
                 FieldType::Facet(_) | FieldType::Str(_) if field_type.is_fast() => {
@@ -283,6 +589,7 @@ impl IndexMerger {
                          a`term_ordinal_mapping`.",
                     );
                     self.write_term_id_fast_field(
+                        segment,
                         field,
                         &term_ordinal_mapping,
                         fast_field_serializer,
@@ -297,7 +604,12 @@ impl IndexMerger {
                         self.write_single_fast_field(field, fast_field_serializer, doc_id_mapping)?;
                     }
                     Some(Cardinality::MultiValues) => {
-                        self.write_multi_fast_field(field, fast_field_serializer, doc_id_mapping)?;
+                        self.write_multi_fast_field(
+                            segment,
+                            field,
+                            fast_field_serializer,
+                            doc_id_mapping,
+                        )?;
                     }
                     None => {}
                 },
@@ -306,13 +618,23 @@ impl IndexMerger {
                         self.write_single_fast_field(field, fast_field_serializer, doc_id_mapping)?;
                     }
                     Some(Cardinality::MultiValues) => {
-                        self.write_multi_fast_field(field, fast_field_serializer, doc_id_mapping)?;
+                        self.write_multi_fast_field(
+                            segment,
+                            field,
+                            fast_field_serializer,
+                            doc_id_mapping,
+                        )?;
                     }
                     None => {}
                 },
                 FieldType::Bytes(byte_options) => {
                     if byte_options.is_fast() {
-                        self.write_bytes_fast_field(field, fast_field_serializer, doc_id_mapping)?;
+                        self.write_bytes_fast_field(
+                            segment,
+                            field,
+                            fast_field_serializer,
+                            doc_id_mapping,
+                        )?;
                     }
                 }
                 FieldType::JsonObject(_) | FieldType::Facet(_) | FieldType::Str(_) => {
@@ -320,6 +642,9 @@ impl IndexMerger {
                     // They can be implemented using what is done
                     // for facets in the future
                 }
+                FieldType::DenseVector(_) => {
+                    // Dense vectors are merged with `write_dense_vectors`.
+                }
             }
         }
         Ok(())
@@ -336,11 +661,7 @@ impl IndexMerger {
             .readers
             .iter()
             .filter_map(|reader| {
-                let u64_reader: Arc<dyn Column<u64>> =
-                    reader.fast_fields().typed_fast_field_reader(field).expect(
-                        "Failed to find a reader for single fast field. This is a tantivy bug and \
-                         it should never happen.",
-                    );
+                let u64_reader = single_fast_field_reader_or_default(reader, field);
                 compute_min_max_val(&*u64_reader, reader)
             })
             .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
@@ -349,14 +670,7 @@ impl IndexMerger {
         let fast_field_readers = self
             .readers
             .iter()
-            .map(|reader| {
-                let u64_reader: Arc<dyn Column<u64>> =
-                    reader.fast_fields().typed_fast_field_reader(field).expect(
-                        "Failed to find a reader for single fast field. This is a tantivy bug and \
-                         it should never happen.",
-                    );
-                u64_reader
-            })
+            .map(|reader| single_fast_field_reader_or_default(reader, field))
             .collect::<Vec<_>>();
 
         let stats = FastFieldStats {
@@ -520,11 +834,13 @@ impl IndexMerger {
     // `MultiValuedFastFieldReader`
     //
     fn write_1_n_fast_field_idx_generic<T: MultiValueLength>(
+        &self,
+        segment: &Segment,
         field: Field,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
         doc_id_mapping: &SegmentDocIdMapping,
         reader_and_field_accessors: &[(&SegmentReader, T)],
-    ) -> crate::Result<Vec<u64>> {
+    ) -> crate::Result<BufferedColumn> {
         // We can now create our `idx` serializer, and in a second pass,
         // can effectively push the different indexes.
 
@@ -533,43 +849,35 @@ impl IndexMerger {
         // access on the fly or 2. change the codec api to make random access optional, but
         // they both have also major drawbacks.
 
-        let mut offsets = Vec::with_capacity(doc_id_mapping.len());
+        let mut offsets = self.new_column_buffer(segment, doc_id_mapping.len() + 1)?;
         let mut offset = 0;
         for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
             let reader = &reader_and_field_accessors[old_doc_addr.segment_ord as usize].1;
-            offsets.push(offset);
+            offsets.push(offset)?;
             offset += reader.get_len(old_doc_addr.doc_id) as u64;
         }
-        offsets.push(offset);
-
-        let fastfield_accessor = VecColumn::from(&offsets[..]);
+        offsets.push(offset)?;
+        let offsets = offsets.finish()?;
 
-        fast_field_serializer.create_auto_detect_u64_fast_field(field, fastfield_accessor)?;
+        fast_field_serializer.create_auto_detect_u64_fast_field(field, &offsets)?;
         Ok(offsets)
     }
     /// Returns the fastfield index (index for the data, not the data).
     fn write_multi_value_fast_field_idx(
         &self,
+        segment: &Segment,
         field: Field,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
         doc_id_mapping: &SegmentDocIdMapping,
-    ) -> crate::Result<Vec<u64>> {
+    ) -> crate::Result<BufferedColumn> {
         let reader_ordinal_and_field_accessors = self
             .readers
             .iter()
-            .map(|reader| {
-                let u64s_reader: MultiValuedFastFieldReader<u64> = reader
-                    .fast_fields()
-                    .typed_fast_field_multi_reader::<u64>(field)
-                    .expect(
-                        "Failed to find index for multivalued field. This is a bug in tantivy, \
-                         please report.",
-                    );
-                (reader, u64s_reader)
-            })
+            .map(|reader| (reader, multi_fast_field_reader_or_default(reader, field)))
             .collect::<Vec<_>>();
 
-        Self::write_1_n_fast_field_idx_generic(
+        self.write_1_n_fast_field_idx_generic(
+            segment,
             field,
             fast_field_serializer,
             doc_id_mapping,
@@ -579,6 +887,7 @@ impl IndexMerger {
 
     fn write_term_id_fast_field(
         &self,
+        segment: &Segment,
         field: Field,
         term_ordinal_mappings: &TermOrdinalMapping,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
@@ -591,23 +900,24 @@ impl IndexMerger {
         // The second contains the actual values.
 
         // First we merge the idx fast field.
-        self.write_multi_value_fast_field_idx(field, fast_field_serializer, doc_id_mapping)?;
+        let offsets = self.write_multi_value_fast_field_idx(
+            segment,
+            field,
+            fast_field_serializer,
+            doc_id_mapping,
+        )?;
+        let num_vals = offsets.max_value() as usize;
+        drop(offsets);
 
         let fast_field_reader = self
             .readers
             .iter()
-            .map(|reader| {
-                let ff_reader: MultiValuedFastFieldReader<u64> = reader
-                    .fast_fields()
-                    .u64s(field)
-                    .expect("Could not find multivalued u64 fast value reader.");
-                ff_reader
-            })
+            .map(|reader| multi_fast_field_reader_or_default(reader, field))
             .collect::<Vec<_>>();
         // We can now write the actual fast field values.
         // In the case of hierarchical facets, they are actually term ordinals.
         {
-            let mut vals = Vec::new();
+            let mut vals = self.new_column_buffer(segment, num_vals)?;
             let mut buffer = Vec::new();
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
                 let term_ordinal_mapping: &[TermOrdinal] =
@@ -617,11 +927,11 @@ impl IndexMerger {
                 ff_reader.get_vals(old_doc_addr.doc_id, &mut buffer);
                 for &prev_term_ord in &buffer {
                     let new_term_ord = term_ordinal_mapping[prev_term_ord as usize];
-                    vals.push(new_term_ord);
+                    vals.push(new_term_ord)?;
                 }
             }
 
-            let col = VecColumn::from(&vals[..]);
+            let col = vals.finish()?;
             fast_field_serializer.create_auto_detect_u64_fast_field_with_idx(field, col, 1)?;
         }
         Ok(())
@@ -653,6 +963,7 @@ impl IndexMerger {
     }
     fn write_multi_fast_field(
         &self,
+        segment: &Segment,
         field: Field,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
         doc_id_mapping: &SegmentDocIdMapping,
@@ -662,8 +973,12 @@ impl IndexMerger {
         // The second contains the actual values.
 
         // First we merge the idx fast field.
-        let offsets =
-            self.write_multi_value_fast_field_idx(field, fast_field_serializer, doc_id_mapping)?;
+        let offsets = self.write_multi_value_fast_field_idx(
+            segment,
+            field,
+            fast_field_serializer,
+            doc_id_mapping,
+        )?;
 
         let mut min_value = u64::MAX;
         let mut max_value = u64::MIN;
@@ -680,13 +995,7 @@ impl IndexMerger {
         // We go through a complete first pass to compute the minimum and the
         // maximum value and initialize our Serializer.
         for reader in &self.readers {
-            let ff_reader: MultiValuedFastFieldReader<u64> = reader
-                .fast_fields()
-                .typed_fast_field_multi_reader::<u64>(field)
-                .expect(
-                    "Failed to find multivalued fast field reader. This is a bug in tantivy. \
-                     Please report.",
-                );
+            let ff_reader = multi_fast_field_reader_or_default(reader, field);
             for doc in reader.doc_ids_alive() {
                 ff_reader.get_vals(doc, &mut vals);
                 for &val in &vals {
@@ -714,7 +1023,7 @@ impl IndexMerger {
         struct SortedDocIdMultiValueAccessProvider<'a> {
             doc_id_mapping: &'a SegmentDocIdMapping,
             fast_field_readers: &'a Vec<MultiValuedFastFieldReader<u64>>,
-            offsets: Vec<u64>,
+            offsets: BufferedColumn,
             stats: FastFieldStats,
         }
         impl<'a> Column for SortedDocIdMultiValueAccessProvider<'a> {
@@ -724,12 +1033,12 @@ impl IndexMerger {
                 let new_doc_id: DocId =
                     self.offsets
                         .iter()
-                        .position(|&offset| offset > pos)
+                        .position(|offset| offset > pos)
                         .expect("pos is out of bounds") as DocId
                         - 1u32;
 
                 // now we need to find the position of `pos` in the multivalued bucket
-                let num_pos_covered_until_now = self.offsets[new_doc_id as usize];
+                let num_pos_covered_until_now = self.offsets.get_val(new_doc_id as u64);
                 let pos_in_values = pos - num_pos_covered_until_now;
 
                 let old_doc_addr = self.doc_id_mapping.get_old_doc_addr(new_doc_id);
@@ -785,6 +1094,7 @@ impl IndexMerger {
 
     fn write_bytes_fast_field(
         &self,
+        segment: &Segment,
         field: Field,
         fast_field_serializer: &mut CompositeFastFieldSerializer,
         doc_id_mapping: &SegmentDocIdMapping,
@@ -792,16 +1102,11 @@ impl IndexMerger {
         let reader_and_field_accessors = self
             .readers
             .iter()
-            .map(|reader| {
-                let bytes_reader = reader.fast_fields().bytes(field).expect(
-                    "Failed to find index for bytes field. This is a bug in tantivy, please \
-                     report.",
-                );
-                (reader, bytes_reader)
-            })
+            .map(|reader| (reader, bytes_fast_field_reader_or_default(reader, field)))
             .collect::<Vec<_>>();
 
-        Self::write_1_n_fast_field_idx_generic(
+        self.write_1_n_fast_field_idx_generic(
+            segment,
             field,
             fast_field_serializer,
             doc_id_mapping,
@@ -901,8 +1206,13 @@ impl IndexMerger {
 
         let mut segment_postings_containing_the_term: Vec<(usize, SegmentPostings)> = vec![];
         let mut doc_id_and_positions = vec![];
+        // With a memory budget, the postings of a sorted index are merged without buffering
+        // them.
+        let merge_postings_without_buffering =
+            !doc_id_mapping.is_trivial() && self.memory_budget.is_some();
 
         while merged_terms.advance() {
+            self.cancellation_token.check()?;
             segment_postings_containing_the_term.clear();
             let term_bytes: &[u8] = merged_terms.key();
 
@@ -944,6 +1254,18 @@ impl IndexMerger {
                 }
             }
 
+            if merge_postings_without_buffering {
+                self.write_postings_in_merged_doc_id_order(
+                    &mut segment_postings_containing_the_term,
+                    &merged_doc_id_map,
+                    &mut field_serializer,
+                    &mut positions_buffer,
+                    &mut delta_computer,
+                )?;
+                field_serializer.close_term()?;
+                continue;
+            }
+
             // We can now serialize this postings, by pushing each document to the
             // postings serializer.
             for (segment_ord, mut segment_postings) in
@@ -997,6 +1319,55 @@ impl IndexMerger {
         Ok(term_ord_mapping_opt)
     }
 
+    // Writes the postings of the current term, k-merging the postings of the segments by their
+    // new doc ids.
+    //
+    // The doc ids of a segment keep their relative order in the merged segment, so the new doc
+    // ids of each segment postings are increasing.
+    fn write_postings_in_merged_doc_id_order(
+        &self,
+        segment_postings_containing_the_term: &mut [(usize, SegmentPostings)],
+        merged_doc_id_map: &[Vec<Option<DocId>>],
+        field_serializer: &mut FieldSerializer,
+        positions_buffer: &mut Vec<u32>,
+        delta_computer: &mut DeltaComputer,
+    ) -> crate::Result<()> {
+        loop {
+            let mut next_doc: Option<(usize, DocId)> = None;
+            for (postings_ord, (segment_ord, segment_postings)) in
+                segment_postings_containing_the_term.iter_mut().enumerate()
+            {
+                let old_to_new_doc_id = &merged_doc_id_map[*segment_ord];
+                let mut doc = segment_postings.doc();
+                // deleted doc are skipped as they do not have a `remapped_doc_id`.
+                while doc != TERMINATED && old_to_new_doc_id[doc as usize].is_none() {
+                    doc = segment_postings.advance();
+                }
+                if doc == TERMINATED {
+                    continue;
+                }
+                let remapped_doc_id = old_to_new_doc_id[doc as usize].unwrap();
+                if next_doc
+                    .map(|(_, next_doc_id)| remapped_doc_id < next_doc_id)
+                    .unwrap_or(true)
+                {
+                    next_doc = Some((postings_ord, remapped_doc_id));
+                }
+            }
+            let (postings_ord, remapped_doc_id) = match next_doc {
+                Some(next_doc) => next_doc,
+                None => return Ok(()),
+            };
+            let segment_postings = &mut segment_postings_containing_the_term[postings_ord].1;
+            let term_freq = segment_postings.term_freq();
+            segment_postings.positions(positions_buffer);
+            self.register_buffered_bytes(positions_buffer.capacity() * mem::size_of::<u32>());
+            let delta_positions = delta_computer.compute_delta(positions_buffer);
+            field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+            segment_postings.advance();
+        }
+    }
+
     fn write_postings(
         &self,
         serializer: &mut InvertedIndexSerializer,
@@ -1045,6 +1416,7 @@ impl IndexMerger {
                 .collect();
 
             for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                self.cancellation_token.check()?;
                 let doc_bytes_it = &mut document_iterators[old_doc_addr.segment_ord as usize];
                 if let Some(doc_bytes_res) = doc_bytes_it.next() {
                     let doc_bytes = doc_bytes_res?;
@@ -1060,6 +1432,7 @@ impl IndexMerger {
         } else {
             debug!("trivial-doc-id-mapping");
             for reader in &self.readers {
+                self.cancellation_token.check()?;
                 let store_reader = reader.get_store_reader(1)?;
                 if reader.has_deletes()
                     // If there is not enough data in the store, we avoid stacking in order to
@@ -1077,8 +1450,13 @@ impl IndexMerger {
                     // take 7 in order to not walk over all checkpoints.
                     || store_reader.block_checkpoints().take(7).count() < 6
                     || store_reader.decompressor() != store_writer.compressor().into()
+                    // Blocks compressed with a dictionary can only be decompressed with their
+                    // own segment dictionary.
+                    || store_reader.has_dictionary()
+                    || store_writer.compressor().dictionary_size().is_some()
                 {
                     for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
+                        self.cancellation_token.check()?;
                         let doc_bytes = doc_bytes_res?;
                         store_writer.store_bytes(&doc_bytes)?;
                     }
@@ -1090,6 +1468,68 @@ impl IndexMerger {
         Ok(())
     }
 
+    /// Rebuilds the vectors of the dense vector fields, and their graph, in the order of the
+    /// merged segment.
+    fn write_dense_vectors(
+        &self,
+        mut dense_vectors_serializer: DenseVectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        for (field, field_entry) in self.schema.fields() {
+            let options = if let FieldType::DenseVector(options) = field_entry.field_type() {
+                options
+            } else {
+                continue;
+            };
+            let dense_vector_readers: Vec<Arc<DenseVectorReader>> = self
+                .readers
+                .iter()
+                .map(|reader| reader.dense_vectors().get_field(field))
+                .collect::<crate::Result<_>>()?;
+            let mut doc_ids: Vec<DocId> = Vec::new();
+            let mut values: Vec<f32> = Vec::new();
+            for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+                let dense_vector_reader = &dense_vector_readers[old_doc_addr.segment_ord as usize];
+                if let Some(vector) = dense_vector_reader.vector(old_doc_addr.doc_id) {
+                    doc_ids.push(new_doc_id as DocId);
+                    values.extend_from_slice(vector);
+                }
+            }
+            dense_vectors_serializer.serialize_field(field, options, &doc_ids, &values)?;
+        }
+        dense_vectors_serializer.close()?;
+        Ok(())
+    }
+
+    /// Copies the term vectors of the fields that store them, in the order of the merged
+    /// segment.
+    fn write_term_vectors(
+        &self,
+        mut term_vectors_serializer: TermVectorsSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        for (field, field_entry) in self.schema.fields() {
+            if !has_term_vectors(field_entry) {
+                continue;
+            }
+            let term_vector_readers: Vec<TermVectorReader> = self
+                .readers
+                .iter()
+                .map(|reader| reader.term_vectors().get_field(field))
+                .collect::<crate::Result<_>>()?;
+            let mut doc_offsets: Vec<u64> = vec![0];
+            let mut data: Vec<u8> = Vec::new();
+            for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                let term_vector_reader = &term_vector_readers[old_doc_addr.segment_ord as usize];
+                data.extend_from_slice(term_vector_reader.term_vector_bytes(old_doc_addr.doc_id));
+                doc_offsets.push(data.len() as u64);
+            }
+            term_vectors_serializer.serialize_field(field, &doc_offsets, &data)?;
+        }
+        term_vectors_serializer.close()?;
+        Ok(())
+    }
+
     /// Writes the merged segment by pushing information
     /// to the `SegmentSerializer`.
     ///
@@ -1108,10 +1548,22 @@ impl IndexMerger {
         } else {
             self.get_doc_id_from_concatenated_data()?
         };
+        self.cancellation_token.check()?;
         debug!("write-fieldnorms");
         if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
             self.write_fieldnorms(fieldnorms_serializer, &doc_id_mapping)?;
         }
+        self.cancellation_token.check()?;
+        debug!("write-dense-vectors");
+        if let Some(dense_vectors_serializer) = serializer.extract_dense_vectors_serializer() {
+            self.write_dense_vectors(dense_vectors_serializer, &doc_id_mapping)?;
+        }
+        self.cancellation_token.check()?;
+        debug!("write-term-vectors");
+        if let Some(term_vectors_serializer) = serializer.extract_term_vectors_serializer() {
+            self.write_term_vectors(term_vectors_serializer, &doc_id_mapping)?;
+        }
+        self.cancellation_token.check()?;
         debug!("write-postings");
         let fieldnorm_data = serializer
             .segment()
@@ -1122,30 +1574,43 @@ impl IndexMerger {
             fieldnorm_readers,
             &doc_id_mapping,
         )?;
+        self.cancellation_token.check()?;
         debug!("write-fastfields");
+        let segment = serializer.segment().clone();
         self.write_fast_fields(
+            &segment,
             serializer.get_fast_field_serializer(),
             term_ord_mappings,
             &doc_id_mapping,
         )?;
+        self.cancellation_token.check()?;
         debug!("write-storagefields");
         self.write_storable_fields(serializer.get_store_writer(), &doc_id_mapping)?;
+        self.cancellation_token.check()?;
         debug!("close-serializer");
         serializer.close()?;
+        debug!("peak-buffered-bytes {}", self.peak_buffered_bytes());
         Ok(self.max_doc)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Write;
+
     use byteorder::{BigEndian, ReadBytesExt};
     use schema::FAST;
 
+    use super::IndexMerger;
     use crate::collector::tests::{
         BytesFastFieldTestCollector, FastFieldTestCollector, TEST_COLLECTOR_WITH_SCORE,
     };
     use crate::collector::{Count, FacetCollector};
-    use crate::core::Index;
+    use crate::core::{Index, SegmentReader};
+    use crate::directory::{Directory, TerminatingWrite};
+    use crate::docset::TERMINATED;
+    use crate::indexer::SegmentSerializer;
+    use crate::postings::Postings;
     use crate::query::{AllQuery, BooleanQuery, Scorer, TermQuery};
     use crate::schema::{
         Cardinality, Document, Facet, FacetOptions, IndexRecordOption, NumericOptions, Term,
@@ -1154,7 +1619,7 @@ mod tests {
     use crate::time::OffsetDateTime;
     use crate::{
         assert_nearly_equals, schema, DateTime, DocAddress, DocSet, IndexSettings,
-        IndexSortByField, IndexWriter, Order, Searcher, SegmentId,
+        IndexSortByField, IndexWriter, Order, Searcher, SegmentComponent, SegmentId,
     };
 
     #[test]
@@ -2112,6 +2577,157 @@ mod tests {
         Ok(())
     }
 
+    fn merge_all_segments(
+        index: &Index,
+        memory_budget_opt: Option<usize>,
+    ) -> crate::Result<(SegmentReader, usize)> {
+        let segments = index.searchable_segments()?;
+        let mut merger = IndexMerger::open(index.schema(), index.settings().clone(), &segments)?;
+        if let Some(memory_budget) = memory_budget_opt {
+            merger.set_memory_budget(memory_budget);
+        }
+        let merged_segment = index.new_segment();
+        let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone(), true)?;
+        let num_docs = merger.write(segment_serializer)?;
+        let temp_path = merged_segment.relative_path(SegmentComponent::TempFastFields);
+        assert!(!index.directory().exists(&temp_path)?);
+        let segment_reader = SegmentReader::open(&merged_segment.with_max_doc(num_docs))?;
+        Ok((segment_reader, merger.peak_buffered_bytes()))
+    }
+
+    #[test]
+    fn test_merge_with_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let int_field = schema_builder.add_u64_field("intval", FAST | INDEXED);
+        let multi_field = schema_builder.add_u64_field(
+            "multi",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let bytes_field = schema_builder.add_bytes_field("bytes", FAST);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "intval".to_string(),
+                    order: Order::Desc,
+                }),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for segment_ord in 0..3u64 {
+                for i in 0..2_000u64 {
+                    // The segments overlap on the sort field, so that the doc ids are k-merged.
+                    let val = i * 3 + segment_ord;
+                    index_writer.add_document(doc!(
+                        int_field => val,
+                        multi_field => val,
+                        multi_field => val % 13,
+                        facet_field => Facet::from(&format!("/cat/{}", val % 17)),
+                        bytes_field => val.to_le_bytes().to_vec(),
+                        text_field => format!("a{} b{} common a{}", val % 5, val % 7, val % 5),
+                    ))?;
+                }
+                index_writer.commit()?;
+            }
+            index_writer.delete_term(Term::from_field_u64(int_field, 9));
+            index_writer.commit()?;
+        }
+        let memory_budget = 16_000;
+        let (reader, peak_buffered_bytes) = merge_all_segments(&index, None)?;
+        assert!(peak_buffered_bytes > memory_budget);
+        let (budget_reader, budget_peak_buffered_bytes) =
+            merge_all_segments(&index, Some(memory_budget))?;
+        assert!(budget_peak_buffered_bytes <= memory_budget);
+
+        assert_eq!(budget_reader.num_docs(), 5_999);
+        assert_eq!(reader.num_docs(), budget_reader.num_docs());
+        let int_values = reader.fast_fields().u64(int_field)?;
+        let budget_int_values = budget_reader.fast_fields().u64(int_field)?;
+        let multi_values = reader.fast_fields().u64s(multi_field)?;
+        let budget_multi_values = budget_reader.fast_fields().u64s(multi_field)?;
+        let facet_ords = reader.fast_fields().u64s_lenient(facet_field)?;
+        let budget_facet_ords = budget_reader.fast_fields().u64s_lenient(facet_field)?;
+        let bytes_values = reader.fast_fields().bytes(bytes_field)?;
+        let budget_bytes_values = budget_reader.fast_fields().bytes(bytes_field)?;
+        let (mut vals, mut budget_vals) = (Vec::new(), Vec::new());
+        for doc in 0..reader.max_doc() {
+            assert_eq!(
+                int_values.get_val(doc as u64),
+                budget_int_values.get_val(doc as u64)
+            );
+            multi_values.get_vals(doc, &mut vals);
+            budget_multi_values.get_vals(doc, &mut budget_vals);
+            assert_eq!(vals, budget_vals);
+            facet_ords.get_vals(doc, &mut vals);
+            budget_facet_ords.get_vals(doc, &mut budget_vals);
+            assert_eq!(vals, budget_vals);
+            assert_eq!(
+                bytes_values.get_bytes(doc),
+                budget_bytes_values.get_bytes(doc)
+            );
+        }
+
+        let inverted_index = reader.inverted_index(text_field)?;
+        let budget_inverted_index = budget_reader.inverted_index(text_field)?;
+        let (mut positions, mut budget_positions) = (Vec::new(), Vec::new());
+        for term_text in ["a3", "b6", "common"] {
+            let term = Term::from_field_text(text_field, term_text);
+            let mut postings = inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            let mut budget_postings = budget_inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            assert_eq!(postings.doc_freq(), budget_postings.doc_freq());
+            while postings.doc() != TERMINATED {
+                assert_eq!(postings.doc(), budget_postings.doc());
+                postings.positions(&mut positions);
+                budget_postings.positions(&mut budget_positions);
+                assert_eq!(positions, budget_positions);
+                postings.advance();
+                budget_postings.advance();
+            }
+            assert_eq!(budget_postings.doc(), TERMINATED);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_temp_fast_fields_file_is_deleted() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let int_field = schema_builder.add_u64_field("intval", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(int_field => 1u64))?;
+        index_writer.commit()?;
+
+        // A spilled column that is never finished still deletes its file.
+        let segments = index.searchable_segments()?;
+        let mut merger = IndexMerger::open(index.schema(), index.settings().clone(), &segments)?;
+        merger.set_memory_budget(0);
+        let merged_segment = index.new_segment();
+        let temp_path = merged_segment.relative_path(SegmentComponent::TempFastFields);
+        let mut column_buffer = merger.new_column_buffer(&merged_segment, 10)?;
+        column_buffer.push(3)?;
+        assert!(index.directory().exists(&temp_path)?);
+        drop(column_buffer);
+        assert!(!index.directory().exists(&temp_path)?);
+
+        // A leftover file of a written segment is removed by the garbage collection.
+        let segment_meta = index.searchable_segment_metas()?[0].clone();
+        let leftover_path = segment_meta.relative_path(SegmentComponent::TempFastFields);
+        let mut write = index.directory().open_write(&leftover_path)?;
+        write.write_all(b"leftover")?;
+        write.terminate()?;
+        index_writer.garbage_collect_files().wait()?;
+        assert!(!index.directory().exists(&leftover_path)?);
+        Ok(())
+    }
+
     #[test]
     fn test_max_doc() {
         // this is the first time I write a unit test for a constant.
@@ -3,8 +3,11 @@ pub mod delete_queue;
 pub mod demuxer;
 pub mod doc_id_mapping;
 mod doc_opstamp_mapping;
+mod document_validation;
 pub mod index_writer;
+mod index_writer_events;
 mod index_writer_status;
+mod json_lines;
 mod json_term_writer;
 mod log_merge_policy;
 mod merge_operation;
@@ -13,6 +16,7 @@ pub mod merger;
 mod merger_sorted_index_test;
 pub mod operation;
 pub mod prepared_commit;
+mod schema_migration;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -24,18 +28,27 @@ mod stamper;
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
+pub(crate) use self::document_validation::validate_document;
+pub use self::document_validation::RejectedDocument;
 pub use self::index_writer::IndexWriter;
+pub use self::index_writer_events::{IndexWriterEvent, IndexWriterEventListener};
+pub use self::json_lines::{JsonLineError, JsonLineErrorKind, JsonLinesReport};
 pub(crate) use self::json_term_writer::{
-    convert_to_fast_value_and_get_term, set_string_and_get_terms, JsonTermWriter,
+    convert_to_fast_value_and_get_term, set_fastvalue_and_get_term, set_string_and_get_terms,
+    JsonTermWriter,
 };
 pub use self::log_merge_policy::LogMergePolicy;
-pub use self::merge_operation::MergeOperation;
+pub use self::merge_operation::{MergeCancellationToken, MergeOperation};
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
 pub use self::prepared_commit::PreparedCommit;
+pub use self::schema_migration::{add_fast_field, rename_field};
 pub use self::segment_entry::SegmentEntry;
 pub use self::segment_manager::SegmentManager;
 pub use self::segment_serializer::SegmentSerializer;
-pub use self::segment_updater::{merge_filtered_segments, merge_indices};
+pub use self::segment_updater::{
+    merge_deduplicated_segments, merge_filtered_segments, merge_indices,
+    merge_indices_with_compatible_schemas,
+};
 pub use self::segment_writer::SegmentWriter;
 use crate::indexer::operation::AddOperation;
 
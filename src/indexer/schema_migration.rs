@@ -0,0 +1,540 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use fastfield_codecs::MonotonicallyMappableToU64;
+
+use crate::core::META_FILEPATH;
+use crate::directory::{CompositeFile, Directory, FileSlice, TerminatingWrite, INDEX_WRITER_LOCK};
+use crate::fastfield::{CompositeFastFieldSerializer, FastFieldsWriter};
+use crate::indexer::segment_updater::save_metas;
+use crate::query::validate_field;
+use crate::schema::{
+    is_valid_field_name, Cardinality, Document, Field, FieldEntry, FieldType, IndexRecordOption,
+    Schema, Value,
+};
+use crate::{
+    DateTime, DocSet, Index, IndexMeta, Segment, SegmentComponent, SegmentMeta, SegmentReader,
+    TantivyError, TERMINATED,
+};
+
+/// Turns an existing field of an index into a fast field, without reindexing the index.
+///
+/// For each segment, the fast field values are read from the doc store if the field is
+/// stored. Otherwise, they are recomputed by uninverting the terms of the indexed field.
+/// In that case:
+/// - the values of a multivalued fast field are sorted and deduplicated, as the inverted index does
+///   not record their order,
+/// - dates are truncated to the second, as they are indexed with this precision.
+///
+/// Only numeric (`u64`, `i64`, `f64` and `bool`) and date fields are supported.
+///
+/// This utility is meant to be run offline. It acquires the index writer lock, and fails with
+/// `TantivyError::LockFailure` if an `IndexWriter` is working on the index.
+///
+/// Segment files are never modified. Each segment is copied into a new segment, that includes
+/// the new fast field, and the new segments and schema are published together by writing
+/// `meta.json`. If the migration fails before that, the index is left unchanged. The files of
+/// the original segments are then garbage collected.
+///
+/// Returns the migrated index.
+pub fn add_fast_field(
+    index: &Index,
+    field: Field,
+    cardinality: Cardinality,
+) -> crate::Result<Index> {
+    let _directory_lock = index
+        .directory()
+        .acquire_lock(&INDEX_WRITER_LOCK)
+        .map_err(|err| {
+            TantivyError::LockFailure(
+                err,
+                Some(
+                    "Failed to acquire index lock. The index must not be opened by an \
+                     `IndexWriter` while a fast field is added."
+                        .to_string(),
+                ),
+            )
+        })?;
+    let IndexMeta {
+        index_settings,
+        segments,
+        schema,
+        opstamp,
+        payload,
+    } = index.load_metas()?;
+    let migrated_schema = add_fast_field_to_schema(&schema, field, cardinality)?;
+    let migrated_index = index.with_schema(migrated_schema.clone());
+    let migrated_segments = segments
+        .into_iter()
+        .map(|segment_meta| {
+            add_fast_field_to_segment(&index.segment(segment_meta), &migrated_index, field)
+        })
+        .collect::<crate::Result<Vec<SegmentMeta>>>()?;
+    let migrated_metas = IndexMeta {
+        index_settings,
+        segments: migrated_segments,
+        schema: migrated_schema,
+        opstamp,
+        payload,
+    };
+    save_metas(&migrated_metas, migrated_index.directory())?;
+    // The segments still in use, for instance by the searchers of `index`, are kept.
+    let mut living_files: HashSet<_> = migrated_index
+        .list_all_segment_metas()
+        .into_iter()
+        .flat_map(|segment_meta| segment_meta.list_files())
+        .collect();
+    living_files.insert(META_FILEPATH.to_path_buf());
+    migrated_index
+        .directory()
+        .clone()
+        .garbage_collect(|| living_files)?;
+    Ok(migrated_index)
+}
+
+/// Renames a field of an index, without reindexing the index.
+///
+/// The segments refer to the fields by their `Field` id only, so their files are left
+/// untouched: only the schema stored in `meta.json` is rewritten. If the index is sorted by
+/// the field, its `sort_by_field` setting is updated as well.
+///
+/// Returns `TantivyError::SchemaError` if `field` does not exist in the schema, if `new_name` is
+/// not a valid field name, or if it is the name of another field of the schema.
+///
+/// This utility is meant to be run offline. It acquires the index writer lock, and fails with
+/// `TantivyError::LockFailure` if an `IndexWriter` is working on the index.
+///
+/// Returns the migrated index.
+pub fn rename_field(index: &Index, field: Field, new_name: &str) -> crate::Result<Index> {
+    let _directory_lock = index
+        .directory()
+        .acquire_lock(&INDEX_WRITER_LOCK)
+        .map_err(|err| {
+            TantivyError::LockFailure(
+                err,
+                Some(
+                    "Failed to acquire index lock. The index must not be opened by an \
+                     `IndexWriter` while a field is renamed."
+                        .to_string(),
+                ),
+            )
+        })?;
+    let IndexMeta {
+        mut index_settings,
+        segments,
+        schema,
+        opstamp,
+        payload,
+    } = index.load_metas()?;
+    let old_name = validate_field(&schema, field)?.name().to_string();
+    if !is_valid_field_name(new_name) {
+        return Err(TantivyError::SchemaError(format!(
+            "{:?} is not a valid field name",
+            new_name
+        )));
+    }
+    if let Some(other_field) = schema.get_field(new_name) {
+        if other_field != field {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot rename field {:?}, the schema already has a field named {:?}",
+                old_name, new_name
+            )));
+        }
+    }
+    if let Some(sort_by_field) = index_settings.sort_by_field.as_mut() {
+        if sort_by_field.field == old_name {
+            sort_by_field.field = new_name.to_string();
+        }
+    }
+    let mut schema_builder = Schema::builder();
+    for (schema_field, schema_field_entry) in schema.fields() {
+        if schema_field == field {
+            schema_builder.add_field(FieldEntry::new(
+                new_name.to_string(),
+                schema_field_entry.field_type().clone(),
+            ));
+        } else {
+            schema_builder.add_field(schema_field_entry.clone());
+        }
+    }
+    let migrated_schema = schema_builder.build();
+    let migrated_metas = IndexMeta {
+        index_settings: index_settings.clone(),
+        segments,
+        schema: migrated_schema.clone(),
+        opstamp,
+        payload,
+    };
+    save_metas(&migrated_metas, index.directory())?;
+    Ok(index
+        .with_schema(migrated_schema)
+        .with_settings(index_settings))
+}
+
+fn add_fast_field_to_schema(
+    schema: &Schema,
+    field: Field,
+    cardinality: Cardinality,
+) -> crate::Result<Schema> {
+    let field_entry = schema.get_field_entry(field);
+    if field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is already a fast field",
+            field_entry.name()
+        )));
+    }
+    if !field_entry.is_stored() && !field_entry.is_indexed() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is neither stored nor indexed, its fast field cannot be computed",
+            field_entry.name()
+        )));
+    }
+    let fast_field_type = match field_entry.field_type().clone() {
+        FieldType::U64(options) => FieldType::U64(options.set_fast(cardinality)),
+        FieldType::I64(options) => FieldType::I64(options.set_fast(cardinality)),
+        FieldType::F64(options) => FieldType::F64(options.set_fast(cardinality)),
+        FieldType::Bool(options) => FieldType::Bool(options.set_fast(cardinality)),
+        FieldType::Date(options) => FieldType::Date(options.set_fast(cardinality)),
+        _ => {
+            return Err(TantivyError::SchemaError(format!(
+                "Adding a fast field is only supported for numeric and date fields, not for field \
+                 {:?}",
+                field_entry.name()
+            )));
+        }
+    };
+    let mut schema_builder = Schema::builder();
+    for (schema_field, schema_field_entry) in schema.fields() {
+        if schema_field == field {
+            schema_builder.add_field(FieldEntry::new(
+                schema_field_entry.name().to_string(),
+                fast_field_type.clone(),
+            ));
+        } else {
+            schema_builder.add_field(schema_field_entry.clone());
+        }
+    }
+    Ok(schema_builder.build())
+}
+
+/// Copies a segment into a new segment of the migrated index, adding the fast field to its
+/// fast fields file.
+fn add_fast_field_to_segment(
+    segment: &Segment,
+    migrated_index: &Index,
+    field: Field,
+) -> crate::Result<SegmentMeta> {
+    let segment_reader = SegmentReader::open(segment)?;
+    let mut fast_fields_writer =
+        FastFieldsWriter::for_fields(&migrated_index.schema(), std::iter::once(field));
+    let field_entry = segment.schema().get_field_entry(field).clone();
+    if field_entry.is_stored() {
+        let store_reader = segment_reader.get_store_reader(1)?;
+        for doc in store_reader.iter(None) {
+            fast_fields_writer.add_document(&doc?);
+        }
+    } else {
+        for values in uninvert_field(&segment_reader, field)? {
+            let mut doc = Document::default();
+            for value in values {
+                doc.add_field_value(field, value_from_u64(field_entry.field_type(), value));
+            }
+            fast_fields_writer.add_document(&doc);
+        }
+    }
+
+    let segment_meta = segment.meta();
+    let mut migrated_segment = migrated_index
+        .new_segment()
+        .with_max_doc(segment_meta.max_doc());
+    if let Some(delete_opstamp) = segment_meta.delete_opstamp() {
+        migrated_segment =
+            migrated_segment.with_delete_meta(segment_meta.num_deleted_docs(), delete_opstamp);
+    }
+    let fast_fields_file = CompositeFile::open(&segment.open_read(SegmentComponent::FastFields)?)?;
+    let mut fast_field_serializer = CompositeFastFieldSerializer::from_write(
+        migrated_segment.open_write(SegmentComponent::FastFields)?,
+    )?;
+    fast_field_serializer.copy_fast_fields(&fast_fields_file)?;
+    fast_fields_writer.serialize(&mut fast_field_serializer, &HashMap::new(), None)?;
+    fast_field_serializer.close()?;
+    for &component in SegmentComponent::iterator() {
+        match component {
+            SegmentComponent::FastFields
+            | SegmentComponent::TempStore
+            | SegmentComponent::TempFastFields
+            | SegmentComponent::TempTerms => {}
+            SegmentComponent::Delete if !segment_meta.has_deletes() => {}
+            // Segments written before dense vector fields, or term vectors, were supported
+            // have no vectors file, or no term vectors file.
+            SegmentComponent::Positions
+            | SegmentComponent::Vectors
+            | SegmentComponent::TermVectors => {
+                if let Some(file_slice) = segment.open_read_if_exists(component)? {
+                    copy_component(&file_slice, &mut migrated_segment, component)?;
+                }
+            }
+            _ => {
+                let file_slice = segment.open_read(component)?;
+                copy_component(&file_slice, &mut migrated_segment, component)?;
+            }
+        }
+    }
+    Ok(migrated_segment.meta().clone())
+}
+
+fn copy_component(
+    file_slice: &FileSlice,
+    segment: &mut Segment,
+    component: SegmentComponent,
+) -> crate::Result<()> {
+    let data = file_slice.read_bytes()?;
+    let mut write = segment.open_write(component)?;
+    write.write_all(data.as_slice())?;
+    write.terminate()?;
+    Ok(())
+}
+
+/// Returns the `u64` representation of the values of each document of the segment, as
+/// found in the inverted index.
+fn uninvert_field(segment_reader: &SegmentReader, field: Field) -> crate::Result<Vec<Vec<u64>>> {
+    let mut values = vec![Vec::new(); segment_reader.max_doc() as usize];
+    let inverted_index = segment_reader.inverted_index(field)?;
+    let mut term_stream = inverted_index.terms().stream()?;
+    while term_stream.advance() {
+        let mut value_bytes = [0u8; 8];
+        value_bytes.copy_from_slice(term_stream.key());
+        let value = u64::from_be_bytes(value_bytes);
+        let mut postings = inverted_index
+            .read_postings_from_terminfo(term_stream.value(), IndexRecordOption::Basic)?;
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            values[doc as usize].push(value);
+            doc = postings.advance();
+        }
+    }
+    Ok(values)
+}
+
+fn value_from_u64(field_type: &FieldType, value: u64) -> Value {
+    match field_type {
+        FieldType::I64(_) => Value::I64(i64::from_u64(value)),
+        FieldType::F64(_) => Value::F64(f64::from_u64(value)),
+        FieldType::Bool(_) => Value::Bool(bool::from_u64(value)),
+        FieldType::Date(_) => Value::Date(DateTime::from_u64(value)),
+        _ => Value::U64(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_fast_field, rename_field};
+    use crate::collector::{Count, TopDocs};
+    use crate::directory::{Directory, RamDirectory};
+    use crate::query::{QueryParser, TermQuery};
+    use crate::schema::{
+        Cardinality, DateOptions, Field, IndexRecordOption, NumericOptions, Schema, INDEXED,
+        STORED, TEXT,
+    };
+    use crate::{DateTime, DocAddress, Index, IndexSettings, TantivyError, Term};
+
+    #[test]
+    fn test_add_fast_field_from_stored_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u64_field("price", STORED | INDEXED);
+        let schema = schema_builder.build();
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => "book", price_field => 30u64))?;
+            index_writer.add_document(doc!(text_field => "book", price_field => 10u64))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(text_field => "book", price_field => 20u64))?;
+            index_writer.add_document(doc!(text_field => "pen", price_field => 1u64))?;
+            index_writer.add_document(doc!(text_field => "book"))?;
+            index_writer.delete_term(Term::from_field_u64(price_field, 10u64));
+            index_writer.commit()?;
+        }
+        let original_files: Vec<_> = index
+            .searchable_segment_metas()?
+            .iter()
+            .flat_map(|segment_meta| segment_meta.list_files())
+            .collect();
+        let migrated_index = add_fast_field(&index, price_field, Cardinality::SingleValue)?;
+        // The files of the original segments are garbage collected.
+        for path in original_files {
+            assert!(!directory.exists(&path)?);
+        }
+        assert!(migrated_index
+            .schema()
+            .get_field_entry(price_field)
+            .is_fast());
+        // The migration is persisted.
+        let reopened_index = Index::open(directory)?;
+        assert_eq!(reopened_index.schema(), migrated_index.schema());
+
+        let searcher = reopened_index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(searcher.num_docs(), 4);
+        let mut prices: Vec<Vec<u64>> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| {
+                let price_reader = segment_reader.fast_fields().u64(price_field).unwrap();
+                (0..segment_reader.max_doc())
+                    .map(|doc| price_reader.get_val(doc as u64))
+                    .collect()
+            })
+            .collect();
+        prices.sort();
+        // Deleted documents keep their value, documents without values get the default value.
+        assert_eq!(prices, vec![vec![20, 1, 0], vec![30, 10]]);
+        let book_query = TermQuery::new(
+            Term::from_field_text(text_field, "book"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(
+            &book_query,
+            &TopDocs::with_limit(2).order_by_u64_field(price_field),
+        )?;
+        let top_prices: Vec<u64> = top_docs.iter().map(|(price, _)| *price).collect();
+        assert_eq!(top_prices, vec![30, 20]);
+        let top_doc_address: DocAddress = top_docs[0].1;
+        let top_doc = searcher.doc(top_doc_address)?;
+        assert_eq!(top_doc.get_first(price_field).unwrap().as_u64(), Some(30));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_fast_field_from_indexed_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tags_field = schema_builder.add_i64_field("tags", INDEXED);
+        let date_field =
+            schema_builder.add_date_field("date", DateOptions::default().set_indexed());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                tags_field => 3i64,
+                tags_field => -1i64,
+                tags_field => 3i64,
+                date_field => DateTime::from_timestamp_micros(1_500_000),
+            ))?;
+            index_writer.add_document(doc!(tags_field => 7i64))?;
+            index_writer.commit()?;
+        }
+        let index = add_fast_field(&index, tags_field, Cardinality::MultiValues)?;
+        let index = add_fast_field(&index, date_field, Cardinality::SingleValue)?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let tags_reader = segment_reader.fast_fields().i64s(tags_field)?;
+        let mut tags = Vec::new();
+        tags_reader.get_vals(0, &mut tags);
+        assert_eq!(tags, vec![-1, 3]);
+        tags_reader.get_vals(1, &mut tags);
+        assert_eq!(tags, vec![7]);
+        let date_reader = segment_reader.fast_fields().date(date_field)?;
+        assert_eq!(date_reader.get_val(0), DateTime::from_timestamp_secs(1));
+        assert_eq!(date_reader.get_val(1), DateTime::from_timestamp_secs(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_fast_field_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let fast_field = schema_builder.add_u64_field(
+            "fast",
+            NumericOptions::default().set_fast(Cardinality::SingleValue),
+        );
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(matches!(
+            add_fast_field(&index, text_field, Cardinality::SingleValue),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            add_fast_field(&index, fast_field, Cardinality::SingleValue),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            add_fast_field(&index, indexed_field, Cardinality::SingleValue),
+            Err(TantivyError::LockFailure(..))
+        ));
+        drop(index_writer);
+        add_fast_field(&index, indexed_field, Cardinality::SingleValue)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title_field => "moby dick", body_field => "whale"))?;
+            index_writer.add_document(doc!(title_field => "ulysses", body_field => "dublin"))?;
+            index_writer.commit()?;
+        }
+        let migrated_index = rename_field(&index, title_field, "name")?;
+        assert_eq!(migrated_index.schema().get_field("name"), Some(title_field));
+        assert_eq!(migrated_index.schema().get_field("title"), None);
+        let reopened_index = Index::open(directory)?;
+        let schema = reopened_index.schema();
+        assert_eq!(schema.get_field("name"), Some(title_field));
+        assert_eq!(schema.get_field("title"), None);
+        let searcher = reopened_index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&reopened_index, vec![body_field]);
+        let query = query_parser.parse_query("name:dick")?;
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        let doc = searcher.doc(top_docs[0].1)?;
+        assert_eq!(
+            doc.get_first(title_field).and_then(|value| value.as_text()),
+            Some("moby dick")
+        );
+        assert!(query_parser.parse_query("title:dick").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_field_rejects_collisions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(matches!(
+            rename_field(&index, title_field, "body"),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            rename_field(&index, title_field, "-title"),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert_eq!(
+            index.load_metas()?.schema.get_field("title"),
+            Some(title_field)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_field_rejects_unknown_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(matches!(
+            rename_field(&index, Field::from_field_id(1), "body"),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::hash_set::HashSet;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Formatter};
 use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
@@ -177,6 +178,29 @@ impl SegmentManager {
         Ok(segment_entries)
     }
 
+    /// Replaces the user metadata of a segment, whether it is committed or not.
+    ///
+    /// Returns false if the segment is unknown.
+    pub(crate) fn set_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: BTreeMap<String, String>,
+    ) -> bool {
+        let mut registers_lock = self.write();
+        let registers = &mut *registers_lock;
+        let segment_entry_opt = registers
+            .uncommitted
+            .get_mut(&segment_id)
+            .or_else(|| registers.committed.get_mut(&segment_id));
+        if let Some(segment_entry) = segment_entry_opt {
+            let segment_meta = segment_entry.meta().clone();
+            segment_entry.set_meta(segment_meta.with_user_metadata(user_metadata));
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn add_segment(&self, segment_entry: SegmentEntry) {
         let mut registers_lock = self.write();
         registers_lock.uncommitted.add_segment_entry(segment_entry);
@@ -89,6 +89,10 @@ impl SegmentRegister {
         self.segment_states.get(segment_id).cloned()
     }
 
+    pub fn get_mut(&mut self, segment_id: &SegmentId) -> Option<&mut SegmentEntry> {
+        self.segment_states.get_mut(segment_id)
+    }
+
     pub fn new(segment_metas: Vec<SegmentMeta>, delete_cursor: &DeleteCursor) -> SegmentRegister {
         let mut segment_states = HashMap::new();
         for segment_meta in segment_metas {
@@ -3,6 +3,8 @@ use crate::fastfield::CompositeFastFieldSerializer;
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::postings::InvertedIndexSerializer;
 use crate::store::StoreWriter;
+use crate::termvector::TermVectorsSerializer;
+use crate::vector::DenseVectorsSerializer;
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -11,6 +13,8 @@ pub struct SegmentSerializer {
     pub(crate) store_writer: StoreWriter,
     fast_field_serializer: CompositeFastFieldSerializer,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
+    dense_vectors_serializer: Option<DenseVectorsSerializer>,
+    term_vectors_serializer: Option<TermVectorsSerializer>,
     postings_serializer: InvertedIndexSerializer,
 }
 
@@ -37,6 +41,12 @@ impl SegmentSerializer {
         let fieldnorms_write = segment.open_write(SegmentComponent::FieldNorms)?;
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
+        let dense_vectors_write = segment.open_write(SegmentComponent::Vectors)?;
+        let dense_vectors_serializer = DenseVectorsSerializer::from_write(dense_vectors_write)?;
+
+        let term_vectors_write = segment.open_write(SegmentComponent::TermVectors)?;
+        let term_vectors_serializer = TermVectorsSerializer::from_write(term_vectors_write)?;
+
         let postings_serializer = InvertedIndexSerializer::open(&mut segment)?;
         let settings = segment.index().settings();
         let store_writer = StoreWriter::new(
@@ -50,6 +60,8 @@ impl SegmentSerializer {
             store_writer,
             fast_field_serializer,
             fieldnorms_serializer: Some(fieldnorms_serializer),
+            dense_vectors_serializer: Some(dense_vectors_serializer),
+            term_vectors_serializer: Some(term_vectors_serializer),
             postings_serializer,
         })
     }
@@ -84,6 +96,20 @@ impl SegmentSerializer {
         self.fieldnorms_serializer.take()
     }
 
+    /// Extract the dense vectors serializer.
+    ///
+    /// Note the dense vectors serializer can only be extracted once.
+    pub fn extract_dense_vectors_serializer(&mut self) -> Option<DenseVectorsSerializer> {
+        self.dense_vectors_serializer.take()
+    }
+
+    /// Extract the term vectors serializer.
+    ///
+    /// Note the term vectors serializer can only be extracted once.
+    pub fn extract_term_vectors_serializer(&mut self) -> Option<TermVectorsSerializer> {
+        self.term_vectors_serializer.take()
+    }
+
     /// Accessor to the `StoreWriter`.
     pub fn get_store_writer(&mut self) -> &mut StoreWriter {
         &mut self.store_writer
@@ -94,6 +120,12 @@ impl SegmentSerializer {
         if let Some(fieldnorms_serializer) = self.extract_fieldnorms_serializer() {
             fieldnorms_serializer.close()?;
         }
+        if let Some(dense_vectors_serializer) = self.extract_dense_vectors_serializer() {
+            dense_vectors_serializer.close()?;
+        }
+        if let Some(term_vectors_serializer) = self.extract_term_vectors_serializer() {
+            term_vectors_serializer.close()?;
+        }
         self.fast_field_serializer.close()?;
         self.postings_serializer.close()?;
         self.store_writer.close()?;
@@ -1,33 +1,76 @@
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
+use common::BitSet;
 use fail::fail_point;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use super::segment_manager::SegmentManager;
 use crate::core::{
-    Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta, META_FILEPATH,
+    Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta, SegmentReader, META_FILEPATH,
 };
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
-use crate::fastfield::AliveBitSet;
+use crate::fastfield::{type_and_cardinality, AliveBitSet};
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::index_writer::advance_deletes;
+use crate::indexer::index_writer_events::{
+    EventSender, IndexWriterEvent, IndexWriterEventListener,
+};
 use crate::indexer::merge_operation::MergeOperationInventory;
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
 use crate::indexer::{
-    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
-    SegmentSerializer,
+    DefaultMergePolicy, MergeCancellationToken, MergeCandidate, MergeOperation, MergePolicy,
+    SegmentEntry, SegmentSerializer,
 };
-use crate::{FutureResult, Opstamp};
+use crate::schema::{Cardinality, Field, Schema};
+use crate::{DocId, FutureResult, Opstamp};
+
+/// Number of threads running the merges, unless configured otherwise.
+pub(crate) const DEFAULT_NUM_MERGE_THREADS: usize = 4;
 
-const NUM_MERGE_THREADS: usize = 4;
+/// Callback run by each merge thread when it starts, with the index of the thread.
+pub(crate) type MergeThreadStartHandler = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// The thread pool running the merges, along with the settings it was built with.
+struct MergeThreadPool {
+    num_threads: usize,
+    start_handler_opt: Option<MergeThreadStartHandler>,
+    pool: ThreadPool,
+}
+
+impl MergeThreadPool {
+    fn build(
+        num_threads: usize,
+        start_handler_opt: Option<MergeThreadStartHandler>,
+    ) -> crate::Result<MergeThreadPool> {
+        if num_threads == 0 {
+            return Err(crate::TantivyError::InvalidArgument(
+                "The number of merge threads must be at least 1.".to_string(),
+            ));
+        }
+        let mut pool_builder = ThreadPoolBuilder::new()
+            .thread_name(|i| format!("merge_thread_{i}"))
+            .num_threads(num_threads);
+        if let Some(start_handler) = start_handler_opt.clone() {
+            pool_builder = pool_builder.start_handler(move |i| start_handler(i));
+        }
+        let pool = pool_builder.build().map_err(|_| {
+            crate::TantivyError::SystemError("Failed to spawn segment merging thread".to_string())
+        })?;
+        Ok(MergeThreadPool {
+            num_threads,
+            start_handler_opt,
+            pool,
+        })
+    }
+}
 
 /// Save the index meta file.
 /// This operation is atomic:
@@ -91,6 +134,9 @@ fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    memory_budget_opt: Option<usize>,
+    user_metadata: BTreeMap<String, String>,
+    cancellation_token: &MergeCancellationToken,
 ) -> crate::Result<Option<SegmentEntry>> {
     let num_docs = segment_entries
         .iter()
@@ -117,8 +163,12 @@ fn merge(
         .collect();
 
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger =
+    let mut merger: IndexMerger =
         IndexMerger::open(index.schema(), index.settings().clone(), &segments[..])?;
+    if let Some(memory_budget) = memory_budget_opt {
+        merger.set_memory_budget(memory_budget);
+    }
+    merger.set_cancellation_token(cancellation_token.clone());
 
     // ... we just serialize this index merger in our new segment to merge the segments.
     let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone(), true)?;
@@ -127,7 +177,9 @@ fn merge(
 
     let merged_segment_id = merged_segment.id();
 
-    let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    let segment_meta = index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_user_metadata(user_metadata);
     Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
 }
 
@@ -176,6 +228,77 @@ pub fn merge_indices<T: Into<Box<dyn Directory>>>(
     merge_filtered_segments(&segments, target_settings, non_filter, output_directory)
 }
 
+/// Advanced: Merges the segments of indices with compatible schemas in a new index.
+///
+/// Schemas are compatible if they only differ by fields added at the end of the schema: the
+/// fields of each schema need to be the first fields of the schema with the most fields,
+/// which becomes the schema of the merged index. This is typically the case of indices
+/// built at different times, from a schema that only ever gained new fields.
+///
+/// The documents of an index lacking a field are merged as if they had no value for it:
+/// single-valued fast fields get their default value, and the documents have neither terms,
+/// fast field values nor stored values for this field.
+///
+/// Returns `TantivyError::InvalidArgument` if the indices list is empty, if their index
+/// settings differ or if their schemas are not compatible.
+///
+/// `output_directory`: is assumed to be empty.
+///
+/// # Warning
+/// This function does NOT check or take the `IndexWriter` is running. It is not
+/// meant to work if you have an IndexWriter running for the origin indices, or
+/// the destination Index.
+pub fn merge_indices_with_compatible_schemas<T: Into<Box<dyn Directory>>>(
+    indices: &[Index],
+    output_directory: T,
+) -> crate::Result<Index> {
+    let target_schema = indices
+        .iter()
+        .map(Index::schema)
+        .max_by_key(Schema::num_fields)
+        .ok_or_else(|| {
+            crate::TantivyError::InvalidArgument("No indices given to merge".to_string())
+        })?;
+    for index in indices {
+        let schema = index.schema();
+        let is_compatible = schema
+            .fields()
+            .zip(target_schema.fields())
+            .all(|((_, field_entry), (_, target_field_entry))| field_entry == target_field_entry);
+        if !is_compatible {
+            return Err(crate::TantivyError::InvalidArgument(
+                "Attempt to merge indices with incompatible schemas. Schemas may only differ by \
+                 fields added at the end of the schema."
+                    .to_string(),
+            ));
+        }
+    }
+
+    let target_settings = indices[0].settings().clone();
+    if indices
+        .iter()
+        .skip(1)
+        .any(|index| index.settings() != &target_settings)
+    {
+        return Err(crate::TantivyError::InvalidArgument(
+            "Attempt to merge indices with different index_settings".to_string(),
+        ));
+    }
+
+    // The segments are read with the target schema. The fields they lack have no data.
+    let mut segments: Vec<Segment> = Vec::new();
+    for index in indices {
+        segments.extend(
+            index
+                .with_schema(target_schema.clone())
+                .searchable_segments()?,
+        );
+    }
+
+    let non_filter = segments.iter().map(|_| None).collect::<Vec<_>>();
+    merge_filtered_segments(&segments, target_settings, non_filter, output_directory)
+}
+
 /// Advanced: Merges a list of segments from different indices in a new index.
 /// Additional you can provide a delete bitset for each segment to ignore doc_ids.
 ///
@@ -259,6 +382,77 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
     Ok(merged_index)
 }
 
+/// Advanced: Merges a list of segments in a new index, keeping only one document per value of
+/// the `key_field` fast field.
+///
+/// When several documents share the same key, the document of the last segment in `segments`
+/// is kept, the segments being considered from the oldest to the newest. Within a segment,
+/// the document with the highest doc id is kept. This is the last document added, unless the
+/// index is sorted by a field (see [`IndexSettings::sort_by_field`]): the doc ids then follow
+/// the order of the sort field rather than the order of insertion. Documents whose key only
+/// appears once are always kept.
+///
+/// `key_field` must be a single-valued fast field of type u64, i64, f64, bool or date.
+/// Deleted documents are ignored.
+///
+/// `output_directory`: is assumed to be empty.
+///
+/// # Warning
+/// This function does NOT check or take the `IndexWriter` is running. It is not
+/// meant to work if you have an IndexWriter running for the origin indices, or
+/// the destination Index.
+#[doc(hidden)]
+pub fn merge_deduplicated_segments<T: Into<Box<dyn Directory>>>(
+    segments: &[Segment],
+    target_settings: IndexSettings,
+    key_field: Field,
+    output_directory: T,
+) -> crate::Result<Index> {
+    let filter_doc_ids = deduplicate_by_key(segments, key_field)?;
+    merge_filtered_segments(segments, target_settings, filter_doc_ids, output_directory)
+}
+
+/// Returns, for each segment, the alive bitset of the documents kept by
+/// [`merge_deduplicated_segments`].
+fn deduplicate_by_key(
+    segments: &[Segment],
+    key_field: Field,
+) -> crate::Result<Vec<Option<AliveBitSet>>> {
+    let readers = segments
+        .iter()
+        .map(SegmentReader::open)
+        .collect::<crate::Result<Vec<_>>>()?;
+    let mut kept_docs: HashMap<u64, (usize, DocId)> = HashMap::new();
+    for (segment_ord, reader) in readers.iter().enumerate() {
+        let field_entry = reader.schema().get_field_entry(key_field);
+        match type_and_cardinality(field_entry.field_type()) {
+            Some((_, Cardinality::SingleValue)) => {}
+            _ => {
+                return Err(crate::TantivyError::SchemaError(format!(
+                    "Field {:?} is not a single-valued fast field, it cannot be used as a \
+                     deduplication key.",
+                    field_entry.name()
+                )));
+            }
+        }
+        let key_reader = reader.fast_fields().u64_lenient(key_field)?;
+        for doc in reader.doc_ids_alive() {
+            kept_docs.insert(key_reader.get_val(doc as u64), (segment_ord, doc));
+        }
+    }
+    let mut alive_bitsets: Vec<BitSet> = readers
+        .iter()
+        .map(|reader| BitSet::with_max_value(reader.max_doc()))
+        .collect();
+    for (segment_ord, doc) in kept_docs.into_values() {
+        alive_bitsets[segment_ord].insert(doc);
+    }
+    Ok(alive_bitsets
+        .iter()
+        .map(|alive_bitset| Some(AliveBitSet::from_bitset(alive_bitset)))
+        .collect())
+}
+
 pub(crate) struct InnerSegmentUpdater {
     // we keep a copy of the current active IndexMeta to
     // avoid loading the file every time we need it in the
@@ -268,14 +462,19 @@ pub(crate) struct InnerSegmentUpdater {
     // the unique active `SegmentUpdater`.
     active_index_meta: RwLock<Arc<IndexMeta>>,
     pool: ThreadPool,
-    merge_thread_pool: ThreadPool,
+    merge_thread_pool: RwLock<MergeThreadPool>,
 
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    merge_memory_budget: RwLock<Option<usize>>,
+    term_block_memory_budget: RwLock<Option<usize>>,
     killed: AtomicBool,
+    // Cancels the merges started by the merge policy when the segment updater is killed.
+    merge_cancellation_token: MergeCancellationToken,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    event_sender: RwLock<Option<EventSender>>,
 }
 
 impl SegmentUpdater {
@@ -295,26 +494,22 @@ impl SegmentUpdater {
                     "Failed to spawn segment updater thread".to_string(),
                 )
             })?;
-        let merge_thread_pool = ThreadPoolBuilder::new()
-            .thread_name(|i| format!("merge_thread_{i}"))
-            .num_threads(NUM_MERGE_THREADS)
-            .build()
-            .map_err(|_| {
-                crate::TantivyError::SystemError(
-                    "Failed to spawn segment merging thread".to_string(),
-                )
-            })?;
+        let merge_thread_pool = MergeThreadPool::build(DEFAULT_NUM_MERGE_THREADS, None)?;
         let index_meta = index.load_metas()?;
         Ok(SegmentUpdater(Arc::new(InnerSegmentUpdater {
             active_index_meta: RwLock::new(Arc::new(index_meta)),
             pool,
-            merge_thread_pool,
+            merge_thread_pool: RwLock::new(merge_thread_pool),
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            merge_memory_budget: RwLock::new(None),
+            term_block_memory_budget: RwLock::new(None),
             killed: AtomicBool::new(false),
+            merge_cancellation_token: MergeCancellationToken::default(),
             stamper,
             merge_operations: Default::default(),
+            event_sender: RwLock::new(None),
         })))
     }
 
@@ -327,6 +522,65 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_merge_memory_budget(&self) -> Option<usize> {
+        *self.merge_memory_budget.read().unwrap()
+    }
+
+    pub fn set_merge_memory_budget(&self, merge_memory_budget_opt: Option<usize>) {
+        *self.merge_memory_budget.write().unwrap() = merge_memory_budget_opt;
+    }
+
+    pub fn get_term_block_memory_budget(&self) -> Option<usize> {
+        *self.term_block_memory_budget.read().unwrap()
+    }
+
+    pub fn set_term_block_memory_budget(&self, term_block_memory_budget_opt: Option<usize>) {
+        *self.term_block_memory_budget.write().unwrap() = term_block_memory_budget_opt;
+    }
+
+    pub fn num_merge_threads(&self) -> usize {
+        self.merge_thread_pool
+            .read()
+            .unwrap()
+            .pool
+            .current_num_threads()
+    }
+
+    /// Replaces the merge thread pool. The merges running on the previous pool are not
+    /// interrupted: its threads are released once these are over.
+    pub fn set_num_merge_threads(&self, num_merge_threads: usize) -> crate::Result<()> {
+        let mut merge_thread_pool = self.merge_thread_pool.write().unwrap();
+        let start_handler_opt = merge_thread_pool.start_handler_opt.clone();
+        *merge_thread_pool = MergeThreadPool::build(num_merge_threads, start_handler_opt)?;
+        Ok(())
+    }
+
+    pub fn set_merge_thread_start_handler(
+        &self,
+        start_handler: MergeThreadStartHandler,
+    ) -> crate::Result<()> {
+        let mut merge_thread_pool = self.merge_thread_pool.write().unwrap();
+        let num_threads = merge_thread_pool.num_threads;
+        *merge_thread_pool = MergeThreadPool::build(num_threads, Some(start_handler))?;
+        Ok(())
+    }
+
+    /// Replaces the listener of the events of the segment updater.
+    pub fn set_event_listener(
+        &self,
+        listener: Arc<dyn IndexWriterEventListener>,
+    ) -> crate::Result<()> {
+        let event_sender = EventSender::spawn(listener)?;
+        *self.event_sender.write().unwrap() = Some(event_sender);
+        Ok(())
+    }
+
+    fn send_event(&self, make_event: impl FnOnce() -> IndexWriterEvent) {
+        if let Some(event_sender) = self.event_sender.read().unwrap().as_ref() {
+            event_sender.send(make_event());
+        }
+    }
+
     fn schedule_task<T: 'static + Send, F: FnOnce() -> crate::Result<T> + 'static + Send>(
         &self,
         task: F,
@@ -347,12 +601,35 @@ impl SegmentUpdater {
     pub fn schedule_add_segment(&self, segment_entry: SegmentEntry) -> FutureResult<()> {
         let segment_updater = self.clone();
         self.schedule_task(move || {
+            segment_updater
+                .send_event(|| IndexWriterEvent::SegmentCreated(segment_entry.meta().clone()));
             segment_updater.segment_manager.add_segment(segment_entry);
             segment_updater.consider_merge_options();
             Ok(())
         })
     }
 
+    pub(crate) fn schedule_set_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: BTreeMap<String, String>,
+    ) -> FutureResult<()> {
+        let segment_updater = self.clone();
+        self.schedule_task(move || {
+            if segment_updater
+                .segment_manager
+                .set_user_metadata(segment_id, user_metadata)
+            {
+                Ok(())
+            } else {
+                Err(crate::TantivyError::InvalidArgument(format!(
+                    "Segment {} is not a segment of the index.",
+                    segment_id.short_uuid_string()
+                )))
+            }
+        })
+    }
+
     /// Orders `SegmentManager` to remove all segments
     pub(crate) fn remove_all_segments(&self) {
         self.segment_manager.remove_all_segments();
@@ -360,6 +637,7 @@ impl SegmentUpdater {
 
     pub fn kill(&mut self) {
         self.killed.store(true, Ordering::Release);
+        self.merge_cancellation_token.cancel();
     }
 
     pub fn is_alive(&self) -> bool {
@@ -444,9 +722,14 @@ impl SegmentUpdater {
     ) -> FutureResult<Opstamp> {
         let segment_updater: SegmentUpdater = self.clone();
         self.schedule_task(move || {
+            segment_updater.send_event(|| IndexWriterEvent::CommitStarted { opstamp });
             let segment_entries = segment_updater.purge_deletes(opstamp)?;
             segment_updater.segment_manager.commit(segment_entries);
             segment_updater.save_metas(opstamp, payload)?;
+            segment_updater.send_event(|| IndexWriterEvent::CommitFinished {
+                opstamp,
+                segments: segment_updater.segment_manager.committed_segment_metas(),
+            });
             let _ = garbage_collect_files(segment_updater.clone());
             segment_updater.consider_merge_options();
             Ok(opstamp)
@@ -461,9 +744,18 @@ impl SegmentUpdater {
         self.active_index_meta.read().unwrap().clone()
     }
 
-    pub(crate) fn make_merge_operation(&self, segment_ids: &[SegmentId]) -> MergeOperation {
+    pub(crate) fn make_merge_operation(
+        &self,
+        segment_ids: &[SegmentId],
+        cancellation_token: MergeCancellationToken,
+    ) -> MergeOperation {
         let commit_opstamp = self.load_meta().opstamp;
-        MergeOperation::new(&self.merge_operations, commit_opstamp, segment_ids.to_vec())
+        MergeOperation::with_cancellation_token(
+            &self.merge_operations,
+            commit_opstamp,
+            segment_ids.to_vec(),
+            cancellation_token,
+        )
     }
 
     // Starts a merge operation. This function will block until the merge operation is effectively
@@ -493,6 +785,7 @@ impl SegmentUpdater {
         );
 
         let segment_updater = self.clone();
+        let merge_memory_budget_opt = self.get_merge_memory_budget();
         let segment_entries: Vec<SegmentEntry> = match self
             .segment_manager
             .start_merge(merge_operation.segment_ids())
@@ -509,10 +802,20 @@ impl SegmentUpdater {
 
         info!("Starting merge  - {:?}", merge_operation.segment_ids());
 
+        let segment_metas: Vec<SegmentMeta> = segment_entries
+            .iter()
+            .map(|segment_entry| segment_entry.meta().clone())
+            .collect();
+        let user_metadata = self.get_merge_policy().merge_user_metadata(&segment_metas);
+        self.send_event(|| IndexWriterEvent::MergeStarted {
+            segments: segment_metas,
+        });
+
         let (scheduled_result, merging_future_send) =
             FutureResult::create("Merge operation failed.");
 
-        self.merge_thread_pool.spawn(move || {
+        let merge_thread_pool = self.merge_thread_pool.read().unwrap();
+        merge_thread_pool.pool.spawn(move || {
             // The fact that `merge_operation` is moved here is important.
             // Its lifetime is used to track how many merging thread are currently running,
             // as well as which segment is currently in merge and therefore should not be
@@ -521,6 +824,9 @@ impl SegmentUpdater {
                 &segment_updater.index,
                 segment_entries,
                 merge_operation.target_opstamp(),
+                merge_memory_budget_opt,
+                user_metadata,
+                merge_operation.cancellation_token(),
             ) {
                 Ok(after_merge_segment_entry) => {
                     let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
@@ -532,7 +838,11 @@ impl SegmentUpdater {
                         merge_operation.segment_ids().to_vec(),
                         merge_error
                     );
-                    if cfg!(test) {
+                    segment_updater.send_event(|| IndexWriterEvent::MergeFailed {
+                        segment_ids: merge_operation.segment_ids().to_vec(),
+                        error: merge_error.to_string(),
+                    });
+                    if cfg!(test) && !matches!(merge_error, crate::TantivyError::MergeCancelled) {
                         panic!("{:?}", merge_error);
                     }
                     let _send_result = merging_future_send.send(Err(merge_error));
@@ -561,7 +871,12 @@ impl SegmentUpdater {
             .compute_merge_candidates(&uncommitted_segments)
             .into_iter()
             .map(|merge_candidate| {
-                MergeOperation::new(&self.merge_operations, current_opstamp, merge_candidate.0)
+                MergeOperation::with_cancellation_token(
+                    &self.merge_operations,
+                    current_opstamp,
+                    merge_candidate.0,
+                    self.merge_cancellation_token.clone(),
+                )
             })
             .collect();
 
@@ -570,7 +885,12 @@ impl SegmentUpdater {
             .compute_merge_candidates(&committed_segments)
             .into_iter()
             .map(|merge_candidate: MergeCandidate| {
-                MergeOperation::new(&self.merge_operations, commit_opstamp, merge_candidate.0)
+                MergeOperation::with_cancellation_token(
+                    &self.merge_operations,
+                    commit_opstamp,
+                    merge_candidate.0,
+                    self.merge_cancellation_token.clone(),
+                )
             });
         merge_candidates.extend(committed_merge_candidates);
 
@@ -615,6 +935,10 @@ impl SegmentUpdater {
                                     advance_deletes_err
                                 );
                                 assert!(!cfg!(test), "Merge failed.");
+                                segment_updater.send_event(|| IndexWriterEvent::MergeFailed {
+                                    segment_ids: merge_operation.segment_ids().to_vec(),
+                                    error: advance_deletes_err.to_string(),
+                                });
 
                                 // ... cancel merge
                                 // `merge_operations` are tracked. As it is dropped, the
@@ -625,9 +949,16 @@ impl SegmentUpdater {
                     }
                 }
                 let previous_metas = segment_updater.load_meta();
+                let merged_segment = after_merge_segment_entry
+                    .as_ref()
+                    .map(|after_merge_segment_entry| after_merge_segment_entry.meta().clone());
                 let segments_status = segment_updater
                     .segment_manager
                     .end_merge(merge_operation.segment_ids(), after_merge_segment_entry)?;
+                segment_updater.send_event(|| IndexWriterEvent::MergeFinished {
+                    segment_ids: merge_operation.segment_ids().to_vec(),
+                    merged_segment,
+                });
 
                 if segments_status == SegmentsStatus::Committed {
                     segment_updater
@@ -667,16 +998,22 @@ impl SegmentUpdater {
 
 #[cfg(test)]
 mod tests {
-    use super::merge_indices;
+    use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+    use super::{merge_indices, merge_indices_with_compatible_schemas};
     use crate::collector::TopDocs;
     use crate::directory::RamDirectory;
     use crate::fastfield::AliveBitSet;
     use crate::indexer::merge_policy::tests::MergeWheneverPossible;
     use crate::indexer::merger::IndexMerger;
-    use crate::indexer::segment_updater::merge_filtered_segments;
+    use crate::indexer::segment_updater::{merge_deduplicated_segments, merge_filtered_segments};
+    use crate::indexer::{MergeCandidate, MergePolicy, NoMergePolicy};
     use crate::query::QueryParser;
     use crate::schema::*;
-    use crate::{Directory, DocAddress, Index, Segment};
+    use crate::{
+        Directory, DocAddress, Index, IndexSettings, IndexSortByField, Order, Segment, SegmentId,
+        SegmentMeta, TantivyError,
+    };
 
     #[test]
     fn test_delete_during_merge() -> crate::Result<()> {
@@ -879,6 +1216,98 @@ mod tests {
         Ok(())
     }
 
+    /// Merge policy concatenating the distinct values of each user metadata entry.
+    #[derive(Debug)]
+    struct ConcatenateUserMetadata;
+
+    impl MergePolicy for ConcatenateUserMetadata {
+        fn compute_merge_candidates(&self, _segments: &[SegmentMeta]) -> Vec<MergeCandidate> {
+            Vec::new()
+        }
+
+        fn merge_user_metadata(&self, segments: &[SegmentMeta]) -> BTreeMap<String, String> {
+            let mut values: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+            for segment in segments {
+                for (key, value) in segment.user_metadata() {
+                    values.entry(key.clone()).or_default().insert(value.clone());
+                }
+            }
+            values
+                .into_iter()
+                .map(|(key, values)| (key, values.into_iter().collect::<Vec<_>>().join(",")))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_segment_user_metadata() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let user_metadata = |entries: &[(&str, &str)]| -> BTreeMap<String, String> {
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        let mut add_segment = |text: &str, entries: &[(&str, &str)]| -> crate::Result<SegmentId> {
+            let segment_ids_before: HashSet<SegmentId> =
+                index.searchable_segment_ids()?.into_iter().collect();
+            index_writer.add_document(doc!(text_field => text))?;
+            index_writer.commit()?;
+            let segment_id = index
+                .searchable_segment_ids()?
+                .into_iter()
+                .find(|segment_id| !segment_ids_before.contains(segment_id))
+                .unwrap();
+            index_writer.set_segment_user_metadata(segment_id, user_metadata(entries))?;
+            Ok(segment_id)
+        };
+        let segment_a = add_segment("a", &[("source", "a.json"), ("batch", "1")])?;
+        let segment_b = add_segment("b", &[("source", "b.json"), ("batch", "1")])?;
+        let segment_c = add_segment("c", &[("source", "c.json")])?;
+        let segment_d = add_segment("d", &[("source", "d.json")])?;
+        // The metadata of the last segment is persisted upon the next commit.
+        index_writer.commit()?;
+        let user_metadata_of = |segment_id: SegmentId| -> crate::Result<BTreeMap<String, String>> {
+            let index_meta = index.load_metas()?;
+            let segment_meta = index_meta
+                .segments
+                .iter()
+                .find(|segment_meta| segment_meta.id() == segment_id)
+                .unwrap();
+            Ok(segment_meta.user_metadata().clone())
+        };
+        assert_eq!(
+            user_metadata_of(segment_a)?,
+            user_metadata(&[("source", "a.json"), ("batch", "1")])
+        );
+        assert_eq!(
+            user_metadata_of(segment_d)?,
+            user_metadata(&[("source", "d.json")])
+        );
+        assert!(matches!(
+            index_writer.set_segment_user_metadata(SegmentId::generate_random(), BTreeMap::new()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        // By default, the entries that differ between the merged segments are dropped.
+        let merged_ab = index_writer.merge(&[segment_a, segment_b]).wait()?.unwrap();
+        assert_eq!(merged_ab.user_metadata(), &user_metadata(&[("batch", "1")]));
+
+        index_writer.set_merge_policy(Box::new(ConcatenateUserMetadata));
+        let merged_abcd = index_writer
+            .merge(&[merged_ab.id(), segment_c, segment_d])
+            .wait()?
+            .unwrap();
+        let expected_metadata = user_metadata(&[("source", "c.json,d.json"), ("batch", "1")]);
+        assert_eq!(merged_abcd.user_metadata(), &expected_metadata);
+        assert_eq!(user_metadata_of(merged_abcd.id())?, expected_metadata);
+        Ok(())
+    }
+
     #[test]
     fn test_merge_empty_indices_array() {
         let merge_result = merge_indices(&[], RamDirectory::default());
@@ -914,6 +1343,122 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_indices_with_compatible_schemas() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT | STORED);
+        schema_builder.add_u64_field("price", FAST);
+        let old_schema = schema_builder.build();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let tag_field = schema_builder.add_text_field("tag", TEXT | STORED);
+        let rating_field = schema_builder.add_i64_field("rating", FAST);
+        let labels_field = schema_builder.add_u64_field(
+            "labels",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let new_schema = schema_builder.build();
+
+        let old_index = Index::create_in_ram(old_schema);
+        {
+            let mut index_writer = old_index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => "old book", price_field => 10u64))?;
+            index_writer.add_document(doc!(text_field => "old pen", price_field => 2u64))?;
+            index_writer.commit()?;
+        }
+        let new_index = Index::create_in_ram(new_schema.clone());
+        {
+            let mut index_writer = new_index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                text_field => "new book",
+                price_field => 20u64,
+                tag_field => "novel",
+                rating_field => -3i64,
+                labels_field => 1u64,
+                labels_field => 2u64,
+            ))?;
+            index_writer.commit()?;
+        }
+        let index = merge_indices_with_compatible_schemas(
+            &[old_index, new_index],
+            RamDirectory::default(),
+        )?;
+        assert_eq!(index.schema(), new_schema);
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 3);
+        let query_parser = QueryParser::for_index(&index, vec![text_field, tag_field]);
+        let search = |query: &str| -> crate::Result<Vec<DocAddress>> {
+            let query = query_parser.parse_query(query)?;
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+            Ok(top_docs
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect())
+        };
+        assert_eq!(search("book")?.len(), 2);
+        let novel_docs = search("tag:novel")?;
+        assert_eq!(novel_docs.len(), 1);
+
+        let segment_reader = searcher.segment_reader(0);
+        let fast_fields = segment_reader.fast_fields();
+        let price_reader = fast_fields.u64(price_field)?;
+        let rating_reader = fast_fields.i64(rating_field)?;
+        let labels_reader = fast_fields.u64s(labels_field)?;
+        let mut docs = Vec::new();
+        for doc in 0..segment_reader.max_doc() {
+            let mut labels = Vec::new();
+            labels_reader.get_vals(doc, &mut labels);
+            docs.push((
+                price_reader.get_val(doc as u64),
+                rating_reader.get_val(doc as u64),
+                labels,
+            ));
+        }
+        docs.sort();
+        // The documents of the old index get the default values of the new fields.
+        assert_eq!(
+            docs,
+            vec![(2, 0, vec![]), (10, 0, vec![]), (20, -3, vec![1, 2])]
+        );
+        let novel_doc = searcher.doc(novel_docs[0])?;
+        assert_eq!(
+            novel_doc.get_first(tag_field).unwrap().as_text(),
+            Some("novel")
+        );
+        for doc_address in search("old")? {
+            let old_doc = searcher.doc(doc_address)?;
+            assert!(old_doc.get_first(tag_field).is_none());
+            assert!(old_doc.get_first(text_field).is_some());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_indices_with_incompatible_schemas() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_u64_field("price", FAST);
+        let first_index = Index::create_in_ram(schema_builder.build());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("price", FAST);
+        schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_text_field("tag", TEXT);
+        let second_index = Index::create_in_ram(schema_builder.build());
+        let result = merge_indices_with_compatible_schemas(
+            &[first_index, second_index],
+            RamDirectory::default(),
+        );
+        assert!(matches!(result, Err(TantivyError::InvalidArgument(_))));
+        assert!(matches!(
+            merge_indices_with_compatible_schemas(&[], RamDirectory::default()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_merge_filtered_segments() -> crate::Result<()> {
         let first_index = {
@@ -968,6 +1513,117 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_deduplicated_segments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key_field = schema_builder.add_u64_field("key", FAST | STORED);
+        let version_field = schema_builder.add_text_field("version", STRING | STORED);
+        let schema = schema_builder.build();
+        let build_index = |docs: &[(u64, &str)]| -> crate::Result<Index> {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_for_tests()?;
+            for &(key, version) in docs {
+                index_writer.add_document(doc!(key_field => key, version_field => version))?;
+            }
+            index_writer.delete_term(Term::from_field_text(version_field, "deleted"));
+            index_writer.commit()?;
+            Ok(index)
+        };
+        let indices = vec![
+            build_index(&[(1, "a"), (2, "a"), (3, "a")])?,
+            build_index(&[(2, "b"), (4, "b"), (4, "b2"), (3, "b")])?,
+            build_index(&[(3, "c"), (5, "c"), (1, "deleted")])?,
+        ];
+        let mut segments: Vec<Segment> = Vec::new();
+        for index in &indices {
+            segments.extend(index.searchable_segments()?);
+        }
+
+        let merged_index = merge_deduplicated_segments(
+            &segments,
+            indices[0].settings().clone(),
+            key_field,
+            RamDirectory::default(),
+        )?;
+
+        let searcher = merged_index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 5);
+        let mut versions = BTreeMap::new();
+        for doc_id in 0..searcher.segment_reader(0).max_doc() {
+            let doc = searcher.doc(DocAddress::new(0, doc_id))?;
+            let key = doc.get_first(key_field).and_then(Value::as_u64).unwrap();
+            let version = doc
+                .get_first(version_field)
+                .and_then(Value::as_text)
+                .unwrap();
+            assert!(versions.insert(key, version.to_string()).is_none());
+        }
+        let expected_versions: BTreeMap<u64, String> =
+            [(1, "a"), (2, "b"), (3, "c"), (4, "b2"), (5, "c")]
+                .into_iter()
+                .map(|(key, version)| (key, version.to_string()))
+                .collect();
+        assert_eq!(versions, expected_versions);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_deduplicated_segments_sorted_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key_field = schema_builder.add_u64_field("key", FAST | STORED);
+        let rank_field = schema_builder.add_u64_field("rank", FAST | STORED);
+        let settings = IndexSettings {
+            sort_by_field: Some(IndexSortByField {
+                field: "rank".to_string(),
+                order: Order::Desc,
+            }),
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(settings.clone())
+            .create_in_ram()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(key_field => 1u64, rank_field => 1u64))?;
+        index_writer.add_document(doc!(key_field => 1u64, rank_field => 5u64))?;
+        index_writer.add_document(doc!(key_field => 1u64, rank_field => 3u64))?;
+        index_writer.commit()?;
+
+        let merged_index = merge_deduplicated_segments(
+            &index.searchable_segments()?,
+            settings,
+            key_field,
+            RamDirectory::default(),
+        )?;
+
+        let searcher = merged_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        // The docs are sorted by decreasing rank, so the document with the lowest rank has the
+        // highest doc id, although it was added first.
+        let doc = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(doc.get_first(rank_field).and_then(Value::as_u64), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_deduplicated_segments_requires_fast_key() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key_field = schema_builder.add_u64_field("key", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(key_field => 1u64))?;
+        index_writer.commit()?;
+        let merge_res = merge_deduplicated_segments(
+            &index.searchable_segments()?,
+            index.settings().clone(),
+            key_field,
+            RamDirectory::default(),
+        );
+        assert!(matches!(merge_res, Err(TantivyError::SchemaError(_))));
+        Ok(())
+    }
+
     #[test]
     fn test_merge_single_filtered_segments() -> crate::Result<()> {
         let first_index = {
@@ -9,13 +9,16 @@ use crate::indexer::json_term_writer::index_json_values;
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::postings::{
     compute_table_size, serialize_postings, IndexingContext, IndexingPosition,
-    PerFieldPostingsWriter, PostingsWriter,
+    PerFieldPostingsWriter, PostingsWriter, TermBlocks, TermBlocksWriter,
 };
-use crate::schema::{FieldEntry, FieldType, FieldValue, Schema, Term, Value};
+use crate::schema::{Field, FieldEntry, FieldType, FieldValue, Schema, Term, Value};
 use crate::store::{StoreReader, StoreWriter};
+use crate::termvector::TermVectorsWriter;
 use crate::tokenizer::{
-    BoxTokenStream, FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer,
+    BoxTokenStream, FacetTokenizer, PreTokenizedStream, PreTokenizedString, TextAnalyzer, Token,
+    Tokenizer, MAX_TOKEN_LEN,
 };
+use crate::vector::DenseVectorsWriter;
 use crate::{DatePrecision, DocId, Document, Opstamp, SegmentComponent};
 
 /// Computes the initial size of the hash table.
@@ -63,9 +66,18 @@ pub struct SegmentWriter {
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
+    pub(crate) dense_vectors_writer: DenseVectorsWriter,
+    pub(crate) term_vectors_writer: TermVectorsWriter,
     pub(crate) doc_opstamps: Vec<Opstamp>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
     term_buffer: Term,
+    // `u64` representation of the values of the indexed numeric fields of the current document,
+    // shared with the fast field writers.
+    indexed_numeric_vals: Vec<(Field, u64)>,
+    // Memory budget of the buffered terms and postings, above which they are flushed as a term
+    // block.
+    term_block_memory_budget: Option<usize>,
+    term_blocks_writer: Option<TermBlocksWriter>,
     schema: Schema,
 }
 
@@ -113,13 +125,42 @@ impl SegmentWriter {
             fieldnorms_writer: FieldNormsWriter::for_schema(&schema),
             segment_serializer,
             fast_field_writers: FastFieldsWriter::from_schema(&schema),
+            dense_vectors_writer: DenseVectorsWriter::from_schema(&schema),
+            term_vectors_writer: TermVectorsWriter::from_schema(&schema),
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
             term_buffer: Term::new(),
+            indexed_numeric_vals: Vec::new(),
+            term_block_memory_budget: None,
+            term_blocks_writer: None,
             schema,
         })
     }
 
+    /// Sets the memory budget, in bytes, of the buffered terms and postings.
+    ///
+    /// Once the budget is reached, they are flushed after the current document as a sorted
+    /// block to the `TempTerms` file of the segment.
+    pub(crate) fn set_term_block_memory_budget(&mut self, term_block_memory_budget: Option<usize>) {
+        self.term_block_memory_budget = term_block_memory_budget;
+    }
+
+    /// Flushes the buffered terms and postings as a sorted term block.
+    fn flush_term_block(&mut self) -> crate::Result<()> {
+        if self.ctx.term_index.len() == 0 {
+            return Ok(());
+        }
+        if self.term_blocks_writer.is_none() {
+            let term_blocks_writer = TermBlocksWriter::open(self.segment_serializer.segment_mut())?;
+            self.term_blocks_writer = Some(term_blocks_writer);
+        }
+        if let Some(term_blocks_writer) = self.term_blocks_writer.as_mut() {
+            term_blocks_writer.write_block(&self.ctx, &self.per_field_postings_writers)?;
+        }
+        self.ctx.clear();
+        Ok(())
+    }
+
     /// Lay on disk the current content of the `SegmentWriter`
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
@@ -135,9 +176,29 @@ impl SegmentWriter {
             .clone()
             .map(|sort_by_field| get_doc_id_mapping_from_field(sort_by_field, &self))
             .transpose()?;
+        let term_blocks_opt: Option<TermBlocks> = self
+            .term_blocks_writer
+            .map(TermBlocksWriter::finish)
+            .transpose()?;
+        if let Some(dense_vectors_serializer) =
+            self.segment_serializer.extract_dense_vectors_serializer()
+        {
+            self.dense_vectors_writer
+                .serialize(dense_vectors_serializer, mapping.as_ref())?;
+        }
+        if let Some(term_vectors_serializer) =
+            self.segment_serializer.extract_term_vectors_serializer()
+        {
+            self.term_vectors_writer.serialize(
+                term_vectors_serializer,
+                self.max_doc,
+                mapping.as_ref(),
+            )?;
+        }
         remap_and_write(
             &self.per_field_postings_writers,
             self.ctx,
+            term_blocks_opt.as_ref(),
             self.fast_field_writers,
             &self.fieldnorms_writer,
             &self.schema,
@@ -152,7 +213,10 @@ impl SegmentWriter {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
+            + self.dense_vectors_writer.mem_usage()
+            + self.term_vectors_writer.mem_usage()
             + self.segment_serializer.mem_usage()
+            + self.doc_opstamps.capacity() * std::mem::size_of::<Opstamp>()
     }
 
     fn index_document(&mut self, doc: &Document) -> crate::Result<()> {
@@ -196,14 +260,20 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Str(_) => {
+                FieldType::Str(ref text_options) => {
                     let mut token_streams: Vec<BoxTokenStream> = vec![];
                     let mut offsets = vec![];
                     let mut total_offset = 0;
+                    let has_term_vectors = self.term_vectors_writer.has_term_vectors(field);
+                    // Offsets of the values in their concatenation, separated by a space.
+                    let mut text_offsets = vec![];
+                    let mut text_offset = 0;
 
                     for value in values {
                         match value {
                             Value::PreTokStr(tok_str) => {
+                                text_offsets.push(text_offset);
+                                text_offset += tok_str.text.len() + 1;
                                 offsets.push(total_offset);
                                 if let Some(last_token) = tok_str.tokens.last() {
                                     total_offset += last_token.offset_to;
@@ -214,6 +284,8 @@ impl SegmentWriter {
                             Value::Str(ref text) => {
                                 let text_analyzer =
                                     &self.per_field_text_analyzers[field.field_id() as usize];
+                                text_offsets.push(text_offset);
+                                text_offset += text.len() + 1;
                                 offsets.push(total_offset);
                                 total_offset += text.len();
                                 token_streams.push(text_analyzer.token_stream(text));
@@ -222,10 +294,51 @@ impl SegmentWriter {
                         }
                     }
 
-                    let mut indexing_position = IndexingPosition::default();
+                    let mut indexing_position = text_options
+                        .get_indexing_options()
+                        .map(|indexing_options| {
+                            let mut indexing_position = IndexingPosition::with_position_gap(
+                                indexing_options.position_gap(),
+                            );
+                            indexing_position.max_num_tokens =
+                                indexing_options.max_indexed_tokens();
+                            indexing_position
+                        })
+                        .unwrap_or_default();
 
-                    for mut token_stream in token_streams {
+                    let mut term_vector: Vec<Token> = Vec::new();
+                    for (mut token_stream, text_offset) in
+                        token_streams.into_iter().zip(text_offsets)
+                    {
                         assert_eq!(term_buffer.as_slice().len(), 5);
+                        if has_term_vectors {
+                            // The tokens are collected, and the postings are then fed with
+                            // them, so that the text is only tokenized once.
+                            let start_position = indexing_position.end_position as usize;
+                            let mut tokens: Vec<Token> = Vec::new();
+                            token_stream.process(&mut |token| tokens.push(token.clone()));
+                            let num_remaining_tokens = indexing_position
+                                .num_remaining_tokens()
+                                .map_or(usize::MAX, |num_tokens| num_tokens as usize);
+                            term_vector.extend(
+                                tokens
+                                    .iter()
+                                    .filter(|token| token.text.len() <= MAX_TOKEN_LEN)
+                                    .take(num_remaining_tokens)
+                                    .map(|token| Token {
+                                        offset_from: text_offset + token.offset_from,
+                                        offset_to: text_offset + token.offset_to,
+                                        position: start_position + token.position,
+                                        text: token.text.clone(),
+                                        position_length: token.position_length,
+                                    }),
+                            );
+                            token_stream = PreTokenizedStream::from(PreTokenizedString {
+                                text: String::new(),
+                                tokens,
+                            })
+                            .into();
+                        }
                         postings_writer.index_text(
                             doc_id,
                             &mut *token_stream,
@@ -235,6 +348,10 @@ impl SegmentWriter {
                             self.fast_field_writers.get_term_id_writer_mut(field),
                         );
                     }
+                    if has_term_vectors {
+                        self.term_vectors_writer
+                            .record(doc_id, field, &term_vector)?;
+                    }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
                             .record(doc_id, field, indexing_position.num_tokens);
@@ -245,6 +362,7 @@ impl SegmentWriter {
                         let u64_val = value.as_u64().ok_or_else(make_schema_error)?;
                         term_buffer.set_u64(u64_val);
                         postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                        self.indexed_numeric_vals.push((field, u64_val));
                     }
                 }
                 FieldType::Date(_) => {
@@ -256,23 +374,26 @@ impl SegmentWriter {
                 }
                 FieldType::I64(_) => {
                     for value in values {
-                        let i64_val = value.as_i64().ok_or_else(make_schema_error)?;
-                        term_buffer.set_i64(i64_val);
+                        let u64_val = value.as_i64().ok_or_else(make_schema_error)?.to_u64();
+                        term_buffer.set_u64(u64_val);
                         postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                        self.indexed_numeric_vals.push((field, u64_val));
                     }
                 }
                 FieldType::F64(_) => {
                     for value in values {
-                        let f64_val = value.as_f64().ok_or_else(make_schema_error)?;
-                        term_buffer.set_f64(f64_val);
+                        let u64_val = value.as_f64().ok_or_else(make_schema_error)?.to_u64();
+                        term_buffer.set_u64(u64_val);
                         postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                        self.indexed_numeric_vals.push((field, u64_val));
                     }
                 }
                 FieldType::Bool(_) => {
                     for value in values {
-                        let bool_val = value.as_bool().ok_or_else(make_schema_error)?;
-                        term_buffer.set_bool(bool_val);
+                        let u64_val = value.as_bool().ok_or_else(make_schema_error)?.to_u64();
+                        term_buffer.set_u64(u64_val);
                         postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                        self.indexed_numeric_vals.push((field, u64_val));
                     }
                 }
                 FieldType::Bytes(_) => {
@@ -296,6 +417,9 @@ impl SegmentWriter {
                         ctx,
                     )?;
                 }
+                FieldType::DenseVector(_) => {
+                    // Dense vector fields are never indexed.
+                }
             }
         }
         Ok(())
@@ -306,13 +430,23 @@ impl SegmentWriter {
     /// As a user, you should rather use `IndexWriter`'s add_document.
     pub fn add_document(&mut self, add_operation: AddOperation) -> crate::Result<()> {
         let doc = add_operation.document;
+        self.dense_vectors_writer.add_document(self.max_doc, &doc)?;
         self.doc_opstamps.push(add_operation.opstamp);
-        self.fast_field_writers.add_document(&doc);
+        self.fast_field_writers
+            .add_document_except_indexed_numeric(&doc);
+        self.indexed_numeric_vals.clear();
         self.index_document(&doc)?;
+        self.fast_field_writers
+            .add_indexed_numeric_vals(&self.indexed_numeric_vals);
         let prepared_doc = prepare_doc_for_store(doc, &self.schema);
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&prepared_doc)?;
         self.max_doc += 1;
+        if let Some(term_block_memory_budget) = self.term_block_memory_budget {
+            if self.ctx.buffered_terms_mem_usage() >= term_block_memory_budget {
+                self.flush_term_block()?;
+            }
+        }
         Ok(())
     }
 
@@ -342,9 +476,11 @@ impl SegmentWriter {
 /// to the `SegmentSerializer`.
 ///
 /// `doc_id_map` is used to map to the new doc_id order.
+#[allow(clippy::too_many_arguments)]
 fn remap_and_write(
     per_field_postings_writers: &PerFieldPostingsWriter,
     ctx: IndexingContext,
+    term_blocks_opt: Option<&TermBlocks>,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
     schema: &Schema,
@@ -361,6 +497,7 @@ fn remap_and_write(
     let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
     let term_ord_map = serialize_postings(
         ctx,
+        term_blocks_opt,
         per_field_postings_writers,
         fieldnorm_readers,
         doc_id_map,
@@ -431,16 +568,25 @@ pub fn prepare_doc_for_store(doc: Document, schema: &Schema) -> Document {
 
 #[cfg(test)]
 mod tests {
-    use super::compute_initial_table_size;
+    use super::{compute_initial_table_size, SegmentWriter};
     use crate::collector::Count;
+    use crate::core::Segment;
+    use crate::directory::Directory;
     use crate::indexer::json_term_writer::JsonTermWriter;
+    use crate::indexer::operation::AddOperation;
     use crate::postings::TermInfo;
-    use crate::query::PhraseQuery;
-    use crate::schema::{IndexRecordOption, Schema, Type, STORED, STRING, TEXT};
+    use crate::query::{PhraseQuery, TermQuery};
+    use crate::schema::{
+        Cardinality, Facet, FacetOptions, IndexRecordOption, NumericOptions, Schema,
+        TextFieldIndexing, TextOptions, Type, FAST, INDEXED, STORED, STRING, TEXT,
+    };
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{PreTokenizedString, Token};
-    use crate::{DateTime, DocAddress, DocSet, Document, Index, Postings, Term, TERMINATED};
+    use crate::{
+        DateTime, DocAddress, DocSet, Document, Index, IndexSettings, IndexSortByField, Order,
+        Postings, SegmentComponent, Term, TERMINATED,
+    };
 
     #[test]
     fn test_hashmap_size() {
@@ -451,6 +597,66 @@ mod tests {
         assert_eq!(compute_initial_table_size(4_000_000_000).unwrap(), 1 << 19);
     }
 
+    #[test]
+    fn test_indexed_and_fast_numeric_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let delta = schema_builder.add_i64_field("delta", INDEXED | FAST);
+        let price = schema_builder.add_f64_field("price", INDEXED | FAST);
+        let available = schema_builder.add_bool_field("available", INDEXED | FAST);
+        let fast_only = schema_builder.add_i64_field("fast_only", FAST);
+        let tags = schema_builder.add_i64_field(
+            "tags",
+            NumericOptions::default()
+                .set_indexed()
+                .set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id => 3u64,
+            delta => -7i64,
+            price => 1.5f64,
+            available => true,
+            fast_only => -1i64,
+            tags => 4i64,
+            tags => -2i64,
+        ))?;
+        // Missing values, and several values for single-valued fields.
+        index_writer.add_document(doc!(id => 5u64, id => 6u64, tags => 1i64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0u32);
+        let fast_fields = segment_reader.fast_fields();
+        assert_eq!(fast_fields.u64(id)?.get_val(0), 3);
+        assert_eq!(fast_fields.u64(id)?.get_val(1), 5);
+        assert_eq!(fast_fields.i64(delta)?.get_val(0), -7);
+        assert_eq!(fast_fields.i64(delta)?.get_val(1), 0);
+        assert_eq!(fast_fields.f64(price)?.get_val(0), 1.5);
+        assert_eq!(fast_fields.f64(price)?.get_val(1), 0.0);
+        assert!(fast_fields.bool(available)?.get_val(0));
+        assert!(!fast_fields.bool(available)?.get_val(1));
+        assert_eq!(fast_fields.i64(fast_only)?.get_val(0), -1);
+        let tags_reader = fast_fields.i64s(tags)?;
+        let mut vals = Vec::new();
+        tags_reader.get_vals(0, &mut vals);
+        assert_eq!(vals, vec![4, -2]);
+        tags_reader.get_vals(1, &mut vals);
+        assert_eq!(vals, vec![1]);
+
+        // The terms are the ones of the fast values.
+        let count = |term: Term| {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count(Term::from_field_u64(id, 6))?, 1);
+        assert_eq!(count(Term::from_field_i64(delta, -7))?, 1);
+        assert_eq!(count(Term::from_field_f64(price, 1.5))?, 1);
+        assert_eq!(count(Term::from_field_bool(available, true))?, 1);
+        assert_eq!(count(Term::from_field_i64(tags, -2))?, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_prepare_for_store() {
         let mut schema_builder = Schema::builder();
@@ -480,6 +686,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pre_tokenized_text_postings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let token = |text: &str, position: usize, offset_from: usize| Token {
+            offset_from,
+            offset_to: offset_from + text.len(),
+            position,
+            text: text.to_string(),
+            position_length: 1,
+        };
+        // The tokens are indexed as is: they are neither lowercased nor split by the
+        // tokenizer of the field, and keep their positions.
+        let pre_tokenized_text = PreTokenizedString {
+            text: String::from("New York IS big"),
+            tokens: vec![
+                token("New York", 0, 0),
+                token("IS", 2, 9),
+                token("big", 5, 12),
+            ],
+        };
+        let mut doc = Document::default();
+        doc.add_pre_tokenized_text(text_field, pre_tokenized_text);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let inverted_index = searcher.segment_reader(0u32).inverted_index(text_field)?;
+        let mut terms = Vec::new();
+        let mut term_stream = inverted_index.terms().stream()?;
+        while term_stream.advance() {
+            terms.push(String::from_utf8(term_stream.key().to_vec()).unwrap());
+        }
+        assert_eq!(terms, vec!["IS", "New York", "big"]);
+        for (text, expected_position) in [("New York", 0), ("IS", 2), ("big", 5)] {
+            let term = Term::from_field_text(text_field, text);
+            let mut postings = inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            assert_eq!(postings.doc(), 0);
+            let mut positions = Vec::new();
+            postings.positions(&mut positions);
+            assert_eq!(positions, vec![expected_position]);
+            assert_eq!(postings.advance(), TERMINATED);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_json_indexing() {
         let mut schema_builder = Schema::builder();
@@ -635,7 +890,9 @@ mod tests {
             TermInfo {
                 doc_freq: 1,
                 postings_range: 2..4,
-                positions_range: 2..5
+                positions_range: 2..5,
+                max_term_freq: 2,
+                min_fieldnorm_id: 0,
             }
         );
         let mut postings = inv_index
@@ -680,7 +937,9 @@ mod tests {
             TermInfo {
                 doc_freq: 1,
                 postings_range: 0..1,
-                positions_range: 0..0
+                positions_range: 0..0,
+                max_term_freq: 1,
+                min_fieldnorm_id: 0,
             }
         );
         let mut postings = inv_index
@@ -728,4 +987,233 @@ mod tests {
         let phrase_query = PhraseQuery::new(vec![nothello_term, happy_term]);
         assert_eq!(searcher.search(&phrase_query, &Count).unwrap(), 0);
     }
+
+    fn term_blocks_test_index(sort_by_field: Option<IndexSortByField>) -> Index {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_text_field("tag", STRING | FAST);
+        schema_builder.add_facet_field("facet", FacetOptions::default());
+        schema_builder.add_u64_field("id", INDEXED | FAST);
+        schema_builder.add_json_field("json", TEXT);
+        Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field,
+                ..Default::default()
+            })
+            .create_in_ram()
+            .unwrap()
+    }
+
+    fn term_blocks_test_doc(index: &Index, i: u64) -> Document {
+        let schema = index.schema();
+        let words = ["hello", "happy", "tax", "payer", "nothello", "day"];
+        let text = (0..1 + i % 4)
+            .map(|j| words[((i + j * j) % words.len() as u64) as usize])
+            .collect::<Vec<_>>()
+            .join(" ");
+        let json_object = serde_json::json!({"title": text, "num": i % 5});
+        let mut doc = Document::default();
+        doc.add_json_object(
+            schema.get_field("json").unwrap(),
+            json_object.as_object().unwrap().clone(),
+        );
+        doc.add_text(schema.get_field("text").unwrap(), &text);
+        doc.add_text(schema.get_field("tag").unwrap(), format!("tag{}", i % 7));
+        doc.add_facet(
+            schema.get_field("facet").unwrap(),
+            Facet::from(&format!("/category/{}", i % 3)),
+        );
+        doc.add_u64(schema.get_field("id").unwrap(), (i * 37) % 101);
+        doc
+    }
+
+    /// Builds a segment, flushing a term block every `flush_every` documents.
+    fn build_segment_with_term_blocks(index: &Index, flush_every: Option<u64>) -> Segment {
+        let segment = index.new_segment();
+        let mut segment_writer = SegmentWriter::for_segment(10_000_000, segment.clone()).unwrap();
+        for i in 0..100 {
+            segment_writer
+                .add_document(AddOperation {
+                    opstamp: i,
+                    document: term_blocks_test_doc(index, i),
+                })
+                .unwrap();
+            if let Some(flush_every) = flush_every {
+                if i % flush_every == flush_every - 1 {
+                    segment_writer.flush_term_block().unwrap();
+                }
+            }
+        }
+        segment_writer.finalize().unwrap();
+        segment
+    }
+
+    #[test]
+    fn test_term_blocks_identical_to_buffered_segment() {
+        let sort_by_fields = [
+            None,
+            Some(IndexSortByField {
+                field: "id".to_string(),
+                order: Order::Desc,
+            }),
+        ];
+        for sort_by_field in sort_by_fields {
+            let index = term_blocks_test_index(sort_by_field);
+            let buffered_segment = build_segment_with_term_blocks(&index, None);
+            for flush_every in [1, 7, 60] {
+                let segment = build_segment_with_term_blocks(&index, Some(flush_every));
+                for component in [
+                    SegmentComponent::Terms,
+                    SegmentComponent::Postings,
+                    SegmentComponent::Positions,
+                    SegmentComponent::FastFields,
+                    SegmentComponent::FieldNorms,
+                ] {
+                    let read_component = |segment: &Segment| {
+                        segment
+                            .open_read(component)
+                            .unwrap()
+                            .read_bytes()
+                            .unwrap()
+                            .as_slice()
+                            .to_vec()
+                    };
+                    assert_eq!(
+                        read_component(&segment),
+                        read_component(&buffered_segment),
+                        "{:?} differs when flushing every {flush_every} documents",
+                        segment.relative_path(component)
+                    );
+                }
+                assert!(!index
+                    .directory()
+                    .exists(&segment.relative_path(SegmentComponent::TempTerms))
+                    .unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_writer_term_block_memory_budget() -> crate::Result<()> {
+        let index = term_blocks_test_index(None);
+        let text = index.schema().get_field("text").unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+        assert_eq!(index_writer.get_term_block_memory_budget(), None);
+        // Flushes a term block after each document.
+        index_writer.set_term_block_memory_budget(Some(0));
+        assert_eq!(index_writer.get_term_block_memory_budget(), Some(0));
+        for i in 0..20 {
+            index_writer.add_document(term_blocks_test_doc(&index, i))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let hello_query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqsAndPositions,
+        );
+        let num_hello_docs = (0..20)
+            .filter(|&i| {
+                term_blocks_test_doc(&index, i)
+                    .get_first(text)
+                    .and_then(|value| value.as_text())
+                    .unwrap()
+                    .split(' ')
+                    .any(|word| word == "hello")
+            })
+            .count();
+        assert_eq!(searcher.search(&hello_query, &Count)?, num_hello_docs);
+        Ok(())
+    }
+
+    #[test]
+    fn test_garbage_collect_stale_term_blocks_file() -> crate::Result<()> {
+        let index = term_blocks_test_index(None);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(term_blocks_test_doc(&index, 0))?;
+        index_writer.commit()?;
+        // A `TempTerms` file left behind by a segment, e.g. after a crash.
+        let segment = index.searchable_segments()?.pop().unwrap();
+        let temp_path = segment.relative_path(SegmentComponent::TempTerms);
+        index
+            .directory()
+            .atomic_write(&temp_path, b"stale term blocks")?;
+        index_writer.garbage_collect_files().wait()?;
+        assert!(!index.directory().exists(&temp_path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_indexed_tokens() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_indexing = TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_term_vectors(true)
+            .set_max_indexed_tokens(4);
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default()
+                .set_indexing_options(text_indexing)
+                .set_stored(),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // The limit applies over all of the values of the field.
+        index_writer
+            .add_document(doc!(text => "alpha beta", text => "gamma delta epsilon zeta"))?;
+        index_writer.add_document(doc!(text => "alpha beta"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |word: &str| {
+            TermQuery::new(Term::from_field_text(text, word), IndexRecordOption::Basic)
+        };
+        assert_eq!(searcher.search(&term_query("alpha"), &Count)?, 2);
+        assert_eq!(searcher.search(&term_query("delta"), &Count)?, 1);
+        // The tokens beyond the limit are not searchable.
+        assert_eq!(searcher.search(&term_query("epsilon"), &Count)?, 0);
+        assert_eq!(searcher.search(&term_query("zeta"), &Count)?, 0);
+        let phrase_query = |words: &[&str]| {
+            PhraseQuery::new(
+                words
+                    .iter()
+                    .map(|word| Term::from_field_text(text, word))
+                    .collect(),
+            )
+        };
+        // A phrase query matches if it is within the indexed prefix, not if it straddles the
+        // cutoff.
+        assert_eq!(
+            searcher.search(&phrase_query(&["gamma", "delta"]), &Count)?,
+            1
+        );
+        assert_eq!(
+            searcher.search(&phrase_query(&["delta", "epsilon"]), &Count)?,
+            0
+        );
+        let segment_reader = searcher.segment_reader(0);
+        let fieldnorm_reader = segment_reader.get_fieldnorms_reader(text)?;
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 4);
+        assert_eq!(fieldnorm_reader.fieldnorm(1), 2);
+        let term_vector = segment_reader
+            .term_vectors()
+            .get_field(text)?
+            .term_vector(0)?;
+        assert_eq!(
+            term_vector
+                .iter()
+                .map(|token| token.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["alpha", "beta", "gamma", "delta"]
+        );
+        // The stored value is not truncated.
+        let doc = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(
+            doc.get_all(text)
+                .filter_map(|value| value.as_text())
+                .collect::<Vec<_>>(),
+            vec!["alpha beta", "gamma delta epsilon zeta"]
+        );
+        Ok(())
+    }
 }
@@ -27,6 +27,12 @@ mod atomic_impl {
             self.0.store(val, order);
             val
         }
+
+        pub fn compare_exchange(&self, current: u64, new: u64, order: Ordering) -> bool {
+            self.0
+                .compare_exchange(current, new, order, Ordering::SeqCst)
+                .is_ok()
+        }
     }
 }
 
@@ -59,6 +65,15 @@ mod atomic_impl {
             *lock = val;
             val
         }
+
+        pub fn compare_exchange(&self, current: u64, new: u64, _order: Ordering) -> bool {
+            let mut lock = self.0.write().unwrap();
+            if *lock != current {
+                return false;
+            }
+            *lock = new;
+            true
+        }
     }
 }
 
@@ -94,6 +109,13 @@ impl Stamper {
     pub fn revert(&self, to_opstamp: Opstamp) -> Opstamp {
         self.0.revert(to_opstamp, Ordering::SeqCst)
     }
+
+    /// Gives back `opstamp`, so that it is supplied by the next call to `stamp`, if it is
+    /// the last stamp supplied. Returns false if another stamp was supplied since.
+    pub fn unstamp(&self, opstamp: Opstamp) -> bool {
+        self.0
+            .compare_exchange(opstamp + 1, opstamp, Ordering::SeqCst)
+    }
 }
 
 #[cfg(test)]
@@ -129,4 +151,17 @@ mod test {
         assert_eq!(stamper.stamp(), 6);
         assert_eq!(stamper_clone.stamp(), 7);
     }
+
+    #[test]
+    fn test_stamper_unstamp() {
+        let stamper = Stamper::new(7u64);
+        assert_eq!(stamper.stamp(), 7u64);
+        assert!(stamper.unstamp(7));
+        assert_eq!(stamper.stamp(), 7u64);
+
+        let opstamp = stamper.stamp();
+        assert_eq!(stamper.stamp(), 9u64);
+        assert!(!stamper.unstamp(opstamp));
+        assert_eq!(stamper.stamp(), 10u64);
+    }
 }
@@ -282,6 +282,8 @@ pub mod schema;
 pub mod space_usage;
 pub mod store;
 pub mod termdict;
+pub mod termvector;
+pub mod vector;
 
 mod reader;
 
@@ -299,19 +301,25 @@ use serde::{Deserialize, Serialize};
 
 pub use self::docset::{DocSet, TERMINATED};
 pub use crate::core::{
-    Executor, Index, IndexBuilder, IndexMeta, IndexSettings, IndexSortByField, InvertedIndexReader,
-    Order, Searcher, SearcherGeneration, Segment, SegmentComponent, SegmentId, SegmentMeta,
-    SegmentReader, SingleSegmentIndexWriter,
+    Executor, FieldIndexStats, Index, IndexBuilder, IndexMeta, IndexSettings, IndexSnapshot,
+    IndexSortByField, InvertedIndexReader, Order, Searcher, SearcherGeneration, Segment,
+    SegmentComponent, SegmentId, SegmentMeta, SegmentReader, SingleSegmentIndexWriter,
+    TermPostings, TermPostingsIter, TermStats,
 };
 pub use crate::directory::Directory;
 pub use crate::indexer::demuxer::*;
 pub use crate::indexer::operation::UserOperation;
-pub use crate::indexer::{merge_filtered_segments, merge_indices, IndexWriter, PreparedCommit};
+pub use crate::indexer::{
+    add_fast_field, merge_deduplicated_segments, merge_filtered_segments, merge_indices,
+    merge_indices_with_compatible_schemas, rename_field, IndexWriter, IndexWriterEvent,
+    IndexWriterEventListener, JsonLineError, JsonLineErrorKind, JsonLinesReport,
+    MergeCancellationToken, PreparedCommit, RejectedDocument,
+};
 pub use crate::postings::Postings;
 pub use crate::schema::{DateOptions, DatePrecision, Document, Term};
 
 /// Index format version.
-const INDEX_FORMAT_VERSION: u32 = 4;
+const INDEX_FORMAT_VERSION: u32 = 5;
 
 /// Structure version for the index.
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -409,7 +417,7 @@ impl DocAddress {
 ///
 /// The id used for the segment is actually an ordinal
 /// in the list of `Segment`s held by a `Searcher`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct DocAddress {
     /// The segment ordinal id that identifies the segment
     /// hosting the document in the `Searcher` it is called from.
@@ -26,6 +26,10 @@
 //! * *BitPackedPositionsDeltaBitWidth* := (*BitWidth*: u8)^*NumBitPackedBlocks*
 //! * *VIntPosDeltas* := *VIntPosDelta*^(*P* % 128).
 //!
+//! If the field uses [`PositionsCompression::Bitpacked`](crate::schema::PositionsCompression),
+//! the final block is bitpacked instead:
+//! * *BitPackedPosDeltas* := *BitWidth*: u8 (*PosDelta* bitpacked on *BitWidth* bits)^(*P* % 128)
+//!
 //! The skip widths encoded separately makes it easy and fast to rapidly skip over n positions.
 mod reader;
 mod serializer;
@@ -48,10 +52,18 @@ pub mod tests {
     use super::PositionSerializer;
     use crate::directory::OwnedBytes;
     use crate::positions::reader::PositionReader;
+    use crate::schema::PositionsCompression;
 
     fn create_positions_data(vals: &[u32]) -> crate::Result<OwnedBytes> {
+        create_positions_data_with_compression(vals, PositionsCompression::VInt)
+    }
+
+    fn create_positions_data_with_compression(
+        vals: &[u32],
+        compression: PositionsCompression,
+    ) -> crate::Result<OwnedBytes> {
         let mut positions_buffer = vec![];
-        let mut serializer = PositionSerializer::new(&mut positions_buffer);
+        let mut serializer = PositionSerializer::new(&mut positions_buffer, compression);
         serializer.write_positions_delta(vals);
         serializer.close_term()?;
         serializer.close()?;
@@ -71,9 +83,13 @@ pub mod tests {
 
     proptest! {
         #[test]
-        fn test_position_delta(delta_positions in gen_delta_positions()) {
-            let delta_positions_data = create_positions_data(&delta_positions).unwrap();
-            let mut position_reader = PositionReader::open(delta_positions_data).unwrap();
+        fn test_position_delta(
+            delta_positions in gen_delta_positions(),
+            compression in select(&[PositionsCompression::VInt, PositionsCompression::Bitpacked][..])
+        ) {
+            let delta_positions_data =
+                create_positions_data_with_compression(&delta_positions, compression).unwrap();
+            let mut position_reader = PositionReader::open(delta_positions_data, compression).unwrap();
             let mut minibuf = [0u32; 1];
             for (offset, &delta_position) in delta_positions.iter().enumerate() {
                 position_reader.read(offset as u64, &mut minibuf[..]);
@@ -87,7 +103,7 @@ pub mod tests {
         let position_deltas: Vec<u32> = (0..1000).collect();
         let positions_data = create_positions_data(&position_deltas[..])?;
         assert_eq!(positions_data.len(), 1224);
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         for &n in &[1, 10, 127, 128, 130, 312] {
             let mut v = vec![0u32; n];
             position_reader.read(0, &mut v[..]);
@@ -98,21 +114,44 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_position_read_bitpacked() -> crate::Result<()> {
+        let position_deltas: Vec<u32> = (0..1000).map(|i| i % 13).collect();
+        let vint_data = create_positions_data(&position_deltas[..])?;
+        let positions_data = create_positions_data_with_compression(
+            &position_deltas[..],
+            PositionsCompression::Bitpacked,
+        )?;
+        // 7 bitpacked blocks of 4 bits, then 104 deltas on 4 bits instead of one byte each.
+        assert_eq!(vint_data.len(), 1 + 7 + 7 * 64 + 104);
+        assert_eq!(positions_data.len(), 1 + 7 + 7 * 64 + 1 + 52);
+        let mut position_reader =
+            PositionReader::open(positions_data, PositionsCompression::Bitpacked)?;
+        for &offset in &[0u64, 1u64, 127u64, 128u64, 130u64, 895u64, 896u64, 990u64] {
+            let mut v = vec![0u32; 1000 - offset as usize];
+            position_reader.read(offset, &mut v[..]);
+            assert_eq!(&v[..], &position_deltas[offset as usize..]);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_empty_position() -> crate::Result<()> {
         let mut positions_buffer = vec![];
-        let mut serializer = PositionSerializer::new(&mut positions_buffer);
+        let mut serializer =
+            PositionSerializer::new(&mut positions_buffer, PositionsCompression::VInt);
         serializer.close_term()?;
         serializer.close()?;
         let position_delta = OwnedBytes::new(positions_buffer);
-        assert!(PositionReader::open(position_delta).is_ok());
+        assert!(PositionReader::open(position_delta, PositionsCompression::VInt).is_ok());
         Ok(())
     }
 
     #[test]
     fn test_multiple_write_positions() -> crate::Result<()> {
         let mut positions_buffer = vec![];
-        let mut serializer = PositionSerializer::new(&mut positions_buffer);
+        let mut serializer =
+            PositionSerializer::new(&mut positions_buffer, PositionsCompression::VInt);
         serializer.write_positions_delta(&[1u32, 12u32]);
         serializer.write_positions_delta(&[4u32, 17u32]);
         serializer.write_positions_delta(&[443u32]);
@@ -120,7 +159,7 @@ pub mod tests {
         serializer.close()?;
         let position_delta = OwnedBytes::new(positions_buffer);
         let mut output_delta_pos_buffer = vec![0u32; 5];
-        let mut position_reader = PositionReader::open(position_delta)?;
+        let mut position_reader = PositionReader::open(position_delta, PositionsCompression::VInt)?;
         position_reader.read(0, &mut output_delta_pos_buffer[..]);
         assert_eq!(
             &output_delta_pos_buffer[..],
@@ -134,7 +173,7 @@ pub mod tests {
         let position_deltas: Vec<u32> = (0..1000).collect();
         let positions_data = create_positions_data(&position_deltas[..])?;
         assert_eq!(positions_data.len(), 1224);
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         for &offset in &[1u64, 10u64, 127u64, 128u64, 130u64, 312u64] {
             for &len in &[1, 10, 130, 500] {
                 let mut v = vec![0u32; len];
@@ -153,7 +192,7 @@ pub mod tests {
         let positions_data = create_positions_data(&position_deltas[..])?;
         assert_eq!(positions_data.len(), 1224);
 
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         let mut buf = [0u32; 7];
         let mut c = 0;
 
@@ -175,7 +214,7 @@ pub mod tests {
         let positions_delta: Vec<u32> = (0..2_000_000).collect();
         let positions_data = create_positions_data(&positions_delta[..])?;
         assert_eq!(positions_data.len(), 5003499);
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         let mut buf = [0u32; 256];
         position_reader.read(128, &mut buf);
         for i in 0..256 {
@@ -194,7 +233,7 @@ pub mod tests {
         let positions_data = create_positions_data(&positions_delta[..])?;
         assert_eq!(positions_data.len(), 533);
         let mut buf = [0u32; 1];
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         position_reader.read(230, &mut buf);
         assert_eq!(buf[0], 230);
         position_reader.read(9, &mut buf);
@@ -208,7 +247,7 @@ pub mod tests {
         let positions_delta: Vec<u32> = iter::repeat(CONST_VAL).take(2_000_000).collect();
         let positions_data = create_positions_data(&positions_delta[..])?;
         assert_eq!(positions_data.len(), 1_015_627);
-        let mut position_reader = PositionReader::open(positions_data)?;
+        let mut position_reader = PositionReader::open(positions_data, PositionsCompression::VInt)?;
         let mut buf = [0u32; 1];
         position_reader.read(0, &mut buf);
         assert_eq!(buf[0], CONST_VAL);
@@ -227,7 +266,8 @@ pub mod tests {
             128 * 1024 + 7,
             128 * 10 * 1024 + 10,
         ] {
-            let mut position_reader = PositionReader::open(positions_data.clone())?;
+            let mut position_reader =
+                PositionReader::open(positions_data.clone(), PositionsCompression::VInt)?;
             let mut buf = [0u32; 1];
             position_reader.read(offset, &mut buf);
             assert_eq!(buf[0], offset as u32);
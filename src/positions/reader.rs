@@ -5,6 +5,7 @@ use common::{BinarySerializable, VInt};
 use crate::directory::OwnedBytes;
 use crate::positions::COMPRESSION_BLOCK_SIZE;
 use crate::postings::compression::{BlockDecoder, VIntDecoder};
+use crate::schema::PositionsCompression;
 
 /// When accessing the positions of a term, we get a positions_idx from the `Terminfo`.
 /// This means we need to skip to the `nth` position efficiently.
@@ -33,6 +34,8 @@ pub struct PositionReader {
     // As we advance, anchor increases simultaneously with bit_widths and positions get consumed.
     anchor_offset: u64,
 
+    compression: PositionsCompression,
+
     // These are just copies used for .reset().
     original_bit_widths: OwnedBytes,
     original_positions: OwnedBytes,
@@ -40,7 +43,12 @@ pub struct PositionReader {
 
 impl PositionReader {
     /// Open and reads the term positions encoded into the positions_data owned bytes.
-    pub fn open(mut positions_data: OwnedBytes) -> io::Result<PositionReader> {
+    ///
+    /// `compression` must be the encoding the positions were serialized with.
+    pub fn open(
+        mut positions_data: OwnedBytes,
+        compression: PositionsCompression,
+    ) -> io::Result<PositionReader> {
         let num_positions_bitpacked_blocks = VInt::deserialize(&mut positions_data)?.0 as usize;
         let (bit_widths, positions) = positions_data.split(num_positions_bitpacked_blocks);
         Ok(PositionReader {
@@ -49,6 +57,7 @@ impl PositionReader {
             block_decoder: BlockDecoder::default(),
             block_offset: i64::MAX as u64,
             anchor_offset: 0u64,
+            compression,
             original_bit_widths: bit_widths,
             original_positions: positions,
         })
@@ -94,9 +103,15 @@ impl PositionReader {
             self.block_decoder
                 .uncompress_block_unsorted(compressed_data, bit_width);
         } else {
-            // that block is vint encoded.
-            self.block_decoder
-                .uncompress_vint_unsorted_until_end(compressed_data);
+            // that block is the last, incomplete, block.
+            match self.compression {
+                PositionsCompression::VInt => self
+                    .block_decoder
+                    .uncompress_vint_unsorted_until_end(compressed_data),
+                PositionsCompression::Bitpacked => self
+                    .block_decoder
+                    .uncompress_bitpacked_tail_until_end(compressed_data),
+            }
         }
         self.block_offset = self.anchor_offset + (block_rel_id * COMPRESSION_BLOCK_SIZE) as u64;
     }
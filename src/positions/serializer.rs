@@ -4,6 +4,7 @@ use common::{BinarySerializable, CountingWriter, VInt};
 
 use crate::positions::COMPRESSION_BLOCK_SIZE;
 use crate::postings::compression::{BlockEncoder, VIntEncoder};
+use crate::schema::PositionsCompression;
 
 /// The PositionSerializer is in charge of serializing all of the positions
 /// of all of the terms of a given field.
@@ -15,17 +16,21 @@ pub struct PositionSerializer<W: io::Write> {
     positions_buffer: Vec<u8>,
     block: Vec<u32>,
     bit_widths: Vec<u8>,
+    compression: PositionsCompression,
 }
 
 impl<W: io::Write> PositionSerializer<W> {
     /// Creates a new PositionSerializer writing into the given positions_wrt.
-    pub fn new(positions_wrt: W) -> PositionSerializer<W> {
+    ///
+    /// `compression` defines how the last incomplete block of each term is encoded.
+    pub fn new(positions_wrt: W, compression: PositionsCompression) -> PositionSerializer<W> {
         PositionSerializer {
             block_encoder: BlockEncoder::new(),
             positions_wrt: CountingWriter::wrap(positions_wrt),
             positions_buffer: Vec::with_capacity(128_000),
             block: Vec::with_capacity(128),
             bit_widths: Vec::new(),
+            compression,
         }
     }
 
@@ -68,8 +73,15 @@ impl<W: io::Write> PositionSerializer<W> {
             self.positions_buffer.extend(block_encoded);
         } else {
             debug_assert!(self.block.len() < COMPRESSION_BLOCK_SIZE);
-            let block_vint_encoded = self.block_encoder.compress_vint_unsorted(&self.block[..]);
-            self.positions_buffer.extend_from_slice(block_vint_encoded);
+            let block_encoded = match self.compression {
+                PositionsCompression::VInt => {
+                    self.block_encoder.compress_vint_unsorted(&self.block[..])
+                }
+                PositionsCompression::Bitpacked => {
+                    self.block_encoder.compress_bitpacked_tail(&self.block[..])
+                }
+            };
+            self.positions_buffer.extend_from_slice(block_encoded);
         }
         self.block.clear();
     }
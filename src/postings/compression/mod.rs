@@ -1,5 +1,6 @@
 use bitpacking::{BitPacker, BitPacker4x};
 use common::FixedSize;
+use tantivy_bitpacker::{compute_num_bits, BitPacker as TailBitPacker, BitUnpacker};
 
 pub const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
 const COMPRESSED_BLOCK_MAX_SIZE: usize = COMPRESSION_BLOCK_SIZE * u32::SIZE_IN_BYTES;
@@ -47,6 +48,30 @@ impl BlockEncoder {
             .compress(block, &mut self.output[..], num_bits);
         (num_bits, &self.output[..written_size])
     }
+
+    /// Compresses a block of less than `COMPRESSION_BLOCK_SIZE` integers, by
+    /// bitpacking them after a one byte header holding the bit width.
+    ///
+    /// Contrary to variable byte encoding, values do not need to use at least one byte each.
+    pub fn compress_bitpacked_tail(&mut self, block: &[u32]) -> &[u8] {
+        debug_assert!(block.len() < COMPRESSION_BLOCK_SIZE);
+        let max_val = block.iter().cloned().max().unwrap_or(0u32);
+        let num_bits = compute_num_bits(max_val as u64);
+        let mut output: &mut [u8] = &mut self.output[..];
+        output[0] = num_bits;
+        output = &mut output[1..];
+        let mut bitpacker = TailBitPacker::new();
+        for &val in block {
+            bitpacker
+                .write(val as u64, num_bits, &mut output)
+                .expect("Compressed block cannot exceed the output buffer");
+        }
+        bitpacker
+            .flush(&mut output)
+            .expect("Compressed block cannot exceed the output buffer");
+        let written_size = COMPRESSED_BLOCK_MAX_SIZE - output.len();
+        &self.output[..written_size]
+    }
 }
 
 #[derive(Clone)]
@@ -88,6 +113,36 @@ impl BlockDecoder {
             .decompress(compressed_data, &mut self.output, num_bits)
     }
 
+    /// Uncompresses a block encoded with `BlockEncoder::compress_bitpacked_tail`, decoding
+    /// values until the end of `compressed_data`.
+    ///
+    /// The number of values is not encoded, so when the last byte is not entirely used,
+    /// the output may contain trailing extra values. Callers are expected to only read
+    /// the values they wrote.
+    pub fn uncompress_bitpacked_tail_until_end(&mut self, compressed_data: &[u8]) {
+        self.output_len = 0;
+        if compressed_data.is_empty() {
+            return;
+        }
+        let num_bits = compressed_data[0];
+        let data = &compressed_data[1..];
+        if num_bits == 0 {
+            self.output.iter_mut().for_each(|el| *el = 0u32);
+            self.output_len = COMPRESSION_BLOCK_SIZE;
+            return;
+        }
+        // The bit unpacker reads 8 bytes at a time, so we copy the data to a padded buffer.
+        let mut padded_data = [0u8; COMPRESSED_BLOCK_MAX_SIZE + 8];
+        let data = &data[..data.len().min(COMPRESSED_BLOCK_MAX_SIZE)];
+        padded_data[..data.len()].copy_from_slice(data);
+        let num_els = (data.len() * 8 / num_bits as usize).min(COMPRESSION_BLOCK_SIZE);
+        let bit_unpacker = BitUnpacker::new(num_bits);
+        for (idx, el) in self.output[..num_els].iter_mut().enumerate() {
+            *el = bit_unpacker.get(idx as u64, &padded_data[..]) as u32;
+        }
+        self.output_len = num_els;
+    }
+
     #[inline]
     pub fn output_array(&self) -> &[u32] {
         &self.output[..self.output_len]
@@ -305,6 +360,24 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_encode_bitpacked_tail() {
+        let mut encoder = BlockEncoder::new();
+        for input in [
+            vec![],
+            vec![0u32; 5],
+            vec![3u32, 1u32, 2u32],
+            (0u32..127u32).map(|i| i * 7 % 13).collect(),
+            vec![u32::MAX; 127],
+        ] {
+            let encoded_data = encoder.compress_bitpacked_tail(&input);
+            let mut decoder = BlockDecoder::default();
+            decoder.uncompress_bitpacked_tail_until_end(encoded_data);
+            assert!(decoder.output_array().len() >= input.len());
+            assert_eq!(&decoder.output_array()[..input.len()], &input[..]);
+        }
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
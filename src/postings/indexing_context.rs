@@ -24,4 +24,18 @@ impl IndexingContext {
     pub(crate) fn mem_usage(&self) -> usize {
         self.term_index.mem_usage() + self.arena.mem_usage()
     }
+
+    /// Returns the memory usage of the buffered terms and of their postings, in bytes.
+    ///
+    /// Unlike [`IndexingContext::mem_usage`], it does not include the term hash map table,
+    /// which is kept by [`IndexingContext::clear`].
+    pub(crate) fn buffered_terms_mem_usage(&self) -> usize {
+        self.term_index.arena_mem_usage() + self.arena.mem_usage()
+    }
+
+    /// Removes all of the buffered terms and their postings.
+    pub(crate) fn clear(&mut self) {
+        self.term_index.clear();
+        self.arena = MemoryArena::new();
+    }
 }
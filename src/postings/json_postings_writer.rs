@@ -5,6 +5,7 @@ use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::postings::postings_writer::SpecializedPostingsWriter;
 use crate::postings::recorder::{BufferLender, NothingRecorder, Recorder};
 use crate::postings::stacker::Addr;
+use crate::postings::term_blocks::TermPostingsBuffer;
 use crate::postings::{
     FieldSerializer, IndexingContext, IndexingPosition, PostingsWriter, UnorderedTermId,
 };
@@ -91,6 +92,33 @@ impl<Rec: Recorder> PostingsWriter for JsonPostingsWriter<Rec> {
         Ok(())
     }
 
+    fn buffer_term_postings(
+        &self,
+        term: &Term<&[u8]>,
+        addr: Addr,
+        ctx: &IndexingContext,
+        buffer_lender: &mut BufferLender,
+        postings: &mut TermPostingsBuffer,
+    ) {
+        if let Some((_, typ, _)) = as_json_path_type_value_bytes(term.value_bytes()) {
+            if typ == Type::Str {
+                SpecializedPostingsWriter::<Rec>::buffer_one_term(
+                    addr,
+                    ctx,
+                    buffer_lender,
+                    postings,
+                );
+            } else {
+                SpecializedPostingsWriter::<NothingRecorder>::buffer_one_term(
+                    addr,
+                    ctx,
+                    buffer_lender,
+                    postings,
+                );
+            }
+        }
+    }
+
     fn total_num_tokens(&self) -> u64 {
         self.str_posting_writer.total_num_tokens() + self.non_str_posting_writer.total_num_tokens()
     }
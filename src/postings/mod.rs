@@ -16,6 +16,7 @@ mod segment_postings;
 mod serializer;
 mod skip;
 mod stacker;
+mod term_blocks;
 mod term_info;
 
 pub use self::block_segment_postings::BlockSegmentPostings;
@@ -27,6 +28,7 @@ pub use self::segment_postings::SegmentPostings;
 pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
 pub(crate) use self::skip::{BlockInfo, SkipReader};
 pub(crate) use self::stacker::compute_table_size;
+pub(crate) use self::term_blocks::{TermBlocks, TermBlocksWriter};
 pub use self::term_info::TermInfo;
 
 pub(crate) type UnorderedTermId = u64;
@@ -10,17 +10,16 @@ use crate::fastfield::MultiValuedFastFieldWriter;
 use crate::fieldnorm::FieldNormReaders;
 use crate::indexer::doc_id_mapping::DocIdMapping;
 use crate::postings::recorder::{BufferLender, Recorder};
+use crate::postings::term_blocks::{TermBlockReader, TermPostingsBuffer};
 use crate::postings::{
-    FieldSerializer, IndexingContext, InvertedIndexSerializer, PerFieldPostingsWriter,
+    FieldSerializer, IndexingContext, InvertedIndexSerializer, PerFieldPostingsWriter, TermBlocks,
     UnorderedTermId,
 };
-use crate::schema::{Field, FieldType, Schema, Term};
+use crate::schema::{Field, FieldType, Schema, Term, DEFAULT_POSITION_GAP};
 use crate::termdict::TermOrdinal;
 use crate::tokenizer::{Token, TokenStream, MAX_TOKEN_LEN};
 use crate::DocId;
 
-const POSITION_GAP: u32 = 1;
-
 fn make_field_partition(
     term_offsets: &[(Term<&[u8]>, Addr, UnorderedTermId)],
 ) -> Vec<(Field, Range<usize>)> {
@@ -49,8 +48,12 @@ fn make_field_partition(
 /// Serialize the inverted index.
 /// It pushes all term, one field at a time, towards the
 /// postings serializer.
+///
+/// If term blocks were flushed while the segment was being built, they are stitched with the
+/// terms still in memory.
 pub(crate) fn serialize_postings(
     ctx: IndexingContext,
+    term_blocks_opt: Option<&TermBlocks>,
     per_field_postings_writers: &PerFieldPostingsWriter,
     fieldnorm_readers: FieldNormReaders,
     doc_id_map: Option<&DocIdMapping>,
@@ -61,6 +64,18 @@ pub(crate) fn serialize_postings(
         Vec::with_capacity(ctx.term_index.len());
     term_offsets.extend(ctx.term_index.iter());
     term_offsets.sort_unstable_by_key(|(k, _, _)| k.clone());
+    if let Some(term_blocks) = term_blocks_opt {
+        return serialize_stitched_postings(
+            &ctx,
+            &term_offsets,
+            term_blocks,
+            per_field_postings_writers,
+            fieldnorm_readers,
+            doc_id_map,
+            schema,
+            serializer,
+        );
+    }
     let mut unordered_term_mappings: HashMap<Field, FnvHashMap<UnorderedTermId, TermOrdinal>> =
         HashMap::new();
 
@@ -89,6 +104,7 @@ pub(crate) fn serialize_postings(
             | FieldType::Bool(_) => {}
             FieldType::Bytes(_) => {}
             FieldType::JsonObject(_) => {}
+            FieldType::DenseVector(_) => {}
         }
 
         let postings_writer = per_field_postings_writers.get_for_field(field);
@@ -106,10 +122,139 @@ pub(crate) fn serialize_postings(
     Ok(unordered_term_mappings)
 }
 
-#[derive(Default)]
+/// Returns the smallest term that remains to be serialized, among the current terms of the
+/// term blocks and the next in-memory term.
+fn next_term_to_serialize<'a>(
+    block_readers: &[TermBlockReader<'a>],
+    next_in_memory_term: Option<&Term<&'a [u8]>>,
+) -> Option<Term<&'a [u8]>> {
+    block_readers
+        .iter()
+        .filter_map(|block_reader| block_reader.term())
+        .chain(next_in_memory_term.cloned())
+        .min()
+}
+
+/// Serializes the inverted index by merging the sorted term blocks with the in-memory terms.
+///
+/// The blocks were flushed in the order of the documents, so the documents of a term are
+/// gathered by increasing doc id by reading the blocks in the order they were written, and
+/// the in-memory terms last.
+#[allow(clippy::too_many_arguments)]
+fn serialize_stitched_postings(
+    ctx: &IndexingContext,
+    term_offsets: &[(Term<&[u8]>, Addr, UnorderedTermId)],
+    term_blocks: &TermBlocks,
+    per_field_postings_writers: &PerFieldPostingsWriter,
+    fieldnorm_readers: FieldNormReaders,
+    doc_id_map: Option<&DocIdMapping>,
+    schema: &Schema,
+    serializer: &mut InvertedIndexSerializer,
+) -> crate::Result<HashMap<Field, FnvHashMap<UnorderedTermId, TermOrdinal>>> {
+    let mut block_readers = term_blocks.readers()?;
+    let mut in_memory_terms = term_offsets.iter().peekable();
+    let mut unordered_term_mappings: HashMap<Field, FnvHashMap<UnorderedTermId, TermOrdinal>> =
+        HashMap::new();
+    let mut buffer_lender = BufferLender::default();
+    let mut postings = TermPostingsBuffer::default();
+    let mut unordered_term_ids = Vec::new();
+    while let Some(first_term) = next_term_to_serialize(
+        &block_readers,
+        in_memory_terms.peek().map(|(term, _, _)| term),
+    ) {
+        let field = first_term.field();
+        // Populating the (unordered term ord) -> (ordered term ord) mapping, as in
+        // `serialize_postings`.
+        let maps_term_ordinals = matches!(
+            schema.get_field_entry(field).field_type(),
+            FieldType::Str(_) | FieldType::Facet(_)
+        );
+        let mut mapping: FnvHashMap<UnorderedTermId, TermOrdinal> = FnvHashMap::default();
+        let postings_writer = per_field_postings_writers.get_for_field(field);
+        let fieldnorm_reader = fieldnorm_readers.get_field(field)?;
+        let mut field_serializer =
+            serializer.new_field(field, postings_writer.total_num_tokens(), fieldnorm_reader)?;
+        while let Some(term) = next_term_to_serialize(
+            &block_readers,
+            in_memory_terms.peek().map(|(term, _, _)| term),
+        )
+        .filter(|term| term.field() == field)
+        {
+            postings.clear();
+            unordered_term_ids.clear();
+            for block_reader in &mut block_readers {
+                if block_reader.term().as_ref() == Some(&term) {
+                    block_reader.read_postings(&mut postings)?;
+                    unordered_term_ids.push(block_reader.unordered_term_id());
+                    block_reader.advance()?;
+                }
+            }
+            if let Some((_, addr, unordered_term_id)) =
+                in_memory_terms.next_if(|(in_memory_term, _, _)| *in_memory_term == term)
+            {
+                postings_writer.buffer_term_postings(
+                    &term,
+                    *addr,
+                    ctx,
+                    &mut buffer_lender,
+                    &mut postings,
+                );
+                unordered_term_ids.push(*unordered_term_id);
+            }
+            if postings.is_empty() {
+                continue;
+            }
+            let term_ord =
+                field_serializer.new_term(term.value_bytes(), postings.term_doc_freq())?;
+            postings.write_to(doc_id_map, &mut field_serializer);
+            field_serializer.close_term()?;
+            if maps_term_ordinals {
+                mapping.extend(
+                    unordered_term_ids
+                        .iter()
+                        .map(|&unordered_term_id| (unordered_term_id, term_ord)),
+                );
+            }
+        }
+        field_serializer.close()?;
+        if maps_term_ordinals {
+            unordered_term_mappings.insert(field, mapping);
+        }
+    }
+    Ok(unordered_term_mappings)
+}
+
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Number of positions skipped after each indexed value.
+    pub position_gap: u32,
+    /// Number of tokens after which the tokens are not indexed anymore.
+    pub max_num_tokens: Option<u32>,
+}
+
+impl IndexingPosition {
+    pub fn with_position_gap(position_gap: u32) -> IndexingPosition {
+        IndexingPosition {
+            num_tokens: 0,
+            end_position: 0,
+            position_gap,
+            max_num_tokens: None,
+        }
+    }
+
+    /// Returns the number of tokens that can still be indexed, or `None` if the number of
+    /// tokens is not limited.
+    pub fn num_remaining_tokens(&self) -> Option<u32> {
+        self.max_num_tokens
+            .map(|max_num_tokens| max_num_tokens.saturating_sub(self.num_tokens))
+    }
+}
+
+impl Default for IndexingPosition {
+    fn default() -> Self {
+        IndexingPosition::with_position_gap(DEFAULT_POSITION_GAP)
+    }
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -142,6 +287,16 @@ pub(crate) trait PostingsWriter: Send + Sync {
         serializer: &mut FieldSerializer,
     ) -> io::Result<()>;
 
+    /// Appends the postings of a term to `postings`, without remapping the doc ids.
+    fn buffer_term_postings(
+        &self,
+        term: &Term<&[u8]>,
+        addr: Addr,
+        ctx: &IndexingContext,
+        buffer_lender: &mut BufferLender,
+        postings: &mut TermPostingsBuffer,
+    );
+
     /// Tokenize a text and subscribe all of its token.
     fn index_text(
         &mut self,
@@ -155,7 +310,10 @@ pub(crate) trait PostingsWriter: Send + Sync {
         let end_of_path_idx = term_buffer.as_slice().len();
         let mut num_tokens = 0;
         let mut end_position = 0;
-        token_stream.process(&mut |token: &Token| {
+        let max_num_tokens = indexing_position.num_remaining_tokens().unwrap_or(u32::MAX);
+        // The tokenization stops as soon as the maximum number of tokens is reached.
+        while num_tokens < max_num_tokens && token_stream.advance() {
+            let token: &Token = token_stream.token();
             // We skip all tokens with a len greater than u16.
             if token.text.len() > MAX_TOKEN_LEN {
                 warn!(
@@ -164,7 +322,7 @@ pub(crate) trait PostingsWriter: Send + Sync {
                     token.text.len(),
                     MAX_TOKEN_LEN
                 );
-                return;
+                continue;
             }
             term_buffer.truncate(end_of_path_idx);
             term_buffer.append_bytes(token.text.as_bytes());
@@ -176,9 +334,9 @@ pub(crate) trait PostingsWriter: Send + Sync {
             }
 
             num_tokens += 1;
-        });
+        }
 
-        indexing_position.end_position = end_position + POSITION_GAP;
+        indexing_position.end_position = end_position + indexing_position.position_gap;
         indexing_position.num_tokens += num_tokens;
         term_buffer.truncate(end_of_path_idx);
     }
@@ -219,6 +377,18 @@ impl<Rec: Recorder> SpecializedPostingsWriter<Rec> {
         serializer.close_term()?;
         Ok(())
     }
+
+    #[inline]
+    pub(crate) fn buffer_one_term(
+        addr: Addr,
+        ctx: &IndexingContext,
+        buffer_lender: &mut BufferLender,
+        postings: &mut TermPostingsBuffer,
+    ) {
+        let recorder: Rec = ctx.term_index.read(addr);
+        postings.add_term_doc_freq(recorder.term_doc_freq().unwrap_or(0u32));
+        recorder.serialize(&ctx.arena, None, postings, buffer_lender);
+    }
 }
 
 impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
@@ -264,6 +434,17 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
         Ok(())
     }
 
+    fn buffer_term_postings(
+        &self,
+        _term: &Term<&[u8]>,
+        addr: Addr,
+        ctx: &IndexingContext,
+        buffer_lender: &mut BufferLender,
+        postings: &mut TermPostingsBuffer,
+    ) {
+        Self::buffer_one_term(addr, ctx, buffer_lender, postings);
+    }
+
     fn total_num_tokens(&self) -> u64 {
         self.total_num_tokens
     }
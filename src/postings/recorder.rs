@@ -47,6 +47,20 @@ impl<'a> Iterator for VInt32Reader<'a> {
     }
 }
 
+/// Receives the postings of a term pushed by a [`Recorder`], one document at a time.
+pub(crate) trait DocSink {
+    /// Writes a document containing the term, along with its term frequency and the
+    /// delta-encoded positions of the term.
+    fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]);
+}
+
+impl<'a> DocSink for FieldSerializer<'a> {
+    #[inline]
+    fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
+        FieldSerializer::write_doc(self, doc_id, term_freq, position_deltas);
+    }
+}
+
 /// Recorder is in charge of recording relevant information about
 /// the presence of a term in a document.
 ///
@@ -68,11 +82,11 @@ pub(crate) trait Recorder: Copy + Default + Send + Sync + 'static {
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, arena: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
-    fn serialize(
+    fn serialize<S: DocSink>(
         &self,
         arena: &MemoryArena,
         doc_id_map: Option<&DocIdMapping>,
-        serializer: &mut FieldSerializer<'_>,
+        serializer: &mut S,
         buffer_lender: &mut BufferLender,
     );
     /// Returns the number of document containing this term.
@@ -111,11 +125,11 @@ impl Recorder for NothingRecorder {
 
     fn close_doc(&mut self, _arena: &mut MemoryArena) {}
 
-    fn serialize(
+    fn serialize<S: DocSink>(
         &self,
         arena: &MemoryArena,
         doc_id_map: Option<&DocIdMapping>,
-        serializer: &mut FieldSerializer<'_>,
+        serializer: &mut S,
         buffer_lender: &mut BufferLender,
     ) {
         let (buffer, doc_ids) = buffer_lender.lend_all();
@@ -184,11 +198,11 @@ impl Recorder for TermFrequencyRecorder {
         self.current_tf = 0;
     }
 
-    fn serialize(
+    fn serialize<S: DocSink>(
         &self,
         arena: &MemoryArena,
         doc_id_map: Option<&DocIdMapping>,
-        serializer: &mut FieldSerializer<'_>,
+        serializer: &mut S,
         buffer_lender: &mut BufferLender,
     ) {
         let buffer = buffer_lender.lend_u8();
@@ -257,11 +271,11 @@ impl Recorder for TfAndPositionRecorder {
         self.stack.writer(arena).write_u32_vint(POSITION_END);
     }
 
-    fn serialize(
+    fn serialize<S: DocSink>(
         &self,
         arena: &MemoryArena,
         doc_id_map: Option<&DocIdMapping>,
-        serializer: &mut FieldSerializer<'_>,
+        serializer: &mut S,
         buffer_lender: &mut BufferLender,
     ) {
         let (buffer_u8, buffer_positions) = buffer_lender.lend_all();
@@ -137,7 +137,10 @@ impl<'a> FieldSerializer<'a> {
             fieldnorm_reader,
         );
         let positions_serializer_opt = if index_record_option.has_positions() {
-            Some(PositionSerializer::new(positions_write))
+            Some(PositionSerializer::new(
+                positions_write,
+                field_type.positions_compression(),
+            ))
         } else {
             None
         };
@@ -160,10 +163,19 @@ impl<'a> FieldSerializer<'a> {
                 0u64
             } as usize;
         let addr = self.postings_serializer.written_bytes() as usize;
+        // Without fieldnorms, all of the documents are considered to have the lowest
+        // fieldnorm id.
+        let min_fieldnorm_id = if self.postings_serializer.fieldnorm_reader.is_some() {
+            u8::MAX
+        } else {
+            0u8
+        };
         TermInfo {
             doc_freq: 0,
             postings_range: addr..addr,
             positions_range: positions_start..positions_start,
+            max_term_freq: 0,
+            min_fieldnorm_id,
         }
     }
 
@@ -198,6 +210,15 @@ impl<'a> FieldSerializer<'a> {
     /// on the configuration of the field in the `Schema`.
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
         self.current_term_info.doc_freq += 1;
+        // Term frequencies are 0 if the field does not record them, but the documents are
+        // then scored as if the term appeared once.
+        self.current_term_info.max_term_freq =
+            self.current_term_info.max_term_freq.max(term_freq.max(1));
+        if let Some(fieldnorm_reader) = self.postings_serializer.fieldnorm_reader.as_ref() {
+            let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc_id);
+            self.current_term_info.min_fieldnorm_id =
+                self.current_term_info.min_fieldnorm_id.min(fieldnorm_id);
+        }
         self.postings_serializer.write_doc(doc_id, term_freq);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
             assert_eq!(term_freq as usize, position_deltas.len());
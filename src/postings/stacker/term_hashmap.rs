@@ -55,6 +55,9 @@ pub struct TermHashMap {
     mask: usize,
     occupied: Vec<usize>,
     len: usize,
+    // Unordered term id of the first term of the hashmap. It is non-zero once the hashmap has
+    // been cleared, so that the unordered term ids remain unique.
+    first_term_id: UnorderedTermId,
 }
 
 struct QuadraticProbing {
@@ -116,9 +119,24 @@ impl TermHashMap {
             mask: table_size_power_of_2 - 1,
             occupied: Vec::with_capacity(table_size_power_of_2 / 2),
             len: 0,
+            first_term_id: 0,
         }
     }
 
+    /// Removes all of the terms, keeping the capacity of the table.
+    ///
+    /// The unordered term ids of the terms inserted afterwards keep increasing, so that an
+    /// unordered term id is never reused.
+    pub(crate) fn clear(&mut self) {
+        for &bucket in &self.occupied {
+            self.table[bucket] = KeyValue::default();
+        }
+        self.occupied.clear();
+        self.first_term_id += self.len as UnorderedTermId;
+        self.len = 0;
+        self.memory_arena = MemoryArena::new();
+    }
+
     pub fn read<Item: Copy + 'static>(&self, addr: Addr) -> Item {
         self.memory_arena.read(addr)
     }
@@ -131,6 +149,11 @@ impl TermHashMap {
         self.table.len() * mem::size_of::<KeyValue>()
     }
 
+    /// Returns the memory usage of the arena storing the keys and the values, in bytes.
+    pub fn arena_mem_usage(&self) -> usize {
+        self.memory_arena.mem_usage()
+    }
+
     fn is_saturated(&self) -> bool {
         self.table.len() < self.occupied.len() * 3
     }
@@ -155,7 +178,7 @@ impl TermHashMap {
 
     fn set_bucket(&mut self, hash: u32, key_value_addr: Addr, bucket: usize) -> UnorderedTermId {
         self.occupied.push(bucket);
-        let unordered_term_id = self.len as UnorderedTermId;
+        let unordered_term_id = self.first_term_id + self.len as UnorderedTermId;
         self.len += 1;
         self.table[bucket] = KeyValue {
             key_value_addr,
@@ -279,6 +302,29 @@ mod tests {
         assert_eq!(vanilla_hash_map.len(), 2);
     }
 
+    #[test]
+    fn test_hash_map_clear() {
+        let mut hash_map: TermHashMap = TermHashMap::new(1 << 10);
+        assert_eq!(hash_map.mutate_or_create(b"abc", |_: Option<u32>| 3u32), 0);
+        assert_eq!(hash_map.mutate_or_create(b"abcd", |_: Option<u32>| 4u32), 1);
+        hash_map.clear();
+        assert_eq!(hash_map.len(), 0);
+        assert_eq!(hash_map.iter().count(), 0);
+        // The terms inserted after the hashmap is cleared get new unordered term ids.
+        assert_eq!(
+            hash_map.mutate_or_create(b"abc", |opt_val: Option<u32>| {
+                assert_eq!(opt_val, None);
+                5u32
+            }),
+            2
+        );
+        let terms: Vec<(Vec<u8>, u64)> = hash_map
+            .iter()
+            .map(|(key, _, unordered_term_id)| (key.as_slice().to_vec(), unordered_term_id))
+            .collect();
+        assert_eq!(terms, vec![(b"abc".to_vec(), 2)]);
+    }
+
     #[test]
     fn test_compute_previous_power_of_two() {
         assert_eq!(compute_previous_power_of_two(8), 8);
@@ -0,0 +1,346 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
+use common::{CountingWriter, VInt};
+
+use crate::core::{Segment, SegmentComponent};
+use crate::directory::{Directory, OwnedBytes, TerminatingWrite, WritePtr};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::postings::recorder::{BufferLender, DocSink};
+use crate::postings::{IndexingContext, PerFieldPostingsWriter, UnorderedTermId};
+use crate::schema::Term;
+use crate::DocId;
+
+/// A document of the postings of a term, buffered by a `TermPostingsBuffer`.
+struct BufferedDoc {
+    doc_id: DocId,
+    term_freq: u32,
+    positions: Range<usize>,
+}
+
+/// Postings of a term, gathered from one or more term blocks and from the in-memory terms.
+#[derive(Default)]
+pub(crate) struct TermPostingsBuffer {
+    term_doc_freq: u32,
+    docs: Vec<BufferedDoc>,
+    position_deltas: Vec<u32>,
+}
+
+impl TermPostingsBuffer {
+    pub fn clear(&mut self) {
+        self.term_doc_freq = 0;
+        self.docs.clear();
+        self.position_deltas.clear();
+    }
+
+    /// Adds the document frequency of the term, as recorded by a recorder.
+    pub fn add_term_doc_freq(&mut self, term_doc_freq: u32) {
+        self.term_doc_freq += term_doc_freq;
+    }
+
+    /// Returns the document frequency of the term, as it would be recorded by a recorder
+    /// buffering all of its postings.
+    pub fn term_doc_freq(&self) -> u32 {
+        self.term_doc_freq
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Pushes the buffered documents to `sink`, in the order of the new doc ids if a doc id
+    /// mapping is given.
+    pub fn write_to<S: DocSink>(&mut self, doc_id_map: Option<&DocIdMapping>, sink: &mut S) {
+        if let Some(doc_id_map) = doc_id_map {
+            for doc in &mut self.docs {
+                doc.doc_id = doc_id_map.get_new_doc_id(doc.doc_id);
+            }
+            self.docs.sort_unstable_by_key(|doc| doc.doc_id);
+        }
+        for doc in &self.docs {
+            sink.write_doc(
+                doc.doc_id,
+                doc.term_freq,
+                &self.position_deltas[doc.positions.clone()],
+            );
+        }
+    }
+}
+
+impl DocSink for TermPostingsBuffer {
+    fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
+        let positions_start = self.position_deltas.len();
+        self.position_deltas.extend_from_slice(position_deltas);
+        self.docs.push(BufferedDoc {
+            doc_id,
+            term_freq,
+            positions: positions_start..self.position_deltas.len(),
+        });
+    }
+}
+
+/// Writes the sorted blocks of terms and postings flushed while a segment is being built to
+/// the `TempTerms` file of the segment.
+///
+/// Each term of a block is encoded as:
+/// - the length of the term, and the term itself, including its field,
+/// - its unordered term id,
+/// - the length of its postings, and the postings: the document frequency of the term, the number
+///   of documents, and for each document the doc id, the term frequency, the number of positions
+///   and the delta-encoded positions.
+///
+/// All of the numbers are VInt-encoded.
+pub(crate) struct TermBlocksWriter {
+    segment: Segment,
+    write: CountingWriter<WritePtr>,
+    block_start: u64,
+    block_ranges: Vec<Range<usize>>,
+    buffer: Vec<u8>,
+}
+
+impl TermBlocksWriter {
+    pub fn open(segment: &mut Segment) -> crate::Result<TermBlocksWriter> {
+        let write = segment.open_write(SegmentComponent::TempTerms)?;
+        Ok(TermBlocksWriter {
+            segment: segment.clone(),
+            write: CountingWriter::wrap(write),
+            block_start: 0,
+            block_ranges: Vec::new(),
+            buffer: Vec::new(),
+        })
+    }
+
+    fn write_term(
+        &mut self,
+        term: &[u8],
+        unordered_term_id: UnorderedTermId,
+        postings: &TermPostingsBuffer,
+    ) -> io::Result<()> {
+        self.buffer.clear();
+        VInt(postings.term_doc_freq as u64).serialize_into_vec(&mut self.buffer);
+        VInt(postings.docs.len() as u64).serialize_into_vec(&mut self.buffer);
+        for doc in &postings.docs {
+            let position_deltas = &postings.position_deltas[doc.positions.clone()];
+            VInt(doc.doc_id as u64).serialize_into_vec(&mut self.buffer);
+            VInt(doc.term_freq as u64).serialize_into_vec(&mut self.buffer);
+            VInt(position_deltas.len() as u64).serialize_into_vec(&mut self.buffer);
+            for &position_delta in position_deltas {
+                VInt(position_delta as u64).serialize_into_vec(&mut self.buffer);
+            }
+        }
+        let mut header = Vec::with_capacity(term.len() + 30);
+        VInt(term.len() as u64).serialize_into_vec(&mut header);
+        header.extend_from_slice(term);
+        VInt(unordered_term_id).serialize_into_vec(&mut header);
+        VInt(self.buffer.len() as u64).serialize_into_vec(&mut header);
+        self.write.write_all(&header)?;
+        self.write.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    /// Writes the buffered terms of `ctx` and their postings as a new sorted block.
+    pub fn write_block(
+        &mut self,
+        ctx: &IndexingContext,
+        per_field_postings_writers: &PerFieldPostingsWriter,
+    ) -> io::Result<()> {
+        let mut term_offsets: Vec<_> = ctx.term_index.iter().collect();
+        term_offsets.sort_unstable_by_key(|(term, _, _)| term.clone());
+        let mut buffer_lender = BufferLender::default();
+        let mut postings = TermPostingsBuffer::default();
+        for (term, addr, unordered_term_id) in term_offsets {
+            postings.clear();
+            per_field_postings_writers
+                .get_for_field(term.field())
+                .buffer_term_postings(&term, addr, ctx, &mut buffer_lender, &mut postings);
+            if !postings.is_empty() {
+                self.write_term(term.as_slice(), unordered_term_id, &postings)?;
+            }
+        }
+        let block_end = self.write.written_bytes();
+        self.block_ranges
+            .push(self.block_start as usize..block_end as usize);
+        self.block_start = block_end;
+        Ok(())
+    }
+
+    /// Closes the `TempTerms` file and reads the blocks back.
+    ///
+    /// The file is deleted right away: the data stays available until the returned
+    /// `TermBlocks` is dropped.
+    pub fn finish(self) -> crate::Result<TermBlocks> {
+        self.write.finish().terminate()?;
+        let data = self
+            .segment
+            .open_read(SegmentComponent::TempTerms)?
+            .read_bytes()?;
+        let temp_path = self.segment.relative_path(SegmentComponent::TempTerms);
+        if let Err(delete_err) = self.segment.index().directory().delete(&temp_path) {
+            warn!(
+                "Failed to delete the temporary term blocks file {:?}: {:?}",
+                temp_path, delete_err
+            );
+        }
+        Ok(TermBlocks {
+            data,
+            block_ranges: self.block_ranges,
+        })
+    }
+}
+
+/// Sorted blocks of terms and postings, written by a [`TermBlocksWriter`].
+pub(crate) struct TermBlocks {
+    data: OwnedBytes,
+    block_ranges: Vec<Range<usize>>,
+}
+
+impl TermBlocks {
+    /// Returns a reader for each block, in the order the blocks were written, positioned on
+    /// their first term.
+    ///
+    /// Returns an error if the first term of a block is corrupted.
+    pub fn readers(&self) -> io::Result<Vec<TermBlockReader<'_>>> {
+        self.block_ranges
+            .iter()
+            .map(|block_range| {
+                let mut reader = TermBlockReader {
+                    data: &self.data.as_slice()[block_range.clone()],
+                    term: &[],
+                    unordered_term_id: 0,
+                    postings: &[],
+                };
+                reader.advance()?;
+                Ok(reader)
+            })
+            .collect()
+    }
+}
+
+/// Reads the terms of a block, by increasing order.
+pub(crate) struct TermBlockReader<'a> {
+    data: &'a [u8],
+    term: &'a [u8],
+    unordered_term_id: UnorderedTermId,
+    postings: &'a [u8],
+}
+
+fn corrupted_term_block() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Corrupted term block")
+}
+
+fn read_vint(data: &mut &[u8]) -> io::Result<u64> {
+    VInt::deserialize_u64(data)
+}
+
+fn read_u32_vint(data: &mut &[u8]) -> io::Result<u32> {
+    u32::try_from(read_vint(data)?).map_err(|_| corrupted_term_block())
+}
+
+/// Splits the first `len` bytes off `data`.
+fn read_bytes<'a>(data: &mut &'a [u8], len: u64) -> io::Result<&'a [u8]> {
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= data.len())
+        .ok_or_else(corrupted_term_block)?;
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+impl<'a> TermBlockReader<'a> {
+    /// Advances to the next term of the block.
+    ///
+    /// Returns `false` if there are no more terms, in which case the current term is empty.
+    /// Returns an error if the block is truncated or corrupted.
+    pub fn advance(&mut self) -> io::Result<bool> {
+        if self.data.is_empty() {
+            self.term = &[];
+            return Ok(false);
+        }
+        let term_len = read_vint(&mut self.data)?;
+        self.term = read_bytes(&mut self.data, term_len)?;
+        self.unordered_term_id = read_vint(&mut self.data)?;
+        let postings_len = read_vint(&mut self.data)?;
+        self.postings = read_bytes(&mut self.data, postings_len)?;
+        Ok(true)
+    }
+
+    /// Returns the current term, or `None` if all of the terms of the block have been read.
+    pub fn term(&self) -> Option<Term<&'a [u8]>> {
+        if self.term.is_empty() {
+            None
+        } else {
+            Some(Term::wrap(self.term))
+        }
+    }
+
+    pub fn unordered_term_id(&self) -> UnorderedTermId {
+        self.unordered_term_id
+    }
+
+    /// Appends the postings of the current term to `postings`.
+    ///
+    /// Returns an error if the postings are truncated or corrupted.
+    pub fn read_postings(&self, postings: &mut TermPostingsBuffer) -> io::Result<()> {
+        let mut data = self.postings;
+        postings.add_term_doc_freq(read_u32_vint(&mut data)?);
+        let num_docs = read_u32_vint(&mut data)?;
+        let mut position_deltas = Vec::new();
+        for _ in 0..num_docs {
+            let doc_id = read_u32_vint(&mut data)?;
+            let term_freq = read_u32_vint(&mut data)?;
+            let num_positions = read_u32_vint(&mut data)?;
+            position_deltas.clear();
+            for _ in 0..num_positions {
+                position_deltas.push(read_u32_vint(&mut data)?);
+            }
+            postings.write_doc(doc_id, term_freq, &position_deltas);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use common::VInt;
+
+    use super::{TermBlocks, TermPostingsBuffer};
+    use crate::directory::OwnedBytes;
+
+    fn term_blocks(data: Vec<u8>) -> TermBlocks {
+        let block_range = 0..data.len();
+        TermBlocks {
+            data: OwnedBytes::new(data),
+            block_ranges: vec![block_range],
+        }
+    }
+
+    #[test]
+    fn test_term_block_reader_corrupted() {
+        let mut data = Vec::new();
+        // A term of length 100, of which only 3 bytes are written.
+        VInt(100).serialize_into_vec(&mut data);
+        data.extend_from_slice(b"abc");
+        let err = term_blocks(data).readers().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut data = Vec::new();
+        VInt(3).serialize_into_vec(&mut data);
+        data.extend_from_slice(b"abc");
+        VInt(0).serialize_into_vec(&mut data);
+        // Postings claiming 2 documents, with the doc id of the first one only.
+        let mut postings = Vec::new();
+        for val in [2, 2, 7] {
+            VInt(val).serialize_into_vec(&mut postings);
+        }
+        VInt(postings.len() as u64).serialize_into_vec(&mut data);
+        data.extend_from_slice(&postings);
+        let term_blocks = term_blocks(data);
+        let readers = term_blocks.readers().unwrap();
+        assert!(readers[0]
+            .read_postings(&mut TermPostingsBuffer::default())
+            .is_err());
+    }
+}
@@ -4,6 +4,9 @@ use std::ops::Range;
 
 use common::{BinarySerializable, FixedSize};
 
+use crate::query::Bm25Weight;
+use crate::Score;
+
 /// `TermInfo` wraps the metadata associated to a Term.
 /// It is segment-local.
 #[derive(Debug, Default, Eq, PartialEq, Clone)]
@@ -14,9 +17,57 @@ pub struct TermInfo {
     pub postings_range: Range<usize>,
     /// Byte range of the positions of this terms in the positions (`.pos`) file.
     pub positions_range: Range<usize>,
+    /// Highest term frequency of the term in a document of the segment.
+    ///
+    /// It is 1 if the field does not record the term frequencies, and 0 if it is unknown,
+    /// for the segments written before tantivy 0.19.
+    pub max_term_freq: u32,
+    /// Lowest fieldnorm id of the documents of the segment containing the term.
+    ///
+    /// It is 0 if the field does not record fieldnorms.
+    pub min_fieldnorm_id: u8,
 }
 
 impl TermInfo {
+    /// Size of a `TermInfo` serialized without its `max_term_freq` and `min_fieldnorm_id`.
+    pub(crate) const SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES: usize =
+        3 * u32::SIZE_IN_BYTES + 2 * u64::SIZE_IN_BYTES;
+
+    /// Returns an upper bound of the score of the term for any document of the segment, or
+    /// `None` if the segment was written before tantivy 0.19, which did not record it.
+    ///
+    /// The BM25 score increases with the term frequency, and decreases with the fieldnorm, so
+    /// that the score of `max_term_freq` and `min_fieldnorm_id` upper-bounds the score of
+    /// every document containing the term, whatever the average fieldnorm `bm25_weight` was
+    /// computed with.
+    pub fn max_score(&self, bm25_weight: &Bm25Weight) -> Option<Score> {
+        if self.max_term_freq == 0 {
+            return None;
+        }
+        Some(bm25_weight.score(self.min_fieldnorm_id, self.max_term_freq))
+    }
+
+    /// Deserializes a `TermInfo` serialized without its `max_term_freq` and
+    /// `min_fieldnorm_id`, as in the term dictionaries written before tantivy 0.19.
+    pub(crate) fn deserialize_without_max_impacts<R: io::Read>(
+        reader: &mut R,
+    ) -> io::Result<TermInfo> {
+        let doc_freq = u32::deserialize(reader)?;
+        let postings_start_offset = u64::deserialize(reader)? as usize;
+        let postings_num_bytes = u32::deserialize(reader)? as usize;
+        let postings_end_offset = postings_start_offset + postings_num_bytes;
+        let positions_start_offset = u64::deserialize(reader)? as usize;
+        let positions_num_bytes = u32::deserialize(reader)? as usize;
+        let positions_end_offset = positions_start_offset + positions_num_bytes;
+        Ok(TermInfo {
+            doc_freq,
+            postings_range: postings_start_offset..postings_end_offset,
+            positions_range: positions_start_offset..positions_end_offset,
+            max_term_freq: 0,
+            min_fieldnorm_id: 0,
+        })
+    }
+
     pub(crate) fn posting_num_bytes(&self) -> u32 {
         let num_bytes = self.postings_range.len();
         assert!(num_bytes <= u32::MAX as usize);
@@ -35,9 +86,13 @@ impl FixedSize for TermInfo {
     /// This is large, but in practise, `TermInfo` are encoded in blocks and
     /// only the first `TermInfo` of a block is serialized uncompressed.
     /// The subsequent `TermInfo` are delta encoded and bitpacked.
-    const SIZE_IN_BYTES: usize = 3 * u32::SIZE_IN_BYTES + 2 * u64::SIZE_IN_BYTES;
+    const SIZE_IN_BYTES: usize =
+        TermInfo::SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES + MAX_IMPACTS_NUM_BYTES;
 }
 
+/// Size of the `max_term_freq` and `min_fieldnorm_id` of a serialized `TermInfo`.
+const MAX_IMPACTS_NUM_BYTES: usize = u32::SIZE_IN_BYTES + u8::SIZE_IN_BYTES;
+
 impl BinarySerializable for TermInfo {
     fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         self.doc_freq.serialize(writer)?;
@@ -45,22 +100,16 @@ impl BinarySerializable for TermInfo {
         self.posting_num_bytes().serialize(writer)?;
         (self.positions_range.start as u64).serialize(writer)?;
         self.positions_num_bytes().serialize(writer)?;
+        self.max_term_freq.serialize(writer)?;
+        self.min_fieldnorm_id.serialize(writer)?;
         Ok(())
     }
 
     fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<Self> {
-        let doc_freq = u32::deserialize(reader)?;
-        let postings_start_offset = u64::deserialize(reader)? as usize;
-        let postings_num_bytes = u32::deserialize(reader)? as usize;
-        let postings_end_offset = postings_start_offset + postings_num_bytes;
-        let positions_start_offset = u64::deserialize(reader)? as usize;
-        let positions_num_bytes = u32::deserialize(reader)? as usize;
-        let positions_end_offset = positions_start_offset + positions_num_bytes;
-        Ok(TermInfo {
-            doc_freq,
-            postings_range: postings_start_offset..postings_end_offset,
-            positions_range: positions_start_offset..positions_end_offset,
-        })
+        let mut term_info = TermInfo::deserialize_without_max_impacts(reader)?;
+        term_info.max_term_freq = u32::deserialize(reader)?;
+        term_info.min_fieldnorm_id = u8::deserialize(reader)?;
+        Ok(term_info)
     }
 }
 
@@ -68,7 +117,10 @@ impl BinarySerializable for TermInfo {
 mod tests {
 
     use super::TermInfo;
+    use crate::query::{Bm25Weight, Scorer, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
     use crate::tests::fixed_size_test;
+    use crate::{DocSet, Index, Term, TERMINATED};
 
     // TODO add serialize/deserialize test for terminfo
 
@@ -76,4 +128,50 @@ mod tests {
     fn test_fixed_size() {
         fixed_size_test::<TermInfo>();
     }
+
+    #[test]
+    fn test_term_info_max_score_upper_bounds_scores() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let words = ["a", "b", "c", "d", "e"];
+        for doc_id in 0..300usize {
+            let num_tokens = 1 + (doc_id * 7) % 13;
+            let doc_text: Vec<&str> = (0..num_tokens)
+                .map(|i| words[(doc_id * i + i * i) % words.len()])
+                .collect();
+            index_writer.add_document(doc!(text => doc_text.join(" ")))?;
+            if doc_id == 150 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        for word in words {
+            let term = Term::from_field_text(text, word);
+            let bm25_weight = Bm25Weight::for_terms(&searcher, std::slice::from_ref(&term))?;
+            let term_query = TermQuery::new(term.clone(), IndexRecordOption::WithFreqs);
+            let weight = term_query.specialized_weight(&searcher, true)?;
+            for segment_reader in searcher.segment_readers() {
+                let term_info = segment_reader
+                    .inverted_index(text)?
+                    .get_term_info(&term)?
+                    .unwrap();
+                let max_score = term_info.max_score(&bm25_weight).unwrap();
+                let mut scorer = weight.specialized_scorer(segment_reader, 1.0)?;
+                assert_eq!(scorer.max_score(), max_score);
+                let mut max_observed_score = 0.0;
+                while scorer.doc() != TERMINATED {
+                    let score = scorer.score();
+                    assert!(score <= max_score);
+                    max_observed_score = score.max(max_observed_score);
+                    scorer.advance();
+                }
+                assert!(max_observed_score > 0.0);
+            }
+        }
+        Ok(())
+    }
 }
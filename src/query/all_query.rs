@@ -8,6 +8,8 @@ use crate::{DocId, Score};
 /// Query that matches all of the documents.
 ///
 /// All of the document get the score 1.0.
+///
+/// Like for any other query, deleted documents are not matched.
 #[derive(Clone, Debug)]
 pub struct AllQuery;
 
@@ -22,13 +24,14 @@ pub struct AllWeight;
 
 impl Weight for AllWeight {
     fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
-        let all_scorer = AllScorer {
-            doc: 0u32,
-            max_doc: reader.max_doc(),
-        };
+        let all_scorer = AllScorer::new(reader.max_doc());
         Ok(Box::new(BoostScorer::new(all_scorer, boost)))
     }
 
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        Ok(reader.num_docs())
+    }
+
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
         if doc >= reader.max_doc() {
             return Err(does_not_match(doc));
@@ -43,6 +46,14 @@ pub struct AllScorer {
     max_doc: DocId,
 }
 
+impl AllScorer {
+    /// Creates a scorer matching all of the documents in `0..max_doc`.
+    pub fn new(max_doc: DocId) -> AllScorer {
+        let doc = if max_doc == 0 { TERMINATED } else { 0u32 };
+        AllScorer { doc, max_doc }
+    }
+}
+
 impl DocSet for AllScorer {
     fn advance(&mut self) -> DocId {
         if self.doc + 1 >= self.max_doc {
@@ -70,11 +81,12 @@ impl Scorer for AllScorer {
 
 #[cfg(test)]
 mod tests {
-    use super::AllQuery;
-    use crate::docset::TERMINATED;
+    use super::{AllQuery, AllScorer};
+    use crate::collector::{Count, TopDocs};
+    use crate::docset::{DocSet, TERMINATED};
     use crate::query::Query;
-    use crate::schema::{Schema, TEXT};
-    use crate::Index;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, Term};
 
     fn create_test_index() -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
@@ -132,4 +144,35 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_all_scorer_empty_segment() {
+        let mut scorer = AllScorer::new(0);
+        assert_eq!(scorer.doc(), TERMINATED);
+        assert_eq!(scorer.advance(), TERMINATED);
+    }
+
+    #[test]
+    fn test_all_query_excludes_deleted_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..5 {
+            index_writer.add_document(doc!(id => i.to_string()))?;
+        }
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(id, "1"));
+        index_writer.delete_term(Term::from_field_text(id, "3"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(AllQuery.count(&searcher)?, 3);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 3);
+        let top_docs = searcher.search(&AllQuery, &TopDocs::with_limit(10))?;
+        let mut docs: Vec<u32> = top_docs.iter().map(|(_, doc)| doc.doc_id).collect();
+        docs.sort_unstable();
+        assert_eq!(docs, vec![0, 2, 4]);
+        assert!(top_docs.iter().all(|(score, _)| *score == 1.0));
+        Ok(())
+    }
 }
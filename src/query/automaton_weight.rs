@@ -1,11 +1,13 @@
+use std::collections::BTreeSet;
 use std::io;
+use std::ops::Bound;
 use std::sync::Arc;
 
 use common::BitSet;
 use tantivy_fst::Automaton;
 
-use crate::core::SegmentReader;
-use crate::query::{BitSetDocSet, ConstScorer, Explanation, Scorer, Weight};
+use crate::core::{Searcher, SegmentReader};
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, MaxExpansionsBehavior, Scorer, Weight};
 use crate::schema::{Field, IndexRecordOption};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::{DocId, Score, TantivyError};
@@ -14,6 +16,8 @@ use crate::{DocId, Score, TantivyError};
 pub struct AutomatonWeight<A> {
     field: Field,
     automaton: Arc<A>,
+    // Upper bound of the matching terms searched for, set when the terms are truncated.
+    upper_bound: Bound<Vec<u8>>,
 }
 
 impl<A> AutomatonWeight<A>
@@ -26,16 +30,68 @@ where
         AutomatonWeight {
             field,
             automaton: automaton.into(),
+            upper_bound: Bound::Unbounded,
         }
     }
 
+    /// Caps the number of terms the automaton matches over the segments of `searcher` to
+    /// `max_expansions`.
+    ///
+    /// Matching more terms is an error, or, with [`MaxExpansionsBehavior::Truncate`], only the
+    /// `max_expansions` smallest matching terms are searched for. At most
+    /// `max_expansions + 1` terms are read from each term dictionary.
+    pub(crate) fn limit_expansions(
+        mut self,
+        searcher: &Searcher,
+        max_expansions: usize,
+        max_expansions_behavior: MaxExpansionsBehavior,
+    ) -> crate::Result<AutomatonWeight<A>> {
+        let mut matching_keys: BTreeSet<Vec<u8>> = BTreeSet::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            // The `max_expansions + 1` smallest matching terms of each segment are enough to
+            // get the `max_expansions + 1` smallest matching terms overall.
+            let mut term_stream = self.automaton_stream(inverted_index.terms())?;
+            let mut num_keys = 0;
+            while num_keys <= max_expansions && term_stream.advance() {
+                matching_keys.insert(term_stream.key().to_vec());
+                num_keys += 1;
+            }
+        }
+        if matching_keys.len() <= max_expansions {
+            return Ok(self);
+        }
+        if max_expansions_behavior == MaxExpansionsBehavior::Error {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Query on field {:?} matches more than {} terms",
+                searcher.schema().get_field_name(self.field),
+                max_expansions
+            )));
+        }
+        self.upper_bound = match max_expansions.checked_sub(1) {
+            Some(last_key_rank) => Bound::Included(
+                matching_keys
+                    .into_iter()
+                    .nth(last_key_rank)
+                    .expect("More than `max_expansions` keys"),
+            ),
+            // Nothing is smaller than the empty key.
+            None => Bound::Excluded(Vec::new()),
+        };
+        Ok(self)
+    }
+
     fn automaton_stream<'a>(
         &'a self,
         term_dict: &'a TermDictionary,
     ) -> io::Result<TermStreamer<'a, &'a A>> {
         let automaton: &A = &*self.automaton;
         let term_stream_builder = term_dict.search(automaton);
-        term_stream_builder.into_stream()
+        match &self.upper_bound {
+            Bound::Included(upper_bound) => term_stream_builder.le(upper_bound).into_stream(),
+            Bound::Excluded(upper_bound) => term_stream_builder.lt(upper_bound).into_stream(),
+            Bound::Unbounded => term_stream_builder.into_stream(),
+        }
     }
 }
 
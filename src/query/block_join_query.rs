@@ -0,0 +1,439 @@
+use std::fmt;
+
+use common::BitSet;
+
+use crate::docset::{DocSet, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, Explanation, Query, Scorer, Weight};
+use crate::schema::Schema;
+use crate::{DocId, Score, Searcher, SegmentReader, Term};
+
+/// Defines how the scores of the matching children of a parent are combined into the score
+/// of the parent, in a [`BlockJoinQuery`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockJoinScoreMode {
+    /// Parents get a constant score of 1.
+    None,
+    /// Average of the scores of the matching children.
+    Avg,
+    /// Highest score of the matching children.
+    Max,
+    /// Sum of the scores of the matching children.
+    Total,
+}
+
+/// `BlockJoinQuery` matches parent documents having at least one child document matching
+/// a child query.
+///
+/// # Document blocks
+///
+/// Parents and children are identified by their position in the index: a parent is indexed
+/// right after its children, as a block added with
+/// [`IndexWriter::add_document_block`](crate::IndexWriter::add_document_block). The block
+/// then lives contiguously in one segment, and stays contiguous through merges.
+///
+/// Parent documents are marked by the `parents_query`, typically a term query on a field
+/// only set on parents (e.g. `doc_type:order`). A child matching the child query is joined
+/// to the first parent following it. Documents matched by both queries are parents and are
+/// never considered as children.
+///
+/// The join relies on the order of the documents within segments. It is therefore not
+/// supported for indices sorted by a field, and blocks have to be deleted as a whole, e.g. by
+/// a term shared by the parent and its children: deleting only a parent would attach its
+/// children to the next parent.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{BlockJoinQuery, BlockJoinScoreMode, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, STORED, STRING};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let doc_type = schema_builder.add_text_field("doc_type", STRING);
+/// let order_id = schema_builder.add_text_field("order_id", STRING | STORED);
+/// let product = schema_builder.add_text_field("product", STRING);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document_block(
+///     vec![doc!(product => "apple"), doc!(product => "pear")],
+///     doc!(doc_type => "order", order_id => "order1"),
+/// )?;
+/// index_writer.add_document_block(
+///     vec![doc!(product => "pear")],
+///     doc!(doc_type => "order", order_id => "order2"),
+/// )?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = BlockJoinQuery::new(
+///     Box::new(TermQuery::new(
+///         Term::from_field_text(product, "apple"),
+///         IndexRecordOption::Basic,
+///     )),
+///     Box::new(TermQuery::new(
+///         Term::from_field_text(doc_type, "order"),
+///         IndexRecordOption::Basic,
+///     )),
+///     BlockJoinScoreMode::Max,
+/// );
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+/// assert_eq!(top_docs.len(), 1);
+/// let order = searcher.doc(top_docs[0].1)?;
+/// assert_eq!(order.get_first(order_id).unwrap().as_text(), Some("order1"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlockJoinQuery {
+    child_query: Box<dyn Query>,
+    parents_query: Box<dyn Query>,
+    score_mode: BlockJoinScoreMode,
+}
+
+impl BlockJoinQuery {
+    /// Creates a `BlockJoinQuery` matching the parents, as marked by `parents_query`, of the
+    /// documents matching `child_query`.
+    pub fn new(
+        child_query: Box<dyn Query>,
+        parents_query: Box<dyn Query>,
+        score_mode: BlockJoinScoreMode,
+    ) -> BlockJoinQuery {
+        BlockJoinQuery {
+            child_query,
+            parents_query,
+            score_mode,
+        }
+    }
+}
+
+impl Clone for BlockJoinQuery {
+    fn clone(&self) -> Self {
+        BlockJoinQuery {
+            child_query: self.child_query.box_clone(),
+            parents_query: self.parents_query.box_clone(),
+            score_mode: self.score_mode,
+        }
+    }
+}
+
+impl fmt::Debug for BlockJoinQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BlockJoin(child_query={:?}, parents_query={:?}, score_mode={:?})",
+            self.child_query, self.parents_query, self.score_mode
+        )
+    }
+}
+
+impl Query for BlockJoinQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let child_weight = self.child_query.weight(searcher, scoring_enabled)?;
+        let parents_weight = self.parents_query.weight(searcher, false)?;
+        Ok(Box::new(BlockJoinWeight {
+            child_weight,
+            parents_weight,
+            score_mode: self.score_mode,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.child_query.query_terms(visitor)
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.child_query.validate(schema)?;
+        self.parents_query.validate(schema)
+    }
+}
+
+struct BlockJoinWeight {
+    child_weight: Box<dyn Weight>,
+    parents_weight: Box<dyn Weight>,
+    score_mode: BlockJoinScoreMode,
+}
+
+impl BlockJoinWeight {
+    fn block_join_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<BlockJoinScorer> {
+        let mut parents = BitSet::with_max_value(reader.max_doc());
+        self.parents_weight
+            .for_each(reader, &mut |doc, _| parents.insert(doc))?;
+        let mut parents = BitSetDocSet::from(parents);
+        let mut child_scorer = self.child_weight.scorer(reader, boost)?;
+        let mut parent_docs = Vec::new();
+        let mut parent_scores = Vec::new();
+        let mut num_children = Vec::new();
+        let mut child = child_scorer.doc();
+        while child != TERMINATED {
+            if !reader.is_deleted(child) {
+                let parent = if parents.doc() < child {
+                    parents.seek(child)
+                } else {
+                    parents.doc()
+                };
+                if parent == TERMINATED {
+                    // Documents after the last parent are orphans.
+                    break;
+                }
+                if parent != child {
+                    let child_score = child_scorer.score();
+                    if parent_docs.last() == Some(&parent) {
+                        let parent_score = parent_scores.last_mut().unwrap();
+                        if self.score_mode == BlockJoinScoreMode::Max {
+                            *parent_score = child_score.max(*parent_score);
+                        } else {
+                            *parent_score += child_score;
+                        }
+                        *num_children.last_mut().unwrap() += 1;
+                    } else {
+                        parent_docs.push(parent);
+                        parent_scores.push(child_score);
+                        num_children.push(1u32);
+                    }
+                }
+            }
+            child = child_scorer.advance();
+        }
+        match self.score_mode {
+            BlockJoinScoreMode::None => parent_scores.fill(1.0),
+            BlockJoinScoreMode::Avg => {
+                for (parent_score, &num_children) in parent_scores.iter_mut().zip(&num_children) {
+                    *parent_score /= num_children as Score;
+                }
+            }
+            BlockJoinScoreMode::Max | BlockJoinScoreMode::Total => {}
+        }
+        Ok(BlockJoinScorer {
+            parent_docs,
+            parent_scores,
+            num_children,
+            cursor: 0,
+        })
+    }
+}
+
+impl Weight for BlockJoinWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.block_join_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.block_join_scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new(
+            format!(
+                "BlockJoinQuery {:?} of {} matching children",
+                self.score_mode, scorer.num_children[scorer.cursor]
+            ),
+            scorer.score(),
+        ))
+    }
+}
+
+/// Scorer over the parents of the matching children of a segment.
+struct BlockJoinScorer {
+    parent_docs: Vec<DocId>,
+    parent_scores: Vec<Score>,
+    num_children: Vec<u32>,
+    cursor: usize,
+}
+
+impl DocSet for BlockJoinScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.parent_docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.parent_docs
+            .get(self.cursor)
+            .copied()
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.parent_docs.len() as u32
+    }
+}
+
+impl Scorer for BlockJoinScorer {
+    fn score(&mut self) -> Score {
+        self.parent_scores[self.cursor]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockJoinQuery, BlockJoinScoreMode};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{Query, TermQuery};
+    use crate::schema::{Field, IndexRecordOption, Schema, STORED, STRING, TEXT};
+    use crate::{assert_nearly_equals, Index, Searcher, Term};
+
+    fn term_query(field: Field, text: &str) -> Box<dyn Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, text),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    fn matching_orders(searcher: &Searcher, query: &dyn Query) -> crate::Result<Vec<String>> {
+        let order_id = searcher.schema().get_field("order_id").unwrap();
+        let mut orders = Vec::new();
+        for (_, doc_address) in searcher.search(query, &TopDocs::with_limit(10))? {
+            let doc = searcher.doc(doc_address)?;
+            orders.push(
+                doc.get_first(order_id)
+                    .unwrap()
+                    .as_text()
+                    .unwrap()
+                    .to_string(),
+            );
+        }
+        orders.sort();
+        Ok(orders)
+    }
+
+    #[test]
+    fn test_block_join_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let doc_type = schema_builder.add_text_field("doc_type", STRING);
+        let order_id = schema_builder.add_text_field("order_id", STRING | STORED);
+        let product = schema_builder.add_text_field("product", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document_block(
+            vec![
+                doc!(order_id => "order1", product => "green apple"),
+                doc!(order_id => "order1", product => "pear"),
+            ],
+            doc!(doc_type => "order", order_id => "order1"),
+        )?;
+        index_writer.add_document_block(
+            vec![doc!(order_id => "order2", product => "pear")],
+            doc!(doc_type => "order", order_id => "order2"),
+        )?;
+        index_writer.commit()?;
+        // An order without line item, in another segment.
+        index_writer.add_document_block(vec![], doc!(doc_type => "order", order_id => "order3"))?;
+        index_writer.add_document_block(
+            vec![
+                doc!(order_id => "order4", product => "apple"),
+                doc!(order_id => "order4", product => "apple apple"),
+                doc!(order_id => "order4", product => "kiwi"),
+            ],
+            doc!(doc_type => "order", order_id => "order4"),
+        )?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let orders_query = || term_query(doc_type, "order");
+        let block_join = |child_text: &str, score_mode| {
+            BlockJoinQuery::new(term_query(product, child_text), orders_query(), score_mode)
+        };
+        let apple_orders = block_join("apple", BlockJoinScoreMode::Max);
+        assert_eq!(
+            matching_orders(&searcher, &apple_orders)?,
+            vec!["order1", "order4"]
+        );
+        assert_eq!(searcher.search(&apple_orders, &Count)?, 2);
+        assert_eq!(
+            matching_orders(&searcher, &block_join("pear", BlockJoinScoreMode::Avg))?,
+            vec!["order1", "order2"]
+        );
+        assert_eq!(
+            matching_orders(&searcher, &block_join("kiwi", BlockJoinScoreMode::None))?,
+            vec!["order4"]
+        );
+        assert!(
+            matching_orders(&searcher, &block_join("banana", BlockJoinScoreMode::Total))?
+                .is_empty()
+        );
+        // The parents are never joined to themselves.
+        let parents_as_children = BlockJoinQuery::new(
+            term_query(doc_type, "order"),
+            orders_query(),
+            BlockJoinScoreMode::Max,
+        );
+        assert_eq!(searcher.search(&parents_as_children, &Count)?, 0);
+
+        // Deleting the line items of a block by their order id.
+        {
+            index_writer.delete_term(Term::from_field_text(order_id, "order1"));
+            index_writer.add_document_block(
+                vec![doc!(order_id => "order1", product => "pear")],
+                doc!(doc_type => "order", order_id => "order1"),
+            )?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(matching_orders(&searcher, &apple_orders)?, vec!["order4"]);
+        assert_eq!(
+            matching_orders(&searcher, &block_join("pear", BlockJoinScoreMode::Max))?,
+            vec!["order1", "order2"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_join_query_score_mode() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let doc_type = schema_builder.add_text_field("doc_type", STRING);
+        let product = schema_builder.add_text_field("product", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document_block(
+            vec![
+                doc!(product => "apple"),
+                doc!(product => "apple apple"),
+                doc!(product => "kiwi"),
+            ],
+            doc!(doc_type => "order"),
+        )?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let apple_query = term_query(product, "apple");
+        let child_scores: Vec<f32> = [0u32, 1u32]
+            .iter()
+            .map(|&doc| {
+                apple_query
+                    .explain(&searcher, crate::DocAddress::new(0, doc))
+                    .map(|explanation| explanation.value())
+            })
+            .collect::<crate::Result<_>>()?;
+        let parent_score = |score_mode| -> crate::Result<f32> {
+            let query = BlockJoinQuery::new(
+                apple_query.box_clone(),
+                term_query(doc_type, "order"),
+                score_mode,
+            );
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+            assert_eq!(top_docs[0].1, crate::DocAddress::new(0, 3));
+            Ok(top_docs[0].0)
+        };
+        assert_nearly_equals!(
+            parent_score(BlockJoinScoreMode::Max)?,
+            child_scores[0].max(child_scores[1])
+        );
+        assert_nearly_equals!(
+            parent_score(BlockJoinScoreMode::Total)?,
+            child_scores[0] + child_scores[1]
+        );
+        assert_nearly_equals!(
+            parent_score(BlockJoinScoreMode::Avg)?,
+            (child_scores[0] + child_scores[1]) / 2.0
+        );
+        assert_nearly_equals!(parent_score(BlockJoinScoreMode::None)?, 1.0);
+        Ok(())
+    }
+}
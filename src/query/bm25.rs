@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::fieldnorm::FieldNormReader;
 use crate::query::Explanation;
+use crate::schema::FieldType;
 use crate::{Score, Searcher, Term};
 
 const K1: Score = 1.2;
@@ -32,6 +33,8 @@ pub struct Bm25Params {
     pub avg_fieldnorm: Score,
 }
 
+/// Weight of a term, or of a set of terms, computing the BM25 score of the documents
+/// containing it from their term frequency and fieldnorm.
 #[derive(Clone)]
 pub struct Bm25Weight {
     idf_explain: Explanation,
@@ -41,6 +44,7 @@ pub struct Bm25Weight {
 }
 
 impl Bm25Weight {
+    /// Returns the weight multiplied by `boost`.
     pub fn boost_by(&self, boost: Score) -> Bm25Weight {
         Bm25Weight {
             idf_explain: self.idf_explain.clone(),
@@ -50,6 +54,11 @@ impl Bm25Weight {
         }
     }
 
+    /// Computes the weight of a set of terms of the same field, from the statistics of all
+    /// of the segments of `searcher`.
+    ///
+    /// # Panics
+    /// Panics if `terms` is empty, or if the terms belong to different fields.
     pub fn for_terms(searcher: &Searcher, terms: &[Term]) -> crate::Result<Bm25Weight> {
         assert!(!terms.is_empty(), "Bm25 requires at least one term");
         let field = terms[0].field();
@@ -70,13 +79,9 @@ impl Bm25Weight {
         }
         let average_fieldnorm = total_num_tokens as Score / total_num_docs as Score;
 
-        if terms.len() == 1 {
+        let bm25_weight = if terms.len() == 1 {
             let term_doc_freq = searcher.doc_freq(&terms[0])?;
-            Ok(Bm25Weight::for_one_term(
-                term_doc_freq,
-                total_num_docs,
-                average_fieldnorm,
-            ))
+            Bm25Weight::for_one_term(term_doc_freq, total_num_docs, average_fieldnorm)
         } else {
             let mut idf_sum: Score = 0.0;
             for term in terms {
@@ -84,10 +89,19 @@ impl Bm25Weight {
                 idf_sum += idf(term_doc_freq, total_num_docs);
             }
             let idf_explain = Explanation::new("idf", idf_sum);
-            Ok(Bm25Weight::new(idf_explain, average_fieldnorm))
+            Bm25Weight::new(idf_explain, average_fieldnorm)
+        };
+        // The index time boost of the field.
+        match searcher.schema().get_field_entry(field).field_type() {
+            FieldType::Str(text_options) if text_options.boost() != 1.0 => {
+                Ok(bm25_weight.boost_by(text_options.boost()))
+            }
+            _ => Ok(bm25_weight),
         }
     }
 
+    /// Computes the weight of a term from its document frequency, the number of documents
+    /// and the average fieldnorm of its field.
     pub fn for_one_term(
         term_doc_freq: u64,
         total_num_docs: u64,
@@ -114,11 +128,14 @@ impl Bm25Weight {
         }
     }
 
+    /// Returns the score of a document in which the term appears `term_freq` times, given
+    /// the fieldnorm id of the document.
     #[inline]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
         self.weight * self.tf_factor(fieldnorm_id, term_freq)
     }
 
+    /// Returns the maximum score any document can get.
     pub fn max_score(&self) -> Score {
         self.score(255u8, 2_013_265_944)
     }
@@ -130,6 +147,7 @@ impl Bm25Weight {
         term_freq / (term_freq + norm)
     }
 
+    /// Explains the score of a document, given its fieldnorm id and term frequency.
     pub fn explain(&self, fieldnorm_id: u8, term_freq: u32) -> Explanation {
         // The explain format is directly copied from Lucene's.
         // (So, Kudos to Lucene)
@@ -165,11 +183,36 @@ impl Bm25Weight {
 mod tests {
 
     use super::idf;
-    use crate::{assert_nearly_equals, Score};
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, Score};
 
     #[test]
     fn test_idf() {
         let score: Score = 2.0;
         assert_nearly_equals!(idf(1, 2), score.ln());
     }
+
+    fn top_doc_with_title_boost(title_boost: Score) -> crate::Result<DocAddress> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT.set_boost(title_boost));
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a guide", body => "rust rust rust"))?;
+        index_writer.add_document(doc!(title => "rust", body => "a guide"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![title, body]).parse_query("rust")?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        Ok(top_docs[0].1)
+    }
+
+    #[test]
+    fn test_field_boost_changes_ranking() -> crate::Result<()> {
+        assert_eq!(top_doc_with_title_boost(1.0)?, DocAddress::new(0, 0));
+        assert_eq!(top_doc_with_title_boost(5.0)?, DocAddress::new(0, 1));
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use super::boolean_weight::BooleanWeight;
-use crate::query::{Occur, Query, SumWithCoordsCombiner, TermQuery, Weight};
-use crate::schema::{IndexRecordOption, Term};
+use crate::query::{EmptyQuery, Occur, Query, SumWithCoordsCombiner, TermQuery, Weight};
+use crate::schema::{IndexRecordOption, Schema, Term};
 use crate::Searcher;
 
 /// The boolean query returns a set of documents
@@ -163,6 +163,73 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        for (_occur, subquery) in &self.subqueries {
+            subquery.validate(schema)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the subqueries, and then:
+    /// - removes the `Should` and `MustNot` clauses matching no documents. If a `Must` clause
+    ///   matches no documents, the whole query is rewritten into an `EmptyQuery`.
+    /// - flattens the nested boolean queries, when this does not change their meaning: `Should`
+    ///   unions of `Should` clauses, `Must` intersections of `Must` and `MustNot` clauses, and
+    ///   `MustNot` unions of `Should` clauses.
+    /// - replaces a boolean query with a single `Must` or `Should` clause by that clause.
+    fn rewrite(&self) -> Box<dyn Query> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::with_capacity(self.subqueries.len());
+        for (occur, subquery) in &self.subqueries {
+            let subquery = subquery.rewrite();
+            if subquery.is::<EmptyQuery>() {
+                if *occur == Occur::Must {
+                    return subquery;
+                }
+                continue;
+            }
+            if let Some(boolean_query) = subquery.downcast_ref::<BooleanQuery>() {
+                if can_flatten(*occur, boolean_query.clauses()) {
+                    for (sub_occur, sub_subquery) in boolean_query.clauses() {
+                        let flattened_occur = if *occur == Occur::MustNot {
+                            Occur::MustNot
+                        } else {
+                            *sub_occur
+                        };
+                        clauses.push((flattened_occur, sub_subquery.box_clone()));
+                    }
+                    continue;
+                }
+            }
+            clauses.push((*occur, subquery));
+        }
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            return Box::new(EmptyQuery);
+        }
+        if clauses.len() == 1 {
+            let (_occur, subquery) = clauses.pop().unwrap();
+            return subquery;
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+}
+
+/// Returns true if the clauses of a boolean query, appearing as a clause with the given
+/// `occur`, can be merged into the parent boolean query without changing its meaning.
+fn can_flatten(occur: Occur, clauses: &[(Occur, Box<dyn Query>)]) -> bool {
+    match occur {
+        Occur::Should | Occur::MustNot => clauses
+            .iter()
+            .all(|(sub_occur, _)| *sub_occur == Occur::Should),
+        Occur::Must => {
+            clauses
+                .iter()
+                .all(|(sub_occur, _)| *sub_occur != Occur::Should)
+                && clauses
+                    .iter()
+                    .any(|(sub_occur, _)| *sub_occur == Occur::Must)
+        }
+    }
 }
 
 impl BooleanQuery {
@@ -206,10 +273,10 @@ impl BooleanQuery {
 #[cfg(test)]
 mod tests {
     use super::BooleanQuery;
-    use crate::collector::{Count, DocSetCollector};
-    use crate::query::{QueryClone, QueryParser, TermQuery};
+    use crate::collector::{Count, DocSetCollector, TopDocs};
+    use crate::query::{BoostQuery, EmptyQuery, Occur, Query, QueryClone, QueryParser, TermQuery};
     use crate::schema::{IndexRecordOption, Schema, TEXT};
-    use crate::{DocAddress, Index, Term};
+    use crate::{assert_nearly_equals, DocAddress, Index, Term};
 
     fn create_test_index() -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
@@ -322,4 +389,163 @@ mod tests {
         ));
         Ok(())
     }
+
+    /// Describes the structure of a query made of boolean, boost and term queries.
+    fn describe(query: &dyn Query) -> String {
+        if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+            term_query.term().as_str().unwrap().to_string()
+        } else if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+            let clauses: Vec<String> = boolean_query
+                .clauses()
+                .iter()
+                .map(|(occur, subquery)| {
+                    let prefix = match occur {
+                        Occur::Must => "+",
+                        Occur::MustNot => "-",
+                        Occur::Should => "",
+                    };
+                    format!("{}{}", prefix, describe(subquery.as_ref()))
+                })
+                .collect();
+            format!("({})", clauses.join(" "))
+        } else if query.is::<EmptyQuery>() {
+            "<empty>".to_string()
+        } else {
+            format!("{:?}", query)
+        }
+    }
+
+    #[test]
+    fn test_rewrite_structure() -> crate::Result<()> {
+        let index = create_test_index()?;
+        let text = index.schema().get_field("text").unwrap();
+        let term = |text_str: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_str),
+                IndexRecordOption::WithFreqs,
+            ))
+        };
+        let boolean = |clauses: Vec<(Occur, Box<dyn Query>)>| -> Box<dyn Query> {
+            Box::new(BooleanQuery::new(clauses))
+        };
+        let rewritten = |query: Box<dyn Query>| describe(query.rewrite().as_ref());
+
+        // Nested unions are flattened.
+        assert_eq!(
+            rewritten(Box::new(BooleanQuery::union(vec![
+                term("a"),
+                Box::new(BooleanQuery::union(vec![term("b"), term("c")])),
+            ]))),
+            "(a b c)"
+        );
+        // Nested intersections are flattened, along with their exclusions.
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Must, term("a")),
+                (
+                    Occur::Must,
+                    boolean(vec![(Occur::Must, term("b")), (Occur::MustNot, term("d"))])
+                ),
+                (Occur::Should, term("c")),
+            ])),
+            "(+a +b -d c)"
+        );
+        // Excluding a union is excluding each of its clauses.
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Must, term("a")),
+                (
+                    Occur::MustNot,
+                    BooleanQuery::union(vec![term("b"), term("d")]).box_clone()
+                ),
+            ])),
+            "(+a -b -d)"
+        );
+        // Optional clauses of a required boolean query are not flattened.
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Must, term("a")),
+                (
+                    Occur::Must,
+                    boolean(vec![(Occur::Should, term("b")), (Occur::Should, term("c"))])
+                ),
+            ])),
+            "(+a +(b c))"
+        );
+        // Exclusions of empty sets are removed, and single clause queries collapsed.
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Must, boolean(vec![(Occur::Should, term("a"))])),
+                (Occur::MustNot, Box::new(EmptyQuery)),
+            ])),
+            "a"
+        );
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Should, term("a")),
+                (
+                    Occur::Should,
+                    Box::new(BoostQuery::new(Box::new(EmptyQuery), 2.0))
+                ),
+            ])),
+            "a"
+        );
+        // Queries that cannot match are rewritten to an `EmptyQuery`.
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Should, term("a")),
+                (
+                    Occur::Must,
+                    boolean(vec![(Occur::Must, Box::new(EmptyQuery))])
+                ),
+            ])),
+            "<empty>"
+        );
+        assert_eq!(
+            rewritten(boolean(vec![
+                (Occur::Should, Box::new(EmptyQuery)),
+                (Occur::MustNot, term("a")),
+            ])),
+            "<empty>"
+        );
+        assert_eq!(rewritten(boolean(vec![])), "<empty>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_preserves_results() -> crate::Result<()> {
+        let index = create_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        for query_str in [
+            "a OR (b OR (c OR d))",
+            "+a +(+b -d) c",
+            "+a -(b OR d)",
+            "a AND (c OR (b AND -d))",
+            "+(a) +(b OR c)",
+            "(-a) OR b",
+            "+c -(a OR (b OR d))",
+            "(a^2 OR b) AND (c OR d)",
+        ] {
+            let query = query_parser.parse_query(query_str)?;
+            let rewritten_query = query.rewrite();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            let rewritten_top_docs = searcher.search(&rewritten_query, &TopDocs::with_limit(10))?;
+            assert_eq!(top_docs.len(), rewritten_top_docs.len(), "{}", query_str);
+            for ((score, doc), (rewritten_score, rewritten_doc)) in
+                top_docs.into_iter().zip(rewritten_top_docs)
+            {
+                assert_eq!(doc, rewritten_doc, "{}", query_str);
+                assert_nearly_equals!(score, rewritten_score);
+            }
+            assert_eq!(
+                query.count(&searcher)?,
+                rewritten_query.count(&searcher)?,
+                "{}",
+                query_str
+            );
+        }
+        Ok(())
+    }
 }
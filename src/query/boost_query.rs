@@ -2,7 +2,8 @@ use std::fmt;
 
 use crate::fastfield::AliveBitSet;
 use crate::query::explanation::does_not_match;
-use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::query::{EmptyQuery, Explanation, Query, Scorer, Weight};
+use crate::schema::Schema;
 use crate::{DocId, DocSet, Score, Searcher, SegmentReader, Term};
 
 /// `BoostQuery` is a wrapper over a query used to boost its score.
@@ -51,6 +52,18 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.query.validate(schema)
+    }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        let query = self.query.rewrite();
+        if query.is::<EmptyQuery>() {
+            return query;
+        }
+        Box::new(BoostQuery::new(query, self.boost))
+    }
 }
 
 pub(crate) struct BoostWeight {
@@ -1,13 +1,18 @@
 use std::fmt;
 
-use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::query::{EmptyQuery, Explanation, Query, Scorer, Weight};
+use crate::schema::Schema;
 use crate::{DocId, DocSet, Score, Searcher, SegmentReader, TantivyError, Term};
 
 /// `ConstScoreQuery` is a wrapper over a query to provide a constant score.
-/// It can avoid unnecessary score computation on the wrapped query.
+/// It avoids unnecessary score computation on the wrapped query: the wrapped query is always
+/// run with scoring disabled, so that no term frequency nor field norm is read.
 ///
 /// The document set matched by the `ConstScoreQuery` is strictly the same as the underlying query.
 /// The configured score is used for each document.
+///
+/// This makes it a good fit for pure filtering.
+#[doc(alias = "ConstantScoreQuery")]
 pub struct ConstScoreQuery {
     query: Box<dyn Query>,
     score: Score,
@@ -37,7 +42,7 @@ impl fmt::Debug for ConstScoreQuery {
 
 impl Query for ConstScoreQuery {
     fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
-        let inner_weight = self.query.weight(searcher, scoring_enabled)?;
+        let inner_weight = self.query.weight(searcher, false)?;
         Ok(if scoring_enabled {
             Box::new(ConstWeight::new(inner_weight, self.score))
         } else {
@@ -48,6 +53,18 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.query.validate(schema)
+    }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        let query = self.query.rewrite();
+        if query.is::<EmptyQuery>() {
+            return query;
+        }
+        Box::new(ConstScoreQuery::new(query, self.score))
+    }
 }
 
 struct ConstWeight {
@@ -141,9 +158,46 @@ impl<TDocSet: DocSet + 'static> Scorer for ConstScorer<TDocSet> {
 #[cfg(test)]
 mod tests {
     use super::ConstScoreQuery;
-    use crate::query::{AllQuery, Query};
-    use crate::schema::Schema;
-    use crate::{DocAddress, Document, Index};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{DocAddress, Document, Index, Term};
+
+    #[test]
+    fn test_const_score_query_score() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.add_document(doc!(text => "a a a b"))?;
+        index_writer.add_document(doc!(text => "b"))?;
+        index_writer.add_document(doc!(text => "a b c d e f"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let query = ConstScoreQuery::new(Box::new(term_query.clone()), 0.5);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 3);
+        for &(score, _) in &top_docs {
+            assert_eq!(score, 0.5);
+        }
+        assert_eq!(
+            top_docs
+                .iter()
+                .map(|&(_, doc_address)| doc_address.doc_id)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 3]
+        );
+        assert_eq!(
+            searcher.search(&query, &Count)?,
+            searcher.search(&term_query, &Count)?
+        );
+        Ok(())
+    }
 
     #[test]
     fn test_const_score_query_explain() -> crate::Result<()> {
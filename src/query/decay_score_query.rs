@@ -0,0 +1,628 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::BitSet;
+use fastfield_codecs::Column;
+
+use crate::fastfield::{type_and_cardinality, AliveBitSet, FastType, MultiValuedFastFieldReader};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    supports_exists_query, validate_field, EmptyQuery, ExistsQuery, Explanation, Query, Scorer,
+    Weight,
+};
+use crate::schema::{Cardinality, Field, Schema};
+use crate::{DateTime, DocId, DocSet, Score, Searcher, SegmentReader, TantivyError, Term};
+
+/// Defines how the decay factor of a [`DecayScoreQuery`] decreases with the distance between
+/// the date of a document and the origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecayFunction {
+    /// `decay ^ (distance / scale)`
+    Exponential,
+    /// `decay ^ ((distance / scale) ^ 2)`, i.e. a normal decay.
+    ///
+    /// Documents close to the origin are barely penalized, while the factor drops quickly
+    /// beyond the scale.
+    Gauss,
+}
+
+impl DecayFunction {
+    fn factor(self, distance: f64, scale: f64, decay: f64) -> f64 {
+        let normalized_distance = distance / scale;
+        let exponent = match self {
+            DecayFunction::Exponential => normalized_distance,
+            DecayFunction::Gauss => normalized_distance * normalized_distance,
+        };
+        decay.powf(exponent)
+    }
+}
+
+/// `DecayScoreQuery` is a wrapper over a query, multiplying its score by a factor decaying
+/// with the distance between the value of a date fast field and an origin.
+///
+/// This is typically used to boost recent documents, by using the current time as the origin.
+///
+/// The document set matched by the `DecayScoreQuery` is strictly the same as the underlying
+/// query. The decay factor is `1` at the origin and `decay` at a distance of `scale` from the
+/// origin, either before or after it. How it decreases in between and beyond is defined by
+/// the [`DecayFunction`].
+///
+/// The field needs to be a date fast field. For multivalued fast fields, the first value of
+/// the document is used.
+///
+/// Documents without any date keep the score of the underlying query, unless a date is given
+/// with [`DecayScoreQuery::with_missing_value`]. Single-valued fast fields record the default
+/// date, `1970-01-01`, for the documents without any date: they can only be told apart if the
+/// field is also indexed. Otherwise, the default date is used, and setting a missing value
+/// returns an error.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{DecayFunction, DecayScoreQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DateTime, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(
+///     title => "Release notes",
+///     published => DateTime::from_timestamp_secs(1_000_000),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "Release notes",
+///     published => DateTime::from_timestamp_secs(2_000_000),
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("release")?;
+/// let query = DecayScoreQuery::new(
+///     query,
+///     published,
+///     DecayFunction::Exponential,
+///     DateTime::from_timestamp_secs(2_000_000),
+///     Duration::from_secs(86_400),
+///     0.5,
+/// );
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecayScoreQuery {
+    query: Box<dyn Query>,
+    field: Field,
+    function: DecayFunction,
+    origin: DateTime,
+    scale: Duration,
+    decay: f64,
+    missing_value: Option<DateTime>,
+}
+
+impl DecayScoreQuery {
+    /// Builds a decay score query, multiplying the score of `query` by a factor which is `1`
+    /// for the documents whose date is `origin`, and `decay` for the documents whose date is
+    /// `scale` away from the `origin`.
+    ///
+    /// `scale` needs to be positive, and `decay` needs to be strictly between `0` and `1`,
+    /// otherwise searching returns an error.
+    pub fn new(
+        query: Box<dyn Query>,
+        field: Field,
+        function: DecayFunction,
+        origin: DateTime,
+        scale: Duration,
+        decay: f64,
+    ) -> DecayScoreQuery {
+        DecayScoreQuery {
+            query,
+            field,
+            function,
+            origin,
+            scale,
+            decay,
+            missing_value: None,
+        }
+    }
+
+    /// Sets the date used for the documents without any date.
+    ///
+    /// The field needs to be either indexed or a multivalued fast field, so that the documents
+    /// without any date can be found.
+    #[must_use]
+    pub fn with_missing_value(mut self, missing_value: DateTime) -> DecayScoreQuery {
+        self.missing_value = Some(missing_value);
+        self
+    }
+
+    /// Checks the field and the parameters of the query, and returns the cardinality of the
+    /// date fast field.
+    fn cardinality(&self, schema: &Schema) -> crate::Result<Cardinality> {
+        let field_entry = validate_field(schema, self.field)?;
+        let cardinality_opt = type_and_cardinality(field_entry.field_type())
+            .filter(|(fast_type, _)| matches!(fast_type, FastType::Date))
+            .map(|(_, cardinality)| cardinality);
+        let cardinality = cardinality_opt.ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a date fast field.",
+                field_entry.name()
+            ))
+        })?;
+        if self.missing_value.is_some() && !supports_exists_query(field_entry) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is neither indexed nor a multivalued fast field: the documents \
+                 without any date cannot be found.",
+                field_entry.name()
+            )));
+        }
+        if self.scale.is_zero() {
+            return Err(TantivyError::InvalidArgument(
+                "The scale of a decay score query must be positive.".to_string(),
+            ));
+        }
+        if !(self.decay > 0.0 && self.decay < 1.0) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The decay of a decay score query must be strictly between 0 and 1, got {}.",
+                self.decay
+            )));
+        }
+        Ok(cardinality)
+    }
+}
+
+impl Clone for DecayScoreQuery {
+    fn clone(&self) -> Self {
+        DecayScoreQuery {
+            query: self.query.box_clone(),
+            field: self.field,
+            function: self.function,
+            origin: self.origin,
+            scale: self.scale,
+            decay: self.decay,
+            missing_value: self.missing_value,
+        }
+    }
+}
+
+impl fmt::Debug for DecayScoreQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DecayScore(query={:?}, field={:?}, function={:?}, origin={:?}, scale={:?}, decay={}, \
+             missing_value={:?})",
+            self.query,
+            self.field,
+            self.function,
+            self.origin,
+            self.scale,
+            self.decay,
+            self.missing_value
+        )
+    }
+}
+
+impl Query for DecayScoreQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let cardinality = self.cardinality(searcher.schema())?;
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        if !scoring_enabled {
+            return Ok(weight);
+        }
+        // Single-valued fast fields do not record which documents have a date: they are found
+        // in the inverted index instead.
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        let exists_weight_opt =
+            if cardinality == Cardinality::SingleValue && field_entry.is_indexed() {
+                Some(ExistsQuery::new(self.field).weight(searcher, false)?)
+            } else {
+                None
+            };
+        Ok(Box::new(DecayScoreWeight {
+            weight,
+            field: self.field,
+            cardinality,
+            exists_weight_opt,
+            decay_params: DecayParams {
+                function: self.function,
+                origin_micros: self.origin.into_timestamp_micros(),
+                scale_micros: self.scale.as_micros() as f64,
+                decay: self.decay,
+                missing_value: self.missing_value,
+            },
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.cardinality(schema)?;
+        self.query.validate(schema)
+    }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        let query = self.query.rewrite();
+        if query.is::<EmptyQuery>() {
+            return query;
+        }
+        Box::new(DecayScoreQuery {
+            query,
+            ..self.clone()
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DecayParams {
+    function: DecayFunction,
+    origin_micros: i64,
+    scale_micros: f64,
+    decay: f64,
+    missing_value: Option<DateTime>,
+}
+
+impl DecayParams {
+    /// Returns the decay factor for a document, or `None` if the document has no date and
+    /// there is no missing value.
+    fn factor(&self, date_opt: Option<DateTime>) -> Option<f64> {
+        let date = date_opt.or(self.missing_value)?;
+        let distance = (date.into_timestamp_micros() as f64 - self.origin_micros as f64).abs();
+        Some(
+            self.function
+                .factor(distance, self.scale_micros, self.decay),
+        )
+    }
+}
+
+struct DecayScoreWeight {
+    weight: Box<dyn Weight>,
+    field: Field,
+    cardinality: Cardinality,
+    // Matches the documents having a date, for indexed single-valued fast fields.
+    exists_weight_opt: Option<Box<dyn Weight>>,
+    decay_params: DecayParams,
+}
+
+impl DecayScoreWeight {
+    fn date_values(&self, reader: &SegmentReader) -> crate::Result<DateValues> {
+        let fast_fields = reader.fast_fields();
+        let date_values = match self.cardinality {
+            Cardinality::SingleValue => {
+                let docs_with_date_opt = match &self.exists_weight_opt {
+                    Some(exists_weight) => {
+                        let mut docs_with_date = BitSet::with_max_value(reader.max_doc());
+                        exists_weight.for_each(reader, &mut |doc, _| docs_with_date.insert(doc))?;
+                        Some(docs_with_date)
+                    }
+                    None => None,
+                };
+                DateValues::Single {
+                    reader: fast_fields.date(self.field)?,
+                    docs_with_date_opt,
+                }
+            }
+            Cardinality::MultiValues => DateValues::Multi {
+                reader: fast_fields.dates(self.field)?,
+                buffer: Vec::new(),
+            },
+        };
+        Ok(date_values)
+    }
+}
+
+impl Weight for DecayScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let underlying = self.weight.scorer(reader, boost)?;
+        Ok(Box::new(DecayScorer {
+            underlying,
+            date_values: self.date_values(reader)?,
+            decay_params: self.decay_params,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let date_opt = self.date_values(reader)?.value(doc);
+        let description = match self.decay_params.factor(date_opt) {
+            Some(factor) => format!(
+                "DecayScore {:?} with date {:?}, factor {}",
+                self.decay_params.function,
+                date_opt.or(self.decay_params.missing_value),
+                factor
+            ),
+            None => "DecayScore without date".to_string(),
+        };
+        let mut explanation = Explanation::new(description, scorer.score());
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+enum DateValues {
+    Single {
+        reader: Arc<dyn Column<DateTime>>,
+        // `None` if the documents without any date cannot be told apart.
+        docs_with_date_opt: Option<BitSet>,
+    },
+    Multi {
+        reader: MultiValuedFastFieldReader<DateTime>,
+        buffer: Vec<DateTime>,
+    },
+}
+
+impl DateValues {
+    fn value(&mut self, doc: DocId) -> Option<DateTime> {
+        match self {
+            DateValues::Single {
+                reader,
+                docs_with_date_opt,
+            } => {
+                let has_date = docs_with_date_opt
+                    .as_ref()
+                    .map(|docs_with_date| docs_with_date.contains(doc))
+                    .unwrap_or(true);
+                if has_date {
+                    Some(reader.get_val(u64::from(doc)))
+                } else {
+                    None
+                }
+            }
+            DateValues::Multi { reader, buffer } => {
+                reader.get_vals(doc, buffer);
+                buffer.first().copied()
+            }
+        }
+    }
+}
+
+struct DecayScorer {
+    underlying: Box<dyn Scorer>,
+    date_values: DateValues,
+    decay_params: DecayParams,
+}
+
+impl DocSet for DecayScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for DecayScorer {
+    fn score(&mut self) -> Score {
+        let score = self.underlying.score();
+        let doc = self.underlying.doc();
+        match self.decay_params.factor(self.date_values.value(doc)) {
+            Some(factor) => (f64::from(score) * factor) as Score,
+            None => score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{DecayFunction, DecayScoreQuery};
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, Query};
+    use crate::schema::{Cardinality, DateOptions, Field, Schema, FAST, INDEXED, TEXT};
+    use crate::{assert_nearly_equals, DateTime, DocAddress, Index, Score, TantivyError};
+
+    const DAY_SECS: i64 = 86_400;
+    const ORIGIN_SECS: i64 = 1_000 * DAY_SECS;
+
+    /// Returns the index, and its `published`, `updated` and `reviewed` fields.
+    fn build_index() -> crate::Result<(Index, Field, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let published = schema_builder.add_date_field("published", FAST);
+        let updated = schema_builder.add_date_field(
+            "updated",
+            DateOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let reviewed = schema_builder.add_date_field("reviewed", FAST | INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // The document `i` is `i` days old.
+        for age_days in 0..10 {
+            let date = DateTime::from_timestamp_secs(ORIGIN_SECS - age_days * DAY_SECS);
+            index_writer
+                .add_document(doc!(published => date, updated => date, reviewed => date))?;
+        }
+        // This document has neither an update date nor a review date.
+        let date = DateTime::from_timestamp_secs(ORIGIN_SECS);
+        index_writer.add_document(doc!(published => date))?;
+        index_writer.commit()?;
+        Ok((index, published, updated, reviewed))
+    }
+
+    fn decay_query(field: Field, function: DecayFunction) -> DecayScoreQuery {
+        DecayScoreQuery::new(
+            Box::new(AllQuery),
+            field,
+            function,
+            DateTime::from_timestamp_secs(ORIGIN_SECS),
+            Duration::from_secs(2 * DAY_SECS as u64),
+            0.5,
+        )
+    }
+
+    fn scores_by_doc(index: &Index, query: &dyn Query) -> crate::Result<Vec<Score>> {
+        let searcher = index.reader()?.searcher();
+        let mut top_docs = searcher.search(query, &TopDocs::with_limit(100))?;
+        top_docs.sort_by_key(|(_, doc_address)| doc_address.doc_id);
+        Ok(top_docs.into_iter().map(|(score, _)| score).collect())
+    }
+
+    #[test]
+    fn test_decay_score_query_decreases_with_age() -> crate::Result<()> {
+        let (index, published, _, _) = build_index()?;
+        for function in [DecayFunction::Exponential, DecayFunction::Gauss] {
+            let scores = scores_by_doc(&index, &decay_query(published, function))?;
+            assert_eq!(scores.len(), 11);
+            assert_nearly_equals!(scores[0], 1.0);
+            // The decay factor at a distance of `scale` is `decay`.
+            assert_nearly_equals!(scores[2], 0.5);
+            for age_days in 1..10 {
+                assert!(scores[age_days] < scores[age_days - 1]);
+            }
+        }
+        let exponential_scores =
+            scores_by_doc(&index, &decay_query(published, DecayFunction::Exponential))?;
+        assert_nearly_equals!(exponential_scores[1], 0.5f32.sqrt());
+        assert_nearly_equals!(exponential_scores[4], 0.25);
+        let gauss_scores = scores_by_doc(&index, &decay_query(published, DecayFunction::Gauss))?;
+        assert_nearly_equals!(gauss_scores[1], 0.5f32.powf(0.25));
+        assert_nearly_equals!(gauss_scores[4], 0.0625);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decay_score_query_future_dates() -> crate::Result<()> {
+        let (index, published, _, _) = build_index()?;
+        // With an origin in the past, the distance to the origin is used.
+        let query = DecayScoreQuery::new(
+            Box::new(AllQuery),
+            published,
+            DecayFunction::Exponential,
+            DateTime::from_timestamp_secs(ORIGIN_SECS - 4 * DAY_SECS),
+            Duration::from_secs(2 * DAY_SECS as u64),
+            0.5,
+        );
+        let scores = scores_by_doc(&index, &query)?;
+        assert_nearly_equals!(scores[4], 1.0);
+        assert_nearly_equals!(scores[2], 0.5);
+        assert_nearly_equals!(scores[6], 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decay_score_query_missing_date() -> crate::Result<()> {
+        let (index, _, updated, reviewed) = build_index()?;
+        let searcher = index.reader()?.searcher();
+        // The dates are either multivalued, or single-valued and indexed.
+        for field in [updated, reviewed] {
+            let query = decay_query(field, DecayFunction::Exponential);
+            let scores = scores_by_doc(&index, &query)?;
+            // The last document has no date and keeps its score.
+            assert_nearly_equals!(scores[4], 0.25);
+            assert_nearly_equals!(scores[10], 1.0);
+            let explanation = query.explain(&searcher, DocAddress::new(0, 10))?;
+            assert_nearly_equals!(explanation.value(), 1.0);
+
+            let query =
+                query.with_missing_value(DateTime::from_timestamp_secs(ORIGIN_SECS - 8 * DAY_SECS));
+            let scores = scores_by_doc(&index, &query)?;
+            assert_nearly_equals!(scores[4], 0.25);
+            assert_nearly_equals!(scores[10], 0.0625);
+            let explanation = query.explain(&searcher, DocAddress::new(0, 10))?;
+            assert_nearly_equals!(explanation.value(), 0.0625);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_decay_score_query_missing_date_not_indexed() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let published = schema_builder.add_date_field("published", FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "no date"))?;
+        index_writer.commit()?;
+        // The document gets the default date of the single-valued fast field.
+        let query = decay_query(published, DecayFunction::Exponential);
+        let scores = scores_by_doc(&index, &query)?;
+        assert_nearly_equals!(scores[0], 0.0);
+        // Its missing date cannot be found.
+        let query = query.with_missing_value(DateTime::from_timestamp_secs(ORIGIN_SECS));
+        let searcher = index.reader()?.searcher();
+        assert!(matches!(
+            searcher.search(&query, &TopDocs::with_limit(10)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decay_score_query_invalid_arguments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let published = schema_builder.add_date_field("published", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let top_docs = TopDocs::with_limit(10);
+        let query = decay_query(text, DecayFunction::Gauss);
+        assert!(matches!(
+            searcher.search(&query, &top_docs),
+            Err(TantivyError::SchemaError(_))
+        ));
+        for decay in [0.0, 1.0, f64::NAN] {
+            let query = DecayScoreQuery::new(
+                Box::new(AllQuery),
+                published,
+                DecayFunction::Gauss,
+                DateTime::from_timestamp_secs(ORIGIN_SECS),
+                Duration::from_secs(DAY_SECS as u64),
+                decay,
+            );
+            assert!(matches!(
+                searcher.search(&query, &top_docs),
+                Err(TantivyError::InvalidArgument(_))
+            ));
+        }
+        let query = DecayScoreQuery::new(
+            Box::new(AllQuery),
+            published,
+            DecayFunction::Gauss,
+            DateTime::from_timestamp_secs(ORIGIN_SECS),
+            Duration::ZERO,
+            0.5,
+        );
+        assert!(matches!(
+            searcher.search(&query, &top_docs),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
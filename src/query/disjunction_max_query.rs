@@ -1,6 +1,7 @@
 use tantivy_query_grammar::Occur;
 
 use crate::query::{BooleanWeight, DisjunctionMaxCombiner, Query, Weight};
+use crate::schema::Schema;
 use crate::{Score, Searcher, Term};
 
 /// The disjunction max query кeturns documents matching one or more wrapped queries,
@@ -110,6 +111,13 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        for disjunct in &self.disjuncts {
+            disjunct.validate(schema)?;
+        }
+        Ok(())
+    }
 }
 
 impl DisjunctionMaxQuery {
@@ -36,6 +36,10 @@ impl Weight for EmptyWeight {
     fn explain(&self, _reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
         Err(does_not_match(doc))
     }
+
+    fn count(&self, _reader: &SegmentReader) -> crate::Result<u32> {
+        Ok(0)
+    }
 }
 
 /// `EmptyScorer` is a dummy `Scorer` in which no document matches.
@@ -65,9 +69,11 @@ impl Scorer for EmptyScorer {
 
 #[cfg(test)]
 mod tests {
+    use crate::collector::{Count, TopDocs};
     use crate::docset::TERMINATED;
-    use crate::query::EmptyScorer;
-    use crate::DocSet;
+    use crate::query::{EmptyQuery, EmptyScorer};
+    use crate::schema::{Schema, TEXT};
+    use crate::{DocSet, Index};
 
     #[test]
     fn test_empty_scorer() {
@@ -76,4 +82,21 @@ mod tests {
         assert_eq!(empty_scorer.advance(), TERMINATED);
         assert_eq!(empty_scorer.doc(), TERMINATED);
     }
+
+    #[test]
+    fn test_empty_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.add_document(doc!(text => "happy tax payer"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&EmptyQuery, &Count)?, 0);
+        assert!(searcher
+            .search(&EmptyQuery, &TopDocs::with_limit(10))?
+            .is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,161 @@
+use std::ops::Bound;
+
+use common::BitSet;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::error::TantivyError;
+use crate::fastfield::type_and_cardinality;
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    validate_field, BitSetDocSet, ConstScorer, EmptyScorer, Explanation, Query, RangeQuery, Scorer,
+    Weight,
+};
+use crate::schema::{Cardinality, Field, FieldEntry, Schema};
+use crate::{DocId, Score};
+
+/// `ExistsQuery` matches all documents that have at least one value for a given field.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// If the field is indexed, the matching documents are the ones found in the postings of
+/// all of the terms of the field. Otherwise, if the field is a multivalued fast field, they
+/// are the documents having at least one value in the fast field.
+///
+/// The weight of the query returns an error for a field that is neither indexed, nor a
+/// multivalued fast field. In particular, single-valued fast fields record a default value for
+/// the documents that do not have any, so that they cannot tell whether a document has a
+/// value.
+#[derive(Clone, Debug)]
+pub struct ExistsQuery {
+    field: Field,
+}
+
+impl ExistsQuery {
+    /// Creates a new `ExistsQuery` matching the documents that have a value for `field`.
+    pub fn new(field: Field) -> ExistsQuery {
+        ExistsQuery { field }
+    }
+
+    /// The field targeted by the query.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+/// Returns true if the existence of a value for the field `field_entry` can be checked.
+pub(crate) fn supports_exists_query(field_entry: &FieldEntry) -> bool {
+    field_entry.is_indexed()
+        || matches!(
+            type_and_cardinality(field_entry.field_type()),
+            Some((_, Cardinality::MultiValues))
+        )
+}
+
+impl Query for ExistsQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        self.validate(searcher.schema())?;
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        if field_entry.is_indexed() {
+            let value_type = field_entry.field_type().value_type();
+            let all_terms_query = RangeQuery::new_term_bounds(
+                self.field,
+                value_type,
+                &Bound::Unbounded,
+                &Bound::Unbounded,
+            );
+            return all_terms_query.weight(searcher, scoring_enabled);
+        }
+        Ok(Box::new(ExistsWeight { field: self.field }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        let field_entry = validate_field(schema, self.field)?;
+        if !supports_exists_query(field_entry) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is neither indexed nor a multivalued fast field: the existence of its \
+                 values cannot be checked.",
+                field_entry.name()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Weight of an `ExistsQuery` on a multivalued fast field that is not indexed.
+struct ExistsWeight {
+    field: Field,
+}
+
+impl Weight for ExistsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let multi_reader = reader.fast_fields().u64s_lenient(self.field)?;
+        if multi_reader.total_num_vals() == 0 {
+            return Ok(Box::new(EmptyScorer));
+        }
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        for doc in 0..max_doc {
+            if multi_reader.num_vals(doc) > 0 {
+                doc_bitset.insert(doc);
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ExistsQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExistsQuery;
+    use crate::collector::DocSetCollector;
+    use crate::schema::{Cardinality, NumericOptions, Schema, FAST, STORED, TEXT};
+    use crate::{Index, TantivyError};
+
+    #[test]
+    fn test_exists_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tags = schema_builder.add_u64_field(
+            "tags",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let price = schema_builder.add_u64_field("price", FAST);
+        let comment = schema_builder.add_text_field("comment", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a", tags => 1u64, tags => 2u64))?;
+        index_writer.add_document(doc!(price => 3u64))?;
+        index_writer.add_document(doc!(title => "b", comment => "c"))?;
+        index_writer.add_document(doc!(tags => 4u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let matching_docs = |query: &ExistsQuery| -> crate::Result<Vec<u32>> {
+            let mut docs: Vec<u32> = searcher
+                .search(query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort_unstable();
+            Ok(docs)
+        };
+        assert_eq!(matching_docs(&ExistsQuery::new(title))?, vec![0, 2]);
+        assert_eq!(matching_docs(&ExistsQuery::new(tags))?, vec![0, 3]);
+        for field in [price, comment] {
+            assert!(matches!(
+                searcher.search(&ExistsQuery::new(field), &DocSetCollector),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        Ok(())
+    }
+}
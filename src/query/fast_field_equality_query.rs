@@ -0,0 +1,222 @@
+use std::ops::Bound;
+
+use crate::core::Searcher;
+use crate::fastfield::FastValue;
+use crate::query::{FastFieldRangeQuery, Query, Weight};
+use crate::schema::{Field, Schema};
+
+/// `FastFieldEqualityQuery` matches all documents whose fast field value is equal to a given
+/// value.
+///
+/// This is the equivalent of a SQL `field = value` filter, for fields that are fast but not
+/// indexed. Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// The query is a [`FastFieldRangeQuery`] on the range `[value, value]`, and does not rely on
+/// the inverted index either.
+///
+/// Both single-valued and multivalued fast fields are supported. In the latter case, a
+/// document matches if any of its values is equal to the value.
+///
+/// For the f64 fast fields stored with the precision of an f32, the value is truncated like
+/// the stored values, so that it matches the documents having this value.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::FastFieldEqualityQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let year_field = schema_builder.add_u64_field("year", FAST);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// for year in 1_900u64..2_000u64 {
+///     index_writer.add_document(doc!(year_field => year))?;
+///     index_writer.add_document(doc!(year_field => year))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+/// let query = FastFieldEqualityQuery::new(year_field, 1_984u64);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FastFieldEqualityQuery {
+    range_query: FastFieldRangeQuery,
+}
+
+impl FastFieldEqualityQuery {
+    /// Creates a new `FastFieldEqualityQuery` matching the documents for which the fast field
+    /// `field` has the value `value`.
+    ///
+    /// If the field is not a fast field of the type `TFastValue`, an error will be returned
+    /// when the `Weight` object is created.
+    pub fn new<TFastValue: FastValue>(field: Field, value: TFastValue) -> FastFieldEqualityQuery {
+        FastFieldEqualityQuery {
+            range_query: FastFieldRangeQuery::new(
+                field,
+                Bound::Included(value),
+                Bound::Included(value),
+            ),
+        }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.range_query.field()
+    }
+}
+
+impl Query for FastFieldEqualityQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        self.range_query.weight(searcher, scoring_enabled)
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.range_query.validate(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastFieldEqualityQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{Cardinality, IndexRecordOption, NumericOptions, Schema, FAST, STRING};
+    use crate::{DocAddress, Index, IndexSettings, IndexSortByField, Order, Term};
+
+    #[test]
+    fn test_fast_field_equality_query_not_indexed() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_i64_field("id", FAST);
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        for id in -5i64..5i64 {
+            let text = if id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(id_field => id.abs(), text_field => text))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldEqualityQuery::new(id_field, 3i64);
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        let mut doc_ids: Vec<u32> = docs.into_iter().map(|doc| doc.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![2, 8]);
+        assert_eq!(
+            searcher.search(&FastFieldEqualityQuery::new(id_field, 0i64), &Count)?,
+            1
+        );
+        assert_eq!(
+            searcher.search(&FastFieldEqualityQuery::new(id_field, 12i64), &Count)?,
+            0
+        );
+
+        // The scorer can be intersected with the scorer of another query.
+        let even_query = TermQuery::new(
+            Term::from_field_text(text_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let subqueries: Vec<(Occur, Box<dyn Query>)> = vec![
+            (Occur::Must, Box::new(even_query)),
+            (
+                Occur::Must,
+                Box::new(FastFieldEqualityQuery::new(id_field, 4i64)),
+            ),
+        ];
+        let docs = searcher.search(&BooleanQuery::new(subqueries), &DocSetCollector)?;
+        assert_eq!(docs.len(), 2);
+        assert!(docs.contains(&DocAddress::new(0, 1)));
+        assert!(docs.contains(&DocAddress::new(0, 9)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_equality_query_sorted_index() -> crate::Result<()> {
+        for order in [Order::Asc, Order::Desc] {
+            let mut schema_builder = Schema::builder();
+            let val_field = schema_builder.add_u64_field("val", FAST);
+            let schema = schema_builder.build();
+            let settings = IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "val".to_string(),
+                    order,
+                }),
+                ..Default::default()
+            };
+            let index = Index::builder()
+                .schema(schema)
+                .settings(settings)
+                .create_in_ram()?;
+            let mut index_writer = index.writer_for_tests()?;
+            for val in 0u64..100u64 {
+                for _ in 0..val % 4 {
+                    index_writer.add_document(doc!(val_field => val % 10))?;
+                }
+            }
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            for val in 0u64..10u64 {
+                let query = FastFieldEqualityQuery::new(val_field, val);
+                let expected_count = (0u64..100u64)
+                    .filter(|doc_val| doc_val % 10 == val)
+                    .map(|doc_val| doc_val % 4)
+                    .sum::<u64>();
+                assert_eq!(searcher.search(&query, &Count)? as u64, expected_count);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_equality_query_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tags_field = schema_builder.add_u64_field(
+            "tags",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tags_field => 1u64, tags_field => 2u64))?;
+        index_writer.add_document(doc!(tags_field => 3u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(tags_field => 4u64, tags_field => 2u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldEqualityQuery::new(tags_field, 2u64);
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        assert!(docs.contains(&DocAddress::new(0, 0)));
+        assert!(docs.contains(&DocAddress::new(0, 3)));
+        assert_eq!(docs.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_equality_query_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let not_fast_field = schema_builder.add_u64_field("not_fast", NumericOptions::default());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let searcher = index.reader()?.searcher();
+        let wrong_type_query = FastFieldEqualityQuery::new(fast_field, 1i64);
+        assert!(searcher.search(&wrong_type_query, &Count).is_err());
+        let not_fast_query = FastFieldEqualityQuery::new(not_fast_field, 1u64);
+        assert!(searcher.search(&not_fast_query, &Count).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use std::ops::{Bound, RangeInclusive};
+
+use common::BitSet;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::fastfield::{truncate_to_stored_precision, FastValue};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    validate_fast_field, BitSetDocSet, ConstScorer, EmptyScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{Cardinality, Field, FieldType, Schema, Type};
+use crate::{DocId, Score};
+
+/// `FastFieldRangeQuery` matches all documents that have a fast field value within a given
+/// range.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// Unlike a `RangeQuery`, this query does not rely on the inverted index, so that it can be
+/// used on fields that are fast but not indexed. For each segment, the fast field column is
+/// scanned, and each value is checked against the range.
+///
+/// Segments whose fast field values range does not intersect with the range are skipped
+/// entirely.
+///
+/// Both single-valued and multivalued fast fields are supported. In the latter case, a
+/// document matches if any of its values is within the range, and a document without any
+/// value never matches.
+///
+/// For the f64 fast fields stored with the precision of an f32, the bounds are truncated
+/// like the values, so that a value stays within the range if it is within its bounds.
+///
+/// # Example
+///
+/// ```rust
+/// use std::ops::Bound;
+///
+/// use tantivy::collector::Count;
+/// use tantivy::query::FastFieldRangeQuery;
+/// use tantivy::schema::{Cardinality, NumericOptions, Schema};
+/// use tantivy::{doc, Index};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let prices_options = NumericOptions::default().set_fast(Cardinality::MultiValues);
+/// let prices_field = schema_builder.add_u64_field("prices", prices_options);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(prices_field => 5u64, prices_field => 50u64))?;
+/// index_writer.add_document(doc!(prices_field => 15u64))?;
+/// index_writer.add_document(doc!(prices_field => 100u64))?;
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+/// let query =
+///     FastFieldRangeQuery::new(prices_field, Bound::Included(10u64), Bound::Excluded(100u64));
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FastFieldRangeQuery {
+    field: Field,
+    value_type: Type,
+    // u64 representation of the bounds, which preserves their order.
+    left_bound: Bound<u64>,
+    right_bound: Bound<u64>,
+}
+
+fn bound_to_u64<TFastValue: FastValue>(bound: &Bound<TFastValue>) -> Bound<u64> {
+    match bound {
+        Bound::Included(val) => Bound::Included(val.to_u64()),
+        Bound::Excluded(val) => Bound::Excluded(val.to_u64()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Converts the bounds of a range into an inclusive range of the values stored in the fast
+/// field, or `None` if the range is empty.
+///
+/// The bounds are truncated to the precision of the stored values before the excluded bounds
+/// are made inclusive, so that they exclude the truncated values equal to them.
+fn to_u64_range(
+    left_bound: &Bound<u64>,
+    right_bound: &Bound<u64>,
+    field_type: &FieldType,
+) -> Option<RangeInclusive<u64>> {
+    let truncate = |val: u64| truncate_to_stored_precision(val, field_type);
+    let start = match *left_bound {
+        Bound::Included(val) => truncate(val),
+        Bound::Excluded(val) => truncate(val).checked_add(1)?,
+        Bound::Unbounded => u64::MIN,
+    };
+    let end = match *right_bound {
+        Bound::Included(val) => truncate(val),
+        Bound::Excluded(val) => truncate(val).checked_sub(1)?,
+        Bound::Unbounded => u64::MAX,
+    };
+    if start > end {
+        return None;
+    }
+    Some(start..=end)
+}
+
+impl FastFieldRangeQuery {
+    /// Creates a new `FastFieldRangeQuery` matching the documents for which the fast field
+    /// `field` has a value within the bounds.
+    ///
+    /// If the field is not a fast field of the type `TFastValue`, an error will be returned
+    /// when the `Weight` object is created.
+    pub fn new<TFastValue: FastValue>(
+        field: Field,
+        left_bound: Bound<TFastValue>,
+        right_bound: Bound<TFastValue>,
+    ) -> FastFieldRangeQuery {
+        FastFieldRangeQuery {
+            field,
+            value_type: TFastValue::to_type(),
+            left_bound: bound_to_u64(&left_bound),
+            right_bound: bound_to_u64(&right_bound),
+        }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    fn cardinality(&self, schema: &Schema) -> crate::Result<Cardinality> {
+        validate_fast_field(schema, self.field, &[self.value_type])
+    }
+}
+
+impl Query for FastFieldRangeQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let cardinality = self.cardinality(searcher.schema())?;
+        let field_type = searcher.schema().get_field_entry(self.field).field_type();
+        Ok(Box::new(FastFieldRangeWeight {
+            field: self.field,
+            cardinality,
+            range: to_u64_range(&self.left_bound, &self.right_bound, field_type),
+        }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.cardinality(schema)?;
+        Ok(())
+    }
+}
+
+struct FastFieldRangeWeight {
+    field: Field,
+    cardinality: Cardinality,
+    range: Option<RangeInclusive<u64>>,
+}
+
+impl Weight for FastFieldRangeWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let range = if let Some(range) = self.range.as_ref() {
+            range
+        } else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let is_disjoint_from =
+            |min_value: u64, max_value: u64| max_value < *range.start() || min_value > *range.end();
+        let max_doc = reader.max_doc();
+        let fast_field_readers = reader.fast_fields();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        match self.cardinality {
+            Cardinality::SingleValue => {
+                let column = fast_field_readers.u64_lenient(self.field)?;
+                if is_disjoint_from(column.min_value(), column.max_value()) {
+                    return Ok(Box::new(EmptyScorer));
+                }
+                for (doc, val) in column.iter().enumerate() {
+                    if range.contains(&val) {
+                        doc_bitset.insert(doc as DocId);
+                    }
+                }
+            }
+            Cardinality::MultiValues => {
+                let multi_reader = fast_field_readers.u64s_lenient(self.field)?;
+                if multi_reader.total_num_vals() == 0
+                    || is_disjoint_from(multi_reader.min_value(), multi_reader.max_value())
+                {
+                    return Ok(Box::new(EmptyScorer));
+                }
+                let mut vals = Vec::new();
+                for doc in 0..max_doc {
+                    multi_reader.get_vals(doc, &mut vals);
+                    if vals.iter().any(|val| range.contains(val)) {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FastFieldRangeQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::FastFieldRangeQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::schema::{Cardinality, NumericOptions, Schema, FAST, INDEXED};
+    use crate::{Index, Searcher};
+
+    fn matching_docs(searcher: &Searcher, query: &FastFieldRangeQuery) -> crate::Result<Vec<u32>> {
+        let mut doc_ids: Vec<u32> = searcher
+            .search(query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc| doc.doc_id)
+            .collect();
+        doc_ids.sort_unstable();
+        Ok(doc_ids)
+    }
+
+    #[test]
+    fn test_fast_field_range_query_single_value() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_i64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for id in -5i64..5i64 {
+            index_writer.add_document(doc!(id_field => id))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldRangeQuery::new(id_field, Bound::Excluded(-2i64), Bound::Included(1));
+        assert_eq!(matching_docs(&searcher, &query)?, vec![4, 5, 6]);
+        let query = FastFieldRangeQuery::new(id_field, Bound::Unbounded, Bound::Excluded(-3i64));
+        assert_eq!(matching_docs(&searcher, &query)?, vec![0, 1]);
+        let out_of_range_query =
+            FastFieldRangeQuery::new(id_field, Bound::Included(10i64), Bound::Unbounded);
+        assert_eq!(searcher.search(&out_of_range_query, &Count)?, 0);
+        let empty_range_query =
+            FastFieldRangeQuery::new(id_field, Bound::Included(1i64), Bound::Excluded(1));
+        assert_eq!(searcher.search(&empty_range_query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_range_query_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let prices_field = schema_builder.add_u64_field(
+            "prices",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // One value within the range, and one value out of the range.
+        index_writer.add_document(doc!(prices_field => 5u64, prices_field => 50u64))?;
+        // No value.
+        index_writer.add_document(doc!())?;
+        // All of the values out of the range, on both sides.
+        index_writer.add_document(doc!(prices_field => 1u64, prices_field => 100u64))?;
+        // All of the values within the range.
+        index_writer.add_document(doc!(prices_field => 10u64, prices_field => 20u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldRangeQuery::new(
+            prices_field,
+            Bound::Included(10u64),
+            Bound::Excluded(100u64),
+        );
+        assert_eq!(matching_docs(&searcher, &query)?, vec![0, 3]);
+        let all_values_query =
+            FastFieldRangeQuery::new::<u64>(prices_field, Bound::Unbounded, Bound::Unbounded);
+        assert_eq!(matching_docs(&searcher, &all_values_query)?, vec![0, 2, 3]);
+        let boundary_query = FastFieldRangeQuery::new(
+            prices_field,
+            Bound::Excluded(50u64),
+            Bound::Included(100u64),
+        );
+        assert_eq!(matching_docs(&searcher, &boundary_query)?, vec![2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_range_query_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let wrong_type_query =
+            FastFieldRangeQuery::new(fast_field, Bound::Included(1i64), Bound::Unbounded);
+        assert!(searcher.search(&wrong_type_query, &Count).is_err());
+        let not_fast_query =
+            FastFieldRangeQuery::new(indexed_field, Bound::Included(1u64), Bound::Unbounded);
+        assert!(searcher.search(&not_fast_query, &Count).is_err());
+        Ok(())
+    }
+}
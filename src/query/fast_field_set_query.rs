@@ -0,0 +1,267 @@
+use common::BitSet;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::fastfield::{truncate_to_stored_precision, FastValue};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    validate_fast_field, BitSetDocSet, ConstScorer, EmptyScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{Cardinality, Field, Schema, Type};
+use crate::{DocId, Score};
+
+/// `FastFieldSetQuery` matches all documents whose fast field value belongs to a given set
+/// of values.
+///
+/// This is the equivalent of a SQL `field IN (a, b, c, ...)` filter.
+/// Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// Unlike a `BooleanQuery` of `TermQuery`, this query does not rely on the inverted index.
+/// For each segment, the fast field column is scanned, and each value is probed within the
+/// sorted set of values. The cost of the query is therefore proportional to the number of
+/// documents in the segment, regardless of the number of values in the set, which makes it
+/// a good fit for large sets of values.
+///
+/// Segments whose fast field values range does not intersect with the set are skipped
+/// entirely.
+///
+/// The field needs to be declared as a fast field. Both single-valued and multivalued fast
+/// fields are supported. In the latter case, a document matches if any of its values belongs
+/// to the set.
+///
+/// For the f64 fast fields stored with the precision of an f32, the values of the set are
+/// truncated like the stored values, so that they match the documents having these values.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::FastFieldSetQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let id_field = schema_builder.add_u64_field("id", FAST);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// for id in 0u64..1_000u64 {
+///     index_writer.add_document(doc!(id_field => id))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+/// let query = FastFieldSetQuery::new(id_field, vec![3u64, 17u64, 500u64, 2_000u64]);
+/// assert_eq!(searcher.search(&query, &Count)?, 3);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FastFieldSetQuery {
+    field: Field,
+    value_type: Type,
+    // Sorted and deduplicated u64 representation of the values.
+    values: Vec<u64>,
+}
+
+impl FastFieldSetQuery {
+    /// Creates a new `FastFieldSetQuery` matching the documents for which the fast field
+    /// `field` has a value within `values`.
+    ///
+    /// The values do not need to be sorted nor deduplicated.
+    /// If the field is not a fast field of the type `TFastValue`, an error will be returned
+    /// when the `Weight` object is created.
+    pub fn new<TFastValue: FastValue>(
+        field: Field,
+        values: impl IntoIterator<Item = TFastValue>,
+    ) -> FastFieldSetQuery {
+        let mut values: Vec<u64> = values.into_iter().map(|val| val.to_u64()).collect();
+        values.sort_unstable();
+        values.dedup();
+        FastFieldSetQuery {
+            field,
+            value_type: TFastValue::to_type(),
+            values,
+        }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    fn cardinality(&self, schema: &Schema) -> crate::Result<Cardinality> {
+        validate_fast_field(schema, self.field, &[self.value_type])
+    }
+}
+
+impl Query for FastFieldSetQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let cardinality = self.cardinality(searcher.schema())?;
+        let field_type = searcher.schema().get_field_entry(self.field).field_type();
+        // The truncation preserves the order of the values.
+        let mut values: Vec<u64> = self
+            .values
+            .iter()
+            .map(|&val| truncate_to_stored_precision(val, field_type))
+            .collect();
+        values.dedup();
+        Ok(Box::new(FastFieldSetWeight {
+            field: self.field,
+            cardinality,
+            values,
+        }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.cardinality(schema)?;
+        Ok(())
+    }
+}
+
+struct FastFieldSetWeight {
+    field: Field,
+    cardinality: Cardinality,
+    values: Vec<u64>,
+}
+
+impl FastFieldSetWeight {
+    fn contains(&self, val: u64) -> bool {
+        self.values.binary_search(&val).is_ok()
+    }
+
+    /// Returns true if no value of the set is within `[min_value, max_value]`.
+    fn is_disjoint_from(&self, min_value: u64, max_value: u64) -> bool {
+        let start = self.values.partition_point(|&val| val < min_value);
+        self.values
+            .get(start)
+            .map(|&val| val > max_value)
+            .unwrap_or(true)
+    }
+}
+
+impl Weight for FastFieldSetWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let fast_field_readers = reader.fast_fields();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        match self.cardinality {
+            Cardinality::SingleValue => {
+                let column = fast_field_readers.u64_lenient(self.field)?;
+                if self.is_disjoint_from(column.min_value(), column.max_value()) {
+                    return Ok(Box::new(EmptyScorer));
+                }
+                for (doc, val) in column.iter().enumerate() {
+                    if self.contains(val) {
+                        doc_bitset.insert(doc as DocId);
+                    }
+                }
+            }
+            Cardinality::MultiValues => {
+                let multi_reader = fast_field_readers.u64s_lenient(self.field)?;
+                if self.is_disjoint_from(multi_reader.min_value(), multi_reader.max_value()) {
+                    return Ok(Box::new(EmptyScorer));
+                }
+                let mut vals = Vec::new();
+                for doc in 0..max_doc {
+                    multi_reader.get_vals(doc, &mut vals);
+                    if vals.iter().any(|&val| self.contains(val)) {
+                        doc_bitset.insert(doc);
+                    }
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FastFieldSetQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastFieldSetQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::schema::{Cardinality, NumericOptions, Schema, FAST, INDEXED};
+    use crate::{DocAddress, Index};
+
+    #[test]
+    fn test_fast_field_set_query_single_value() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_i64_field("id", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        for id in -5i64..5i64 {
+            index_writer.add_document(doc!(id_field => id))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldSetQuery::new(id_field, vec![4i64, -5, 0, 0, 12]);
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        let mut doc_ids: Vec<u32> = docs.into_iter().map(|doc| doc.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![0, 5, 9]);
+
+        let out_of_range_query = FastFieldSetQuery::new(id_field, vec![-100i64, 100]);
+        assert_eq!(searcher.search(&out_of_range_query, &Count)?, 0);
+        let empty_query = FastFieldSetQuery::new(id_field, Vec::<i64>::new());
+        assert_eq!(searcher.search(&empty_query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_set_query_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tags_field = schema_builder.add_u64_field(
+            "tags",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tags_field => 1u64, tags_field => 2u64))?;
+        index_writer.add_document(doc!(tags_field => 3u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(tags_field => 4u64, tags_field => 2u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = FastFieldSetQuery::new(tags_field, vec![2u64, 5u64]);
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        assert!(docs.contains(&DocAddress::new(0, 0)));
+        assert!(docs.contains(&DocAddress::new(0, 3)));
+        assert_eq!(docs.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_field_set_query_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let searcher = index.reader()?.searcher();
+        let wrong_type_query = FastFieldSetQuery::new(fast_field, vec![1i64]);
+        assert!(searcher.search(&wrong_type_query, &Count).is_err());
+        let not_fast_query = FastFieldSetQuery::new(indexed_field, vec![1u64]);
+        assert!(searcher.search(&not_fast_query, &Count).is_err());
+        Ok(())
+    }
+}
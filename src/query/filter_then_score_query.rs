@@ -0,0 +1,305 @@
+use std::fmt;
+
+use crate::docset::{DocSet, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyQuery, Explanation, Query, Scorer, Weight};
+use crate::schema::Schema;
+use crate::{DocId, Score, Searcher, SegmentReader, Term};
+
+/// `FilterThenScoreQuery` matches the documents matching both a filter and a scoring query,
+/// scoring them with the scoring query only.
+///
+/// The filter is run with scoring disabled, and drives the iteration: the scoring query is
+/// only advanced to the documents of the filter, and its score is only computed on the
+/// documents matching both queries.
+///
+/// A `BooleanQuery` intersecting the two queries leaves the choice of the leading docset to
+/// their estimated size, which does not always reflect their actual cost. This query is a good
+/// fit when the filter is known to be cheap and selective, and the scoring query is expensive.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{FilterThenScoreQuery, RangeQuery, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, INDEXED, TEXT};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let year = schema_builder.add_u64_field("year", INDEXED);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(body => "the old man and the sea", year => 1952u64))?;
+/// index_writer.add_document(doc!(body => "the sun also rises", year => 1926u64))?;
+/// index_writer.add_document(doc!(body => "the sea wolf", year => 1904u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = FilterThenScoreQuery::new(
+///     Box::new(RangeQuery::new_u64(year, 1900..1950)),
+///     Box::new(TermQuery::new(
+///         Term::from_field_text(body, "sea"),
+///         IndexRecordOption::WithFreqs,
+///     )),
+/// );
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+/// assert_eq!(top_docs.len(), 1);
+/// assert_eq!(top_docs[0].1.doc_id, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FilterThenScoreQuery {
+    filter: Box<dyn Query>,
+    query: Box<dyn Query>,
+}
+
+impl FilterThenScoreQuery {
+    /// Builds a query matching the documents of `filter` that also match `query`, scored by
+    /// `query`.
+    pub fn new(filter: Box<dyn Query>, query: Box<dyn Query>) -> FilterThenScoreQuery {
+        FilterThenScoreQuery { filter, query }
+    }
+}
+
+impl Clone for FilterThenScoreQuery {
+    fn clone(&self) -> Self {
+        FilterThenScoreQuery {
+            filter: self.filter.box_clone(),
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for FilterThenScoreQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FilterThenScore(filter={:?}, query={:?})",
+            self.filter, self.query
+        )
+    }
+}
+
+impl Query for FilterThenScoreQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let filter_weight = self.filter.weight(searcher, false)?;
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(FilterThenScoreWeight {
+            filter_weight,
+            weight,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.filter.query_terms(visitor);
+        self.query.query_terms(visitor);
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.filter.validate(schema)?;
+        self.query.validate(schema)
+    }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        let filter = self.filter.rewrite();
+        if filter.is::<EmptyQuery>() {
+            return filter;
+        }
+        let query = self.query.rewrite();
+        if query.is::<EmptyQuery>() {
+            return query;
+        }
+        Box::new(FilterThenScoreQuery::new(filter, query))
+    }
+}
+
+struct FilterThenScoreWeight {
+    filter_weight: Box<dyn Weight>,
+    weight: Box<dyn Weight>,
+}
+
+impl Weight for FilterThenScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let filter = self.filter_weight.scorer(reader, 1.0)?;
+        let scorer = self.weight.scorer(reader, boost)?;
+        Ok(Box::new(FilterThenScoreScorer::new(filter, scorer)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("FilterThenScore", scorer.score());
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        Ok(explanation)
+    }
+}
+
+/// Scorer of a `FilterThenScoreQuery`, iterating over the documents of the filter and
+/// advancing the scoring scorer to them.
+struct FilterThenScoreScorer {
+    filter: Box<dyn Scorer>,
+    scorer: Box<dyn Scorer>,
+    doc: DocId,
+}
+
+impl FilterThenScoreScorer {
+    fn new(filter: Box<dyn Scorer>, scorer: Box<dyn Scorer>) -> FilterThenScoreScorer {
+        let mut filter_then_score_scorer = FilterThenScoreScorer {
+            filter,
+            scorer,
+            doc: TERMINATED,
+        };
+        let doc = filter_then_score_scorer.filter.doc();
+        filter_then_score_scorer.align(doc);
+        filter_then_score_scorer
+    }
+
+    /// Goes to the first document greater or equal to `doc`, the current document of the
+    /// filter, matched by the scoring scorer too.
+    fn align(&mut self, mut doc: DocId) -> DocId {
+        while doc != TERMINATED {
+            let mut scorer_doc = self.scorer.doc();
+            if scorer_doc < doc {
+                scorer_doc = self.scorer.seek(doc);
+            }
+            if scorer_doc == doc {
+                break;
+            }
+            if scorer_doc == TERMINATED {
+                doc = TERMINATED;
+                break;
+            }
+            doc = self.filter.seek(scorer_doc);
+        }
+        self.doc = doc;
+        doc
+    }
+}
+
+impl DocSet for FilterThenScoreScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        let doc = self.filter.advance();
+        self.align(doc)
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        let doc = self.filter.seek(target);
+        self.align(doc)
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.filter.size_hint().min(self.scorer.size_hint())
+    }
+}
+
+impl Scorer for FilterThenScoreScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilterThenScoreQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::docset::{DocSet, TERMINATED};
+    use crate::query::{BooleanQuery, BoostQuery, EmptyQuery, Occur, Query, RangeQuery, TermQuery};
+    use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, TEXT};
+    use crate::{DocAddress, Index, Searcher, Term};
+
+    fn build_test_index() -> crate::Result<(Searcher, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for id in 0u64..300 {
+            let text = match id % 3 {
+                0 => "a",
+                1 => "a a b",
+                _ => "b",
+            };
+            index_writer.add_document(doc!(text_field => text, id_field => id))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        Ok((searcher, text_field, id_field))
+    }
+
+    fn term_query(field: Field, text: &str) -> Box<dyn Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, text),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    #[test]
+    fn test_filter_then_score_query() -> crate::Result<()> {
+        let (searcher, text_field, id_field) = build_test_index()?;
+        let filter: Box<dyn Query> = Box::new(RangeQuery::new_u64(id_field, 90..120));
+        let query = FilterThenScoreQuery::new(filter.box_clone(), term_query(text_field, "a"));
+        // The filter does not contribute to the score of the documents.
+        let boolean_query = BooleanQuery::new(vec![
+            (Occur::Must, term_query(text_field, "a")),
+            (Occur::Must, Box::new(BoostQuery::new(filter, 0.0))),
+        ]);
+        assert_eq!(searcher.search(&query, &Count)?, 20);
+        assert_eq!(query.count(&searcher)?, 20);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(30))?;
+        let expected_top_docs = searcher.search(&boolean_query, &TopDocs::with_limit(30))?;
+        assert_eq!(top_docs.len(), 20);
+        assert_eq!(top_docs, expected_top_docs);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_then_score_query_seek() -> crate::Result<()> {
+        let (searcher, text_field, id_field) = build_test_index()?;
+        let query = FilterThenScoreQuery::new(
+            Box::new(RangeQuery::new_u64(id_field, 0..50)),
+            term_query(text_field, "b"),
+        );
+        let weight = query.weight(&searcher, true)?;
+        let mut scorer = weight.scorer(searcher.segment_reader(0), 1.0)?;
+        assert_eq!(scorer.doc(), 1);
+        assert_eq!(scorer.seek(10), 10);
+        assert_eq!(scorer.seek(12), 13);
+        assert_eq!(scorer.advance(), 14);
+        assert_eq!(scorer.seek(49), 49);
+        assert_eq!(scorer.advance(), TERMINATED);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_then_score_query_explain_and_rewrite() -> crate::Result<()> {
+        let (searcher, text_field, id_field) = build_test_index()?;
+        let query = FilterThenScoreQuery::new(
+            Box::new(RangeQuery::new_u64(id_field, 0..10)),
+            term_query(text_field, "a"),
+        );
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        let term_explanation =
+            term_query(text_field, "a").explain(&searcher, DocAddress::new(0, 1))?;
+        assert_eq!(explanation.value(), term_explanation.value());
+        assert!(query.explain(&searcher, DocAddress::new(0, 2)).is_err());
+        assert!(query.explain(&searcher, DocAddress::new(0, 10)).is_err());
+
+        let empty_filter_query =
+            FilterThenScoreQuery::new(Box::new(EmptyQuery), term_query(text_field, "a"));
+        assert!(empty_filter_query.rewrite().is::<EmptyQuery>());
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+use std::fmt;
+use std::sync::Arc;
+
+use fastfield_codecs::Column;
+
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::fastfield::{AliveBitSet, MultiValuedFastFieldReader};
+use crate::query::explanation::does_not_match;
+use crate::query::{validate_fast_field, EmptyQuery, Explanation, Query, Scorer, Weight};
+use crate::schema::{Cardinality, Field, Schema, Type};
+use crate::{DocId, DocSet, Score, Searcher, SegmentReader, Term};
+
+/// Defines how a [`FunctionScoreQuery`] combines the score of its underlying query with the
+/// fast field value of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldValueFunction {
+    /// `score * value`
+    Multiply,
+    /// `score + value`
+    Add,
+    /// `score * ln(1 + value)`
+    ///
+    /// Negative values are considered to be 0.
+    Log,
+}
+
+impl FieldValueFunction {
+    fn combine(self, score: Score, value: f64) -> Score {
+        match self {
+            FieldValueFunction::Multiply => (f64::from(score) * value) as Score,
+            FieldValueFunction::Add => (f64::from(score) + value) as Score,
+            FieldValueFunction::Log => (f64::from(score) * value.max(0.0).ln_1p()) as Score,
+        }
+    }
+}
+
+/// `FunctionScoreQuery` is a wrapper over a query, combining its score with the value of a
+/// numeric fast field, such as the popularity of a document.
+///
+/// The document set matched by the `FunctionScoreQuery` is strictly the same as the
+/// underlying query. The score of each document is computed from the score of the underlying
+/// query and the fast field value of the document, using a [`FieldValueFunction`].
+///
+/// The field needs to be a `u64`, `i64` or `f64` fast field. For multivalued fast fields,
+/// the first value of the document is used.
+///
+/// Documents without any value (which can only happen with multivalued fast fields) keep the
+/// score of the underlying query, unless a value is given with
+/// [`FunctionScoreQuery::with_missing_value`].
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{FieldValueFunction, FunctionScoreQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let popularity = schema_builder.add_u64_field("popularity", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib", popularity => 1u64))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl", popularity => 100u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// let query = FunctionScoreQuery::new(query, popularity, FieldValueFunction::Log);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct FunctionScoreQuery {
+    query: Box<dyn Query>,
+    field: Field,
+    function: FieldValueFunction,
+    missing_value: Option<f64>,
+}
+
+impl FunctionScoreQuery {
+    /// Builds a function score query, combining the score of `query` with the value of the
+    /// fast field `field`.
+    pub fn new(
+        query: Box<dyn Query>,
+        field: Field,
+        function: FieldValueFunction,
+    ) -> FunctionScoreQuery {
+        FunctionScoreQuery {
+            query,
+            field,
+            function,
+            missing_value: None,
+        }
+    }
+
+    /// Sets the value used for the documents without any value.
+    #[must_use]
+    pub fn with_missing_value(mut self, missing_value: f64) -> FunctionScoreQuery {
+        self.missing_value = Some(missing_value);
+        self
+    }
+
+    fn cardinality(&self, schema: &Schema) -> crate::Result<Cardinality> {
+        validate_fast_field(schema, self.field, &[Type::U64, Type::I64, Type::F64])
+    }
+}
+
+impl Clone for FunctionScoreQuery {
+    fn clone(&self) -> Self {
+        FunctionScoreQuery {
+            query: self.query.box_clone(),
+            field: self.field,
+            function: self.function,
+            missing_value: self.missing_value,
+        }
+    }
+}
+
+impl fmt::Debug for FunctionScoreQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FunctionScore(query={:?}, field={:?}, function={:?}, missing_value={:?})",
+            self.query, self.field, self.function, self.missing_value
+        )
+    }
+}
+
+impl Query for FunctionScoreQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let cardinality = self.cardinality(searcher.schema())?;
+        let value_type = searcher
+            .schema()
+            .get_field_entry(self.field)
+            .field_type()
+            .value_type();
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        if !scoring_enabled {
+            return Ok(weight);
+        }
+        Ok(Box::new(FunctionScoreWeight {
+            weight,
+            field: self.field,
+            value_type,
+            cardinality,
+            function: self.function,
+            missing_value: self.missing_value,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.cardinality(schema)?;
+        self.query.validate(schema)
+    }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        let query = self.query.rewrite();
+        if query.is::<EmptyQuery>() {
+            return query;
+        }
+        Box::new(FunctionScoreQuery {
+            query,
+            ..self.clone()
+        })
+    }
+}
+
+struct FunctionScoreWeight {
+    weight: Box<dyn Weight>,
+    field: Field,
+    value_type: Type,
+    cardinality: Cardinality,
+    function: FieldValueFunction,
+    missing_value: Option<f64>,
+}
+
+impl FunctionScoreWeight {
+    fn field_values(&self, reader: &SegmentReader) -> crate::Result<FieldValues> {
+        let fast_fields = reader.fast_fields();
+        let field_values = match self.cardinality {
+            Cardinality::SingleValue => FieldValues::Single(fast_fields.u64_lenient(self.field)?),
+            Cardinality::MultiValues => FieldValues::Multi {
+                reader: fast_fields.u64s_lenient(self.field)?,
+                buffer: Vec::new(),
+            },
+        };
+        Ok(field_values)
+    }
+}
+
+impl Weight for FunctionScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let underlying = self.weight.scorer(reader, boost)?;
+        Ok(Box::new(FunctionScorer {
+            underlying,
+            field_values: self.field_values(reader)?,
+            value_type: self.value_type,
+            function: self.function,
+            missing_value: self.missing_value,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut field_values = self.field_values(reader)?;
+        let description = match field_values.value(doc, self.value_type) {
+            Some(value) => format!("FunctionScore {:?} with value {}", self.function, value),
+            None => match self.missing_value {
+                Some(value) => format!(
+                    "FunctionScore {:?} with missing value {}",
+                    self.function, value
+                ),
+                None => "FunctionScore without value".to_string(),
+            },
+        };
+        let mut explanation = Explanation::new(description, scorer.score());
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+enum FieldValues {
+    Single(Arc<dyn Column<u64>>),
+    Multi {
+        reader: MultiValuedFastFieldReader<u64>,
+        buffer: Vec<u64>,
+    },
+}
+
+impl FieldValues {
+    fn value(&mut self, doc: DocId, value_type: Type) -> Option<f64> {
+        let val = match self {
+            FieldValues::Single(reader) => reader.get_val(u64::from(doc)),
+            FieldValues::Multi { reader, buffer } => {
+                reader.get_vals(doc, buffer);
+                *buffer.first()?
+            }
+        };
+        Some(f64_from_fastfield_u64(val, &value_type))
+    }
+}
+
+struct FunctionScorer {
+    underlying: Box<dyn Scorer>,
+    field_values: FieldValues,
+    value_type: Type,
+    function: FieldValueFunction,
+    missing_value: Option<f64>,
+}
+
+impl DocSet for FunctionScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for FunctionScorer {
+    fn score(&mut self) -> Score {
+        let score = self.underlying.score();
+        let doc = self.underlying.doc();
+        match self
+            .field_values
+            .value(doc, self.value_type)
+            .or(self.missing_value)
+        {
+            Some(value) => self.function.combine(score, value),
+            None => score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldValueFunction, FunctionScoreQuery};
+    use crate::collector::TopDocs;
+    use crate::query::{Query, TermQuery};
+    use crate::schema::{
+        Cardinality, Field, IndexRecordOption, NumericOptions, Schema, FAST, STRING, TEXT,
+    };
+    use crate::{assert_nearly_equals, DocAddress, Index, Score, Searcher, TantivyError, Term};
+
+    fn top_docs(searcher: &Searcher, query: &dyn Query) -> crate::Result<Vec<(Score, DocAddress)>> {
+        searcher.search(query, &TopDocs::with_limit(10))
+    }
+
+    fn build_index() -> crate::Result<(Index, Field, Field, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let ratings = schema_builder.add_f64_field(
+            "ratings",
+            NumericOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // The first document has the best text score, the second one the highest popularity.
+        index_writer.add_document(doc!(
+            text => "rust rust",
+            popularity => 2u64,
+            delta => -4i64,
+            ratings => 0.1f64,
+        ))?;
+        index_writer.add_document(doc!(
+            text => "rust and a few other words",
+            popularity => 50u64,
+            delta => 3i64,
+        ))?;
+        index_writer.add_document(doc!(text => "other", popularity => 1_000u64))?;
+        index_writer.commit()?;
+        Ok((index, text, popularity, delta, ratings))
+    }
+
+    #[test]
+    fn test_function_score_query() -> crate::Result<()> {
+        let (index, text, popularity, delta, _) = build_index()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "rust"),
+            IndexRecordOption::WithFreqs,
+        );
+        let base = top_docs(&searcher, &term_query)?;
+        assert_eq!(base.len(), 2);
+        assert_eq!(base[0].1, DocAddress::new(0, 0));
+        let (score_0, score_1) = (base[0].0, base[1].0);
+
+        let multiply = FunctionScoreQuery::new(
+            Box::new(term_query.clone()),
+            popularity,
+            FieldValueFunction::Multiply,
+        );
+        let results = top_docs(&searcher, &multiply)?;
+        // The ranking changes: the more popular document comes first.
+        assert_eq!(results[0].1, DocAddress::new(0, 1));
+        assert_nearly_equals!(results[0].0, score_1 * 50.0);
+        assert_eq!(results[1].1, DocAddress::new(0, 0));
+        assert_nearly_equals!(results[1].0, score_0 * 2.0);
+
+        let add =
+            FunctionScoreQuery::new(Box::new(term_query.clone()), delta, FieldValueFunction::Add);
+        let results = top_docs(&searcher, &add)?;
+        assert_eq!(results[0].1, DocAddress::new(0, 1));
+        assert_nearly_equals!(results[0].0, score_1 + 3.0);
+        assert_nearly_equals!(results[1].0, score_0 - 4.0);
+
+        let log = FunctionScoreQuery::new(
+            Box::new(term_query.clone()),
+            popularity,
+            FieldValueFunction::Log,
+        );
+        let results = top_docs(&searcher, &log)?;
+        assert_eq!(results[0].1, DocAddress::new(0, 1));
+        assert_nearly_equals!(results[0].0, score_1 * 51f32.ln());
+        assert_nearly_equals!(results[1].0, score_0 * 3f32.ln());
+        // Negative values are considered to be 0.
+        let log_negative =
+            FunctionScoreQuery::new(Box::new(term_query), delta, FieldValueFunction::Log);
+        let results = top_docs(&searcher, &log_negative)?;
+        assert_eq!(results[1].1, DocAddress::new(0, 0));
+        assert_eq!(results[1].0, 0.0);
+
+        let explanation = log.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_nearly_equals!(explanation.value(), score_1 * 51f32.ln());
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_score_query_missing_value() -> crate::Result<()> {
+        let (index, text, _, _, ratings) = build_index()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "rust"),
+            IndexRecordOption::WithFreqs,
+        );
+        let base = top_docs(&searcher, &term_query)?;
+        let (score_0, score_1) = (base[0].0, base[1].0);
+
+        let query =
+            FunctionScoreQuery::new(Box::new(term_query), ratings, FieldValueFunction::Multiply);
+        // The second document has no rating and keeps its score.
+        let results = top_docs(&searcher, &query)?;
+        assert_eq!(results[0].1, DocAddress::new(0, 1));
+        assert_nearly_equals!(results[0].0, score_1);
+        assert_nearly_equals!(results[1].0, score_0 * 0.1);
+
+        let query = query.with_missing_value(0.0);
+        let results = top_docs(&searcher, &query)?;
+        assert_eq!(results[0].1, DocAddress::new(0, 0));
+        assert_nearly_equals!(results[0].0, score_0 * 0.1);
+        assert_eq!(results[1].0, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_score_query_invalid_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let query = FunctionScoreQuery::new(
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, "rust"),
+                IndexRecordOption::Basic,
+            )),
+            text,
+            FieldValueFunction::Multiply,
+        );
+        assert!(matches!(
+            top_docs(&searcher, &query),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
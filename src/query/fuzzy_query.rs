@@ -5,8 +5,8 @@ use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
 use once_cell::sync::Lazy;
 use tantivy_fst::Automaton;
 
-use crate::query::{AutomatonWeight, Query, Weight};
-use crate::schema::Term;
+use crate::query::{validate_indexed_field, AutomatonWeight, MaxExpansionsBehavior, Query, Weight};
+use crate::schema::{Schema, Term};
 use crate::Searcher;
 use crate::TantivyError::InvalidArgument;
 
@@ -106,6 +106,8 @@ pub struct FuzzyTermQuery {
     transposition_cost_one: bool,
     ///
     prefix: bool,
+    /// Maximum number of terms the query expands to.
+    max_expansions: Option<(usize, MaxExpansionsBehavior)>,
 }
 
 impl FuzzyTermQuery {
@@ -116,6 +118,7 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: false,
+            max_expansions: None,
         }
     }
 
@@ -126,44 +129,82 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: true,
+            max_expansions: None,
         }
     }
 
+    /// Caps the number of terms the query expands to over the segments of the searcher.
+    ///
+    /// By default, the number of terms is not capped. If more than `max_expansions` terms are
+    /// within distance, building the weight fails or only the `max_expansions` smallest terms
+    /// are searched for, depending on `max_expansions_behavior`.
+    #[must_use]
+    pub fn with_max_expansions(
+        mut self,
+        max_expansions: usize,
+        max_expansions_behavior: MaxExpansionsBehavior,
+    ) -> FuzzyTermQuery {
+        self.max_expansions = Some((max_expansions, max_expansions_behavior));
+        self
+    }
+
     fn specialized_weight(&self) -> crate::Result<AutomatonWeight<DfaWrapper>> {
-        // LEV_BUILDER is a HashMap, whose `get` method returns an Option
-        match LEV_BUILDER.get(&(self.distance, self.transposition_cost_one)) {
-            // Unwrap the option and build the Ok(AutomatonWeight)
-            Some(automaton_builder) => {
-                let term_text = self.term.as_str().ok_or_else(|| {
-                    crate::TantivyError::InvalidArgument(
-                        "The fuzzy term query requires a string term.".to_string(),
-                    )
-                })?;
-                let automaton = if self.prefix {
-                    automaton_builder.build_prefix_dfa(term_text)
-                } else {
-                    automaton_builder.build_dfa(term_text)
-                };
-                Ok(AutomatonWeight::new(
-                    self.term.field(),
-                    DfaWrapper(automaton),
-                ))
-            }
-            None => Err(InvalidArgument(format!(
-                "Levenshtein distance of {} is not allowed. Choose a value in the {:?} range",
-                self.distance, VALID_LEVENSHTEIN_DISTANCE_RANGE
-            ))),
+        let term_text = self.term.as_str().ok_or_else(|| {
+            crate::TantivyError::InvalidArgument(
+                "The fuzzy term query requires a string term.".to_string(),
+            )
+        })?;
+        let automaton = build_dfa(
+            term_text,
+            self.distance,
+            self.transposition_cost_one,
+            self.prefix,
+        )?;
+        Ok(AutomatonWeight::new(self.term.field(), automaton))
+    }
+}
+
+/// Builds the automaton matching the terms within Levenshtein distance `distance` of
+/// `term_text`, or of one of its prefixes if `prefix` is true.
+pub(crate) fn build_dfa(
+    term_text: &str,
+    distance: u8,
+    transposition_cost_one: bool,
+    prefix: bool,
+) -> crate::Result<DfaWrapper> {
+    // LEV_BUILDER is a HashMap, whose `get` method returns an Option
+    match LEV_BUILDER.get(&(distance, transposition_cost_one)) {
+        Some(automaton_builder) => {
+            let automaton = if prefix {
+                automaton_builder.build_prefix_dfa(term_text)
+            } else {
+                automaton_builder.build_dfa(term_text)
+            };
+            Ok(DfaWrapper(automaton))
         }
+        None => Err(InvalidArgument(format!(
+            "Levenshtein distance of {} is not allowed. Choose a value in the {:?} range",
+            distance, VALID_LEVENSHTEIN_DISTANCE_RANGE
+        ))),
     }
 }
 
 impl Query for FuzzyTermQuery {
     fn weight(
         &self,
-        _searcher: &Searcher,
+        searcher: &Searcher,
         _scoring_enabled: bool,
     ) -> crate::Result<Box<dyn Weight>> {
-        Ok(Box::new(self.specialized_weight()?))
+        let mut weight = self.specialized_weight()?;
+        if let Some((max_expansions, max_expansions_behavior)) = self.max_expansions {
+            weight = weight.limit_expansions(searcher, max_expansions, max_expansions_behavior)?;
+        }
+        Ok(Box::new(weight))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        validate_indexed_field(schema, self.term.field())?;
+        Ok(())
     }
 }
 
@@ -171,8 +212,9 @@ impl Query for FuzzyTermQuery {
 mod test {
     use super::FuzzyTermQuery;
     use crate::collector::{Count, TopDocs};
-    use crate::schema::{Schema, TEXT};
-    use crate::{assert_nearly_equals, Index, Term};
+    use crate::query::MaxExpansionsBehavior;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{assert_nearly_equals, Index, TantivyError, Term};
 
     #[test]
     pub fn test_fuzzy_term() -> crate::Result<()> {
@@ -248,4 +290,41 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    pub fn test_fuzzy_term_max_expansions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let word = schema_builder.add_text_field("word", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for word_text in ["cat", "bat", "hat", "mat", "rat", "cats", "dog"] {
+            index_writer.add_document(doc!(word => word_text))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fuzzy_query = FuzzyTermQuery::new(Term::from_field_text(word, "cat"), 1, true);
+        assert_eq!(searcher.search(&fuzzy_query, &Count)?, 6);
+
+        let capped_query = fuzzy_query
+            .clone()
+            .with_max_expansions(6, MaxExpansionsBehavior::Error);
+        assert_eq!(searcher.search(&capped_query, &Count)?, 6);
+        let capped_query = fuzzy_query
+            .clone()
+            .with_max_expansions(5, MaxExpansionsBehavior::Error);
+        assert!(matches!(
+            searcher.search(&capped_query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        // The smallest terms are kept: "bat", "cat", "cats".
+        let truncated_query = fuzzy_query.with_max_expansions(3, MaxExpansionsBehavior::Truncate);
+        let top_docs = searcher.search(&truncated_query, &TopDocs::with_limit(10))?;
+        let mut doc_ids: Vec<u32> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![0, 1, 5]);
+        Ok(())
+    }
 }
@@ -3,24 +3,35 @@
 mod all_query;
 mod automaton_weight;
 mod bitset;
+mod block_join_query;
 mod bm25;
 mod boolean_query;
 mod boost_query;
 mod const_score_query;
+mod decay_score_query;
 mod disjunction_max_query;
 mod empty_query;
 mod exclude;
+mod exists_query;
 mod explanation;
+mod fast_field_equality_query;
+mod fast_field_range_query;
+mod fast_field_set_query;
+mod filter_then_score_query;
+mod function_score_query;
 mod fuzzy_query;
 mod intersection;
 mod more_like_this;
+mod nearest_neighbor_query;
 mod phrase_query;
+mod prefix_query;
 mod query;
 mod query_parser;
 mod range_query;
 mod regex_query;
 mod reqopt_scorer;
 mod scorer;
+mod segment_pruning_query;
 mod term_query;
 mod union;
 mod weight;
@@ -34,21 +45,34 @@ pub use tantivy_query_grammar::Occur;
 pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
-pub(crate) use self::bm25::Bm25Weight;
+pub use self::block_join_query::{BlockJoinQuery, BlockJoinScoreMode};
+pub use self::bm25::Bm25Weight;
 pub use self::boolean_query::BooleanQuery;
 pub(crate) use self::boolean_query::BooleanWeight;
 pub use self::boost_query::BoostQuery;
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
+pub use self::decay_score_query::{DecayFunction, DecayScoreQuery};
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
+pub(crate) use self::exists_query::supports_exists_query;
+pub use self::exists_query::ExistsQuery;
 pub use self::explanation::Explanation;
+pub use self::fast_field_equality_query::FastFieldEqualityQuery;
+pub use self::fast_field_range_query::FastFieldRangeQuery;
+pub use self::fast_field_set_query::FastFieldSetQuery;
+pub use self::filter_then_score_query::FilterThenScoreQuery;
+pub use self::function_score_query::{FieldValueFunction, FunctionScoreQuery};
+pub(crate) use self::fuzzy_query::build_dfa;
 #[cfg(test)]
 pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::{intersect_scorers, Intersection};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
-pub use self::phrase_query::PhraseQuery;
+pub use self::nearest_neighbor_query::{ExactNearestNeighborQuery, NearestNeighborQuery};
+pub use self::phrase_query::{PhraseQuery, PhraseScorer};
+pub use self::prefix_query::{MaxExpansionsBehavior, PrefixExpansion, PrefixQuery};
+pub(crate) use self::query::{validate_fast_field, validate_field, validate_indexed_field};
 pub use self::query::{Query, QueryClone};
 pub use self::query_parser::{QueryParser, QueryParserError};
 pub use self::range_query::RangeQuery;
@@ -58,7 +82,8 @@ pub use self::score_combiner::{
     DisjunctionMaxCombiner, ScoreCombiner, SumCombiner, SumWithCoordsCombiner,
 };
 pub use self::scorer::Scorer;
-pub use self::term_query::TermQuery;
+pub use self::segment_pruning_query::SegmentPruningQuery;
+pub use self::term_query::{TermQuery, TermScorer};
 pub use self::union::Union;
 #[cfg(test)]
 pub use self::vec_docset::VecDocSet;
@@ -66,9 +91,14 @@ pub use self::weight::Weight;
 
 #[cfg(test)]
 mod tests {
-    use crate::query::QueryParser;
-    use crate::schema::{Schema, TEXT};
-    use crate::{Index, Term};
+    use std::ops::Bound;
+
+    use crate::query::{
+        BooleanQuery, BoostQuery, FastFieldRangeQuery, Occur, PhraseQuery, Query, QueryParser,
+        RangeQuery, TermQuery,
+    };
+    use crate::schema::{Field, IndexRecordOption, Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{Index, TantivyError, Term};
 
     #[test]
     fn test_query_terms() {
@@ -110,4 +140,95 @@ mod tests {
             assert_eq!(vec![(&term_a, false), (&term_b, false)], terms);
         }
     }
+
+    fn validation_schema() -> (Schema, Field, Field, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let string_field = schema_builder.add_text_field("string", STRING);
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let schema = schema_builder.build();
+        (schema, text_field, string_field, indexed_field, fast_field)
+    }
+
+    fn assert_schema_error(validation: crate::Result<()>) {
+        assert!(
+            matches!(validation, Err(TantivyError::SchemaError(_))),
+            "{:?}",
+            validation
+        );
+    }
+
+    #[test]
+    fn test_validate_valid_queries() -> crate::Result<()> {
+        let (schema, text_field, _, indexed_field, fast_field) = validation_schema();
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b"),
+        ]);
+        phrase_query.validate(&schema)?;
+        RangeQuery::new_u64(indexed_field, 0..10).validate(&schema)?;
+        FastFieldRangeQuery::new(fast_field, Bound::Included(0u64), Bound::Unbounded)
+            .validate(&schema)?;
+        let query_parser = QueryParser::new(schema.clone(), vec![text_field], Default::default());
+        query_parser
+            .parse_query("text:a AND indexed:[1 TO 3]")?
+            .validate(&schema)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_non_existent_field() {
+        let (schema, ..) = validation_schema();
+        let unknown_field = Field::from_field_id(schema.num_fields() as u32);
+        let term_query = TermQuery::new(
+            Term::from_field_text(unknown_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        assert_schema_error(term_query.validate(&schema));
+        assert_schema_error(RangeQuery::new_u64(unknown_field, 0..10).validate(&schema));
+    }
+
+    #[test]
+    fn test_validate_phrase_query_without_positions() {
+        let (schema, _, string_field, ..) = validation_schema();
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(string_field, "a"),
+            Term::from_field_text(string_field, "b"),
+        ]);
+        assert_schema_error(phrase_query.validate(&schema));
+    }
+
+    #[test]
+    fn test_validate_range_query_on_non_indexed_field() {
+        let (schema, _, _, indexed_field, fast_field) = validation_schema();
+        assert_schema_error(RangeQuery::new_u64(fast_field, 0..10).validate(&schema));
+        assert_schema_error(RangeQuery::new_i64(indexed_field, 0..10).validate(&schema));
+    }
+
+    #[test]
+    fn test_validate_fast_field_range_query_on_non_fast_field() {
+        let (schema, _, _, indexed_field, _) = validation_schema();
+        let query =
+            FastFieldRangeQuery::new(indexed_field, Bound::Included(0u64), Bound::Unbounded);
+        assert_schema_error(query.validate(&schema));
+    }
+
+    #[test]
+    fn test_validate_nested_query() {
+        let (schema, text_field, _, _, fast_field) = validation_schema();
+        let valid_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        ));
+        let invalid_query: Box<dyn Query> = Box::new(BoostQuery::new(
+            Box::new(RangeQuery::new_u64(fast_field, 0..10)),
+            2.0,
+        ));
+        let boolean_query = BooleanQuery::new(vec![
+            (Occur::Must, valid_query),
+            (Occur::Should, invalid_query),
+        ]);
+        assert_schema_error(boolean_query.validate(&schema));
+    }
 }
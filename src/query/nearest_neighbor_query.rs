@@ -0,0 +1,658 @@
+use common::BitSet;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::error::TantivyError;
+use crate::query::explanation::does_not_match;
+use crate::query::{validate_field, Explanation, Query, Scorer, Weight};
+use crate::schema::{Field, FieldType, Schema};
+use crate::{DocId, DocSet, Score, TERMINATED};
+
+const DEFAULT_EF_SEARCH: usize = 64;
+
+/// `NearestNeighborQuery` matches the documents whose vector, in a dense vector field, is
+/// among the `k` nearest to a given vector.
+///
+/// The score of a document is its similarity to the vector, in `[0, 1]`:
+/// - `(1 + cosine) / 2` with the [`Cosine`](crate::schema::VectorMetric::Cosine) metric,
+/// - `1 / (1 + distance²)` with the [`L2`](crate::schema::VectorMetric::L2) metric,
+/// - the dot product with the [`Dot`](crate::schema::VectorMetric::Dot) metric.
+///
+/// # Implementation
+///
+/// The search is approximate: the HNSW graph of the vectors of each segment is searched for
+/// the `k` nearest alive documents of the segment. Collecting the results with
+/// [`TopDocs`](crate::collector::TopDocs) with a limit of `k` then gives the `k` nearest
+/// documents of the index.
+///
+/// The number of candidates considered by the search of each segment can be increased with
+/// [`NearestNeighborQuery::set_ef_search`], to improve the recall at the expense of the
+/// search time.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::NearestNeighborQuery;
+/// use tantivy::schema::{DenseVectorOptions, Schema, Value, VectorMetric};
+/// use tantivy::{doc, DocAddress, Index};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let embedding = schema_builder.add_dense_vector_field(
+///     "embedding",
+///     DenseVectorOptions::new(2).set_metric(VectorMetric::L2),
+/// );
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(embedding => Value::DenseVector(vec![0.0f32, 0.0])))?;
+/// index_writer.add_document(doc!(embedding => Value::DenseVector(vec![1.0f32, 1.0])))?;
+/// index_writer.add_document(doc!(embedding => Value::DenseVector(vec![5.0f32, 5.0])))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = NearestNeighborQuery::new(embedding, vec![0.9, 1.2], 2);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// assert_eq!(top_docs[1].1, DocAddress::new(0, 0));
+/// # Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct NearestNeighborQuery {
+    field: Field,
+    vector: Vec<f32>,
+    k: usize,
+    ef_search: usize,
+}
+
+impl NearestNeighborQuery {
+    /// Creates a new `NearestNeighborQuery` matching the `k` documents whose vector in the
+    /// dense vector field `field` is the nearest to `vector`.
+    ///
+    /// If the field is not a dense vector field, or if `vector` does not have its dimension,
+    /// an error will be returned when the `Weight` object is created.
+    ///
+    /// # Panics
+    /// The method panics if `k` is 0.
+    pub fn new(field: Field, vector: Vec<f32>, k: usize) -> NearestNeighborQuery {
+        assert!(k >= 1, "k must be strictly greater than 0.");
+        NearestNeighborQuery {
+            field,
+            vector,
+            k,
+            ef_search: DEFAULT_EF_SEARCH,
+        }
+    }
+
+    /// Sets the number of candidates considered by the search of each segment.
+    ///
+    /// It defaults to 64. Values lower than `k` are ignored.
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.ef_search = ef_search;
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+/// Checks that `field` is a dense vector field, and that `vector` has its dimension.
+fn check_query_vector(schema: &Schema, field: Field, vector: &[f32]) -> crate::Result<()> {
+    let field_entry = validate_field(schema, field)?;
+    let options = if let FieldType::DenseVector(options) = field_entry.field_type() {
+        options
+    } else {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a dense vector field.",
+            field_entry.name()
+        )));
+    };
+    if vector.len() != options.dimension() {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Expected a vector of dimension {} for field {:?}, got {}",
+            options.dimension(),
+            field_entry.name(),
+            vector.len()
+        )));
+    }
+    Ok(())
+}
+
+impl Query for NearestNeighborQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        check_query_vector(searcher.schema(), self.field, &self.vector)?;
+        Ok(Box::new(NearestNeighborWeight {
+            query: self.clone(),
+        }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        check_query_vector(schema, self.field, &self.vector)
+    }
+}
+
+struct NearestNeighborWeight {
+    query: NearestNeighborQuery,
+}
+
+impl Weight for NearestNeighborWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let dense_vector_reader = reader.dense_vectors().get_field(self.query.field)?;
+        let docs = dense_vector_reader.search(
+            &self.query.vector,
+            self.query.k,
+            self.query.ef_search,
+            |doc| !reader.is_deleted(doc),
+        );
+        Ok(Box::new(NearestNeighborScorer::new(docs, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("NearestNeighborQuery", scorer.score()))
+    }
+}
+
+/// `ExactNearestNeighborQuery` matches the documents whose vector, in a dense vector field,
+/// is among the `k` nearest to a given vector.
+///
+/// Unlike the [`NearestNeighborQuery`], the search is exact: the vectors of all of the
+/// documents, or of the documents matching a filter query, are compared to the vector. This
+/// is slower on large indexes, but is a good fit when the filter is selective, and gives a
+/// reference to measure the recall of the [`NearestNeighborQuery`].
+///
+/// The scores are the same as the ones of the [`NearestNeighborQuery`].
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{ExactNearestNeighborQuery, TermQuery};
+/// use tantivy::schema::{DenseVectorOptions, IndexRecordOption, Schema, Value, VectorMetric, STRING};
+/// use tantivy::{doc, DocAddress, Index, Term};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let color = schema_builder.add_text_field("color", STRING);
+/// let embedding = schema_builder.add_dense_vector_field(
+///     "embedding",
+///     DenseVectorOptions::new(2).set_metric(VectorMetric::Dot),
+/// );
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(color => "red", embedding => Value::DenseVector(vec![3.0f32, 0.0])))?;
+/// index_writer.add_document(doc!(color => "blue", embedding => Value::DenseVector(vec![1.0f32, 1.0])))?;
+/// index_writer.add_document(doc!(color => "blue", embedding => Value::DenseVector(vec![0.0f32, 1.0])))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let mut query = ExactNearestNeighborQuery::new(embedding, vec![1.0, 0.5], 1);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+/// assert_eq!(top_docs, vec![(3.0, DocAddress::new(0, 0))]);
+///
+/// query.set_filter(Box::new(TermQuery::new(
+///     Term::from_field_text(color, "blue"),
+///     IndexRecordOption::Basic,
+/// )));
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+/// assert_eq!(top_docs, vec![(1.5, DocAddress::new(0, 1))]);
+/// # Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Debug)]
+pub struct ExactNearestNeighborQuery {
+    field: Field,
+    vector: Vec<f32>,
+    k: usize,
+    filter: Option<Box<dyn Query>>,
+}
+
+impl Clone for ExactNearestNeighborQuery {
+    fn clone(&self) -> Self {
+        ExactNearestNeighborQuery {
+            field: self.field,
+            vector: self.vector.clone(),
+            k: self.k,
+            filter: self.filter.as_ref().map(|filter| filter.box_clone()),
+        }
+    }
+}
+
+impl ExactNearestNeighborQuery {
+    /// Creates a new `ExactNearestNeighborQuery` matching the `k` documents whose vector in
+    /// the dense vector field `field` is the nearest to `vector`.
+    ///
+    /// If the field is not a dense vector field, or if `vector` does not have its dimension,
+    /// an error will be returned when the `Weight` object is created.
+    ///
+    /// # Panics
+    /// The method panics if `k` is 0.
+    pub fn new(field: Field, vector: Vec<f32>, k: usize) -> ExactNearestNeighborQuery {
+        assert!(k >= 1, "k must be strictly greater than 0.");
+        ExactNearestNeighborQuery {
+            field,
+            vector,
+            k,
+            filter: None,
+        }
+    }
+
+    /// Restricts the candidates to the documents matching `filter`.
+    ///
+    /// The filter does not contribute to the score.
+    pub fn set_filter(&mut self, filter: Box<dyn Query>) {
+        self.filter = Some(filter);
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Query for ExactNearestNeighborQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        check_query_vector(searcher.schema(), self.field, &self.vector)?;
+        let filter_weight = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.weight(searcher, false))
+            .transpose()?;
+        Ok(Box::new(ExactNearestNeighborWeight {
+            field: self.field,
+            vector: self.vector.clone(),
+            k: self.k,
+            filter_weight,
+        }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        check_query_vector(schema, self.field, &self.vector)?;
+        if let Some(filter) = &self.filter {
+            filter.validate(schema)?;
+        }
+        Ok(())
+    }
+}
+
+struct ExactNearestNeighborWeight {
+    field: Field,
+    vector: Vec<f32>,
+    k: usize,
+    filter_weight: Option<Box<dyn Weight>>,
+}
+
+impl Weight for ExactNearestNeighborWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let dense_vector_reader = reader.dense_vectors().get_field(self.field)?;
+        let docs = if let Some(filter_weight) = self.filter_weight.as_ref() {
+            let mut candidates = BitSet::with_max_value(reader.max_doc());
+            let mut filter_scorer = filter_weight.scorer(reader, 1.0)?;
+            let mut doc = filter_scorer.doc();
+            while doc != TERMINATED {
+                candidates.insert(doc);
+                doc = filter_scorer.advance();
+            }
+            dense_vector_reader.exact_search(&self.vector, self.k, |doc| {
+                candidates.contains(doc) && !reader.is_deleted(doc)
+            })
+        } else {
+            dense_vector_reader.exact_search(&self.vector, self.k, |doc| !reader.is_deleted(doc))
+        };
+        Ok(Box::new(NearestNeighborScorer::new(docs, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new(
+            "ExactNearestNeighborQuery",
+            scorer.score(),
+        ))
+    }
+}
+
+struct NearestNeighborScorer {
+    // Nearest documents, sorted by doc id, along with their score.
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    doc: DocId,
+    boost: Score,
+}
+
+impl NearestNeighborScorer {
+    fn new(mut docs: Vec<(DocId, Score)>, boost: Score) -> NearestNeighborScorer {
+        docs.sort_unstable_by_key(|&(doc, _)| doc);
+        let doc = docs.first().map(|&(doc, _)| doc).unwrap_or(TERMINATED);
+        NearestNeighborScorer {
+            docs,
+            cursor: 0,
+            doc,
+            boost,
+        }
+    }
+}
+
+impl DocSet for NearestNeighborScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.cursor += 1;
+        self.doc = self
+            .docs
+            .get(self.cursor)
+            .map(|&(doc, _)| doc)
+            .unwrap_or(TERMINATED);
+        self.doc
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len().saturating_sub(self.cursor) as u32
+    }
+}
+
+impl Scorer for NearestNeighborScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1 * self.boost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{ExactNearestNeighborQuery, NearestNeighborQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{Query, QueryParser, TermQuery};
+    use crate::schema::{
+        DenseVectorOptions, IndexRecordOption, Schema, Value, VectorMetric, INDEXED, STORED, STRING,
+    };
+    use crate::{Index, IndexWriter, Searcher, TantivyError, Term};
+
+    /// Deterministic pseudo random vectors, in `[-1, 1]^dimension`.
+    fn random_vectors(num_vectors: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        let mut next_val = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((state >> 40) as f32 / (1u64 << 23) as f32) - 1.0
+        };
+        (0..num_vectors)
+            .map(|_| (0..dimension).map(|_| next_val()).collect())
+            .collect()
+    }
+
+    fn distance(metric: VectorMetric, left: &[f32], right: &[f32]) -> f32 {
+        match metric {
+            VectorMetric::Cosine => {
+                let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+                let norm = |vector: &[f32]| vector.iter().map(|val| val * val).sum::<f32>().sqrt();
+                1.0 - dot / (norm(left) * norm(right))
+            }
+            VectorMetric::L2 => left.iter().zip(right).map(|(l, r)| (l - r) * (l - r)).sum(),
+            VectorMetric::Dot => -left.iter().zip(right).map(|(l, r)| l * r).sum::<f32>(),
+        }
+    }
+
+    fn nearest_ids(searcher: &Searcher, query: &dyn Query, k: usize) -> Vec<u64> {
+        let id = searcher.schema().get_field("id").unwrap();
+        searcher
+            .search(query, &TopDocs::with_limit(k))
+            .unwrap()
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc = searcher.doc(doc_address).unwrap();
+                doc.get_first(id).unwrap().as_u64().unwrap()
+            })
+            .collect()
+    }
+
+    fn test_recall_aux(metric: VectorMetric) -> crate::Result<()> {
+        const NUM_VECTORS: usize = 2_000;
+        const DIMENSION: usize = 16;
+        const K: usize = 10;
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED);
+        let embedding = schema_builder.add_dense_vector_field(
+            "embedding",
+            DenseVectorOptions::new(DIMENSION).set_metric(metric),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let vectors = random_vectors(NUM_VECTORS, DIMENSION, 42);
+        for (doc_id, vector) in vectors.iter().enumerate() {
+            index_writer.add_document(
+                doc!(id => doc_id as u64, embedding => Value::DenseVector(vector.clone())),
+            )?;
+            if doc_id == NUM_VECTORS / 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let mut num_found = 0;
+        let queries = random_vectors(50, DIMENSION, 7);
+        for query_vector in &queries {
+            let mut expected: Vec<(f32, u64)> = vectors
+                .iter()
+                .enumerate()
+                .map(|(doc_id, vector)| (distance(metric, query_vector, vector), doc_id as u64))
+                .collect();
+            expected.sort_by(|left, right| left.partial_cmp(right).unwrap());
+            let expected: HashSet<u64> = expected[..K].iter().map(|&(_, doc_id)| doc_id).collect();
+            let query = NearestNeighborQuery::new(embedding, query_vector.clone(), K);
+            let found = nearest_ids(&searcher, &query, K);
+            assert_eq!(found.len(), K);
+            num_found += found
+                .iter()
+                .filter(|doc_id| expected.contains(doc_id))
+                .count();
+        }
+        let recall = num_found as f32 / (queries.len() * K) as f32;
+        assert!(recall >= 0.9, "recall {recall} is too low");
+        Ok(())
+    }
+
+    #[test]
+    fn test_nearest_neighbor_query_recall_cosine() -> crate::Result<()> {
+        test_recall_aux(VectorMetric::Cosine)
+    }
+
+    #[test]
+    fn test_nearest_neighbor_query_recall_l2() -> crate::Result<()> {
+        test_recall_aux(VectorMetric::L2)
+    }
+
+    #[test]
+    fn test_nearest_neighbor_query_deletes_and_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED);
+        let parity = schema_builder.add_text_field("parity", STRING);
+        let embedding = schema_builder.add_dense_vector_field(
+            "embedding",
+            DenseVectorOptions::new(1).set_metric(VectorMetric::L2),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..100u64 {
+            let parity_text = if doc_id % 2 == 0 { "even" } else { "odd" };
+            if doc_id % 10 == 3 {
+                // Documents without vector.
+                index_writer.add_document(doc!(id => doc_id, parity => parity_text))?;
+            } else {
+                index_writer.add_document(doc!(
+                    id => doc_id,
+                    parity => parity_text,
+                    embedding => Value::DenseVector(vec![doc_id as f32])
+                ))?;
+            }
+            if doc_id % 25 == 24 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(Term::from_field_text(parity, "even"));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let query = NearestNeighborQuery::new(embedding, vec![50.2], 3);
+        assert_eq!(nearest_ids(&reader.searcher(), &query, 3), vec![51, 49, 47]);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(nearest_ids(&searcher, &query, 3), vec![51, 49, 47]);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        let explanation = Query::explain(&query, &searcher, top_docs[0].1)?;
+        assert_eq!(explanation.value(), top_docs[0].0);
+        let nearest_query = NearestNeighborQuery::new(embedding, vec![50.2], 1);
+        assert!(Query::explain(&nearest_query, &searcher, top_docs[1].1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nearest_neighbor_query_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let embedding =
+            schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(2));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.add_document(doc!(embedding => Value::DenseVector(vec![1.0f32]))),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer
+                .add_document(doc!(embedding => Value::DenseVector(vec![1.0f32, f32::NAN]))),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer
+                .add_document(doc!(embedding => Value::DenseVector(vec![1.0f32, 0.0]), embedding => Value::DenseVector(vec![0.0f32, 1.0]))),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        index_writer.add_document(
+            doc!(text => "hello", embedding => Value::DenseVector(vec![1.0f32, 0.0])),
+        )?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |query: NearestNeighborQuery| searcher.search(&query, &Count);
+        assert_eq!(
+            count(NearestNeighborQuery::new(embedding, vec![0.0, 1.0], 1))?,
+            1
+        );
+        assert!(matches!(
+            count(NearestNeighborQuery::new(embedding, vec![0.0, 1.0, 2.0], 1)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            count(NearestNeighborQuery::new(text, vec![0.0, 1.0], 1)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(QueryParser::for_index(&index, vec![embedding])
+            .parse_query("embedding:1")
+            .is_err());
+        Ok(())
+    }
+
+    fn test_exact_nearest_neighbor_aux(metric: VectorMetric) -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED | INDEXED);
+        let parity = schema_builder.add_text_field("parity", STRING);
+        let embedding = schema_builder
+            .add_dense_vector_field("embedding", DenseVectorOptions::new(3).set_metric(metric));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let vectors = random_vectors(60, 3, 3);
+        for (doc_id, vector) in vectors.iter().enumerate() {
+            let parity_text = if doc_id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                id => doc_id as u64,
+                parity => parity_text,
+                embedding => Value::DenseVector(vector.clone())
+            ))?;
+            if doc_id % 20 == 19 {
+                index_writer.commit()?;
+            }
+        }
+        // Documents which are a multiple of 3 are deleted.
+        for doc_id in (0..60u64).step_by(3) {
+            index_writer.delete_term(Term::from_field_u64(id, doc_id));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_vector = vec![0.3f32, -0.5, 0.8];
+        let expected_ids = |accept: &dyn Fn(u64) -> bool| -> Vec<u64> {
+            let mut expected: Vec<(f32, u64)> = vectors
+                .iter()
+                .enumerate()
+                .map(|(doc_id, vector)| (distance(metric, &query_vector, vector), doc_id as u64))
+                .filter(|&(_, doc_id)| doc_id % 3 != 0 && accept(doc_id))
+                .collect();
+            expected.sort_by(|left, right| left.partial_cmp(right).unwrap());
+            expected
+                .into_iter()
+                .take(5)
+                .map(|(_, doc_id)| doc_id)
+                .collect()
+        };
+
+        let mut query = ExactNearestNeighborQuery::new(embedding, query_vector.clone(), 5);
+        assert_eq!(nearest_ids(&searcher, &query, 5), expected_ids(&|_| true));
+        assert_eq!(searcher.search(&query, &Count)?, 15);
+
+        query.set_filter(Box::new(TermQuery::new(
+            Term::from_field_text(parity, "odd"),
+            IndexRecordOption::Basic,
+        )));
+        assert_eq!(
+            nearest_ids(&searcher, &query, 5),
+            expected_ids(&|doc_id| doc_id % 2 == 1)
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        let explanation = Query::explain(&query, &searcher, top_docs[0].1)?;
+        assert_eq!(explanation.value(), top_docs[0].0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact_nearest_neighbor_query_cosine() -> crate::Result<()> {
+        test_exact_nearest_neighbor_aux(VectorMetric::Cosine)
+    }
+
+    #[test]
+    fn test_exact_nearest_neighbor_query_l2() -> crate::Result<()> {
+        test_exact_nearest_neighbor_aux(VectorMetric::L2)
+    }
+
+    #[test]
+    fn test_exact_nearest_neighbor_query_dot() -> crate::Result<()> {
+        test_exact_nearest_neighbor_aux(VectorMetric::Dot)
+    }
+}
@@ -15,8 +15,10 @@ pub mod tests {
     use crate::collector::tests::{TEST_COLLECTOR_WITHOUT_SCORE, TEST_COLLECTOR_WITH_SCORE};
     use crate::core::Index;
     use crate::query::{QueryParser, Weight};
-    use crate::schema::{Schema, Term, TEXT};
-    use crate::{assert_nearly_equals, DocAddress, DocId, TERMINATED};
+    use crate::schema::{
+        IndexRecordOption, PositionsCompression, Schema, Term, TextFieldIndexing, TextOptions, TEXT,
+    };
+    use crate::{assert_nearly_equals, DocAddress, DocId, DocSet, TERMINATED};
 
     pub fn create_index(texts: &[&'static str]) -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
@@ -384,4 +386,186 @@ pub mod tests {
         assert_eq!(&matching_docs(r#"arr.text:"elliot smith""#), &[2]);
         Ok(())
     }
+
+    #[test]
+    pub fn test_phrase_query_multivalued_position_gap() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let default_gap_field = schema_builder.add_text_field("default_gap", TEXT);
+        let large_gap_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_position_gap(100),
+        );
+        let large_gap_field = schema_builder.add_text_field("large_gap", large_gap_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(
+                default_gap_field => "a b",
+                default_gap_field => "c d",
+                large_gap_field => "a b",
+                large_gap_field => "c d",
+            ))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let num_matches = |field, texts: &[&str], slop: u32| {
+            let terms: Vec<Term> = texts
+                .iter()
+                .map(|text| Term::from_field_text(field, text))
+                .collect();
+            let mut phrase_query = PhraseQuery::new(terms);
+            phrase_query.set_slop(slop);
+            searcher
+                .search(&phrase_query, &TEST_COLLECTOR_WITHOUT_SCORE)
+                .unwrap()
+                .docs()
+                .len()
+        };
+        // Within a single value, both fields behave the same.
+        assert_eq!(num_matches(default_gap_field, &["a", "b"], 0), 1);
+        assert_eq!(num_matches(large_gap_field, &["c", "d"], 0), 1);
+        // Exact phrases never straddle two values.
+        assert_eq!(num_matches(default_gap_field, &["b", "c"], 0), 0);
+        assert_eq!(num_matches(large_gap_field, &["b", "c"], 0), 0);
+        // With the default gap, a small slop is enough to match across values.
+        assert_eq!(num_matches(default_gap_field, &["b", "c"], 1), 1);
+        assert_eq!(num_matches(large_gap_field, &["b", "c"], 1), 0);
+        assert_eq!(num_matches(large_gap_field, &["b", "c"], 50), 0);
+        assert_eq!(num_matches(large_gap_field, &["b", "c"], 100), 1);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_phrase_query_positions_compression() -> crate::Result<()> {
+        let create_index = |positions_compression: PositionsCompression| -> crate::Result<Index> {
+            let mut schema_builder = Schema::builder();
+            let text_options = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                    .set_positions_compression(positions_compression),
+            );
+            let text_field = schema_builder.add_text_field("text", text_options);
+            let index = Index::create_in_ram(schema_builder.build());
+            let mut index_writer = index.writer_for_tests()?;
+            for segment in 0..2 {
+                for i in 0..100usize {
+                    let text: Vec<&str> = (0..(i % 7 + 3) * 10 + segment)
+                        .map(|j| ["a", "b", "c", "d", "e"][(i + j * j + j / 3) % 5])
+                        .collect();
+                    index_writer.add_document(doc!(text_field => text.join(" ")))?;
+                }
+                index_writer.commit()?;
+            }
+            Ok(index)
+        };
+        let vint_index = create_index(PositionsCompression::VInt)?;
+        let bitpacked_index = create_index(PositionsCompression::Bitpacked)?;
+        let positions_size = |index: &Index| -> crate::Result<usize> {
+            Ok(index
+                .reader()?
+                .searcher()
+                .space_usage()?
+                .segments()
+                .iter()
+                .map(|segment| segment.positions().total())
+                .sum())
+        };
+        assert!(positions_size(&bitpacked_index)? < positions_size(&vint_index)?);
+        let matching_docs = |index: &Index, texts: &[&str]| -> crate::Result<Vec<DocAddress>> {
+            let text_field = index.schema().get_field("text").unwrap();
+            let terms: Vec<Term> = texts
+                .iter()
+                .map(|text| Term::from_field_text(text_field, text))
+                .collect();
+            let searcher = index.reader()?.searcher();
+            let fruits =
+                searcher.search(&PhraseQuery::new(terms), &TEST_COLLECTOR_WITHOUT_SCORE)?;
+            Ok(fruits.docs().to_vec())
+        };
+        let phrases: [&[&str]; 4] = [&["a", "d"], &["e", "e"], &["a", "b", "e"], &["b", "a", "c"]];
+        for phrase in phrases {
+            let expected = matching_docs(&vint_index, phrase)?;
+            assert!(!expected.is_empty());
+            assert_eq!(matching_docs(&bitpacked_index, phrase)?, expected);
+        }
+        // Merging decodes and reencodes the positions.
+        for index in [&vint_index, &bitpacked_index] {
+            let segment_ids = index.searchable_segment_ids()?;
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        for phrase in phrases {
+            let expected = matching_docs(&vint_index, phrase)?;
+            assert_eq!(matching_docs(&bitpacked_index, phrase)?, expected);
+        }
+        Ok(())
+    }
+
+    /// Returns the texts of the tokens of `text` at the given positions.
+    fn tokens_at(index: &Index, text: &str, positions: &[u32]) -> Vec<String> {
+        let tokenizer = index.tokenizers().get("default").unwrap();
+        let mut tokens = Vec::new();
+        tokenizer.token_stream(text).process(&mut |token| {
+            if positions.contains(&(token.position as u32)) {
+                tokens.push(token.text.clone());
+            }
+        });
+        tokens
+    }
+
+    #[test]
+    pub fn test_phrase_scorer_matched_positions() -> crate::Result<()> {
+        let texts = [
+            "The quick brown fox jumps over the lazy dog",
+            "a quick brown dog, and another quick brown fox: quick brown fox",
+            "quick or brown fox",
+        ];
+        let index = create_index(&texts)?;
+        let text_field = index.schema().get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let matched_tokens = |phrase: &[&str], slop: u32| -> crate::Result<Vec<Vec<String>>> {
+            let terms: Vec<Term> = phrase
+                .iter()
+                .map(|text| Term::from_field_text(text_field, text))
+                .collect();
+            let mut phrase_query = PhraseQuery::new(terms);
+            phrase_query.set_slop(slop);
+            let phrase_weight = phrase_query.phrase_weight(&searcher, true)?;
+            let mut phrase_scorer = phrase_weight
+                .phrase_scorer(searcher.segment_reader(0), 1.0)?
+                .unwrap();
+            let mut positions = Vec::new();
+            let mut tokens = Vec::new();
+            while phrase_scorer.doc() != TERMINATED {
+                phrase_scorer.matched_positions(&mut positions);
+                let text = texts[phrase_scorer.doc() as usize];
+                tokens.push(tokens_at(&index, text, &positions));
+                phrase_scorer.advance();
+            }
+            Ok(tokens)
+        };
+        assert_eq!(
+            matched_tokens(&["quick", "brown", "fox"], 0)?,
+            vec![
+                vec!["quick", "brown", "fox"],
+                vec!["quick", "brown", "fox", "quick", "brown", "fox"],
+            ]
+        );
+        assert_eq!(
+            matched_tokens(&["quick", "fox"], 1)?,
+            vec![vec!["quick", "fox"], vec!["quick", "fox", "quick", "fox"]]
+        );
+        assert_eq!(
+            matched_tokens(&["quick", "fox"], 2)?,
+            vec![
+                vec!["quick", "fox"],
+                vec!["quick", "fox", "quick", "fox"],
+                vec!["quick", "fox"],
+            ]
+        );
+        Ok(())
+    }
 }
@@ -1,8 +1,8 @@
 use super::PhraseWeight;
 use crate::core::searcher::Searcher;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{Query, Weight};
-use crate::schema::{Field, IndexRecordOption, Term};
+use crate::query::{validate_field, Query, Weight};
+use crate::schema::{Field, IndexRecordOption, Schema, Term};
 
 /// `PhraseQuery` matches a specific sequence of words.
 ///
@@ -94,20 +94,7 @@ impl PhraseQuery {
         searcher: &Searcher,
         scoring_enabled: bool,
     ) -> crate::Result<PhraseWeight> {
-        let schema = searcher.schema();
-        let field_entry = schema.get_field_entry(self.field);
-        let has_positions = field_entry
-            .field_type()
-            .get_index_record_option()
-            .map(IndexRecordOption::has_positions)
-            .unwrap_or(false);
-        if !has_positions {
-            let field_name = field_entry.name();
-            return Err(crate::TantivyError::SchemaError(format!(
-                "Applied phrase query on field {:?}, which does not have positions indexed",
-                field_name
-            )));
-        }
+        self.validate(searcher.schema())?;
         let terms = self.phrase_terms();
         let bm25_weight = Bm25Weight::for_terms(searcher, &terms)?;
         let mut weight = PhraseWeight::new(self.phrase_terms.clone(), bm25_weight, scoring_enabled);
@@ -132,4 +119,21 @@ impl Query for PhraseQuery {
             visitor(term, true);
         }
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        let field_entry = validate_field(schema, self.field)?;
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(IndexRecordOption::has_positions)
+            .unwrap_or(false);
+        if !has_positions {
+            let field_name = field_entry.name();
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Applied phrase query on field {:?}, which does not have positions indexed",
+                field_name
+            )));
+        }
+        Ok(())
+    }
 }
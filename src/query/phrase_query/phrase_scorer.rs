@@ -43,6 +43,9 @@ impl<TPostings: Postings> DocSet for PostingsWithOffset<TPostings> {
     }
 }
 
+/// Scorer for the documents matching a phrase, as returned by the [`PhraseQuery`] weight.
+///
+/// [`PhraseQuery`]: crate::query::PhraseQuery
 pub struct PhraseScorer<TPostings: Postings> {
     intersection_docset: Intersection<PostingsWithOffset<TPostings>, PostingsWithOffset<TPostings>>,
     num_terms: usize,
@@ -244,8 +247,39 @@ fn intersection_exists_with_slop(left: &[u32], right: &[u32], slop: u32) -> bool
     false
 }
 
+/// Extends each chain of aligned positions with the matching aligned position of the next term
+/// of the phrase, following the same rules as `intersection` and `intersection_with_slop`.
+///
+/// Chains that do not match are dropped.
+fn extend_chains(mut chains: Vec<Vec<u32>>, right: &[u32], slop: u32) -> Vec<Vec<u32>> {
+    let mut extended_chains = Vec::new();
+    let mut left_index = 0;
+    let mut right_index = 0;
+    let last_val = |chain: &[u32]| chain.last().copied().unwrap_or(0);
+    while left_index < chains.len() && right_index < right.len() {
+        let left_val = last_val(&chains[left_index]);
+        let right_val = right[right_index];
+        let right_slop = right_val.saturating_sub(slop);
+        if left_val < right_slop {
+            left_index += 1;
+        } else if left_val <= right_val {
+            while left_index + 1 < chains.len() && last_val(&chains[left_index + 1]) <= right_val {
+                left_index += 1;
+            }
+            let mut chain = std::mem::take(&mut chains[left_index]);
+            chain.push(right_val);
+            extended_chains.push(chain);
+            left_index += 1;
+            right_index += 1;
+        } else {
+            right_index += 1;
+        }
+    }
+    extended_chains
+}
+
 impl<TPostings: Postings> PhraseScorer<TPostings> {
-    pub fn new(
+    pub(crate) fn new(
         term_postings: Vec<(usize, TPostings)>,
         similarity_weight: Bm25Weight,
         fieldnorm_reader: FieldNormReader,
@@ -281,10 +315,48 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         scorer
     }
 
+    /// Returns the number of occurrences of the phrase in the current document.
+    ///
+    /// It is only computed if scoring is enabled.
     pub fn phrase_count(&self) -> u32 {
         self.phrase_count
     }
 
+    /// Returns the positions of the terms of all of the phrase matches within the current
+    /// document, in increasing order.
+    ///
+    /// The positions are not computed while iterating over the matching documents, but only
+    /// when this method is called. It should therefore only be called for the few documents
+    /// that need to be annotated, typically for highlighting.
+    pub fn matched_positions(&mut self, output: &mut Vec<u32>) {
+        output.clear();
+        if self.doc() == TERMINATED {
+            return;
+        }
+        // Each chain holds the aligned positions of the terms of a phrase match.
+        self.intersection_docset
+            .docset_mut_specialized(0)
+            .positions(&mut self.left);
+        let mut chains: Vec<Vec<u32>> = self.left.iter().map(|&pos| vec![pos]).collect();
+        for term_ord in 1..self.num_terms {
+            self.intersection_docset
+                .docset_mut_specialized(term_ord)
+                .positions(&mut self.right);
+            chains = extend_chains(chains, &self.right, self.slop);
+        }
+        for chain in chains {
+            for (term_ord, aligned_pos) in chain.into_iter().enumerate() {
+                let offset = self
+                    .intersection_docset
+                    .docset_mut_specialized(term_ord)
+                    .offset;
+                output.push(aligned_pos - offset);
+            }
+        }
+        output.sort_unstable();
+        output.dedup();
+    }
+
     fn phrase_match(&mut self) -> bool {
         if self.scoring_enabled {
             let count = self.compute_phrase_count();
@@ -0,0 +1,332 @@
+use std::collections::BTreeSet;
+use std::io;
+
+use common::BitSet;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::error::TantivyError;
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    validate_indexed_field, BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{IndexRecordOption, Schema, Term};
+use crate::termdict::TermDictionary;
+use crate::{DocId, Score};
+
+/// Defines what a query expanding to the terms matching a pattern, e.g. a [`PrefixQuery`],
+/// a [`FuzzyTermQuery`](crate::query::FuzzyTermQuery) or a
+/// [`RegexQuery`](crate::query::RegexQuery), does when its pattern matches more than
+/// `max_expansions` terms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MaxExpansionsBehavior {
+    /// Building the query weight fails with a `TantivyError::InvalidArgument`.
+    #[default]
+    Error,
+    /// Only the `max_expansions` smallest matching terms are searched for.
+    Truncate,
+}
+
+/// The terms a [`PrefixQuery`] expands to.
+#[derive(Clone, Debug)]
+pub struct PrefixExpansion {
+    terms: Vec<Term>,
+    truncated: bool,
+}
+
+impl PrefixExpansion {
+    /// Returns the sorted terms matching the prefix.
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// Returns the number of terms matching the prefix.
+    ///
+    /// If the expansion was truncated, this is the number of terms kept, i.e.
+    /// `max_expansions`.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Returns true if more than `max_expansions` terms match the prefix, and
+    /// only the first `max_expansions` were kept.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// `PrefixQuery` matches all documents containing a term starting with a given prefix.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// A short prefix can match a very large number of terms. The number of terms the query
+/// expands to is capped by `max_expansions`. By default, exceeding the cap is an error.
+/// With [`MaxExpansionsBehavior::Truncate`], only the `max_expansions` smallest matching terms
+/// are searched for instead.
+///
+/// # Implementation
+///
+/// The expansion is computed over the term dictionaries of all of the segments of the
+/// searcher when the weight is built. At most `max_expansions + 1` terms are read from each
+/// term dictionary.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::PrefixQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Term};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = PrefixQuery::new(Term::from_field_text(title, "d"), 10);
+/// assert_eq!(query.expand(&searcher)?.num_terms(), 2);
+/// assert_eq!(searcher.search(&query, &Count)?, 3);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrefixQuery {
+    prefix: Term,
+    max_expansions: usize,
+    max_expansions_behavior: MaxExpansionsBehavior,
+}
+
+impl PrefixQuery {
+    /// Creates a new `PrefixQuery` matching the terms starting with `prefix`, expanding to at
+    /// most `max_expansions` terms.
+    pub fn new(prefix: Term, max_expansions: usize) -> PrefixQuery {
+        PrefixQuery {
+            prefix,
+            max_expansions,
+            max_expansions_behavior: MaxExpansionsBehavior::default(),
+        }
+    }
+
+    /// Sets what happens when the prefix matches more than `max_expansions` terms.
+    #[must_use]
+    pub fn with_max_expansions_behavior(
+        mut self,
+        max_expansions_behavior: MaxExpansionsBehavior,
+    ) -> PrefixQuery {
+        self.max_expansions_behavior = max_expansions_behavior;
+        self
+    }
+
+    /// Returns the prefix.
+    pub fn prefix(&self) -> &Term {
+        &self.prefix
+    }
+
+    /// Returns the maximum number of terms the prefix may expand to.
+    pub fn max_expansions(&self) -> usize {
+        self.max_expansions
+    }
+
+    /// Computes the terms the prefix expands to over the segments of `searcher`.
+    ///
+    /// Returns an error if more than `max_expansions` terms match and the query is
+    /// configured with [`MaxExpansionsBehavior::Error`].
+    pub fn expand(&self, searcher: &Searcher) -> crate::Result<PrefixExpansion> {
+        let field = self.prefix.field();
+        let mut matching_keys: BTreeSet<Vec<u8>> = BTreeSet::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            // The `max_expansions + 1` smallest matching terms of each segment are enough to
+            // get the `max_expansions + 1` smallest matching terms overall.
+            self.collect_matching_keys(inverted_index.terms(), &mut matching_keys)?;
+        }
+        let truncated = matching_keys.len() > self.max_expansions;
+        if truncated && self.max_expansions_behavior == MaxExpansionsBehavior::Error {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Prefix {:?} matches more than {} terms",
+                self.prefix, self.max_expansions
+            )));
+        }
+        let terms = matching_keys
+            .into_iter()
+            .take(self.max_expansions)
+            .map(|key| {
+                let mut term = self.prefix.clone();
+                term.clear_with_type(self.prefix.typ());
+                term.append_bytes(&key);
+                term
+            })
+            .collect();
+        Ok(PrefixExpansion { terms, truncated })
+    }
+
+    fn collect_matching_keys(
+        &self,
+        term_dict: &TermDictionary,
+        matching_keys: &mut BTreeSet<Vec<u8>>,
+    ) -> io::Result<()> {
+        let prefix = self.prefix.value_bytes();
+        let mut term_stream = term_dict.range().ge(prefix).into_stream()?;
+        let mut num_keys = 0;
+        while num_keys <= self.max_expansions && term_stream.advance() {
+            let key = term_stream.key();
+            if !key.starts_with(prefix) {
+                break;
+            }
+            matching_keys.insert(key.to_vec());
+            num_keys += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Query for PrefixQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let expansion = self.expand(searcher)?;
+        Ok(Box::new(PrefixWeight {
+            terms: expansion.terms,
+        }))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        validate_indexed_field(schema, self.prefix.field())?;
+        Ok(())
+    }
+}
+
+/// Weight associated to the `PrefixQuery`.
+struct PrefixWeight {
+    terms: Vec<Term>,
+}
+
+impl Weight for PrefixWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        if let Some(first_term) = self.terms.first() {
+            let inverted_index = reader.inverted_index(first_term.field())?;
+            for term in &self.terms {
+                let term_info = if let Some(term_info) = inverted_index.get_term_info(term)? {
+                    term_info
+                } else {
+                    continue;
+                };
+                let mut block_segment_postings = inverted_index
+                    .read_block_postings_from_terminfo(&term_info, IndexRecordOption::Basic)?;
+                loop {
+                    let docs = block_segment_postings.docs();
+                    if docs.is_empty() {
+                        break;
+                    }
+                    for &doc in docs {
+                        doc_bitset.insert(doc);
+                    }
+                    block_segment_postings.advance();
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("PrefixQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MaxExpansionsBehavior, PrefixQuery};
+    use crate::collector::Count;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_prefix_query_few_and_many_matches() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let word = schema_builder.add_text_field("word", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..100 {
+                index_writer.add_document(doc!(word => format!("many{:02}", i)))?;
+                if i % 10 == 0 {
+                    // Spread the terms over several segments.
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.add_document(doc!(word => "few1"))?;
+            index_writer.add_document(doc!(word => "few2"))?;
+            index_writer.add_document(doc!(word => "few2"))?;
+            index_writer.add_document(doc!(word => "fewer"))?;
+            index_writer.add_document(doc!(word => "fe"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+
+        let few = PrefixQuery::new(Term::from_field_text(word, "few"), 10);
+        let expansion = few.expand(&searcher)?;
+        assert_eq!(expansion.num_terms(), 3);
+        assert!(!expansion.is_truncated());
+        let terms: Vec<&str> = expansion
+            .terms()
+            .iter()
+            .map(|term| term.as_str().unwrap())
+            .collect();
+        assert_eq!(terms, vec!["few1", "few2", "fewer"]);
+        assert_eq!(searcher.search(&few, &Count)?, 4);
+
+        let no_match = PrefixQuery::new(Term::from_field_text(word, "nope"), 10);
+        assert_eq!(no_match.expand(&searcher)?.num_terms(), 0);
+        assert_eq!(searcher.search(&no_match, &Count)?, 0);
+
+        let many = PrefixQuery::new(Term::from_field_text(word, "many"), 10);
+        assert!(matches!(
+            many.expand(&searcher),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            searcher.search(&many, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        let many = many.with_max_expansions_behavior(MaxExpansionsBehavior::Truncate);
+        let expansion = many.expand(&searcher)?;
+        assert_eq!(expansion.num_terms(), 10);
+        assert!(expansion.is_truncated());
+        let terms: Vec<&str> = expansion
+            .terms()
+            .iter()
+            .map(|term| term.as_str().unwrap())
+            .collect();
+        let expected: Vec<String> = (0..10).map(|i| format!("many{:02}", i)).collect();
+        assert_eq!(terms, expected);
+        assert_eq!(searcher.search(&many, &Count)?, 10);
+
+        // Exactly `max_expansions` matching terms is not an error.
+        let all_many = PrefixQuery::new(Term::from_field_text(word, "many"), 100);
+        let expansion = all_many.expand(&searcher)?;
+        assert_eq!(expansion.num_terms(), 100);
+        assert!(!expansion.is_truncated());
+        assert_eq!(searcher.search(&all_many, &Count)?, 100);
+        Ok(())
+    }
+}
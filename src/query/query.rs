@@ -4,8 +4,10 @@ use downcast_rs::impl_downcast;
 
 use super::Weight;
 use crate::core::searcher::Searcher;
+use crate::fastfield::type_and_cardinality;
 use crate::query::Explanation;
-use crate::{DocAddress, Term};
+use crate::schema::{Cardinality, Field, FieldEntry, Schema, Type};
+use crate::{DocAddress, TantivyError, Term};
 
 /// The `Query` trait defines a set of documents and a scoring method
 /// for those documents.
@@ -75,6 +77,79 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Returns a simplified query, matching the same documents with the same scores.
+    ///
+    /// For instance, nested `BooleanQuery` are flattened, clauses that cannot match any
+    /// document are removed and boolean queries with a single clause are replaced by
+    /// that clause. This saves the setup of the useless intermediary scorers.
+    ///
+    /// The default implementation returns a clone of the query.
+    fn rewrite(&self) -> Box<dyn Query> {
+        self.box_clone()
+    }
+
+    /// Checks that the query can be executed against an index with the given schema.
+    ///
+    /// The query tree is walked to detect, before running the query, the fields that do not
+    /// exist in the schema or that were not configured the way the query requires: for
+    /// instance a `PhraseQuery` on a field without positions, or a `RangeQuery` on a field
+    /// that is not indexed.
+    ///
+    /// The default implementation accepts any schema.
+    fn validate(&self, _schema: &Schema) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the entry of `field` in `schema`, or an error if the schema has no such field.
+pub(crate) fn validate_field(schema: &Schema, field: Field) -> crate::Result<&FieldEntry> {
+    if field.field_id() as usize >= schema.num_fields() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} does not exist in the schema.",
+            field
+        )));
+    }
+    Ok(schema.get_field_entry(field))
+}
+
+/// Returns the entry of `field` in `schema`, or an error if the field does not exist or is not
+/// indexed.
+pub(crate) fn validate_indexed_field(schema: &Schema, field: Field) -> crate::Result<&FieldEntry> {
+    let field_entry = validate_field(schema, field)?;
+    if !field_entry.is_indexed() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not indexed.",
+            field_entry.name()
+        )));
+    }
+    Ok(field_entry)
+}
+
+/// Returns the cardinality of the fast field `field`, or an error if the field does not exist,
+/// is not of one of the `value_types`, or is not a fast field.
+pub(crate) fn validate_fast_field(
+    schema: &Schema,
+    field: Field,
+    value_types: &[Type],
+) -> crate::Result<Cardinality> {
+    let field_entry = validate_field(schema, field)?;
+    let value_type = field_entry.field_type().value_type();
+    if !value_types.contains(&value_type) {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is of type {:?}, expected {:?}.",
+            field_entry.name(),
+            value_type,
+            value_types
+        )));
+    }
+    let (_, cardinality) = type_and_cardinality(field_entry.field_type()).ok_or_else(|| {
+        TantivyError::SchemaError(format!(
+            "Field {:?} is not a fast field.",
+            field_entry.name()
+        ))
+    })?;
+    Ok(cardinality)
 }
 
 /// Implements `box_clone`.
@@ -103,6 +178,14 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn rewrite(&self) -> Box<dyn Query> {
+        self.as_ref().rewrite()
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.as_ref().validate(schema)
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
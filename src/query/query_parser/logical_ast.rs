@@ -15,6 +15,7 @@ pub enum LogicalLiteral {
         lower: Bound<Term>,
         upper: Bound<Term>,
     },
+    Exists(Field),
     All,
 }
 
@@ -22,6 +23,8 @@ pub enum LogicalAst {
     Clause(Vec<(Occur, LogicalAst)>),
     Leaf(Box<LogicalLiteral>),
     Boost(Box<LogicalAst>, Score),
+    // Disjunction max of the asts, with its tie breaker.
+    DisMax(Vec<LogicalAst>, Score),
 }
 
 impl LogicalAst {
@@ -59,6 +62,16 @@ impl fmt::Debug for LogicalAst {
                 Ok(())
             }
             LogicalAst::Boost(ref ast, boost) => write!(formatter, "{:?}^{}", ast, boost),
+            LogicalAst::DisMax(ref disjuncts, tie_breaker) => {
+                formatter.write_str("DisMax(")?;
+                for (i, disjunct) in disjuncts.iter().enumerate() {
+                    if i > 0 {
+                        formatter.write_str(" ")?;
+                    }
+                    write!(formatter, "{:?}", disjunct)?;
+                }
+                write!(formatter, ")~{}", tie_breaker)
+            }
             LogicalAst::Leaf(ref literal) => write!(formatter, "{:?}", literal),
         }
     }
@@ -87,6 +100,7 @@ impl fmt::Debug for LogicalLiteral {
                 ref upper,
                 ..
             } => write!(formatter, "({:?} TO {:?})", lower, upper),
+            LogicalLiteral::Exists(field) => write!(formatter, "_exists_:{:?}", field),
             LogicalLiteral::All => write!(formatter, "*"),
         }
     }
@@ -7,21 +7,27 @@ use tantivy_query_grammar::{UserInputAst, UserInputBound, UserInputLeaf, UserInp
 
 use super::logical_ast::*;
 use crate::core::Index;
+use crate::fastfield::FastValue;
 use crate::indexer::{
-    convert_to_fast_value_and_get_term, set_string_and_get_terms, JsonTermWriter,
+    convert_to_fast_value_and_get_term, set_fastvalue_and_get_term, set_string_and_get_terms,
+    JsonTermWriter,
 };
 use crate::query::{
-    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, Occur, PhraseQuery, Query, RangeQuery,
-    TermQuery,
+    supports_exists_query, AllQuery, BooleanQuery, BoostQuery, DisjunctionMaxQuery, EmptyQuery,
+    ExistsQuery, Occur, PhraseQuery, Query, RangeQuery, TermQuery,
 };
 use crate::schema::{
-    Facet, FacetParseError, Field, FieldType, IndexRecordOption, Schema, Term, Type,
+    Facet, FacetParseError, Field, FieldType, IndexRecordOption, JsonObjectOptions, Schema, Term,
+    Type,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{DateTime, Score};
 
+// Pseudo field name of the existence queries, e.g. `_exists_:title`.
+const EXISTS_FIELD_NAME: &str = "_exists_";
+
 /// Possible error that may happen when parsing a query.
 #[derive(Debug, PartialEq, Eq, Error)]
 pub enum QueryParserError {
@@ -104,6 +110,14 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
                 Some(LogicalAst::Clause(trimmed_children))
             }
         }
+        LogicalAst::DisMax(disjuncts, tie_breaker) => {
+            let trimmed_disjuncts = disjuncts.into_iter().flat_map(trim_ast).collect::<Vec<_>>();
+            if trimmed_disjuncts.is_empty() {
+                None
+            } else {
+                Some(LogicalAst::DisMax(trimmed_disjuncts, tie_breaker))
+            }
+        }
         _ => Some(logical_ast),
     }
 }
@@ -161,6 +175,11 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///
 /// * all docs query: A plain `*` will match all documents in the index.
 ///
+/// * existence query: `_exists_:title` will match all documents that have a value for the field
+///   `title`, and `NOT _exists_:title` all documents that do not. The field must either be indexed,
+///   or be a multivalued fast field. If the schema has a field named `_exists_`, `_exists_:title`
+///   is a regular term query on that field.
+///
 /// Parts of the queries can be boosted by appending `^boostfactor`.
 /// For instance, `"SRE"^2.0 OR devops^0.4` will boost documents containing `SRE` instead of
 /// devops. Negative boosts are not allowed.
@@ -171,20 +190,48 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///
 /// Phrase terms support the `~` slop operator which allows to set the phrase's matching
 /// distance in words. `"big wolf"~1` will return documents containing the phrase `"big bad wolf"`.
+///
+/// A phrase without a field, e.g. `"machine learning"`, becomes a phrase query on each of the
+/// default fields, which are all required to have positions indexed. The phrase queries are
+/// combined in a disjunction, or in a disjunction max (See
+/// [`set_default_fields_dis_max(...)`](#method.set_default_fields_dis_max)).
 #[derive(Clone)]
 pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
+    default_fields_tie_breaker: Option<Score>,
     conjunction_by_default: bool,
     tokenizer_manager: TokenizerManager,
     boost: HashMap<Field, Score>,
     field_names: HashMap<String, Field>,
 }
 
+fn is_exists_leaf(ast: &LogicalAst) -> bool {
+    if let LogicalAst::Leaf(literal) = ast {
+        matches!(**literal, LogicalLiteral::Exists(_))
+    } else {
+        false
+    }
+}
+
+// Returns true if `ast` is only made of `NOT _exists_:field` clauses.
+fn only_excludes_existence(ast: &LogicalAst) -> bool {
+    match ast {
+        LogicalAst::Clause(children) => {
+            !children.is_empty()
+                && children
+                    .iter()
+                    .all(|(occur, child)| *occur == Occur::MustNot && is_exists_leaf(child))
+        }
+        _ => false,
+    }
+}
+
 fn all_negative(ast: &LogicalAst) -> bool {
     match ast {
         LogicalAst::Leaf(_) => false,
         LogicalAst::Boost(ref child_ast, _) => all_negative(child_ast),
+        LogicalAst::DisMax(disjuncts, _) => disjuncts.iter().all(all_negative),
         LogicalAst::Clause(children) => children
             .iter()
             .all(|(ref occur, child)| (*occur == Occur::MustNot) || all_negative(child)),
@@ -232,6 +279,7 @@ impl QueryParser {
         QueryParser {
             schema,
             default_fields,
+            default_fields_tie_breaker: None,
             tokenizer_manager,
             conjunction_by_default: false,
             boost: Default::default(),
@@ -263,6 +311,29 @@ impl QueryParser {
         QueryParser::new(index.schema(), default_fields, index.tokenizers().clone())
     }
 
+    /// Sets the fields searched by the terms of a query that do not target a field.
+    ///
+    /// A bare term `foo` is expanded to a disjunction over all of the default fields, e.g.
+    /// `(field_a:foo OR field_b:foo)`. The boosts set with
+    /// [`QueryParser::set_field_boost`] are applied to each of them.
+    ///
+    /// If there are no default fields, parsing a query with a bare term returns
+    /// [`QueryParserError::NoDefaultFieldDeclared`].
+    pub fn set_default_fields(&mut self, default_fields: Vec<Field>) {
+        self.default_fields = default_fields;
+    }
+
+    /// Combines the queries of a term or a phrase over the default fields in a
+    /// [`DisjunctionMaxQuery`], instead of a disjunction.
+    ///
+    /// A document matching the term in several fields is then given the score of its best
+    /// matching field, plus `tie_breaker` times the scores of the other matching fields,
+    /// rather than the sum of the scores of all of its matching fields. The boosts set with
+    /// [`QueryParser::set_field_boost`] are applied to the query of each field.
+    pub fn set_default_fields_dis_max(&mut self, tie_breaker: Score) {
+        self.default_fields_tie_breaker = Some(tie_breaker);
+    }
+
     /// Set the default way to compose queries to a conjunction.
     ///
     /// By default, the query `happy tax payer` is equivalent to the query
@@ -315,6 +386,13 @@ impl QueryParser {
                 return Ok(ast);
             }
         }
+        if only_excludes_existence(&ast) {
+            // `NOT _exists_:field` matches the documents that do not have a value for `field`.
+            if let LogicalAst::Clause(mut children) = ast {
+                children.push((Occur::Must, LogicalAst::from(LogicalLiteral::All)));
+                return Ok(LogicalAst::Clause(children));
+            }
+        }
         if all_negative(&ast) {
             return Err(QueryParserError::AllButQueryForbidden);
         }
@@ -366,13 +444,13 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_entry.name().to_string())
                 })?;
-                let text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                            field: field_entry.name().to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                let text_analyzer = self
+                    .tokenizer_manager
+                    .get(option.search_tokenizer())
+                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                        field: field_entry.name().to_string(),
+                        tokenizer: option.search_tokenizer().to_string(),
+                    })?;
                 let mut terms: Vec<Term> = Vec::new();
                 let mut token_stream = text_analyzer.token_stream(phrase);
                 token_stream.process(&mut |token| {
@@ -387,11 +465,18 @@ impl QueryParser {
                 Ok(terms.into_iter().next().unwrap())
             }
             FieldType::JsonObject(_) => {
-                // Json range are not supported.
+                // Json ranges are computed by `compute_json_range_ast`, as their type depends on
+                // their bounds.
                 Err(QueryParserError::UnsupportedQuery(
                     "Range query are not supported on json field.".to_string(),
                 ))
             }
+            FieldType::DenseVector(_) => {
+                // Dense vector fields are never indexed.
+                Err(QueryParserError::FieldNotIndexed(
+                    field_entry.name().to_string(),
+                ))
+            }
             FieldType::Facet(_) => match Facet::from_text(phrase) {
                 Ok(facet) => Ok(Term::from_facet(field, &facet)),
                 Err(e) => Err(QueryParserError::from(e)),
@@ -453,13 +538,13 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_name.to_string())
                 })?;
-                let text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                            field: field_name.to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                let text_analyzer = self
+                    .tokenizer_manager
+                    .get(option.search_tokenizer())
+                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                        field: field_name.to_string(),
+                        tokenizer: option.search_tokenizer().to_string(),
+                    })?;
                 let index_record_option = option.index_option();
                 Ok(generate_literals_for_str(
                     field_name,
@@ -477,13 +562,13 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_name.to_string())
                 })?;
-                let text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                            field: field_name.to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                let text_analyzer = self
+                    .tokenizer_manager
+                    .get(option.search_tokenizer())
+                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                        field: field_name.to_string(),
+                        tokenizer: option.search_tokenizer().to_string(),
+                    })?;
                 let index_record_option = option.index_option();
                 generate_literals_for_json_object(
                     field_name,
@@ -506,6 +591,10 @@ impl QueryParser {
                 let bytes_term = Term::from_field_bytes(field, &bytes);
                 Ok(vec![LogicalLiteral::Term(bytes_term)])
             }
+            FieldType::DenseVector(_) => {
+                // Dense vector fields are never indexed.
+                Err(QueryParserError::FieldNotIndexed(field_name.to_string()))
+            }
         }
     }
 
@@ -534,6 +623,134 @@ impl QueryParser {
         }
     }
 
+    /// Computes the logical ast of a range query on the json path `json_path` of `field`.
+    ///
+    /// The values of a json path are not bound to a single type: for instance, the path can
+    /// hold numbers in some documents and strings in others. The type of the range is inferred
+    /// from its bounds, the same way it is for the values of a json object when it is indexed.
+    ///
+    /// Numbers may have been indexed as u64, i64 or f64, so numeric bounds yield a union of
+    /// one range per numeric type. Date bounds yield a range over the dates, and other bounds
+    /// a range over the terms of the strings. If both bounds are unbounded, the range matches
+    /// any value of the path.
+    fn compute_json_range_ast(
+        &self,
+        field: Field,
+        json_options: &JsonObjectOptions,
+        json_path: &str,
+        lower: &UserInputBound,
+        upper: &UserInputBound,
+    ) -> Result<LogicalAst, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        let text_options = json_options
+            .get_text_indexing_options()
+            .ok_or_else(|| QueryParserError::FieldNotIndexed(field_entry.name().to_string()))?;
+        if json_path.is_empty() {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "Range query on json field {:?} requires a json path.",
+                field_entry.name()
+            )));
+        }
+        let bound_strs: Vec<&str> = [lower, upper]
+            .into_iter()
+            .filter_map(json_bound_str)
+            .collect();
+        let mut ranges: Vec<(Bound<Term>, Bound<Term>)> = Vec::new();
+        if bound_strs.is_empty() {
+            let path_start = json_term_prefix(field, json_path, None);
+            let path_end = successor(path_start.clone());
+            ranges.push((Bound::Included(path_start), Bound::Excluded(path_end)));
+        } else if bound_strs
+            .iter()
+            .all(|bound_str| OffsetDateTime::parse(bound_str, &Rfc3339).is_ok())
+        {
+            let to_date_term = |bound_str: &str| {
+                let dt = OffsetDateTime::parse(bound_str, &Rfc3339)?;
+                Ok(json_fast_value_term(
+                    field,
+                    json_path,
+                    DateTime::from_utc(dt),
+                ))
+            };
+            ranges.push((
+                json_range_bound(field, json_path, Type::Date, lower, true, to_date_term)?,
+                json_range_bound(field, json_path, Type::Date, upper, false, to_date_term)?,
+            ));
+        } else if bound_strs
+            .iter()
+            .all(|bound_str| matches!(f64::from_str(bound_str), Ok(val) if val.is_finite()))
+        {
+            let (int_lower, int_upper) =
+                (json_int_bound(lower, true)?, json_int_bound(upper, false)?);
+            let u64_range = (int_lower.max(0), int_upper.min(u64::MAX as i128));
+            if u64_range.0 <= u64_range.1 {
+                ranges.push((
+                    Bound::Included(json_fast_value_term(field, json_path, u64_range.0 as u64)),
+                    Bound::Included(json_fast_value_term(field, json_path, u64_range.1 as u64)),
+                ));
+            }
+            let i64_range = (
+                int_lower.max(i64::MIN as i128),
+                int_upper.min(i64::MAX as i128),
+            );
+            if i64_range.0 <= i64_range.1 {
+                ranges.push((
+                    Bound::Included(json_fast_value_term(field, json_path, i64_range.0 as i64)),
+                    Bound::Included(json_fast_value_term(field, json_path, i64_range.1 as i64)),
+                ));
+            }
+            let to_f64_term = |bound_str: &str| {
+                let val = f64::from_str(bound_str)?;
+                Ok(json_fast_value_term(field, json_path, val))
+            };
+            ranges.push((
+                json_range_bound(field, json_path, Type::F64, lower, true, to_f64_term)?,
+                json_range_bound(field, json_path, Type::F64, upper, false, to_f64_term)?,
+            ));
+        } else {
+            let text_analyzer = self
+                .tokenizer_manager
+                .get(text_options.search_tokenizer())
+                .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                    field: field_entry.name().to_string(),
+                    tokenizer: text_options.search_tokenizer().to_string(),
+                })?;
+            let to_str_term = |bound_str: &str| {
+                let mut term = Term::new();
+                let mut json_term_writer =
+                    JsonTermWriter::from_field_and_json_path(field, json_path, &mut term);
+                let terms =
+                    set_string_and_get_terms(&mut json_term_writer, bound_str, &text_analyzer);
+                if terms.len() != 1 {
+                    return Err(QueryParserError::UnsupportedQuery(format!(
+                        "Range query boundary cannot have multiple tokens: {bound_str:?}."
+                    )));
+                }
+                Ok(terms.into_iter().next().unwrap().1)
+            };
+            ranges.push((
+                json_range_bound(field, json_path, Type::Str, lower, true, to_str_term)?,
+                json_range_bound(field, json_path, Type::Str, upper, false, to_str_term)?,
+            ));
+        }
+        let mut range_asts: Vec<(Occur, LogicalAst)> = ranges
+            .into_iter()
+            .map(|(lower, upper)| {
+                let range_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Range {
+                    field,
+                    value_type: Type::Json,
+                    lower,
+                    upper,
+                }));
+                (Occur::Should, range_ast)
+            })
+            .collect();
+        if range_asts.len() == 1 {
+            return Ok(range_asts.pop().unwrap().1);
+        }
+        Ok(LogicalAst::Clause(range_asts))
+    }
+
     fn compute_logical_ast_with_occur(
         &self,
         user_input_ast: UserInputAst,
@@ -557,6 +774,19 @@ impl QueryParser {
         }
     }
 
+    fn compute_exists_ast(&self, field_name: &str) -> Result<LogicalAst, QueryParserError> {
+        let field = *self
+            .field_names
+            .get(field_name)
+            .ok_or_else(|| QueryParserError::FieldDoesNotExist(field_name.to_string()))?;
+        let field_entry = self.schema.get_field_entry(field);
+        if !supports_exists_query(field_entry) {
+            return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
+        }
+        let boost = self.field_boost(field);
+        Ok(LogicalAst::from(LogicalLiteral::Exists(field)).boost(boost))
+    }
+
     fn field_boost(&self, field: Field) -> Score {
         self.boost.get(&field).cloned().unwrap_or(1.0)
     }
@@ -626,6 +856,12 @@ impl QueryParser {
         leaf: UserInputLeaf,
     ) -> Result<LogicalAst, QueryParserError> {
         match leaf {
+            UserInputLeaf::Literal(literal)
+                if literal.field_name.as_deref() == Some(EXISTS_FIELD_NAME)
+                    && !self.field_names.contains_key(EXISTS_FIELD_NAME) =>
+            {
+                self.compute_exists_ast(&literal.phrase)
+            }
             UserInputLeaf::Literal(literal) => {
                 let term_phrases: Vec<(Field, &str, &str)> =
                     self.compute_path_triplets_for_literal(&literal)?;
@@ -641,6 +877,8 @@ impl QueryParser {
                 }
                 let result_ast: LogicalAst = if asts.len() == 1 {
                     asts.into_iter().next().unwrap()
+                } else if let Some(tie_breaker) = self.default_fields_tie_breaker {
+                    LogicalAst::DisMax(asts, tie_breaker)
                 } else {
                     LogicalAst::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
                 };
@@ -661,6 +899,15 @@ impl QueryParser {
                     .split_full_path(&full_path)
                     .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone()))?;
                 let field_entry = self.schema.get_field_entry(field);
+                if let FieldType::JsonObject(ref json_options) = *field_entry.field_type() {
+                    return self.compute_json_range_ast(
+                        field,
+                        json_options,
+                        json_path,
+                        &lower,
+                        &upper,
+                    );
+                }
                 let value_type = field_entry.field_type().value_type();
                 let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Range {
                     field,
@@ -688,6 +935,7 @@ fn convert_literal_to_query(logical_literal: LogicalLiteral) -> Box<dyn Query> {
         } => Box::new(RangeQuery::new_term_bounds(
             field, value_type, &lower, &upper,
         )),
+        LogicalLiteral::Exists(field) => Box::new(ExistsQuery::new(field)),
         LogicalLiteral::All => Box::new(AllQuery),
     }
 }
@@ -721,6 +969,106 @@ fn generate_literals_for_str(
     Ok(Some(LogicalLiteral::Phrase(terms, slop)))
 }
 
+/// Returns the string of a bound of a range query on a json path, or `None` if the bound is
+/// unbounded.
+fn json_bound_str(bound: &UserInputBound) -> Option<&str> {
+    match bound.term_str() {
+        "*" => None,
+        bound_str => Some(bound_str),
+    }
+}
+
+/// Returns the json term of `json_path` without a value, followed by the code of `typ` if
+/// it is given.
+fn json_term_prefix(field: Field, json_path: &str, typ: Option<Type>) -> Term {
+    let mut term = Term::new();
+    let mut json_term_writer =
+        JsonTermWriter::from_field_and_json_path(field, json_path, &mut term);
+    json_term_writer.close_path_and_set_type(typ.unwrap_or(Type::Str));
+    if typ.is_none() {
+        let num_bytes = term.as_slice().len();
+        term.truncate(num_bytes - 1);
+    }
+    term
+}
+
+/// Returns the smallest term greater than all of the terms starting with `term`.
+///
+/// The last byte of `term` is expected to be a type code or the end of a path.
+fn successor(mut term: Term) -> Term {
+    let num_bytes = term.as_slice().len();
+    let last_byte = term.as_slice()[num_bytes - 1];
+    term.truncate(num_bytes - 1);
+    term.append_bytes(&[last_byte + 1]);
+    term
+}
+
+fn json_fast_value_term<T: FastValue>(field: Field, json_path: &str, val: T) -> Term {
+    let mut term = Term::new();
+    let mut json_term_writer =
+        JsonTermWriter::from_field_and_json_path(field, json_path, &mut term);
+    set_fastvalue_and_get_term(&mut json_term_writer, val)
+}
+
+/// Returns the bound of a range over the values of type `typ` of a json path.
+///
+/// An unbounded bound is restricted to the values of type `typ`.
+fn json_range_bound<F>(
+    field: Field,
+    json_path: &str,
+    typ: Type,
+    bound: &UserInputBound,
+    is_lower: bool,
+    to_term: F,
+) -> Result<Bound<Term>, QueryParserError>
+where
+    F: Fn(&str) -> Result<Term, QueryParserError>,
+{
+    let bound_str = match json_bound_str(bound) {
+        Some(bound_str) => bound_str,
+        None => {
+            let type_prefix = json_term_prefix(field, json_path, Some(typ));
+            return Ok(if is_lower {
+                Bound::Included(type_prefix)
+            } else {
+                Bound::Excluded(successor(type_prefix))
+            });
+        }
+    };
+    let term = to_term(bound_str)?;
+    match *bound {
+        UserInputBound::Exclusive(_) => Ok(Bound::Excluded(term)),
+        _ => Ok(Bound::Included(term)),
+    }
+}
+
+/// Returns the inclusive integer bound matching a numeric bound of a range query.
+///
+/// Unbounded bounds are mapped to the extreme values of `i128`, which cover the values of
+/// both `u64` and `i64`.
+fn json_int_bound(bound: &UserInputBound, is_lower: bool) -> Result<i128, QueryParserError> {
+    let bound_str = match json_bound_str(bound) {
+        Some(bound_str) => bound_str,
+        None => return Ok(if is_lower { i128::MIN } else { i128::MAX }),
+    };
+    let is_exclusive = matches!(bound, UserInputBound::Exclusive(_));
+    if let Ok(val) = i128::from_str(bound_str) {
+        return Ok(match (is_lower, is_exclusive) {
+            (true, true) => val + 1,
+            (false, true) => val - 1,
+            _ => val,
+        });
+    }
+    let val = f64::from_str(bound_str)?;
+    // `as` saturates to the extreme values of `i128`.
+    Ok(match (is_lower, is_exclusive) {
+        (true, false) => val.ceil() as i128,
+        (true, true) => val.floor() as i128 + 1,
+        (false, false) => val.floor() as i128,
+        (false, true) => val.ceil() as i128 - 1,
+    })
+}
+
 fn generate_literals_for_json_object(
     field_name: &str,
     field: Field,
@@ -774,6 +1122,13 @@ fn convert_to_query(logical_ast: LogicalAst) -> Box<dyn Query> {
             let boosted_query = BoostQuery::new(query, boost);
             Box::new(boosted_query)
         }
+        Some(LogicalAst::DisMax(disjuncts, tie_breaker)) => {
+            let disjunct_queries = disjuncts.into_iter().map(convert_to_query).collect();
+            Box::new(DisjunctionMaxQuery::with_tie_breaker(
+                disjunct_queries,
+                tie_breaker,
+            ))
+        }
         None => Box::new(EmptyQuery),
     }
 }
@@ -784,15 +1139,16 @@ mod test {
 
     use super::super::logical_ast::*;
     use super::{QueryParser, QueryParserError};
+    use crate::collector::{DocSetCollector, TopDocs};
     use crate::query::Query;
     use crate::schema::{
         FacetOptions, Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions,
         INDEXED, STORED, STRING, TEXT,
     };
     use crate::tokenizer::{
-        LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
+        LowerCaser, NgramTokenizer, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
     };
-    use crate::Index;
+    use crate::{Index, Score};
 
     fn make_schema() -> Schema {
         let mut schema_builder = Schema::builder();
@@ -897,6 +1253,39 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_default_fields() {
+        let schema = make_schema();
+        let title = schema.get_field("title").unwrap();
+        let text = schema.get_field("text").unwrap();
+        let mut query_parser = make_query_parser_with_default_fields(&[]);
+        assert!(matches!(
+            query_parser.parse_query("foo"),
+            Err(QueryParserError::NoDefaultFieldDeclared)
+        ));
+        // Terms targeting a field do not need default fields.
+        assert!(query_parser.parse_query("title:foo").is_ok());
+
+        query_parser.set_default_fields(vec![title]);
+        assert_eq!(
+            format!(
+                "{:?}",
+                query_parser.parse_query_to_logical_ast("foo").unwrap()
+            ),
+            r#"Term(type=Str, field=0, "foo")"#
+        );
+
+        query_parser.set_default_fields(vec![title, text]);
+        query_parser.set_field_boost(text, 2.0);
+        assert_eq!(
+            format!(
+                "{:?}",
+                query_parser.parse_query_to_logical_ast("foo").unwrap()
+            ),
+            r#"(Term(type=Str, field=0, "foo") Term(type=Str, field=1, "foo")^2)"#
+        );
+    }
+
     #[test]
     pub fn test_parse_query_with_default_boost_and_custom_boost() {
         let mut query_parser = make_query_parser();
@@ -1314,6 +1703,253 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_phrase_default_fields() {
+        let schema = make_schema();
+        let title = schema.get_field("title").unwrap();
+        let mut query_parser = make_query_parser();
+        query_parser.set_field_boost(title, 3.0);
+        assert_eq!(
+            format!(
+                "{:?}",
+                query_parser
+                    .parse_query_to_logical_ast(r#""machine learning""#)
+                    .unwrap()
+            ),
+            r#"("[(0, Term(type=Str, field=0, "machine")), (1, Term(type=Str, field=0, "learning"))]"^3 "[(0, Term(type=Str, field=1, "machine")), (1, Term(type=Str, field=1, "learning"))]")"#
+        );
+        query_parser.set_default_fields_dis_max(0.5);
+        assert_eq!(
+            format!(
+                "{:?}",
+                query_parser
+                    .parse_query_to_logical_ast(r#""machine learning"~1"#)
+                    .unwrap()
+            ),
+            r#"DisMax("[(0, Term(type=Str, field=0, "machine")), (1, Term(type=Str, field=0, "learning"))]"~1^3 "[(0, Term(type=Str, field=1, "machine")), (1, Term(type=Str, field=1, "learning"))]"~1)~0.5"#
+        );
+
+        // A single term is not a phrase, and does not require positions.
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let text_field_indexing =
+            TextFieldIndexing::default().set_index_option(IndexRecordOption::WithFreqs);
+        let text_options = TextOptions::default().set_indexing_options(text_field_indexing);
+        let tags = schema_builder.add_text_field("tags", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let query_parser = QueryParser::for_index(&index, vec![title, tags]);
+        assert!(query_parser.parse_query("machine").is_ok());
+        assert!(query_parser
+            .parse_query(r#"title:"machine learning""#)
+            .is_ok());
+        assert_eq!(
+            query_parser
+                .parse_query(r#""machine learning""#)
+                .unwrap_err(),
+            QueryParserError::FieldDoesNotHavePositionsIndexed("tags".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_phrase_dis_max_ranking() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "machine learning",
+            body => "a book about machine learning"
+        ))?;
+        index_writer.add_document(doc!(title => "machine learning", body => "a book"))?;
+        index_writer.add_document(doc!(
+            title => "learning",
+            body => "machine learning, machine learning"
+        ))?;
+        index_writer.add_document(doc!(title => "learning machine", body => "learning"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut query_parser = QueryParser::for_index(&index, vec![title, body]);
+        query_parser.set_field_boost(title, 10.0);
+        let top_scores = |query_parser: &QueryParser| -> crate::Result<Vec<(Score, u32)>> {
+            let query = query_parser.parse_query(r#""machine learning""#).unwrap();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            Ok(top_docs
+                .into_iter()
+                .map(|(score, doc_address)| (score, doc_address.doc_id))
+                .collect())
+        };
+
+        // The phrase is only matched in the right order, in any of the fields.
+        let disjunction_scores = top_scores(&query_parser)?;
+        assert_eq!(disjunction_scores.len(), 3);
+        assert_eq!(disjunction_scores[0].1, 0);
+
+        // Without a tie breaker, the documents matching in the title get the same score,
+        // whether they also match in the body or not.
+        query_parser.set_default_fields_dis_max(0.0);
+        let dis_max_scores = top_scores(&query_parser)?;
+        assert_eq!(dis_max_scores.len(), 3);
+        assert_eq!(dis_max_scores[2].1, 2);
+        let title_score = |doc_id: u32| {
+            dis_max_scores
+                .iter()
+                .find(|(_, doc)| *doc == doc_id)
+                .map(|(score, _)| *score)
+                .unwrap()
+        };
+        assert!((title_score(0) - title_score(1)).abs() < 0.001);
+        assert!(disjunction_scores[0].0 > title_score(0));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_search_tokenizer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field_indexing = TextFieldIndexing::default()
+            .set_tokenizer("edge_ngram")
+            .set_search_tokenizer("default")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let text_options = TextOptions::default().set_indexing_options(text_field_indexing);
+        let title = schema_builder.add_text_field("title", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "edge_ngram",
+            TextAnalyzer::from(NgramTokenizer::new(1, 10, true)).filter(LowerCaser),
+        );
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "Hello"))?;
+        index_writer.add_document(doc!(title => "Help"))?;
+        index_writer.add_document(doc!(title => "World"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let matching_docs = |query: &str| -> crate::Result<Vec<u32>> {
+            let query = query_parser.parse_query(query).unwrap();
+            let mut docs: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort_unstable();
+            Ok(docs)
+        };
+        // The query is not split into n-grams, so that it matches the words it is a prefix of.
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("hel").unwrap()),
+            r#"TermQuery(Term(type=Str, field=0, "hel"))"#
+        );
+        assert_eq!(matching_docs("Hel")?, vec![0, 1]);
+        assert_eq!(matching_docs("hello")?, vec![0]);
+        assert_eq!(matching_docs("wo")?, vec![2]);
+        assert!(matching_docs("orld")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_query_nested_and_phrase_boosts() {
+        let query_parser = make_query_parser();
+        let query = query_parser.parse_query("(title:a^2)^3").unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            r#"Boost(query=Boost(query=TermQuery(Term(type=Str, field=0, "a")), boost=2), boost=3)"#
+        );
+        let query = query_parser.parse_query(r#"title:"a b"^2"#).unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            r#"Boost(query=PhraseQuery { field: Field(0), phrase_terms: [(0, Term(type=Str, field=0, "a")), (1, Term(type=Str, field=0, "b"))], slop: 0 }, boost=2)"#
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_boost_ranking() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // Doc 0 contains `rust` in its title, doc 1 in its body.
+        index_writer.add_document(doc!(title => "rust", body => "tantivy"))?;
+        index_writer.add_document(doc!(title => "tantivy", body => "rust"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let top_doc = |query: &str| -> crate::Result<u32> {
+            let query = query_parser.parse_query(query).unwrap();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+            assert_eq!(top_docs.len(), 2);
+            Ok(top_docs[0].1.doc_id)
+        };
+        assert_eq!(top_doc("title:rust^2 body:rust")?, 0);
+        assert_eq!(top_doc("title:rust body:rust^2")?, 1);
+        assert_eq!(top_doc("(title:rust body:rust^2)^0.5 title:rust^3")?, 0);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_query_exists() {
+        test_parse_query_to_logical_ast_helper("_exists_:title", "_exists_:Field(0)", false);
+        test_parse_query_to_logical_ast_helper(
+            "NOT _exists_:title",
+            "(-_exists_:Field(0) +*)",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "-_exists_:title -_exists_:text",
+            "(-_exists_:Field(0) -_exists_:Field(1) +*)",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "toto -_exists_:title",
+            r#"((Term(type=Str, field=0, "toto") Term(type=Str, field=1, "toto")) -_exists_:Field(0))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper("_exists_:json^2", "_exists_:Field(14)^2", false);
+        assert_matches!(
+            parse_query_to_logical_ast("_exists_:notindexed_text", false),
+            Err(QueryParserError::FieldNotIndexed(field_name)) if field_name == "notindexed_text"
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("_exists_:doesnotexist", false),
+            Err(QueryParserError::FieldDoesNotExist(field_name)) if field_name == "doesnotexist"
+        );
+        // Other excluding clauses are still forbidden.
+        assert_matches!(
+            parse_query_to_logical_ast("-_exists_:title -title:toto", false),
+            Err(QueryParserError::AllButQueryForbidden)
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_exists_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "rust", body => "tantivy"))?;
+        index_writer.add_document(doc!(body => "rust"))?;
+        index_writer.add_document(doc!(title => "lucene"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let matching_docs = |query: &str| -> crate::Result<Vec<u32>> {
+            let query = query_parser.parse_query(query).unwrap();
+            let mut docs: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort_unstable();
+            Ok(docs)
+        };
+        assert_eq!(matching_docs("_exists_:title")?, vec![0, 2]);
+        assert_eq!(matching_docs("NOT _exists_:title")?, vec![1]);
+        assert_eq!(matching_docs("_exists_:title AND _exists_:body")?, vec![0]);
+        assert_eq!(matching_docs("rust -_exists_:title")?, vec![1]);
+        Ok(())
+    }
+
     #[test]
     pub fn test_query_parser_expected_int() {
         let query_parser = make_query_parser();
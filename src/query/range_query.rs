@@ -6,8 +6,10 @@ use common::BitSet;
 use crate::core::{Searcher, SegmentReader};
 use crate::error::TantivyError;
 use crate::query::explanation::does_not_match;
-use crate::query::{BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight};
-use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::query::{
+    validate_indexed_field, BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{Field, FieldEntry, IndexRecordOption, Schema, Term, Type};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::{DocId, Score};
 
@@ -32,6 +34,9 @@ fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
 /// The current implement will iterate over the terms within the range
 /// and append all of the document cross into a `BitSet`.
 ///
+/// The field needs to be indexed. For fields that are fast but not indexed, see
+/// [`FastFieldRangeQuery`](crate::query::FastFieldRangeQuery).
+///
 /// # Example
 ///
 /// ```rust
@@ -240,16 +245,9 @@ impl RangeQuery {
             Term::from_field_bytes(self.field, bytes)
         })
     }
-}
 
-impl Query for RangeQuery {
-    fn weight(
-        &self,
-        searcher: &Searcher,
-        _scoring_enabled: bool,
-    ) -> crate::Result<Box<dyn Weight>> {
-        let schema = searcher.schema();
-        let value_type = schema.get_field_entry(self.field).field_type().value_type();
+    fn check_value_type(&self, field_entry: &FieldEntry) -> crate::Result<()> {
+        let value_type = field_entry.field_type().value_type();
         if value_type != self.value_type {
             let err_msg = format!(
                 "Create a range query of the type {:?}, when the field given was of type {:?}",
@@ -257,12 +255,28 @@ impl Query for RangeQuery {
             );
             return Err(TantivyError::SchemaError(err_msg));
         }
+        Ok(())
+    }
+}
+
+impl Query for RangeQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        self.check_value_type(searcher.schema().get_field_entry(self.field))?;
         Ok(Box::new(RangeWeight {
             field: self.field,
             left_bound: self.left_bound.clone(),
             right_bound: self.right_bound.clone(),
         }))
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        let field_entry = validate_indexed_field(schema, self.field)?;
+        self.check_value_type(field_entry)
+    }
 }
 
 pub struct RangeWeight {
@@ -506,4 +520,45 @@ mod tests {
         assert_eq!(top_docs.len(), 1);
         Ok(())
     }
+
+    #[test]
+    fn test_range_query_json_path() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let json = schema_builder.add_json_field("json", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        for json_doc in [
+            r#"{"json": {"user": {"age": 25, "name": "alice"}}}"#,
+            r#"{"json": {"user": {"age": 35, "name": "bob"}}}"#,
+            r#"{"json": {"user": {"age": -5, "name": "carol"}}}"#,
+            r#"{"json": {"user": {"age": 40.5, "name": "dave"}}}"#,
+            // The same path holds a string.
+            r#"{"json": {"user": {"age": "unknown", "name": "eve"}}}"#,
+            r#"{"json": {"user": {"score": 50, "login": "2022-05-01T10:00:00Z"}}}"#,
+        ] {
+            index_writer.add_document(schema.parse_document(json_doc)?)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![json]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("json.user.age:>30")?, 2);
+        assert_eq!(count("json.user.age:>=25")?, 3);
+        assert_eq!(count("json.user.age:<30")?, 2);
+        assert_eq!(count("json.user.age:<0")?, 1);
+        assert_eq!(count("json.user.age:[-10 TO 30]")?, 2);
+        assert_eq!(count("json.user.age:{25 TO 40.5}")?, 1);
+        assert_eq!(count("json.user.age:[25.5 TO 40.5]")?, 2);
+        assert_eq!(count("json.user.age:[* TO *]")?, 5);
+        assert_eq!(count("json.user.age:>a")?, 1);
+        assert_eq!(count("json.user.name:[alice TO bob]")?, 2);
+        assert_eq!(count("json.user.name:>c")?, 3);
+        assert_eq!(count("json.user.login:>2022-01-01T00:00:00Z")?, 1);
+        assert_eq!(count("json.user.login:<2022-01-01T00:00:00Z")?, 0);
+        assert!(query_parser.parse_query("json:>3").is_err());
+        Ok(())
+    }
 }
@@ -4,8 +4,8 @@ use std::sync::Arc;
 use tantivy_fst::Regex;
 
 use crate::error::TantivyError;
-use crate::query::{AutomatonWeight, Query, Weight};
-use crate::schema::Field;
+use crate::query::{validate_indexed_field, AutomatonWeight, MaxExpansionsBehavior, Query, Weight};
+use crate::schema::{Field, Schema};
 use crate::Searcher;
 
 /// A Regex Query matches all of the documents
@@ -15,6 +15,9 @@ use crate::Searcher;
 /// Wildcard queries (e.g. ho*se) can be achieved
 /// by converting them to their regex counterparts.
 ///
+/// A permissive pattern can match a very large number of terms. The number of terms the
+/// query expands to can be capped with [`RegexQuery::with_max_expansions`].
+///
 /// ```rust
 /// use tantivy::collector::Count;
 /// use tantivy::query::RegexQuery;
@@ -58,6 +61,7 @@ use crate::Searcher;
 pub struct RegexQuery {
     regex: Arc<Regex>,
     field: Field,
+    max_expansions: Option<(usize, MaxExpansionsBehavior)>,
 }
 
 impl RegexQuery {
@@ -73,9 +77,25 @@ impl RegexQuery {
         RegexQuery {
             regex: regex.into(),
             field,
+            max_expansions: None,
         }
     }
 
+    /// Caps the number of terms the query expands to over the segments of the searcher.
+    ///
+    /// By default, the number of terms is not capped. If more than `max_expansions` terms
+    /// match the pattern, building the weight fails or only the `max_expansions` smallest
+    /// terms are searched for, depending on `max_expansions_behavior`.
+    #[must_use]
+    pub fn with_max_expansions(
+        mut self,
+        max_expansions: usize,
+        max_expansions_behavior: MaxExpansionsBehavior,
+    ) -> RegexQuery {
+        self.max_expansions = Some((max_expansions, max_expansions_behavior));
+        self
+    }
+
     fn specialized_weight(&self) -> AutomatonWeight<Regex> {
         AutomatonWeight::new(self.field, self.regex.clone())
     }
@@ -84,10 +104,19 @@ impl RegexQuery {
 impl Query for RegexQuery {
     fn weight(
         &self,
-        _searcher: &Searcher,
+        searcher: &Searcher,
         _scoring_enabled: bool,
     ) -> crate::Result<Box<dyn Weight>> {
-        Ok(Box::new(self.specialized_weight()))
+        let mut weight = self.specialized_weight();
+        if let Some((max_expansions, max_expansions_behavior)) = self.max_expansions {
+            weight = weight.limit_expansions(searcher, max_expansions, max_expansions_behavior)?;
+        }
+        Ok(Box::new(weight))
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        validate_indexed_field(schema, self.field)?;
+        Ok(())
     }
 }
 
@@ -98,9 +127,10 @@ mod test {
     use tantivy_fst::Regex;
 
     use super::RegexQuery;
-    use crate::collector::TopDocs;
-    use crate::schema::{Field, Schema, TEXT};
-    use crate::{assert_nearly_equals, Index, IndexReader};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::MaxExpansionsBehavior;
+    use crate::schema::{Field, Schema, STORED, STRING, TEXT};
+    use crate::{assert_nearly_equals, Index, IndexReader, TantivyError};
 
     fn build_test_index() -> crate::Result<(IndexReader, Field)> {
         let mut schema_builder = Schema::builder();
@@ -181,4 +211,57 @@ mod test {
         verify_regex_query(matching_one, matching_zero, reader);
         Ok(())
     }
+
+    #[test]
+    pub fn test_regex_query_max_expansions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let word = schema_builder.add_text_field("word", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..5_000 {
+            index_writer.add_document(doc!(word => format!("word{:04}", i)))?;
+            if i % 1_000 == 999 {
+                // Spread the terms over several segments.
+                index_writer.commit()?;
+            }
+        }
+        index_writer.add_document(doc!(word => "other"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+
+        let wildcard_query = RegexQuery::from_pattern("wo.*", word)?;
+        assert_eq!(searcher.search(&wildcard_query, &Count)?, 5_000);
+
+        let capped_query = wildcard_query
+            .clone()
+            .with_max_expansions(100, MaxExpansionsBehavior::Error);
+        assert!(matches!(
+            searcher.search(&capped_query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let uncapped_query = wildcard_query
+            .clone()
+            .with_max_expansions(5_000, MaxExpansionsBehavior::Error);
+        assert_eq!(searcher.search(&uncapped_query, &Count)?, 5_000);
+
+        // The smallest matching terms over all of the segments are kept.
+        let truncated_query = wildcard_query
+            .clone()
+            .with_max_expansions(100, MaxExpansionsBehavior::Truncate);
+        let top_docs = searcher.search(&truncated_query, &TopDocs::with_limit(200))?;
+        let mut words: Vec<String> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc = searcher.doc(doc_address)?;
+                Ok(doc.get_first(word).unwrap().as_text().unwrap().to_string())
+            })
+            .collect::<crate::Result<_>>()?;
+        words.sort();
+        let expected_words: Vec<String> = (0..100).map(|i| format!("word{:04}", i)).collect();
+        assert_eq!(words, expected_words);
+        let empty_query = wildcard_query.with_max_expansions(0, MaxExpansionsBehavior::Truncate);
+        assert_eq!(searcher.search(&empty_query, &Count)?, 0);
+        Ok(())
+    }
 }
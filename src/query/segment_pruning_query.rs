@@ -0,0 +1,370 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::core::{Searcher, SegmentReader};
+use crate::fastfield::FastValue;
+use crate::query::explanation::does_not_match;
+use crate::query::{validate_fast_field, EmptyScorer, Explanation, Query, Scorer, Weight};
+use crate::schema::{Cardinality, Field, Schema, Type};
+use crate::{DocId, Score, Term};
+
+/// `SegmentPruningQuery` is a wrapper over a query that skips the segments which
+/// cannot contain any document with a fast field value within a given range.
+///
+/// For each segment, the minimum and maximum values of the fast field, as persisted in the
+/// fast field metadata, are compared to the range. If they do not overlap, the segment is
+/// skipped entirely: the underlying query is not even evaluated on it.
+/// Otherwise, the underlying query is run as is on the segment.
+///
+/// This is useful when segments are partitioned by the value of a field, typically when the
+/// index is sorted on a timestamp: a query restricted to a recent time window then only
+/// visits the few segments overlapping with that window.
+///
+/// Pruning happens at the segment level only: documents of the segments that are not skipped
+/// are **not** filtered individually. The underlying query should therefore include the
+/// restriction itself (e.g. with a `RangeQuery`) if the exact set of matching documents
+/// matters.
+///
+/// The field needs to be declared as a fast field. Both single-valued and multivalued fast
+/// fields are supported.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::{RangeQuery, SegmentPruningQuery};
+/// use tantivy::schema::{Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index};
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let timestamp = schema_builder.add_u64_field("timestamp", INDEXED | FAST);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// for day in 0u64..10u64 {
+///     for hour in 0u64..24u64 {
+///         index_writer.add_document(doc!(timestamp => day * 24 + hour))?;
+///     }
+///     // One segment per day.
+///     index_writer.commit()?;
+/// }
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+/// let window = 200u64..220u64;
+/// let query = SegmentPruningQuery::new(
+///     Box::new(RangeQuery::new_u64(timestamp, window.clone())),
+///     timestamp,
+///     window,
+/// );
+/// assert_eq!(searcher.search(&query, &Count)?, 20);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+pub struct SegmentPruningQuery {
+    query: Box<dyn Query>,
+    field: Field,
+    value_type: Type,
+    // u64 representation of the range.
+    range: Range<u64>,
+}
+
+impl SegmentPruningQuery {
+    /// Creates a new `SegmentPruningQuery`, running `query` only on the segments that may
+    /// contain a document for which the fast field `field` has a value within `range`.
+    ///
+    /// If the field is not a fast field of the type `TFastValue`, an error will be returned
+    /// when the `Weight` object is created.
+    pub fn new<TFastValue: FastValue>(
+        query: Box<dyn Query>,
+        field: Field,
+        range: Range<TFastValue>,
+    ) -> SegmentPruningQuery {
+        SegmentPruningQuery {
+            query,
+            field,
+            value_type: TFastValue::to_type(),
+            range: range.start.to_u64()..range.end.to_u64(),
+        }
+    }
+
+    /// Field used to prune segments.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    fn cardinality(&self, schema: &Schema) -> crate::Result<Cardinality> {
+        validate_fast_field(schema, self.field, &[self.value_type])
+    }
+}
+
+impl Clone for SegmentPruningQuery {
+    fn clone(&self) -> Self {
+        SegmentPruningQuery {
+            query: self.query.box_clone(),
+            field: self.field,
+            value_type: self.value_type,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for SegmentPruningQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SegmentPruning(query={:?}, field={:?}, range={:?})",
+            self.query, self.field, self.range
+        )
+    }
+}
+
+impl Query for SegmentPruningQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let cardinality = self.cardinality(searcher.schema())?;
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(SegmentPruningWeight {
+            weight,
+            field: self.field,
+            cardinality,
+            range: self.range.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        self.cardinality(schema)?;
+        self.query.validate(schema)
+    }
+}
+
+struct SegmentPruningWeight {
+    weight: Box<dyn Weight>,
+    field: Field,
+    cardinality: Cardinality,
+    range: Range<u64>,
+}
+
+impl SegmentPruningWeight {
+    /// Returns true if the segment cannot contain any value within the range.
+    fn is_pruned(&self, reader: &SegmentReader) -> crate::Result<bool> {
+        if reader.max_doc() == 0 || self.range.is_empty() {
+            return Ok(true);
+        }
+        let fast_field_readers = reader.fast_fields();
+        let (min_value, max_value) = match self.cardinality {
+            Cardinality::SingleValue => {
+                let column = fast_field_readers.u64_lenient(self.field)?;
+                (column.min_value(), column.max_value())
+            }
+            Cardinality::MultiValues => {
+                let multi_reader = fast_field_readers.u64s_lenient(self.field)?;
+                (multi_reader.min_value(), multi_reader.max_value())
+            }
+        };
+        Ok(max_value < self.range.start || min_value >= self.range.end)
+    }
+}
+
+impl Weight for SegmentPruningWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        if self.is_pruned(reader)? {
+            return Ok(Box::new(EmptyScorer));
+        }
+        self.weight.scorer(reader, boost)
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        if self.is_pruned(reader)? {
+            return Err(does_not_match(doc));
+        }
+        self.weight.explain(reader, doc)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        if self.is_pruned(reader)? {
+            return Ok(0);
+        }
+        self.weight.count(reader)
+    }
+
+    fn for_each(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score),
+    ) -> crate::Result<()> {
+        if self.is_pruned(reader)? {
+            return Ok(());
+        }
+        self.weight.for_each(reader, callback)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        if self.is_pruned(reader)? {
+            return Ok(());
+        }
+        self.weight.for_each_pruning(threshold, reader, callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::SegmentPruningQuery;
+    use crate::collector::Count;
+    use crate::query::{AllQuery, Explanation, Query, Scorer, Weight};
+    use crate::schema::{Cardinality, DateOptions, Schema, FAST, INDEXED};
+    use crate::{DateTime, DocId, Index, Score, Searcher, SegmentId, SegmentReader};
+
+    /// Query matching all documents, recording the segments it was evaluated on.
+    #[derive(Clone, Debug, Default)]
+    struct RecordingQuery {
+        visited_segments: Arc<Mutex<Vec<SegmentId>>>,
+    }
+
+    struct RecordingWeight {
+        weight: Box<dyn Weight>,
+        visited_segments: Arc<Mutex<Vec<SegmentId>>>,
+    }
+
+    impl Query for RecordingQuery {
+        fn weight(
+            &self,
+            searcher: &Searcher,
+            scoring_enabled: bool,
+        ) -> crate::Result<Box<dyn Weight>> {
+            Ok(Box::new(RecordingWeight {
+                weight: AllQuery.weight(searcher, scoring_enabled)?,
+                visited_segments: self.visited_segments.clone(),
+            }))
+        }
+    }
+
+    impl Weight for RecordingWeight {
+        fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+            self.visited_segments
+                .lock()
+                .unwrap()
+                .push(reader.segment_id());
+            self.weight.scorer(reader, boost)
+        }
+
+        fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+            self.weight.explain(reader, doc)
+        }
+    }
+
+    fn timestamp_index(timestamp_options: DateOptions) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_date_field("timestamp", timestamp_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        // Segment i contains the timestamps within [100 * i, 100 * i + 10).
+        for segment in 0..4 {
+            for i in 0..10 {
+                let secs = 100 * segment + i;
+                index_writer
+                    .add_document(doc!(timestamp => DateTime::from_timestamp_secs(secs)))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok(index)
+    }
+
+    fn secs_range(start: i64, end: i64) -> std::ops::Range<DateTime> {
+        DateTime::from_timestamp_secs(start)..DateTime::from_timestamp_secs(end)
+    }
+
+    #[test]
+    fn test_segment_pruning_query_skips_out_of_range_segments() -> crate::Result<()> {
+        let index = timestamp_index(FAST.into())?;
+        let timestamp = index.schema().get_field("timestamp").unwrap();
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+        let segment_with_min = |min_secs: i64| -> SegmentId {
+            searcher
+                .segment_readers()
+                .iter()
+                .find(|reader| {
+                    reader.fast_fields().date(timestamp).unwrap().min_value()
+                        == DateTime::from_timestamp_secs(min_secs)
+                })
+                .unwrap()
+                .segment_id()
+        };
+
+        let recording_query = RecordingQuery::default();
+        let query = SegmentPruningQuery::new(
+            Box::new(recording_query.clone()),
+            timestamp,
+            secs_range(105, 205),
+        );
+        // The underlying query is run on the overlapping segments as is.
+        assert_eq!(searcher.search(&query, &Count)?, 20);
+        let mut visited_segments = recording_query.visited_segments.lock().unwrap().clone();
+        visited_segments.sort();
+        let mut expected_segments = vec![segment_with_min(100), segment_with_min(200)];
+        expected_segments.sort();
+        assert_eq!(visited_segments, expected_segments);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_pruning_query_bounds() -> crate::Result<()> {
+        let index = timestamp_index(FAST.into())?;
+        let timestamp = index.schema().get_field("timestamp").unwrap();
+        let searcher = index.reader()?.searcher();
+        let count = |start: i64, end: i64| -> crate::Result<usize> {
+            let query =
+                SegmentPruningQuery::new(Box::new(AllQuery), timestamp, secs_range(start, end));
+            searcher.search(&query, &Count)
+        };
+        // The end of the range is excluded.
+        assert_eq!(count(0, 100)?, 10);
+        assert_eq!(count(0, 101)?, 20);
+        assert_eq!(count(309, 1_000)?, 10);
+        assert_eq!(count(310, 1_000)?, 0);
+        assert_eq!(count(50, 80)?, 0);
+        assert_eq!(count(80, 50)?, 0);
+        assert_eq!(count(-1_000, 1_000)?, 40);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_pruning_query_multivalued() -> crate::Result<()> {
+        let index = timestamp_index(DateOptions::default().set_fast(Cardinality::MultiValues))?;
+        let timestamp = index.schema().get_field("timestamp").unwrap();
+        let searcher = index.reader()?.searcher();
+        let query = SegmentPruningQuery::new(Box::new(AllQuery), timestamp, secs_range(305, 306));
+        assert_eq!(searcher.search(&query, &Count)?, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_pruning_query_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let searcher = index.reader()?.searcher();
+        let wrong_type_query = SegmentPruningQuery::new(Box::new(AllQuery), fast_field, 0i64..1i64);
+        assert!(searcher.search(&wrong_type_query, &Count).is_err());
+        let not_fast_query =
+            SegmentPruningQuery::new(Box::new(AllQuery), indexed_field, 0u64..1u64);
+        assert!(searcher.search(&not_fast_query, &Count).is_err());
+        Ok(())
+    }
+}
@@ -12,7 +12,7 @@ mod tests {
     use crate::collector::TopDocs;
     use crate::docset::DocSet;
     use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
-    use crate::query::{Query, QueryParser, Scorer, TermQuery};
+    use crate::query::{Query, QueryParser, Scorer, TermQuery, TermScorer, Weight};
     use crate::schema::{Field, IndexRecordOption, Schema, STRING, TEXT};
     use crate::{assert_nearly_equals, DocAddress, Index, Term, TERMINATED};
 
@@ -213,4 +213,52 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    pub fn test_term_scorer_matched_positions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let text = "to be or not to be, that is the question";
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => text))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let term = Term::from_field_text(text_field, "be");
+        let mut expected_positions = Vec::new();
+        index
+            .tokenizers()
+            .get("default")
+            .unwrap()
+            .token_stream(text)
+            .process(&mut |token| {
+                if token.text == "be" {
+                    expected_positions.push(token.position as u32);
+                }
+            });
+        assert_eq!(expected_positions, vec![1, 5]);
+        let mut positions = Vec::new();
+        {
+            let term_query = TermQuery::new(term.clone(), IndexRecordOption::WithFreqsAndPositions);
+            let term_weight = term_query.specialized_weight(&searcher, true)?;
+            let mut term_scorer =
+                term_weight.specialized_scorer(searcher.segment_reader(0), 1.0)?;
+            assert_eq!(term_scorer.doc(), 0);
+            term_scorer.matched_positions(&mut positions);
+            assert_eq!(positions, expected_positions);
+        }
+        {
+            // Positions are not available if they were not requested.
+            let term_query = TermQuery::new(term, IndexRecordOption::WithFreqs);
+            let term_weight = term_query.specialized_weight(&searcher, true)?;
+            let mut scorer = term_weight.scorer(searcher.segment_reader(0), 1.0)?;
+            let term_scorer = scorer.downcast_mut::<TermScorer>().unwrap();
+            term_scorer.matched_positions(&mut positions);
+            assert!(positions.is_empty());
+        }
+        Ok(())
+    }
 }
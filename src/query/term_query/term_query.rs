@@ -2,8 +2,8 @@ use std::fmt;
 
 use super::term_weight::TermWeight;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{Explanation, Query, Weight};
-use crate::schema::IndexRecordOption;
+use crate::query::{validate_indexed_field, Explanation, Query, Weight};
+use crate::schema::{IndexRecordOption, Schema};
 use crate::{Searcher, Term};
 
 /// A Term query matches all of the documents
@@ -90,11 +90,7 @@ impl TermQuery {
         searcher: &Searcher,
         scoring_enabled: bool,
     ) -> crate::Result<TermWeight> {
-        let field_entry = searcher.schema().get_field_entry(self.term.field());
-        if !field_entry.is_indexed() {
-            let error_msg = format!("Field {:?} is not indexed.", field_entry.name());
-            return Err(crate::TantivyError::SchemaError(error_msg));
-        }
+        validate_indexed_field(searcher.schema(), self.term.field())?;
         let bm25_weight = if scoring_enabled {
             Bm25Weight::for_terms(searcher, &[self.term.clone()])?
         } else {
@@ -123,4 +119,9 @@ impl Query for TermQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         visitor(&self.term, false);
     }
+
+    fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        validate_indexed_field(schema, self.term.field())?;
+        Ok(())
+    }
 }
@@ -5,26 +5,41 @@ use crate::query::bm25::Bm25Weight;
 use crate::query::{Explanation, Scorer};
 use crate::{DocId, Score};
 
+/// Scorer for the documents matching a single term, as returned by the [`TermQuery`]
+/// weight.
+///
+/// [`TermQuery`]: crate::query::TermQuery
 #[derive(Clone)]
 pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
     similarity_weight: Bm25Weight,
+    max_score: Score,
 }
 
 impl TermScorer {
-    pub fn new(
+    pub(crate) fn new(
         postings: SegmentPostings,
         fieldnorm_reader: FieldNormReader,
         similarity_weight: Bm25Weight,
     ) -> TermScorer {
+        let max_score = similarity_weight.max_score();
         TermScorer {
             postings,
             fieldnorm_reader,
             similarity_weight,
+            max_score,
         }
     }
 
+    /// Sets the maximum score returned by [`TermScorer::max_score`], usually to a bound tighter
+    /// than the one of the similarity weight.
+    ///
+    /// `max_score` must upper-bound the score of all of the documents of the scorer.
+    pub(crate) fn set_max_score(&mut self, max_score: Score) {
+        self.max_score = max_score;
+    }
+
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
         self.postings.block_cursor.shallow_seek(target_doc);
     }
@@ -74,24 +89,38 @@ impl TermScorer {
             .block_max_score(&self.fieldnorm_reader, &self.similarity_weight)
     }
 
+    /// Returns the number of occurrences of the term in the current document.
     pub fn term_freq(&self) -> u32 {
         self.postings.term_freq()
     }
 
+    /// Returns the positions of the term within the current document, in increasing order.
+    ///
+    /// The positions are only read when this method is called.
+    /// They are only available if the scorer was created with
+    /// `IndexRecordOption::WithFreqsAndPositions`. Otherwise, `output` is left empty.
+    pub fn matched_positions(&mut self, output: &mut Vec<u32>) {
+        self.postings.positions(output);
+    }
+
+    /// Returns the fieldnorm id of the current document.
     pub fn fieldnorm_id(&self) -> u8 {
         self.fieldnorm_reader.fieldnorm_id(self.doc())
     }
 
+    /// Explains the score of the current document.
     pub fn explain(&self) -> Explanation {
         let fieldnorm_id = self.fieldnorm_id();
         let term_freq = self.term_freq();
         self.similarity_weight.explain(fieldnorm_id, term_freq)
     }
 
+    /// Returns the maximum score any document can get.
     pub fn max_score(&self) -> Score {
-        self.similarity_weight.max_score()
+        self.max_score
     }
 
+    /// Returns the last document of the current block.
     pub fn last_doc_in_block(&self) -> DocId {
         self.postings.block_cursor.skip_reader.last_doc_in_block()
     }
@@ -112,14 +112,17 @@ impl TermWeight {
         let fieldnorm_reader =
             fieldnorm_reader_opt.unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 1));
         let similarity_weight = self.similarity_weight.boost_by(boost);
-        let postings_opt: Option<SegmentPostings> =
-            inverted_index.read_postings(&self.term, self.index_record_option)?;
-        if let Some(segment_postings) = postings_opt {
-            Ok(TermScorer::new(
-                segment_postings,
-                fieldnorm_reader,
-                similarity_weight,
-            ))
+        if let Some(term_info) = inverted_index.get_term_info(&self.term)? {
+            let segment_postings =
+                inverted_index.read_postings_from_terminfo(&term_info, self.index_record_option)?;
+            let max_score_opt = term_info.max_score(&similarity_weight);
+            let mut term_scorer =
+                TermScorer::new(segment_postings, fieldnorm_reader, similarity_weight);
+            // Without scoring, the fieldnorms are not read, and the bound does not apply.
+            if let Some(max_score) = max_score_opt.filter(|_| self.scoring_enabled) {
+                term_scorer.set_max_score(max_score);
+            }
+            Ok(term_scorer)
         } else {
             Ok(TermScorer::new(
                 SegmentPostings::empty(),
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+/// Metric used to compare the vectors of a dense vector field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorMetric {
+    /// Cosine similarity. Vectors are normalized when they are indexed.
+    #[default]
+    Cosine,
+    /// Euclidean distance.
+    L2,
+    /// Dot product. Unlike with `Cosine`, the vectors are not normalized, so that their norm
+    /// is taken into account.
+    Dot,
+}
+
+fn default_m() -> usize {
+    16
+}
+
+fn default_ef_construction() -> usize {
+    100
+}
+
+/// Define how a dense vector field should be handled by tantivy.
+///
+/// A dense vector field associates a vector of `dimension` `f32` to a document. The vectors
+/// of a segment are indexed in a HNSW graph, which can be searched with a
+/// [`NearestNeighborQuery`](crate::query::NearestNeighborQuery).
+///
+/// `m` and `ef_construction` are the parameters of the graph: respectively the number of
+/// neighbors of each vector, and the number of candidates considered when inserting a vector.
+/// Higher values improve the recall, at the expense of the indexing time and of the size
+/// of the graph.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenseVectorOptions {
+    dimension: usize,
+    #[serde(default)]
+    metric: VectorMetric,
+    #[serde(default)]
+    stored: bool,
+    #[serde(default = "default_m")]
+    m: usize,
+    #[serde(default = "default_ef_construction")]
+    ef_construction: usize,
+}
+
+impl DenseVectorOptions {
+    /// Creates the options of a dense vector field of vectors of `dimension` values.
+    ///
+    /// # Panics
+    /// The method panics if `dimension` is 0.
+    pub fn new(dimension: usize) -> DenseVectorOptions {
+        assert!(dimension >= 1, "Dimension must be strictly greater than 0.");
+        DenseVectorOptions {
+            dimension,
+            metric: VectorMetric::default(),
+            stored: false,
+            m: default_m(),
+            ef_construction: default_ef_construction(),
+        }
+    }
+
+    /// Returns the number of values of the vectors.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the metric used to compare vectors.
+    pub fn metric(&self) -> VectorMetric {
+        self.metric
+    }
+
+    /// Returns true if the vectors are stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns the number of neighbors of each vector of the graph.
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// Returns the number of candidates considered when inserting a vector in the graph.
+    pub fn ef_construction(&self) -> usize {
+        self.ef_construction
+    }
+
+    /// Sets the metric used to compare vectors.
+    #[must_use]
+    pub fn set_metric(mut self, metric: VectorMetric) -> DenseVectorOptions {
+        self.metric = metric;
+        self
+    }
+
+    /// Sets the field as stored.
+    #[must_use]
+    pub fn set_stored(mut self) -> DenseVectorOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Sets the number of neighbors of each vector of the graph.
+    ///
+    /// # Panics
+    /// The method panics if `m` is lower than 2.
+    #[must_use]
+    pub fn set_m(mut self, m: usize) -> DenseVectorOptions {
+        assert!(m >= 2, "M must be greater or equal to 2.");
+        self.m = m;
+        self
+    }
+
+    /// Sets the number of candidates considered when inserting a vector in the graph.
+    ///
+    /// # Panics
+    /// The method panics if `ef_construction` is 0.
+    #[must_use]
+    pub fn set_ef_construction(mut self, ef_construction: usize) -> DenseVectorOptions {
+        assert!(
+            ef_construction >= 1,
+            "ef_construction must be strictly greater than 0."
+        );
+        self.ef_construction = ef_construction;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DenseVectorOptions, VectorMetric};
+
+    #[test]
+    fn test_dense_vector_options_deser_defaults() {
+        let options: DenseVectorOptions = serde_json::from_str(r#"{"dimension": 3}"#).unwrap();
+        assert_eq!(options, DenseVectorOptions::new(3));
+        assert_eq!(options.metric(), VectorMetric::Cosine);
+        assert_eq!(options.m(), 16);
+        let options: DenseVectorOptions =
+            serde_json::from_str(r#"{"dimension": 2, "metric": "l2", "stored": true}"#).unwrap();
+        assert_eq!(
+            options,
+            DenseVectorOptions::new(2)
+                .set_metric(VectorMetric::L2)
+                .set_stored()
+        );
+    }
+}
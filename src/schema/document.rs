@@ -88,6 +88,9 @@ impl Document {
     }
 
     /// Add a pre-tokenized text field.
+    ///
+    /// The tokens are indexed as is, at their positions, without going through the tokenizer
+    /// of the field. The original text is the value that gets stored.
     pub fn add_pre_tokenized_text(&mut self, field: Field, pre_tokenized_text: PreTokenizedString) {
         self.add_field_value(field, pre_tokenized_text);
     }
@@ -122,6 +125,11 @@ impl Document {
         self.add_field_value(field, value.into());
     }
 
+    /// Add a dense vector field
+    pub fn add_dense_vector(&mut self, field: Field, vector: Vec<f32>) {
+        self.add_field_value(field, Value::DenseVector(vector));
+    }
+
     /// Add a JSON field
     pub fn add_json_object(
         &mut self,
@@ -191,6 +199,62 @@ impl Document {
     pub fn get_first(&self, field: Field) -> Option<&Value> {
         self.get_all(field).next()
     }
+
+    /// Returns all of the text values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_text(&self, field: Field) -> impl Iterator<Item = &str> {
+        self.get_all(field).filter_map(Value::as_text)
+    }
+
+    /// Returns all of the u64 values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_u64(&self, field: Field) -> impl Iterator<Item = u64> + '_ {
+        self.get_all(field).filter_map(Value::as_u64)
+    }
+
+    /// Returns all of the i64 values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_i64(&self, field: Field) -> impl Iterator<Item = i64> + '_ {
+        self.get_all(field).filter_map(Value::as_i64)
+    }
+
+    /// Returns all of the f64 values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_f64(&self, field: Field) -> impl Iterator<Item = f64> + '_ {
+        self.get_all(field).filter_map(Value::as_f64)
+    }
+
+    /// Returns all of the bool values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_bool(&self, field: Field) -> impl Iterator<Item = bool> + '_ {
+        self.get_all(field).filter_map(Value::as_bool)
+    }
+
+    /// Returns all of the date values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_date(&self, field: Field) -> impl Iterator<Item = DateTime> + '_ {
+        self.get_all(field).filter_map(Value::as_date)
+    }
+
+    /// Returns all of the bytes values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_bytes(&self, field: Field) -> impl Iterator<Item = &[u8]> {
+        self.get_all(field).filter_map(Value::as_bytes)
+    }
+
+    /// Returns all of the facet values associated the given field.
+    ///
+    /// The values of another type are skipped.
+    pub fn get_all_facet(&self, field: Field) -> impl Iterator<Item = &Facet> {
+        self.get_all(field).filter_map(Value::as_facet)
+    }
 }
 
 impl BinarySerializable for Document {
@@ -218,6 +282,7 @@ mod tests {
     use common::BinarySerializable;
 
     use crate::schema::*;
+    use crate::DateTime;
 
     #[test]
     fn test_doc() {
@@ -228,6 +293,67 @@ mod tests {
         assert_eq!(doc.field_values().len(), 1);
     }
 
+    #[test]
+    fn test_doc_typed_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let flag = schema_builder.add_bool_field("flag", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let bytes = schema_builder.add_bytes_field("bytes", FAST);
+        let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let date_time = DateTime::from_timestamp_secs(1_000);
+        let mut doc = doc!(
+            title => "first",
+            count => 1u64,
+            delta => -1i64,
+            title => "second",
+            count => 2u64,
+            score => 0.5f64,
+            flag => true,
+            flag => false,
+            date => date_time,
+            bytes => b"abc".to_vec(),
+            facet => Facet::from("/a/b"),
+        );
+        // A value of another type is skipped.
+        doc.add_text(count, "three");
+
+        assert_eq!(
+            doc.get_all_text(title).collect::<Vec<&str>>(),
+            vec!["first", "second"]
+        );
+        assert_eq!(doc.get_all_u64(count).collect::<Vec<u64>>(), vec![1, 2]);
+        assert_eq!(
+            doc.get_all_text(count).collect::<Vec<&str>>(),
+            vec!["three"]
+        );
+        assert_eq!(doc.get_all_i64(delta).collect::<Vec<i64>>(), vec![-1]);
+        assert_eq!(doc.get_all_f64(score).collect::<Vec<f64>>(), vec![0.5]);
+        assert_eq!(
+            doc.get_all_bool(flag).collect::<Vec<bool>>(),
+            vec![true, false]
+        );
+        assert_eq!(
+            doc.get_all_date(date).collect::<Vec<DateTime>>(),
+            vec![date_time]
+        );
+        assert_eq!(
+            doc.get_all_bytes(bytes).collect::<Vec<&[u8]>>(),
+            vec![&b"abc"[..]]
+        );
+        assert_eq!(
+            doc.get_all_facet(facet).collect::<Vec<&Facet>>(),
+            vec![&Facet::from("/a/b")]
+        );
+        // The accessors of another type, or of a field without values, are empty.
+        assert_eq!(doc.get_all_u64(title).count(), 0);
+        assert_eq!(doc.get_all_i64(count).count(), 0);
+        assert_eq!(doc.get_all_text(score).count(), 0);
+    }
+
     #[test]
     fn test_doc_serialization_issue() {
         let mut doc = Document::default();
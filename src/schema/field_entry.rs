@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::{
-    is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
-    TextOptions,
+    is_valid_field_name, DateOptions, DenseVectorOptions, FacetOptions, FieldType,
+    JsonObjectOptions, NumericOptions, TextOptions,
 };
 
 /// A `FieldEntry` represents a field and its configuration.
@@ -75,6 +75,14 @@ impl FieldEntry {
         Self::new(field_name, FieldType::JsonObject(json_object_options))
     }
 
+    /// Creates a field entry for a dense vector field
+    pub fn new_dense_vector(
+        field_name: String,
+        dense_vector_options: DenseVectorOptions,
+    ) -> FieldEntry {
+        Self::new(field_name, FieldType::DenseVector(dense_vector_options))
+    }
+
     /// Returns the name of the field
     pub fn name(&self) -> &str {
         &self.name
@@ -114,6 +122,7 @@ impl FieldEntry {
             FieldType::Facet(ref options) => options.is_stored(),
             FieldType::Bytes(ref options) => options.is_stored(),
             FieldType::JsonObject(ref options) => options.is_stored(),
+            FieldType::DenseVector(ref options) => options.is_stored(),
         }
     }
 }
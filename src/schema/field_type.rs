@@ -5,8 +5,8 @@ use thiserror::Error;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
-    DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, TextFieldIndexing,
-    TextOptions, Value,
+    DateOptions, DenseVectorOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions,
+    PositionsCompression, TextFieldIndexing, TextOptions, Value,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -61,9 +61,11 @@ pub enum Type {
     Bytes = b'b',
     /// Leaf in a Json object.
     Json = b'j',
+    /// `Vec<f32>`
+    DenseVector = b'v',
 }
 
-const ALL_TYPES: [Type; 9] = [
+const ALL_TYPES: [Type; 10] = [
     Type::Str,
     Type::U64,
     Type::I64,
@@ -73,6 +75,7 @@ const ALL_TYPES: [Type; 9] = [
     Type::Facet,
     Type::Bytes,
     Type::Json,
+    Type::DenseVector,
 ];
 
 impl Type {
@@ -99,6 +102,7 @@ impl Type {
             Type::Facet => "Facet",
             Type::Bytes => "Bytes",
             Type::Json => "Json",
+            Type::DenseVector => "DenseVector",
         }
     }
 
@@ -115,6 +119,7 @@ impl Type {
             b'h' => Some(Type::Facet),
             b'b' => Some(Type::Bytes),
             b'j' => Some(Type::Json),
+            b'v' => Some(Type::DenseVector),
             _ => None,
         }
     }
@@ -145,6 +150,8 @@ pub enum FieldType {
     Bytes(BytesOptions),
     /// Json object
     JsonObject(JsonObjectOptions),
+    /// Vector of `f32` of a fixed dimension (one per document)
+    DenseVector(DenseVectorOptions),
 }
 
 impl FieldType {
@@ -160,6 +167,7 @@ impl FieldType {
             FieldType::Facet(_) => Type::Facet,
             FieldType::Bytes(_) => Type::Bytes,
             FieldType::JsonObject(_) => Type::Json,
+            FieldType::DenseVector(_) => Type::DenseVector,
         }
     }
 
@@ -175,6 +183,7 @@ impl FieldType {
             FieldType::Facet(ref _facet_options) => true,
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_indexed(),
+            FieldType::DenseVector(_) => false,
         }
     }
 
@@ -199,6 +208,22 @@ impl FieldType {
         }
     }
 
+    /// Returns the encoding used for the positions of the field.
+    ///
+    /// Fields that are not text fields use the default encoding.
+    pub fn positions_compression(&self) -> PositionsCompression {
+        match self {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map(TextFieldIndexing::positions_compression),
+            FieldType::JsonObject(json_object_options) => json_object_options
+                .get_text_indexing_options()
+                .map(TextFieldIndexing::positions_compression),
+            _ => None,
+        }
+        .unwrap_or_default()
+    }
+
     /// returns true if the field is fast.
     pub fn is_fast(&self) -> bool {
         match *self {
@@ -210,7 +235,7 @@ impl FieldType {
             | FieldType::Bool(ref int_options) => int_options.is_fast(),
             FieldType::Date(ref date_options) => date_options.is_fast(),
             FieldType::Facet(_) => true,
-            FieldType::JsonObject(_) => false,
+            FieldType::JsonObject(_) | FieldType::DenseVector(_) => false,
         }
     }
 
@@ -229,6 +254,7 @@ impl FieldType {
             FieldType::Facet(_) => false,
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
             FieldType::JsonObject(ref _json_object_options) => false,
+            FieldType::DenseVector(_) => false,
         }
     }
 
@@ -273,6 +299,7 @@ impl FieldType {
             FieldType::JsonObject(ref json_obj_options) => json_obj_options
                 .get_text_indexing_options()
                 .map(TextFieldIndexing::index_option),
+            FieldType::DenseVector(_) => None,
         }
     }
 
@@ -312,6 +339,10 @@ impl FieldType {
                         expected: "a json object",
                         json: JsonValue::String(field_text),
                     }),
+                    FieldType::DenseVector(_) => Err(ValueParsingError::TypeError {
+                        expected: "an array of numbers",
+                        json: JsonValue::String(field_text),
+                    }),
                 }
             }
             JsonValue::Number(field_val_num) => match self {
@@ -359,6 +390,10 @@ impl FieldType {
                     expected: "a json object",
                     json: JsonValue::Number(field_val_num),
                 }),
+                FieldType::DenseVector(_) => Err(ValueParsingError::TypeError {
+                    expected: "an array of numbers",
+                    json: JsonValue::Number(field_val_num),
+                }),
             },
             JsonValue::Object(json_map) => match self {
                 FieldType::Str(_) => {
@@ -386,6 +421,24 @@ impl FieldType {
                     json: JsonValue::Bool(json_bool_val),
                 }),
             },
+            JsonValue::Array(json_values) => match self {
+                FieldType::DenseVector(_) => {
+                    let vector: Option<Vec<f32>> = json_values
+                        .iter()
+                        .map(|json_value| json_value.as_f64().map(|val| val as f32))
+                        .collect();
+                    vector
+                        .map(Value::DenseVector)
+                        .ok_or(ValueParsingError::TypeError {
+                            expected: "an array of numbers",
+                            json: JsonValue::Array(json_values),
+                        })
+                }
+                _ => Err(ValueParsingError::TypeError {
+                    expected: self.value_type().name(),
+                    json: JsonValue::Array(json_values),
+                }),
+            },
             _ => Err(ValueParsingError::TypeError {
                 expected: self.value_type().name(),
                 json: json.clone(),
@@ -400,7 +453,7 @@ mod tests {
 
     use super::FieldType;
     use crate::schema::field_type::ValueParsingError;
-    use crate::schema::{Schema, TextOptions, Type, Value, INDEXED};
+    use crate::schema::{DenseVectorOptions, Schema, TextOptions, Type, Value, INDEXED};
     use crate::time::{Date, Month, PrimitiveDateTime, Time};
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{DateTime, Document};
@@ -518,6 +571,37 @@ mod tests {
         assert_eq!(serialized_value_json, pre_tokenized_string_json);
     }
 
+    #[test]
+    fn test_dense_vector_value_from_json() {
+        let mut schema_builder = Schema::builder();
+        let embedding =
+            schema_builder.add_dense_vector_field("embedding", DenseVectorOptions::new(3));
+        let schema = schema_builder.build();
+        let doc = schema
+            .parse_document(r#"{"embedding": [1, 0.5, -2.0]}"#)
+            .unwrap();
+        assert_eq!(
+            doc.get_first(embedding),
+            Some(&Value::DenseVector(vec![1.0, 0.5, -2.0]))
+        );
+        assert_eq!(doc.get_all(embedding).count(), 1);
+        let schema_json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(schema_json[0]["type"], json!("dense_vector"));
+        assert_eq!(
+            serde_json::from_value::<Schema>(schema_json).unwrap(),
+            schema
+        );
+        let field_type = FieldType::DenseVector(DenseVectorOptions::new(3));
+        assert_eq!(
+            field_type.value_from_json(json!([1, "a"])),
+            Err(ValueParsingError::TypeError {
+                expected: "an array of numbers",
+                json: json!([1, "a"]),
+            })
+        );
+        assert!(field_type.value_from_json(json!(1.0)).is_err());
+    }
+
     #[test]
     fn test_type_codes() {
         for type_val in Type::iter_values() {
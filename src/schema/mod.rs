@@ -118,6 +118,7 @@ mod field_value;
 
 mod bytes_options;
 mod date_time_options;
+mod dense_vector_options;
 mod field;
 mod flags;
 mod index_record_option;
@@ -129,6 +130,7 @@ mod value;
 
 pub use self::bytes_options::BytesOptions;
 pub use self::date_time_options::{DateOptions, DatePrecision};
+pub use self::dense_vector_options::{DenseVectorOptions, VectorMetric};
 pub use self::document::Document;
 pub(crate) use self::facet::FACET_SEP_BYTE;
 pub use self::facet::{Facet, FacetParseError};
@@ -141,12 +143,13 @@ pub use self::flags::{FAST, INDEXED, STORED};
 pub use self::index_record_option::IndexRecordOption;
 pub use self::json_object_options::JsonObjectOptions;
 pub use self::named_field_document::NamedFieldDocument;
-pub use self::numeric_options::NumericOptions;
 #[allow(deprecated)]
 pub use self::numeric_options::{Cardinality, IntOptions};
+pub use self::numeric_options::{FloatPrecision, MultiValueReduction, NumericOptions};
 pub use self::schema::{DocParsingError, Schema, SchemaBuilder};
 pub use self::term::Term;
-pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
+pub(crate) use self::text_options::DEFAULT_POSITION_GAP;
+pub use self::text_options::{PositionsCompression, TextFieldIndexing, TextOptions, STRING, TEXT};
 pub use self::value::Value;
 
 /// Validator for a potential `field_name`.
@@ -16,6 +16,54 @@ pub enum Cardinality {
     MultiValues,
 }
 
+/// Defines how the values of a document are reduced to the value of a single-valued fast field,
+/// when the document has more than one value for the field.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiValueReduction {
+    /// Only the first value of the document is kept.
+    #[default]
+    First,
+    /// The values of the document are summed.
+    ///
+    /// The sum saturates at the bounds of the type of the field: the sum of the `u64` values
+    /// of a document is at most `u64::MAX`, and the sum of its `i64` values lies within
+    /// `i64::MIN..=i64::MAX`.
+    Sum,
+}
+
+impl MultiValueReduction {
+    fn is_first(&self) -> bool {
+        *self == MultiValueReduction::First
+    }
+}
+
+/// Defines the precision with which the values of an f64 fast field are stored.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FloatPrecision {
+    /// The values are stored as is.
+    #[default]
+    F64,
+    /// The values are stored with the precision of an f32: their mantissa is truncated to its
+    /// 23 most significant bits, which halves the space taken by the fast field.
+    ///
+    /// The values are rounded down, with a relative error below `2^-23`, that is about
+    /// `1.2e-7`. Unlike a conversion to an f32, the range of the values is preserved. The fast
+    /// field values keep the order of the original values, so that range queries on the fast
+    /// field remain correctly ordered, but close values may become equal.
+    ///
+    /// The values and bounds of the fast field equality, set and range queries are truncated
+    /// the same way, so that a value still matches the queries on itself.
+    F32,
+}
+
+impl FloatPrecision {
+    fn is_f64(&self) -> bool {
+        *self == FloatPrecision::F64
+    }
+}
+
 #[deprecated(since = "0.17.0", note = "Use NumericOptions instead.")]
 /// Deprecated use [NumericOptions] instead.
 pub type IntOptions = NumericOptions;
@@ -30,6 +78,10 @@ pub struct NumericOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     fast: Option<Cardinality>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "MultiValueReduction::is_first")]
+    reduction: MultiValueReduction,
+    #[serde(default, skip_serializing_if = "FloatPrecision::is_f64")]
+    float_precision: FloatPrecision,
 }
 
 /// For backward compatibility we add an intermediary to interpret the
@@ -45,6 +97,10 @@ struct NumericOptionsDeser {
     #[serde(default)]
     fast: Option<Cardinality>,
     stored: bool,
+    #[serde(default)]
+    reduction: MultiValueReduction,
+    #[serde(default)]
+    float_precision: FloatPrecision,
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -54,6 +110,8 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fieldnorms: deser.fieldnorms.unwrap_or(deser.indexed),
             fast: deser.fast,
             stored: deser.stored,
+            reduction: deser.reduction,
+            float_precision: deser.float_precision,
         }
     }
 }
@@ -124,8 +182,8 @@ impl NumericOptions {
     ///
     /// Fast fields are designed for random access.
     /// Access time are similar to a random lookup in an array.
-    /// If more than one value is associated to a fast field, only the last one is
-    /// kept.
+    /// If more than one value is associated to a single-valued fast field, they are reduced
+    /// as defined by [`NumericOptions::set_multi_value_reduction`].
     #[must_use]
     pub fn set_fast(mut self, cardinality: Cardinality) -> NumericOptions {
         self.fast = Some(cardinality);
@@ -139,6 +197,37 @@ impl NumericOptions {
     pub fn get_fastfield_cardinality(&self) -> Option<Cardinality> {
         self.fast
     }
+
+    /// Sets how the values of a document are reduced to the value of the fast field, when the
+    /// document has more than one value for the field.
+    ///
+    /// This only has an effect on the u64, i64 and f64 single-valued fast fields. It defaults
+    /// to [`MultiValueReduction::First`].
+    #[must_use]
+    pub fn set_multi_value_reduction(mut self, reduction: MultiValueReduction) -> NumericOptions {
+        self.reduction = reduction;
+        self
+    }
+
+    /// Returns how the values of a document are reduced to the value of the fast field.
+    pub fn get_multi_value_reduction(&self) -> MultiValueReduction {
+        self.reduction
+    }
+
+    /// Sets the precision with which the values of the fast field are stored.
+    ///
+    /// This only has an effect on the f64 fast fields. It defaults to [`FloatPrecision::F64`].
+    /// See [`FloatPrecision::F32`] for the precision loss.
+    #[must_use]
+    pub fn set_float_precision(mut self, float_precision: FloatPrecision) -> NumericOptions {
+        self.float_precision = float_precision;
+        self
+    }
+
+    /// Returns the precision with which the values of the fast field are stored.
+    pub fn get_float_precision(&self) -> FloatPrecision {
+        self.float_precision
+    }
 }
 
 impl From<()> for NumericOptions {
@@ -154,6 +243,8 @@ impl From<FastFlag> for NumericOptions {
             fieldnorms: false,
             stored: false,
             fast: Some(Cardinality::SingleValue),
+            reduction: MultiValueReduction::First,
+            float_precision: FloatPrecision::F64,
         }
     }
 }
@@ -165,6 +256,8 @@ impl From<StoredFlag> for NumericOptions {
             fieldnorms: false,
             stored: true,
             fast: None,
+            reduction: MultiValueReduction::First,
+            float_precision: FloatPrecision::F64,
         }
     }
 }
@@ -176,6 +269,8 @@ impl From<IndexedFlag> for NumericOptions {
             fieldnorms: true,
             stored: false,
             fast: None,
+            reduction: MultiValueReduction::First,
+            float_precision: FloatPrecision::F64,
         }
     }
 }
@@ -190,6 +285,16 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             fieldnorms: self.fieldnorms | other.fieldnorms,
             stored: self.stored | other.stored,
             fast: self.fast.or(other.fast),
+            reduction: if self.reduction.is_first() {
+                other.reduction
+            } else {
+                self.reduction
+            },
+            float_precision: if self.float_precision.is_f64() {
+                other.float_precision
+            } else {
+                self.float_precision
+            },
         }
     }
 }
@@ -222,7 +327,9 @@ mod tests {
                 indexed: true,
                 fieldnorms: true,
                 fast: None,
-                stored: false
+                stored: false,
+                reduction: MultiValueReduction::First,
+                float_precision: FloatPrecision::F64,
             }
         );
     }
@@ -240,7 +347,9 @@ mod tests {
                 indexed: false,
                 fieldnorms: false,
                 fast: None,
-                stored: false
+                stored: false,
+                reduction: MultiValueReduction::First,
+                float_precision: FloatPrecision::F64,
             }
         );
     }
@@ -259,7 +368,9 @@ mod tests {
                 indexed: true,
                 fieldnorms: false,
                 fast: None,
-                stored: false
+                stored: false,
+                reduction: MultiValueReduction::First,
+                float_precision: FloatPrecision::F64,
             }
         );
     }
@@ -279,8 +390,34 @@ mod tests {
                 indexed: false,
                 fieldnorms: true,
                 fast: None,
-                stored: false
+                stored: false,
+                reduction: MultiValueReduction::First,
+                float_precision: FloatPrecision::F64,
             }
         );
     }
+
+    #[test]
+    fn test_int_options_multi_value_reduction_serde() {
+        let int_options = NumericOptions::from(FastFlag);
+        let json = serde_json::to_string(&int_options).unwrap();
+        assert!(!json.contains("reduction"));
+        let sum_options = int_options.set_multi_value_reduction(MultiValueReduction::Sum);
+        let json = serde_json::to_string(&sum_options).unwrap();
+        assert!(json.contains(r#""reduction":"sum""#));
+        let deser_options: NumericOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_options, sum_options);
+    }
+
+    #[test]
+    fn test_int_options_float_precision_serde() {
+        let int_options = NumericOptions::from(FastFlag);
+        let json = serde_json::to_string(&int_options).unwrap();
+        assert!(!json.contains("float_precision"));
+        let f32_options = int_options.set_float_precision(FloatPrecision::F32);
+        let json = serde_json::to_string(&f32_options).unwrap();
+        assert!(json.contains(r#""float_precision":"f32""#));
+        let deser_options: NumericOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_options, f32_options);
+    }
 }
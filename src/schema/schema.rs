@@ -193,6 +193,19 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a dense vector field to the schema.
+    ///
+    /// Dense vector fields are not searchable through terms: their vectors can only be
+    /// searched with a [`NearestNeighborQuery`](crate::query::NearestNeighborQuery).
+    pub fn add_dense_vector_field(
+        &mut self,
+        field_name: &str,
+        field_options: DenseVectorOptions,
+    ) -> Field {
+        let field_entry = FieldEntry::new_dense_vector(field_name.to_string(), field_options);
+        self.add_field(field_entry)
+    }
+
     /// Adds a json object field to the schema.
     pub fn add_json_field<T: Into<JsonObjectOptions>>(
         &mut self,
@@ -344,7 +357,10 @@ impl Schema {
                 let field_entry = self.get_field_entry(field);
                 let field_type = field_entry.field_type();
                 match json_value {
-                    JsonValue::Array(json_items) => {
+                    // The array of a dense vector field is a single value.
+                    JsonValue::Array(json_items)
+                        if !matches!(field_type, FieldType::DenseVector(_)) =>
+                    {
                         for json_item in json_items {
                             let value = field_type
                                 .value_from_json(json_item)
@@ -421,11 +437,12 @@ pub enum DocParsingError {
 
 impl DocParsingError {
     /// Builds a NotJson DocParsingError
-    fn invalid_json(invalid_json: &str) -> Self {
+    pub(crate) fn invalid_json(invalid_json: &str) -> Self {
         let sample_json: String = if invalid_json.len() < 20 {
             invalid_json.to_string()
         } else {
-            format!("{:?}...", &invalid_json[0..20])
+            let sample: String = invalid_json.chars().take(20).collect();
+            format!("{:?}...", sample)
         };
         DocParsingError::InvalidJson(sample_json)
     }
@@ -803,6 +820,11 @@ mod tests {
             );
             assert_matches!(json_err, Err(InvalidJson(_)));
         }
+        {
+            // The sample of the invalid json must not split a multibyte character.
+            let json_err = schema.parse_document("{\"title\": \"ééééééééé\"");
+            assert_matches!(json_err, Err(InvalidJson(_)));
+        }
     }
 
     #[test]
@@ -117,7 +117,6 @@ impl Term {
     /// the natural order of the values.
     pub fn set_u64(&mut self, val: u64) {
         self.set_fast_value(val);
-        self.set_bytes(val.to_be_bytes().as_ref());
     }
 
     fn set_fast_value<T: FastValue>(&mut self, val: T) {
@@ -415,6 +414,9 @@ fn debug_value_bytes(typ: Type, bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Re
                 debug_value_bytes(typ, bytes, f)?;
             }
         }
+        Type::DenseVector => {
+            write_opt(f, Some(bytes))?;
+        }
     }
     Ok(())
 }
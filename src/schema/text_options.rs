@@ -6,9 +6,10 @@ use serde::{Deserialize, Serialize};
 use super::flags::FastFlag;
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
 use crate::schema::IndexRecordOption;
+use crate::Score;
 
 /// Define how a text field should be handled by tantivy.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TextOptions {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -17,6 +18,30 @@ pub struct TextOptions {
     stored: bool,
     #[serde(default)]
     fast: bool,
+    #[serde(default = "default_boost")]
+    #[serde(skip_serializing_if = "is_default_boost")]
+    boost: Score,
+}
+
+const DEFAULT_BOOST: Score = 1.0;
+
+fn default_boost() -> Score {
+    DEFAULT_BOOST
+}
+
+fn is_default_boost(boost: &Score) -> bool {
+    *boost == DEFAULT_BOOST
+}
+
+impl Default for TextOptions {
+    fn default() -> TextOptions {
+        TextOptions {
+            indexing: None,
+            stored: false,
+            fast: false,
+            boost: DEFAULT_BOOST,
+        }
+    }
 }
 
 impl TextOptions {
@@ -67,6 +92,21 @@ impl TextOptions {
         self.indexing = Some(indexing);
         self
     }
+
+    /// Sets a boost applied to the BM25 score of the terms of the field.
+    ///
+    /// Unlike a [`BoostQuery`](crate::query::BoostQuery), the boost is part of the schema and
+    /// applies to every query on the field. Defaults to `1.0`.
+    #[must_use]
+    pub fn set_boost(mut self, boost: Score) -> TextOptions {
+        self.boost = boost;
+        self
+    }
+
+    /// Returns the boost applied to the BM25 score of the terms of the field.
+    pub fn boost(&self) -> Score {
+        self.boost
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
@@ -101,8 +141,15 @@ impl TokenizerName {
 /// Essentially, should we store the term frequency and/or the positions (See
 /// [`IndexRecordOption`](./enum.IndexRecordOption.html)).
 /// - The name of the `Tokenizer` that should be used to process the field.
+/// - The name of the `Tokenizer` that should be used to process the queries on the field. Defaults
+///   to the tokenizer of the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - The position gap inserted between the values of a multivalued field. Defaults to `1`.
+/// - The encoding of the positions (See [`PositionsCompression`]). Defaults to
+///   [`PositionsCompression::VInt`].
+/// - Flag indicating, if term vectors should be stored (See [termvector](crate::termvector)).
+///   Defaults to `false`.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -111,18 +158,81 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search_tokenizer: Option<TokenizerName>,
+    #[serde(default = "default_position_gap")]
+    #[serde(skip_serializing_if = "is_default_position_gap")]
+    position_gap: u32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_positions_compression")]
+    positions_compression: PositionsCompression,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_term_vectors")]
+    term_vectors: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_indexed_tokens: Option<u32>,
+}
+
+/// Defines how the positions of the terms of a text field are encoded.
+///
+/// Positions are encoded as deltas, in blocks of 128 values bitpacked together.
+/// The only difference between the encodings is how the last, incomplete, block of
+/// a term is encoded. Because most terms have less than 128 positions, this
+/// last block usually accounts for most of the positions file.
+///
+/// The encoding is part of the schema, so changing it requires reindexing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PositionsCompression {
+    /// The last block is encoded using variable byte integers.
+    ///
+    /// Each position takes at least one byte.
+    #[default]
+    #[serde(rename = "vint")]
+    VInt,
+    /// The last block is bitpacked as well, using the bit width of its largest delta.
+    ///
+    /// This yields smaller positions files when deltas are small, at the cost of an
+    /// extra byte per term and slightly slower decoding.
+    #[serde(rename = "bitpacked")]
+    Bitpacked,
+}
+
+fn is_default_positions_compression(positions_compression: &PositionsCompression) -> bool {
+    *positions_compression == PositionsCompression::default()
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
     true
 }
 
+/// Default number of positions inserted between two values of the same field.
+pub(crate) const DEFAULT_POSITION_GAP: u32 = 1;
+
+fn default_position_gap() -> u32 {
+    DEFAULT_POSITION_GAP
+}
+
+fn is_default_position_gap(position_gap: &u32) -> bool {
+    *position_gap == DEFAULT_POSITION_GAP
+}
+
+fn is_default_term_vectors(term_vectors: &bool) -> bool {
+    !*term_vectors
+}
+
 impl Default for TextFieldIndexing {
     fn default() -> TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: TokenizerName::default(),
+            search_tokenizer: None,
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            position_gap: DEFAULT_POSITION_GAP,
+            positions_compression: PositionsCompression::default(),
+            term_vectors: false,
+            max_indexed_tokens: None,
         }
     }
 }
@@ -140,6 +250,29 @@ impl TextFieldIndexing {
         self.tokenizer.name()
     }
 
+    /// Sets the tokenizer to be used for the queries on a given field, if it differs from
+    /// the tokenizer used at indexing time.
+    ///
+    /// For instance, a field indexed with an edge n-gram tokenizer for autocompletion should
+    /// be searched with a plain tokenizer, so that the n-grams of the query do not match the
+    /// n-grams of unrelated words.
+    #[must_use]
+    pub fn set_search_tokenizer(mut self, tokenizer_name: &str) -> TextFieldIndexing {
+        self.search_tokenizer = Some(TokenizerName::from_name(tokenizer_name));
+        self
+    }
+
+    /// Returns the tokenizer that will be used for the queries on this field.
+    ///
+    /// Unless set with [`TextFieldIndexing::set_search_tokenizer`], this is the tokenizer used
+    /// at indexing time.
+    pub fn search_tokenizer(&self) -> &str {
+        self.search_tokenizer
+            .as_ref()
+            .unwrap_or(&self.tokenizer)
+            .name()
+    }
+
     /// Sets fieldnorms
     #[must_use]
     pub fn set_fieldnorms(mut self, fieldnorms: bool) -> TextFieldIndexing {
@@ -167,28 +300,110 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets the number of positions to skip between two values of a multivalued field.
+    ///
+    /// With the default gap of `1`, a phrase query with a slop of `1` may match
+    /// across the boundary of two values. Setting a gap larger than the largest
+    /// slop used at query time prevents phrases from straddling two values.
+    #[must_use]
+    pub fn set_position_gap(mut self, position_gap: u32) -> TextFieldIndexing {
+        self.position_gap = position_gap;
+        self
+    }
+
+    /// Returns the number of positions skipped between two values of a multivalued field.
+    pub fn position_gap(&self) -> u32 {
+        self.position_gap
+    }
+
+    /// Sets the encoding used for the positions of the field.
+    ///
+    /// See [`PositionsCompression`] for more detail.
+    #[must_use]
+    pub fn set_positions_compression(
+        mut self,
+        positions_compression: PositionsCompression,
+    ) -> TextFieldIndexing {
+        self.positions_compression = positions_compression;
+        self
+    }
+
+    /// Returns the encoding used for the positions of the field.
+    pub fn positions_compression(&self) -> PositionsCompression {
+        self.positions_compression
+    }
+
+    /// Sets whether the [term vectors](crate::termvector) of the field should be stored.
+    ///
+    /// The term vector of a document lists the tokens of the field, along with their position
+    /// and offsets, so that the text does not need to be tokenized again to be highlighted.
+    #[must_use]
+    pub fn set_term_vectors(mut self, term_vectors: bool) -> TextFieldIndexing {
+        self.term_vectors = term_vectors;
+        self
+    }
+
+    /// Returns true if and only if the [term vectors](crate::termvector) of the field are
+    /// stored.
+    pub fn term_vectors(&self) -> bool {
+        self.term_vectors
+    }
+
+    /// Sets the maximum number of tokens of the field that are indexed, per document.
+    ///
+    /// The tokens following the first `max_indexed_tokens` tokens of a document, over all of
+    /// its values, are not indexed: they cannot be searched, are not counted in the fieldnorm,
+    /// and are not part of the term vector. This bounds the growth of the postings of huge
+    /// fields. The stored value of the field, if any, is not truncated.
+    ///
+    /// As the positions of the indexed tokens are unchanged, a phrase query only matches if all
+    /// of its terms are within the indexed prefix.
+    #[must_use]
+    pub fn set_max_indexed_tokens(mut self, max_indexed_tokens: u32) -> TextFieldIndexing {
+        self.max_indexed_tokens = Some(max_indexed_tokens);
+        self
+    }
+
+    /// Returns the maximum number of tokens of the field that are indexed per document, or
+    /// `None` if all of the tokens are indexed.
+    pub fn max_indexed_tokens(&self) -> Option<u32> {
+        self.max_indexed_tokens
+    }
 }
 
 /// The field will be untokenized and indexed.
 pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
+        search_tokenizer: None,
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        position_gap: DEFAULT_POSITION_GAP,
+        positions_compression: PositionsCompression::VInt,
+        term_vectors: false,
+        max_indexed_tokens: None,
     }),
     stored: false,
     fast: false,
+    boost: DEFAULT_BOOST,
 };
 
 /// The field will be tokenized and indexed.
 pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
+        search_tokenizer: None,
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        position_gap: DEFAULT_POSITION_GAP,
+        positions_compression: PositionsCompression::VInt,
+        term_vectors: false,
+        max_indexed_tokens: None,
     }),
     stored: false,
     fast: false,
+    boost: DEFAULT_BOOST,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -200,6 +415,7 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
             indexing: self.indexing.or(other.indexing),
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
+            boost: self.boost * other.boost,
         }
     }
 }
@@ -216,6 +432,7 @@ impl From<StoredFlag> for TextOptions {
             indexing: None,
             stored: true,
             fast: false,
+            boost: DEFAULT_BOOST,
         }
     }
 }
@@ -226,6 +443,7 @@ impl From<FastFlag> for TextOptions {
             indexing: None,
             stored: false,
             fast: true,
+            boost: DEFAULT_BOOST,
         }
     }
 }
@@ -285,4 +503,87 @@ mod tests {
         let options3: TextOptions = serde_json::from_str("{}").unwrap();
         assert_eq!(options3.indexing, None);
     }
+
+    #[test]
+    fn test_position_gap_serde() {
+        let options: TextFieldIndexing = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.position_gap(), 1);
+        assert_eq!(
+            serde_json::to_string(&options).unwrap(),
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"default"}"#
+        );
+        let options = options.set_position_gap(100);
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"default","position_gap":100}"#
+        );
+        let options_deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(options_deser.position_gap(), 100);
+    }
+
+    #[test]
+    fn test_max_indexed_tokens_serde() {
+        let options: TextFieldIndexing = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.max_indexed_tokens(), None);
+        let options = options.set_max_indexed_tokens(1_000);
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"default","max_indexed_tokens":1000}"#
+        );
+        let options_deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(options_deser.max_indexed_tokens(), Some(1_000));
+    }
+
+    #[test]
+    fn test_search_tokenizer_serde() {
+        let options: TextFieldIndexing = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.search_tokenizer(), "default");
+        let options = options
+            .set_tokenizer("edge_ngram")
+            .set_search_tokenizer("raw");
+        assert_eq!(options.tokenizer(), "edge_ngram");
+        assert_eq!(options.search_tokenizer(), "raw");
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"edge_ngram","search_tokenizer":"raw"}"#
+        );
+        let options_deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(options_deser, options);
+    }
+
+    #[test]
+    fn test_positions_compression_serde() {
+        let options: TextFieldIndexing = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.positions_compression(), PositionsCompression::VInt);
+        let options = options.set_positions_compression(PositionsCompression::Bitpacked);
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"record":"basic","fieldnorms":true,"tokenizer":"default","positions_compression":"bitpacked"}"#
+        );
+        let options_deser: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            options_deser.positions_compression(),
+            PositionsCompression::Bitpacked
+        );
+    }
+
+    #[test]
+    fn test_boost_serde() {
+        let options: TextOptions = serde_json::from_str(r#"{"stored":true}"#).unwrap();
+        assert_eq!(options.boost(), 1.0);
+        assert_eq!(
+            serde_json::to_string(&options).unwrap(),
+            r#"{"stored":true,"fast":false}"#
+        );
+        let options = options.set_boost(2.5);
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(json, r#"{"stored":true,"fast":false,"boost":2.5}"#);
+        let options_deser: TextOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(options_deser.boost(), 2.5);
+        assert_eq!((TEXT.set_boost(2.0) | STORED).boost(), 2.0);
+    }
 }
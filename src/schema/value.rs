@@ -32,6 +32,8 @@ pub enum Value {
     Bytes(Vec<u8>),
     /// Json object value.
     JsonObject(serde_json::Map<String, serde_json::Value>),
+    /// Dense vector value.
+    DenseVector(Vec<f32>),
 }
 
 impl Eq for Value {}
@@ -50,6 +52,7 @@ impl Serialize for Value {
             Value::Facet(ref facet) => facet.serialize(serializer),
             Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
             Value::JsonObject(ref obj) => obj.serialize(serializer),
+            Value::DenseVector(ref vector) => vector.serialize(serializer),
         }
     }
 }
@@ -201,6 +204,17 @@ impl Value {
             None
         }
     }
+
+    /// Returns the vector, provided the value is of the `DenseVector` type.
+    ///
+    /// Returns None if the value is not of type `DenseVector`.
+    pub fn as_dense_vector(&self) -> Option<&[f32]> {
+        if let Value::DenseVector(vector) = self {
+            Some(vector)
+        } else {
+            None
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -306,6 +320,7 @@ mod binary_serialize {
     const EXT_CODE: u8 = 7;
     const JSON_OBJ_CODE: u8 = 8;
     const BOOL_CODE: u8 = 9;
+    const DENSE_VECTOR_CODE: u8 = 10;
 
     // extended types
 
@@ -366,6 +381,10 @@ mod binary_serialize {
                     serde_json::to_writer(writer, &map)?;
                     Ok(())
                 }
+                Value::DenseVector(ref vector) => {
+                    DENSE_VECTOR_CODE.serialize(writer)?;
+                    vector.serialize(writer)
+                }
             }
         }
 
@@ -436,6 +455,7 @@ mod binary_serialize {
                     let json_map = <serde_json::Map::<String, serde_json::Value> as serde::Deserialize>::deserialize(&mut de)?;
                     Ok(Value::JsonObject(json_map))
                 }
+                DENSE_VECTOR_CODE => Ok(Value::DenseVector(Vec::<f32>::deserialize(reader)?)),
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("No field type is associated with code {:?}", type_code),
@@ -131,22 +131,64 @@ fn search_fragments<'a>(
     max_num_chars: usize,
 ) -> Vec<FragmentCandidate> {
     let mut token_stream = tokenizer.token_stream(text);
-    let mut fragment = FragmentCandidate::new(0);
-    let mut fragments: Vec<FragmentCandidate> = vec![];
+    let mut fragments_builder = FragmentsBuilder::new(terms, max_num_chars);
     while let Some(next) = token_stream.next() {
-        if (next.offset_to - fragment.start_offset) > max_num_chars {
+        fragments_builder.add_token(next);
+    }
+    fragments_builder.finish()
+}
+
+/// Same as [`search_fragments`], but from the tokens of the text, rather than by tokenizing
+/// it.
+fn search_fragments_in_tokens(
+    tokens: &[Token],
+    terms: &BTreeMap<String, Score>,
+    max_num_chars: usize,
+) -> Vec<FragmentCandidate> {
+    let mut fragments_builder = FragmentsBuilder::new(terms, max_num_chars);
+    for token in tokens {
+        fragments_builder.add_token(token);
+    }
+    fragments_builder.finish()
+}
+
+/// Splits a text in fragments, token by token.
+struct FragmentsBuilder<'a> {
+    terms: &'a BTreeMap<String, Score>,
+    max_num_chars: usize,
+    fragment: FragmentCandidate,
+    fragments: Vec<FragmentCandidate>,
+}
+
+impl<'a> FragmentsBuilder<'a> {
+    fn new(terms: &'a BTreeMap<String, Score>, max_num_chars: usize) -> FragmentsBuilder<'a> {
+        FragmentsBuilder {
+            terms,
+            max_num_chars,
+            fragment: FragmentCandidate::new(0),
+            fragments: vec![],
+        }
+    }
+
+    fn add_token(&mut self, token: &Token) {
+        if (token.offset_to - self.fragment.start_offset) > self.max_num_chars {
+            let fragment = std::mem::replace(
+                &mut self.fragment,
+                FragmentCandidate::new(token.offset_from),
+            );
             if fragment.score > 0.0 {
-                fragments.push(fragment)
+                self.fragments.push(fragment)
             };
-            fragment = FragmentCandidate::new(next.offset_from);
         }
-        fragment.try_add_token(next, terms);
-    }
-    if fragment.score > 0.0 {
-        fragments.push(fragment)
+        self.fragment.try_add_token(token, self.terms);
     }
 
-    fragments
+    fn finish(mut self) -> Vec<FragmentCandidate> {
+        if self.fragment.score > 0.0 {
+            self.fragments.push(self.fragment)
+        }
+        self.fragments
+    }
 }
 
 /// Returns a Snippet
@@ -359,6 +401,44 @@ impl SnippetGenerator {
             search_fragments(&self.tokenizer, text, &self.terms_text, self.max_num_chars);
         select_best_fragment_combination(&fragment_candidates[..], text)
     }
+
+    /// Generates a snippet for the given `Document`, from its [term
+    /// vector](crate::termvector) rather than by tokenizing its text again.
+    ///
+    /// The term vector is typically obtained from
+    /// [`TermVectorReader::term_vector`](crate::termvector::TermVectorReader::term_vector).
+    pub fn snippet_from_doc_and_term_vector(
+        &self,
+        doc: &Document,
+        term_vector: &[Token],
+    ) -> Snippet {
+        let text: String = doc
+            .get_all(self.field)
+            .flat_map(Value::as_text)
+            .collect::<Vec<&str>>()
+            .join(" ");
+        self.snippet_from_tokens(&text, term_vector)
+    }
+
+    /// Generates a snippet for the given text, from its tokens rather than by tokenizing it.
+    ///
+    /// The offsets of the tokens are expected to be sorted, and relative to `text`. Tokens
+    /// that do not fall within `text` are ignored.
+    pub fn snippet_from_tokens(&self, text: &str, tokens: &[Token]) -> Snippet {
+        let valid_tokens: Vec<Token> = tokens
+            .iter()
+            .filter(|token| {
+                token.offset_from <= token.offset_to
+                    && token.offset_to <= text.len()
+                    && text.is_char_boundary(token.offset_from)
+                    && text.is_char_boundary(token.offset_to)
+            })
+            .cloned()
+            .collect();
+        let fragment_candidates =
+            search_fragments_in_tokens(&valid_tokens, &self.terms_text, self.max_num_chars);
+        select_best_fragment_combination(&fragment_candidates[..], text)
+    }
 }
 
 #[cfg(test)]
@@ -370,8 +450,8 @@ mod tests {
     use super::{collapse_overlapped_ranges, search_fragments, select_best_fragment_combination};
     use crate::query::QueryParser;
     use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
-    use crate::tokenizer::{NgramTokenizer, SimpleTokenizer};
-    use crate::{Index, SnippetGenerator};
+    use crate::tokenizer::{NgramTokenizer, RawTokenizer, SimpleTokenizer, TextAnalyzer};
+    use crate::{DocAddress, Index, SnippetGenerator};
 
     const TEST_TEXT: &str = r#"Rust is a systems programming language sponsored by
 Mozilla which describes it as a "safe, concurrent, practical language", supporting functional and
@@ -636,6 +716,63 @@ Survey in 2016, 2017, and 2018."#;
         Ok(())
     }
 
+    #[test]
+    fn test_snippet_from_term_vector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("en_stem")
+                    .set_term_vectors(true),
+            )
+            .set_stored();
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => TEST_TEXT))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field]).parse_query("rust design")?;
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        let doc = searcher.doc(DocAddress::new(0, 0))?;
+        let term_vector = searcher
+            .segment_reader(0)
+            .term_vectors()
+            .get_field(text_field)?
+            .term_vector(0)?;
+        let expected_html = "imperative-procedural paradigms. <b>Rust</b> is syntactically \
+                             similar to C++[according to whom?],\nbut its <b>designers</b> intend \
+                             it to provide better memory safety";
+        assert_eq!(
+            snippet_generator
+                .snippet_from_doc_and_term_vector(&doc, &term_vector)
+                .to_html(),
+            expected_html
+        );
+        // The term vector is used instead of the tokenizer: a generator whose tokenizer does
+        // not split the text still highlights the terms.
+        let raw_snippet_generator = SnippetGenerator::new(
+            snippet_generator.terms_text().clone(),
+            TextAnalyzer::from(RawTokenizer),
+            text_field,
+            150,
+        );
+        assert!(raw_snippet_generator.snippet_from_doc(&doc).is_empty());
+        assert_eq!(
+            raw_snippet_generator
+                .snippet_from_doc_and_term_vector(&doc, &term_vector)
+                .to_html(),
+            expected_html
+        );
+        // Tokens out of the text are ignored.
+        let mut out_of_text_token = term_vector[0].clone();
+        out_of_text_token.offset_to = TEST_TEXT.len() + 1;
+        assert!(snippet_generator
+            .snippet_from_tokens(TEST_TEXT, &[out_of_text_token])
+            .is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_collapse_overlapped_ranges() {
         assert_eq!(&collapse_overlapped_ranges(&[0..1, 2..3,]), &[0..1, 2..3]);
@@ -12,7 +12,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::Field;
-use crate::SegmentComponent;
+use crate::{SegmentComponent, SegmentId};
 
 /// Indicates space usage in bytes
 pub type ByteCount = usize;
@@ -71,6 +71,8 @@ pub struct SegmentSpaceUsage {
     positions: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
     fieldnorms: PerFieldSpaceUsage,
+    vectors: PerFieldSpaceUsage,
+    term_vectors: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
@@ -88,6 +90,8 @@ impl SegmentSpaceUsage {
         positions: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
+        vectors: PerFieldSpaceUsage,
+        term_vectors: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
         deletes: ByteCount,
     ) -> SegmentSpaceUsage {
@@ -96,6 +100,8 @@ impl SegmentSpaceUsage {
             + positions.total()
             + fast_fields.total()
             + fieldnorms.total()
+            + vectors.total()
+            + term_vectors.total()
             + store.total()
             + deletes;
         SegmentSpaceUsage {
@@ -105,6 +111,8 @@ impl SegmentSpaceUsage {
             positions,
             fast_fields,
             fieldnorms,
+            vectors,
+            term_vectors,
             store,
             deletes,
             total,
@@ -122,8 +130,12 @@ impl SegmentSpaceUsage {
             Postings => PerField(self.postings().clone()),
             Positions => PerField(self.positions().clone()),
             FastFields => PerField(self.fast_fields().clone()),
+            TempFastFields => PerField(self.fast_fields().clone()),
             FieldNorms => PerField(self.fieldnorms().clone()),
+            Vectors => PerField(self.vectors().clone()),
+            TermVectors => PerField(self.term_vectors().clone()),
             Terms => PerField(self.termdict().clone()),
+            TempTerms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
             Delete => Basic(self.deletes()),
@@ -160,6 +172,16 @@ impl SegmentSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for dense vectors
+    pub fn vectors(&self) -> &PerFieldSpaceUsage {
+        &self.vectors
+    }
+
+    /// Space usage for term vectors
+    pub fn term_vectors(&self) -> &PerFieldSpaceUsage {
+        &self.term_vectors
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -176,6 +198,53 @@ impl SegmentSpaceUsage {
     }
 }
 
+/// Document counts and sizes of the components of a segment.
+///
+/// Unlike [`SegmentSpaceUsage`], the stats are computed from the metadata of the segment
+/// only, without any IO nor per field breakdown.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentStats {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Number of alive documents.
+    pub num_docs: u32,
+    /// Number of deleted documents.
+    pub num_deleted_docs: u32,
+    /// Size of the term dictionary.
+    pub termdict: ByteCount,
+    /// Size of the postings.
+    pub postings: ByteCount,
+    /// Size of the positions.
+    pub positions: ByteCount,
+    /// Size of the fast fields.
+    pub fast_fields: ByteCount,
+    /// Size of the field norms.
+    pub fieldnorms: ByteCount,
+    /// Size of the dense vectors.
+    pub vectors: ByteCount,
+    /// Size of the term vectors.
+    pub term_vectors: ByteCount,
+    /// Size of the doc store.
+    pub store: ByteCount,
+    /// Size of the alive bitset.
+    pub deletes: ByteCount,
+}
+
+impl SegmentStats {
+    /// Returns the total size of the components of the segment.
+    pub fn total(&self) -> ByteCount {
+        self.termdict
+            + self.postings
+            + self.positions
+            + self.fast_fields
+            + self.fieldnorms
+            + self.vectors
+            + self.term_vectors
+            + self.store
+            + self.deletes
+    }
+}
+
 /// Represents space usage for the Store for this segment.
 ///
 /// This is composed of two parts.
@@ -477,4 +546,59 @@ mod test {
         assert!(segment_space_usage.deletes() > 0);
         Ok(())
     }
+
+    #[test]
+    fn test_segment_stats() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..3u64 {
+                index_writer.add_document(doc!(id => i, text => "hello happy tax payer"))?;
+            }
+            index_writer.commit()?;
+            for i in 3..5u64 {
+                index_writer.add_document(doc!(id => i, text => "hello"))?;
+            }
+            index_writer.commit()?;
+            index_writer.delete_term(Term::from_field_u64(id, 1u64));
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let mut segment_stats = searcher.segment_stats();
+        segment_stats.sort_by_key(|stats| stats.num_docs + stats.num_deleted_docs);
+        assert_eq!(segment_stats.len(), 2);
+        assert_eq!(
+            (segment_stats[0].num_docs, segment_stats[0].num_deleted_docs),
+            (2, 0)
+        );
+        assert_eq!(
+            (segment_stats[1].num_docs, segment_stats[1].num_deleted_docs),
+            (2, 1)
+        );
+        assert_eq!(segment_stats[0].deletes, 0);
+        assert!(segment_stats[1].deletes > 0);
+
+        let searcher_space_usage = searcher.space_usage()?;
+        for stats in &segment_stats {
+            assert!(stats.store > 0);
+            assert!(stats.positions > 0);
+            let segment_ord = searcher
+                .segment_readers()
+                .iter()
+                .position(|segment_reader| segment_reader.segment_id() == stats.segment_id)
+                .unwrap();
+            let segment_space_usage = &searcher_space_usage.segments()[segment_ord];
+            assert_eq!(stats.termdict, segment_space_usage.termdict().total());
+            assert_eq!(stats.postings, segment_space_usage.postings().total());
+            assert_eq!(stats.positions, segment_space_usage.positions().total());
+            assert_eq!(stats.fast_fields, segment_space_usage.fast_fields().total());
+            assert_eq!(stats.fieldnorms, segment_space_usage.fieldnorms().total());
+            assert_eq!(stats.deletes, segment_space_usage.deletes());
+            assert!(stats.total() >= segment_space_usage.total());
+        }
+        Ok(())
+    }
 }
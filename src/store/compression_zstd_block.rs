@@ -1,6 +1,7 @@
 use std::io;
 
 use zstd::bulk::{compress_to_buffer, decompress_to_buffer};
+use zstd::dict::DecoderDictionary;
 use zstd::DEFAULT_COMPRESSION_LEVEL;
 
 #[inline]
@@ -29,6 +30,79 @@ pub fn compress(
 
 #[inline]
 pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    decompress_with(compressed, decompressed, decompress_to_buffer)
+}
+
+/// Trains a dictionary over samples, concatenated in `samples`.
+pub fn train_dictionary(
+    samples: &[u8],
+    sample_sizes: &[usize],
+    dictionary_size: usize,
+) -> io::Result<Vec<u8>> {
+    zstd::dict::from_continuous(samples, sample_sizes, dictionary_size)
+}
+
+/// Compresses blocks with a dictionary.
+pub struct DictionaryCompressor {
+    compressor: zstd::bulk::Compressor<'static>,
+}
+
+impl DictionaryCompressor {
+    pub fn new(dictionary: &[u8], compression_level: Option<i32>) -> io::Result<Self> {
+        let compressor = zstd::bulk::Compressor::with_dictionary(
+            compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+            dictionary,
+        )?;
+        Ok(DictionaryCompressor { compressor })
+    }
+
+    pub fn compress(&mut self, uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+        let count_size = std::mem::size_of::<u32>();
+        let max_size = zstd::zstd_safe::compress_bound(uncompressed.len()) + count_size;
+
+        compressed.clear();
+        compressed.resize(max_size, 0);
+
+        let compressed_size = self
+            .compressor
+            .compress_to_buffer(uncompressed, &mut compressed[count_size..])?;
+
+        compressed[0..count_size].copy_from_slice(&(uncompressed.len() as u32).to_le_bytes());
+        compressed.resize(compressed_size + count_size, 0);
+
+        Ok(())
+    }
+}
+
+/// Decompresses blocks with a dictionary.
+///
+/// The dictionary is digested once, when the `DictionaryDecompressor` is created.
+pub struct DictionaryDecompressor {
+    dictionary: DecoderDictionary<'static>,
+}
+
+impl DictionaryDecompressor {
+    pub fn new(dictionary: &[u8]) -> Self {
+        DictionaryDecompressor {
+            dictionary: DecoderDictionary::copy(dictionary),
+        }
+    }
+
+    pub fn decompress(&self, compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+        let mut decompressor =
+            zstd::bulk::Decompressor::with_prepared_dictionary(&self.dictionary)?;
+        decompress_with(compressed, decompressed, |compressed, decompressed| {
+            decompressor.decompress_to_buffer(compressed, decompressed)
+        })
+    }
+}
+
+#[inline]
+fn decompress_with(
+    compressed: &[u8],
+    decompressed: &mut Vec<u8>,
+    mut decompress_to_buffer: impl FnMut(&[u8], &mut [u8]) -> io::Result<usize>,
+) -> io::Result<()> {
     let count_size = std::mem::size_of::<u32>();
     let uncompressed_size = u32::from_le_bytes(
         compressed
@@ -41,7 +115,7 @@ pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<(
     decompressed.clear();
     decompressed.resize(uncompressed_size, 0);
 
-    let decompressed_size = decompress_to_buffer(&compressed[count_size..], decompressed)?;
+    let decompressed_size = decompress_to_buffer(&compressed[count_size..], &mut decompressed[..])?;
 
     if decompressed_size != uncompressed_size {
         return Err(io::Error::new(
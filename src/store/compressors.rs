@@ -74,13 +74,44 @@ impl<'de> Deserialize<'de> for Compressor {
 }
 
 #[derive(Clone, Default, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
-/// The Zstd compressor, with optional compression level.
+/// The Zstd compressor, with optional compression level and shared dictionary.
 pub struct ZstdCompressor {
     /// The compression level, if unset defaults to zstd::DEFAULT_COMPRESSION_LEVEL = 3
     pub compression_level: Option<i32>,
+    // Set with `ZstdCompressor::with_dictionary_size`.
+    #[serde(default)]
+    dictionary_size: Option<usize>,
 }
 
 impl ZstdCompressor {
+    /// Creates a zstd compressor with the given compression level, and without dictionary.
+    pub fn new(compression_level: Option<i32>) -> ZstdCompressor {
+        ZstdCompressor {
+            compression_level,
+            dictionary_size: None,
+        }
+    }
+
+    /// Sets the maximum size of the dictionary shared by all of the blocks of a doc store.
+    ///
+    /// A dictionary is then trained on the first documents of each segment, up to 100 times
+    /// the dictionary size and at most 16MB of documents, and is used to compress all of the
+    /// blocks of the segment doc store.
+    /// This significantly improves the compression ratio of small and similar documents.
+    ///
+    /// By default, no dictionary is used.
+    #[must_use]
+    pub fn with_dictionary_size(mut self, dictionary_size: usize) -> ZstdCompressor {
+        self.dictionary_size = Some(dictionary_size);
+        self
+    }
+
+    /// Returns the maximum size of the dictionary shared by all of the blocks of a doc store,
+    /// or `None` if no dictionary is used.
+    pub fn dictionary_size(&self) -> Option<usize> {
+        self.dictionary_size
+    }
+
     fn deser_from_str(val: &str) -> Result<ZstdCompressor, String> {
         if !val.starts_with("zstd") {
             return Err(format!("needs to start with zstd, but got {}", val));
@@ -92,7 +123,7 @@ impl ZstdCompressor {
 
         let mut compressor = ZstdCompressor::default();
         for option in options.split(',') {
-            let (opt_name, value) = options
+            let (opt_name, value) = option
                 .split_once('=')
                 .ok_or_else(|| format!("no '=' found in option {:?}", option))?;
 
@@ -106,6 +137,15 @@ impl ZstdCompressor {
                     })?;
                     compressor.compression_level = Some(value);
                 }
+                "dictionary_size" => {
+                    let value = value.parse::<usize>().map_err(|err| {
+                        format!(
+                            "Could not parse value {} of option {}, e: {}",
+                            value, opt_name, err
+                        )
+                    })?;
+                    compressor.dictionary_size = Some(value);
+                }
                 _ => {
                     return Err(format!("unknown zstd option {:?}", opt_name));
                 }
@@ -114,10 +154,17 @@ impl ZstdCompressor {
         Ok(compressor)
     }
     fn ser_to_string(&self) -> String {
+        let mut options = Vec::new();
         if let Some(compression_level) = self.compression_level {
-            format!("zstd(compression_level={})", compression_level)
-        } else {
+            options.push(format!("compression_level={}", compression_level));
+        }
+        if let Some(dictionary_size) = self.dictionary_size {
+            options.push(format!("dictionary_size={}", dictionary_size));
+        }
+        if options.is_empty() {
             "zstd".to_string()
+        } else {
+            format!("zstd({})", options.join(","))
         }
     }
 }
@@ -139,6 +186,22 @@ impl Default for Compressor {
 }
 
 impl Compressor {
+    /// Returns the maximum size of the dictionary shared by all of the blocks of a doc store,
+    /// if the compressor uses one.
+    pub(crate) fn dictionary_size(&self) -> Option<usize> {
+        match self {
+            Self::Zstd(zstd_compressor) => zstd_compressor.dictionary_size,
+            _ => None,
+        }
+    }
+
+    pub(crate) fn compression_level(&self) -> Option<i32> {
+        match self {
+            Self::Zstd(zstd_compressor) => zstd_compressor.compression_level,
+            _ => None,
+        }
+    }
+
     #[inline]
     pub(crate) fn compress_into(
         &self,
@@ -207,6 +270,7 @@ mod tests {
     fn zstd_serde_roundtrip() {
         let compressor = ZstdCompressor {
             compression_level: Some(15),
+            dictionary_size: None,
         };
 
         assert_eq!(
@@ -218,6 +282,15 @@ mod tests {
             ZstdCompressor::deser_from_str(&ZstdCompressor::default().ser_to_string()).unwrap(),
             ZstdCompressor::default()
         );
+
+        let compressor = ZstdCompressor {
+            compression_level: Some(15),
+            dictionary_size: Some(16_384),
+        };
+        assert_eq!(
+            ZstdCompressor::deser_from_str(&compressor.ser_to_string()).unwrap(),
+            compressor
+        );
     }
 
     #[test]
@@ -232,7 +305,23 @@ mod tests {
         assert_eq!(
             ZstdCompressor::deser_from_str("zstd(compression_level=15)").unwrap(),
             ZstdCompressor {
-                compression_level: Some(15)
+                compression_level: Some(15),
+                dictionary_size: None,
+            }
+        );
+        assert_eq!(
+            ZstdCompressor::deser_from_str("zstd(compression_level=15,dictionary_size=16384)")
+                .unwrap(),
+            ZstdCompressor {
+                compression_level: Some(15),
+                dictionary_size: Some(16_384),
+            }
+        );
+        assert_eq!(
+            ZstdCompressor::deser_from_str("zstd(dictionary_size=16384)").unwrap(),
+            ZstdCompressor {
+                compression_level: None,
+                dictionary_size: Some(16_384),
             }
         );
         assert_eq!(
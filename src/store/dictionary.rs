@@ -0,0 +1,114 @@
+//! Compression dictionary shared by all of the blocks of a doc store.
+//!
+//! Small documents compress poorly, as each block is compressed independently and
+//! lacks context. A dictionary trained on a sample of the documents of a segment provides
+//! that context to all of the blocks of the segment doc store.
+//!
+//! Dictionaries are only supported by the zstd compressor.
+
+use std::io;
+
+#[cfg(not(feature = "zstd-compression"))]
+fn zstd_feature_not_activated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "zstd-compression feature flag not activated",
+    )
+}
+
+/// Trains a dictionary of at most `dictionary_size` bytes over samples, concatenated in
+/// `samples`.
+pub(crate) fn train_dictionary(
+    samples: &[u8],
+    sample_sizes: &[usize],
+    dictionary_size: usize,
+) -> io::Result<Vec<u8>> {
+    #[cfg(feature = "zstd-compression")]
+    {
+        super::compression_zstd_block::train_dictionary(samples, sample_sizes, dictionary_size)
+    }
+    #[cfg(not(feature = "zstd-compression"))]
+    {
+        let _ = (samples, sample_sizes, dictionary_size);
+        Err(zstd_feature_not_activated())
+    }
+}
+
+/// Compresses blocks with a dictionary.
+pub(crate) struct DictionaryCompressor {
+    #[cfg(feature = "zstd-compression")]
+    compressor: super::compression_zstd_block::DictionaryCompressor,
+}
+
+impl DictionaryCompressor {
+    pub fn new(dictionary: &[u8], compression_level: Option<i32>) -> io::Result<Self> {
+        #[cfg(feature = "zstd-compression")]
+        {
+            let compressor = super::compression_zstd_block::DictionaryCompressor::new(
+                dictionary,
+                compression_level,
+            )?;
+            Ok(DictionaryCompressor { compressor })
+        }
+        #[cfg(not(feature = "zstd-compression"))]
+        {
+            let _ = (dictionary, compression_level);
+            Err(zstd_feature_not_activated())
+        }
+    }
+
+    pub fn compress_into(
+        &mut self,
+        uncompressed: &[u8],
+        compressed: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        #[cfg(feature = "zstd-compression")]
+        {
+            self.compressor.compress(uncompressed, compressed)
+        }
+        #[cfg(not(feature = "zstd-compression"))]
+        {
+            let _ = (uncompressed, compressed);
+            Err(zstd_feature_not_activated())
+        }
+    }
+}
+
+/// Decompresses blocks with a dictionary.
+///
+/// The dictionary is loaded once, when the `DictionaryDecompressor` is created.
+pub(crate) struct DictionaryDecompressor {
+    #[cfg(feature = "zstd-compression")]
+    decompressor: super::compression_zstd_block::DictionaryDecompressor,
+}
+
+impl DictionaryDecompressor {
+    pub fn new(dictionary: &[u8]) -> io::Result<Self> {
+        #[cfg(feature = "zstd-compression")]
+        {
+            let decompressor =
+                super::compression_zstd_block::DictionaryDecompressor::new(dictionary);
+            Ok(DictionaryDecompressor { decompressor })
+        }
+        #[cfg(not(feature = "zstd-compression"))]
+        {
+            let _ = dictionary;
+            Err(zstd_feature_not_activated())
+        }
+    }
+
+    pub fn decompress(&self, compressed_block: &[u8]) -> io::Result<Vec<u8>> {
+        #[cfg(feature = "zstd-compression")]
+        {
+            let mut decompressed_block = vec![];
+            self.decompressor
+                .decompress(compressed_block, &mut decompressed_block)?;
+            Ok(decompressed_block)
+        }
+        #[cfg(not(feature = "zstd-compression"))]
+        {
+            let _ = compressed_block;
+            Err(zstd_feature_not_activated())
+        }
+    }
+}
@@ -9,28 +9,35 @@ use crate::directory::FileSlice;
 pub struct DocStoreFooter {
     pub offset: u64,
     pub decompressor: Decompressor,
+    /// Length of the compression dictionary shared by all of the blocks, or 0 if the
+    /// blocks are compressed without dictionary.
+    pub dictionary_len: u32,
 }
 
 /// Serialises the footer to a byte-array
 /// - offset : 8 bytes
 /// - compressor id: 1 byte
-/// - reserved for future use: 15 bytes
+/// - dictionary length: 4 bytes
+/// - reserved for future use: 11 bytes
 impl BinarySerializable for DocStoreFooter {
     fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         BinarySerializable::serialize(&self.offset, writer)?;
         BinarySerializable::serialize(&self.decompressor.get_id(), writer)?;
-        writer.write_all(&[0; 15])?;
+        BinarySerializable::serialize(&self.dictionary_len, writer)?;
+        writer.write_all(&[0; 11])?;
         Ok(())
     }
 
     fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<Self> {
         let offset = u64::deserialize(reader)?;
         let compressor_id = u8::deserialize(reader)?;
-        let mut skip_buf = [0; 15];
+        let dictionary_len = u32::deserialize(reader)?;
+        let mut skip_buf = [0; 11];
         reader.read_exact(&mut skip_buf)?;
         Ok(DocStoreFooter {
             offset,
             decompressor: Decompressor::from_id(compressor_id),
+            dictionary_len,
         })
     }
 }
@@ -40,10 +47,11 @@ impl FixedSize for DocStoreFooter {
 }
 
 impl DocStoreFooter {
-    pub fn new(offset: u64, decompressor: Decompressor) -> Self {
+    pub fn new(offset: u64, decompressor: Decompressor, dictionary_len: u32) -> Self {
         DocStoreFooter {
             offset,
             decompressor,
+            dictionary_len,
         }
     }
 
@@ -71,3 +79,15 @@ fn doc_store_footer_test() {
     // methods
     assert_eq!(core::mem::size_of::<DocStoreFooter>(), 16);
 }
+
+#[test]
+fn doc_store_footer_serialization_test() {
+    let footer = DocStoreFooter::new(12, Decompressor::Lz4, 3);
+    let mut buffer = Vec::new();
+    footer.serialize(&mut buffer).unwrap();
+    assert_eq!(buffer.len(), DocStoreFooter::SIZE_IN_BYTES);
+    assert_eq!(
+        DocStoreFooter::deserialize(&mut &buffer[..]).unwrap(),
+        footer
+    );
+}
@@ -7,6 +7,10 @@
 //! When the buffer exceeds 16K, the buffer is compressed using `brotli`, `LZ4` or `snappy`
 //! and the resulting block is written to disk.
 //!
+//! With the `zstd` compressor, the blocks of a segment can optionally be compressed with a
+//! shared dictionary (see [`ZstdCompressor::with_dictionary_size`]), trained on the first documents
+//! of the segment and stored alongside the blocks.
+//!
 //! One can then request for a specific `DocId`.
 //! A skip list helps navigating to the right block,
 //! decompresses it entirely and returns the document within it.
@@ -34,6 +38,7 @@
 
 mod compressors;
 mod decompressors;
+mod dictionary;
 mod footer;
 mod index;
 mod reader;
@@ -221,6 +226,115 @@ pub mod tests {
         )
     }
 
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_store_zstd_dictionary() -> crate::Result<()> {
+        let compressor = Compressor::Zstd(ZstdCompressor::new(None).with_dictionary_size(1_024));
+        // The dictionary is trained on the first blocks, or when closing the store writer.
+        test_store(compressor, 1_000, true)?;
+        test_store(compressor, 1_000, false)?;
+        test_store(compressor, BLOCK_SIZE, true)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_store_zstd_dictionary_compresses_small_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let event_field = schema_builder.add_text_field("event", STORED);
+        let events: Vec<String> = (0..5_000)
+            .map(|i| {
+                format!(
+                    r#"{{"user":"user-{}","action":"{}","page":"/products/{}","status":"ok"}}"#,
+                    i % 97,
+                    ["click", "view", "purchase"][i % 3],
+                    i % 251
+                )
+            })
+            .collect();
+        let directory = RamDirectory::create();
+        let write_store = |path: &Path, dictionary_size: Option<usize>| -> crate::Result<usize> {
+            let mut zstd_compressor = ZstdCompressor::new(None);
+            if let Some(dictionary_size) = dictionary_size {
+                zstd_compressor = zstd_compressor.with_dictionary_size(dictionary_size);
+            }
+            let compressor = Compressor::Zstd(zstd_compressor);
+            let mut store_writer =
+                StoreWriter::new(directory.open_write(path)?, compressor, 512, false)?;
+            for event in &events {
+                store_writer.store(&doc!(event_field => event.as_str()))?;
+            }
+            store_writer.close()?;
+            let store_file = directory.open_read(path)?;
+            let store_num_bytes = common::HasLen::len(&store_file);
+            let store = StoreReader::open(store_file, 10)?;
+            assert_eq!(store.has_dictionary(), dictionary_size.is_some());
+            for (doc, event) in store.iter(None).zip(&events) {
+                assert_eq!(
+                    doc?.get_first(event_field).unwrap().as_text(),
+                    Some(&event[..])
+                );
+            }
+            Ok(store_num_bytes)
+        };
+        let without_dictionary_num_bytes = write_store(Path::new("without_dictionary"), None)?;
+        let with_dictionary_num_bytes = write_store(Path::new("with_dictionary"), Some(4_096))?;
+        // The dictionary itself is included in the size of the store.
+        assert!(
+            with_dictionary_num_bytes * 4 < without_dictionary_num_bytes * 3,
+            "with dictionary: {}, without dictionary: {}",
+            with_dictionary_num_bytes,
+            without_dictionary_num_bytes
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd-compression")]
+    #[test]
+    fn test_merge_with_zstd_dictionary() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text_field", TEXT | STORED);
+        let schema = schema_builder.build();
+        let mut index = Index::builder().schema(schema).create_in_ram()?;
+        index.settings_mut().docstore_compression =
+            Compressor::Zstd(ZstdCompressor::new(None).with_dictionary_size(1_024));
+        index.settings_mut().docstore_blocksize = 1_000;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0..400 {
+                index_writer.add_document(doc!(text_field=> format!("{} {}", i, LOREM)))?;
+                if i % 200 == 199 {
+                    index_writer.commit()?;
+                }
+            }
+            let segment_ids = index.searchable_segment_ids()?;
+            assert_eq!(segment_ids.len(), 2);
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let store = searcher.segment_readers()[0].get_store_reader(10)?;
+        assert!(store.has_dictionary());
+        let mut texts: Vec<String> = store
+            .iter(None)
+            .map(|doc| {
+                doc.map(|doc| {
+                    doc.get_first(text_field)
+                        .unwrap()
+                        .as_text()
+                        .unwrap()
+                        .to_string()
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        texts.sort_by_key(|text| text.split(' ').next().unwrap().parse::<u32>().unwrap());
+        for (i, text) in texts.iter().enumerate() {
+            assert_eq!(text, &format!("{} {}", i, LOREM));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_store_with_delete() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -8,6 +8,7 @@ use common::{BinarySerializable, HasLen, VInt};
 use lru::LruCache;
 use ownedbytes::OwnedBytes;
 
+use super::dictionary::DictionaryDecompressor;
 use super::footer::DocStoreFooter;
 use super::index::SkipIndex;
 use super::Decompressor;
@@ -26,6 +27,7 @@ type Block = OwnedBytes;
 /// Reads document off tantivy's [`Store`](./index.html)
 pub struct StoreReader {
     decompressor: Decompressor,
+    dictionary_decompressor: Option<DictionaryDecompressor>,
     data: FileSlice,
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
@@ -108,14 +110,23 @@ impl Sum for CacheStats {
 impl StoreReader {
     /// Opens a store reader
     pub fn open(store_file: FileSlice, cache_size: usize) -> io::Result<StoreReader> {
-        let (footer, data_and_offset) = DocStoreFooter::extract_footer(store_file)?;
-
+        let (footer, data_offset_and_dictionary) = DocStoreFooter::extract_footer(store_file)?;
+
+        let (data_and_offset, dictionary_file) =
+            data_offset_and_dictionary.split_from_end(footer.dictionary_len as usize);
+        let dictionary_decompressor = if footer.dictionary_len > 0 {
+            let dictionary = dictionary_file.read_bytes()?;
+            Some(DictionaryDecompressor::new(dictionary.as_slice())?)
+        } else {
+            None
+        };
         let (data_file, offset_index_file) = data_and_offset.split(footer.offset as usize);
         let index_data = offset_index_file.read_bytes()?;
         let space_usage = StoreSpaceUsage::new(data_file.len(), offset_index_file.len());
         let skip_index = SkipIndex::open(index_data);
         Ok(StoreReader {
             decompressor: footer.decompressor,
+            dictionary_decompressor,
             data: data_file,
             cache: BlockCache {
                 cache: Mutex::new(LruCache::new(cache_size)),
@@ -135,6 +146,20 @@ impl StoreReader {
         self.decompressor
     }
 
+    /// Returns true if the blocks are compressed with a dictionary.
+    pub(crate) fn has_dictionary(&self) -> bool {
+        self.dictionary_decompressor.is_some()
+    }
+
+    /// Decompresses a block, with the dictionary if any.
+    fn decompress_block(&self, compressed_block: &[u8]) -> io::Result<Vec<u8>> {
+        if let Some(dictionary_decompressor) = self.dictionary_decompressor.as_ref() {
+            dictionary_decompressor.decompress(compressed_block)
+        } else {
+            self.decompressor.decompress(compressed_block)
+        }
+    }
+
     /// Returns the cache hit and miss statistics of the store reader.
     pub(crate) fn cache_stats(&self) -> CacheStats {
         self.cache.stats()
@@ -168,8 +193,7 @@ impl StoreReader {
         }
 
         let compressed_block = self.get_compressed_block(checkpoint)?;
-        let decompressed_block =
-            OwnedBytes::new(self.decompressor.decompress(compressed_block.as_ref())?);
+        let decompressed_block = OwnedBytes::new(self.decompress_block(compressed_block.as_ref())?);
 
         self.cache
             .put_into_cache(cache_key, decompressed_block.clone());
@@ -237,6 +261,23 @@ impl StoreReader {
         })
     }
 
+    /// Iterator over all Documents and their `DocId`, in their order as they are stored in the
+    /// doc store.
+    ///
+    /// Each block of the doc store is decompressed only once, which makes this much cheaper
+    /// than calling [`StoreReader::get`] for every document when exporting a segment.
+    /// The alive_bitset has to be forwarded from the `SegmentReader` or the results maybe wrong.
+    pub fn iter_with_doc_ids<'a: 'b, 'b>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = crate::Result<(DocId, Document)>> + 'b {
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|doc_bytes_res| {
+                let (doc_id, mut doc_bytes) = doc_bytes_res?;
+                Ok((doc_id, Document::deserialize(&mut doc_bytes)?))
+            })
+    }
+
     /// Iterator over all RawDocuments in their order as they are stored in the doc store.
     /// Use this, if you want to extract all Documents from the doc store.
     /// The alive_bitset has to be forwarded from the `SegmentReader` or the results maybe wrong.
@@ -244,6 +285,14 @@ impl StoreReader {
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<OwnedBytes>> + 'b {
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|doc_bytes_res| doc_bytes_res.map(|(_, doc_bytes)| doc_bytes))
+    }
+
+    fn iter_raw_with_doc_ids<'a: 'b, 'b>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = crate::Result<(DocId, OwnedBytes)>> + 'b {
         let last_doc_id = self
             .block_checkpoints()
             .last()
@@ -274,7 +323,7 @@ impl StoreReader {
 
                 let alive = alive_bitset.map_or(true, |bitset| bitset.is_alive(doc_id));
                 if alive {
-                    let ret = Some((curr_block.clone(), num_skipped, reset_block_pos));
+                    let ret = Some((doc_id, curr_block.clone(), num_skipped, reset_block_pos));
                     // the map block will move over the num_skipped, so we reset to 0
                     num_skipped = 0;
                     reset_block_pos = false;
@@ -285,7 +334,7 @@ impl StoreReader {
                     None
                 }
             })
-            .map(move |(block, num_skipped, reset_block_pos)| {
+            .map(move |(doc_id, block, num_skipped, reset_block_pos)| {
                 let block = block
                     .ok_or_else(|| {
                         DataCorruption::comment_only(
@@ -319,7 +368,7 @@ impl StoreReader {
                 let end_pos = block_start_pos + doc_length;
                 let doc_bytes = block.slice(block_start_pos..end_pos);
                 block_start_pos = end_pos;
-                Ok(doc_bytes)
+                Ok((doc_id, doc_bytes))
             })
     }
 
@@ -348,8 +397,7 @@ impl StoreReader {
             .read_bytes_async()
             .await?;
 
-        let decompressed_block =
-            OwnedBytes::new(self.decompressor.decompress(compressed_block.as_ref())?);
+        let decompressed_block = OwnedBytes::new(self.decompress_block(compressed_block.as_ref())?);
 
         self.cache
             .put_into_cache(cache_key, decompressed_block.clone());
@@ -3,14 +3,23 @@ use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::thread::JoinHandle;
 use std::{io, thread};
 
-use common::{BinarySerializable, CountingWriter, TerminatingWrite};
+use common::{BinarySerializable, CountingWriter, TerminatingWrite, VInt};
 
 use crate::directory::WritePtr;
+use crate::store::dictionary::{train_dictionary, DictionaryCompressor};
 use crate::store::footer::DocStoreFooter;
 use crate::store::index::{Checkpoint, SkipIndexBuilder};
 use crate::store::{Compressor, Decompressor, StoreReader};
 use crate::DocId;
 
+/// Number of bytes of documents sampled to train the compression dictionary,
+/// relatively to the dictionary size.
+const DICTIONARY_SAMPLE_SIZE_RATIO: usize = 100;
+
+/// Upper bound on the number of bytes of documents buffered to train the compression
+/// dictionary, whatever the dictionary size.
+const MAX_DICTIONARY_SAMPLE_NUM_BYTES: usize = 16_000_000;
+
 pub struct BlockCompressor(BlockCompressorVariants);
 
 // The struct wrapping an enum is just here to keep the
@@ -76,8 +85,51 @@ impl BlockCompressor {
     }
 }
 
+/// Blocks buffered until enough documents have been sampled to train the
+/// compression dictionary.
+struct DictionaryTraining {
+    dictionary_size: usize,
+    pending_blocks: Vec<(Vec<u8>, u32)>,
+    num_pending_bytes: usize,
+}
+
+impl DictionaryTraining {
+    /// Number of bytes of documents to sample before training the dictionary.
+    fn sample_num_bytes(&self) -> usize {
+        self.dictionary_size
+            .saturating_mul(DICTIONARY_SAMPLE_SIZE_RATIO)
+            .min(MAX_DICTIONARY_SAMPLE_NUM_BYTES)
+    }
+
+    fn is_sample_complete(&self) -> bool {
+        self.num_pending_bytes >= self.sample_num_bytes()
+    }
+
+    /// Trains the dictionary, using each of the documents of the pending blocks as a sample.
+    fn train(&self) -> io::Result<Vec<u8>> {
+        let mut samples = Vec::with_capacity(self.num_pending_bytes);
+        let mut sample_sizes = Vec::new();
+        for (block, _) in &self.pending_blocks {
+            let mut cursor = &block[..];
+            while !cursor.is_empty() {
+                let num_remaining_bytes = cursor.len();
+                let doc_num_bytes = VInt::deserialize(&mut cursor)?.val() as usize;
+                cursor = cursor
+                    .get(doc_num_bytes..)
+                    .ok_or(io::ErrorKind::InvalidData)?;
+                sample_sizes.push(num_remaining_bytes - cursor.len());
+            }
+            samples.extend_from_slice(block);
+        }
+        train_dictionary(&samples, &sample_sizes, self.dictionary_size)
+    }
+}
+
 struct BlockCompressorImpl {
     compressor: Compressor,
+    // Set as long as the dictionary has not been trained.
+    dictionary_training: Option<DictionaryTraining>,
+    dictionary_opt: Option<(Vec<u8>, DictionaryCompressor)>,
     first_doc_in_block: DocId,
     offset_index_writer: SkipIndexBuilder,
     intermediary_buffer: Vec<u8>,
@@ -86,8 +138,18 @@ struct BlockCompressorImpl {
 
 impl BlockCompressorImpl {
     fn new(compressor: Compressor, writer: WritePtr) -> Self {
+        let dictionary_training =
+            compressor
+                .dictionary_size()
+                .map(|dictionary_size| DictionaryTraining {
+                    dictionary_size,
+                    pending_blocks: Vec::new(),
+                    num_pending_bytes: 0,
+                });
         Self {
             compressor,
+            dictionary_training,
+            dictionary_opt: None,
             first_doc_in_block: 0,
             offset_index_writer: SkipIndexBuilder::new(),
             intermediary_buffer: Vec::new(),
@@ -97,9 +159,55 @@ impl BlockCompressorImpl {
 
     fn compress_block_and_write(&mut self, data: &[u8], num_docs_in_block: u32) -> io::Result<()> {
         assert!(num_docs_in_block > 0);
+        if let Some(dictionary_training) = self.dictionary_training.as_mut() {
+            dictionary_training
+                .pending_blocks
+                .push((data.to_vec(), num_docs_in_block));
+            dictionary_training.num_pending_bytes += data.len();
+            if dictionary_training.is_sample_complete() {
+                self.train_dictionary_and_flush()?;
+            }
+            return Ok(());
+        }
+        self.write_block(data, num_docs_in_block)
+    }
+
+    /// Trains the dictionary on the pending blocks, and writes them.
+    ///
+    /// If the training fails, typically because there are not enough samples,
+    /// blocks are compressed without dictionary.
+    fn train_dictionary_and_flush(&mut self) -> io::Result<()> {
+        let dictionary_training = match self.dictionary_training.take() {
+            Some(dictionary_training) => dictionary_training,
+            None => return Ok(()),
+        };
+        match dictionary_training.train() {
+            Ok(dictionary) => {
+                let dictionary_compressor =
+                    DictionaryCompressor::new(&dictionary, self.compressor.compression_level())?;
+                self.dictionary_opt = Some((dictionary, dictionary_compressor));
+            }
+            Err(err) => {
+                debug!(
+                    "Failed to train doc store dictionary, compressing without dictionary: {}",
+                    err
+                );
+            }
+        }
+        for (block, num_docs_in_block) in dictionary_training.pending_blocks {
+            self.write_block(&block, num_docs_in_block)?;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self, data: &[u8], num_docs_in_block: u32) -> io::Result<()> {
         self.intermediary_buffer.clear();
-        self.compressor
-            .compress_into(data, &mut self.intermediary_buffer)?;
+        if let Some((_, dictionary_compressor)) = self.dictionary_opt.as_mut() {
+            dictionary_compressor.compress_into(data, &mut self.intermediary_buffer)?;
+        } else {
+            self.compressor
+                .compress_into(data, &mut self.intermediary_buffer)?;
+        }
 
         let start_offset = self.writer.written_bytes() as usize;
         self.writer.write_all(&self.intermediary_buffer)?;
@@ -122,6 +230,12 @@ impl BlockCompressorImpl {
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
     fn stack(&mut self, store_reader: StoreReader) -> io::Result<()> {
+        if self.compressor.dictionary_size().is_some() || store_reader.has_dictionary() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Doc stores compressed with a dictionary cannot be stacked.",
+            ));
+        }
         let doc_shift = self.first_doc_in_block;
         let start_shift = self.writer.written_bytes() as usize;
 
@@ -142,10 +256,20 @@ impl BlockCompressorImpl {
     }
 
     fn close(mut self) -> io::Result<()> {
+        self.train_dictionary_and_flush()?;
         let header_offset: u64 = self.writer.written_bytes() as u64;
-        let docstore_footer =
-            DocStoreFooter::new(header_offset, Decompressor::from(self.compressor));
         self.offset_index_writer.serialize_into(&mut self.writer)?;
+        let dictionary_len = if let Some((dictionary, _)) = self.dictionary_opt.as_ref() {
+            self.writer.write_all(dictionary)?;
+            dictionary.len() as u32
+        } else {
+            0
+        };
+        let docstore_footer = DocStoreFooter::new(
+            header_offset,
+            Decompressor::from(self.compressor),
+            dictionary_len,
+        );
         docstore_footer.serialize(&mut self.writer)?;
         self.writer.terminate()
     }
@@ -240,7 +364,9 @@ mod tests {
     use std::path::Path;
 
     use crate::directory::RamDirectory;
-    use crate::store::store_compressor::BlockCompressor;
+    use crate::store::store_compressor::{
+        BlockCompressor, DictionaryTraining, MAX_DICTIONARY_SAMPLE_NUM_BYTES,
+    };
     use crate::store::Compressor;
     use crate::Directory;
 
@@ -266,4 +392,22 @@ mod tests {
         let data2 = ram_directory.open_read(path2).unwrap();
         assert_eq!(data1.read_bytes().unwrap(), data2.read_bytes().unwrap());
     }
+
+    #[test]
+    fn test_dictionary_sample_is_bounded() {
+        let dictionary_training = |dictionary_size: usize| DictionaryTraining {
+            dictionary_size,
+            pending_blocks: Vec::new(),
+            num_pending_bytes: 0,
+        };
+        assert_eq!(dictionary_training(1_024).sample_num_bytes(), 102_400);
+        assert_eq!(
+            dictionary_training(1_000_000).sample_num_bytes(),
+            MAX_DICTIONARY_SAMPLE_NUM_BYTES
+        );
+        assert_eq!(
+            dictionary_training(usize::MAX).sample_num_bytes(),
+            MAX_DICTIONARY_SAMPLE_NUM_BYTES
+        );
+    }
 }
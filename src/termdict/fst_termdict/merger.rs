@@ -111,6 +111,8 @@ mod bench {
             doc_freq: term_ord as u32,
             postings_range: offset(term_ord)..offset(term_ord + 1),
             positions_range: offset(term_ord)..offset(term_ord + 1),
+            max_term_freq: 1,
+            min_fieldnorm_id: 0,
         }
     }
 
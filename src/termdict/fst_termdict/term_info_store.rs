@@ -11,6 +11,10 @@ use crate::termdict::TermOrdinal;
 
 const BLOCK_LEN: usize = 256;
 
+/// Flag set on the length of the block metas of the stores holding the `max_term_freq` and
+/// `min_fieldnorm_id` of the terms. The stores written before tantivy 0.19 do not have them.
+const MAX_IMPACTS_FLAG: u64 = 1 << 63;
+
 #[derive(Debug, Eq, PartialEq, Default)]
 struct TermInfoBlockMeta {
     offset: u64,
@@ -18,6 +22,8 @@ struct TermInfoBlockMeta {
     doc_freq_nbits: u8,
     postings_offset_nbits: u8,
     positions_offset_nbits: u8,
+    max_term_freq_nbits: u8,
+    min_fieldnorm_id_nbits: u8,
 }
 
 impl BinarySerializable for TermInfoBlockMeta {
@@ -28,6 +34,8 @@ impl BinarySerializable for TermInfoBlockMeta {
             self.doc_freq_nbits,
             self.postings_offset_nbits,
             self.positions_offset_nbits,
+            self.max_term_freq_nbits,
+            self.min_fieldnorm_id_nbits,
         ])?;
         Ok(())
     }
@@ -35,7 +43,7 @@ impl BinarySerializable for TermInfoBlockMeta {
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
         let offset = u64::deserialize(reader)?;
         let ref_term_info = TermInfo::deserialize(reader)?;
-        let mut buffer = [0u8; 3];
+        let mut buffer = [0u8; 5];
         reader.read_exact(&mut buffer)?;
         Ok(TermInfoBlockMeta {
             offset,
@@ -43,18 +51,45 @@ impl BinarySerializable for TermInfoBlockMeta {
             doc_freq_nbits: buffer[0],
             postings_offset_nbits: buffer[1],
             positions_offset_nbits: buffer[2],
+            max_term_freq_nbits: buffer[3],
+            min_fieldnorm_id_nbits: buffer[4],
         })
     }
 }
 
 impl FixedSize for TermInfoBlockMeta {
-    const SIZE_IN_BYTES: usize =
-        u64::SIZE_IN_BYTES + TermInfo::SIZE_IN_BYTES + 3 * u8::SIZE_IN_BYTES;
+    const SIZE_IN_BYTES: usize = u64::SIZE_IN_BYTES + TermInfo::SIZE_IN_BYTES + 5;
 }
 
 impl TermInfoBlockMeta {
+    /// Size of a block meta serialized without the max impacts of the terms.
+    const SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES: usize =
+        u64::SIZE_IN_BYTES + TermInfo::SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES + 3;
+
+    /// Deserializes a block meta serialized without the max impacts of the terms. Their
+    /// number of bits is 0, so that they are read as 0.
+    fn deserialize_without_max_impacts<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let offset = u64::deserialize(reader)?;
+        let ref_term_info = TermInfo::deserialize_without_max_impacts(reader)?;
+        let mut buffer = [0u8; 3];
+        reader.read_exact(&mut buffer)?;
+        Ok(TermInfoBlockMeta {
+            offset,
+            ref_term_info,
+            doc_freq_nbits: buffer[0],
+            postings_offset_nbits: buffer[1],
+            positions_offset_nbits: buffer[2],
+            max_term_freq_nbits: 0,
+            min_fieldnorm_id_nbits: 0,
+        })
+    }
+
     fn num_bits(&self) -> u8 {
-        self.doc_freq_nbits + self.postings_offset_nbits + self.positions_offset_nbits
+        self.doc_freq_nbits
+            + self.postings_offset_nbits
+            + self.positions_offset_nbits
+            + self.max_term_freq_nbits
+            + self.min_fieldnorm_id_nbits
     }
 
     // Here inner_offset is the offset within the block, WITHOUT the first term_info.
@@ -85,10 +120,20 @@ impl TermInfoBlockMeta {
 
         let doc_freq = extract_bits(data, doc_freq_addr, self.doc_freq_nbits) as u32;
 
+        let max_term_freq_addr = doc_freq_addr + self.doc_freq_nbits as usize;
+        let max_term_freq =
+            extract_bits(data, max_term_freq_addr, self.max_term_freq_nbits) as u32;
+
+        let min_fieldnorm_id_addr = max_term_freq_addr + self.max_term_freq_nbits as usize;
+        let min_fieldnorm_id =
+            extract_bits(data, min_fieldnorm_id_addr, self.min_fieldnorm_id_nbits) as u8;
+
         TermInfo {
             doc_freq,
             postings_range: postings_start_offset..postings_end_offset,
             positions_range: positions_start_offset..positions_end_offset,
+            max_term_freq,
+            min_fieldnorm_id,
         }
     }
 }
@@ -97,6 +142,7 @@ pub struct TermInfoStore {
     num_terms: usize,
     block_meta_bytes: OwnedBytes,
     term_info_bytes: OwnedBytes,
+    has_max_impacts: bool,
 }
 
 fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
@@ -124,7 +170,9 @@ impl TermInfoStore {
     pub fn open(term_info_store_file: FileSlice) -> crate::Result<TermInfoStore> {
         let (len_slice, main_slice) = term_info_store_file.split(16);
         let mut bytes = len_slice.read_bytes()?;
-        let len = u64::deserialize(&mut bytes)? as usize;
+        let len_with_flag = u64::deserialize(&mut bytes)?;
+        let has_max_impacts = len_with_flag & MAX_IMPACTS_FLAG != 0;
+        let len = (len_with_flag & !MAX_IMPACTS_FLAG) as usize;
         let num_terms = u64::deserialize(&mut bytes)? as usize;
         let (block_meta_file, term_info_file) = main_slice.split(len);
         let term_info_bytes = term_info_file.read_bytes()?;
@@ -132,15 +180,22 @@ impl TermInfoStore {
             num_terms,
             block_meta_bytes: block_meta_file.read_bytes()?,
             term_info_bytes,
+            has_max_impacts,
         })
     }
 
     pub fn get(&self, term_ord: TermOrdinal) -> TermInfo {
         let block_id = (term_ord as usize) / BLOCK_LEN;
         let buffer = self.block_meta_bytes.as_slice();
-        let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
-        let term_info_block_data = TermInfoBlockMeta::deserialize(&mut block_data)
-            .expect("Failed to deserialize terminfoblockmeta");
+        let term_info_block_data = if self.has_max_impacts {
+            let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
+            TermInfoBlockMeta::deserialize(&mut block_data)
+        } else {
+            let mut block_data: &[u8] =
+                &buffer[block_id * TermInfoBlockMeta::SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES..];
+            TermInfoBlockMeta::deserialize_without_max_impacts(&mut block_data)
+        }
+        .expect("Failed to deserialize terminfoblockmeta");
         let inner_offset = (term_ord as usize) % BLOCK_LEN;
         if inner_offset == 0 {
             return term_info_block_data.ref_term_info;
@@ -185,6 +240,16 @@ fn bitpack_serialize<W: Write>(
         term_info_block_meta.doc_freq_nbits,
         write,
     )?;
+    bit_packer.write(
+        u64::from(term_info.max_term_freq),
+        term_info_block_meta.max_term_freq_nbits,
+        write,
+    )?;
+    bit_packer.write(
+        u64::from(term_info.min_fieldnorm_id),
+        term_info_block_meta.min_fieldnorm_id_nbits,
+        write,
+    )?;
     Ok(())
 }
 
@@ -217,9 +282,13 @@ impl TermInfoStoreWriter {
         }
 
         let mut max_doc_freq: u32 = 0u32;
+        let mut max_term_freq: u32 = 0u32;
+        let mut max_min_fieldnorm_id: u8 = 0u8;
 
         for term_info in &self.term_infos[1..] {
             max_doc_freq = cmp::max(max_doc_freq, term_info.doc_freq);
+            max_term_freq = cmp::max(max_term_freq, term_info.max_term_freq);
+            max_min_fieldnorm_id = cmp::max(max_min_fieldnorm_id, term_info.min_fieldnorm_id);
         }
 
         let max_doc_freq_nbits: u8 = compute_num_bits(u64::from(max_doc_freq));
@@ -232,6 +301,8 @@ impl TermInfoStoreWriter {
             doc_freq_nbits: max_doc_freq_nbits,
             postings_offset_nbits: max_postings_offset_nbits,
             positions_offset_nbits: max_positions_offset_nbits,
+            max_term_freq_nbits: compute_num_bits(u64::from(max_term_freq)),
+            min_fieldnorm_id_nbits: compute_num_bits(u64::from(max_min_fieldnorm_id)),
         };
 
         term_info_block_meta.serialize(&mut self.buffer_block_metas)?;
@@ -277,7 +348,7 @@ impl TermInfoStoreWriter {
             self.flush_block()?;
         }
         let len = self.buffer_block_metas.len() as u64;
-        len.serialize(write)?;
+        (len | MAX_IMPACTS_FLAG).serialize(write)?;
         self.num_terms.serialize(write)?;
         write.write_all(&self.buffer_block_metas)?;
         write.write_all(&self.buffer_term_infos)?;
@@ -289,10 +360,12 @@ impl TermInfoStoreWriter {
 mod tests {
 
     use common;
-    use common::BinarySerializable;
+    use common::{BinarySerializable, FixedSize};
     use tantivy_bitpacker::{compute_num_bits, BitPacker};
 
-    use super::{extract_bits, TermInfoBlockMeta, TermInfoStore, TermInfoStoreWriter};
+    use super::{
+        extract_bits, TermInfoBlockMeta, TermInfoStore, TermInfoStoreWriter, MAX_IMPACTS_FLAG,
+    };
     use crate::directory::FileSlice;
     use crate::postings::TermInfo;
 
@@ -326,10 +399,14 @@ mod tests {
                 doc_freq: 512,
                 postings_range: 51..57,
                 positions_range: 110..134,
+                max_term_freq: 7,
+                min_fieldnorm_id: 12,
             },
             doc_freq_nbits: 10,
             postings_offset_nbits: 5,
             positions_offset_nbits: 8,
+            max_term_freq_nbits: 3,
+            min_fieldnorm_id_nbits: 4,
         };
         let mut buffer: Vec<u8> = Vec::new();
         term_info_block_meta.serialize(&mut buffer).unwrap();
@@ -348,6 +425,8 @@ mod tests {
                 doc_freq: i as u32,
                 postings_range: offset(i)..offset(i + 1),
                 positions_range: offset(i) * 3..offset(i + 1) * 3,
+                max_term_freq: (i % 37) as u32 + 1,
+                min_fieldnorm_id: (i % 256) as u8,
             };
             store_writer.write_term_info(&term_info)?;
             term_infos.push(term_info);
@@ -365,4 +444,46 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_open_without_max_impacts() -> crate::Result<()> {
+        let mut store_writer = TermInfoStoreWriter::new();
+        let mut term_infos = vec![];
+        for i in 0usize..1000usize {
+            let term_info = TermInfo {
+                doc_freq: i as u32,
+                postings_range: i * 7..(i + 1) * 7,
+                positions_range: i * 11..(i + 1) * 11,
+                max_term_freq: 0,
+                min_fieldnorm_id: 0,
+            };
+            store_writer.write_term_info(&term_info)?;
+            term_infos.push(term_info);
+        }
+        let mut buffer = Vec::new();
+        store_writer.serialize(&mut buffer)?;
+        // Rewrites the store as tantivy 0.18 did: the null max impacts take no bits in the
+        // bitpacked term infos, but the block metas need to be stripped from them.
+        let len = (u64::deserialize(&mut &buffer[..8])? & !MAX_IMPACTS_FLAG) as usize;
+        let block_metas = &buffer[16..16 + len];
+        let mut legacy_block_metas = Vec::new();
+        for block_meta in block_metas.chunks(TermInfoBlockMeta::SIZE_IN_BYTES) {
+            let ref_term_info_end = 8 + TermInfo::SIZE_WITHOUT_MAX_IMPACTS_IN_BYTES;
+            legacy_block_metas.extend_from_slice(&block_meta[..ref_term_info_end]);
+            let nbits_start = 8 + TermInfo::SIZE_IN_BYTES;
+            legacy_block_metas.extend_from_slice(&block_meta[nbits_start..nbits_start + 3]);
+        }
+        let mut legacy_buffer = Vec::new();
+        (legacy_block_metas.len() as u64).serialize(&mut legacy_buffer)?;
+        legacy_buffer.extend_from_slice(&buffer[8..16]);
+        legacy_buffer.extend_from_slice(&legacy_block_metas);
+        legacy_buffer.extend_from_slice(&buffer[16 + len..]);
+
+        let term_info_store = TermInfoStore::open(FileSlice::from(legacy_buffer))?;
+        assert_eq!(term_info_store.num_terms(), 1000);
+        for (term_ord, term_info) in term_infos.iter().enumerate() {
+            assert_eq!(&term_info_store.get(term_ord as u64), term_info);
+        }
+        Ok(())
+    }
 }
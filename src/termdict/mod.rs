@@ -29,10 +29,13 @@ mod sstable_termdict;
 #[cfg(feature = "quickwit")]
 use sstable_termdict as termdict;
 
+mod term_fst;
+
 #[cfg(test)]
 mod tests;
 
 /// Position of the term in the sorted list of terms.
 pub type TermOrdinal = u64;
 
+pub use self::term_fst::{Suggestion, TermFst, TermFstStreamer};
 pub use self::termdict::{TermDictionary, TermDictionaryBuilder, TermMerger, TermStreamer};
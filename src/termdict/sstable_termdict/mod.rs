@@ -18,6 +18,11 @@ use crate::postings::TermInfo;
 
 pub struct TermSSTable;
 
+/// Flag set on the number of terms of the blocks holding the `max_term_freq` and
+/// `min_fieldnorm_id` of their terms. The blocks written before tantivy 0.19 do not have
+/// them. The number of terms of a block is far below this flag.
+const MAX_IMPACTS_FLAG: u64 = 1 << 32;
+
 impl SSTable for TermSSTable {
     type Value = TermInfo;
     type Reader = TermInfoReader;
@@ -38,19 +43,32 @@ impl ValueReader for TermInfoReader {
 
     fn read(&mut self, reader: &mut BlockReader) -> io::Result<()> {
         self.term_infos.clear();
-        let num_els = VInt::deserialize_u64(reader)?;
+        let num_els_with_flag = VInt::deserialize_u64(reader)?;
+        if num_els_with_flag == 0 {
+            return Ok(());
+        }
+        let has_max_impacts = num_els_with_flag & MAX_IMPACTS_FLAG != 0;
+        let num_els = num_els_with_flag & !MAX_IMPACTS_FLAG;
         let mut postings_start = VInt::deserialize_u64(reader)? as usize;
         let mut positions_start = VInt::deserialize_u64(reader)? as usize;
         for _ in 0..num_els {
             let doc_freq = VInt::deserialize_u64(reader)? as u32;
             let postings_num_bytes = VInt::deserialize_u64(reader)?;
             let positions_num_bytes = VInt::deserialize_u64(reader)?;
+            let (max_term_freq, min_fieldnorm_id) = if has_max_impacts {
+                let max_term_freq = VInt::deserialize_u64(reader)? as u32;
+                (max_term_freq, VInt::deserialize_u64(reader)? as u8)
+            } else {
+                (0, 0)
+            };
             let postings_end = postings_start + postings_num_bytes as usize;
             let positions_end = positions_start + positions_num_bytes as usize;
             let term_info = TermInfo {
                 doc_freq,
                 postings_range: postings_start..postings_end,
                 positions_range: positions_start..positions_end,
+                max_term_freq,
+                min_fieldnorm_id,
             };
             self.term_infos.push(term_info);
             postings_start = postings_end;
@@ -73,16 +91,19 @@ impl ValueWriter for TermInfoWriter {
     }
 
     fn write_block(&mut self, buffer: &mut Vec<u8>) {
-        VInt(self.term_infos.len() as u64).serialize_into_vec(buffer);
         if self.term_infos.is_empty() {
+            VInt(0).serialize_into_vec(buffer);
             return;
         }
+        VInt(self.term_infos.len() as u64 | MAX_IMPACTS_FLAG).serialize_into_vec(buffer);
         VInt(self.term_infos[0].postings_range.start as u64).serialize_into_vec(buffer);
         VInt(self.term_infos[0].positions_range.start as u64).serialize_into_vec(buffer);
         for term_info in &self.term_infos {
             VInt(term_info.doc_freq as u64).serialize_into_vec(buffer);
             VInt(term_info.postings_range.len() as u64).serialize_into_vec(buffer);
             VInt(term_info.positions_range.len() as u64).serialize_into_vec(buffer);
+            VInt(term_info.max_term_freq as u64).serialize_into_vec(buffer);
+            VInt(term_info.min_fieldnorm_id as u64).serialize_into_vec(buffer);
         }
         self.term_infos.clear();
     }
@@ -92,6 +113,8 @@ impl ValueWriter for TermInfoWriter {
 mod tests {
     use std::io;
 
+    use common::VInt;
+
     use super::BlockReader;
     use crate::directory::OwnedBytes;
     use crate::postings::TermInfo;
@@ -105,16 +128,22 @@ mod tests {
             doc_freq: 120u32,
             postings_range: 17..45,
             positions_range: 10..122,
+            max_term_freq: 3,
+            min_fieldnorm_id: 20,
         });
         term_info_writer.write(&TermInfo {
             doc_freq: 10u32,
             postings_range: 45..450,
             positions_range: 122..1100,
+            max_term_freq: 1,
+            min_fieldnorm_id: 4,
         });
         term_info_writer.write(&TermInfo {
             doc_freq: 17u32,
             postings_range: 450..462,
             positions_range: 1100..1302,
+            max_term_freq: 12,
+            min_fieldnorm_id: 0,
         });
         let mut buffer = Vec::new();
         term_info_writer.write_block(&mut buffer);
@@ -126,7 +155,33 @@ mod tests {
             &TermInfo {
                 doc_freq: 120u32,
                 postings_range: 17..45,
-                positions_range: 10..122
+                positions_range: 10..122,
+                max_term_freq: 3,
+                min_fieldnorm_id: 20,
+            }
+        );
+        assert!(block_reader.buffer().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_terminfos_without_max_impacts() -> io::Result<()> {
+        // A block written before tantivy 0.19.
+        let mut buffer = Vec::new();
+        for val in [2u64, 17, 10, 120, 28, 112, 10, 405, 978] {
+            VInt(val).serialize_into_vec(&mut buffer);
+        }
+        let mut block_reader = make_block_reader(&buffer[..]);
+        let mut term_info_reader = TermInfoReader::default();
+        term_info_reader.read(&mut block_reader)?;
+        assert_eq!(
+            term_info_reader.value(1),
+            &TermInfo {
+                doc_freq: 10u32,
+                postings_range: 45..450,
+                positions_range: 122..1100,
+                max_term_freq: 0,
+                min_fieldnorm_id: 0,
             }
         );
         assert!(block_reader.buffer().is_empty());
@@ -200,6 +200,8 @@ mod tests {
             doc_freq: 1000u32 + i as u32,
             postings_range: (i + 10) * (i * 10)..((i + 1) + 10) * ((i + 1) * 10),
             positions_range: i * 500..(i + 1) * 500,
+            max_term_freq: i as u32 + 1,
+            min_fieldnorm_id: (i % 256) as u8,
         }
     }
 
@@ -0,0 +1,389 @@
+use std::cmp::Reverse;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use levenshtein_automata::Distance;
+use tantivy_fst::raw::Fst;
+use tantivy_fst::{Automaton, IntoStreamer, Streamer};
+
+use crate::directory::{FileSlice, OwnedBytes};
+use crate::error::DataCorruption;
+use crate::query::build_dfa;
+use crate::schema::Field;
+use crate::termdict::TermMerger;
+use crate::{InvertedIndexReader, Searcher, TantivyError};
+
+/// Standalone FST of the terms of a field, associating each term to its document frequency.
+///
+/// A `TermFst` is built from all of the segments of a [`Searcher`], and can then be written to
+/// a file that is self-contained: it does not require the index, nor its postings, to be
+/// opened. This makes it a good fit for query autocompletion services, through prefix and
+/// fuzzy lookups.
+///
+/// Terms are stored as the bytes of their value, i.e. [`Term::value_bytes`](crate::Term).
+/// The document frequencies are summed over the segments, and include the deleted documents.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::termdict::TermFst;
+/// use tantivy::directory::FileSlice;
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "Tantivy, a search engine library"))?;
+/// index_writer.commit()?;
+///
+/// let term_fst = TermFst::build(&index.reader()?.searcher(), title)?;
+/// let mut fst_bytes = Vec::new();
+/// term_fst.write(&mut fst_bytes)?;
+///
+/// let term_fst = TermFst::open(FileSlice::from(fst_bytes))?;
+/// let mut completions = term_fst.prefix("se");
+/// assert_eq!(completions.next(), Some((&b"search"[..], 1)));
+/// assert_eq!(completions.next(), None);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TermFst {
+    fst_bytes: OwnedBytes,
+    fst_map: tantivy_fst::Map<OwnedBytes>,
+}
+
+/// Automaton matching the terms starting with a given prefix.
+///
+/// Its state is the length of the prefix matched so far, or `None` once the term diverged
+/// from the prefix.
+struct Prefix(Vec<u8>);
+
+impl Automaton for Prefix {
+    type State = Option<usize>;
+
+    fn start(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn is_match(&self, state: &Option<usize>) -> bool {
+        *state == Some(self.0.len())
+    }
+
+    fn can_match(&self, state: &Option<usize>) -> bool {
+        state.is_some()
+    }
+
+    fn will_always_match(&self, state: &Option<usize>) -> bool {
+        self.is_match(state)
+    }
+
+    fn accept(&self, state: &Option<usize>, byte: u8) -> Option<usize> {
+        match *state {
+            Some(len) if len == self.0.len() => Some(len),
+            Some(len) if self.0[len] == byte => Some(len + 1),
+            _ => None,
+        }
+    }
+}
+
+fn convert_fst_error(err: tantivy_fst::Error) -> TantivyError {
+    TantivyError::InternalError(format!("Failed to build the term fst: {:?}", err))
+}
+
+impl TermFst {
+    /// Builds the `TermFst` of the terms of `field`, over all of the segments of `searcher`.
+    pub fn build(searcher: &Searcher, field: Field) -> crate::Result<TermFst> {
+        let inverted_indexes: Vec<Arc<InvertedIndexReader>> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<_>>()?;
+        let term_streams = inverted_indexes
+            .iter()
+            .map(|inverted_index| inverted_index.terms().stream())
+            .collect::<io::Result<_>>()?;
+        let mut term_merger = TermMerger::new(term_streams);
+        let mut map_builder = tantivy_fst::MapBuilder::memory();
+        while term_merger.advance() {
+            let doc_freq: u64 = term_merger
+                .current_segment_ords_and_term_infos()
+                .map(|(_, term_info)| u64::from(term_info.doc_freq))
+                .sum();
+            map_builder
+                .insert(term_merger.key(), doc_freq)
+                .map_err(convert_fst_error)?;
+        }
+        let fst_bytes = map_builder.into_inner().map_err(convert_fst_error)?;
+        TermFst::open(FileSlice::from(fst_bytes))
+    }
+
+    /// Opens a `TermFst` previously written with [`TermFst::write`].
+    pub fn open(file: FileSlice) -> crate::Result<TermFst> {
+        let fst_bytes = file.read_bytes()?;
+        let fst = Fst::new(fst_bytes.clone()).map_err(|err| {
+            DataCorruption::comment_only(format!("Term fst data is corrupted: {:?}", err))
+        })?;
+        Ok(TermFst {
+            fst_bytes,
+            fst_map: tantivy_fst::Map::from(fst),
+        })
+    }
+
+    /// Writes the `TermFst` to `wrt`.
+    pub fn write<W: Write>(&self, wrt: &mut W) -> io::Result<()> {
+        wrt.write_all(self.fst_bytes.as_slice())
+    }
+
+    /// Returns the number of terms.
+    pub fn num_terms(&self) -> usize {
+        self.fst_map.len()
+    }
+
+    /// Returns the document frequency of `term`, or `None` if the term does not exist.
+    pub fn doc_freq<K: AsRef<[u8]>>(&self, term: K) -> Option<u64> {
+        self.fst_map.get(term)
+    }
+
+    /// Returns a stream of the terms accepted by `automaton`, along with their document
+    /// frequency, in lexicographical order.
+    pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermFstStreamer<'a> {
+        TermFstStreamer(Box::new(self.fst_map.search(automaton).into_stream()))
+    }
+
+    /// Returns a stream of the terms starting with `prefix`.
+    pub fn prefix<K: AsRef<[u8]>>(&self, prefix: K) -> TermFstStreamer<'_> {
+        self.search(Prefix(prefix.as_ref().to_vec()))
+    }
+
+    /// Returns a stream of the terms within Levenshtein distance `distance` of `term`, or of
+    /// one of its prefixes if `prefix` is true.
+    ///
+    /// See [`FuzzyTermQuery`](crate::query::FuzzyTermQuery) for the supported distances.
+    pub fn fuzzy(
+        &self,
+        term: &str,
+        distance: u8,
+        transposition_cost_one: bool,
+        prefix: bool,
+    ) -> crate::Result<TermFstStreamer<'_>> {
+        let automaton = build_dfa(term, distance, transposition_cost_one, prefix)?;
+        Ok(self.search(automaton))
+    }
+
+    /// Returns spelling suggestions for the possibly misspelled `term`: the terms within
+    /// Damerau-Levenshtein distance `max_distance` of `term`, excluding `term` itself.
+    ///
+    /// At most `limit` suggestions are returned, ranked by increasing edit distance, then by
+    /// decreasing document frequency, so that at the same distance a common term comes before a
+    /// rare one.
+    ///
+    /// See [`FuzzyTermQuery`](crate::query::FuzzyTermQuery) for the supported distances.
+    pub fn suggest(
+        &self,
+        term: &str,
+        max_distance: u8,
+        limit: usize,
+    ) -> crate::Result<Vec<Suggestion>> {
+        let automaton = build_dfa(term, max_distance, true, false)?;
+        let mut suggestions = Vec::new();
+        let mut stream = self.search(&automaton);
+        while let Some((candidate, doc_freq)) = stream.next() {
+            if candidate == term.as_bytes() {
+                continue;
+            }
+            let distance = match automaton.0.eval(candidate) {
+                Distance::Exact(distance) => distance,
+                Distance::AtLeast(_) => continue,
+            };
+            if let Ok(candidate) = std::str::from_utf8(candidate) {
+                suggestions.push(Suggestion {
+                    term: candidate.to_string(),
+                    distance,
+                    doc_freq,
+                });
+            }
+        }
+        // The stream is in lexicographical order, and the sort is stable: ties are broken by
+        // term.
+        suggestions.sort_by_key(|suggestion| (suggestion.distance, Reverse(suggestion.doc_freq)));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+}
+
+/// Spelling suggestion returned by [`TermFst::suggest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    term: String,
+    distance: u8,
+    doc_freq: u64,
+}
+
+impl Suggestion {
+    /// Returns the suggested term.
+    pub fn term(&self) -> &str {
+        &self.term
+    }
+
+    /// Returns the edit distance between the suggested term and the original term.
+    pub fn distance(&self) -> u8 {
+        self.distance
+    }
+
+    /// Returns the document frequency of the suggested term.
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+}
+
+type BoxTermStream<'a> = Box<dyn for<'b> Streamer<'b, Item = (&'b [u8], u64)> + 'a>;
+
+/// Stream of the terms of a [`TermFst`], along with their document frequency.
+pub struct TermFstStreamer<'a>(BoxTermStream<'a>);
+
+impl<'a> TermFstStreamer<'a> {
+    /// Advances the stream, and returns the next term and its document frequency.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&[u8], u64)> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Suggestion, TermFst, TermFstStreamer};
+    use crate::directory::FileSlice;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, TantivyError};
+
+    fn collect_terms(mut stream: TermFstStreamer) -> Vec<(String, u64)> {
+        let mut terms = Vec::new();
+        while let Some((term, doc_freq)) = stream.next() {
+            terms.push((String::from_utf8(term.to_vec()).unwrap(), doc_freq));
+        }
+        terms
+    }
+
+    #[test]
+    fn test_term_fst_round_trip() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let other = schema_builder.add_text_field("other", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello world", other => "helium"))?;
+        index_writer.add_document(doc!(text => "help"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "hello helicopter"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let term_fst = TermFst::build(&searcher, text)?;
+        let mut fst_bytes = Vec::new();
+        term_fst.write(&mut fst_bytes)?;
+        drop(searcher);
+        drop(index_writer);
+        drop(index);
+
+        let term_fst = TermFst::open(FileSlice::from(fst_bytes))?;
+        assert_eq!(term_fst.num_terms(), 4);
+        assert_eq!(term_fst.doc_freq("hello"), Some(2));
+        assert_eq!(term_fst.doc_freq("helium"), None);
+        assert_eq!(
+            collect_terms(term_fst.prefix("hel")),
+            vec![
+                ("helicopter".to_string(), 1),
+                ("hello".to_string(), 2),
+                ("help".to_string(), 1),
+            ]
+        );
+        assert!(collect_terms(term_fst.prefix("x")).is_empty());
+        assert_eq!(
+            collect_terms(term_fst.fuzzy("helo", 1, true, false)?),
+            vec![("hello".to_string(), 2), ("help".to_string(), 1)]
+        );
+        assert_eq!(
+            collect_terms(term_fst.fuzzy("wrl", 1, true, true)?),
+            vec![("world".to_string(), 1)]
+        );
+        assert!(matches!(
+            term_fst.fuzzy("hello", 5, true, false),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_fst_suggest() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..10 {
+            if i < 2 {
+                index_writer.add_document(doc!(text => "house horse"))?;
+            } else if i < 3 {
+                index_writer.add_document(doc!(text => "mouse"))?;
+            } else {
+                index_writer.add_document(doc!(text => "horse hose"))?;
+            }
+            if i % 4 == 3 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let term_fst = TermFst::build(&index.reader()?.searcher(), text)?;
+        let suggestion_terms = |suggestions: Vec<Suggestion>| -> Vec<(String, u8, u64)> {
+            suggestions
+                .iter()
+                .map(|suggestion| {
+                    (
+                        suggestion.term().to_string(),
+                        suggestion.distance(),
+                        suggestion.doc_freq(),
+                    )
+                })
+                .collect()
+        };
+        // "horse" and "house" are both one substitution away from "hoese": the common "horse"
+        // outranks the rare "house".
+        assert_eq!(
+            suggestion_terms(term_fst.suggest("hoese", 1, 10)?),
+            vec![
+                ("horse".to_string(), 1, 9),
+                ("hose".to_string(), 1, 7),
+                ("house".to_string(), 1, 2),
+            ]
+        );
+        assert_eq!(
+            suggestion_terms(term_fst.suggest("hoese", 2, 10)?),
+            vec![
+                ("horse".to_string(), 1, 9),
+                ("hose".to_string(), 1, 7),
+                ("house".to_string(), 1, 2),
+                ("mouse".to_string(), 2, 1),
+            ]
+        );
+        assert_eq!(
+            suggestion_terms(term_fst.suggest("hoese", 2, 1)?),
+            vec![("horse".to_string(), 1, 9)]
+        );
+        // The term itself is not suggested.
+        assert_eq!(
+            suggestion_terms(term_fst.suggest("house", 1, 10)?),
+            vec![
+                ("horse".to_string(), 1, 9),
+                ("hose".to_string(), 1, 7),
+                ("mouse".to_string(), 1, 1),
+            ]
+        );
+        assert!(term_fst.suggest("zzzzzz", 2, 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_fst_corrupted() {
+        assert!(TermFst::open(FileSlice::from(b"not an fst".to_vec())).is_err());
+    }
+}
@@ -13,6 +13,8 @@ fn make_term_info(term_ord: u64) -> TermInfo {
         doc_freq: term_ord as u32,
         postings_range: offset(term_ord)..offset(term_ord + 1),
         positions_range: offset(term_ord) * 2..offset(term_ord + 1) * 2,
+        max_term_freq: term_ord as u32 % 10 + 1,
+        min_fieldnorm_id: (term_ord % 256) as u8,
     }
 }
 
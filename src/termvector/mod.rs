@@ -0,0 +1,219 @@
+//! Term vectors of the documents, used to highlight them without tokenizing their text again.
+//!
+//! The term vector of a document lists the tokens of a text field, in the order they were
+//! produced by the tokenizer, along with their position and their offsets. Term vectors are
+//! stored for the fields whose [`TextFieldIndexing`](crate::schema::TextFieldIndexing)
+//! enables them, in the `.tv` file of the segment.
+//!
+//! The offsets of the tokens of a multivalued field are relative to the concatenation of its
+//! values, separated by a single space, as in
+//! [`SnippetGenerator::snippet_from_doc`](crate::SnippetGenerator::snippet_from_doc).
+//!
+//! Positions are the ones that are indexed in the postings, including the gap applied
+//! between the values of a multivalued field.
+mod reader;
+mod serializer;
+mod writer;
+
+use std::io;
+
+use common::{BinarySerializable, VInt};
+
+pub use self::reader::{TermVectorReader, TermVectorReaders};
+pub use self::serializer::TermVectorsSerializer;
+pub use self::writer::TermVectorsWriter;
+use crate::schema::{FieldEntry, FieldType};
+use crate::tokenizer::Token;
+
+/// Returns true if term vectors are stored for the field `field_entry`.
+pub(crate) fn has_term_vectors(field_entry: &FieldEntry) -> bool {
+    match field_entry.field_type() {
+        FieldType::Str(text_options) => text_options
+            .get_indexing_options()
+            .map(|indexing_options| indexing_options.term_vectors())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn serialize_term_vector(tokens: &[Token], output: &mut Vec<u8>) -> io::Result<()> {
+    VInt(tokens.len() as u64).serialize(output)?;
+    for token in tokens {
+        VInt(token.text.len() as u64).serialize(output)?;
+        output.extend_from_slice(token.text.as_bytes());
+        VInt(token.position as u64).serialize(output)?;
+        VInt(token.position_length as u64).serialize(output)?;
+        VInt(token.offset_from as u64).serialize(output)?;
+        VInt((token.offset_to - token.offset_from) as u64).serialize(output)?;
+    }
+    Ok(())
+}
+
+/// Minimum number of bytes taken by a serialized token: its text length, position, position
+/// length and offsets take at least one byte each.
+const MIN_SERIALIZED_TOKEN_NUM_BYTES: usize = 5;
+
+fn deserialize_term_vector(mut data: &[u8]) -> io::Result<Vec<Token>> {
+    let num_tokens = VInt::deserialize(&mut data)?.val() as usize;
+    // The number of tokens is checked before allocating them, so that corrupted data
+    // cannot trigger a huge allocation.
+    if num_tokens > data.len() / MIN_SERIALIZED_TOKEN_NUM_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Term vector token count exceeds the term vector data",
+        ));
+    }
+    let mut tokens = Vec::with_capacity(num_tokens);
+    for _ in 0..num_tokens {
+        let text_len = VInt::deserialize(&mut data)?.val() as usize;
+        if text_len > data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Term vector token exceeds the term vector data",
+            ));
+        }
+        let text = String::from_utf8(data[..text_len].to_vec())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        data = &data[text_len..];
+        let position = VInt::deserialize(&mut data)?.val() as usize;
+        let position_length = VInt::deserialize(&mut data)?.val() as usize;
+        let offset_from = VInt::deserialize(&mut data)?.val() as usize;
+        let offset_to = offset_from + VInt::deserialize(&mut data)?.val() as usize;
+        tokens.push(Token {
+            offset_from,
+            offset_to,
+            position,
+            text,
+            position_length,
+        });
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{BinarySerializable, VInt};
+
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, STORED, TEXT};
+    use crate::tokenizer::Token;
+    use crate::{DocAddress, Document, Index, SnippetGenerator, TantivyError};
+
+    fn token(text: &str, position: usize, offset_from: usize) -> Token {
+        Token {
+            offset_from,
+            offset_to: offset_from + text.len(),
+            position,
+            text: text.to_string(),
+            position_length: 1,
+        }
+    }
+
+    #[test]
+    fn test_term_vectors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_term_vectors(true))
+            .set_stored();
+        let title = schema_builder.add_text_field("title", text_options);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_text(title, "Hello World");
+        doc.add_text(title, "hello  again");
+        index_writer.add_document(doc)?;
+        index_writer.add_document(doc!(body => "no title"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "The world"))?;
+        index_writer.commit()?;
+
+        let expected_term_vectors = vec![
+            vec![
+                token("hello", 0, 0),
+                token("world", 1, 6),
+                // The second value starts at the offset 12, after the space separating the
+                // values, and its positions start after the position gap.
+                token("hello", 3, 12),
+                token("again", 4, 19),
+            ],
+            vec![],
+            vec![token("the", 0, 0), token("world", 1, 4)],
+        ];
+        let check_term_vectors = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            let mut term_vectors = Vec::new();
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                let term_vector_reader = segment_reader.term_vectors().get_field(title)?;
+                for doc in 0..segment_reader.max_doc() {
+                    let stored_doc = searcher.doc(DocAddress::new(segment_ord as u32, doc))?;
+                    term_vectors.push((
+                        stored_doc.get_first(title).is_none(),
+                        term_vector_reader.term_vector(doc)?,
+                    ));
+                }
+                assert!(matches!(
+                    segment_reader.term_vectors().get_field(body),
+                    Err(TantivyError::SchemaError(_))
+                ));
+            }
+            // Segments are not ordered: sort the term vectors by their first token.
+            term_vectors.sort_by_key(|(_, term_vector)| {
+                term_vector.first().map(|token| token.text.clone())
+            });
+            assert!(term_vectors[0].0);
+            let term_vectors: Vec<Vec<Token>> = term_vectors
+                .into_iter()
+                .map(|(_, term_vector)| term_vector)
+                .collect();
+            let mut expected = expected_term_vectors.clone();
+            expected.sort_by_key(|term_vector| term_vector.first().map(|token| token.text.clone()));
+            assert_eq!(term_vectors, expected);
+            Ok(())
+        };
+        check_term_vectors(&index)?;
+
+        // The offsets match the tokens of the text of the document.
+        let text = "Hello World hello  again";
+        for token in &expected_term_vectors[0] {
+            assert_eq!(
+                text[token.offset_from..token.offset_to].to_lowercase(),
+                token.text
+            );
+        }
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        check_term_vectors(&index)?;
+
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let query =
+            crate::query::QueryParser::for_index(&index, vec![title]).parse_query("again")?;
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, title)?;
+        let term_vector_reader = segment_reader.term_vectors().get_field(title)?;
+        for doc in 0..segment_reader.max_doc() {
+            let stored_doc = searcher.doc(DocAddress::new(0, doc))?;
+            let tokens = term_vector_reader.term_vector(doc)?;
+            let snippet = snippet_generator.snippet_from_doc_and_term_vector(&stored_doc, &tokens);
+            assert_eq!(
+                snippet.to_html(),
+                snippet_generator.snippet_from_doc(&stored_doc).to_html()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_term_vector_corrupted_num_tokens() {
+        let tokens = vec![token("hello", 0, 0), token("world", 1, 6)];
+        let mut data = Vec::new();
+        super::serialize_term_vector(&tokens, &mut data).unwrap();
+        assert_eq!(super::deserialize_term_vector(&data).unwrap(), tokens);
+        let mut corrupted_data = Vec::new();
+        VInt(u64::MAX >> 8).serialize(&mut corrupted_data).unwrap();
+        corrupted_data.extend_from_slice(&data[1..]);
+        let err = super::deserialize_term_vector(&corrupted_data).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
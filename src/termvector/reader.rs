@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use common::BinarySerializable;
+
+use super::{deserialize_term_vector, has_term_vectors};
+use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::error::DataCorruption;
+use crate::schema::{Field, Schema};
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::tokenizer::Token;
+use crate::{DocId, TantivyError};
+
+/// Provides access to the term vectors of all of the fields of a segment that store them.
+#[derive(Clone)]
+pub struct TermVectorReaders {
+    schema: Schema,
+    data: Arc<CompositeFile>,
+}
+
+impl TermVectorReaders {
+    pub(crate) fn open(schema: Schema, file: &FileSlice) -> crate::Result<TermVectorReaders> {
+        let data = CompositeFile::open(file)?;
+        Ok(TermVectorReaders {
+            schema,
+            data: Arc::new(data),
+        })
+    }
+
+    /// Readers for a segment written before term vectors were supported.
+    pub(crate) fn empty(schema: Schema) -> TermVectorReaders {
+        TermVectorReaders {
+            schema,
+            data: Arc::new(CompositeFile::empty()),
+        }
+    }
+
+    /// Returns the reader of the term vectors of `field`.
+    ///
+    /// Returns an error if `field` does not store term vectors.
+    pub fn get_field(&self, field: Field) -> crate::Result<TermVectorReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !has_term_vectors(field_entry) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} does not store term vectors.",
+                field_entry.name()
+            )));
+        }
+        if let Some(file) = self.data.open_read(field) {
+            TermVectorReader::open(file)
+        } else {
+            Ok(TermVectorReader::empty())
+        }
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reader of the term vectors of a field of a segment.
+pub struct TermVectorReader {
+    doc_offsets: Vec<u64>,
+    data: OwnedBytes,
+}
+
+impl TermVectorReader {
+    fn open(file: FileSlice) -> crate::Result<TermVectorReader> {
+        let bytes = file.read_bytes()?;
+        let mut cursor = bytes.as_slice();
+        let doc_offsets = Vec::<u64>::deserialize(&mut cursor)?;
+        let data = bytes.slice(bytes.len() - cursor.len()..bytes.len());
+        let is_valid = doc_offsets.windows(2).all(|window| window[0] <= window[1])
+            && doc_offsets.last().copied() == Some(data.len() as u64);
+        if !is_valid {
+            return Err(DataCorruption::comment_only(
+                "Term vector offsets are inconsistent with the term vector data",
+            )
+            .into());
+        }
+        Ok(TermVectorReader { doc_offsets, data })
+    }
+
+    fn empty() -> TermVectorReader {
+        TermVectorReader {
+            doc_offsets: Vec::new(),
+            data: OwnedBytes::empty(),
+        }
+    }
+
+    /// Returns the serialized term vector of `doc`.
+    ///
+    /// The term vector of a document that does not have one is empty.
+    pub(crate) fn term_vector_bytes(&self, doc: DocId) -> &[u8] {
+        let doc = doc as usize;
+        if doc + 1 >= self.doc_offsets.len() {
+            return &[];
+        }
+        let start = self.doc_offsets[doc] as usize;
+        let end = self.doc_offsets[doc + 1] as usize;
+        &self.data.as_slice()[start..end]
+    }
+
+    /// Returns the tokens of the field for the document `doc`, in the order they were
+    /// produced by the tokenizer.
+    ///
+    /// See the [module documentation](crate::termvector) for the meaning of the positions
+    /// and the offsets.
+    pub fn term_vector(&self, doc: DocId) -> crate::Result<Vec<Token>> {
+        let term_vector_bytes = self.term_vector_bytes(doc);
+        if term_vector_bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(deserialize_term_vector(term_vector_bytes)?)
+    }
+}
@@ -0,0 +1,42 @@
+use std::io::{self, Write};
+
+use common::BinarySerializable;
+
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+
+/// The term vectors serializer is in charge of the serialization of the term vectors of all
+/// of the fields that store them.
+pub struct TermVectorsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl TermVectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<TermVectorsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(TermVectorsSerializer { composite_write })
+    }
+
+    /// Serializes the term vectors of the given field.
+    ///
+    /// The term vector of the document `doc` is `data[doc_offsets[doc]..doc_offsets[doc + 1]]`.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        doc_offsets: &[u64],
+        data: &[u8],
+    ) -> io::Result<()> {
+        let write = self.composite_write.for_field(field);
+        doc_offsets.to_vec().serialize(write)?;
+        write.write_all(data)?;
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::io;
+
+use super::{has_term_vectors, serialize_term_vector, TermVectorsSerializer};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{Field, Schema};
+use crate::tokenizer::Token;
+use crate::DocId;
+
+/// Term vectors of a field, serialized one after the other.
+struct FieldTermVectors {
+    // `doc_offsets[doc]` is the offset of the term vector of `doc` in `data`. It has one more
+    // item than the number of documents recorded so far.
+    doc_offsets: Vec<u64>,
+    data: Vec<u8>,
+}
+
+impl FieldTermVectors {
+    fn fill_up_to(&mut self, num_docs: usize) {
+        while self.doc_offsets.len() < num_docs + 1 {
+            self.doc_offsets.push(self.data.len() as u64);
+        }
+    }
+
+    fn term_vector(&self, doc: DocId) -> &[u8] {
+        let start = self.doc_offsets[doc as usize] as usize;
+        let end = self.doc_offsets[doc as usize + 1] as usize;
+        &self.data[start..end]
+    }
+}
+
+/// The `TermVectorsWriter` is in charge of accumulating the term vectors of the fields that
+/// store them, until the segment is serialized.
+pub struct TermVectorsWriter {
+    field_term_vectors: Vec<Option<FieldTermVectors>>,
+}
+
+impl TermVectorsWriter {
+    /// Create all `TermVectorsWriter` required by the schema.
+    pub fn from_schema(schema: &Schema) -> TermVectorsWriter {
+        let field_term_vectors = schema
+            .fields()
+            .map(|(_, field_entry)| {
+                if has_term_vectors(field_entry) {
+                    Some(FieldTermVectors {
+                        doc_offsets: vec![0],
+                        data: Vec::new(),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        TermVectorsWriter { field_term_vectors }
+    }
+
+    /// Returns true if the term vectors of `field` are stored.
+    pub fn has_term_vectors(&self, field: Field) -> bool {
+        matches!(
+            self.field_term_vectors.get(field.field_id() as usize),
+            Some(Some(_))
+        )
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.field_term_vectors
+            .iter()
+            .flatten()
+            .map(|field_term_vectors| {
+                field_term_vectors.doc_offsets.capacity() * std::mem::size_of::<u64>()
+                    + field_term_vectors.data.capacity()
+            })
+            .sum()
+    }
+
+    /// Records the term vector of `field` for the document `doc`.
+    ///
+    /// Documents are expected to be recorded in increasing order, at most once per field.
+    pub fn record(&mut self, doc: DocId, field: Field, tokens: &[Token]) -> io::Result<()> {
+        if let Some(Some(field_term_vectors)) =
+            self.field_term_vectors.get_mut(field.field_id() as usize)
+        {
+            field_term_vectors.fill_up_to(doc as usize);
+            serialize_term_vector(tokens, &mut field_term_vectors.data)?;
+            field_term_vectors
+                .doc_offsets
+                .push(field_term_vectors.data.len() as u64);
+        }
+        Ok(())
+    }
+
+    /// Serializes all of the term vectors, in the order of `doc_id_map` if the segment is
+    /// sorted.
+    pub fn serialize(
+        &mut self,
+        mut serializer: TermVectorsSerializer,
+        max_doc: DocId,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (field_id, field_term_vectors) in self.field_term_vectors.iter_mut().enumerate() {
+            let field_term_vectors = if let Some(field_term_vectors) = field_term_vectors {
+                field_term_vectors
+            } else {
+                continue;
+            };
+            field_term_vectors.fill_up_to(max_doc as usize);
+            let field = Field::from_field_id(field_id as u32);
+            if let Some(doc_id_map) = doc_id_map {
+                let mut doc_offsets: Vec<u64> = vec![0];
+                let mut data: Vec<u8> = Vec::with_capacity(field_term_vectors.data.len());
+                for old_doc in doc_id_map.iter_old_doc_ids() {
+                    data.extend_from_slice(field_term_vectors.term_vector(old_doc));
+                    doc_offsets.push(data.len() as u64);
+                }
+                serializer.serialize_field(field, &doc_offsets, &data)?;
+            } else {
+                serializer.serialize_field(
+                    field,
+                    &field_term_vectors.doc_offsets,
+                    &field_term_vectors.data,
+                )?;
+            }
+        }
+        serializer.close()
+    }
+}
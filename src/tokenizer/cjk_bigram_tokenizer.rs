@@ -0,0 +1,273 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{BoxTokenStream, Token, TokenStream, Tokenizer};
+
+/// Tokenizes mixed CJK (Chinese, Japanese, Korean) and non-CJK text.
+///
+/// CJK scripts do not separate words with whitespaces. Runs of Han, Hiragana, Katakana and
+/// Hangul characters are therefore split into overlapping bigrams: `東京都` gives `東京` and
+/// `京都`. A CJK character without any CJK neighbour is emitted as a unigram.
+///
+/// The rest of the text is tokenized like the [`SimpleTokenizer`](super::SimpleTokenizer)
+/// does, by splitting on whitespaces and punctuation.
+///
+/// With [`CjkBigramTokenizer::with_unigrams`], the CJK characters are also emitted as
+/// unigrams, which makes single character queries match, at the cost of a larger index.
+/// Each unigram is then followed by the bigram starting at the same position.
+///
+/// Offsets are the byte offsets of the tokens in the original text.
+///
+/// | Term     | tantivy | 全文 | 文検 | 検索 |
+/// |----------|---------|------|------|------|
+/// | Position | 0       | 1    | 2    | 3    |
+/// | Offsets  | 0,7     | 8,14 | 11,17| 14,20|
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = CjkBigramTokenizer::default();
+/// let mut stream = tokenizer.token_stream("tantivy 全文検索");
+/// let mut tokens = Vec::new();
+/// while let Some(token) = stream.next() {
+///     tokens.push(token.text.clone());
+/// }
+/// assert_eq!(tokens, vec!["tantivy", "全文", "文検", "検索"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct CjkBigramTokenizer {
+    output_unigrams: bool,
+}
+
+impl CjkBigramTokenizer {
+    /// Creates a tokenizer that also emits the CJK characters as unigrams.
+    pub fn with_unigrams() -> CjkBigramTokenizer {
+        CjkBigramTokenizer {
+            output_unigrams: true,
+        }
+    }
+}
+
+pub struct CjkBigramTokenStream<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    output_unigrams: bool,
+    // True if the previous character was a CJK character.
+    in_cjk_run: bool,
+    // Bigram to emit at the position of the last unigram.
+    pending_bigram: Option<(usize, usize)>,
+    token: Token,
+}
+
+impl Tokenizer for CjkBigramTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(CjkBigramTokenStream {
+            text,
+            chars: text.char_indices().peekable(),
+            output_unigrams: self.output_unigrams,
+            in_cjk_run: false,
+            pending_bigram: None,
+            token: Token::default(),
+        })
+    }
+}
+
+/// Returns true if `c` belongs to one of the Han, Hiragana, Katakana or Hangul blocks.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}' // Hangul Jamo
+        | '\u{3040}'..='\u{309F}' // Hiragana
+        | '\u{30A0}'..='\u{30FF}' // Katakana
+        | '\u{3130}'..='\u{318F}' // Hangul Compatibility Jamo
+        | '\u{31F0}'..='\u{31FF}' // Katakana Phonetic Extensions
+        | '\u{3400}'..='\u{4DBF}' // CJK Unified Ideographs Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FFFF}' // CJK Unified Ideographs Extension B and later
+    )
+}
+
+impl<'a> CjkBigramTokenStream<'a> {
+    fn set_token(&mut self, offset_from: usize, offset_to: usize, position_length: usize) {
+        self.token.offset_from = offset_from;
+        self.token.offset_to = offset_to;
+        self.token.position_length = position_length;
+        self.token.text.push_str(&self.text[offset_from..offset_to]);
+    }
+
+    // Returns the end offset of the next character, if it is a CJK character.
+    fn next_cjk_end(&mut self) -> Option<usize> {
+        self.chars
+            .peek()
+            .filter(|&&(_, c)| is_cjk(c))
+            .map(|&(offset, c)| offset + c.len_utf8())
+    }
+
+    // Searches for the end of the current non-CJK token.
+    fn search_token_end(&mut self) -> usize {
+        while let Some(&(offset, c)) = self.chars.peek() {
+            if is_cjk(c) || !c.is_alphanumeric() {
+                return offset;
+            }
+            self.chars.next();
+        }
+        self.text.len()
+    }
+}
+
+impl<'a> TokenStream for CjkBigramTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        if let Some((offset_from, offset_to)) = self.pending_bigram.take() {
+            self.set_token(offset_from, offset_to, 2);
+            return true;
+        }
+        while let Some((offset_from, c)) = self.chars.next() {
+            if is_cjk(c) {
+                let was_in_cjk_run = self.in_cjk_run;
+                self.in_cjk_run = true;
+                let next_cjk_end_opt = self.next_cjk_end();
+                if self.output_unigrams {
+                    self.pending_bigram =
+                        next_cjk_end_opt.map(|next_cjk_end| (offset_from, next_cjk_end));
+                    self.token.position = self.token.position.wrapping_add(1);
+                    self.set_token(offset_from, offset_from + c.len_utf8(), 1);
+                    return true;
+                }
+                if let Some(next_cjk_end) = next_cjk_end_opt {
+                    self.token.position = self.token.position.wrapping_add(1);
+                    self.set_token(offset_from, next_cjk_end, 1);
+                    return true;
+                }
+                if !was_in_cjk_run {
+                    // Isolated CJK character.
+                    self.token.position = self.token.position.wrapping_add(1);
+                    self.set_token(offset_from, offset_from + c.len_utf8(), 1);
+                    return true;
+                }
+                // Last character of a CJK run, already part of the previous bigram.
+                continue;
+            }
+            self.in_cjk_run = false;
+            if c.is_alphanumeric() {
+                let offset_to = self.search_token_end();
+                self.token.position = self.token.position.wrapping_add(1);
+                self.set_token(offset_from, offset_to, 1);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{CjkBigramTokenizer, TextAnalyzer, Token};
+    use crate::Index;
+
+    fn token_stream_helper(tokenizer: CjkBigramTokenizer, text: &str) -> Vec<Token> {
+        let mut token_stream = TextAnalyzer::from(tokenizer).token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        for token in &tokens {
+            assert_eq!(&text[token.offset_from..token.offset_to], token.text);
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer() {
+        let tokens = token_stream_helper(
+            CjkBigramTokenizer::default(),
+            "Rust製の全文検索エンジン tantivy, 東 and 한국어!",
+        );
+        assert_eq!(tokens.len(), 15);
+        assert_token(&tokens[0], 0, "Rust", 0, 4);
+        assert_token(&tokens[1], 1, "製の", 4, 10);
+        assert_token(&tokens[2], 2, "の全", 7, 13);
+        assert_token(&tokens[3], 3, "全文", 10, 16);
+        assert_token(&tokens[4], 4, "文検", 13, 19);
+        assert_token(&tokens[5], 5, "検索", 16, 22);
+        assert_token(&tokens[6], 6, "索エ", 19, 25);
+        assert_token(&tokens[7], 7, "エン", 22, 28);
+        assert_token(&tokens[8], 8, "ンジ", 25, 31);
+        assert_token(&tokens[9], 9, "ジン", 28, 34);
+        assert_token(&tokens[10], 10, "tantivy", 35, 42);
+        assert_token(&tokens[11], 11, "東", 44, 47);
+        assert_token(&tokens[12], 12, "and", 48, 51);
+        assert_token(&tokens[13], 13, "한국", 52, 58);
+        assert_token(&tokens[14], 14, "국어", 55, 61);
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer_adjacent_runs() {
+        let tokens = token_stream_helper(CjkBigramTokenizer::default(), "東京v2大阪。京");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "東京", 0, 6);
+        assert_token(&tokens[1], 1, "v2", 6, 8);
+        assert_token(&tokens[2], 2, "大阪", 8, 14);
+        assert_token(&tokens[3], 3, "京", 17, 20);
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer_with_unigrams() {
+        let tokens = token_stream_helper(CjkBigramTokenizer::with_unigrams(), "東京都 in 日");
+        assert_eq!(tokens.len(), 7);
+        assert_token(&tokens[0], 0, "東", 0, 3);
+        assert_token(&tokens[1], 0, "東京", 0, 6);
+        assert_eq!(tokens[1].position_length, 2);
+        assert_token(&tokens[2], 1, "京", 3, 6);
+        assert_token(&tokens[3], 1, "京都", 3, 9);
+        assert_token(&tokens[4], 2, "都", 6, 9);
+        assert_eq!(tokens[4].position_length, 1);
+        assert_token(&tokens[5], 3, "in", 10, 12);
+        assert_token(&tokens[6], 4, "日", 13, 16);
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("cjk_bigram")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "東京都に住む"))?;
+        index_writer.add_document(doc!(text => "京都 Kyoto"))?;
+        index_writer.add_document(doc!(text => "都市"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("京都")?, 2);
+        // Queries of several bigrams are phrase queries.
+        assert_eq!(count("東京都")?, 1);
+        assert_eq!(count("京都に")?, 1);
+        assert_eq!(count("kyoto")?, 1);
+        assert_eq!(count("大阪")?, 0);
+        Ok(())
+    }
+}
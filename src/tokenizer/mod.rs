@@ -45,6 +45,11 @@
 //! Does not actual tokenizer your text. It keeps it entirely unprocessed.
 //! It can be useful to index uuids, or urls for instance.
 //!
+//! ## `raw_lowercase`
+//! Like `raw`, keeps the text as a single token, but lowercases it. It can be useful for
+//! keywords that should match exactly, regardless of their case: `Foo` and `foo` match, but
+//! `foo bar` is not split into two tokens.
+//!
 //! ## `en_stem`
 //!
 //! In addition to what `default` does, the `en_stem` tokenizer also
@@ -52,6 +57,12 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! ## `cjk_bigram`
+//!
+//! Splits runs of Chinese, Japanese and Korean characters into overlapping bigrams, and
+//! tokenizes the rest of the text like `default` does. See
+//! [`CjkBigramTokenizer`](./struct.CjkBigramTokenizer.html).
+//!
 //!
 //! # Custom tokenizers
 //!
@@ -119,28 +130,35 @@
 //! ```
 mod alphanum_only;
 mod ascii_folding_filter;
+mod cjk_bigram_tokenizer;
 mod empty_tokenizer;
 mod facet_tokenizer;
 mod lower_caser;
 mod ngram_tokenizer;
 mod raw_tokenizer;
 mod remove_long;
+mod shingle_filter;
 mod simple_tokenizer;
+mod split_identifier_filter;
 mod stemmer;
 mod stop_word_filter;
 mod tokenized_string;
 mod tokenizer;
 mod tokenizer_manager;
+mod truncate_long;
 mod whitespace_tokenizer;
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
+pub use self::cjk_bigram_tokenizer::CjkBigramTokenizer;
 pub use self::facet_tokenizer::FacetTokenizer;
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::remove_long::RemoveLongFilter;
+pub use self::shingle_filter::ShingleFilter;
 pub use self::simple_tokenizer::SimpleTokenizer;
+pub use self::split_identifier_filter::SplitIdentifierFilter;
 pub use self::stemmer::{Language, Stemmer};
 pub use self::stop_word_filter::StopWordFilter;
 pub use self::tokenized_string::{PreTokenizedStream, PreTokenizedString};
@@ -148,6 +166,7 @@ pub use self::tokenizer::{
     BoxTokenFilter, BoxTokenStream, TextAnalyzer, Token, TokenFilter, TokenStream, Tokenizer,
 };
 pub use self::tokenizer_manager::TokenizerManager;
+pub use self::truncate_long::TruncateLongFilter;
 pub use self::whitespace_tokenizer::WhitespaceTokenizer;
 
 /// Maximum authorized len (in bytes) for a token.
@@ -162,7 +181,11 @@ pub mod tests {
     use super::{
         Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, Token, TokenizerManager,
     };
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, TEXT};
     use crate::tokenizer::TextAnalyzer;
+    use crate::Index;
 
     /// This is a function that can be used in tests and doc tests
     /// to assert a token's correctness.
@@ -202,6 +225,51 @@ pub mod tests {
         assert_token(&tokens[0], 0, "Hello, happy tax payer!", 0, 23);
     }
 
+    #[test]
+    fn test_raw_lowercase_tokenizer() {
+        let tokenizer_manager = TokenizerManager::default();
+        let raw_lowercase_tokenizer = tokenizer_manager.get("raw_lowercase").unwrap();
+        let mut tokens: Vec<Token> = vec![];
+        raw_lowercase_tokenizer
+            .token_stream("Hello, Happy Tax Payer!")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 1);
+        assert_token(&tokens[0], 0, "hello, happy tax payer!", 0, 23);
+    }
+
+    #[test]
+    fn test_raw_lowercase_tokenizer_exact_match() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let keyword_indexing = TextFieldIndexing::default()
+            .set_tokenizer("raw_lowercase")
+            .set_index_option(IndexRecordOption::Basic);
+        let keyword = schema_builder.add_text_field(
+            "keyword",
+            TextOptions::default().set_indexing_options(keyword_indexing),
+        );
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(keyword => "Foo", text => "Foo"))?;
+        index_writer.add_document(doc!(keyword => "Foo Bar", text => "Foo Bar"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        // The case is ignored...
+        assert_eq!(count("keyword:foo")?, 1);
+        assert_eq!(count("keyword:FOO")?, 1);
+        assert_eq!(count(r#"keyword:"foo bar""#)?, 1);
+        assert_eq!(count(r#"keyword:"FOO BAR""#)?, 1);
+        // ... but the value is not split into words, unlike with the default analyzer.
+        assert_eq!(count("keyword:bar")?, 0);
+        assert_eq!(count("text:foo")?, 2);
+        assert_eq!(count("text:bar")?, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_en_tokenizer() {
         let tokenizer_manager = TokenizerManager::default();
@@ -297,4 +365,25 @@ pub mod tests {
         assert_token(&tokens[2], 2, "tax", 13, 16);
         assert_token(&tokens[3], 3, "payer!", 17, 23);
     }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer() {
+        let tokenizer_manager = TokenizerManager::default();
+        let cjk_tokenizer = tokenizer_manager.get("cjk_bigram").unwrap();
+        let mut tokens: Vec<Token> = vec![];
+        {
+            let mut add_token = |token: &Token| {
+                tokens.push(token.clone());
+            };
+            cjk_tokenizer
+                .token_stream("Tantivy全文検索")
+                .process(&mut add_token);
+        }
+
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "tantivy", 0, 7);
+        assert_token(&tokens[1], 1, "全文", 7, 13);
+        assert_token(&tokens[2], 2, "文検", 10, 16);
+        assert_token(&tokens[3], 3, "検索", 13, 19);
+    }
 }
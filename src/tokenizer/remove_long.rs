@@ -19,6 +19,9 @@ use crate::tokenizer::BoxTokenStream;
 ///
 /// It is especially useful when indexing unconstrained content.
 /// e.g. Mail containing base-64 encoded pictures etc.
+///
+/// See [`TruncateLongFilter`](super::TruncateLongFilter) to keep the beginning of
+/// long tokens instead.
 #[derive(Clone)]
 pub struct RemoveLongFilter {
     length_limit: usize,
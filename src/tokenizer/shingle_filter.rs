@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+
+use super::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+/// Combines adjacent tokens into shingles, i.e. word n-grams.
+///
+/// For each token, the filter emits the shingles starting at this token, from the smallest
+/// to the largest size, their words being joined by a separator, a space by default. Their
+/// position is the position of their first word, their `position_length` the number of
+/// positions they span, and their offsets go from the start of their first word to the end of
+/// their last word.
+///
+/// A minimum size of 1 emits the tokens themselves along with the shingles, so that both the
+/// words and the word n-grams can be searched. If the text has fewer tokens than the minimum
+/// size, no token is emitted.
+///
+/// Shingles make it possible to match adjacent words without indexing positions, by
+/// searching for the shingle as a single term.
+///
+/// | Term     | the | the quick | quick | quick fox | fox   |
+/// |----------|-----|-----------|-------|-----------|-------|
+/// | Position | 0   | 0         | 1     | 1         | 2     |
+/// | Offsets  | 0,3 | 0,9       | 4,9   | 4,13      | 10,13 |
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = TextAnalyzer::from(SimpleTokenizer)
+///     .filter(LowerCaser)
+///     .filter(ShingleFilter::new(2, 3).separator("_"));
+/// let mut stream = tokenizer.token_stream("The quick brown fox");
+/// let mut tokens = Vec::new();
+/// while let Some(token) = stream.next() {
+///     tokens.push(token.text.clone());
+/// }
+/// assert_eq!(
+///     tokens,
+///     vec!["the_quick", "the_quick_brown", "quick_brown", "quick_brown_fox", "brown_fox"]
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ShingleFilter {
+    min_shingle_size: usize,
+    max_shingle_size: usize,
+    separator: String,
+}
+
+impl ShingleFilter {
+    /// Creates a filter emitting the shingles of `min_shingle_size` to `max_shingle_size`
+    /// tokens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_shingle_size` is 0 or greater than `max_shingle_size`.
+    pub fn new(min_shingle_size: usize, max_shingle_size: usize) -> ShingleFilter {
+        assert!(
+            min_shingle_size > 0,
+            "min_shingle_size must be greater than 0"
+        );
+        assert!(
+            min_shingle_size <= max_shingle_size,
+            "min_shingle_size must not be greater than max_shingle_size"
+        );
+        ShingleFilter {
+            min_shingle_size,
+            max_shingle_size,
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Sets the separator inserted between the words of a shingle.
+    #[must_use]
+    pub fn separator(mut self, separator: &str) -> ShingleFilter {
+        self.separator = separator.to_string();
+        self
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(ShingleTokenStream {
+            tail: token_stream,
+            filter: self.clone(),
+            window: VecDeque::with_capacity(self.max_shingle_size),
+            tail_exhausted: false,
+            shingle_size: self.min_shingle_size,
+            token: Token::default(),
+        })
+    }
+}
+
+pub struct ShingleTokenStream<'a> {
+    tail: BoxTokenStream<'a>,
+    filter: ShingleFilter,
+    // The tokens of `tail` starting with the first word of the current shingles.
+    window: VecDeque<Token>,
+    tail_exhausted: bool,
+    // Size of the next shingle starting with the first token of the window.
+    shingle_size: usize,
+    token: Token,
+}
+
+impl<'a> ShingleTokenStream<'a> {
+    fn fill_window(&mut self) {
+        while !self.tail_exhausted && self.window.len() < self.filter.max_shingle_size {
+            if self.tail.advance() {
+                self.window.push_back(self.tail.token().clone());
+            } else {
+                self.tail_exhausted = true;
+            }
+        }
+    }
+
+    fn emit_shingle(&mut self, shingle_size: usize) {
+        let first = &self.window[0];
+        let last = &self.window[shingle_size - 1];
+        self.token.text.clear();
+        for (ord, word) in self.window.iter().take(shingle_size).enumerate() {
+            if ord > 0 {
+                self.token.text.push_str(&self.filter.separator);
+            }
+            self.token.text.push_str(&word.text);
+        }
+        self.token.offset_from = first.offset_from;
+        self.token.offset_to = last.offset_to;
+        self.token.position = first.position;
+        self.token.position_length = last.position + last.position_length - first.position;
+    }
+}
+
+impl<'a> TokenStream for ShingleTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        loop {
+            self.fill_window();
+            if self.window.is_empty() {
+                return false;
+            }
+            if self.shingle_size <= self.window.len() {
+                self.emit_shingle(self.shingle_size);
+                self.shingle_size += 1;
+                return true;
+            }
+            // All of the shingles starting with the first token of the window were emitted.
+            self.window.pop_front();
+            self.shingle_size = self.filter.min_shingle_size;
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        LowerCaser, ShingleFilter, SimpleTokenizer, StopWordFilter, TextAnalyzer, Token,
+    };
+    use crate::{Index, Term};
+
+    fn token_stream_helper(filter: ShingleFilter, text: &str) -> Vec<Token> {
+        let mut token_stream = TextAnalyzer::from(SimpleTokenizer)
+            .filter(filter)
+            .token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    fn shingles(filter: ShingleFilter, text: &str) -> Vec<String> {
+        token_stream_helper(filter, text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_shingle_filter_short_sentence() {
+        assert_eq!(
+            shingles(ShingleFilter::new(2, 2), "hello world"),
+            vec!["hello world"]
+        );
+        assert_eq!(
+            shingles(ShingleFilter::new(2, 3), "hello world"),
+            vec!["hello world"]
+        );
+        assert_eq!(
+            shingles(ShingleFilter::new(1, 2), "hello world"),
+            vec!["hello", "hello world", "world"]
+        );
+        // There are fewer words than the minimum shingle size.
+        assert!(shingles(ShingleFilter::new(2, 2), "hello").is_empty());
+        assert!(shingles(ShingleFilter::new(3, 4), "hello world").is_empty());
+        assert!(shingles(ShingleFilter::new(1, 2), "").is_empty());
+        assert_eq!(shingles(ShingleFilter::new(1, 3), "hello"), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_shingle_filter_long_sentence() {
+        let tokens = token_stream_helper(
+            ShingleFilter::new(2, 3).separator("_"),
+            "the quick brown fox jumps over the lazy dog",
+        );
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "the_quick",
+                "the_quick_brown",
+                "quick_brown",
+                "quick_brown_fox",
+                "brown_fox",
+                "brown_fox_jumps",
+                "fox_jumps",
+                "fox_jumps_over",
+                "jumps_over",
+                "jumps_over_the",
+                "over_the",
+                "over_the_lazy",
+                "the_lazy",
+                "the_lazy_dog",
+                "lazy_dog",
+            ]
+        );
+        assert_token(&tokens[0], 0, "the_quick", 0, 9);
+        assert_eq!(tokens[0].position_length, 2);
+        assert_token(&tokens[1], 0, "the_quick_brown", 0, 15);
+        assert_eq!(tokens[1].position_length, 3);
+        assert_token(&tokens[2], 1, "quick_brown", 4, 15);
+        assert_token(&tokens[14], 7, "lazy_dog", 35, 43);
+        assert_eq!(tokens[14].position_length, 2);
+    }
+
+    #[test]
+    fn test_shingle_filter_positions_with_gaps() {
+        let analyzer = TextAnalyzer::from(SimpleTokenizer)
+            .filter(StopWordFilter::remove(vec!["the".to_string()]))
+            .filter(ShingleFilter::new(1, 2));
+        let mut token_stream = analyzer.token_stream("fox over the dog");
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "fox", 0, 3);
+        assert_token(&tokens[1], 0, "fox over", 0, 8);
+        assert_token(&tokens[2], 1, "over", 4, 8);
+        // The shingle spans the position of the removed stop word.
+        assert_token(&tokens[3], 1, "over dog", 4, 16);
+        assert_eq!(tokens[3].position_length, 3);
+        assert_token(&tokens[4], 3, "dog", 13, 16);
+    }
+
+    #[test]
+    fn test_shingle_filter_search_without_positions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("shingles")
+                    .set_index_option(IndexRecordOption::WithFreqs),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "shingles",
+            TextAnalyzer::from(SimpleTokenizer)
+                .filter(LowerCaser)
+                .filter(ShingleFilter::new(1, 2)),
+        );
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "New York City"))?;
+        index_writer.add_document(doc!(text => "a new city in York"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |term: &str| -> crate::Result<usize> {
+            let query = TermQuery::new(
+                Term::from_field_text(text, term),
+                IndexRecordOption::WithFreqs,
+            );
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("york")?, 2);
+        assert_eq!(count("new york")?, 1);
+        assert_eq!(count("new city")?, 1);
+        assert_eq!(count("york city")?, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+use super::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+/// Splits identifiers written in camelCase, PascalCase or snake_case into their words.
+///
+/// A token is split:
+/// - on its non alphanumeric characters, like underscores, which are dropped,
+/// - before an uppercase letter following a lowercase letter: `getUser` gives `get` and `User`,
+/// - before the last uppercase letter of a run of uppercase letters followed by a lowercase letter:
+///   `HTTPServer` gives `HTTP` and `Server`,
+/// - between letters and digits: `utf8Decode` gives `utf`, `8` and `Decode`.
+///
+/// The words of a token get consecutive positions, so that phrase queries like `"user name"`
+/// match `getUserName`. The positions of the following tokens are shifted accordingly.
+/// Tokens without any alphanumeric character, like `__`, are removed.
+///
+/// With [`SplitIdentifierFilter::preserve_original`], a token that is split is also emitted
+/// as is, before its words, at the position of its first word and spanning all of them.
+///
+/// This filter needs to see the case of the tokens, so it must be placed before the
+/// [`LowerCaser`](super::LowerCaser). Also, the tokenizer should not split identifiers on
+/// underscores itself: combine it with the
+/// [`WhitespaceTokenizer`](super::WhitespaceTokenizer) rather than with the
+/// [`SimpleTokenizer`](super::SimpleTokenizer).
+///
+/// | Term     | getUserName | get  | User | Name  |
+/// |----------|-------------|------|------|-------|
+/// | Position | 0           | 0    | 1    | 2     |
+/// | Offsets  | 0,11        | 0,3  | 3,7  | 7,11  |
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let tokenizer = TextAnalyzer::from(WhitespaceTokenizer)
+///     .filter(SplitIdentifierFilter::default())
+///     .filter(LowerCaser);
+/// let mut stream = tokenizer.token_stream("getUserName HTTPServer __init__");
+/// let mut tokens = Vec::new();
+/// while let Some(token) = stream.next() {
+///     tokens.push(token.text.clone());
+/// }
+/// assert_eq!(tokens, vec!["get", "user", "name", "http", "server", "init"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct SplitIdentifierFilter {
+    preserve_original: bool,
+}
+
+impl SplitIdentifierFilter {
+    /// Creates a filter that also emits the tokens that are split as is.
+    pub fn preserve_original() -> SplitIdentifierFilter {
+        SplitIdentifierFilter {
+            preserve_original: true,
+        }
+    }
+}
+
+impl TokenFilter for SplitIdentifierFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(SplitIdentifierTokenStream {
+            tail: token_stream,
+            preserve_original: self.preserve_original,
+            parts: Vec::new(),
+            cursor: 0,
+            base_position: 0,
+            position_shift: 0,
+            token: Token::default(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum CharClass {
+    Upper,
+    // Lowercase letters, as well as the letters without case.
+    Lower,
+    Digit,
+    Delimiter,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_alphabetic() {
+        CharClass::Lower
+    } else if c.is_numeric() {
+        CharClass::Digit
+    } else {
+        CharClass::Delimiter
+    }
+}
+
+/// Pushes the byte ranges of the words of `text` to `parts`.
+fn split_identifier(text: &str, parts: &mut Vec<(usize, usize)>) {
+    parts.clear();
+    let mut word_start: Option<usize> = None;
+    let mut prev_class = CharClass::Delimiter;
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let class = char_class(c);
+        if class == CharClass::Delimiter {
+            if let Some(start) = word_start.take() {
+                parts.push((start, offset));
+            }
+            prev_class = class;
+            continue;
+        }
+        if let Some(start) = word_start {
+            let is_boundary = match (prev_class, class) {
+                (CharClass::Lower, CharClass::Upper) => true,
+                (CharClass::Upper, CharClass::Upper) => {
+                    let next_class = chars.peek().map(|&(_, next_c)| char_class(next_c));
+                    next_class == Some(CharClass::Lower)
+                }
+                (CharClass::Digit, CharClass::Digit) => false,
+                (CharClass::Digit, _) | (_, CharClass::Digit) => true,
+                _ => false,
+            };
+            if is_boundary {
+                parts.push((start, offset));
+                word_start = Some(offset);
+            }
+        } else {
+            word_start = Some(offset);
+        }
+        prev_class = class;
+    }
+    if let Some(start) = word_start {
+        parts.push((start, text.len()));
+    }
+}
+
+pub struct SplitIdentifierTokenStream<'a> {
+    tail: BoxTokenStream<'a>,
+    preserve_original: bool,
+    // Byte ranges of the words of the current token of `tail`.
+    parts: Vec<(usize, usize)>,
+    // Index of the next word to emit.
+    cursor: usize,
+    // Position of the first word of the current token of `tail`.
+    base_position: usize,
+    // Number of positions added by the tokens split so far.
+    position_shift: usize,
+    token: Token,
+}
+
+impl<'a> SplitIdentifierTokenStream<'a> {
+    fn emit_part(&mut self, part_ord: usize) {
+        let (start, end) = self.parts[part_ord];
+        let tail_token = self.tail.token();
+        self.token.text.clear();
+        self.token.text.push_str(&tail_token.text[start..end]);
+        // The offsets of the words can only be computed if the token text was not altered.
+        if tail_token.offset_to - tail_token.offset_from == tail_token.text.len() {
+            self.token.offset_from = tail_token.offset_from + start;
+            self.token.offset_to = tail_token.offset_from + end;
+        } else {
+            self.token.offset_from = tail_token.offset_from;
+            self.token.offset_to = tail_token.offset_to;
+        }
+        self.token.position = self.base_position + part_ord;
+        self.token.position_length = 1;
+    }
+
+    fn emit_original(&mut self, position_length: usize) {
+        self.token.clone_from(self.tail.token());
+        self.token.position = self.base_position;
+        self.token.position_length = position_length;
+    }
+}
+
+impl<'a> TokenStream for SplitIdentifierTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        loop {
+            if self.cursor < self.parts.len() {
+                self.emit_part(self.cursor);
+                self.cursor += 1;
+                return true;
+            }
+            if !self.tail.advance() {
+                return false;
+            }
+            split_identifier(&self.tail.token().text, &mut self.parts);
+            self.cursor = 0;
+            self.base_position = self.tail.token().position + self.position_shift;
+            let num_parts = self.parts.len();
+            if num_parts == 0 {
+                continue;
+            }
+            self.position_shift += num_parts - 1;
+            if self.parts[..] == [(0, self.tail.token().text.len())] {
+                self.parts.clear();
+                self.emit_original(1);
+                return true;
+            }
+            if self.preserve_original {
+                self.emit_original(num_parts);
+                return true;
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        LowerCaser, SplitIdentifierFilter, TextAnalyzer, Token, WhitespaceTokenizer,
+    };
+    use crate::Index;
+
+    fn token_stream_helper(filter: SplitIdentifierFilter, text: &str) -> Vec<Token> {
+        let mut token_stream = TextAnalyzer::from(WhitespaceTokenizer)
+            .filter(filter)
+            .token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        for token in &tokens {
+            assert_eq!(&text[token.offset_from..token.offset_to], token.text);
+        }
+        tokens
+    }
+
+    fn split(text: &str) -> Vec<String> {
+        token_stream_helper(SplitIdentifierFilter::default(), text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_identifier_filter_words() {
+        assert_eq!(split("getUserName"), vec!["get", "User", "Name"]);
+        assert_eq!(split("GetUserName"), vec!["Get", "User", "Name"]);
+        assert_eq!(split("get_user_name"), vec!["get", "user", "name"]);
+        assert_eq!(split("MAX_BUFFER_SIZE"), vec!["MAX", "BUFFER", "SIZE"]);
+        assert_eq!(split("HTTPServer"), vec!["HTTP", "Server"]);
+        assert_eq!(split("XMLHttpRequest"), vec!["XML", "Http", "Request"]);
+        assert_eq!(
+            split("parseHTTP2Response"),
+            vec!["parse", "HTTP", "2", "Response"]
+        );
+        assert_eq!(split("utf8_decode"), vec!["utf", "8", "decode"]);
+        assert_eq!(split("sha256sum"), vec!["sha", "256", "sum"]);
+        assert_eq!(split("__init__"), vec!["init"]);
+        assert_eq!(split("_privateField"), vec!["private", "Field"]);
+        assert_eq!(split("URL"), vec!["URL"]);
+        assert_eq!(split("été_Über"), vec!["été", "Über"]);
+        assert!(split("__ -").is_empty());
+    }
+
+    #[test]
+    fn test_split_identifier_filter_positions_and_offsets() {
+        let tokens =
+            token_stream_helper(SplitIdentifierFilter::default(), "call getUserName _id now");
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "call", 0, 4);
+        assert_token(&tokens[1], 1, "get", 5, 8);
+        assert_token(&tokens[2], 2, "User", 8, 12);
+        assert_token(&tokens[3], 3, "Name", 12, 16);
+        assert_token(&tokens[4], 4, "id", 18, 20);
+        assert_token(&tokens[5], 5, "now", 21, 24);
+    }
+
+    #[test]
+    fn test_split_identifier_filter_preserve_original() {
+        let tokens = token_stream_helper(
+            SplitIdentifierFilter::preserve_original(),
+            "HTTPServer new __init__",
+        );
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "HTTPServer", 0, 10);
+        assert_eq!(tokens[0].position_length, 2);
+        assert_token(&tokens[1], 0, "HTTP", 0, 4);
+        assert_token(&tokens[2], 1, "Server", 4, 10);
+        assert_eq!(tokens[2].position_length, 1);
+        assert_token(&tokens[3], 2, "new", 11, 14);
+        assert_eq!(tokens[3].position_length, 1);
+        assert_token(&tokens[4], 3, "__init__", 15, 23);
+        assert_eq!(tokens[4].position_length, 1);
+        assert_token(&tokens[5], 3, "init", 17, 21);
+    }
+
+    #[test]
+    fn test_split_identifier_filter_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let code = schema_builder.add_text_field(
+            "code",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("code")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "code",
+            TextAnalyzer::from(WhitespaceTokenizer)
+                .filter(SplitIdentifierFilter::preserve_original())
+                .filter(LowerCaser),
+        );
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(code => "let name = getUserName(user_id);"))?;
+        index_writer.add_document(doc!(code => "fn user() -> HTTPServer"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![code]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count("user")?, 2);
+        assert_eq!(count("name")?, 1);
+        assert_eq!(count("server")?, 1);
+        assert_eq!(count("\"user name\"")?, 1);
+        assert_eq!(count("httpserver")?, 1);
+        assert_eq!(count("\"user id\"")?, 1);
+        Ok(())
+    }
+}
@@ -4,7 +4,8 @@ use std::sync::{Arc, RwLock};
 use crate::tokenizer::stemmer::Language;
 use crate::tokenizer::tokenizer::TextAnalyzer;
 use crate::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, WhitespaceTokenizer,
+    CjkBigramTokenizer, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    WhitespaceTokenizer,
 };
 
 /// The tokenizer manager serves as a store for
@@ -59,6 +60,10 @@ impl Default for TokenizerManager {
     fn default() -> TokenizerManager {
         let manager = TokenizerManager::new();
         manager.register("raw", RawTokenizer);
+        manager.register(
+            "raw_lowercase",
+            TextAnalyzer::from(RawTokenizer).filter(LowerCaser),
+        );
         manager.register(
             "default",
             TextAnalyzer::from(SimpleTokenizer)
@@ -73,6 +78,12 @@ impl Default for TokenizerManager {
                 .filter(Stemmer::new(Language::English)),
         );
         manager.register("whitespace", WhitespaceTokenizer);
+        manager.register(
+            "cjk_bigram",
+            TextAnalyzer::from(CjkBigramTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser),
+        );
         manager
     }
 }
@@ -0,0 +1,148 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = TextAnalyzer::from(SimpleTokenizer)
+//!   .filter(TruncateLongFilter::limit(5));
+//!
+//! let mut stream = tokenizer.token_stream("toolong nice");
+//! // because `toolong` is more than 5 characters, it is truncated.
+//! assert_eq!(stream.next().unwrap().text, "toolo");
+//! assert_eq!(stream.next().unwrap().text, "nice");
+//! assert!(stream.next().is_none());
+//! ```
+use super::{Token, TokenFilter, TokenStream};
+use crate::tokenizer::BoxTokenStream;
+
+/// `TruncateLongFilter` truncates the tokens that are longer
+/// than a given number of bytes (in UTF-8 representation).
+///
+/// Contrary to [`RemoveLongFilter`](super::RemoveLongFilter), the beginning of
+/// a long token remains searchable.
+///
+/// Tokens are truncated on a char boundary, so a truncated token may be
+/// slightly shorter than the limit.
+///
+/// If the text of the token is the exact slice of the original text spanned by its
+/// offsets, `offset_to` is moved back so that the offsets span the truncated text.
+/// Otherwise, e.g. if a previous filter changed the length of the token, the offsets are
+/// left untouched.
+#[derive(Clone)]
+pub struct TruncateLongFilter {
+    length_limit: usize,
+}
+
+impl TruncateLongFilter {
+    /// Creates a `TruncateLongFilter` given a limit in bytes of the UTF-8 representation.
+    pub fn limit(length_limit: usize) -> TruncateLongFilter {
+        TruncateLongFilter { length_limit }
+    }
+}
+
+impl TokenFilter for TruncateLongFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(TruncateLongFilterStream {
+            token_length_limit: self.length_limit,
+            tail: token_stream,
+        })
+    }
+}
+
+pub struct TruncateLongFilterStream<'a> {
+    token_length_limit: usize,
+    tail: BoxTokenStream<'a>,
+}
+
+fn truncate_token(token: &mut Token, token_length_limit: usize) {
+    if token.text.len() <= token_length_limit {
+        return;
+    }
+    let mut truncated_len = token_length_limit;
+    while !token.text.is_char_boundary(truncated_len) {
+        truncated_len -= 1;
+    }
+    if token.offset_to - token.offset_from == token.text.len() {
+        token.offset_to = token.offset_from + truncated_len;
+    }
+    token.text.truncate(truncated_len);
+}
+
+impl<'a> TokenStream for TruncateLongFilterStream<'a> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        truncate_token(self.tail.token_mut(), self.token_length_limit);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        LowerCaser, SimpleTokenizer, TextAnalyzer, Token, TruncateLongFilter, MAX_TOKEN_LEN,
+    };
+
+    #[test]
+    fn test_truncate_long() {
+        let tokens = token_stream_helper("hello tantivy, happy searching!");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "hello", 0, 5);
+        assert_token(&tokens[1], 1, "tantiv", 6, 12);
+        assert_token(&tokens[2], 2, "happy", 15, 20);
+        assert_token(&tokens[3], 3, "search", 21, 27);
+    }
+
+    #[test]
+    fn test_truncate_long_on_char_boundary() {
+        // `é` is two bytes long: it cannot be cut in half.
+        let tokens = token_stream_helper("abcdeé");
+        assert_eq!(tokens.len(), 1);
+        assert_token(&tokens[0], 0, "abcde", 0, 5);
+    }
+
+    #[test]
+    fn test_truncate_over_long_token() {
+        let text = format!("small {} word", "a".repeat(10 * MAX_TOKEN_LEN));
+        let analyzer = TextAnalyzer::from(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(TruncateLongFilter::limit(MAX_TOKEN_LEN));
+        let mut tokens: Vec<Token> = vec![];
+        analyzer
+            .token_stream(&text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[1].text.len(), MAX_TOKEN_LEN);
+        assert_token(
+            &tokens[2],
+            2,
+            "word",
+            10 * MAX_TOKEN_LEN + 7,
+            10 * MAX_TOKEN_LEN + 11,
+        );
+        // The offsets span the truncated token in the original text.
+        for token in &tokens {
+            assert_eq!(&text[token.offset_from..token.offset_to], token.text);
+        }
+    }
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let a = TextAnalyzer::from(SimpleTokenizer).filter(TruncateLongFilter::limit(6));
+        let mut token_stream = a.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+}
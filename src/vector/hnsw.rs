@@ -0,0 +1,368 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::io::{self, Read, Write};
+
+use common::BinarySerializable;
+
+use crate::schema::VectorMetric;
+
+/// Vectors of a field, laid out contiguously.
+///
+/// The ordinal of a vector is its rank in the field.
+pub(crate) struct Vectors<'a> {
+    dimension: usize,
+    metric: VectorMetric,
+    values: &'a [f32],
+}
+
+impl<'a> Vectors<'a> {
+    pub fn new(dimension: usize, metric: VectorMetric, values: &'a [f32]) -> Vectors<'a> {
+        assert_eq!(values.len() % dimension, 0);
+        Vectors {
+            dimension,
+            metric,
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len() / self.dimension
+    }
+
+    pub fn get(&self, ord: u32) -> &'a [f32] {
+        let start = ord as usize * self.dimension;
+        &self.values[start..start + self.dimension]
+    }
+
+    pub fn distance_to(&self, query: &[f32], ord: u32) -> f32 {
+        distance(self.metric, query, self.get(ord))
+    }
+}
+
+/// Distance between two vectors, the lower the closer.
+///
+/// For the `Cosine` metric, the vectors are expected to be normalized.
+pub(crate) fn distance(metric: VectorMetric, left: &[f32], right: &[f32]) -> f32 {
+    match metric {
+        VectorMetric::Cosine => {
+            let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+            1.0 - dot
+        }
+        VectorMetric::L2 => left.iter().zip(right).map(|(l, r)| (l - r) * (l - r)).sum(),
+        VectorMetric::Dot => -left.iter().zip(right).map(|(l, r)| l * r).sum::<f32>(),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Neighbor {
+    pub distance: f32,
+    pub ord: u32,
+}
+
+impl PartialEq for Neighbor {
+    fn eq(&self, other: &Neighbor) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Neighbor {}
+
+impl PartialOrd for Neighbor {
+    fn partial_cmp(&self, other: &Neighbor) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbor {
+    fn cmp(&self, other: &Neighbor) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.ord.cmp(&other.ord))
+    }
+}
+
+/// Set of the vectors visited by a search, that can be cleared in constant time.
+struct VisitedSet {
+    marks: Vec<u32>,
+    generation: u32,
+}
+
+impl VisitedSet {
+    fn with_capacity(num_vectors: usize) -> VisitedSet {
+        VisitedSet {
+            marks: vec![0; num_vectors],
+            generation: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        if self.generation == u32::MAX {
+            self.marks.iter_mut().for_each(|mark| *mark = 0);
+            self.generation = 0;
+        }
+        self.generation += 1;
+    }
+
+    fn resize(&mut self, num_vectors: usize) {
+        self.marks.resize(num_vectors, 0);
+    }
+
+    /// Returns true if `ord` was not visited yet.
+    fn insert(&mut self, ord: u32) -> bool {
+        let mark = &mut self.marks[ord as usize];
+        if *mark == self.generation {
+            return false;
+        }
+        *mark = self.generation;
+        true
+    }
+}
+
+/// Hash used to draw the level of the vectors, so that building a graph is deterministic.
+fn splitmix64(val: u64) -> u64 {
+    let mut z = val.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn random_level(ord: u32, level_mult: f64) -> usize {
+    // Uniform in (0, 1].
+    let uniform = ((splitmix64(u64::from(ord)) >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    (-uniform.ln() * level_mult) as usize
+}
+
+/// Selects up to `max_neighbors` neighbors among `candidates`, sorted by increasing distance.
+///
+/// A candidate is preferred if it is closer to the vector than to the neighbors selected so
+/// far, so that the neighbors cover the different directions around the vector.
+fn select_neighbors(vectors: &Vectors, candidates: &[Neighbor], max_neighbors: usize) -> Vec<u32> {
+    let mut selected: Vec<Neighbor> = Vec::with_capacity(max_neighbors);
+    let mut pruned: Vec<Neighbor> = Vec::new();
+    for &candidate in candidates {
+        if selected.len() >= max_neighbors {
+            break;
+        }
+        let candidate_vector = vectors.get(candidate.ord);
+        if selected.iter().all(|neighbor| {
+            vectors.distance_to(candidate_vector, neighbor.ord) > candidate.distance
+        }) {
+            selected.push(candidate);
+        } else {
+            pruned.push(candidate);
+        }
+    }
+    for candidate in pruned {
+        if selected.len() >= max_neighbors {
+            break;
+        }
+        selected.push(candidate);
+    }
+    selected.into_iter().map(|neighbor| neighbor.ord).collect()
+}
+
+/// Hierarchical navigable small world graph over the vectors of a field.
+///
+/// Each vector is a node of the layers `0..=level`, where its level is drawn with an
+/// exponentially decreasing probability. The searches start from the single node of the top
+/// layer, and go down layer by layer, from the closest node found in the layer above.
+#[derive(Debug, Default)]
+pub(crate) struct Hnsw {
+    // `neighbors[ord][layer]` are the neighbors of the vector `ord` in `layer`.
+    neighbors: Vec<Vec<Vec<u32>>>,
+    entry_point: u32,
+}
+
+impl Hnsw {
+    /// Builds the graph over `vectors`.
+    ///
+    /// Each vector has up to `m` neighbors, and `2 * m` in the bottom layer.
+    pub fn build(vectors: &Vectors, m: usize, ef_construction: usize) -> Hnsw {
+        let level_mult = 1.0 / (m as f64).ln();
+        let mut hnsw = Hnsw::default();
+        let mut visited = VisitedSet::with_capacity(0);
+        for ord in 0..vectors.len() as u32 {
+            visited.resize(ord as usize + 1);
+            hnsw.insert(
+                vectors,
+                ord,
+                random_level(ord, level_mult),
+                m,
+                ef_construction,
+                &mut visited,
+            );
+        }
+        hnsw
+    }
+
+    fn max_level(&self) -> usize {
+        self.neighbors[self.entry_point as usize].len() - 1
+    }
+
+    fn insert(
+        &mut self,
+        vectors: &Vectors,
+        ord: u32,
+        level: usize,
+        m: usize,
+        ef_construction: usize,
+        visited: &mut VisitedSet,
+    ) {
+        self.neighbors.push(vec![Vec::new(); level + 1]);
+        if ord == 0 {
+            self.entry_point = ord;
+            return;
+        }
+        let query = vectors.get(ord);
+        let max_level = self.max_level();
+        let mut entry_points = vec![Neighbor {
+            distance: vectors.distance_to(query, self.entry_point),
+            ord: self.entry_point,
+        }];
+        for layer in (level + 1..=max_level).rev() {
+            entry_points = self.search_layer(vectors, query, &entry_points, 1, layer, visited);
+        }
+        for layer in (0..=level.min(max_level)).rev() {
+            let candidates = self.search_layer(
+                vectors,
+                query,
+                &entry_points,
+                ef_construction,
+                layer,
+                visited,
+            );
+            let max_neighbors = if layer == 0 { 2 * m } else { m };
+            let selected = select_neighbors(vectors, &candidates, m);
+            for &neighbor_ord in &selected {
+                let neighbor_vector = vectors.get(neighbor_ord);
+                let neighbor_links = &mut self.neighbors[neighbor_ord as usize][layer];
+                neighbor_links.push(ord);
+                if neighbor_links.len() > max_neighbors {
+                    let mut links: Vec<Neighbor> = neighbor_links
+                        .iter()
+                        .map(|&link_ord| Neighbor {
+                            distance: vectors.distance_to(neighbor_vector, link_ord),
+                            ord: link_ord,
+                        })
+                        .collect();
+                    links.sort();
+                    *neighbor_links = select_neighbors(vectors, &links, max_neighbors);
+                }
+            }
+            self.neighbors[ord as usize][layer] = selected;
+            entry_points = candidates;
+        }
+        if level > max_level {
+            self.entry_point = ord;
+        }
+    }
+
+    /// Returns the `ef` closest vectors to `query` found in `layer`, by increasing distance.
+    fn search_layer(
+        &self,
+        vectors: &Vectors,
+        query: &[f32],
+        entry_points: &[Neighbor],
+        ef: usize,
+        layer: usize,
+        visited: &mut VisitedSet,
+    ) -> Vec<Neighbor> {
+        visited.clear();
+        let mut candidates: BinaryHeap<Reverse<Neighbor>> = BinaryHeap::new();
+        let mut results: BinaryHeap<Neighbor> = BinaryHeap::new();
+        for &entry_point in entry_points {
+            if visited.insert(entry_point.ord) {
+                candidates.push(Reverse(entry_point));
+                results.push(entry_point);
+            }
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+        while let Some(Reverse(candidate)) = candidates.pop() {
+            let furthest = results.peek().copied();
+            if results.len() >= ef && furthest.map(|furthest| candidate > furthest) == Some(true) {
+                break;
+            }
+            for &neighbor_ord in &self.neighbors[candidate.ord as usize][layer] {
+                if !visited.insert(neighbor_ord) {
+                    continue;
+                }
+                let neighbor = Neighbor {
+                    distance: vectors.distance_to(query, neighbor_ord),
+                    ord: neighbor_ord,
+                };
+                let is_closer = results
+                    .peek()
+                    .map(|furthest| neighbor < *furthest)
+                    .unwrap_or(true);
+                if results.len() < ef || is_closer {
+                    candidates.push(Reverse(neighbor));
+                    results.push(neighbor);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    /// Returns the `ef` closest vectors to `query` found in the graph, by increasing distance.
+    pub fn search(&self, vectors: &Vectors, query: &[f32], ef: usize) -> Vec<Neighbor> {
+        if self.neighbors.is_empty() {
+            return Vec::new();
+        }
+        let mut visited = VisitedSet::with_capacity(self.neighbors.len());
+        let mut entry_points = vec![Neighbor {
+            distance: vectors.distance_to(query, self.entry_point),
+            ord: self.entry_point,
+        }];
+        for layer in (1..=self.max_level()).rev() {
+            entry_points = self.search_layer(vectors, query, &entry_points, 1, layer, &mut visited);
+        }
+        self.search_layer(vectors, query, &entry_points, ef, 0, &mut visited)
+    }
+}
+
+impl BinarySerializable for Hnsw {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.neighbors.serialize(writer)?;
+        self.entry_point.serialize(writer)
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Hnsw> {
+        let neighbors = Vec::<Vec<Vec<u32>>>::deserialize(reader)?;
+        let entry_point = u32::deserialize(reader)?;
+        Ok(Hnsw {
+            neighbors,
+            entry_point,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::BinarySerializable;
+
+    use super::{Hnsw, Vectors};
+    use crate::schema::VectorMetric;
+
+    #[test]
+    fn test_hnsw_finds_exact_match() {
+        let values: Vec<f32> = (0..200)
+            .flat_map(|i| [(i % 20) as f32, (i / 20) as f32])
+            .collect();
+        let vectors = Vectors::new(2, VectorMetric::L2, &values);
+        let hnsw = Hnsw::build(&vectors, 4, 20);
+        let mut buffer = Vec::new();
+        hnsw.serialize(&mut buffer).unwrap();
+        let hnsw = Hnsw::deserialize(&mut &buffer[..]).unwrap();
+        for ord in 0..200u32 {
+            let neighbors = hnsw.search(&vectors, vectors.get(ord), 10);
+            assert_eq!(neighbors[0].ord, ord);
+            assert_eq!(neighbors[0].distance, 0.0);
+        }
+        assert!(Hnsw::default().search(&vectors, &[0.0, 0.0], 10).is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+//! Dense vectors associated to the documents, and their approximate nearest neighbor search.
+//!
+//! The vectors of each dense vector field of a segment are stored in the `.vec` file of the
+//! segment, along with a HNSW (hierarchical navigable small world) graph. The graph is built
+//! when the segment is serialized, and rebuilt when segments are merged.
+//!
+//! The graph is searched by the [`NearestNeighborQuery`](crate::query::NearestNeighborQuery).
+//! The [`ExactNearestNeighborQuery`](crate::query::ExactNearestNeighborQuery) scans all of the
+//! vectors instead.
+mod hnsw;
+mod reader;
+mod serializer;
+mod writer;
+
+pub use self::reader::{DenseVectorReader, DenseVectorReaders};
+pub use self::serializer::DenseVectorsSerializer;
+pub(crate) use self::writer::validate_dense_vectors;
+pub use self::writer::DenseVectorsWriter;
+use crate::schema::VectorMetric;
+use crate::Score;
+
+/// Scales `vector` to a unit norm. Null vectors are left untouched.
+pub(crate) fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|val| val * val).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for val in vector.iter_mut() {
+            *val /= norm;
+        }
+    }
+}
+
+/// Converts the distance between two vectors to a score, the higher the closer.
+///
+/// With the `Cosine` metric, the score is `(1 + cosine) / 2`. With the `L2` metric, it is
+/// `1 / (1 + distance²)`. With the `Dot` metric, it is the dot product itself, and is
+/// therefore not bounded.
+pub(crate) fn distance_to_score(metric: VectorMetric, distance: f32) -> Score {
+    match metric {
+        VectorMetric::Cosine => 1.0 - distance / 2.0,
+        VectorMetric::L2 => 1.0 / (1.0 + distance),
+        VectorMetric::Dot => -distance,
+    }
+}
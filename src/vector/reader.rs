@@ -0,0 +1,248 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use common::BinarySerializable;
+
+use super::hnsw::{Hnsw, Neighbor, Vectors};
+use super::{distance_to_score, normalize};
+use crate::directory::{CompositeFile, FileSlice};
+use crate::error::DataCorruption;
+use crate::schema::{DenseVectorOptions, Field, FieldType, Schema, VectorMetric};
+use crate::space_usage::PerFieldSpaceUsage;
+use crate::{DocId, Score, TantivyError};
+
+/// Provides access to the vectors of all of the dense vector fields of a segment.
+///
+/// The vectors of a field, and their graph, are loaded in memory the first time the field
+/// is accessed, and are then shared by all of the subsequent accesses.
+#[derive(Clone)]
+pub struct DenseVectorReaders {
+    schema: Schema,
+    data: Arc<CompositeFile>,
+    readers: Arc<RwLock<HashMap<Field, Arc<DenseVectorReader>>>>,
+}
+
+impl DenseVectorReaders {
+    pub(crate) fn open(schema: Schema, file: &FileSlice) -> crate::Result<DenseVectorReaders> {
+        let data = CompositeFile::open(file)?;
+        Ok(DenseVectorReaders::new(schema, data))
+    }
+
+    /// Readers for a segment written before dense vector fields were supported.
+    pub(crate) fn empty(schema: Schema) -> DenseVectorReaders {
+        DenseVectorReaders::new(schema, CompositeFile::empty())
+    }
+
+    fn new(schema: Schema, data: CompositeFile) -> DenseVectorReaders {
+        DenseVectorReaders {
+            schema,
+            data: Arc::new(data),
+            readers: Default::default(),
+        }
+    }
+
+    /// Returns the reader of the vectors of `field`.
+    ///
+    /// Returns an error if `field` is not a dense vector field.
+    pub fn get_field(&self, field: Field) -> crate::Result<Arc<DenseVectorReader>> {
+        if let Some(reader) = self
+            .readers
+            .read()
+            .expect("Lock poisoned. This should never happen")
+            .get(&field)
+        {
+            return Ok(Arc::clone(reader));
+        }
+        let field_entry = self.schema.get_field_entry(field);
+        let options = if let FieldType::DenseVector(options) = field_entry.field_type() {
+            options
+        } else {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a dense vector field.",
+                field_entry.name()
+            )));
+        };
+        let reader = if let Some(file) = self.data.open_read(field) {
+            DenseVectorReader::open(options, file)?
+        } else {
+            DenseVectorReader::empty(options)
+        };
+        let reader = Arc::new(reader);
+        self.readers
+            .write()
+            .expect("Lock poisoned. This should never happen")
+            .insert(field, Arc::clone(&reader));
+        Ok(reader)
+    }
+
+    /// Return a break down of the space usage per field.
+    pub fn space_usage(&self) -> PerFieldSpaceUsage {
+        self.data.space_usage()
+    }
+}
+
+/// Reader of the vectors of a dense vector field of a segment.
+pub struct DenseVectorReader {
+    dimension: usize,
+    metric: VectorMetric,
+    doc_ids: Vec<DocId>,
+    values: Vec<f32>,
+    hnsw: Hnsw,
+}
+
+impl DenseVectorReader {
+    fn open(options: &DenseVectorOptions, file: FileSlice) -> crate::Result<DenseVectorReader> {
+        let bytes = file.read_bytes()?;
+        let mut data = bytes.as_slice();
+        let doc_ids = Vec::<DocId>::deserialize(&mut data)?;
+        let values = Vec::<f32>::deserialize(&mut data)?;
+        let hnsw = Hnsw::deserialize(&mut data)?;
+        if values.len() != doc_ids.len() * options.dimension() {
+            return Err(DataCorruption::comment_only(format!(
+                "Expected {} vectors of dimension {}, got {} values",
+                doc_ids.len(),
+                options.dimension(),
+                values.len()
+            ))
+            .into());
+        }
+        Ok(DenseVectorReader {
+            dimension: options.dimension(),
+            metric: options.metric(),
+            doc_ids,
+            values,
+            hnsw,
+        })
+    }
+
+    fn empty(options: &DenseVectorOptions) -> DenseVectorReader {
+        DenseVectorReader {
+            dimension: options.dimension(),
+            metric: options.metric(),
+            doc_ids: Vec::new(),
+            values: Vec::new(),
+            hnsw: Hnsw::default(),
+        }
+    }
+
+    /// Returns the dimension of the vectors.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the metric used to compare the vectors.
+    pub fn metric(&self) -> VectorMetric {
+        self.metric
+    }
+
+    /// Returns the number of documents having a vector.
+    pub fn num_vectors(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    /// Returns the vector of `doc`, if it has one.
+    ///
+    /// With the `Cosine` metric, the vector is normalized.
+    pub fn vector(&self, doc: DocId) -> Option<&[f32]> {
+        let ord = self.doc_ids.binary_search(&doc).ok()?;
+        Some(&self.values[ord * self.dimension..(ord + 1) * self.dimension])
+    }
+
+    fn vectors(&self) -> Vectors<'_> {
+        Vectors::new(self.dimension, self.metric, &self.values)
+    }
+
+    fn prepare_query(&self, query: &[f32]) -> Vec<f32> {
+        assert_eq!(
+            query.len(),
+            self.dimension,
+            "The query vector does not have the dimension of the field."
+        );
+        let mut query = query.to_vec();
+        if self.metric == VectorMetric::Cosine {
+            normalize(&mut query);
+        }
+        query
+    }
+
+    /// Returns the approximate `k` nearest documents to `query` accepted by `filter`, by
+    /// decreasing score.
+    ///
+    /// `ef_search` is the number of candidates considered by the search: the higher, the
+    /// better the recall. If the search finds less than `k` documents accepted by `filter`,
+    /// it is retried with more candidates.
+    ///
+    /// # Panics
+    /// The method panics if the dimension of `query` is not the dimension of the field.
+    pub fn search<F: Fn(DocId) -> bool>(
+        &self,
+        query: &[f32],
+        k: usize,
+        ef_search: usize,
+        filter: F,
+    ) -> Vec<(DocId, Score)> {
+        let query = self.prepare_query(query);
+        let vectors = self.vectors();
+        let mut ef = ef_search.max(k);
+        loop {
+            let results: Vec<(DocId, Score)> = self
+                .hnsw
+                .search(&vectors, &query, ef)
+                .into_iter()
+                .map(|neighbor| (self.doc_ids[neighbor.ord as usize], neighbor.distance))
+                .filter(|&(doc, _)| filter(doc))
+                .take(k)
+                .map(|(doc, distance)| (doc, distance_to_score(self.metric, distance)))
+                .collect();
+            if results.len() >= k || ef >= self.num_vectors() {
+                return results;
+            }
+            ef = (ef * 2).min(self.num_vectors());
+        }
+    }
+
+    /// Returns the exact `k` nearest documents to `query` accepted by `filter`, by decreasing
+    /// score.
+    ///
+    /// All of the vectors accepted by `filter` are compared to `query`.
+    ///
+    /// # Panics
+    /// The method panics if the dimension of `query` is not the dimension of the field.
+    pub fn exact_search<F: Fn(DocId) -> bool>(
+        &self,
+        query: &[f32],
+        k: usize,
+        filter: F,
+    ) -> Vec<(DocId, Score)> {
+        let query = self.prepare_query(query);
+        let vectors = self.vectors();
+        // Max-heap of the `k` nearest vectors found so far.
+        let mut nearest: BinaryHeap<Neighbor> = BinaryHeap::with_capacity(k + 1);
+        for (ord, &doc) in self.doc_ids.iter().enumerate() {
+            if !filter(doc) {
+                continue;
+            }
+            let neighbor = Neighbor {
+                distance: vectors.distance_to(&query, ord as u32),
+                ord: ord as u32,
+            };
+            if nearest.len() < k {
+                nearest.push(neighbor);
+            } else if let Some(mut furthest) = nearest.peek_mut() {
+                if neighbor < *furthest {
+                    *furthest = neighbor;
+                }
+            }
+        }
+        nearest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|neighbor| {
+                (
+                    self.doc_ids[neighbor.ord as usize],
+                    distance_to_score(self.metric, neighbor.distance),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+
+use common::{BinarySerializable, VInt};
+
+use super::hnsw::{Hnsw, Vectors};
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::{DenseVectorOptions, Field};
+use crate::DocId;
+
+// Same format as the serialization of a `Vec<T>`.
+fn serialize_slice<T: BinarySerializable, W: Write>(items: &[T], write: &mut W) -> io::Result<()> {
+    VInt(items.len() as u64).serialize(write)?;
+    for item in items {
+        item.serialize(write)?;
+    }
+    Ok(())
+}
+
+/// The dense vectors serializer is in charge of the serialization of the vectors of all
+/// dense vector fields, along with their HNSW graph.
+pub struct DenseVectorsSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl DenseVectorsSerializer {
+    /// Constructor
+    pub fn from_write(write: WritePtr) -> io::Result<DenseVectorsSerializer> {
+        let composite_write = CompositeWrite::wrap(write);
+        Ok(DenseVectorsSerializer { composite_write })
+    }
+
+    /// Serializes the vectors of the given field, and builds their graph.
+    ///
+    /// `doc_ids` are the sorted ids of the documents having a vector, and `values` their
+    /// vectors, laid out contiguously.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        options: &DenseVectorOptions,
+        doc_ids: &[DocId],
+        values: &[f32],
+    ) -> io::Result<()> {
+        let vectors = Vectors::new(options.dimension(), options.metric(), values);
+        assert_eq!(vectors.len(), doc_ids.len());
+        let hnsw = Hnsw::build(&vectors, options.m(), options.ef_construction());
+        let write = self.composite_write.for_field(field);
+        serialize_slice(doc_ids, write)?;
+        serialize_slice(values, write)?;
+        hnsw.serialize(write)?;
+        write.flush()?;
+        Ok(())
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::io;
+
+use super::{normalize, DenseVectorsSerializer};
+use crate::indexer::doc_id_mapping::DocIdMapping;
+use crate::schema::{DenseVectorOptions, Document, Field, FieldType, Schema, VectorMetric};
+use crate::{DocId, TantivyError};
+
+/// Checks that the values of the dense vector fields of `document` are valid vectors.
+///
+/// A document can have at most one vector per field, and the vectors must have the
+/// dimension of their field, and contain only finite values.
+pub(crate) fn validate_dense_vectors(schema: &Schema, document: &Document) -> crate::Result<()> {
+    let mut vector_fields: Vec<Field> = Vec::new();
+    for field_value in document.field_values() {
+        let field = field_value.field();
+        let field_entry = schema.get_field_entry(field);
+        let options = if let FieldType::DenseVector(options) = field_entry.field_type() {
+            options
+        } else {
+            continue;
+        };
+        let vector = field_value.value().as_dense_vector().ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Expected a dense vector for field {:?}",
+                field_entry.name()
+            ))
+        })?;
+        if vector.len() != options.dimension() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Expected a vector of dimension {} for field {:?}, got {}",
+                options.dimension(),
+                field_entry.name(),
+                vector.len()
+            )));
+        }
+        if !vector.iter().all(|val| val.is_finite()) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The vector of field {:?} contains a non finite value",
+                field_entry.name()
+            )));
+        }
+        if vector_fields.contains(&field) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "A document can only have one vector for field {:?}",
+                field_entry.name()
+            )));
+        }
+        vector_fields.push(field);
+    }
+    Ok(())
+}
+
+struct FieldVectors {
+    options: DenseVectorOptions,
+    doc_ids: Vec<DocId>,
+    values: Vec<f32>,
+}
+
+/// The `DenseVectorsWriter` is in charge of accumulating the vectors of the dense vector
+/// fields of a segment, until the segment is serialized.
+pub struct DenseVectorsWriter {
+    schema: Schema,
+    field_vectors: Vec<Option<FieldVectors>>,
+}
+
+impl DenseVectorsWriter {
+    /// Create all `DenseVectorsWriter` required by the schema.
+    pub fn from_schema(schema: &Schema) -> DenseVectorsWriter {
+        let field_vectors = schema
+            .fields()
+            .map(|(_, field_entry)| match field_entry.field_type() {
+                FieldType::DenseVector(options) => Some(FieldVectors {
+                    options: options.clone(),
+                    doc_ids: Vec::new(),
+                    values: Vec::new(),
+                }),
+                _ => None,
+            })
+            .collect();
+        DenseVectorsWriter {
+            schema: schema.clone(),
+            field_vectors,
+        }
+    }
+
+    /// The memory used inclusive childs
+    pub fn mem_usage(&self) -> usize {
+        self.field_vectors
+            .iter()
+            .flatten()
+            .map(|field_vectors| {
+                field_vectors.doc_ids.capacity() * std::mem::size_of::<DocId>()
+                    + field_vectors.values.capacity() * std::mem::size_of::<f32>()
+            })
+            .sum()
+    }
+
+    /// Indexes the vectors of the dense vector fields of a document.
+    ///
+    /// The document is validated with [`validate_dense_vectors`] before any vector is
+    /// recorded.
+    pub fn add_document(&mut self, doc: DocId, document: &Document) -> crate::Result<()> {
+        validate_dense_vectors(&self.schema, document)?;
+        for field_value in document.field_values() {
+            let field_id = field_value.field().field_id() as usize;
+            if let Some(Some(field_vectors)) = self.field_vectors.get_mut(field_id) {
+                // The values were validated above.
+                let vector = field_value.value().as_dense_vector().unwrap_or_default();
+                let start = field_vectors.values.len();
+                field_vectors.doc_ids.push(doc);
+                field_vectors.values.extend_from_slice(vector);
+                if field_vectors.options.metric() == VectorMetric::Cosine {
+                    normalize(&mut field_vectors.values[start..]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes all of the vectors, in the order of `doc_id_map` if the segment is sorted.
+    pub fn serialize(
+        &self,
+        mut serializer: DenseVectorsSerializer,
+        doc_id_map: Option<&DocIdMapping>,
+    ) -> io::Result<()> {
+        for (field_id, field_vectors) in self.field_vectors.iter().enumerate() {
+            let field_vectors = if let Some(field_vectors) = field_vectors {
+                field_vectors
+            } else {
+                continue;
+            };
+            let field = Field::from_field_id(field_id as u32);
+            let options = &field_vectors.options;
+            if let Some(doc_id_map) = doc_id_map {
+                let dimension = options.dimension();
+                let mut ords: Vec<usize> = (0..field_vectors.doc_ids.len()).collect();
+                ords.sort_by_key(|&ord| doc_id_map.get_new_doc_id(field_vectors.doc_ids[ord]));
+                let doc_ids: Vec<DocId> = ords
+                    .iter()
+                    .map(|&ord| doc_id_map.get_new_doc_id(field_vectors.doc_ids[ord]))
+                    .collect();
+                let values: Vec<f32> = ords
+                    .iter()
+                    .flat_map(|&ord| &field_vectors.values[ord * dimension..(ord + 1) * dimension])
+                    .copied()
+                    .collect();
+                serializer.serialize_field(field, options, &doc_ids, &values)?;
+            } else {
+                serializer.serialize_field(
+                    field,
+                    options,
+                    &field_vectors.doc_ids,
+                    &field_vectors.values,
+                )?;
+            }
+        }
+        serializer.close()
+    }
+}